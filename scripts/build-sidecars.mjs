@@ -0,0 +1,39 @@
+// Build the helper binaries shipped next to the app (`bundle.externalBin` in
+// src-tauri/tauri.conf.json) and copy them to src-tauri/binaries/ under the
+// `<name>-<target triple>` names Tauri bundles them from.
+
+import { execFileSync } from "node:child_process";
+import { copyFileSync, mkdirSync } from "node:fs";
+import { dirname, join } from "node:path";
+import { fileURLToPath } from "node:url";
+
+const SIDECARS = ["synaptic-shim", "synaptic-gateway", "synaptic-mock-server"];
+
+const root = join(dirname(fileURLToPath(import.meta.url)), "..");
+const srcTauri = join(root, "src-tauri");
+const triple =
+    process.env.TAURI_ENV_TARGET_TRIPLE ??
+    /host: (\S+)/.exec(execFileSync("rustc", ["-vV"]).toString())[1];
+const release = process.env.TAURI_ENV_DEBUG !== "true";
+const exe = triple.includes("windows") ? ".exe" : "";
+
+execFileSync(
+    "cargo",
+    [
+        "build",
+        "--package",
+        "synaptic-core",
+        "--target",
+        triple,
+        ...(release ? ["--release"] : []),
+        ...SIDECARS.flatMap((name) => ["--bin", name]),
+    ],
+    { cwd: srcTauri, stdio: "inherit" }
+);
+
+const built = join(srcTauri, "target", triple, release ? "release" : "debug");
+const binaries = join(srcTauri, "binaries");
+mkdirSync(binaries, { recursive: true });
+for (const name of SIDECARS) {
+    copyFileSync(join(built, name + exe), join(binaries, `${name}-${triple}${exe}`));
+}
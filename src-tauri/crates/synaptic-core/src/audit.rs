@@ -0,0 +1,353 @@
+//! Config change audit log
+//!
+//! Every mutation of the MCP configuration is recorded in the `config_audit`
+//! table with who made it, when, and a field-level diff of what changed.
+//! Decisions of tool policies (see `policy`) are recorded there as well.
+
+use crate::config::McpConfig;
+use crate::database::Database;
+use crate::error::SynapticResult;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// ============================================
+// AUDIT DATA MODELS
+// ============================================
+
+/// Kind of configuration change
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Add,
+    Remove,
+    Update,
+    Toggle,
+    Restore,
+    /// Whole-config write from the raw editor
+    Write,
+    /// Server started by its schedule
+    ScheduledStart,
+    /// Server stopped by its schedule
+    ScheduledStop,
+    /// Tool call let through by a tool policy
+    ToolAllowed,
+    /// Tool call refused by a tool policy
+    ToolDenied,
+    /// Server removed along with what it installed
+    Uninstall,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Remove => "remove",
+            Self::Update => "update",
+            Self::Toggle => "toggle",
+            Self::Restore => "restore",
+            Self::Write => "write",
+            Self::ScheduledStart => "scheduled_start",
+            Self::ScheduledStop => "scheduled_stop",
+            Self::ToolAllowed => "tool_allowed",
+            Self::ToolDenied => "tool_denied",
+            Self::Uninstall => "uninstall",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "add" => Self::Add,
+            "remove" => Self::Remove,
+            "update" => Self::Update,
+            "toggle" => Self::Toggle,
+            "restore" => Self::Restore,
+            "scheduled_start" => Self::ScheduledStart,
+            "scheduled_stop" => Self::ScheduledStop,
+            "tool_allowed" => Self::ToolAllowed,
+            "tool_denied" => Self::ToolDenied,
+            "uninstall" => Self::Uninstall,
+            _ => Self::Write,
+        }
+    }
+}
+
+/// A single changed field, addressed by a JSON path like `filesystem.args[2]`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldChange {
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// One row of the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub actor: String,
+    pub action: AuditAction,
+    pub server_name: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub diff: Vec<FieldChange>,
+}
+
+// ============================================
+// DIFFING
+// ============================================
+
+/// Diff two configurations' server maps, scoped to one server if given
+pub fn diff_configs(
+    before: &McpConfig,
+    after: &McpConfig,
+    server_name: Option<&str>,
+) -> (Option<Value>, Option<Value>, Vec<FieldChange>) {
+    let snapshot = |config: &McpConfig| -> Option<Value> {
+        match server_name {
+            Some(name) => config
+                .mcp_servers
+                .get(name)
+                .and_then(|s| serde_json::to_value(s).ok()),
+            None => serde_json::to_value(&config.mcp_servers).ok(),
+        }
+    };
+
+    let before_value = snapshot(before);
+    let after_value = snapshot(after);
+
+    let mut changes = Vec::new();
+    diff_values(
+        server_name.unwrap_or(""),
+        before_value.as_ref(),
+        after_value.as_ref(),
+        &mut changes,
+    );
+
+    (before_value, after_value, changes)
+}
+
+/// Recursively collect leaf-level differences between two JSON values
+pub fn diff_values(
+    path: &str,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    out: &mut Vec<FieldChange>,
+) {
+    match (before, after) {
+        (Some(Value::Object(a)), Some(Value::Object(b))) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_values(&child, a.get(key), b.get(key), out);
+            }
+        }
+        (Some(Value::Array(a)), Some(Value::Array(b))) => {
+            for i in 0..a.len().max(b.len()) {
+                diff_values(&format!("{}[{}]", path, i), a.get(i), b.get(i), out);
+            }
+        }
+        (a, b) if a != b => out.push(FieldChange {
+            path: path.to_string(),
+            before: a.cloned(),
+            after: b.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+// ============================================
+// PERSISTENCE
+// ============================================
+
+/// Name of the local user making the change
+pub fn current_actor() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Record a configuration change
+pub fn record(
+    db: &Database,
+    action: AuditAction,
+    server_name: Option<&str>,
+    before: &McpConfig,
+    after: &McpConfig,
+) -> SynapticResult<()> {
+    let (before_value, after_value, diff) = diff_configs(before, after, server_name);
+    insert(db, action, server_name, before_value, after_value, diff)
+}
+
+/// Record a change to data outside the config file (e.g. Synaptic metadata)
+pub fn record_change(
+    db: &Database,
+    action: AuditAction,
+    server_name: Option<&str>,
+    before: Option<Value>,
+    after: Option<Value>,
+) -> SynapticResult<()> {
+    let mut diff = Vec::new();
+    diff_values(
+        server_name.unwrap_or(""),
+        before.as_ref(),
+        after.as_ref(),
+        &mut diff,
+    );
+    insert(db, action, server_name, before, after, diff)
+}
+
+fn insert(
+    db: &Database,
+    action: AuditAction,
+    server_name: Option<&str>,
+    before_value: Option<Value>,
+    after_value: Option<Value>,
+    diff: Vec<FieldChange>,
+) -> SynapticResult<()> {
+    db.conn().execute(
+        "INSERT INTO config_audit (timestamp, actor, action, server_name, before, after, diff)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            Utc::now().timestamp_millis(),
+            current_actor(),
+            action.as_str(),
+            server_name,
+            before_value,
+            after_value,
+            serde_json::to_value(&diff)?,
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Query the audit log, newest first, optionally for a single server
+pub fn query(
+    db: &Database,
+    server_name: Option<&str>,
+    limit: usize,
+    offset: usize,
+) -> SynapticResult<Vec<AuditEntry>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT id, timestamp, actor, action, server_name, before, after, diff
+         FROM config_audit
+         WHERE (?1 IS NULL OR server_name = ?1)
+         ORDER BY timestamp DESC, id DESC
+         LIMIT ?2 OFFSET ?3",
+    )?;
+
+    let rows = stmt.query_map(params![server_name, limit as i64, offset as i64], |row| {
+        let millis: i64 = row.get(1)?;
+        let action: String = row.get(3)?;
+        let diff: Value = row.get(7)?;
+
+        Ok(AuditEntry {
+            id: row.get(0)?,
+            timestamp: Utc
+                .timestamp_millis_opt(millis)
+                .single()
+                .unwrap_or_default(),
+            actor: row.get(2)?,
+            action: AuditAction::parse(&action),
+            server_name: row.get(4)?,
+            before: row.get(5)?,
+            after: row.get(6)?,
+            diff: serde_json::from_value(diff).unwrap_or_default(),
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::McpServer;
+    use std::collections::HashMap;
+
+    fn config_with(path: &str) -> McpConfig {
+        let mut config = McpConfig::default();
+        config.mcp_servers.insert(
+            "filesystem".to_string(),
+            McpServer {
+                command: "npx".to_string(),
+                args: vec![
+                    "-y".to_string(),
+                    "@modelcontextprotocol/server-filesystem".to_string(),
+                    path.to_string(),
+                ],
+                env: HashMap::new(),
+                cwd: None,
+                enabled: true,
+                ..Default::default()
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn test_diff_reports_changed_arg() {
+        let (_, _, diff) = diff_configs(
+            &config_with("/old"),
+            &config_with("/new"),
+            Some("filesystem"),
+        );
+
+        assert_eq!(
+            diff,
+            vec![FieldChange {
+                path: "filesystem.args[2]".to_string(),
+                before: Some(Value::from("/old")),
+                after: Some(Value::from("/new")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_add_and_remove() {
+        let empty = McpConfig::default();
+        let (before, after, diff) = diff_configs(&empty, &config_with("/a"), Some("filesystem"));
+        assert!(before.is_none());
+        assert!(after.is_some());
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].path, "filesystem");
+    }
+
+    #[test]
+    fn test_record_and_query() {
+        let db = Database::open_in_memory().unwrap();
+        record(
+            &db,
+            AuditAction::Update,
+            Some("filesystem"),
+            &config_with("/old"),
+            &config_with("/new"),
+        )
+        .unwrap();
+        record(
+            &db,
+            AuditAction::Add,
+            Some("other"),
+            &McpConfig::default(),
+            &McpConfig::default(),
+        )
+        .unwrap();
+
+        let entries = query(&db, Some("filesystem"), 10, 0).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, AuditAction::Update);
+        assert_eq!(entries[0].diff[0].before, Some(Value::from("/old")));
+
+        assert_eq!(query(&db, None, 10, 0).unwrap().len(), 2);
+    }
+}
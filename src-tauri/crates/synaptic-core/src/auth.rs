@@ -0,0 +1,660 @@
+//! OAuth 2.1 sign-in for remote MCP servers
+//!
+//! Follows the MCP authorization spec: the server's protected resource
+//! metadata names its authorization server, whose metadata gives the
+//! endpoints. Synaptic registers itself as a public client when the server
+//! allows dynamic client registration, then runs the authorization code flow
+//! with PKCE in the system browser, receiving the code on a loopback
+//! redirect. Tokens are kept in the OS keychain under the server's url, so
+//! they survive renames; on Linux that is the Secret Service, which outlives
+//! a reboot unlike the kernel keyring. [`TokenSource`] hands them to the
+//! HTTP/SSE transports, refreshing them shortly before they expire or when
+//! the server rejects them.
+
+use crate::config::McpServer;
+use crate::error::{SynapticError, SynapticResult};
+use crate::transport;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use reqwest::header::WWW_AUTHENTICATE;
+use reqwest::{Client, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// Keychain service the tokens are stored under
+pub const KEYCHAIN_SERVICE: &str = "wear-synaptic-oauth";
+
+/// Name Synaptic registers itself under
+const CLIENT_NAME: &str = "WeaR-Synaptic";
+
+/// Path of the loopback redirect
+const CALLBACK_PATH: &str = "/callback";
+
+/// How long the user has to finish signing in
+const AUTHORIZATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Tokens this close to expiring are refreshed before use
+const REFRESH_MARGIN: chrono::Duration = chrono::Duration::seconds(60);
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+// ============================================
+// MODELS
+// ============================================
+
+/// What is kept in the keychain for one server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredTokens {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// Where the tokens are refreshed
+    pub token_endpoint: String,
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+}
+
+impl StoredTokens {
+    fn needs_refresh(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires| expires - REFRESH_MARGIN <= Utc::now())
+    }
+}
+
+/// Whether a server has been signed in to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthStatus {
+    pub server_name: String,
+    pub authorized: bool,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub scope: Option<String>,
+    /// Whether an expired access token can be renewed without signing in again
+    pub refreshable: bool,
+}
+
+/// The parts of RFC 8414 authorization server metadata used here
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct AuthServerMetadata {
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub registration_endpoint: Option<String>,
+    #[serde(default)]
+    pub scopes_supported: Vec<String>,
+}
+
+/// The parts of RFC 9728 protected resource metadata used here
+#[derive(Debug, Deserialize)]
+struct ResourceMetadata {
+    #[serde(default)]
+    authorization_servers: Vec<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegisteredClient {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+/// PKCE verifier and its S256 challenge
+#[derive(Debug, Clone)]
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    pub fn new() -> Self {
+        Self::from_verifier(random_token())
+    }
+
+    pub fn from_verifier(verifier: String) -> Self {
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self {
+            verifier,
+            challenge,
+        }
+    }
+}
+
+impl Default for Pkce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 32 random bytes, base64url encoded
+fn random_token() -> String {
+    let mut bytes = Vec::with_capacity(32);
+    bytes.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    bytes.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn auth_error(context: &str, e: impl std::fmt::Display) -> SynapticError {
+    SynapticError::AuthError(format!("{}: {}", context, e))
+}
+
+// ============================================
+// KEYCHAIN
+// ============================================
+
+fn keychain_entry(server: &McpServer) -> SynapticResult<keyring::Entry> {
+    let url = server.url.as_deref().unwrap_or_default();
+    keyring::Entry::new(KEYCHAIN_SERVICE, url).map_err(|e| auth_error("Keychain", e))
+}
+
+/// Tokens stored for a server, if it was signed in to
+pub fn load_tokens(server: &McpServer) -> Option<StoredTokens> {
+    let secret = keychain_entry(server).ok()?.get_password().ok()?;
+    serde_json::from_str(&secret).ok()
+}
+
+fn save_tokens(server: &McpServer, tokens: &StoredTokens) -> SynapticResult<()> {
+    keychain_entry(server)?
+        .set_password(&serde_json::to_string(tokens)?)
+        .map_err(|e| auth_error("Keychain", e))
+}
+
+/// Forget a server's tokens
+pub fn sign_out(server: &McpServer) -> SynapticResult<()> {
+    match keychain_entry(server)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(auth_error("Keychain", e)),
+    }
+}
+
+/// Whether a server is signed in to
+pub fn status(server_name: &str, server: &McpServer) -> AuthStatus {
+    let tokens = load_tokens(server);
+    AuthStatus {
+        server_name: server_name.to_string(),
+        authorized: tokens.is_some(),
+        expires_at: tokens.as_ref().and_then(|t| t.expires_at),
+        scope: tokens.as_ref().and_then(|t| t.scope.clone()),
+        refreshable: tokens.is_some_and(|t| t.refresh_token.is_some()),
+    }
+}
+
+// ============================================
+// DISCOVERY
+// ============================================
+
+/// `resource_metadata` parameter of a `WWW-Authenticate: Bearer` challenge
+pub fn resource_metadata_param(challenge: &str) -> Option<String> {
+    let (_, rest) = challenge.split_once("resource_metadata=")?;
+    let value = match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => rest.split([',', ' ']).next()?,
+    };
+    Some(value.to_string())
+}
+
+/// `/.well-known/<name>` of a url, with its path appended (RFC 8414 / RFC 9728)
+pub fn well_known_url(url: &Url, name: &str) -> Url {
+    let mut well_known = url.clone();
+    let path = url.path().trim_end_matches('/');
+    well_known.set_path(&format!("/.well-known/{}{}", name, path));
+    well_known.set_query(None);
+    well_known.set_fragment(None);
+    well_known
+}
+
+async fn fetch_json<T: serde::de::DeserializeOwned>(client: &Client, url: Url) -> Option<T> {
+    let response = client.get(url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.json().await.ok()
+}
+
+/// Find the authorization server of a remote MCP server, plus the scopes it asks for
+pub async fn discover(
+    client: &Client,
+    server_url: &Url,
+) -> SynapticResult<(AuthServerMetadata, Vec<String>)> {
+    // An unauthorized request may point at the resource metadata
+    let challenged = match client.get(server_url.clone()).send().await {
+        Ok(response) if response.status() == StatusCode::UNAUTHORIZED => response
+            .headers()
+            .get(WWW_AUTHENTICATE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(resource_metadata_param)
+            .and_then(|url| Url::parse(&url).ok()),
+        _ => None,
+    };
+    let resource_url =
+        challenged.unwrap_or_else(|| well_known_url(server_url, "oauth-protected-resource"));
+    let resource: Option<ResourceMetadata> = fetch_json(client, resource_url).await;
+
+    // Without resource metadata the server's origin is its authorization server
+    let issuer = resource
+        .as_ref()
+        .and_then(|r| r.authorization_servers.first())
+        .and_then(|issuer| Url::parse(issuer).ok())
+        .unwrap_or_else(|| {
+            let mut origin = server_url.clone();
+            origin.set_path("/");
+            origin.set_query(None);
+            origin
+        });
+    let mut scopes = resource.map(|r| r.scopes_supported).unwrap_or_default();
+
+    for name in ["oauth-authorization-server", "openid-configuration"] {
+        if let Some(metadata) =
+            fetch_json::<AuthServerMetadata>(client, well_known_url(&issuer, name)).await
+        {
+            if scopes.is_empty() {
+                scopes = metadata.scopes_supported.clone();
+            }
+            return Ok((metadata, scopes));
+        }
+    }
+
+    // Servers without metadata use the default endpoint paths
+    let endpoint = |path: &str| {
+        issuer
+            .join(path)
+            .map(|url| url.to_string())
+            .map_err(|e| auth_error("Authorization server", e))
+    };
+    Ok((
+        AuthServerMetadata {
+            authorization_endpoint: endpoint("/authorize")?,
+            token_endpoint: endpoint("/token")?,
+            registration_endpoint: Some(endpoint("/register")?),
+            scopes_supported: Vec::new(),
+        },
+        scopes,
+    ))
+}
+
+/// Register Synaptic as a public client (RFC 7591)
+async fn register_client(
+    client: &Client,
+    registration_endpoint: &str,
+    redirect_uri: &str,
+) -> SynapticResult<RegisteredClient> {
+    let request = serde_json::json!({
+        "client_name": CLIENT_NAME,
+        "redirect_uris": [redirect_uri],
+        "grant_types": ["authorization_code", "refresh_token"],
+        "response_types": ["code"],
+        "token_endpoint_auth_method": "none",
+    });
+    let response = client
+        .post(registration_endpoint)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| auth_error("Client registration", e))?;
+    if !response.status().is_success() {
+        return Err(auth_error(
+            "Client registration",
+            format!("HTTP {}", response.status().as_u16()),
+        ));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| auth_error("Client registration", e))
+}
+
+// ============================================
+// AUTHORIZATION CODE FLOW
+// ============================================
+
+/// The authorization request the browser is sent to
+pub fn authorization_url(
+    metadata: &AuthServerMetadata,
+    client_id: &str,
+    redirect_uri: &str,
+    pkce: &Pkce,
+    state: &str,
+    scopes: &[String],
+    resource: &Url,
+) -> SynapticResult<Url> {
+    let mut url = Url::parse(&metadata.authorization_endpoint)
+        .map_err(|e| auth_error("Authorization endpoint", e))?;
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("code_challenge", &pkce.challenge)
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", state)
+            .append_pair("resource", resource.as_str());
+        if !scopes.is_empty() {
+            query.append_pair("scope", &scopes.join(" "));
+        }
+    }
+    Ok(url)
+}
+
+/// Sign in to a remote server in the browser and store its tokens
+///
+/// `open_browser` is handed the authorization url; the flow completes when
+/// the browser comes back to the loopback redirect.
+pub async fn authorize(
+    server_name: &str,
+    server: &McpServer,
+    open_browser: impl FnOnce(&str) -> SynapticResult<()>,
+) -> SynapticResult<AuthStatus> {
+    let server_url = transport::remote_url(server)?;
+    let client = http_client()?;
+    let (metadata, scopes) = discover(&client, &server_url).await?;
+
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+    let redirect_uri = format!(
+        "http://127.0.0.1:{}{}",
+        listener.local_addr()?.port(),
+        CALLBACK_PATH
+    );
+
+    let registered = match &metadata.registration_endpoint {
+        Some(endpoint) => register_client(&client, endpoint, &redirect_uri).await?,
+        None => {
+            return Err(SynapticError::AuthError(format!(
+                "{} doesn't support dynamic client registration",
+                server_name
+            )))
+        }
+    };
+
+    let pkce = Pkce::new();
+    let state = random_token();
+    let url = authorization_url(
+        &metadata,
+        &registered.client_id,
+        &redirect_uri,
+        &pkce,
+        &state,
+        &scopes,
+        &server_url,
+    )?;
+    open_browser(url.as_str())?;
+
+    let code = tokio::time::timeout(AUTHORIZATION_TIMEOUT, receive_code(&listener, &state))
+        .await
+        .map_err(|_| SynapticError::AuthError("Sign-in timed out".to_string()))??;
+
+    let form = vec![
+        ("grant_type", "authorization_code".to_string()),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+        ("code_verifier", pkce.verifier),
+        ("resource", server_url.to_string()),
+    ];
+    let tokens = request_tokens(
+        &client,
+        &metadata.token_endpoint,
+        &registered.client_id,
+        registered.client_secret.as_deref(),
+        form,
+        None,
+    )
+    .await?;
+    save_tokens(server, &tokens)?;
+    Ok(status(server_name, server))
+}
+
+/// Wait for the browser to come back with the authorization code
+async fn receive_code(listener: &TcpListener, state: &str) -> SynapticResult<String> {
+    loop {
+        let (socket, _) = listener.accept().await?;
+        let mut socket = BufReader::new(socket);
+        let mut request_line = String::new();
+        socket.read_line(&mut request_line).await?;
+
+        let target = request_line.split(' ').nth(1).unwrap_or_default();
+        let Ok(url) = Url::parse(&format!("http://127.0.0.1{}", target)) else {
+            continue;
+        };
+        // Browsers also ask for favicons and the like
+        if url.path() != CALLBACK_PATH {
+            let _ = socket
+                .get_mut()
+                .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                .await;
+            continue;
+        }
+
+        let outcome = callback_outcome(&url, state);
+        let page = match &outcome {
+            Ok(_) => "Signed in. You can close this window and return to Synaptic.",
+            Err(_) => "Sign-in failed. Return to Synaptic for details.",
+        };
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            page.len(),
+            page
+        );
+        let _ = socket.get_mut().write_all(response.as_bytes()).await;
+        return outcome;
+    }
+}
+
+/// The code of a redirect, checked against the `state` that was sent
+pub fn callback_outcome(url: &Url, state: &str) -> SynapticResult<String> {
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+    };
+    if let Some(error) = param("error") {
+        let description = param("error_description").unwrap_or_default();
+        return Err(SynapticError::AuthError(
+            format!("{} {}", error, description).trim().to_string(),
+        ));
+    }
+    if param("state").as_deref() != Some(state) {
+        return Err(SynapticError::AuthError(
+            "Redirect doesn't belong to this sign-in".to_string(),
+        ));
+    }
+    param("code").ok_or_else(|| SynapticError::AuthError("Redirect carries no code".to_string()))
+}
+
+/// Call the token endpoint; `previous` supplies what a refresh response leaves out
+async fn request_tokens(
+    client: &Client,
+    token_endpoint: &str,
+    client_id: &str,
+    client_secret: Option<&str>,
+    mut form: Vec<(&str, String)>,
+    previous: Option<&StoredTokens>,
+) -> SynapticResult<StoredTokens> {
+    form.push(("client_id", client_id.to_string()));
+    if let Some(secret) = client_secret {
+        form.push(("client_secret", secret.to_string()));
+    }
+    let response = client
+        .post(token_endpoint)
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| auth_error("Token request", e))?;
+    if !response.status().is_success() {
+        let status = response.status().as_u16();
+        let body = response.text().await.unwrap_or_default();
+        return Err(auth_error(
+            "Token request",
+            format!("HTTP {} {}", status, body.trim()),
+        ));
+    }
+    let issued: TokenResponse = response
+        .json()
+        .await
+        .map_err(|e| auth_error("Token request", e))?;
+
+    Ok(StoredTokens {
+        access_token: issued.access_token,
+        refresh_token: issued
+            .refresh_token
+            .or_else(|| previous.and_then(|p| p.refresh_token.clone())),
+        expires_at: issued
+            .expires_in
+            .map(|secs| Utc::now() + chrono::Duration::seconds(secs)),
+        scope: issued
+            .scope
+            .or_else(|| previous.and_then(|p| p.scope.clone())),
+        token_endpoint: token_endpoint.to_string(),
+        client_id: client_id.to_string(),
+        client_secret: client_secret.map(str::to_string),
+    })
+}
+
+fn http_client() -> SynapticResult<Client> {
+    Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| auth_error("HTTP client", e))
+}
+
+// ============================================
+// TOKEN SOURCE
+// ============================================
+
+/// Access tokens of one signed-in server, for its transport
+pub struct TokenSource {
+    server: McpServer,
+    client: Client,
+    tokens: tokio::sync::Mutex<StoredTokens>,
+}
+
+impl TokenSource {
+    /// Source for a remote server that was signed in to
+    pub fn for_server(server: &McpServer) -> Option<Arc<TokenSource>> {
+        let tokens = load_tokens(server)?;
+        Some(Arc::new(TokenSource {
+            server: server.clone(),
+            client: http_client().ok()?,
+            tokens: tokio::sync::Mutex::new(tokens),
+        }))
+    }
+
+    /// Current access token, refreshed first when it is about to expire
+    pub async fn access_token(&self) -> String {
+        let mut tokens = self.tokens.lock().await;
+        if tokens.needs_refresh() {
+            if let Ok(refreshed) = self.refresh(&tokens).await {
+                *tokens = refreshed;
+            }
+        }
+        tokens.access_token.clone()
+    }
+
+    /// A new access token after the server rejected `rejected`, if one can be had
+    pub async fn renew(&self, rejected: &str) -> Option<String> {
+        let mut tokens = self.tokens.lock().await;
+        // Someone else renewed it meanwhile
+        if tokens.access_token != rejected {
+            return Some(tokens.access_token.clone());
+        }
+        let refreshed = self.refresh(&tokens).await.ok()?;
+        *tokens = refreshed;
+        Some(tokens.access_token.clone())
+    }
+
+    async fn refresh(&self, tokens: &StoredTokens) -> SynapticResult<StoredTokens> {
+        let Some(refresh_token) = &tokens.refresh_token else {
+            return Err(SynapticError::AuthError(
+                "No refresh token; sign in again".to_string(),
+            ));
+        };
+        let form = vec![
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", refresh_token.clone()),
+        ];
+        let refreshed = request_tokens(
+            &self.client,
+            &tokens.token_endpoint,
+            &tokens.client_id,
+            tokens.client_secret.as_deref(),
+            form,
+            Some(tokens),
+        )
+        .await?;
+        if let Err(e) = save_tokens(&self.server, &refreshed) {
+            eprintln!("Failed to store refreshed tokens: {}", e);
+        }
+        Ok(refreshed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_challenge_matches_rfc_7636() {
+        let pkce = Pkce::from_verifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string());
+        assert_eq!(
+            pkce.challenge,
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        assert_ne!(Pkce::new().verifier, Pkce::new().verifier);
+    }
+
+    #[test]
+    fn test_discovery_urls() {
+        let challenge = r#"Bearer realm="mcp", resource_metadata="https://mcp.example.com/.well-known/oauth-protected-resource""#;
+        assert_eq!(
+            resource_metadata_param(challenge).as_deref(),
+            Some("https://mcp.example.com/.well-known/oauth-protected-resource")
+        );
+        assert_eq!(resource_metadata_param("Bearer"), None);
+
+        let server = Url::parse("https://mcp.example.com/v1/mcp?x=1").unwrap();
+        assert_eq!(
+            well_known_url(&server, "oauth-protected-resource").as_str(),
+            "https://mcp.example.com/.well-known/oauth-protected-resource/v1/mcp"
+        );
+        let issuer = Url::parse("https://auth.example.com").unwrap();
+        assert_eq!(
+            well_known_url(&issuer, "oauth-authorization-server").as_str(),
+            "https://auth.example.com/.well-known/oauth-authorization-server"
+        );
+    }
+
+    #[test]
+    fn test_callback_checks_state() {
+        let url =
+            |query: &str| Url::parse(&format!("http://127.0.0.1/callback?{}", query)).unwrap();
+        assert_eq!(
+            callback_outcome(&url("code=abc&state=s1"), "s1").unwrap(),
+            "abc"
+        );
+        assert!(callback_outcome(&url("code=abc&state=other"), "s1").is_err());
+        let denied = callback_outcome(&url("error=access_denied&state=s1"), "s1").unwrap_err();
+        assert!(denied.to_string().contains("access_denied"));
+    }
+}
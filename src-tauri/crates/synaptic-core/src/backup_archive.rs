@@ -0,0 +1,158 @@
+//! Export of the backup history as a single zip archive
+//!
+//! Bundles every backup, the current config and a manifest describing them,
+//! for moving to another machine or attaching to a support ticket.
+
+use crate::config::{self, BackupInfo};
+use crate::error::{SynapticError, SynapticResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Name of the manifest entry inside the archive
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Name of the current config entry inside the archive
+pub const CONFIG_ENTRY: &str = "claude_desktop_config.json";
+
+/// Index describing the archive contents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub exported_at: DateTime<Utc>,
+    pub synaptic_version: String,
+    /// Where the config lives on the exporting machine
+    pub config_path: String,
+    pub includes_config: bool,
+    pub backups: Vec<BackupInfo>,
+}
+
+/// Summary returned after a successful export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupExport {
+    pub path: String,
+    pub backup_count: usize,
+    pub size_bytes: u64,
+}
+
+/// Export all backups plus the current config to a zip archive at `dest`
+pub fn export_backups(dest: &Path) -> SynapticResult<BackupExport> {
+    export_backups_in(
+        &config::get_claude_config_path()?,
+        &config::get_backups_dir()?,
+        dest,
+    )
+}
+
+/// Path-parameterized version of [`export_backups`]
+pub fn export_backups_in(
+    config_path: &Path,
+    backups_dir: &Path,
+    dest: &Path,
+) -> SynapticResult<BackupExport> {
+    let backups = config::list_backups_in(backups_dir)?;
+    let includes_config = config_path.exists();
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = File::create(dest).map_err(|e| {
+        SynapticError::BackupError(format!("Failed to create {}: {}", dest.display(), e))
+    })?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let manifest = BackupManifest {
+        exported_at: Utc::now(),
+        synaptic_version: env!("CARGO_PKG_VERSION").to_string(),
+        config_path: config_path.to_string_lossy().to_string(),
+        includes_config,
+        backups: backups.clone(),
+    };
+    add_entry(
+        &mut zip,
+        MANIFEST_FILE,
+        &serde_json::to_vec_pretty(&manifest)?,
+        options,
+    )?;
+
+    if includes_config {
+        add_entry(&mut zip, CONFIG_ENTRY, &fs::read(config_path)?, options)?;
+    }
+
+    for backup in &backups {
+        let content = fs::read(backups_dir.join(&backup.filename))?;
+        add_entry(
+            &mut zip,
+            &format!("backups/{}", backup.filename),
+            &content,
+            options,
+        )?;
+    }
+
+    zip.finish()
+        .map_err(|e| SynapticError::BackupError(format!("Failed to finish archive: {}", e)))?;
+
+    Ok(BackupExport {
+        path: dest.to_string_lossy().to_string(),
+        backup_count: backups.len(),
+        size_bytes: fs::metadata(dest).map(|m| m.len()).unwrap_or(0),
+    })
+}
+
+fn add_entry(
+    zip: &mut ZipWriter<File>,
+    name: &str,
+    content: &[u8],
+    options: SimpleFileOptions,
+) -> SynapticResult<()> {
+    zip.start_file(name, options)
+        .map_err(|e| SynapticError::BackupError(format!("Failed to add {}: {}", name, e)))?;
+    zip.write_all(content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_contains_manifest_config_and_backups() {
+        let root = std::env::temp_dir().join(format!("synaptic-export-{}", uuid::Uuid::new_v4()));
+        let config_path = root.join("claude_desktop_config.json");
+        let backups_dir = root.join("backups");
+        fs::create_dir_all(&backups_dir).unwrap();
+        fs::write(&config_path, r#"{"mcpServers":{}}"#).unwrap();
+        fs::write(backups_dir.join("2025-12-19T08-30-00.json"), "{}").unwrap();
+
+        let dest = root.join("out").join("export.zip");
+        let export = export_backups_in(&config_path, &backups_dir, &dest).unwrap();
+        assert_eq!(export.backup_count, 1);
+        assert!(export.size_bytes > 0);
+
+        let mut archive = zip::ZipArchive::new(File::open(&dest).unwrap()).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(String::from).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "backups/2025-12-19T08-30-00.json",
+                CONFIG_ENTRY,
+                MANIFEST_FILE
+            ]
+        );
+
+        let manifest: BackupManifest =
+            serde_json::from_reader(archive.by_name(MANIFEST_FILE).unwrap()).unwrap();
+        assert!(manifest.includes_config);
+        assert_eq!(manifest.backups.len(), 1);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
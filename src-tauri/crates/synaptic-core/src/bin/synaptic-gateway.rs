@@ -0,0 +1,36 @@
+//! `synaptic-gateway`: stdio bridge to Synaptic's MCP gateway
+//!
+//! Clients that launch their servers over stdio get every server Synaptic
+//! manages through this single entry; see `synaptic_core::gateway`.
+
+use synaptic_core::gateway::{self, DEFAULT_GATEWAY_PORT, GATEWAY_BINARY, GATEWAY_TOKEN_ENV};
+
+#[tokio::main]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    let url = match (args.next().as_deref(), args.next()) {
+        (Some("--url"), Some(url)) => url,
+        (None, _) => gateway::gateway_url(DEFAULT_GATEWAY_PORT),
+        _ => {
+            eprintln!("usage: {} [--url <gateway url>]", GATEWAY_BINARY);
+            std::process::exit(2);
+        }
+    };
+
+    // Entries written by Synaptic carry the token; otherwise read it like Synaptic does
+    let token = match std::env::var(GATEWAY_TOKEN_ENV) {
+        Ok(token) => token,
+        Err(_) => match gateway::gateway_token() {
+            Ok(token) => token,
+            Err(e) => {
+                eprintln!("{}: no gateway token: {}", GATEWAY_BINARY, e);
+                std::process::exit(1);
+            }
+        },
+    };
+
+    if let Err(e) = gateway::bridge_stdio(&url, &token).await {
+        eprintln!("{}: {}", GATEWAY_BINARY, e);
+        std::process::exit(1);
+    }
+}
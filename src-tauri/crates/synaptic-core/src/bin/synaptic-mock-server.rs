@@ -0,0 +1,20 @@
+//! `synaptic-mock-server`: MCP server answering from a definition file
+//!
+//! Gives clients a deterministic server to test against; see
+//! `synaptic_core::mock_server`.
+
+use synaptic_core::mock_server::{self, MOCK_SERVER_BINARY};
+
+#[tokio::main]
+async fn main() {
+    let mut args = std::env::args_os().skip(1);
+    let (Some(definition), None) = (args.next(), args.next()) else {
+        eprintln!("usage: {} <definition.json>", MOCK_SERVER_BINARY);
+        std::process::exit(2);
+    };
+
+    if let Err(e) = mock_server::run(definition.into()).await {
+        eprintln!("{}: {}", MOCK_SERVER_BINARY, e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,21 @@
+//! `synaptic-shim`: stdio bridge between an MCP client and a server
+//!
+//! The client launches it in place of the server's own command once client
+//! capture is enabled in Synaptic; see `synaptic_core::shim`.
+
+use synaptic_core::shim::{self, ShimArgs, SHIM_BINARY};
+
+#[tokio::main]
+async fn main() {
+    let code = match ShimArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => shim::run(args).await.unwrap_or_else(|e| {
+            eprintln!("{}: {}", SHIM_BINARY, e);
+            1
+        }),
+        Err(e) => {
+            eprintln!("{}: {}", SHIM_BINARY, e);
+            2
+        }
+    };
+    std::process::exit(code);
+}
@@ -0,0 +1,169 @@
+//! Bookmarked and annotated inspector messages
+//!
+//! The inspector history is a bounded buffer that `clear_inspector_messages`
+//! empties. Bookmarking a message copies it into the `bookmarks` table with
+//! an optional free-text annotation, so exchanges found while debugging
+//! outlive the buffer.
+
+use crate::database::Database;
+use crate::error::{SynapticError, SynapticResult};
+use crate::inspector::InspectorMessage;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A pinned copy of an inspector message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bookmark {
+    pub message: InspectorMessage,
+    pub annotation: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn now_millis() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or_default()
+}
+
+/// Bookmark a message; bookmarking it again only updates the annotation
+pub fn bookmark(
+    db: &Database,
+    message: &InspectorMessage,
+    annotation: Option<String>,
+) -> SynapticResult<Bookmark> {
+    let now = now_millis();
+    db.conn().execute(
+        "INSERT INTO bookmarks (message_id, server_name, message, annotation, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+         ON CONFLICT(message_id) DO UPDATE SET
+            annotation = COALESCE(excluded.annotation, bookmarks.annotation),
+            updated_at = excluded.updated_at",
+        params![
+            message.id,
+            message.server_name,
+            serde_json::to_value(message)?,
+            annotation,
+            now,
+        ],
+    )?;
+    get(db, &message.id)?.ok_or_else(|| {
+        SynapticError::InspectorError(format!("Bookmark {} was not stored", message.id))
+    })
+}
+
+/// Set or clear the annotation of a bookmarked message
+pub fn annotate(
+    db: &Database,
+    message_id: &str,
+    annotation: Option<String>,
+) -> SynapticResult<Bookmark> {
+    let updated = db.conn().execute(
+        "UPDATE bookmarks SET annotation = ?2, updated_at = ?3 WHERE message_id = ?1",
+        params![message_id, annotation, now_millis()],
+    )?;
+    if updated == 0 {
+        return Err(SynapticError::InspectorError(format!(
+            "Message {} is not bookmarked",
+            message_id
+        )));
+    }
+    get(db, message_id)?.ok_or_else(|| {
+        SynapticError::InspectorError(format!("Message {} is not bookmarked", message_id))
+    })
+}
+
+/// Remove a bookmark; returns whether it existed
+pub fn remove(db: &Database, message_id: &str) -> SynapticResult<bool> {
+    let removed = db.conn().execute(
+        "DELETE FROM bookmarks WHERE message_id = ?1",
+        params![message_id],
+    )?;
+    Ok(removed > 0)
+}
+
+pub fn get(db: &Database, message_id: &str) -> SynapticResult<Option<Bookmark>> {
+    Ok(select(db, Some(message_id), None)?.pop())
+}
+
+/// Bookmarks in message order, optionally for a single server
+pub fn list(db: &Database, server_name: Option<&str>) -> SynapticResult<Vec<Bookmark>> {
+    select(db, None, server_name)
+}
+
+fn select(
+    db: &Database,
+    message_id: Option<&str>,
+    server_name: Option<&str>,
+) -> SynapticResult<Vec<Bookmark>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT message, annotation, created_at, updated_at
+         FROM bookmarks
+         WHERE (?1 IS NULL OR message_id = ?1) AND (?2 IS NULL OR server_name = ?2)",
+    )?;
+    let rows = stmt.query_map(params![message_id, server_name], |row| {
+        Ok((
+            row.get::<_, Value>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, i64>(3)?,
+        ))
+    })?;
+
+    let mut bookmarks = Vec::new();
+    for row in rows {
+        let (message, annotation, created_at, updated_at) = row?;
+        bookmarks.push(Bookmark {
+            message: serde_json::from_value(message)?,
+            annotation,
+            created_at: from_millis(created_at),
+            updated_at: from_millis(updated_at),
+        });
+    }
+    bookmarks.sort_by_key(|b| b.message.timestamp);
+    Ok(bookmarks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::AppState;
+    use serde_json::json;
+
+    #[test]
+    fn test_bookmarks_survive_clearing_the_inspector() {
+        let state = AppState::with_database(Database::open_in_memory().unwrap());
+        let message =
+            InspectorMessage::new_response("fs", json!({"jsonrpc":"2.0","id":1,"result":{}}));
+        state.add_inspector_message("fs", message.clone());
+
+        let found = state.find_inspector_message("fs", &message.id).unwrap();
+        let db = state.database().unwrap();
+        let saved = bookmark(db, &found, Some("slow response".to_string())).unwrap();
+        assert_eq!(saved.annotation.as_deref(), Some("slow response"));
+        // Bookmarking again keeps the annotation
+        assert_eq!(
+            bookmark(db, &found, None).unwrap().annotation.as_deref(),
+            Some("slow response")
+        );
+
+        state.clear_inspector_messages("fs");
+        assert!(state.get_inspector_messages("fs").is_empty());
+        let kept = list(db, Some("fs")).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].message.payload["id"], 1);
+
+        assert_eq!(annotate(db, &message.id, None).unwrap().annotation, None);
+        assert!(annotate(db, "missing", Some("x".to_string())).is_err());
+        assert!(remove(db, &message.id).unwrap());
+        assert!(list(db, None).unwrap().is_empty());
+    }
+}
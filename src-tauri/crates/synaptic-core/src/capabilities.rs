@@ -0,0 +1,251 @@
+//! Negotiated protocol version and capabilities of each server
+//!
+//! The `initialize` request and its response pass through the stdin writer
+//! and stdout reader like any other traffic. The [`CapabilityTracker`] pairs
+//! them up and keeps what each running server answered; every handshake is
+//! also stored in the `server_capabilities` table so the last known
+//! capabilities survive a restart of Synaptic.
+
+use crate::database::Database;
+use crate::error::SynapticResult;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Result of a server's `initialize` handshake
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerCapabilities {
+    pub server_name: String,
+    /// Version the server agreed to
+    pub protocol_version: Option<String>,
+    /// Version the client asked for
+    pub requested_version: Option<String>,
+    /// `capabilities` object of the server (tools, resources, prompts, logging, ...)
+    pub capabilities: Value,
+    /// `serverInfo` (name and version)
+    pub server_info: Value,
+    /// `clientInfo` of the client that initialized the server
+    pub client_info: Value,
+    pub instructions: Option<String>,
+    pub negotiated_at: DateTime<Utc>,
+}
+
+impl ServerCapabilities {
+    /// Whether the server declared a capability, e.g. `tools` or `resources`
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities
+            .get(capability)
+            .is_some_and(|value| !value.is_null())
+    }
+}
+
+/// An `initialize` request waiting for its response
+struct PendingInitialize {
+    id: Value,
+    requested_version: Option<String>,
+    client_info: Value,
+}
+
+/// Pairs `initialize` requests with their responses, per server
+#[derive(Default)]
+pub struct CapabilityTracker {
+    pending: Mutex<HashMap<String, PendingInitialize>>,
+    negotiated: Mutex<HashMap<String, ServerCapabilities>>,
+}
+
+impl CapabilityTracker {
+    /// Look at an outgoing message; remembers `initialize` requests
+    pub fn observe_request(&self, server_name: &str, message: &Value) {
+        if message.get("method").and_then(|m| m.as_str()) != Some("initialize") {
+            return;
+        }
+        let Some(id) = message.get("id") else {
+            return;
+        };
+        let params = message.get("params");
+        self.pending.lock().unwrap().insert(
+            server_name.to_string(),
+            PendingInitialize {
+                id: id.clone(),
+                requested_version: params
+                    .and_then(|p| p.get("protocolVersion"))
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+                client_info: params
+                    .and_then(|p| p.get("clientInfo"))
+                    .cloned()
+                    .unwrap_or(Value::Null),
+            },
+        );
+    }
+
+    /// Look at an incoming message; returns the capabilities when it answers `initialize`
+    pub fn observe_response(
+        &self,
+        server_name: &str,
+        message: &Value,
+    ) -> Option<ServerCapabilities> {
+        let id = message.get("id")?;
+        let request = {
+            let mut pending = self.pending.lock().unwrap();
+            if pending.get(server_name).is_none_or(|p| &p.id != id) {
+                return None;
+            }
+            pending.remove(server_name)?
+        };
+        // A failed handshake leaves nothing to record
+        let result = message.get("result")?;
+
+        let capabilities = ServerCapabilities {
+            server_name: server_name.to_string(),
+            protocol_version: result
+                .get("protocolVersion")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            requested_version: request.requested_version,
+            capabilities: result.get("capabilities").cloned().unwrap_or(Value::Null),
+            server_info: result.get("serverInfo").cloned().unwrap_or(Value::Null),
+            client_info: request.client_info,
+            instructions: result
+                .get("instructions")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            negotiated_at: Utc::now(),
+        };
+        self.negotiated
+            .lock()
+            .unwrap()
+            .insert(server_name.to_string(), capabilities.clone());
+        Some(capabilities)
+    }
+
+    /// Capabilities negotiated since Synaptic started
+    pub fn get(&self, server_name: &str) -> Option<ServerCapabilities> {
+        self.negotiated.lock().unwrap().get(server_name).cloned()
+    }
+
+    /// Forget an unanswered handshake of a server that exited
+    pub fn forget_pending(&self, server_name: &str) {
+        self.pending.lock().unwrap().remove(server_name);
+    }
+}
+
+// ============================================
+// PERSISTENCE
+// ============================================
+
+/// Store a server's latest handshake, replacing the previous one
+pub fn record(db: &Database, capabilities: &ServerCapabilities) -> SynapticResult<()> {
+    db.conn().execute(
+        "INSERT OR REPLACE INTO server_capabilities
+            (server_name, protocol_version, requested_version, capabilities, server_info,
+             client_info, instructions, negotiated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            capabilities.server_name,
+            capabilities.protocol_version,
+            capabilities.requested_version,
+            capabilities.capabilities,
+            capabilities.server_info,
+            capabilities.client_info,
+            capabilities.instructions,
+            capabilities.negotiated_at.timestamp_millis(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Last stored handshake of a server
+pub fn load(db: &Database, server_name: &str) -> SynapticResult<Option<ServerCapabilities>> {
+    let conn = db.conn();
+    let capabilities = conn
+        .query_row(
+            "SELECT server_name, protocol_version, requested_version, capabilities, server_info,
+                    client_info, instructions, negotiated_at
+             FROM server_capabilities
+             WHERE server_name = ?1",
+            params![server_name],
+            |row| {
+                let millis: i64 = row.get(7)?;
+                Ok(ServerCapabilities {
+                    server_name: row.get(0)?,
+                    protocol_version: row.get(1)?,
+                    requested_version: row.get(2)?,
+                    capabilities: row.get(3)?,
+                    server_info: row.get(4)?,
+                    client_info: row.get(5)?,
+                    instructions: row.get(6)?,
+                    negotiated_at: Utc
+                        .timestamp_millis_opt(millis)
+                        .single()
+                        .unwrap_or_default(),
+                })
+            },
+        )
+        .optional()?;
+    Ok(capabilities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_initialize_handshake_is_tracked_and_stored() {
+        let tracker = CapabilityTracker::default();
+        tracker.observe_request(
+            "fs",
+            &json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": {},
+                    "clientInfo": { "name": "claude-desktop", "version": "1.0" }
+                }
+            }),
+        );
+        // Unrelated responses don't complete the handshake
+        assert!(tracker
+            .observe_response("fs", &json!({ "jsonrpc": "2.0", "id": 7, "result": {} }))
+            .is_none());
+
+        let negotiated = tracker
+            .observe_response(
+                "fs",
+                &json!({
+                    "jsonrpc": "2.0",
+                    "id": 0,
+                    "result": {
+                        "protocolVersion": "2025-03-26",
+                        "capabilities": { "tools": { "listChanged": true } },
+                        "serverInfo": { "name": "filesystem", "version": "0.6.2" }
+                    }
+                }),
+            )
+            .unwrap();
+        assert_eq!(negotiated.protocol_version.as_deref(), Some("2025-03-26"));
+        assert_eq!(negotiated.requested_version.as_deref(), Some("2025-06-18"));
+        assert_eq!(negotiated.client_info["name"], "claude-desktop");
+        assert!(negotiated.supports("tools"));
+        assert!(!negotiated.supports("resources"));
+        assert_eq!(tracker.get("fs"), Some(negotiated.clone()));
+        assert!(tracker.get("git").is_none());
+
+        let db = Database::open_in_memory().unwrap();
+        assert!(load(&db, "fs").unwrap().is_none());
+        record(&db, &negotiated).unwrap();
+        let stored = load(&db, "fs").unwrap().unwrap();
+        assert_eq!(stored.server_info["version"], "0.6.2");
+        assert_eq!(
+            stored.negotiated_at.timestamp_millis(),
+            negotiated.negotiated_at.timestamp_millis()
+        );
+    }
+}
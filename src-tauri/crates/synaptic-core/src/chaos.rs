@@ -0,0 +1,139 @@
+//! Latency and fault injection for resilience testing
+//!
+//! Chaos settings are kept per server while Synaptic runs. The stdout reader
+//! drops server messages and delays each one on its own, so delays overlap
+//! and other traffic keeps flowing; the stdin writer answers a share of the
+//! requests with a JSON-RPC error instead of passing them on. Synaptic's own
+//! requests (health pings) are left alone.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Faults injected into one server's traffic
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChaosConfig {
+    /// Milliseconds added before each server message is delivered
+    pub delay_ms: u64,
+    /// Up to this many milliseconds more, picked at random per message
+    pub jitter_ms: u64,
+    /// Chance (0-1) a server message is lost
+    pub drop_probability: f64,
+    /// Chance (0-1) a request is answered with an error instead of reaching the server
+    pub error_probability: f64,
+    pub error_code: i64,
+    pub error_message: String,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            delay_ms: 0,
+            jitter_ms: 0,
+            drop_probability: 0.0,
+            error_probability: 0.0,
+            error_code: -32603,
+            error_message: "Injected fault".to_string(),
+        }
+    }
+}
+
+impl ChaosConfig {
+    /// Delay for the next server message
+    pub fn delay(&self) -> Duration {
+        let jitter = (random_unit() * (self.jitter_ms + 1) as f64) as u64;
+        Duration::from_millis(self.delay_ms + jitter.min(self.jitter_ms))
+    }
+
+    /// Whether to lose the next server message
+    pub fn should_drop(&self) -> bool {
+        random_unit() < self.drop_probability
+    }
+
+    /// Error response standing in for `request`, if this one fails
+    ///
+    /// Notifications (no `id`) can't be answered and always pass.
+    pub fn injected_error(&self, request: &Value) -> Option<Value> {
+        let id = request.get("id")?;
+        request.get("method")?;
+        (random_unit() < self.error_probability).then(|| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": self.error_code, "message": self.error_message },
+            })
+        })
+    }
+}
+
+/// Uniform number in `[0, 1)`, from the random bits of a v4 UUID
+fn random_unit() -> f64 {
+    (uuid::Uuid::new_v4().as_u128() >> 75) as f64 / (1u64 << 53) as f64
+}
+
+/// Chaos settings by server name
+#[derive(Default)]
+pub struct ChaosEngine {
+    configs: Mutex<HashMap<String, ChaosConfig>>,
+}
+
+impl ChaosEngine {
+    /// Set a server's chaos settings, or clear them with `None`
+    pub fn set(&self, server_name: &str, config: Option<ChaosConfig>) {
+        let mut configs = self.configs.lock().unwrap();
+        match config {
+            Some(config) => configs.insert(server_name.to_string(), config),
+            None => configs.remove(server_name),
+        };
+    }
+
+    pub fn get(&self, server_name: &str) -> Option<ChaosConfig> {
+        self.configs.lock().unwrap().get(server_name).cloned()
+    }
+
+    pub fn all(&self) -> HashMap<String, ChaosConfig> {
+        self.configs.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chaos_faults() {
+        let calm = ChaosConfig::default();
+        let request = json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/call" });
+        assert_eq!(calm.delay(), Duration::ZERO);
+        assert!(!calm.should_drop());
+        assert!(calm.injected_error(&request).is_none());
+
+        let chaotic = ChaosConfig {
+            delay_ms: 100,
+            jitter_ms: 50,
+            drop_probability: 1.0,
+            error_probability: 1.0,
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let delay = chaotic.delay();
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(150));
+        }
+        assert!(chaotic.should_drop());
+        let error = chaotic.injected_error(&request).unwrap();
+        assert_eq!(error["id"], 3);
+        assert_eq!(error["error"]["code"], -32603);
+        assert!(chaotic
+            .injected_error(&json!({ "method": "notifications/initialized" }))
+            .is_none());
+
+        let engine = ChaosEngine::default();
+        engine.set("flaky", Some(chaotic.clone()));
+        assert_eq!(engine.get("flaky"), Some(chaotic));
+        engine.set("flaky", None);
+        assert!(engine.all().is_empty());
+    }
+}
@@ -0,0 +1,715 @@
+//! MCP Configuration data structures and file I/O operations
+
+use crate::error::{SynapticError, SynapticResult};
+use crate::framing::Framing;
+use crate::limits::ResourceLimits;
+use crate::ssh::SshTarget;
+use crate::transport::Transport;
+use chrono::{DateTime, Utc};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// ============================================
+// MCP CONFIGURATION SCHEMA
+// ============================================
+
+/// Root configuration structure matching Claude Desktop's config format
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct McpConfig {
+    /// Map of server name to server configuration
+    #[serde(default)]
+    pub mcp_servers: HashMap<String, McpServer>,
+
+    /// Preserve any unknown fields for forward compatibility
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Individual MCP server configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServer {
+    /// Command to execute (e.g., "npx", "uvx", "node"); empty for remote servers
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub command: String,
+
+    /// Arguments passed to the command
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Environment variables for the server process
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+
+    /// Optional working directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+
+    /// Directories put in front of `PATH`, e.g. a venv's bin directory; relative
+    /// ones are resolved against `cwd` (Synaptic extension)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path_prepend: Vec<String>,
+
+    /// Server enabled/disabled state (Synaptic extension)
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// What to do when the spawned process exits (Synaptic extension)
+    #[serde(default, skip_serializing_if = "RestartPolicy::is_default")]
+    pub restart: RestartPolicy,
+
+    /// Message framing on stdio, detected from the server's output by default (Synaptic extension)
+    #[serde(default, skip_serializing_if = "Framing::is_auto")]
+    pub framing: Framing,
+
+    /// Run the command on another machine over SSH (Synaptic extension)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh: Option<SshTarget>,
+
+    /// CPU scheduling priority of the local process (Synaptic extension)
+    #[serde(default, skip_serializing_if = "ProcessPriority::is_normal")]
+    pub priority: ProcessPriority,
+
+    /// Memory and CPU caps of the local process tree (Synaptic extension)
+    #[serde(default, skip_serializing_if = "ResourceLimits::is_empty")]
+    pub limits: ResourceLimits,
+
+    /// Command the launch is wrapped in, e.g. an inspector shim (Synaptic extension)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrapper: Option<CommandWrapper>,
+
+    /// Endpoint of a server reached over HTTP instead of spawned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// How `url` is spoken to; stdio for spawned servers
+    #[serde(default, skip_serializing_if = "Transport::is_stdio")]
+    pub transport: Transport,
+
+    /// Extra HTTP headers sent to `url`, e.g. an API key
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+}
+
+impl Default for McpServer {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            cwd: None,
+            path_prepend: Vec::new(),
+            enabled: true,
+            restart: RestartPolicy::default(),
+            framing: Framing::default(),
+            ssh: None,
+            priority: ProcessPriority::default(),
+            limits: ResourceLimits::default(),
+            wrapper: None,
+            url: None,
+            transport: Transport::default(),
+            headers: HashMap::new(),
+        }
+    }
+}
+
+impl McpServer {
+    /// Whether the server is connected to over HTTP rather than spawned
+    pub fn is_remote(&self) -> bool {
+        self.url.is_some() && !self.transport.is_stdio()
+    }
+
+    /// Whether `other` launches a different process or connection (command, args, env, cwd, PATH, host, framing, priority, limits, wrapper, url, transport or headers)
+    pub fn launch_differs(&self, other: &McpServer) -> bool {
+        self.command != other.command
+            || self.args != other.args
+            || self.env != other.env
+            || self.cwd != other.cwd
+            || self.path_prepend != other.path_prepend
+            || self.framing != other.framing
+            || self.ssh != other.ssh
+            || self.priority != other.priority
+            || self.limits != other.limits
+            || self.wrapper != other.wrapper
+            || self.url != other.url
+            || self.transport != other.transport
+            || self.headers != other.headers
+    }
+
+    /// The entry with the command and args that are actually run, after applying `wrapper`
+    pub fn wrapped(&self) -> McpServer {
+        let Some(wrapper) = &self.wrapper else {
+            return self.clone();
+        };
+
+        let mut args = Vec::new();
+        let mut placed = false;
+        for arg in &wrapper.args {
+            match arg.as_str() {
+                "{command}" => {
+                    args.push(self.command.clone());
+                    placed = true;
+                }
+                "{args}" => {
+                    args.extend(self.args.iter().cloned());
+                    placed = true;
+                }
+                _ => args.push(arg.clone()),
+            }
+        }
+        if !placed {
+            args.push(self.command.clone());
+            args.extend(self.args.iter().cloned());
+        }
+
+        McpServer {
+            command: wrapper.command.clone(),
+            args,
+            wrapper: None,
+            ..self.clone()
+        }
+    }
+
+    /// `path_prepend` entries as directories, relative ones resolved against `cwd`
+    pub fn path_prepend_dirs(&self) -> Vec<PathBuf> {
+        self.path_prepend
+            .iter()
+            .map(|dir| {
+                let dir = PathBuf::from(crate::paths::expand_tilde(dir));
+                match &self.cwd {
+                    Some(cwd) if dir.is_relative() => {
+                        PathBuf::from(crate::paths::expand_tilde(cwd)).join(dir)
+                    }
+                    _ => dir,
+                }
+            })
+            .collect()
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Command a server's launch is wrapped in (an inspector shim, a profiler, ...)
+///
+/// `{command}` and `{args}` in `args` stand for the configured command and
+/// its arguments; with neither, both are appended. Leaving out `{command}`
+/// swaps the binary while keeping the arguments.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandWrapper {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// CPU priority of a spawned server
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProcessPriority {
+    /// Yield to the desktop (nice 10 / below-normal class)
+    Low,
+    #[default]
+    Normal,
+    /// Raised priority (nice -5 / above-normal class); on Unix only with the rights to do so
+    High,
+}
+
+impl ProcessPriority {
+    pub fn is_normal(&self) -> bool {
+        *self == ProcessPriority::Normal
+    }
+}
+
+/// When a spawned server should be restarted after it exits
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartMode {
+    /// Leave the server stopped
+    #[default]
+    Never,
+    /// Restart only after a non-zero exit or signal
+    OnFailure,
+    /// Restart after any exit not requested by the user
+    Always,
+}
+
+/// Per-server restart policy with exponential backoff
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RestartPolicy {
+    pub mode: RestartMode,
+    /// Consecutive restarts before giving up
+    pub max_retries: u32,
+    /// Delay before the first restart; doubled for every further attempt
+    pub initial_backoff_ms: u64,
+    /// Upper bound for the restart delay
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RestartMode::Never,
+            max_retries: 5,
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+impl RestartPolicy {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether an exit should trigger restart number `attempt + 1`
+    pub fn should_restart(&self, success: bool, attempt: u32) -> bool {
+        let wanted = match self.mode {
+            RestartMode::Never => false,
+            RestartMode::OnFailure => !success,
+            RestartMode::Always => true,
+        };
+        wanted && attempt < self.max_retries
+    }
+
+    /// Delay before restart number `attempt + 1`
+    pub fn backoff_delay(&self, attempt: u32) -> std::time::Duration {
+        let factor = 2u64.saturating_pow(attempt);
+        let delay = self
+            .initial_backoff_ms
+            .saturating_mul(factor)
+            .min(self.max_backoff_ms);
+        std::time::Duration::from_millis(delay)
+    }
+}
+
+/// Backup file information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub id: String,
+    pub filename: String,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+// ============================================
+// PATH RESOLUTION
+// ============================================
+
+/// Get the OS-specific path to Claude Desktop config file
+pub fn get_claude_config_path() -> SynapticResult<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        let home = dirs::home_dir().ok_or_else(|| {
+            SynapticError::ConfigNotFound("Could not determine home directory".to_string())
+        })?;
+        Ok(home.join("Library/Application Support/Claude/claude_desktop_config.json"))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let app_data = dirs::config_dir().ok_or_else(|| {
+            SynapticError::ConfigNotFound("Could not determine AppData directory".to_string())
+        })?;
+        Ok(app_data.join("Claude").join("claude_desktop_config.json"))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let config = dirs::config_dir().ok_or_else(|| {
+            SynapticError::ConfigNotFound("Could not determine config directory".to_string())
+        })?;
+        Ok(config.join("Claude/claude_desktop_config.json"))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        Err(SynapticError::ConfigNotFound(
+            "Unsupported operating system".to_string(),
+        ))
+    }
+}
+
+/// Get the WeaR-Synaptic data directory path
+pub fn get_synaptic_data_dir() -> SynapticResult<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        let home = dirs::home_dir().ok_or_else(|| {
+            SynapticError::ConfigNotFound("Could not determine home directory".to_string())
+        })?;
+        Ok(home.join("Library/Application Support/WeaR-Synaptic"))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let app_data = dirs::config_dir().ok_or_else(|| {
+            SynapticError::ConfigNotFound("Could not determine AppData directory".to_string())
+        })?;
+        Ok(app_data.join("WeaR-Synaptic"))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let config = dirs::config_dir().ok_or_else(|| {
+            SynapticError::ConfigNotFound("Could not determine config directory".to_string())
+        })?;
+        Ok(config.join("WeaR-Synaptic"))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        Err(SynapticError::ConfigNotFound(
+            "Unsupported operating system".to_string(),
+        ))
+    }
+}
+
+/// Random bytes for a secret
+pub fn random_secret(len: usize) -> Vec<u8> {
+    let mut secret = vec![0u8; len];
+    SystemRandom::new()
+        .fill(&mut secret)
+        .expect("system random number generator failed");
+    secret
+}
+
+/// Read a secret kept in the data directory, creating it (readable only by the user) on first use
+pub fn load_or_create_secret(file_name: &str, len: usize) -> SynapticResult<Vec<u8>> {
+    let dir = get_synaptic_data_dir()?;
+    let path = dir.join(file_name);
+    if let Ok(secret) = fs::read(&path) {
+        if secret.len() == len {
+            return Ok(secret);
+        }
+        fs::remove_file(&path)?;
+    }
+
+    fs::create_dir_all(&dir)?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    match options.open(&path) {
+        Ok(mut file) => {
+            let secret = random_secret(len);
+            file.write_all(&secret)?;
+            Ok(secret)
+        }
+        // Another process (e.g. the shim) created it first
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(fs::read(&path)?),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Get the backups directory path
+pub fn get_backups_dir() -> SynapticResult<PathBuf> {
+    Ok(get_synaptic_data_dir()?.join("backups"))
+}
+
+// ============================================
+// FILE I/O OPERATIONS
+// ============================================
+
+/// Read and parse the MCP configuration file
+pub fn read_config_file() -> SynapticResult<McpConfig> {
+    let config_path = get_claude_config_path()?;
+
+    if !config_path.exists() {
+        // Return empty config if file doesn't exist
+        return Ok(McpConfig::default());
+    }
+
+    let content = fs::read_to_string(&config_path).map_err(|e| {
+        SynapticError::ConfigReadError(format!("Failed to read {}: {}", config_path.display(), e))
+    })?;
+
+    let config: McpConfig = serde_json::from_str(&content).map_err(|e| {
+        SynapticError::ConfigParseError(format!("Failed to parse {}: {}", config_path.display(), e))
+    })?;
+
+    Ok(config)
+}
+
+/// Write the MCP configuration file with automatic backup
+pub fn write_config_file(config: &McpConfig) -> SynapticResult<()> {
+    let config_path = get_claude_config_path()?;
+
+    // Create backup before writing
+    if config_path.exists() {
+        create_backup()?;
+    }
+
+    // Ensure parent directory exists
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            SynapticError::ConfigWriteError(format!("Failed to create directory: {}", e))
+        })?;
+    }
+
+    // Serialize with pretty formatting
+    let content = serde_json::to_string_pretty(config).map_err(|e| {
+        SynapticError::ConfigWriteError(format!("Failed to serialize config: {}", e))
+    })?;
+
+    fs::write(&config_path, content).map_err(|e| {
+        SynapticError::ConfigWriteError(format!("Failed to write {}: {}", config_path.display(), e))
+    })?;
+
+    Ok(())
+}
+
+// ============================================
+// BACKUP OPERATIONS
+// ============================================
+
+/// Timestamp format used for backup file names
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H-%M-%S";
+
+/// Create a backup of the current config file
+pub fn create_backup() -> SynapticResult<PathBuf> {
+    create_backup_in(&get_claude_config_path()?, &get_backups_dir()?)
+}
+
+/// Create a backup of `config_path` inside `backups_dir`
+pub fn create_backup_in(config_path: &Path, backups_dir: &Path) -> SynapticResult<PathBuf> {
+    // Ensure backups directory exists
+    fs::create_dir_all(backups_dir)
+        .map_err(|e| SynapticError::BackupError(format!("Failed to create backups dir: {}", e)))?;
+
+    // Generate backup filename with timestamp
+    let timestamp = Utc::now().format(BACKUP_TIMESTAMP_FORMAT);
+    let backup_filename = format!("{}.json", timestamp);
+    let backup_path = backups_dir.join(&backup_filename);
+
+    // Copy config to backup
+    if config_path.exists() {
+        fs::copy(config_path, &backup_path)
+            .map_err(|e| SynapticError::BackupError(format!("Failed to create backup: {}", e)))?;
+    }
+
+    Ok(backup_path)
+}
+
+/// List all available backups
+pub fn list_backups() -> SynapticResult<Vec<BackupInfo>> {
+    list_backups_in(&get_backups_dir()?)
+}
+
+/// List the backups stored in `backups_dir`, newest first
+pub fn list_backups_in(backups_dir: &Path) -> SynapticResult<Vec<BackupInfo>> {
+    if !backups_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+
+    let entries = fs::read_dir(backups_dir)
+        .map_err(|e| SynapticError::BackupError(format!("Failed to read backups dir: {}", e)))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().map(|e| e == "json").unwrap_or(false) {
+            let metadata = entry.metadata().ok();
+            let filename = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+
+            // Parse timestamp from filename
+            let id = path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+
+            backups.push(BackupInfo {
+                created_at: backup_created_at(&id, metadata.as_ref()),
+                id,
+                filename,
+                size_bytes: metadata.map(|m| m.len()).unwrap_or(0),
+            });
+        }
+    }
+
+    // Sort by created_at descending (newest first)
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+
+    Ok(backups)
+}
+
+/// Parse the creation time from a backup id, falling back to the file mtime
+fn backup_created_at(id: &str, metadata: Option<&fs::Metadata>) -> DateTime<Utc> {
+    id.get(..19)
+        .and_then(|ts| chrono::NaiveDateTime::parse_from_str(ts, BACKUP_TIMESTAMP_FORMAT).ok())
+        .map(|naive| naive.and_utc())
+        .or_else(|| {
+            metadata
+                .and_then(|m| m.modified().ok())
+                .map(DateTime::<Utc>::from)
+        })
+        .unwrap_or_else(Utc::now)
+}
+
+/// Restore configuration from a backup
+pub fn restore_from_backup(backup_id: &str) -> SynapticResult<()> {
+    let backups_dir = get_backups_dir()?;
+    let backup_path = backups_dir.join(format!("{}.json", backup_id));
+
+    if !backup_path.exists() {
+        return Err(SynapticError::BackupError(format!(
+            "Backup not found: {}",
+            backup_id
+        )));
+    }
+
+    let config_path = get_claude_config_path()?;
+
+    // Create a backup of the current config before restoring
+    if config_path.exists() {
+        create_backup()?;
+    }
+
+    // Copy backup to config path
+    fs::copy(&backup_path, &config_path)
+        .map_err(|e| SynapticError::BackupError(format!("Failed to restore backup: {}", e)))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty_config() {
+        let json = r#"{}"#;
+        let config: McpConfig = serde_json::from_str(json).unwrap();
+        assert!(config.mcp_servers.is_empty());
+    }
+
+    #[test]
+    fn test_parse_config_with_server() {
+        let json = r#"{
+            "mcpServers": {
+                "filesystem": {
+                    "command": "npx",
+                    "args": ["-y", "@anthropic-ai/mcp-server-filesystem", "/tmp"]
+                }
+            }
+        }"#;
+        let config: McpConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.mcp_servers.len(), 1);
+        assert!(config.mcp_servers.contains_key("filesystem"));
+    }
+
+    #[test]
+    fn test_parse_remote_server() {
+        let json = r#"{ "url": "https://mcp.example.com/mcp", "transport": "http" }"#;
+        let server: McpServer = serde_json::from_str(json).unwrap();
+        assert!(server.is_remote());
+        assert!(server.command.is_empty());
+        assert_eq!(
+            serde_json::to_value(&server).unwrap(),
+            serde_json::json!({ "args": [], "env": {}, "enabled": true, "url": "https://mcp.example.com/mcp", "transport": "http" })
+        );
+    }
+
+    #[test]
+    fn test_backup_created_at_parses_id() {
+        let created = backup_created_at("2025-12-19T08-30-00", None);
+        assert_eq!(created.to_rfc3339(), "2025-12-19T08:30:00+00:00");
+    }
+
+    #[test]
+    fn test_restart_policy() {
+        let policy = RestartPolicy {
+            mode: RestartMode::OnFailure,
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 1_500,
+        };
+        assert!(policy.should_restart(false, 0));
+        assert!(!policy.should_restart(true, 0));
+        assert!(!policy.should_restart(false, 3));
+        assert_eq!(policy.backoff_delay(0).as_millis(), 500);
+        assert_eq!(policy.backoff_delay(1).as_millis(), 1_000);
+        assert_eq!(policy.backoff_delay(5).as_millis(), 1_500);
+
+        let server: McpServer =
+            serde_json::from_str(r#"{"command":"npx","restart":{"mode":"on-failure"}}"#).unwrap();
+        assert_eq!(server.restart.mode, RestartMode::OnFailure);
+        assert_eq!(server.restart.max_retries, 5);
+
+        // Default policy stays out of the Claude Desktop config
+        let json = serde_json::to_string(&McpServer::default()).unwrap();
+        assert!(!json.contains("restart"));
+    }
+
+    #[test]
+    fn test_wrapper_builds_the_launched_command() {
+        let server = McpServer {
+            command: "npx".to_string(),
+            args: vec!["-y".to_string(), "pkg".to_string()],
+            ..Default::default()
+        };
+        let wrapped = |command: &str, args: &[&str]| {
+            McpServer {
+                wrapper: Some(CommandWrapper {
+                    command: command.to_string(),
+                    args: args.iter().map(|a| a.to_string()).collect(),
+                }),
+                ..server.clone()
+            }
+            .wrapped()
+        };
+
+        // Prepended interceptor
+        let shim = wrapped("node", &["shim.js", "--"]);
+        assert_eq!(shim.command, "node");
+        assert_eq!(shim.args, ["shim.js", "--", "npx", "-y", "pkg"]);
+        assert!(shim.wrapper.is_none());
+
+        // Placeholders put the original where the wrapper wants it
+        let placed = wrapped("strace", &["-f", "{command}", "{args}", "-o", "trace"]);
+        assert_eq!(placed.args, ["-f", "npx", "-y", "pkg", "-o", "trace"]);
+
+        // Swapped binary, same arguments
+        let swapped = wrapped("bunx", &["{args}"]);
+        assert_eq!(swapped.command, "bunx");
+        assert_eq!(swapped.args, ["-y", "pkg"]);
+
+        let unwrapped = server.wrapped();
+        assert_eq!(
+            (unwrapped.command, unwrapped.args),
+            (server.command, server.args)
+        );
+    }
+
+    #[test]
+    fn test_serialize_config() {
+        let mut config = McpConfig::default();
+        config.mcp_servers.insert(
+            "test".to_string(),
+            McpServer {
+                command: "npx".to_string(),
+                args: vec!["-y".to_string(), "test-package".to_string()],
+                env: HashMap::new(),
+                cwd: None,
+                enabled: true,
+                ..Default::default()
+            },
+        );
+
+        let json = serde_json::to_string_pretty(&config).unwrap();
+        assert!(json.contains("mcpServers"));
+        assert!(json.contains("test"));
+    }
+}
@@ -0,0 +1,552 @@
+//! Conformance tests for MCP servers
+//!
+//! Runs a fixed suite of protocol checks against one server and scores the
+//! outcome, so server authors can use Synaptic as their test harness:
+//!
+//! - `initialize`: the handshake result is well-formed and names a known
+//!   protocol version
+//! - `ping`: answered with an empty result
+//! - `capabilities`: every declared capability's list method works, and
+//!   undeclared ones aren't quietly served
+//! - `tools-schema`: every listed tool has a name and an object `inputSchema`
+//! - `unknown-method`: an unknown method gets a `-32601` error
+//! - `cancellation`: the server keeps answering after one of its requests is
+//!   cancelled, and ignores cancellations of unknown requests
+//!
+//! A stopped server is started for the run and stopped after it. A running
+//! one has already been initialized by its client, so the handshake check is
+//! skipped. The suite's requests are ordinary traffic and show up in the
+//! inspector.
+
+use crate::config::McpServer;
+use crate::error::{SynapticError, SynapticResult};
+use crate::host::Host;
+use crate::probe;
+use crate::process_manager::{spawn_mcp_server, ProcessManager};
+use crate::validation::{validate_jsonrpc, validate_result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Protocol versions a server may answer `initialize` with
+pub const KNOWN_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+/// Time each request of the suite may take
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time a cancelled request is given to be answered anyway
+const CANCELLED_WAIT: Duration = Duration::from_secs(2);
+
+/// Most `tools/list` pages followed
+const MAX_TOOL_PAGES: usize = 50;
+
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Result of one check
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckOutcome {
+    Pass,
+    /// Works, but against a recommendation of the spec
+    Warn,
+    Fail,
+    /// Not applicable to this server; doesn't count towards the score
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceCheck {
+    pub id: String,
+    pub name: String,
+    pub outcome: CheckOutcome,
+    /// What went wrong (or why the check was skipped)
+    pub details: Vec<String>,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConformanceReport {
+    pub server_name: String,
+    /// Whether the server was started for the run (and stopped after it)
+    pub spawned: bool,
+    pub protocol_version: Option<String>,
+    pub capabilities: Value,
+    /// 0-100: passed checks count fully, warnings half, skipped ones not at all
+    pub score: u8,
+    pub checks: Vec<ConformanceCheck>,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+}
+
+/// Score of a set of checks
+pub fn score(checks: &[ConformanceCheck]) -> u8 {
+    let (points, counted) =
+        checks
+            .iter()
+            .fold((0.0, 0usize), |(points, counted), check| {
+                match check.outcome {
+                    CheckOutcome::Pass => (points + 1.0, counted + 1),
+                    CheckOutcome::Warn => (points + 0.5, counted + 1),
+                    CheckOutcome::Fail => (points, counted + 1),
+                    CheckOutcome::Skip => (points, counted),
+                }
+            });
+    if counted == 0 {
+        return 0;
+    }
+    (points * 100.0 / counted as f64).round() as u8
+}
+
+/// Problems one check found
+#[derive(Default)]
+struct Findings {
+    failures: Vec<String>,
+    warnings: Vec<String>,
+    skipped: Option<String>,
+}
+
+impl Findings {
+    fn skip(reason: impl Into<String>) -> Self {
+        Self {
+            skipped: Some(reason.into()),
+            ..Default::default()
+        }
+    }
+
+    fn fail(&mut self, failure: impl Into<String>) {
+        self.failures.push(failure.into());
+    }
+
+    fn warn(&mut self, warning: impl Into<String>) {
+        self.warnings.push(warning.into());
+    }
+
+    fn outcome(&self) -> CheckOutcome {
+        if self.skipped.is_some() {
+            CheckOutcome::Skip
+        } else if !self.failures.is_empty() {
+            CheckOutcome::Fail
+        } else if !self.warnings.is_empty() {
+            CheckOutcome::Warn
+        } else {
+            CheckOutcome::Pass
+        }
+    }
+
+    fn into_details(self) -> Vec<String> {
+        self.skipped
+            .into_iter()
+            .chain(self.failures)
+            .chain(self.warnings)
+            .collect()
+    }
+}
+
+/// Run the suite against a server, starting it temporarily when it isn't running
+pub async fn run_conformance_tests<H: Host>(
+    host: H,
+    server_name: &str,
+    server: McpServer,
+) -> SynapticResult<ConformanceReport> {
+    let Some(pm) = host.process_manager() else {
+        return Err(SynapticError::ProcessError(
+            "Process manager unavailable".to_string(),
+        ));
+    };
+
+    let started_at = Utc::now();
+    let started = Instant::now();
+    let spawned = !pm.is_running(server_name).await;
+    if spawned {
+        spawn_mcp_server(host.clone(), pm, server_name.to_string(), server).await?;
+    }
+
+    let mut suite = Suite {
+        pm,
+        server_name,
+        protocol_version: None,
+        capabilities: Value::Null,
+        checks: Vec::new(),
+    };
+    suite.run(spawned).await;
+    if spawned {
+        probe::stop_temporary(&host, pm, server_name).await;
+    }
+
+    Ok(ConformanceReport {
+        server_name: server_name.to_string(),
+        spawned,
+        protocol_version: suite.protocol_version,
+        capabilities: suite.capabilities,
+        score: score(&suite.checks),
+        checks: suite.checks,
+        started_at,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+struct Suite<'a> {
+    pm: &'a ProcessManager,
+    server_name: &'a str,
+    protocol_version: Option<String>,
+    capabilities: Value,
+    checks: Vec<ConformanceCheck>,
+}
+
+impl Suite<'_> {
+    async fn run(&mut self, spawned: bool) {
+        let started = Instant::now();
+        let findings = self.initialize(spawned).await;
+        self.record("initialize", "Initialize handshake", started, findings);
+
+        let started = Instant::now();
+        let findings = self.ping().await;
+        self.record("ping", "Ping", started, findings);
+
+        let started = Instant::now();
+        let findings = self.capabilities().await;
+        self.record("capabilities", "Capability consistency", started, findings);
+
+        let started = Instant::now();
+        let findings = self.tools_schema().await;
+        self.record("tools-schema", "tools/list schema", started, findings);
+
+        let started = Instant::now();
+        let findings = self.unknown_method().await;
+        self.record("unknown-method", "Unknown method error", started, findings);
+
+        let started = Instant::now();
+        let findings = self.cancellation().await;
+        self.record("cancellation", "Cancellation handling", started, findings);
+    }
+
+    fn record(&mut self, id: &str, name: &str, started: Instant, findings: Findings) {
+        self.checks.push(ConformanceCheck {
+            id: id.to_string(),
+            name: name.to_string(),
+            outcome: findings.outcome(),
+            details: findings.into_details(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+
+    /// Send a request through the inspector; JSON-RPC structure problems of the response are failures
+    async fn send(
+        &self,
+        findings: &mut Findings,
+        method: &str,
+        params: Option<Value>,
+        timeout: Duration,
+    ) -> Option<Value> {
+        let mut request = json!({ "jsonrpc": "2.0", "method": method });
+        if let Some(params) = params {
+            request["params"] = params;
+        }
+        match self
+            .pm
+            .send_request(self.server_name, request, timeout)
+            .await
+        {
+            Ok(response) => {
+                for error in validate_jsonrpc(&response) {
+                    findings.fail(format!("{} response: {}", method, error));
+                }
+                Some(response)
+            }
+            Err(e) => {
+                findings.fail(e.to_string());
+                None
+            }
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> SynapticResult<()> {
+        let notification = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        self.pm
+            .send_to_stdin(self.server_name, notification.to_string())
+            .await
+    }
+
+    async fn initialize(&mut self, spawned: bool) -> Findings {
+        if !spawned {
+            if let Some(negotiated) = self.pm.capabilities.get(self.server_name) {
+                self.protocol_version = negotiated.protocol_version;
+                self.capabilities = negotiated.capabilities;
+            }
+            return Findings::skip("Server was already running and initialized by its client");
+        }
+
+        let mut findings = Findings::default();
+        let Some(response) = self
+            .send(
+                &mut findings,
+                "initialize",
+                Some(probe::initialize_params()),
+                CHECK_TIMEOUT,
+            )
+            .await
+        else {
+            return findings;
+        };
+        let Some(result) = response.get("result") else {
+            findings.fail(format!(
+                "initialize returned an error: {}",
+                response["error"]
+            ));
+            return findings;
+        };
+        for error in validate_result("initialize", result) {
+            findings.fail(error);
+        }
+
+        self.protocol_version = result
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .map(String::from);
+        if let Some(version) = &self.protocol_version {
+            if !KNOWN_PROTOCOL_VERSIONS.contains(&version.as_str()) {
+                findings.warn(format!("Unknown protocol version {}", version));
+            }
+        }
+        self.capabilities = result.get("capabilities").cloned().unwrap_or_default();
+
+        if let Err(e) = self.notify("notifications/initialized", json!({})).await {
+            findings.fail(format!("Couldn't send notifications/initialized: {}", e));
+        }
+        findings
+    }
+
+    async fn ping(&self) -> Findings {
+        let mut findings = Findings::default();
+        if let Some(response) = self.send(&mut findings, "ping", None, CHECK_TIMEOUT).await {
+            match response.get("result") {
+                Some(result) if result.is_object() => {}
+                Some(_) => findings.fail("ping result must be an object"),
+                None => findings.fail(format!("ping returned an error: {}", response["error"])),
+            }
+        }
+        findings
+    }
+
+    /// Whether the server declared a capability
+    fn declares(&self, capability: &str) -> bool {
+        self.capabilities
+            .get(capability)
+            .is_some_and(|c| !c.is_null())
+    }
+
+    async fn capabilities(&self) -> Findings {
+        if !self.capabilities.is_object() {
+            return Findings::skip("The server's capabilities are unknown");
+        }
+
+        let mut findings = Findings::default();
+        let lists = [
+            ("tools", "tools/list"),
+            ("resources", "resources/list"),
+            ("prompts", "prompts/list"),
+        ];
+        for (capability, method) in lists {
+            let mut answer = Findings::default();
+            let response = self.send(&mut answer, method, None, CHECK_TIMEOUT).await;
+            let served = response.as_ref().is_some_and(|r| r.get("result").is_some());
+            match (self.declares(capability), served) {
+                (true, false) => {
+                    let reason = match response.as_ref().and_then(|r| r.get("error")) {
+                        Some(error) => error.to_string(),
+                        None => answer.failures.join("; "),
+                    };
+                    findings.fail(format!(
+                        "Declares {} but {} failed: {}",
+                        capability, method, reason
+                    ));
+                }
+                (false, true) => findings.warn(format!(
+                    "Answers {} without declaring the {} capability",
+                    method, capability
+                )),
+                _ => {}
+            }
+        }
+        findings
+    }
+
+    async fn tools_schema(&self) -> Findings {
+        if self.capabilities.is_object() && !self.declares("tools") {
+            return Findings::skip("Server declares no tools");
+        }
+
+        let mut findings = Findings::default();
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_TOOL_PAGES {
+            let params = cursor.as_ref().map(|cursor| json!({ "cursor": cursor }));
+            let Some(response) = self
+                .send(&mut findings, "tools/list", params, CHECK_TIMEOUT)
+                .await
+            else {
+                return findings;
+            };
+            let Some(page) = response.get("result") else {
+                findings.fail(format!(
+                    "tools/list returned an error: {}",
+                    response["error"]
+                ));
+                return findings;
+            };
+            for error in validate_result("tools/list", page) {
+                findings.fail(error);
+            }
+            if let Some(Value::Array(items)) = page.get("tools") {
+                tools.extend(items.iter().cloned());
+            }
+            cursor = page
+                .get("nextCursor")
+                .and_then(|c| c.as_str())
+                .map(String::from);
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        if tools.is_empty() {
+            findings.warn("Declares tools but lists none");
+        }
+        let mut names = HashSet::new();
+        for tool in &tools {
+            let Some(name) = tool.get("name").and_then(|n| n.as_str()) else {
+                continue;
+            };
+            if !names.insert(name) {
+                findings.fail(format!("Tool {} is listed more than once", name));
+            }
+            if let Some(schema) = tool.get("inputSchema") {
+                if schema.get("type").and_then(|t| t.as_str()) != Some("object") {
+                    findings.fail(format!(
+                        "Tool {}: inputSchema.type must be \"object\"",
+                        name
+                    ));
+                }
+            }
+            if tool.get("description").is_none() {
+                findings.warn(format!("Tool {} has no description", name));
+            }
+        }
+        findings
+    }
+
+    async fn unknown_method(&self) -> Findings {
+        let mut findings = Findings::default();
+        let method = "synaptic/conformance/no-such-method";
+        if let Some(response) = self
+            .send(&mut findings, method, Some(json!({})), CHECK_TIMEOUT)
+            .await
+        {
+            match response.get("error") {
+                None => findings.fail("An unknown method was answered with a result"),
+                Some(error) => {
+                    let code = error.get("code").and_then(|c| c.as_i64());
+                    if code != Some(METHOD_NOT_FOUND) {
+                        findings.warn(format!(
+                            "An unknown method got error code {} instead of {}",
+                            error.get("code").cloned().unwrap_or_default(),
+                            METHOD_NOT_FOUND
+                        ));
+                    }
+                }
+            }
+        }
+        findings
+    }
+
+    async fn cancellation(&self) -> Findings {
+        let mut findings = Findings::default();
+        let method = if self.declares("tools") {
+            "tools/list"
+        } else {
+            "ping"
+        };
+        let id = format!("conformance-{}", uuid::Uuid::new_v4());
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method });
+
+        // Answering anyway is allowed when the request finished first
+        let cancel = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.notify(
+                "notifications/cancelled",
+                json!({ "requestId": id, "reason": "Conformance test" }),
+            )
+            .await
+        };
+        let (_, cancelled) = tokio::join!(
+            self.pm
+                .send_request(self.server_name, request, CANCELLED_WAIT),
+            cancel
+        );
+        if let Err(e) = cancelled {
+            findings.fail(format!("Couldn't send notifications/cancelled: {}", e));
+            return findings;
+        }
+
+        // Unknown requests are ignored too
+        if let Err(e) = self
+            .notify(
+                "notifications/cancelled",
+                json!({ "requestId": "conformance-unknown-request" }),
+            )
+            .await
+        {
+            findings.fail(format!("Couldn't send notifications/cancelled: {}", e));
+            return findings;
+        }
+
+        let mut after = Findings::default();
+        match self.send(&mut after, "ping", None, CHECK_TIMEOUT).await {
+            Some(response) if response.get("result").is_some() => {}
+            _ => findings.fail("Server stopped answering after a cancellation"),
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(outcome: CheckOutcome) -> ConformanceCheck {
+        ConformanceCheck {
+            id: String::new(),
+            name: String::new(),
+            outcome,
+            details: Vec::new(),
+            duration_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_score_weighs_warnings_and_ignores_skips() {
+        let checks = vec![
+            check(CheckOutcome::Pass),
+            check(CheckOutcome::Warn),
+            check(CheckOutcome::Fail),
+            check(CheckOutcome::Pass),
+            check(CheckOutcome::Skip),
+        ];
+        assert_eq!(score(&checks), 63);
+        assert_eq!(score(&[check(CheckOutcome::Skip)]), 0);
+
+        let mut findings = Findings::default();
+        findings.warn("no description");
+        assert_eq!(findings.outcome(), CheckOutcome::Warn);
+        findings.fail("bad schema");
+        assert_eq!(findings.outcome(), CheckOutcome::Fail);
+        assert_eq!(
+            findings.into_details(),
+            vec!["bad schema", "no description"]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Crash reports for server processes
+//!
+//! When a server exits on its own with a failure, the watchdog captures the
+//! exit code or signal, how long it ran and the tail of its stderr, emits it
+//! as a `process-crashed` event and keeps it in the `process_crashes` table.
+
+use crate::database::Database;
+use crate::error::SynapticResult;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::process::ExitStatus;
+
+/// Stderr lines captured with each crash
+pub const CRASH_STDERR_LINES: usize = 20;
+
+/// One unexpected server exit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    /// Row id once persisted
+    pub id: Option<i64>,
+    pub server_name: String,
+    pub pid: u32,
+    pub exit_code: Option<i32>,
+    /// Terminating signal (Unix only)
+    pub signal: Option<i32>,
+    pub uptime_secs: u64,
+    pub stderr_tail: Vec<String>,
+    /// Whether the restart policy is about to bring the server back
+    pub will_restart: bool,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Signal that terminated the process, if any
+pub fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal()
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        None
+    }
+}
+
+/// Persist a crash report, returning its row id
+pub fn record(db: &Database, report: &CrashReport) -> SynapticResult<i64> {
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO process_crashes
+            (timestamp, server_name, pid, exit_code, signal, uptime_secs, stderr_tail, will_restart)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            report.occurred_at.timestamp_millis(),
+            report.server_name,
+            report.pid,
+            report.exit_code,
+            report.signal,
+            report.uptime_secs as i64,
+            serde_json::to_value(&report.stderr_tail)?,
+            report.will_restart,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Query crash history, newest first, optionally for a single server
+pub fn query(
+    db: &Database,
+    server_name: Option<&str>,
+    limit: usize,
+    offset: usize,
+) -> SynapticResult<Vec<CrashReport>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT id, timestamp, server_name, pid, exit_code, signal, uptime_secs, stderr_tail, will_restart
+         FROM process_crashes
+         WHERE (?1 IS NULL OR server_name = ?1)
+         ORDER BY timestamp DESC, id DESC
+         LIMIT ?2 OFFSET ?3",
+    )?;
+
+    let rows = stmt.query_map(params![server_name, limit as i64, offset as i64], |row| {
+        let millis: i64 = row.get(1)?;
+        let uptime: i64 = row.get(6)?;
+        let stderr_tail: serde_json::Value = row.get(7)?;
+
+        Ok(CrashReport {
+            id: Some(row.get(0)?),
+            occurred_at: Utc
+                .timestamp_millis_opt(millis)
+                .single()
+                .unwrap_or_default(),
+            server_name: row.get(2)?,
+            pid: row.get(3)?,
+            exit_code: row.get(4)?,
+            signal: row.get(5)?,
+            uptime_secs: uptime.max(0) as u64,
+            stderr_tail: serde_json::from_value(stderr_tail).unwrap_or_default(),
+            will_restart: row.get(8)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_query() {
+        let db = Database::open_in_memory().unwrap();
+        let report = CrashReport {
+            id: None,
+            server_name: "flaky".to_string(),
+            pid: 42,
+            exit_code: Some(3),
+            signal: None,
+            uptime_secs: 7,
+            stderr_tail: vec!["Error: boom".to_string()],
+            will_restart: true,
+            occurred_at: Utc::now(),
+        };
+
+        let id = record(&db, &report).unwrap();
+        record(
+            &db,
+            &CrashReport {
+                server_name: "other".to_string(),
+                ..report.clone()
+            },
+        )
+        .unwrap();
+
+        let crashes = query(&db, Some("flaky"), 10, 0).unwrap();
+        assert_eq!(crashes.len(), 1);
+        assert_eq!(crashes[0].id, Some(id));
+        assert_eq!(crashes[0].exit_code, Some(3));
+        assert_eq!(crashes[0].stderr_tail, report.stderr_tail);
+        assert!(crashes[0].will_restart);
+
+        assert_eq!(query(&db, None, 10, 0).unwrap().len(), 2);
+    }
+}
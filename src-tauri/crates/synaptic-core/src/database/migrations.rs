@@ -0,0 +1,345 @@
+//! Database schema migrations for Synaptic
+//!
+//! Migrations are applied automatically on app startup.
+//! Each migration is versioned and runs in order.
+//!
+//! Only the core [`Database`](super::Database) applies this list; the
+//! webview's SQL plugin opens the file it migrated. Statements are still
+//! idempotent (`IF NOT EXISTS`) because files migrated by the plugin in
+//! earlier releases have no `user_version` and see every migration again.
+//! A migration SQLite can't make idempotent (`ADD COLUMN`) names a query
+//! telling whether it already ran. Migrations must never alter existing
+//! columns.
+
+/// A single versioned schema migration
+#[derive(Debug, Clone, Copy)]
+pub struct MigrationDef {
+    pub version: i64,
+    pub description: &'static str,
+    pub sql: &'static str,
+    /// Query counting what the migration adds; it's skipped when that isn't zero
+    pub applied_check: Option<&'static str>,
+}
+
+/// All database migrations, in order
+pub const MIGRATIONS: &[MigrationDef] = &[
+    // V1: Initial schema - system_logs table
+    MigrationDef {
+        version: 1,
+        description: "Create system_logs table for persistent inspector logging",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS system_logs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                level TEXT NOT NULL DEFAULT 'INFO',
+                category TEXT NOT NULL DEFAULT 'GENERAL',
+                message TEXT,
+                payload JSON,
+                trace_id TEXT,
+                server_name TEXT,
+                direction TEXT
+            );
+
+            -- Indexes for fast filtering
+            CREATE INDEX IF NOT EXISTS idx_logs_session ON system_logs(session_id);
+            CREATE INDEX IF NOT EXISTS idx_logs_timestamp ON system_logs(timestamp);
+            CREATE INDEX IF NOT EXISTS idx_logs_level ON system_logs(level);
+            CREATE INDEX IF NOT EXISTS idx_logs_category ON system_logs(category);
+            CREATE INDEX IF NOT EXISTS idx_logs_server ON system_logs(server_name);
+        "#,
+        applied_check: None,
+    },
+    // V2: Add sessions table for replay metadata
+    MigrationDef {
+        version: 2,
+        description: "Create sessions table for replay engine",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS sessions (
+                id TEXT PRIMARY KEY,
+                started_at INTEGER NOT NULL,
+                ended_at INTEGER,
+                server_name TEXT,
+                log_count INTEGER DEFAULT 0,
+                description TEXT
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_sessions_started ON sessions(started_at);
+        "#,
+        applied_check: None,
+    },
+    // V3: Config change audit log
+    MigrationDef {
+        version: 3,
+        description: "Create config_audit table for configuration change history",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS config_audit (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                actor TEXT NOT NULL,
+                action TEXT NOT NULL,
+                server_name TEXT,
+                before JSON,
+                after JSON,
+                diff JSON NOT NULL DEFAULT '[]'
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_audit_timestamp ON config_audit(timestamp);
+            CREATE INDEX IF NOT EXISTS idx_audit_server ON config_audit(server_name);
+        "#,
+        applied_check: None,
+    },
+    // V4: Server crash history
+    MigrationDef {
+        version: 4,
+        description: "Create process_crashes table for server crash reports",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS process_crashes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                server_name TEXT NOT NULL,
+                pid INTEGER NOT NULL,
+                exit_code INTEGER,
+                signal INTEGER,
+                uptime_secs INTEGER NOT NULL,
+                stderr_tail JSON NOT NULL DEFAULT '[]',
+                will_restart INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_crashes_timestamp ON process_crashes(timestamp);
+            CREATE INDEX IF NOT EXISTS idx_crashes_server ON process_crashes(server_name);
+        "#,
+        applied_check: None,
+    },
+    // V5: Spawned server processes, for orphan detection after a crash
+    MigrationDef {
+        version: 5,
+        description: "Create spawned_processes table for orphan detection",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS spawned_processes (
+                pid INTEGER PRIMARY KEY,
+                server_name TEXT NOT NULL,
+                command TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                session_id TEXT NOT NULL
+            );
+        "#,
+        applied_check: None,
+    },
+    // V6: Full-text index over logged traffic, kept in sync by triggers
+    MigrationDef {
+        version: 6,
+        description: "Create system_logs_fts full-text index over message payloads",
+        sql: r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS system_logs_fts USING fts5(
+                message,
+                payload,
+                content='system_logs',
+                content_rowid='id'
+            );
+
+            CREATE TRIGGER IF NOT EXISTS system_logs_fts_insert AFTER INSERT ON system_logs BEGIN
+                INSERT INTO system_logs_fts(rowid, message, payload)
+                VALUES (new.id, new.message, new.payload);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS system_logs_fts_delete AFTER DELETE ON system_logs BEGIN
+                INSERT INTO system_logs_fts(system_logs_fts, rowid, message, payload)
+                VALUES ('delete', old.id, old.message, old.payload);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS system_logs_fts_update AFTER UPDATE ON system_logs BEGIN
+                INSERT INTO system_logs_fts(system_logs_fts, rowid, message, payload)
+                VALUES ('delete', old.id, old.message, old.payload);
+                INSERT INTO system_logs_fts(rowid, message, payload)
+                VALUES (new.id, new.message, new.payload);
+            END;
+
+            -- Index rows logged before this migration
+            INSERT INTO system_logs_fts(system_logs_fts) VALUES ('rebuild');
+        "#,
+        applied_check: None,
+    },
+    // V7: Sessions imported from an export are read-only
+    MigrationDef {
+        version: 7,
+        description: "Add imported_from to sessions for read-only imported sessions",
+        sql: r#"
+            ALTER TABLE sessions ADD COLUMN imported_from TEXT;
+        "#,
+        applied_check: Some(
+            "SELECT COUNT(*) FROM pragma_table_info('sessions') WHERE name = 'imported_from'",
+        ),
+    },
+    // V8: Latest initialize handshake of each server
+    MigrationDef {
+        version: 8,
+        description: "Create server_capabilities table for negotiated protocol versions",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS server_capabilities (
+                server_name TEXT PRIMARY KEY,
+                protocol_version TEXT,
+                requested_version TEXT,
+                capabilities JSON NOT NULL DEFAULT 'null',
+                server_info JSON NOT NULL DEFAULT 'null',
+                client_info JSON NOT NULL DEFAULT 'null',
+                instructions TEXT,
+                negotiated_at INTEGER NOT NULL
+            );
+        "#,
+        applied_check: None,
+    },
+    // V9: One row per completed tools/call, for tool analytics
+    MigrationDef {
+        version: 9,
+        description: "Create tool_stats table for tool-call analytics",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS tool_stats (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                server_name TEXT NOT NULL,
+                tool_name TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                is_error INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_tool_stats_server ON tool_stats(server_name, started_at);
+        "#,
+        applied_check: None,
+    },
+    // V10: Saved request library
+    MigrationDef {
+        version: 10,
+        description: "Create request_templates table for saved requests",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS request_templates (
+                name TEXT PRIMARY KEY,
+                server_name TEXT,
+                description TEXT,
+                payload JSON NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+        "#,
+        applied_check: None,
+    },
+    // V11: Bookmarked inspector messages, kept apart from the history buffer
+    MigrationDef {
+        version: 11,
+        description: "Create bookmarks table for pinned and annotated messages",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS bookmarks (
+                message_id TEXT PRIMARY KEY,
+                server_name TEXT NOT NULL,
+                message JSON NOT NULL,
+                annotation TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_bookmarks_server ON bookmarks(server_name);
+        "#,
+        applied_check: None,
+    },
+    // V12: Results of fuzz runs
+    MigrationDef {
+        version: 12,
+        description: "Create fuzz_reports table for server fuzzing results",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS fuzz_reports (
+                id TEXT PRIMARY KEY,
+                server_name TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                report JSON NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_fuzz_reports_server ON fuzz_reports(server_name, started_at);
+        "#,
+        applied_check: None,
+    },
+    // V13: Results of load tests
+    MigrationDef {
+        version: 13,
+        description: "Create load_test_results table for server benchmarks",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS load_test_results (
+                id TEXT PRIMARY KEY,
+                server_name TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                result JSON NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_load_test_results_server ON load_test_results(server_name, started_at);
+        "#,
+        applied_check: None,
+    },
+    // V14: Listings synced from MCP registries, for offline browsing
+    MigrationDef {
+        version: 14,
+        description: "Create registry_cache and registry_sync tables for cached registry listings",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS registry_cache (
+                registry_url TEXT NOT NULL,
+                id TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                entry JSON NOT NULL,
+                PRIMARY KEY (registry_url, id)
+            );
+
+            CREATE TABLE IF NOT EXISTS registry_sync (
+                registry_url TEXT PRIMARY KEY,
+                synced_at INTEGER NOT NULL,
+                etag TEXT
+            );
+        "#,
+        applied_check: None,
+    },
+    // V15: Imported sessions can't be written to
+    MigrationDef {
+        version: 15,
+        description: "Reject writes to imported sessions and their logs",
+        sql: r#"
+            CREATE TRIGGER IF NOT EXISTS system_logs_imported_insert BEFORE INSERT ON system_logs
+            WHEN (SELECT imported_from FROM sessions WHERE id = new.session_id) IS NOT NULL BEGIN
+                SELECT RAISE(ABORT, 'imported sessions are read-only');
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS system_logs_imported_update BEFORE UPDATE ON system_logs
+            WHEN (SELECT imported_from FROM sessions WHERE id = old.session_id) IS NOT NULL BEGIN
+                SELECT RAISE(ABORT, 'imported sessions are read-only');
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS sessions_imported_update BEFORE UPDATE ON sessions
+            WHEN old.imported_from IS NOT NULL BEGIN
+                SELECT RAISE(ABORT, 'imported sessions are read-only');
+            END;
+        "#,
+        applied_check: None,
+    },
+    // V16: Sessions recorded by client capture shims
+    MigrationDef {
+        version: 16,
+        description: "Create capture_sessions table for sessions recorded by synaptic-shim",
+        sql: r#"
+            CREATE TABLE IF NOT EXISTS capture_sessions (
+                session_id TEXT PRIMARY KEY
+            );
+        "#,
+        applied_check: None,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_valid() {
+        assert!(!MIGRATIONS.is_empty());
+
+        // Ensure versions are sequential
+        for (i, m) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(m.version, (i + 1) as i64);
+        }
+    }
+}
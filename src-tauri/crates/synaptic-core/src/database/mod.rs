@@ -0,0 +1,133 @@
+//! Database module for SQLite persistence
+//! Phase 5: Persistent logging with WAL mode
+//!
+//! The webview talks to the same file through `tauri-plugin-sql`; this module
+//! gives the Rust side its own connection for backend-originated writes, and
+//! is the only one migrating the schema.
+
+mod migrations;
+
+pub use migrations::{MigrationDef, MIGRATIONS};
+
+use crate::error::{SynapticError, SynapticResult};
+use rusqlite::Connection;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+/// Database file name shared with the frontend SQL plugin
+pub const DATABASE_FILE: &str = "wear-synaptic.db";
+
+/// Thread-safe SQLite connection
+pub struct Database {
+    conn: Mutex<Connection>,
+}
+
+impl Database {
+    /// Open (or create) the database file and apply pending migrations
+    pub fn open(path: &Path) -> SynapticResult<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let conn = Connection::open(path)?;
+        // Match the frontend's connection settings for concurrent read/write
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        Self::from_connection(conn)
+    }
+
+    /// Open an in-memory database (tests, CLI dry-runs)
+    pub fn open_in_memory() -> SynapticResult<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> SynapticResult<Self> {
+        apply_migrations(&conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Lock the underlying connection
+    pub fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap()
+    }
+}
+
+/// Apply migrations newer than the recorded `user_version`
+fn apply_migrations(conn: &Connection) -> SynapticResult<()> {
+    let current: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        if let Some(check) = migration.applied_check {
+            let applied: i64 = conn.query_row(check, [], |row| row.get(0))?;
+            if applied > 0 {
+                conn.pragma_update(None, "user_version", migration.version)?;
+                continue;
+            }
+        }
+        conn.execute_batch(migration.sql).map_err(|e| {
+            SynapticError::DatabaseError(format!(
+                "Migration {} ({}) failed: {}",
+                migration.version, migration.description, e
+            ))
+        })?;
+        conn.pragma_update(None, "user_version", migration.version)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_in_memory_applies_migrations() {
+        let db = Database::open_in_memory().unwrap();
+        let conn = db.conn();
+
+        let version: i64 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.last().unwrap().version);
+
+        // Re-applying is a no-op
+        apply_migrations(&conn).unwrap();
+    }
+
+    #[test]
+    fn test_a_file_the_sql_plugin_migrated_opens_and_reopens() {
+        let dir = std::env::temp_dir().join(format!("synaptic-db-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(DATABASE_FILE);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Earlier releases let the webview's plugin run every migration,
+        // tracked in its own table and leaving `user_version` at 0
+        {
+            let conn = Connection::open(&path).unwrap();
+            for migration in MIGRATIONS {
+                conn.execute_batch(migration.sql).unwrap();
+            }
+        }
+
+        for _ in 0..2 {
+            let db = Database::open(&path).unwrap();
+            let version: i64 = db
+                .conn()
+                .pragma_query_value(None, "user_version", |row| row.get(0))
+                .unwrap();
+            assert_eq!(version, MIGRATIONS.last().unwrap().version);
+        }
+
+        // The webview's plain connection sees the whole schema
+        let webview = Connection::open(&path).unwrap();
+        webview
+            .query_row("SELECT COUNT(imported_from) FROM sessions", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .unwrap();
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,344 @@
+//! Docker-backed servers
+//!
+//! A server whose command is `docker run ...` is really a container; killing
+//! the docker CLI leaves it running. Synaptic injects `--cidfile` to learn the
+//! container ID, stops and removes the container itself on shutdown, and asks
+//! the daemon for the container's state and logs.
+
+use crate::error::{SynapticError, SynapticResult};
+use crate::host::EventEmitter;
+use crate::process_manager::executable_name;
+use crate::resolve;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+
+/// How long to wait for docker to write the container ID file
+pub const CIDFILE_WAIT: Duration = Duration::from_secs(30);
+
+/// Extra time a `docker stop` gets on top of the container's own grace period
+const DOCKER_CLI_SLACK: Duration = Duration::from_secs(5);
+
+/// How long a `docker inspect` may take before the state is reported unknown
+const INSPECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long an inspected container state is reused
+///
+/// Status polling asks for every docker server at once; without the cache a
+/// slow daemon would get one `docker inspect` per server per poll.
+const INSPECT_CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// When a container was inspected and the state it reported
+type CachedState = (Instant, Option<String>);
+
+/// Recently inspected container states, keyed by container ID
+static INSPECT_CACHE: LazyLock<Mutex<HashMap<String, CachedState>>> =
+    LazyLock::new(Default::default);
+
+/// Whether a server command runs through the docker CLI
+pub fn is_docker_command(command: &str) -> bool {
+    executable_name(command) == "docker"
+}
+
+/// A container started by a `docker run` server
+#[derive(Debug, Clone)]
+pub struct Container {
+    /// Resolved docker CLI
+    pub docker: PathBuf,
+    /// File docker writes the container ID to
+    ///
+    /// Deleted once the container is removed, even when the user supplied it:
+    /// `docker run` refuses to start while the file exists, so a leftover one
+    /// would break the next start.
+    pub cidfile: PathBuf,
+}
+
+impl Container {
+    /// Prepare `docker run` args so the container can be tracked
+    ///
+    /// Returns `None` for other docker subcommands (e.g. `docker exec`).
+    pub fn prepare(docker: PathBuf, args: &[String]) -> Option<(Self, Vec<String>)> {
+        let run = run_index(args)?;
+
+        // Respect a user-supplied --cidfile
+        if let Some(path) = cidfile_arg(args) {
+            let container = Self {
+                docker,
+                cidfile: PathBuf::from(path),
+            };
+            return Some((container, args.to_vec()));
+        }
+
+        let cidfile = std::env::temp_dir().join(format!("synaptic-{}.cid", uuid::Uuid::new_v4()));
+        let mut prepared = args.to_vec();
+        prepared.splice(
+            run + 1..run + 1,
+            [
+                "--cidfile".to_string(),
+                cidfile.to_string_lossy().to_string(),
+            ],
+        );
+
+        let container = Self { docker, cidfile };
+        Some((container, prepared))
+    }
+
+    /// Container ID, once docker has written it
+    pub fn id(&self) -> Option<String> {
+        std::fs::read_to_string(&self.cidfile)
+            .ok()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+    }
+
+    /// Wait up to `timeout` for the container ID
+    pub async fn wait_for_id(&self, timeout: Duration) -> Option<String> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if let Some(id) = self.id() {
+                return Some(id);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return None;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// `docker stop` with the given grace period; true if docker reported success
+    pub async fn stop(&self, grace: Duration) -> bool {
+        let Some(id) = self.id() else { return false };
+        let seconds = grace.as_secs().max(1).to_string();
+        let stop = Command::new(&self.docker)
+            .args(["stop", "-t", &seconds, &id])
+            .output();
+
+        matches!(
+            tokio::time::timeout(grace + DOCKER_CLI_SLACK, stop).await,
+            Ok(Ok(output)) if output.status.success()
+        )
+    }
+
+    /// Force-remove the container and forget the cidfile
+    pub async fn remove(&self) {
+        if let Some(id) = self.id() {
+            let remove = Command::new(&self.docker).args(["rm", "-f", &id]).output();
+            if tokio::time::timeout(DOCKER_CLI_SLACK, remove)
+                .await
+                .is_err()
+            {
+                eprintln!("Timed out removing container {}", id);
+            }
+        }
+        let _ = std::fs::remove_file(&self.cidfile);
+    }
+}
+
+/// Global docker options that take a separate value
+const GLOBAL_VALUE_OPTIONS: &[&str] = &[
+    "-c",
+    "--context",
+    "--config",
+    "-H",
+    "--host",
+    "-l",
+    "--log-level",
+    "--tlscacert",
+    "--tlscert",
+    "--tlskey",
+];
+
+/// Position of the `run` subcommand (`docker run` or `docker container run`)
+fn run_index(args: &[String]) -> Option<usize> {
+    let mut i = 0;
+    while let Some(arg) = args.get(i) {
+        if GLOBAL_VALUE_OPTIONS.contains(&arg.as_str()) {
+            i += 2;
+        } else if arg.starts_with('-') {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+
+    match args.get(i)?.as_str() {
+        "run" => Some(i),
+        "container" if args.get(i + 1)? == "run" => Some(i + 1),
+        _ => None,
+    }
+}
+
+/// Value of `--cidfile` if the args already contain one
+fn cidfile_arg(args: &[String]) -> Option<&str> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--cidfile" {
+            args.get(i + 1).map(String::as_str)
+        } else {
+            arg.strip_prefix("--cidfile=")
+        }
+    })
+}
+
+/// Container state as reported by the daemon (`running`, `exited`, ...)
+///
+/// Answers from a short-lived cache; `None` if docker fails or does not
+/// answer within [`INSPECT_TIMEOUT`].
+pub async fn inspect_state(container_id: &str) -> Option<String> {
+    if let Some((at, state)) = INSPECT_CACHE.lock().unwrap().get(container_id) {
+        if at.elapsed() < INSPECT_CACHE_TTL {
+            return state.clone();
+        }
+    }
+
+    let state = query_state(container_id).await;
+    let mut cache = INSPECT_CACHE.lock().unwrap();
+    cache.retain(|_, (at, _)| at.elapsed() < INSPECT_CACHE_TTL);
+    cache.insert(container_id.to_string(), (Instant::now(), state.clone()));
+    state
+}
+
+/// Run `docker inspect` for a container's state
+async fn query_state(container_id: &str) -> Option<String> {
+    let docker = resolve::resolve_command("docker").ok()?;
+    let inspect = Command::new(docker)
+        .args(["inspect", "-f", "{{.State.Status}}", container_id])
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(INSPECT_TIMEOUT, inspect)
+        .await
+        .ok()?
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Last `lines` lines of a container's logs (stdout lines, then stderr lines)
+pub async fn logs(container_id: &str, lines: usize) -> SynapticResult<Vec<String>> {
+    let docker = resolve::resolve_command("docker")?;
+    let output = Command::new(docker)
+        .args(["logs", "--tail", &lines.to_string(), container_id])
+        .output()
+        .await
+        .map_err(|e| SynapticError::ProcessError(format!("Failed to run docker logs: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(SynapticError::ProcessError(format!(
+            "docker logs failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let mut all: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+        .map(String::from)
+        .collect();
+    let start = all.len().saturating_sub(lines);
+    Ok(all.split_off(start))
+}
+
+/// A line of container output, emitted as `container-log`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerLogLine {
+    pub server_name: String,
+    pub container_id: String,
+    pub line: String,
+}
+
+/// Stream a container's logs as `container-log` events until it stops
+///
+/// Runs `docker logs --follow`, which exits on its own once the container
+/// does, so this returns when the container is gone.
+pub async fn follow_logs<E: EventEmitter>(
+    emitter: &E,
+    server_name: &str,
+    container_id: &str,
+) -> SynapticResult<()> {
+    let docker = resolve::resolve_command("docker")?;
+    let mut child = Command::new(docker)
+        .args(["logs", "--follow", "--tail", "0", container_id])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| SynapticError::ProcessError(format!("Failed to run docker logs: {}", e)))?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .map(|out| read_lines(out, emitter, server_name, container_id));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|err| read_lines(err, emitter, server_name, container_id));
+    if let (Some(stdout), Some(stderr)) = (stdout, stderr) {
+        tokio::join!(stdout, stderr);
+    }
+    let _ = child.wait().await;
+    Ok(())
+}
+
+/// Emit each line of one of the `docker logs` output streams
+async fn read_lines<R: AsyncRead + Unpin, E: EventEmitter>(
+    stream: R,
+    emitter: &E,
+    server_name: &str,
+    container_id: &str,
+) {
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        emitter.emit(
+            "container-log",
+            ContainerLogLine {
+                server_name: server_name.to_string(),
+                container_id: container_id.to_string(),
+                line,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_injects_cidfile_after_run() {
+        let (container, prepared) = Container::prepare(
+            PathBuf::from("docker"),
+            &args(&["--context", "x", "run", "-i", "--rm", "mcp/github"]),
+        )
+        .unwrap();
+
+        assert_eq!(prepared[..4], args(&["--context", "x", "run", "--cidfile"]));
+        assert_eq!(prepared[4], container.cidfile.to_string_lossy());
+        assert_eq!(prepared[5..], args(&["-i", "--rm", "mcp/github"]));
+    }
+
+    #[test]
+    fn test_keeps_user_cidfile_and_skips_other_subcommands() {
+        let (container, prepared) = Container::prepare(
+            PathBuf::from("docker"),
+            &args(&["container", "run", "--cidfile=/tmp/x.cid", "-i", "img"]),
+        )
+        .unwrap();
+        assert_eq!(container.cidfile, PathBuf::from("/tmp/x.cid"));
+        assert_eq!(prepared.len(), 5);
+
+        assert!(Container::prepare(PathBuf::from("docker"), &args(&["exec", "-i", "c"])).is_none());
+    }
+}
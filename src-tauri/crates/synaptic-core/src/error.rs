@@ -1,5 +1,6 @@
 //! Custom error types for Synaptic backend operations
 
+use crate::preflight::PreflightReport;
 use serde::Serialize;
 use thiserror::Error;
 
@@ -41,6 +42,24 @@ pub enum SynapticError {
 
     #[error("Process error: {0}")]
     ProcessError(String),
+
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+
+    #[error("Read-only mode is enabled: {0}")]
+    ReadOnlyMode(String),
+
+    #[error("Approval required: {0}")]
+    ApprovalRequired(String),
+
+    #[error("Startup timed out: {0}")]
+    StartupTimeout(String),
+
+    #[error("Preflight failed: {}", .0.summary())]
+    PreflightFailed(Box<PreflightReport>),
+
+    #[error("Authorization failed: {0}")]
+    AuthError(String),
 }
 
 /// Serializable error response for frontend
@@ -48,6 +67,9 @@ pub enum SynapticError {
 pub struct ErrorResponse {
     pub code: String,
     pub message: String,
+    /// Structured context for some errors (the report of `PREFLIGHT_FAILED`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
 }
 
 impl From<SynapticError> for ErrorResponse {
@@ -65,11 +87,22 @@ impl From<SynapticError> for ErrorResponse {
             SynapticError::RuntimeNotFound(_) => "RUNTIME_NOT_FOUND",
             SynapticError::IoError(_) => "IO_ERROR",
             SynapticError::ProcessError(_) => "PROCESS_ERROR",
+            SynapticError::DatabaseError(_) => "DATABASE_ERROR",
+            SynapticError::ReadOnlyMode(_) => "READ_ONLY_MODE",
+            SynapticError::ApprovalRequired(_) => "APPROVAL_REQUIRED",
+            SynapticError::StartupTimeout(_) => "STARTUP_TIMEOUT",
+            SynapticError::PreflightFailed(_) => "PREFLIGHT_FAILED",
+            SynapticError::AuthError(_) => "AUTH_ERROR",
+        };
+        let details = match &err {
+            SynapticError::PreflightFailed(report) => serde_json::to_value(report).ok(),
+            _ => None,
         };
 
         ErrorResponse {
             code: code.to_string(),
             message: err.to_string(),
+            details,
         }
     }
 }
@@ -99,6 +132,12 @@ impl SynapticError {
             Self::RuntimeNotFound(s) => Self::RuntimeNotFound(s.clone()),
             Self::IoError(s) => Self::IoError(s.clone()),
             Self::ProcessError(s) => Self::ProcessError(s.clone()),
+            Self::DatabaseError(s) => Self::DatabaseError(s.clone()),
+            Self::ReadOnlyMode(s) => Self::ReadOnlyMode(s.clone()),
+            Self::ApprovalRequired(s) => Self::ApprovalRequired(s.clone()),
+            Self::StartupTimeout(s) => Self::StartupTimeout(s.clone()),
+            Self::PreflightFailed(r) => Self::PreflightFailed(r.clone()),
+            Self::AuthError(s) => Self::AuthError(s.clone()),
         }
     }
 }
@@ -115,5 +154,11 @@ impl From<serde_json::Error> for SynapticError {
     }
 }
 
+impl From<rusqlite::Error> for SynapticError {
+    fn from(err: rusqlite::Error) -> Self {
+        SynapticError::DatabaseError(err.to_string())
+    }
+}
+
 /// Result type alias for Synaptic operations
 pub type SynapticResult<T> = Result<T, SynapticError>;
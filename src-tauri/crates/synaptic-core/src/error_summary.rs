@@ -0,0 +1,209 @@
+//! Logged errors grouped into a triage list
+//!
+//! JSON-RPC error responses and stderr lines that look like errors are
+//! reduced to a fingerprint (error code plus message, or the stderr line,
+//! with anything containing a digit blanked out) so the same failure with
+//! different ids, paths or timings lands in one group.
+
+use crate::database::Database;
+use crate::error::SynapticResult;
+use crate::system_logs::{entry_from_row, LogEntry, ENTRY_COLUMNS, LIVE_SESSIONS};
+use crate::tool_stats::TimeRange;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Words marking a stderr line as an error
+const STDERR_ERROR_WORDS: &[&str] = &["error", "fatal", "panic", "exception", "traceback"];
+
+/// Longest fingerprint kept, in characters
+const MAX_FINGERPRINT_CHARS: usize = 200;
+
+/// Where a group of errors was seen
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorSource {
+    /// JSON-RPC error responses
+    Response,
+    /// Error lines on stderr
+    Stderr,
+}
+
+/// Errors sharing a fingerprint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorGroup {
+    pub fingerprint: String,
+    pub source: ErrorSource,
+    pub server_name: Option<String>,
+    /// JSON-RPC error code (responses only)
+    pub code: Option<i64>,
+    /// Message of the most recent occurrence
+    pub message: String,
+    pub count: u64,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Most recent logged payload
+    pub sample: Option<Value>,
+}
+
+/// Blank out tokens containing digits (ids, ports, paths with numbers, durations)
+pub fn normalize(text: &str) -> String {
+    let normalized = text
+        .split_whitespace()
+        .map(|token| {
+            if token.chars().any(|c| c.is_ascii_digit()) {
+                "#"
+            } else {
+                token
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    normalized.chars().take(MAX_FINGERPRINT_CHARS).collect()
+}
+
+/// Source, code, message and fingerprint of a logged error, if it is one
+fn classify(entry: &LogEntry) -> Option<(ErrorSource, Option<i64>, String, String)> {
+    let payload = entry.payload.as_ref()?;
+    if entry.direction.as_deref() == Some("STDERR") {
+        let line = match payload {
+            Value::String(line) => line.trim().to_string(),
+            other => other.to_string(),
+        };
+        let lower = line.to_lowercase();
+        if !STDERR_ERROR_WORDS.iter().any(|word| lower.contains(word)) {
+            return None;
+        }
+        let fingerprint = normalize(&line);
+        return Some((ErrorSource::Stderr, None, line, fingerprint));
+    }
+
+    let error = payload.get("error")?;
+    let code = error.get("code").and_then(|c| c.as_i64());
+    let message = error
+        .get("message")
+        .and_then(|m| m.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let fingerprint = match code {
+        Some(code) => format!("{} {}", code, normalize(&message)),
+        None => normalize(&message),
+    };
+    Some((ErrorSource::Response, code, message, fingerprint))
+}
+
+/// Logged errors grouped by fingerprint, most frequent first
+pub fn summarize(
+    db: &Database,
+    server_name: Option<&str>,
+    range: &TimeRange,
+) -> SynapticResult<Vec<ErrorGroup>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ENTRY_COLUMNS} FROM system_logs l
+         WHERE (?1 IS NULL OR l.server_name = ?1)
+           AND (?2 IS NULL OR l.timestamp >= ?2)
+           AND (?3 IS NULL OR l.timestamp <= ?3)
+           AND {LIVE_SESSIONS}
+           AND (l.direction = 'STDERR'
+                OR (json_valid(l.payload) AND json_type(l.payload, '$.error') IS NOT NULL))
+         ORDER BY l.timestamp, l.id"
+    ))?;
+    let rows = stmt.query_map(
+        params![
+            server_name,
+            range.from.map(|t| t.timestamp_millis()),
+            range.to.map(|t| t.timestamp_millis()),
+        ],
+        entry_from_row,
+    )?;
+
+    let mut groups: HashMap<(Option<String>, ErrorSource, String), ErrorGroup> = HashMap::new();
+    for entry in rows {
+        let entry = entry?;
+        let Some((source, code, message, fingerprint)) = classify(&entry) else {
+            continue;
+        };
+        let key = (entry.server_name.clone(), source, fingerprint.clone());
+        let group = groups.entry(key).or_insert_with(|| ErrorGroup {
+            fingerprint,
+            source,
+            server_name: entry.server_name.clone(),
+            code,
+            message: String::new(),
+            count: 0,
+            first_seen: entry.timestamp,
+            last_seen: entry.timestamp,
+            sample: None,
+        });
+        group.count += 1;
+        group.message = message;
+        group.last_seen = entry.timestamp;
+        group.sample = entry.payload;
+    }
+
+    let mut groups: Vec<ErrorGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| b.last_seen.cmp(&a.last_seen))
+    });
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_manager::McpTrafficEvent;
+    use crate::system_logs;
+
+    #[test]
+    fn test_errors_are_grouped_by_fingerprint() {
+        let db = Database::open_in_memory().unwrap();
+        let event = |direction: &str, content: &str| McpTrafficEvent {
+            server_id: "fs".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            direction: direction.to_string(),
+            content: content.to_string(),
+            message_id: uuid::Uuid::new_v4().to_string(),
+            level: None,
+        };
+        system_logs::insert_traffic(
+            &db,
+            "s1",
+            &[
+                event(
+                    "INCOMING",
+                    r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"No such file /tmp/a1.txt"}}"#,
+                ),
+                event(
+                    "INCOMING",
+                    r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32602,"message":"No such file /tmp/b2.txt"}}"#,
+                ),
+                event(
+                    "INCOMING",
+                    r#"{"jsonrpc":"2.0","id":3,"error":{"code":-32601,"message":"Method not found"}}"#,
+                ),
+                event("INCOMING", r#"{"jsonrpc":"2.0","id":4,"result":{}}"#),
+                event("STDERR", "Error: connection refused after 3000ms"),
+                event("STDERR", "Listening on stdio"),
+            ],
+        )
+        .unwrap();
+
+        let groups = summarize(&db, Some("fs"), &TimeRange::default()).unwrap();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].fingerprint, "-32602 No such file #");
+        assert_eq!(groups[0].count, 2);
+        assert_eq!(groups[0].message, "No such file /tmp/b2.txt");
+        assert_eq!(groups[0].sample.as_ref().unwrap()["id"], 2);
+        assert!(groups.iter().any(|g| g.source == ErrorSource::Stderr
+            && g.fingerprint == "Error: connection refused after #"));
+        assert!(summarize(&db, Some("git"), &TimeRange::default())
+            .unwrap()
+            .is_empty());
+    }
+}
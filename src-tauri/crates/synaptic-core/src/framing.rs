@@ -0,0 +1,497 @@
+//! Message framing on a server's stdio
+//!
+//! MCP's stdio transport is newline-delimited JSON, but some servers (often
+//! ones built on LSP tooling) use `Content-Length` headers instead. The
+//! decoder detects the framing from the server's first output unless the
+//! server config pins it, and the writer follows whatever was detected.
+
+use bytes::{Buf, BytesMut};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::io;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use tokio_util::codec::Decoder;
+
+const CONTENT_LENGTH: &str = "content-length";
+
+/// Header blocks longer than this are treated as garbage
+const MAX_HEADER_BYTES: usize = 8 * 1024;
+
+/// Appended (with the dropped byte count) to messages cut at the size limit
+pub const TRUNCATION_MARKER: &str = "…[truncated ";
+
+/// `text` cut down to `max_bytes` with the truncation marker, as the decoder cuts
+/// oversized messages (0 means unlimited)
+pub fn truncate(text: &str, max_bytes: usize) -> Cow<'_, str> {
+    if max_bytes == 0 || text.len() <= max_bytes {
+        return Cow::Borrowed(text);
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    Cow::Owned(format!(
+        "{}{}{} bytes]",
+        &text[..end],
+        TRUNCATION_MARKER,
+        text.len() - end
+    ))
+}
+
+/// Size a message had before it was cut, if it was
+pub fn truncated_size(message: &str) -> Option<usize> {
+    let (kept, rest) = message.rsplit_once(TRUNCATION_MARKER)?;
+    let skipped: usize = rest.strip_suffix(" bytes]")?.parse().ok()?;
+    Some(kept.len() + skipped)
+}
+
+/// How messages are delimited on stdin/stdout
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Framing {
+    /// Newline-delimited until the server's output shows headers
+    #[default]
+    Auto,
+    /// One JSON message per line (the MCP stdio transport)
+    Newline,
+    /// `Content-Length: N\r\n\r\n` followed by N bytes of JSON
+    ContentLength,
+}
+
+impl Framing {
+    pub fn is_auto(&self) -> bool {
+        *self == Framing::Auto
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Framing::Auto => 0,
+            Framing::Newline => 1,
+            Framing::ContentLength => 2,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Framing::Newline,
+            2 => Framing::ContentLength,
+            _ => Framing::Auto,
+        }
+    }
+}
+
+/// Framing of one process, shared between its stdout reader and stdin writer
+#[derive(Debug, Clone)]
+pub struct SharedFraming(Arc<AtomicU8>);
+
+impl SharedFraming {
+    pub fn new(framing: Framing) -> Self {
+        Self(Arc::new(AtomicU8::new(framing.to_u8())))
+    }
+
+    pub fn get(&self) -> Framing {
+        Framing::from_u8(self.0.load(Ordering::SeqCst))
+    }
+
+    fn set(&self, framing: Framing) {
+        self.0.store(framing.to_u8(), Ordering::SeqCst);
+    }
+
+    /// Frame one outgoing message
+    pub fn encode(&self, message: &str) -> Vec<u8> {
+        match self.get() {
+            Framing::ContentLength => {
+                let mut out = format!("Content-Length: {}\r\n\r\n", message.len()).into_bytes();
+                out.extend_from_slice(message.as_bytes());
+                out
+            }
+            Framing::Auto | Framing::Newline => {
+                let mut out = Vec::with_capacity(message.len() + 1);
+                out.extend_from_slice(message.as_bytes());
+                out.push(b'\n');
+                out
+            }
+        }
+    }
+}
+
+/// Decoder yielding one message per frame, detecting the framing if needed
+///
+/// Messages longer than `max_bytes` are cut down to that size with a
+/// truncation marker appended, and invalid UTF-8 is replaced rather than
+/// failing the stream.
+#[derive(Debug)]
+pub struct MessageDecoder {
+    framing: SharedFraming,
+    max_bytes: usize,
+    /// Oversized message currently being skipped
+    truncating: Option<Truncation>,
+}
+
+/// Head of an oversized message and how much of it was dropped
+#[derive(Debug)]
+struct Truncation {
+    kept: Vec<u8>,
+    skipped: usize,
+    /// Body bytes still to skip (header framing); `None` skips to the next newline
+    body_remaining: Option<usize>,
+}
+
+impl Truncation {
+    fn finish(self) -> String {
+        format!(
+            "{}{}{} bytes]",
+            String::from_utf8_lossy(&self.kept),
+            TRUNCATION_MARKER,
+            self.skipped
+        )
+    }
+}
+
+impl MessageDecoder {
+    /// Decoder cutting messages down to `max_bytes` (0 means unlimited)
+    pub fn new(framing: SharedFraming, max_bytes: usize) -> Self {
+        Self {
+            framing,
+            max_bytes: if max_bytes == 0 {
+                usize::MAX
+            } else {
+                max_bytes
+            },
+            truncating: None,
+        }
+    }
+
+    /// Decide the framing from the first non-blank output
+    fn detect(&self, buf: &BytesMut) -> Option<Framing> {
+        let start = buf.iter().position(|b| !b.is_ascii_whitespace())?;
+        let head = &buf[start..];
+        let prefix = CONTENT_LENGTH.len().min(head.len());
+
+        if head[..prefix].eq_ignore_ascii_case(&CONTENT_LENGTH.as_bytes()[..prefix]) {
+            // Could still turn out to be a header: wait for more bytes
+            (head.len() >= CONTENT_LENGTH.len()).then_some(Framing::ContentLength)
+        } else {
+            Some(Framing::Newline)
+        }
+    }
+
+    /// Keep skipping an oversized message; yields it once its end is reached
+    fn continue_truncation(&mut self, buf: &mut BytesMut) -> Option<String> {
+        let truncation = self.truncating.as_mut()?;
+
+        let done = match truncation.body_remaining {
+            Some(remaining) => {
+                let take = remaining.min(buf.len());
+                buf.advance(take);
+                truncation.skipped += take;
+                truncation.body_remaining = Some(remaining - take);
+                remaining == take
+            }
+            None => match buf.iter().position(|b| *b == b'\n') {
+                Some(end) => {
+                    truncation.skipped += trim_line_end(&buf[..=end]).len();
+                    buf.advance(end + 1);
+                    true
+                }
+                None => {
+                    truncation.skipped += buf.len();
+                    buf.clear();
+                    false
+                }
+            },
+        };
+
+        if done {
+            self.truncating.take().map(Truncation::finish)
+        } else {
+            None
+        }
+    }
+
+    fn decode_line(&mut self, buf: &mut BytesMut) -> Option<String> {
+        loop {
+            let Some(end) = buf.iter().position(|b| *b == b'\n') else {
+                // No newline yet: start dropping once the line is too long
+                if buf.len() > self.max_bytes {
+                    let kept = buf.split_to(self.max_bytes).to_vec();
+                    self.truncating = Some(Truncation {
+                        kept,
+                        skipped: 0,
+                        body_remaining: None,
+                    });
+                    return self.continue_truncation(buf);
+                }
+                return None;
+            };
+
+            let line = buf.split_to(end + 1);
+            let line = trim_line_end(&line);
+            if line.iter().all(|b| b.is_ascii_whitespace()) {
+                continue;
+            }
+            if line.len() > self.max_bytes {
+                return Some(
+                    Truncation {
+                        kept: line[..self.max_bytes].to_vec(),
+                        skipped: line.len() - self.max_bytes,
+                        body_remaining: None,
+                    }
+                    .finish(),
+                );
+            }
+            return Some(lossy(line));
+        }
+    }
+
+    fn decode_frame(&mut self, buf: &mut BytesMut) -> io::Result<Option<String>> {
+        // Tolerate blank lines between frames
+        let start = buf
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(buf.len());
+        buf.advance(start);
+
+        let Some((header_len, separator_len)) = find_header_end(buf) else {
+            if buf.len() > MAX_HEADER_BYTES {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Frame header exceeds the size limit",
+                ));
+            }
+            return Ok(None);
+        };
+
+        let headers = String::from_utf8_lossy(&buf[..header_len]);
+        let length = headers
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(CONTENT_LENGTH))
+            .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Missing Content-Length in frame header: {}", headers.trim()),
+                )
+            })?;
+        let body_start = header_len + separator_len;
+
+        // Oversized body: keep the head and skip the rest as it arrives
+        if length > self.max_bytes {
+            if buf.len() < body_start + self.max_bytes {
+                return Ok(None);
+            }
+            buf.advance(body_start);
+            let kept = buf.split_to(self.max_bytes).to_vec();
+            self.truncating = Some(Truncation {
+                kept,
+                skipped: 0,
+                body_remaining: Some(length - self.max_bytes),
+            });
+            return Ok(self.continue_truncation(buf));
+        }
+
+        if buf.len() < body_start + length {
+            buf.reserve(body_start + length - buf.len());
+            return Ok(None);
+        }
+
+        buf.advance(body_start);
+        let body = buf.split_to(length);
+        Ok(Some(lossy(&body)))
+    }
+}
+
+impl Decoder for MessageDecoder {
+    type Item = String;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<String>> {
+        if self.truncating.is_some() {
+            match self.continue_truncation(buf) {
+                Some(message) => return Ok(Some(message)),
+                None if self.truncating.is_some() => return Ok(None),
+                None => {}
+            }
+        }
+
+        let mut framing = self.framing.get();
+        if framing == Framing::Auto {
+            match self.detect(buf) {
+                Some(detected) => {
+                    self.framing.set(detected);
+                    framing = detected;
+                }
+                None => return Ok(None),
+            }
+        }
+
+        match framing {
+            Framing::ContentLength => self.decode_frame(buf),
+            Framing::Auto | Framing::Newline => Ok(self.decode_line(buf)),
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> io::Result<Option<String>> {
+        if let Some(message) = self.decode(buf)? {
+            return Ok(Some(message));
+        }
+
+        // The stream ended inside an oversized message
+        if let Some(truncation) = self.truncating.take() {
+            buf.clear();
+            return Ok(Some(truncation.finish()));
+        }
+
+        // A final line without a trailing newline still counts
+        if self.framing.get() != Framing::ContentLength
+            && buf.iter().any(|b| !b.is_ascii_whitespace())
+        {
+            let rest = buf.split_to(buf.len());
+            return Ok(Some(lossy(trim_line_end(&rest))));
+        }
+        buf.clear();
+        Ok(None)
+    }
+}
+
+/// Length of the header block and of the blank-line separator after it
+fn find_header_end(buf: &[u8]) -> Option<(usize, usize)> {
+    let crlf = buf
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|i| (i, 4));
+    let lf = buf.windows(2).position(|w| w == b"\n\n").map(|i| (i, 2));
+    match (crlf, lf) {
+        (Some(a), Some(b)) => Some(if a.0 <= b.0 { a } else { b }),
+        (a, b) => a.or(b),
+    }
+}
+
+fn trim_line_end(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Decode as UTF-8, replacing invalid sequences instead of failing
+fn lossy(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_all(framing: &SharedFraming, input: &[u8]) -> Vec<String> {
+        let mut decoder = MessageDecoder::new(framing.clone(), 0);
+        let mut buf = BytesMut::new();
+        let mut out = Vec::new();
+
+        // Feed byte by byte to exercise partial frames
+        for byte in input {
+            buf.extend_from_slice(&[*byte]);
+            while let Some(message) = decoder.decode(&mut buf).unwrap() {
+                out.push(message);
+            }
+        }
+        while let Some(message) = decoder.decode_eof(&mut buf).unwrap() {
+            out.push(message);
+        }
+        out
+    }
+
+    #[test]
+    fn test_detects_newline_framing() {
+        let framing = SharedFraming::new(Framing::Auto);
+        let messages = decode_all(&framing, b"{\"id\":1}\r\n\n{\"id\":2}");
+
+        assert_eq!(messages, vec!["{\"id\":1}", "{\"id\":2}"]);
+        assert_eq!(framing.get(), Framing::Newline);
+        assert_eq!(framing.encode("{}"), b"{}\n");
+    }
+
+    #[test]
+    fn test_detects_content_length_framing() {
+        let framing = SharedFraming::new(Framing::Auto);
+        let body = "{\"text\":\"a\\nb\"}";
+        let input = format!(
+            "Content-Length: {}\r\n\r\n{}content-length: 2\r\nContent-Type: application/json\r\n\r\n{{}}",
+            body.len(),
+            body
+        );
+
+        let messages = decode_all(&framing, input.as_bytes());
+
+        assert_eq!(messages, vec![body, "{}"]);
+        assert_eq!(framing.get(), Framing::ContentLength);
+        assert_eq!(framing.encode("{}"), b"Content-Length: 2\r\n\r\n{}");
+    }
+
+    #[test]
+    fn test_pinned_framing_is_not_redetected() {
+        let framing = SharedFraming::new(Framing::Newline);
+        let messages = decode_all(&framing, b"Content-Length: 2\n");
+        assert_eq!(messages, vec!["Content-Length: 2"]);
+        assert_eq!(framing.get(), Framing::Newline);
+    }
+
+    #[test]
+    fn test_missing_length_is_an_error() {
+        let framing = SharedFraming::new(Framing::ContentLength);
+        let mut decoder = MessageDecoder::new(framing, 0);
+        let mut buf = BytesMut::from(&b"Content-Type: json\r\n\r\n{}"[..]);
+        assert!(decoder.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_truncates_oversized_lines() {
+        let mut decoder = MessageDecoder::new(SharedFraming::new(Framing::Newline), 4);
+        let mut buf = BytesMut::from(&b"abcdefgh"[..]);
+        assert_eq!(decoder.decode(&mut buf).unwrap(), None);
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(
+            b"ij
+ok
+",
+        );
+        assert_eq!(
+            decoder.decode(&mut buf).unwrap().unwrap(),
+            "abcd…[truncated 6 bytes]"
+        );
+        assert_eq!(decoder.decode(&mut buf).unwrap().unwrap(), "ok");
+    }
+
+    #[test]
+    fn test_truncates_oversized_frames() {
+        let framing = SharedFraming::new(Framing::ContentLength);
+        let mut decoder = MessageDecoder::new(framing, 4);
+        let mut buf = BytesMut::from(&b"Content-Length: 10\r\n\r\n0123456"[..]);
+        assert_eq!(decoder.decode(&mut buf).unwrap(), None);
+
+        buf.extend_from_slice(b"789Content-Length: 2\r\n\r\n{}");
+        assert_eq!(
+            decoder.decode(&mut buf).unwrap().unwrap(),
+            "0123…[truncated 6 bytes]"
+        );
+        assert_eq!(decoder.decode(&mut buf).unwrap().unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_truncate_matches_decoder() {
+        assert_eq!(truncate("abcdefghij", 4), "abcd…[truncated 6 bytes]");
+        assert_eq!(truncated_size("abcd…[truncated 6 bytes]"), Some(10));
+        // Cut at a character boundary
+        assert_eq!(truncate("aé", 2), "a…[truncated 2 bytes]");
+        assert_eq!(truncate("abc", 0), "abc");
+        assert_eq!(truncated_size("abc"), None);
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_replaced() {
+        let framing = SharedFraming::new(Framing::Newline);
+        let messages = decode_all(&framing, b"ok \xff\xfe\n");
+        assert_eq!(messages, vec!["ok \u{FFFD}\u{FFFD}"]);
+    }
+}
@@ -0,0 +1,802 @@
+//! Fuzzing MCP servers for robustness
+//!
+//! A fuzz run throws input at a server that a well-behaved one has to
+//! survive: malformed JSON, params and tool arguments of the wrong type,
+//! boundary values derived from each tool's `inputSchema`, and oversized
+//! strings. After every case the server must still be alive and answering;
+//! a server that exits is a crash, one that stops answering is a hang, and a
+//! response breaking the JSON-RPC or MCP rules is a protocol violation.
+//!
+//! Like a probe, a run starts a stopped server and stops it afterwards, and
+//! a server it started is started again after a crash so the run can go on.
+//! Reports are kept in the `fuzz_reports` table.
+
+use crate::config::McpServer;
+use crate::database::Database;
+use crate::error::{SynapticError, SynapticResult};
+use crate::framing;
+use crate::host::Host;
+use crate::policy::wildcard_matches;
+use crate::probe;
+use crate::process_manager::{spawn_mcp_server, ProcessManager};
+use crate::validation::{validate_jsonrpc, validate_result};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::time::{Duration, Instant};
+
+/// Cases run when the options don't say
+pub const DEFAULT_MAX_CASES: usize = 200;
+
+/// Time a case may take when the options don't say
+pub const DEFAULT_CASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Length of the strings in oversized cases
+pub const OVERSIZED_STRING_BYTES: usize = 1024 * 1024;
+
+/// Longest payload kept in a finding
+const STORED_PAYLOAD_BYTES: usize = 1024;
+
+/// How often a case checks whether the server is still alive
+const LIVENESS_POLL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FuzzCategory {
+    /// Lines that aren't valid JSON-RPC at all
+    MalformedJson,
+    /// Params or tool arguments of the wrong type
+    WrongType,
+    /// Values at and past the limits of a tool's `inputSchema`
+    Boundary,
+    /// Strings of [`OVERSIZED_STRING_BYTES`]
+    Oversized,
+}
+
+/// One message thrown at a server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FuzzCase {
+    pub category: FuzzCategory,
+    pub description: String,
+    pub tool: Option<String>,
+    /// The line written to the server's stdin
+    pub payload: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FindingKind {
+    /// The server exited
+    Crash,
+    /// The server stopped answering
+    Hang,
+    /// A response broke the JSON-RPC or MCP rules
+    ProtocolViolation,
+}
+
+/// A case the server didn't handle well
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FuzzFinding {
+    pub kind: FindingKind,
+    pub category: FuzzCategory,
+    pub description: String,
+    pub tool: Option<String>,
+    /// The payload of the case, cut to a kilobyte
+    pub payload: String,
+    pub details: String,
+}
+
+/// What to fuzz
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FuzzOptions {
+    /// Only tools matching this name, `*` matching any run of characters
+    pub tool: Option<String>,
+    /// At most this many cases ([`DEFAULT_MAX_CASES`] when unset)
+    pub max_cases: Option<usize>,
+    /// Time a case may take before the server counts as hung
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FuzzReport {
+    pub id: String,
+    pub server_name: String,
+    /// Whether the server was started for the run (and stopped after it)
+    pub spawned: bool,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub cases_run: usize,
+    pub cases_total: usize,
+    pub findings: Vec<FuzzFinding>,
+    /// Why the run stopped before its last case, e.g. a crashed server it couldn't restart
+    pub aborted: Option<String>,
+}
+
+/// Cases for a server with `tools`, in the order they are run
+pub fn generate_cases(tools: &[Value]) -> Vec<FuzzCase> {
+    let mut cases = Vec::new();
+    let raw = |description: &str, payload: &str| FuzzCase {
+        category: FuzzCategory::MalformedJson,
+        description: description.to_string(),
+        tool: None,
+        payload: payload.to_string(),
+    };
+    cases.extend([
+        raw("Not JSON", "this is not json"),
+        raw(
+            "Truncated object",
+            r#"{"jsonrpc":"2.0","id":"fuzz-1","method":"#,
+        ),
+        raw("Empty array batch", "[]"),
+        raw("Bare string", r#""ping""#),
+        raw(
+            "Wrong jsonrpc version",
+            r#"{"jsonrpc":"1.0","id":"fuzz-2","method":"ping"}"#,
+        ),
+        raw(
+            "Numeric method",
+            r#"{"jsonrpc":"2.0","id":"fuzz-3","method":42}"#,
+        ),
+        raw(
+            "Object id",
+            r#"{"jsonrpc":"2.0","id":{"nested":true},"method":"ping"}"#,
+        ),
+        raw(
+            "Lone surrogate escape",
+            r#"{"jsonrpc":"2.0","id":"fuzz-4","method":"\udc00"}"#,
+        ),
+    ]);
+
+    let request = |category, description: String, tool: Option<&str>, method: &str, params| {
+        let mut message = json!({ "jsonrpc": "2.0", "method": method });
+        if let Some(params) = params {
+            message["params"] = params;
+        }
+        FuzzCase {
+            category,
+            description,
+            tool: tool.map(String::from),
+            payload: message.to_string(),
+        }
+    };
+    let wrong = FuzzCategory::WrongType;
+    cases.push(request(
+        wrong,
+        "tools/list with a numeric cursor".to_string(),
+        None,
+        "tools/list",
+        Some(json!({ "cursor": 42 })),
+    ));
+    cases.push(request(
+        wrong,
+        "tools/call with array params".to_string(),
+        None,
+        "tools/call",
+        Some(json!([])),
+    ));
+    cases.push(request(
+        wrong,
+        "tools/call with a numeric tool name".to_string(),
+        None,
+        "tools/call",
+        Some(json!({ "name": 42 })),
+    ));
+    cases.push(request(
+        wrong,
+        "tools/call without params".to_string(),
+        None,
+        "tools/call",
+        None,
+    ));
+
+    for tool in tools {
+        let Some(name) = tool.get("name").and_then(|n| n.as_str()) else {
+            continue;
+        };
+        let call = |category, description: String, arguments: Value| {
+            request(
+                category,
+                description,
+                Some(name),
+                "tools/call",
+                Some(json!({ "name": name, "arguments": arguments })),
+            )
+        };
+        for arguments in [json!("arguments"), json!(42), json!([]), Value::Null] {
+            cases.push(call(
+                wrong,
+                format!("{}: arguments {}", name, arguments),
+                arguments,
+            ));
+        }
+
+        let schema = tool.get("inputSchema").cloned().unwrap_or_default();
+        let properties = schema
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .cloned()
+            .unwrap_or_default();
+        let required: Vec<&str> = schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|r| r.iter().filter_map(|n| n.as_str()).collect())
+            .unwrap_or_default();
+        let base = base_arguments(&properties, &required);
+
+        if !required.is_empty() {
+            cases.push(call(
+                FuzzCategory::Boundary,
+                format!("{}: required arguments missing", name),
+                json!({}),
+            ));
+        }
+        let mut unknown = base.clone();
+        unknown.insert("__fuzz_unknown".to_string(), json!(true));
+        cases.push(call(
+            FuzzCategory::Boundary,
+            format!("{}: unknown argument", name),
+            Value::Object(unknown),
+        ));
+
+        for (property, property_schema) in &properties {
+            for (category, description, value) in boundary_values(property_schema) {
+                let mut arguments = base.clone();
+                arguments.insert(property.clone(), value);
+                cases.push(call(
+                    category,
+                    format!("{}: {} {}", name, property, description),
+                    Value::Object(arguments),
+                ));
+            }
+        }
+    }
+    cases
+}
+
+/// Arguments filling every required property with a plausible value
+fn base_arguments(properties: &Map<String, Value>, required: &[&str]) -> Map<String, Value> {
+    required
+        .iter()
+        .map(|name| {
+            let schema = properties.get(*name).cloned().unwrap_or_default();
+            (name.to_string(), plausible_value(&schema))
+        })
+        .collect()
+}
+
+fn plausible_value(schema: &Value) -> Value {
+    if let Some(first) = schema
+        .get("enum")
+        .and_then(|e| e.as_array())
+        .and_then(|e| e.first())
+    {
+        return first.clone();
+    }
+    match schema_type(schema) {
+        Some("integer") | Some("number") => schema.get("minimum").cloned().unwrap_or(json!(1)),
+        Some("boolean") => json!(true),
+        Some("array") => json!([]),
+        Some("object") => json!({}),
+        Some("null") => Value::Null,
+        _ => json!("fuzz"),
+    }
+}
+
+/// First type of a schema (`"type": ["string", "null"]` counts as string)
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(t) => Some(t),
+        Value::Array(types) => types.iter().find_map(|t| t.as_str()),
+        _ => None,
+    }
+}
+
+/// Values at and past the limits of a property's schema
+fn boundary_values(schema: &Value) -> Vec<(FuzzCategory, String, Value)> {
+    let boundary =
+        |description: &str, value: Value| (FuzzCategory::Boundary, description.to_string(), value);
+    let wrong = |value: Value| (FuzzCategory::WrongType, format!("as {}", value), value);
+    let limit = |key: &str| schema.get(key).and_then(|v| v.as_f64());
+    let count = |key: &str| schema.get(key).and_then(|v| v.as_u64());
+
+    let mut values = vec![boundary("null", Value::Null)];
+    match schema_type(schema) {
+        Some("integer") | Some("number") => {
+            values.push(boundary("zero", json!(0)));
+            values.push(boundary("negative", json!(-1)));
+            values.push(boundary("largest i64", json!(i64::MAX)));
+            values.push(boundary("smallest i64", json!(i64::MIN)));
+            values.push(boundary("huge float", json!(1e308)));
+            if schema_type(schema) == Some("integer") {
+                values.push(boundary("fraction", json!(1.5)));
+            }
+            if let Some(minimum) = limit("minimum").or(limit("exclusiveMinimum")) {
+                values.push(boundary("below minimum", json!(minimum - 1.0)));
+            }
+            if let Some(maximum) = limit("maximum").or(limit("exclusiveMaximum")) {
+                values.push(boundary("above maximum", json!(maximum + 1.0)));
+            }
+            values.push(wrong(json!("1")));
+        }
+        Some("boolean") => {
+            values.push(wrong(json!("true")));
+            values.push(wrong(json!(0)));
+        }
+        Some("array") => {
+            if let Some(max_items) = count("maxItems") {
+                let items = vec![Value::Null; max_items as usize + 1];
+                values.push(boundary("over maxItems", Value::Array(items)));
+            }
+            values.push(wrong(json!("item")));
+            values.push(wrong(json!({})));
+        }
+        Some("object") => {
+            values.push(wrong(json!([])));
+            values.push(wrong(json!("object")));
+        }
+        _ => {
+            values.push(boundary("empty", json!("")));
+            values.push(boundary(
+                "control and astral characters",
+                json!("\u{0}\u{1b}\u{feff}🦀"),
+            ));
+            if let Some(min_length) = count("minLength").filter(|min| *min > 0) {
+                let short = "x".repeat(min_length as usize - 1);
+                values.push(boundary("under minLength", json!(short)));
+            }
+            if let Some(max_length) = count("maxLength") {
+                let long = "x".repeat(max_length as usize + 1);
+                values.push(boundary("over maxLength", json!(long)));
+            }
+            if schema.get("enum").is_some() {
+                values.push(boundary("outside enum", json!("__fuzz_not_in_enum")));
+            }
+            values.push(wrong(json!(12345)));
+            values.push((
+                FuzzCategory::Oversized,
+                format!("of {} bytes", OVERSIZED_STRING_BYTES),
+                json!("A".repeat(OVERSIZED_STRING_BYTES)),
+            ));
+        }
+    }
+    values
+}
+
+/// Fuzz a server, starting it temporarily when it isn't running
+///
+/// The report is stored when the host has a database.
+pub async fn run_fuzz<H: Host>(
+    host: H,
+    server_name: &str,
+    server: McpServer,
+    options: FuzzOptions,
+) -> SynapticResult<FuzzReport> {
+    let Some(pm) = host.process_manager() else {
+        return Err(SynapticError::ProcessError(
+            "Process manager unavailable".to_string(),
+        ));
+    };
+
+    let started_at = Utc::now();
+    let started = Instant::now();
+    let spawned = !pm.is_running(server_name).await;
+    if spawned {
+        spawn_mcp_server(host.clone(), pm, server_name.to_string(), server.clone()).await?;
+    }
+
+    let mut run = Run {
+        host: &host,
+        pm,
+        server_name,
+        server,
+        spawned,
+        timeout: options
+            .timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_CASE_TIMEOUT),
+        pid: None,
+    };
+    let result = run.fuzz(&options).await;
+    if spawned {
+        probe::stop_temporary(&host, pm, server_name).await;
+    }
+    let (cases_run, cases_total, findings, aborted) = result?;
+
+    let report = FuzzReport {
+        id: uuid::Uuid::new_v4().to_string(),
+        server_name: server_name.to_string(),
+        spawned,
+        started_at,
+        duration_ms: started.elapsed().as_millis() as u64,
+        cases_run,
+        cases_total,
+        findings,
+        aborted,
+    };
+    if let Some(db) = host.app_state().and_then(|state| state.database().ok()) {
+        save_report(db, &report)?;
+    }
+    Ok(report)
+}
+
+struct Run<'a, H: Host> {
+    host: &'a H,
+    pm: &'a ProcessManager,
+    server_name: &'a str,
+    server: McpServer,
+    spawned: bool,
+    timeout: Duration,
+    /// Process the cases go to
+    pid: Option<u32>,
+}
+
+/// How a case went
+enum Outcome {
+    Answered(Value),
+    /// Written without waiting for an answer (not a request)
+    Sent,
+    Exited,
+    TimedOut,
+    Failed(String),
+}
+
+type RunResult = (usize, usize, Vec<FuzzFinding>, Option<String>);
+
+impl<H: Host> Run<'_, H> {
+    async fn fuzz(&mut self, options: &FuzzOptions) -> SynapticResult<RunResult> {
+        if self.spawned {
+            probe::initialize(self.pm, self.server_name).await?;
+        }
+        self.pid = self.current_pid().await;
+
+        let tools = probe::list_all(self.pm, self.server_name, "tools/list", "tools")
+            .await
+            .unwrap_or_default();
+        let tools: Vec<Value> = tools
+            .into_iter()
+            .filter(|tool| {
+                options.tool.as_deref().is_none_or(|pattern| {
+                    let name = tool.get("name").and_then(|n| n.as_str());
+                    name.is_some_and(|name| wildcard_matches(pattern, name))
+                })
+            })
+            .collect();
+        let mut cases = generate_cases(&tools);
+        let cases_total = cases.len();
+        cases.truncate(options.max_cases.unwrap_or(DEFAULT_MAX_CASES));
+
+        let mut findings = Vec::new();
+        let mut cases_run = 0;
+        for case in &cases {
+            cases_run += 1;
+            if let Some(aborted) = self.run_case(case, &mut findings).await {
+                return Ok((cases_run, cases_total, findings, Some(aborted)));
+            }
+        }
+        Ok((cases_run, cases_total, findings, None))
+    }
+
+    /// Run one case; returns why the run can't go on, if it can't
+    async fn run_case(
+        &mut self,
+        case: &FuzzCase,
+        findings: &mut Vec<FuzzFinding>,
+    ) -> Option<String> {
+        let mut finding = |kind, details: String| {
+            findings.push(FuzzFinding {
+                kind,
+                category: case.category,
+                description: case.description.clone(),
+                tool: case.tool.clone(),
+                payload: framing::truncate(&case.payload, STORED_PAYLOAD_BYTES).into_owned(),
+                details,
+            })
+        };
+
+        let outcome = match serde_json::from_str::<Value>(&case.payload) {
+            Ok(request) if request.get("id").is_none() && request.get("method").is_some() => {
+                self.watch(
+                    self.pm
+                        .send_request(self.server_name, request.clone(), self.timeout),
+                )
+                .await
+            }
+            _ => match self
+                .pm
+                .send_to_stdin(self.server_name, case.payload.clone())
+                .await
+            {
+                Ok(()) => Outcome::Sent,
+                Err(e) => Outcome::Failed(e.to_string()),
+            },
+        };
+
+        match outcome {
+            Outcome::Answered(response) => {
+                for violation in violations(case, &response) {
+                    finding(FindingKind::ProtocolViolation, violation);
+                }
+            }
+            Outcome::Exited => {}
+            Outcome::TimedOut => finding(
+                FindingKind::Hang,
+                format!("No response within {} ms", self.timeout.as_millis()),
+            ),
+            Outcome::Failed(error) => return Some(error),
+            Outcome::Sent => {}
+        }
+
+        // Whatever it was sent, the server has to keep answering
+        let ping = json!({ "jsonrpc": "2.0", "method": "ping" });
+        let alive = self
+            .watch(self.pm.send_request(self.server_name, ping, self.timeout))
+            .await;
+        match alive {
+            Outcome::Answered(_) => None,
+            Outcome::TimedOut => {
+                finding(
+                    FindingKind::Hang,
+                    "Stopped answering ping afterwards".to_string(),
+                );
+                self.restart(true).await
+            }
+            _ => {
+                let stderr = self.pm.get_recent_stderr(self.server_name, 5).await;
+                let mut details = "Server exited".to_string();
+                if !stderr.is_empty() {
+                    details = format!("{}: {}", details, stderr.join("\n"));
+                }
+                finding(FindingKind::Crash, details);
+                self.restart(false).await
+            }
+        }
+    }
+
+    /// Wait for a request, giving up early when the server exits
+    async fn watch(
+        &self,
+        request: impl std::future::Future<Output = SynapticResult<Value>>,
+    ) -> Outcome {
+        let exited = async {
+            loop {
+                tokio::time::sleep(LIVENESS_POLL).await;
+                if self.current_pid().await != self.pid {
+                    return;
+                }
+            }
+        };
+        tokio::select! {
+            response = request => match response {
+                Ok(response) => Outcome::Answered(response),
+                Err(_) if self.current_pid().await != self.pid => Outcome::Exited,
+                Err(_) => Outcome::TimedOut,
+            },
+            _ = exited => Outcome::Exited,
+        }
+    }
+
+    async fn current_pid(&self) -> Option<u32> {
+        if !self.pm.is_running(self.server_name).await {
+            return None;
+        }
+        self.pm.get_status(self.server_name).await?.pid
+    }
+
+    /// Get a server that crashed or hung answering again
+    ///
+    /// Returns why the run can't go on, if it can't.
+    async fn restart(&mut self, hung: bool) -> Option<String> {
+        if hung && self.spawned {
+            probe::stop_temporary(self.host, self.pm, self.server_name).await;
+        }
+        if !self.pm.is_running(self.server_name).await {
+            if !self.spawned {
+                return Some("Server stopped; it was not started by the fuzz run".to_string());
+            }
+            // Its exit may still be being handled
+            for _ in 0..20 {
+                if self
+                    .pm
+                    .get_status(self.server_name)
+                    .await
+                    .is_none_or(|s| s.pid.is_none())
+                {
+                    break;
+                }
+                tokio::time::sleep(LIVENESS_POLL).await;
+            }
+            if let Err(e) = spawn_mcp_server(
+                self.host.clone(),
+                self.pm,
+                self.server_name.to_string(),
+                self.server.clone(),
+            )
+            .await
+            {
+                return Some(format!("Couldn't restart the server: {}", e));
+            }
+        } else if hung {
+            return Some("Server stopped answering".to_string());
+        }
+
+        // A restarted process needs a handshake of its own
+        self.pid = self.current_pid().await;
+        match probe::initialize(self.pm, self.server_name).await {
+            Ok(_) => None,
+            Err(e) => Some(format!("Restarted server didn't initialize: {}", e)),
+        }
+    }
+}
+
+/// Rules a response to a case breaks
+fn violations(case: &FuzzCase, response: &Value) -> Vec<String> {
+    let mut violations = validate_jsonrpc(response);
+    let Some(result) = response.get("result") else {
+        return violations;
+    };
+    let request: Value = serde_json::from_str(&case.payload).unwrap_or_default();
+    let method = request["method"].as_str().unwrap_or_default();
+    violations.extend(validate_result(method, result));
+
+    let arguments = &request["params"]["arguments"];
+    let is_error = result.get("isError").and_then(|e| e.as_bool()) == Some(true);
+    if method == "tools/call" && !arguments.is_object() && !arguments.is_null() && !is_error {
+        violations.push(format!(
+            "Accepted arguments {} that aren't an object",
+            arguments
+        ));
+    }
+    violations
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or_default()
+}
+
+pub fn save_report(db: &Database, report: &FuzzReport) -> SynapticResult<()> {
+    db.conn().execute(
+        "INSERT OR REPLACE INTO fuzz_reports (id, server_name, started_at, report)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            report.id,
+            report.server_name,
+            report.started_at.timestamp_millis(),
+            serde_json::to_value(report)?,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Stored reports, newest first, optionally for a single server
+pub fn list_reports(db: &Database, server_name: Option<&str>) -> SynapticResult<Vec<FuzzReport>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT report, started_at FROM fuzz_reports
+         WHERE ?1 IS NULL OR server_name = ?1
+         ORDER BY started_at DESC",
+    )?;
+    let rows = stmt.query_map(params![server_name], |row| {
+        Ok((row.get::<_, Value>(0)?, row.get::<_, i64>(1)?))
+    })?;
+
+    let mut reports = Vec::new();
+    for row in rows {
+        let (report, started_at) = row?;
+        let mut report: FuzzReport = serde_json::from_value(report)?;
+        report.started_at = from_millis(started_at);
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+/// Delete a stored report; returns whether it existed
+pub fn delete_report(db: &Database, id: &str) -> SynapticResult<bool> {
+    let removed = db
+        .conn()
+        .execute("DELETE FROM fuzz_reports WHERE id = ?1", params![id])?;
+    Ok(removed > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cases_follow_the_input_schema() {
+        let tools = vec![json!({
+            "name": "search",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "maxLength": 4 },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": 50 },
+                },
+                "required": ["query"],
+            },
+        })];
+        let cases = generate_cases(&tools);
+        let find = |description: &str| {
+            cases
+                .iter()
+                .find(|c| c.description == description)
+                .unwrap_or_else(|| panic!("no case {}", description))
+        };
+
+        let over = find("search: query over maxLength");
+        assert_eq!(over.category, FuzzCategory::Boundary);
+        assert!(over.payload.contains(r#""query":"xxxxx""#));
+        // Other required arguments keep a plausible value
+        let above: Value =
+            serde_json::from_str(&find("search: limit above maximum").payload).unwrap();
+        assert_eq!(
+            above["params"]["arguments"],
+            json!({ "query": "fuzz", "limit": 51.0 })
+        );
+        let missing: Value =
+            serde_json::from_str(&find("search: required arguments missing").payload).unwrap();
+        assert_eq!(missing["params"]["arguments"], json!({}));
+
+        assert_eq!(
+            find("search: limit as \"1\"").category,
+            FuzzCategory::WrongType
+        );
+        let oversized = find(&format!(
+            "search: query of {} bytes",
+            OVERSIZED_STRING_BYTES
+        ));
+        assert_eq!(oversized.category, FuzzCategory::Oversized);
+        assert!(cases
+            .iter()
+            .any(|c| c.category == FuzzCategory::MalformedJson));
+    }
+
+    #[test]
+    fn test_reports_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+        let report = |id: &str, server_name: &str, minutes: i64| FuzzReport {
+            id: id.to_string(),
+            server_name: server_name.to_string(),
+            spawned: true,
+            started_at: from_millis(minutes * 60_000),
+            duration_ms: 10,
+            cases_run: 1,
+            cases_total: 1,
+            findings: vec![FuzzFinding {
+                kind: FindingKind::Crash,
+                category: FuzzCategory::MalformedJson,
+                description: "Not JSON".to_string(),
+                tool: None,
+                payload: "this is not json".to_string(),
+                details: "Server exited".to_string(),
+            }],
+            aborted: None,
+        };
+        save_report(&db, &report("a", "fs", 1)).unwrap();
+        save_report(&db, &report("b", "fs", 2)).unwrap();
+        save_report(&db, &report("c", "git", 3)).unwrap();
+
+        let ids = |server_name| {
+            list_reports(&db, server_name)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(Some("fs")), vec!["b", "a"]);
+        assert_eq!(
+            list_reports(&db, None).unwrap()[0].findings[0].kind,
+            FindingKind::Crash
+        );
+
+        assert!(delete_report(&db, "a").unwrap());
+        assert!(!delete_report(&db, "a").unwrap());
+        assert_eq!(ids(None), vec!["c", "b"]);
+    }
+}
@@ -0,0 +1,629 @@
+//! MCP gateway: every enabled server behind one endpoint
+//!
+//! The gateway is itself an MCP server, served over Streamable HTTP on a
+//! loopback port. Clients that only speak stdio launch `synaptic-gateway`,
+//! which bridges its stdio to that endpoint, so a client needs a single config
+//! entry however many servers Synaptic manages.
+//!
+//! Only local clients get in: the `Host` must name the loopback port, an
+//! `Origin` (sent by browsers) must be a loopback one, and every request
+//! carries the per-install bearer token [`client_entry`] hands the bridge. A
+//! web page can therefore neither reach the gateway directly nor through DNS
+//! rebinding.
+//!
+//! Tools and prompts are namespaced with their server's name
+//! (`github.create_issue`); resources keep their URIs and are routed to the
+//! server that listed them. Servers are started and initialized on demand.
+//! Calls go through the process manager like any other traffic, so they are
+//! inspected, intercepted and mocked as usual.
+
+use crate::config::{self, McpServer};
+use crate::error::{SynapticError, SynapticResult};
+use crate::host::Host;
+use crate::probe::{self, PROBE_PROTOCOL_VERSION};
+use crate::process_manager::{spawn_mcp_server, ProcessManager};
+use crate::resolve;
+use crate::transport::{self, Transport};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::{HeaderMap, AUTHORIZATION, HOST, ORIGIN};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// Stdio bridge clients launch to reach the gateway
+pub const GATEWAY_BINARY: &str = "synaptic-gateway";
+
+/// Port the gateway listens on unless configured otherwise
+pub const DEFAULT_GATEWAY_PORT: u16 = 39217;
+
+/// Path of the gateway endpoint
+pub const GATEWAY_PATH: &str = "/mcp";
+
+/// Env var the bridge reads the gateway token from
+pub const GATEWAY_TOKEN_ENV: &str = "SYNAPTIC_GATEWAY_TOKEN";
+
+/// File in the data directory holding the gateway token
+const GATEWAY_TOKEN_FILE: &str = "gateway.token";
+
+/// Length of the gateway token, in bytes before encoding
+const GATEWAY_TOKEN_LEN: usize = 32;
+
+/// Separates the server name from a tool or prompt name
+pub const NAMESPACE_SEPARATOR: char = '.';
+
+/// Time a routed request may take
+const ROUTED_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const PARSE_ERROR: i64 = -32700;
+
+/// `github.create_issue` for the `create_issue` tool of `github`
+pub fn namespaced(server_name: &str, name: &str) -> String {
+    format!("{}{}{}", server_name, NAMESPACE_SEPARATOR, name)
+}
+
+/// The server a namespaced name belongs to, and the name on that server
+///
+/// Server names may contain the separator themselves, so the longest
+/// matching server name wins.
+pub fn route<'a, 'n>(
+    name: &'n str,
+    server_names: impl IntoIterator<Item = &'a String>,
+) -> Option<(&'a str, &'n str)> {
+    server_names
+        .into_iter()
+        .filter_map(|server| {
+            let rest = name.strip_prefix(server.as_str())?;
+            let rest = rest.strip_prefix(NAMESPACE_SEPARATOR)?;
+            (!rest.is_empty()).then_some((server.as_str(), rest))
+        })
+        .max_by_key(|(server, _)| server.len())
+}
+
+/// The gateway endpoint's url for a port
+pub fn gateway_url(port: u16) -> String {
+    format!("http://127.0.0.1:{}{}", port, GATEWAY_PATH)
+}
+
+/// Per-install bearer token clients present to the gateway
+pub fn gateway_token() -> SynapticResult<String> {
+    let token = config::load_or_create_secret(GATEWAY_TOKEN_FILE, GATEWAY_TOKEN_LEN)?;
+    Ok(URL_SAFE_NO_PAD.encode(token))
+}
+
+/// Config entry that connects a client to the gateway through `synaptic-gateway`
+pub fn client_entry(port: u16) -> SynapticResult<McpServer> {
+    Ok(McpServer {
+        command: resolve::bundled_binary(GATEWAY_BINARY)?
+            .to_string_lossy()
+            .to_string(),
+        args: vec!["--url".to_string(), gateway_url(port)],
+        env: HashMap::from([(GATEWAY_TOKEN_ENV.to_string(), gateway_token()?)]),
+        ..Default::default()
+    })
+}
+
+fn error_response(id: Value, code: i64, message: impl Into<String>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.into() },
+    })
+}
+
+fn result_response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+// ============================================
+// MCP SERVER
+// ============================================
+
+/// The gateway's MCP server side
+pub struct Gateway<H: Host> {
+    host: H,
+    /// Bearer token every request must carry
+    token: String,
+    /// Which server listed each resource URI
+    resource_owners: std::sync::Mutex<HashMap<String, String>>,
+    /// PID of each server instance the gateway initialized; held while bringing one up
+    initialized: tokio::sync::Mutex<HashMap<String, u32>>,
+}
+
+impl<H: Host> Gateway<H> {
+    pub fn new(host: H, token: String) -> Self {
+        Self {
+            host,
+            token,
+            resource_owners: Default::default(),
+            initialized: Default::default(),
+        }
+    }
+
+    fn process_manager(&self) -> SynapticResult<&ProcessManager> {
+        self.host
+            .process_manager()
+            .ok_or_else(|| SynapticError::ProcessError("Process manager unavailable".to_string()))
+    }
+
+    /// Enabled servers, by name
+    fn servers(&self) -> SynapticResult<HashMap<String, McpServer>> {
+        let state = self
+            .host
+            .app_state()
+            .ok_or_else(|| SynapticError::ProcessError("App state unavailable".to_string()))?;
+        Ok(state
+            .get_config()?
+            .mcp_servers
+            .into_iter()
+            .filter(|(_, server)| server.enabled)
+            .collect())
+    }
+
+    /// Start a server unless it runs, and initialize each instance of it once
+    async fn ensure_ready(&self, server_name: &str, server: &McpServer) -> SynapticResult<()> {
+        let pm = self.process_manager()?;
+        let mut initialized = self.initialized.lock().await;
+        let running = pm
+            .get_status(server_name)
+            .await
+            .and_then(|status| status.pid);
+        let (pid, spawned) = match running {
+            Some(pid) if pm.is_running(server_name).await => (pid, false),
+            _ => {
+                let pid = spawn_mcp_server(
+                    self.host.clone(),
+                    pm,
+                    server_name.to_string(),
+                    server.clone(),
+                )
+                .await?;
+                (pid, true)
+            }
+        };
+        if initialized.get(server_name) != Some(&pid) {
+            // A server started elsewhere may have been initialized already and refuse a second time
+            if let Err(e) = probe::initialize(pm, server_name).await {
+                if spawned {
+                    return Err(e);
+                }
+            }
+            initialized.insert(server_name.to_string(), pid);
+        }
+        Ok(())
+    }
+
+    /// Answer one JSON-RPC message; notifications get no answer
+    pub async fn handle(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned()?;
+        let Some(method) = message.get("method").and_then(|m| m.as_str()) else {
+            return Some(error_response(id, INVALID_PARAMS, "Message has no method"));
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let outcome = match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => self.list("tools/list", "tools", true).await,
+            "prompts/list" => self.list("prompts/list", "prompts", true).await,
+            "resources/list" => self.list("resources/list", "resources", false).await,
+            "tools/call" | "prompts/get" => {
+                return Some(self.forward_named(id, method, params).await)
+            }
+            "resources/read" => return Some(self.forward_resource(id, params).await),
+            _ => {
+                return Some(error_response(
+                    id,
+                    METHOD_NOT_FOUND,
+                    format!("Method not found: {}", method),
+                ))
+            }
+        };
+        Some(match outcome {
+            Ok(result) => result_response(id, result),
+            Err(e) => error_response(id, INTERNAL_ERROR, e.to_string()),
+        })
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        // Answer in the client's version; the servers behind speak their own with Synaptic
+        let version = params
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .unwrap_or(PROBE_PROTOCOL_VERSION);
+        json!({
+            "protocolVersion": version,
+            "capabilities": { "tools": {}, "prompts": {}, "resources": {} },
+            "serverInfo": { "name": "wear-synaptic-gateway", "version": env!("CARGO_PKG_VERSION") },
+        })
+    }
+
+    /// Every server's items of a list, namespaced when `namespace` is set
+    async fn list(&self, method: &str, key: &str, namespace: bool) -> SynapticResult<Value> {
+        let pm = self.process_manager()?;
+        let mut servers: Vec<_> = self.servers()?.into_iter().collect();
+        servers.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut items = Vec::new();
+        for (server_name, server) in servers {
+            // One broken server doesn't hide the others
+            if let Err(e) = self.ensure_ready(&server_name, &server).await {
+                eprintln!("Gateway skipped {}: {}", server_name, e);
+                continue;
+            }
+            let listed = match probe::list_all(pm, &server_name, method, key).await {
+                Ok(listed) => listed,
+                Err(e) => {
+                    eprintln!("Gateway: {} of {} failed: {}", method, server_name, e);
+                    continue;
+                }
+            };
+            for mut item in listed {
+                if namespace {
+                    if let Some(name) = item.get("name").and_then(|n| n.as_str()) {
+                        item["name"] = json!(namespaced(&server_name, name));
+                    }
+                } else if let Some(uri) = item.get("uri").and_then(|u| u.as_str()) {
+                    self.resource_owners
+                        .lock()
+                        .unwrap()
+                        .insert(uri.to_string(), server_name.clone());
+                }
+                items.push(item);
+            }
+        }
+        Ok(json!({ key: items }))
+    }
+
+    /// Route a `tools/call` or `prompts/get` by its namespaced name
+    async fn forward_named(&self, id: Value, method: &str, mut params: Value) -> Value {
+        let servers = match self.servers() {
+            Ok(servers) => servers,
+            Err(e) => return error_response(id, INTERNAL_ERROR, e.to_string()),
+        };
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .unwrap_or_default();
+        let Some((server_name, local)) = route(name, servers.keys()) else {
+            return error_response(id, INVALID_PARAMS, format!("Unknown name: {}", name));
+        };
+        let (server_name, local) = (server_name.to_string(), local.to_string());
+        params["name"] = json!(local);
+        self.forward(id, &server_name, &servers[&server_name], method, params)
+            .await
+    }
+
+    /// Route a `resources/read` to the server that listed the URI
+    async fn forward_resource(&self, id: Value, params: Value) -> Value {
+        let uri = params
+            .get("uri")
+            .and_then(|u| u.as_str())
+            .unwrap_or_default();
+        let owner = self.resource_owners.lock().unwrap().get(uri).cloned();
+        let servers = match self.servers() {
+            Ok(servers) => servers,
+            Err(e) => return error_response(id, INTERNAL_ERROR, e.to_string()),
+        };
+        match owner.and_then(|name| servers.get(&name).map(|server| (name, server))) {
+            Some((name, server)) => {
+                self.forward(id, &name, server, "resources/read", params)
+                    .await
+            }
+            None => error_response(id, INVALID_PARAMS, format!("Unknown resource: {}", uri)),
+        }
+    }
+
+    /// Send a request to one server through the inspector, answering under the client's id
+    async fn forward(
+        &self,
+        id: Value,
+        server_name: &str,
+        server: &McpServer,
+        method: &str,
+        params: Value,
+    ) -> Value {
+        let exchange = async {
+            self.ensure_ready(server_name, server).await?;
+            let request = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+            self.process_manager()?
+                .send_request(server_name, request, ROUTED_REQUEST_TIMEOUT)
+                .await
+        };
+        match exchange.await {
+            Ok(mut response) => {
+                response["id"] = id;
+                response
+            }
+            Err(e) => error_response(id, INTERNAL_ERROR, e.to_string()),
+        }
+    }
+
+    /// Answer a POSTed body: one message or a batch
+    pub async fn handle_body(&self, body: &[u8]) -> Option<Value> {
+        let message: Value = match serde_json::from_slice(body) {
+            Ok(message) => message,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, e.to_string())),
+        };
+        match message {
+            Value::Array(batch) => {
+                let replies = futures::future::join_all(batch.into_iter().map(|m| self.handle(m)));
+                let replies: Vec<Value> = replies.await.into_iter().flatten().collect();
+                (!replies.is_empty()).then_some(Value::Array(replies))
+            }
+            message => self.handle(message).await,
+        }
+    }
+}
+
+// ============================================
+// HTTP ENDPOINT
+// ============================================
+
+/// Serve the gateway on its configured loopback port, if enabled
+pub async fn run_gateway<H: Host>(host: H) {
+    let settings = host
+        .app_state()
+        .and_then(|state| state.get_settings().ok())
+        .unwrap_or_default();
+    if !settings.gateway_enabled {
+        return;
+    }
+
+    let token = match gateway_token() {
+        Ok(token) => token,
+        Err(e) => {
+            eprintln!("Gateway disabled, no token: {}", e);
+            return;
+        }
+    };
+    let addr = SocketAddr::from(([127, 0, 0, 1], settings.gateway_port));
+    match TcpListener::bind(addr).await {
+        Ok(listener) => serve(Arc::new(Gateway::new(host, token)), listener).await,
+        Err(e) => eprintln!("Gateway couldn't listen on {}: {}", addr, e),
+    }
+}
+
+/// Accept gateway connections on `listener` until the process ends
+pub async fn serve<H: Host>(gateway: Arc<Gateway<H>>, listener: TcpListener) {
+    let port = match listener.local_addr() {
+        Ok(addr) => addr.port(),
+        Err(e) => {
+            eprintln!("Gateway has no address: {}", e);
+            return;
+        }
+    };
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                eprintln!("Gateway accept failed: {}", e);
+                continue;
+            }
+        };
+        let gateway = gateway.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |request| {
+                let gateway = gateway.clone();
+                async move { Ok::<_, Infallible>(respond(&gateway, port, request).await) }
+            });
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await;
+        });
+    }
+}
+
+fn plain(status: StatusCode) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::new()));
+    *response.status_mut() = status;
+    response
+}
+
+/// Whether a request comes from a local client rather than a web page
+///
+/// The `Host` must name the gateway's own loopback port, which a page reaching
+/// it through a rebound DNS name can't forge, and an `Origin` must be loopback.
+pub fn is_local_request(headers: &HeaderMap, port: u16) -> bool {
+    let host = headers.get(HOST).and_then(|v| v.to_str().ok());
+    let host_ok = host.is_some_and(|host| {
+        host == format!("127.0.0.1:{}", port) || host == format!("localhost:{}", port)
+    });
+    let origin_ok = match headers.get(ORIGIN) {
+        None => true,
+        Some(origin) => origin
+            .to_str()
+            .ok()
+            .and_then(|origin| Url::parse(origin).ok())
+            .is_some_and(|origin| {
+                matches!(origin.host_str(), Some("127.0.0.1" | "localhost" | "[::1]"))
+            }),
+    };
+    host_ok && origin_ok
+}
+
+/// Whether a request carries the gateway token, compared in constant time
+fn is_authorized(headers: &HeaderMap, token: &str) -> bool {
+    let Some(presented) = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    presented.len() == token.len()
+        && presented
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn respond<H: Host>(
+    gateway: &Gateway<H>,
+    port: u16,
+    request: Request<Incoming>,
+) -> Response<Full<Bytes>> {
+    if !is_local_request(request.headers(), port) {
+        return plain(StatusCode::FORBIDDEN);
+    }
+    if request.uri().path() != GATEWAY_PATH {
+        return plain(StatusCode::NOT_FOUND);
+    }
+    if !is_authorized(request.headers(), &gateway.token) {
+        return plain(StatusCode::UNAUTHORIZED);
+    }
+    match *request.method() {
+        Method::POST => {}
+        // Stateless: no session to end
+        Method::DELETE => return plain(StatusCode::OK),
+        // No server-initiated messages, so no event stream
+        _ => return plain(StatusCode::METHOD_NOT_ALLOWED),
+    }
+
+    let body = match request.into_body().collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(_) => return plain(StatusCode::BAD_REQUEST),
+    };
+    match gateway.handle_body(&body).await {
+        Some(reply) => {
+            let mut response = Response::new(Full::new(Bytes::from(reply.to_string())));
+            response.headers_mut().insert(
+                hyper::header::CONTENT_TYPE,
+                hyper::header::HeaderValue::from_static("application/json"),
+            );
+            response
+        }
+        None => plain(StatusCode::ACCEPTED),
+    }
+}
+
+// ============================================
+// STDIO BRIDGE
+// ============================================
+
+/// Relay stdin/stdout to the gateway at `url` until stdin closes, presenting `token`
+pub async fn bridge_stdio(url: &str, token: &str) -> SynapticResult<()> {
+    let endpoint = McpServer {
+        url: Some(url.to_string()),
+        transport: Transport::Http,
+        headers: HashMap::from([(AUTHORIZATION.to_string(), format!("Bearer {}", token))]),
+        ..Default::default()
+    };
+    let (mut connection, pipes) = transport::connect(GATEWAY_BINARY, &endpoint)?;
+
+    let mut to_gateway = pipes.stdin;
+    let upstream = tokio::spawn(async move {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let line = format!("{}\n", line);
+            if to_gateway.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+    let mut from_gateway = pipes.stdout;
+    let downstream = tokio::spawn(async move {
+        let _ = tokio::io::copy(&mut from_gateway, &mut tokio::io::stdout()).await;
+    });
+    let mut errors = pipes.stderr;
+    tokio::spawn(async move {
+        let _ = tokio::io::copy(&mut errors, &mut tokio::io::stderr()).await;
+    });
+
+    let _ = upstream.await;
+    let outcome = connection.wait().await;
+    // Let the last replies reach stdout
+    let _ = tokio::time::timeout(Duration::from_secs(1), downstream).await;
+    outcome.map_err(SynapticError::ProcessError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_prefers_longest_server_name() {
+        let servers = vec!["github".to_string(), "github.enterprise".to_string()];
+        assert_eq!(
+            route("github.create_issue", &servers),
+            Some(("github", "create_issue"))
+        );
+        assert_eq!(
+            route("github.enterprise.create_issue", &servers),
+            Some(("github.enterprise", "create_issue"))
+        );
+        assert_eq!(route("gitlab.create_issue", &servers), None);
+        assert_eq!(route("github.", &servers), None);
+        assert_eq!(namespaced("github", "create_issue"), "github.create_issue");
+    }
+
+    #[test]
+    fn test_only_local_clients_with_the_token_get_in() {
+        let headers = |pairs: &[(&'static str, &str)]| {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.insert(*name, value.parse().unwrap());
+            }
+            headers
+        };
+        assert!(is_local_request(
+            &headers(&[("host", "127.0.0.1:4000")]),
+            4000
+        ));
+        assert!(is_local_request(
+            &headers(&[
+                ("host", "localhost:4000"),
+                ("origin", "http://localhost:5173")
+            ]),
+            4000
+        ));
+        // A page on another site, or a rebound name, is turned away
+        assert!(!is_local_request(
+            &headers(&[
+                ("host", "127.0.0.1:4000"),
+                ("origin", "https://evil.example")
+            ]),
+            4000
+        ));
+        assert!(!is_local_request(
+            &headers(&[("host", "evil.example:4000")]),
+            4000
+        ));
+        assert!(!is_local_request(
+            &headers(&[("host", "127.0.0.1:4001")]),
+            4000
+        ));
+        assert!(!is_local_request(&headers(&[]), 4000));
+
+        assert!(is_authorized(
+            &headers(&[("authorization", "Bearer s3cret")]),
+            "s3cret"
+        ));
+        assert!(!is_authorized(
+            &headers(&[("authorization", "Bearer s3crex")]),
+            "s3cret"
+        ));
+        assert!(!is_authorized(
+            &headers(&[("authorization", "s3cret")]),
+            "s3cret"
+        ));
+        assert!(!is_authorized(&headers(&[]), "s3cret"));
+    }
+}
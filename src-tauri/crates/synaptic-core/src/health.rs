@@ -0,0 +1,117 @@
+//! MCP-level health checks for running servers
+//!
+//! A live process can still be wedged. The health checker periodically sends
+//! a JSON-RPC `ping` to each running server, records the latency and marks
+//! the server unhealthy after several consecutive failures.
+
+use crate::host::Host;
+use crate::process_manager::{ProcessManager, ProcessState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// How often running servers are pinged
+pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a server has to answer a ping
+pub const HEALTH_PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Consecutive failed pings before a server is marked unhealthy
+pub const UNHEALTHY_AFTER_FAILURES: u32 = 3;
+
+/// Health of one running server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthStatus {
+    pub server_name: String,
+    pub healthy: bool,
+    /// Round-trip time of the last successful ping
+    pub latency_ms: Option<u64>,
+    pub consecutive_failures: u32,
+    pub last_checked: DateTime<Utc>,
+    pub last_error: Option<String>,
+}
+
+impl HealthStatus {
+    fn new(server_name: &str) -> Self {
+        Self {
+            server_name: server_name.to_string(),
+            healthy: true,
+            latency_ms: None,
+            consecutive_failures: 0,
+            last_checked: Utc::now(),
+            last_error: None,
+        }
+    }
+}
+
+/// Ping every running server once
+///
+/// Returns the servers whose health flipped during this round.
+pub async fn run_health_checks(pm: &ProcessManager, timeout: Duration) -> Vec<HealthStatus> {
+    let running: Vec<String> = pm
+        .all_statuses()
+        .await
+        .into_iter()
+        .filter(|s| s.state == ProcessState::Running)
+        .map(|s| s.server_name)
+        .collect();
+
+    let results = futures::future::join_all(running.iter().map(|name| async move {
+        let started = Instant::now();
+        let result = pm.request(name, "ping", None, timeout).await;
+        (name, result.map(|_| started.elapsed()))
+    }))
+    .await;
+
+    let mut health = pm.health.lock().await;
+    health.retain(|name, _| running.contains(name));
+
+    let mut changed = Vec::new();
+    for (name, result) in results {
+        let status = health
+            .entry(name.clone())
+            .or_insert_with(|| HealthStatus::new(name));
+        let was_healthy = status.healthy;
+
+        status.last_checked = Utc::now();
+        match result {
+            Ok(latency) => {
+                status.latency_ms = Some(latency.as_millis() as u64);
+                status.consecutive_failures = 0;
+                status.last_error = None;
+                status.healthy = true;
+            }
+            Err(e) => {
+                status.consecutive_failures += 1;
+                status.last_error = Some(e.to_string());
+                if status.consecutive_failures >= UNHEALTHY_AFTER_FAILURES {
+                    status.healthy = false;
+                }
+            }
+        }
+
+        if status.healthy != was_healthy {
+            changed.push(status.clone());
+        }
+    }
+
+    changed
+}
+
+/// Background loop pinging running servers
+pub async fn run_health_checker<H: Host>(host: H) {
+    let mut ticker = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        let Some(pm) = host.process_manager() else {
+            continue;
+        };
+
+        for status in run_health_checks(pm, HEALTH_PING_TIMEOUT).await {
+            host.emit("server-health-changed", &status);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Per-server retention of captured traffic
+//!
+//! By default a server's inspector history stays in memory across restarts
+//! of its process, holds `inspectorHistoryCap` messages, and every message is
+//! mirrored to `system_logs`. A retention entry in the settings changes that
+//! for one server: the history can be cleared whenever the process exits,
+//! given its own cap, and mirrored to SQLite in full, for errors only, or not
+//! at all.
+
+use crate::process_manager::McpTrafficEvent;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Which captured messages of a server are written to `system_logs`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryMirror {
+    /// Every message
+    #[default]
+    All,
+    /// JSON-RPC error responses, failed tool results and stderr
+    Errors,
+    /// Nothing; the history only lives in memory
+    Off,
+}
+
+impl HistoryMirror {
+    /// Whether a message (or stderr line, when `stderr`) is written to the database
+    pub fn keeps(self, stderr: bool, payload: Option<&Value>) -> bool {
+        match self {
+            HistoryMirror::All => true,
+            HistoryMirror::Off => false,
+            HistoryMirror::Errors => {
+                stderr
+                    || payload.is_some_and(|p| {
+                        p.get("error").is_some()
+                            || p.pointer("/result/isError").and_then(|e| e.as_bool()) == Some(true)
+                    })
+            }
+        }
+    }
+
+    /// Whether a traffic event is written to the database
+    pub fn keeps_event(self, event: &McpTrafficEvent) -> bool {
+        if self == HistoryMirror::All {
+            return true;
+        }
+        let payload: Option<Value> = serde_json::from_str(&event.content).ok();
+        self.keeps(event.direction == "STDERR", payload.as_ref())
+    }
+}
+
+/// How long a server's captured traffic is kept, and where
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRetention {
+    pub server_name: String,
+    /// Clear the in-memory history whenever the server's process exits
+    #[serde(default)]
+    pub clear_on_disconnect: bool,
+    /// Messages kept in memory, instead of `inspectorHistoryCap`
+    #[serde(default)]
+    pub history_cap: Option<usize>,
+    #[serde(default)]
+    pub mirror: HistoryMirror,
+}
+
+impl HistoryRetention {
+    /// The default behaviour: kept across restarts and mirrored in full
+    pub fn new(server_name: &str) -> Self {
+        Self {
+            server_name: server_name.to_string(),
+            clear_on_disconnect: false,
+            history_cap: None,
+            mirror: HistoryMirror::All,
+        }
+    }
+}
+
+/// Retention of `server_name` among `entries`, the default when it has none
+pub fn for_server(entries: &[HistoryRetention], server_name: &str) -> HistoryRetention {
+    entries
+        .iter()
+        .find(|entry| entry.server_name == server_name)
+        .cloned()
+        .unwrap_or_else(|| HistoryRetention::new(server_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(direction: &str, content: &str) -> McpTrafficEvent {
+        McpTrafficEvent {
+            server_id: "fs".to_string(),
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            direction: direction.to_string(),
+            content: content.to_string(),
+            message_id: "m1".to_string(),
+            level: None,
+        }
+    }
+
+    #[test]
+    fn test_errors_mirror_keeps_only_failures() {
+        let mirror = HistoryMirror::Errors;
+        assert!(mirror.keeps_event(&event(
+            "INCOMING",
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"nope"}}"#
+        )));
+        assert!(mirror.keeps_event(&event("STDERR", "listening on stdio")));
+        assert!(mirror.keeps(
+            false,
+            Some(&json!({"id": 2, "result": {"content": [], "isError": true}}))
+        ));
+        assert!(!mirror.keeps_event(&event(
+            "OUTGOING",
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#
+        )));
+        assert!(!mirror.keeps(false, Some(&json!({"id": 2, "result": {}}))));
+
+        assert!(HistoryMirror::All.keeps_event(&event("OUTGOING", "not json")));
+        assert!(!HistoryMirror::Off.keeps_event(&event("STDERR", "panic")));
+    }
+
+    #[test]
+    fn test_servers_without_an_entry_get_the_default() {
+        let entries = vec![HistoryRetention {
+            clear_on_disconnect: true,
+            mirror: HistoryMirror::Off,
+            ..HistoryRetention::new("scratch")
+        }];
+        assert!(for_server(&entries, "scratch").clear_on_disconnect);
+        assert_eq!(for_server(&entries, "fs"), HistoryRetention::new("fs"));
+    }
+}
@@ -0,0 +1,38 @@
+//! Host abstraction implemented by the application embedding the core
+//!
+//! Background tasks (stdio readers, watchdogs) need to emit events and reach
+//! shared state without knowing whether they run inside Tauri, a CLI, or a test.
+
+use crate::process_manager::ProcessManager;
+use crate::state::AppState;
+use serde::Serialize;
+
+/// Emits named events to whoever is listening (the webview, a log, a test)
+pub trait EventEmitter: Send + Sync {
+    /// Emit an already-serialized event payload
+    fn emit_value(&self, event: &str, payload: serde_json::Value);
+
+    /// Serialize and emit an event payload
+    fn emit<S: Serialize>(&self, event: &str, payload: S)
+    where
+        Self: Sized,
+    {
+        if let Ok(value) = serde_json::to_value(payload) {
+            self.emit_value(event, value);
+        }
+    }
+}
+
+/// Provides access to the shared state objects owned by the host
+pub trait StateProvider: Send + Sync {
+    /// Application state (config cache, inspector history)
+    fn app_state(&self) -> Option<&AppState>;
+
+    /// Process manager tracking spawned servers
+    fn process_manager(&self) -> Option<&ProcessManager>;
+}
+
+/// Everything a spawned background task needs from its host
+pub trait Host: EventEmitter + StateProvider + Clone + 'static {}
+
+impl<T> Host for T where T: EventEmitter + StateProvider + Clone + 'static {}
@@ -0,0 +1,120 @@
+//! Idle auto-stop for unused servers
+//!
+//! Every JSON-RPC message to or from a server updates its `last_traffic_at`
+//! (Synaptic's own pings don't count). With `idleTimeoutMins` set, servers
+//! that stay silent that long are stopped and remembered, so the next request
+//! sent through Synaptic starts them again from the same config entry.
+
+use crate::error::SynapticResult;
+use crate::host::Host;
+use crate::process_manager::{spawn_mcp_server, ProcessManager, ProcessState};
+use chrono::Utc;
+use serde::Serialize;
+use std::time::Duration;
+
+/// How often running servers are checked for inactivity
+pub const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Payload of the `server-idle-stopped` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleStopEvent {
+    pub server_name: String,
+    /// Seconds since the last message when the server was stopped
+    pub idle_secs: u64,
+}
+
+/// Names of running servers without traffic for at least `timeout`, with their idle time
+pub async fn idle_servers(pm: &ProcessManager, timeout: Duration) -> Vec<(String, Duration)> {
+    let now = Utc::now();
+    pm.statuses
+        .lock()
+        .await
+        .values()
+        .filter(|status| status.state == ProcessState::Running)
+        .filter_map(|status| {
+            let idle = (now - status.last_traffic_at?).to_std().ok()?;
+            (idle >= timeout).then(|| (status.server_name.clone(), idle))
+        })
+        .collect()
+}
+
+/// Stop every server idle for at least `timeout`, emitting `server-idle-stopped` for each
+pub async fn stop_idle_servers<H: Host>(
+    host: &H,
+    timeout: Duration,
+    grace: Duration,
+) -> Vec<IdleStopEvent> {
+    let Some(pm) = host.process_manager() else {
+        return Vec::new();
+    };
+
+    let mut stopped = Vec::new();
+    for (server_name, idle) in idle_servers(pm, timeout).await {
+        let Some(server) = pm
+            .processes
+            .lock()
+            .await
+            .get(&server_name)
+            .map(|process| process.server.clone())
+        else {
+            continue;
+        };
+        if let Err(e) = pm.kill_process(&server_name, grace).await {
+            eprintln!("Failed to stop idle server {}: {}", server_name, e);
+            continue;
+        }
+        pm.idle_stopped
+            .lock()
+            .await
+            .insert(server_name.clone(), server);
+
+        let event = IdleStopEvent {
+            server_name,
+            idle_secs: idle.as_secs(),
+        };
+        host.emit("server-idle-stopped", &event);
+        stopped.push(event);
+    }
+    stopped
+}
+
+/// Start a server again if it was stopped for inactivity
+///
+/// Returns the new PID, or `None` when the server wasn't idle-stopped.
+pub async fn wake_server<H: Host>(host: &H, server_name: &str) -> SynapticResult<Option<u32>> {
+    let Some(pm) = host.process_manager() else {
+        return Ok(None);
+    };
+    if pm.is_running(server_name).await {
+        return Ok(None);
+    }
+    let Some(server) = pm.idle_stopped.lock().await.remove(server_name) else {
+        return Ok(None);
+    };
+
+    let pid = spawn_mcp_server(host.clone(), pm, server_name.to_string(), server).await?;
+    Ok(Some(pid))
+}
+
+/// Background loop stopping servers idle longer than the configured timeout
+pub async fn run_idle_monitor<H: Host>(host: H) {
+    let mut ticker = tokio::time::interval(IDLE_CHECK_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        let settings = match host.app_state().map(|state| state.get_settings()) {
+            Some(Ok(settings)) => settings,
+            Some(Err(e)) => {
+                eprintln!("Failed to load settings for idle checks: {}", e);
+                continue;
+            }
+            None => continue,
+        };
+
+        if let Some(timeout) = settings.idle_timeout() {
+            stop_idle_servers(&host, timeout, settings.shutdown_grace()).await;
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Inspector module for capturing MCP server JSON-RPC traffic
+
+use crate::preview::PayloadPreview;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use uuid::Uuid;
+
+// ============================================
+// INSPECTOR DATA MODELS
+// ============================================
+
+/// Direction of JSON-RPC message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageDirection {
+    /// Client -> Server (request)
+    Request,
+    /// Server -> Client (response)
+    Response,
+}
+
+/// Captured JSON-RPC message for the inspector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InspectorMessage {
+    /// Unique message ID
+    pub id: String,
+
+    /// Timestamp of capture
+    pub timestamp: DateTime<Utc>,
+
+    /// Direction of message flow
+    pub direction: MessageDirection,
+
+    /// Server name this message belongs to
+    pub server_name: String,
+
+    /// Raw JSON-RPC payload
+    pub payload: serde_json::Value,
+
+    /// Parsed method name (if request)
+    pub method: Option<String>,
+
+    /// Duration in milliseconds (for responses matched to requests)
+    pub duration_ms: Option<u64>,
+
+    /// JSON-RPC / MCP schema problems found in the payload
+    #[serde(default)]
+    pub validation_errors: Vec<String>,
+
+    /// Pretty-printed payload with long strings shortened, for list rows
+    #[serde(default)]
+    pub preview: Option<PayloadPreview>,
+
+    /// The request this message belongs to was cancelled with `notifications/cancelled`
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+impl InspectorMessage {
+    /// Create a new request message
+    pub fn new_request(server_name: &str, payload: serde_json::Value) -> Self {
+        let method = payload.get("method").and_then(|m| m.as_str()).map(String::from);
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            direction: MessageDirection::Request,
+            server_name: server_name.to_string(),
+            preview: Some(PayloadPreview::of(&payload)),
+            payload,
+            method,
+            duration_ms: None,
+            validation_errors: Vec::new(),
+            cancelled: false,
+        }
+    }
+
+    /// Create a new response message
+    pub fn new_response(server_name: &str, payload: serde_json::Value) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            direction: MessageDirection::Response,
+            server_name: server_name.to_string(),
+            preview: Some(PayloadPreview::of(&payload)),
+            payload,
+            method: None,
+            duration_ms: None,
+            validation_errors: Vec::new(),
+            cancelled: false,
+        }
+    }
+}
+
+/// Server-side filters for `get_inspector_messages`; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MessageFilter {
+    pub direction: Option<MessageDirection>,
+    /// Start of the method name, e.g. `tools/`
+    pub method_prefix: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Only JSON-RPC error responses
+    pub errors_only: bool,
+}
+
+impl MessageFilter {
+    pub fn matches(&self, message: &InspectorMessage) -> bool {
+        self.direction
+            .as_ref()
+            .is_none_or(|direction| &message.direction == direction)
+            && self.method_prefix.as_deref().is_none_or(|prefix| {
+                message
+                    .method
+                    .as_deref()
+                    .is_some_and(|method| method.starts_with(prefix))
+            })
+            && self.from.is_none_or(|from| message.timestamp >= from)
+            && self.to.is_none_or(|to| message.timestamp <= to)
+            && (!self.errors_only || message.payload.get("error").is_some())
+    }
+}
+
+/// Most recent captured messages of one server, oldest first
+///
+/// Holds `cap` messages. Older ones are evicted once they've been written to
+/// `system_logs`; if persistence falls behind, up to twice `cap` are kept
+/// before the oldest go regardless.
+#[derive(Debug, Default)]
+pub struct InspectorHistory {
+    messages: VecDeque<InspectorMessage>,
+    /// Ids of messages still waiting to be persisted
+    unpersisted: HashSet<String>,
+    cap: usize,
+}
+
+impl InspectorHistory {
+    /// Append a message; `persisting` says whether it will be written to the database
+    pub fn push(&mut self, message: InspectorMessage, cap: usize, persisting: bool) {
+        self.cap = cap.max(1);
+        if persisting {
+            self.unpersisted.insert(message.id.clone());
+        }
+        self.messages.push_back(message);
+        self.evict();
+    }
+
+    /// Note messages written to the database, letting them be evicted
+    pub fn mark_persisted<'a>(&mut self, ids: impl IntoIterator<Item = &'a str>) {
+        for id in ids {
+            self.unpersisted.remove(id);
+        }
+        self.evict();
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &InspectorMessage> {
+        self.messages.iter()
+    }
+
+    /// Flag the messages carrying JSON-RPC id `request_id` as cancelled
+    pub fn mark_cancelled(&mut self, request_id: &serde_json::Value) {
+        for message in &mut self.messages {
+            if message.payload.get("id") == Some(request_id) {
+                message.cancelled = true;
+            }
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.messages.len() > self.cap {
+            let pending = self
+                .messages
+                .front()
+                .is_some_and(|oldest| self.unpersisted.contains(&oldest.id));
+            if pending && self.messages.len() <= self.cap.saturating_mul(2) {
+                break;
+            }
+            if let Some(oldest) = self.messages.pop_front() {
+                self.unpersisted.remove(&oldest.id);
+            }
+        }
+    }
+}
+
+/// Inspector session state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InspectorSession {
+    pub server_name: String,
+    pub started_at: DateTime<Utc>,
+    pub is_active: bool,
+    pub message_count: usize,
+}
+
+impl InspectorSession {
+    pub fn new(server_name: &str) -> Self {
+        Self {
+            server_name: server_name.to_string(),
+            started_at: Utc::now(),
+            is_active: true,
+            message_count: 0,
+        }
+    }
+}
+
+// ============================================
+// MESSAGE CLASSIFICATION
+// ============================================
+
+// Traffic of servers Synaptic spawns is captured by the process manager;
+// traffic between a client and the servers it runs itself goes through the
+// capture shim (see `shim`).
+
+/// Parse a JSON-RPC message and determine its type
+pub fn parse_jsonrpc_message(raw: &str) -> Option<(MessageDirection, serde_json::Value)> {
+    let value: serde_json::Value = serde_json::from_str(raw).ok()?;
+
+    // If it has a "method" field, it's a request
+    if value.get("method").is_some() {
+        Some((MessageDirection::Request, value))
+    // If it has a "result" or "error" field, it's a response
+    } else if value.get("result").is_some() || value.get("error").is_some() {
+        Some((MessageDirection::Response, value))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let json = r#"{"jsonrpc":"2.0","method":"tools/list","id":1}"#;
+        let result = parse_jsonrpc_message(json);
+        assert!(result.is_some());
+        let (direction, _) = result.unwrap();
+        assert_eq!(direction, MessageDirection::Request);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let json = r#"{"jsonrpc":"2.0","result":{"tools":[]},"id":1}"#;
+        let result = parse_jsonrpc_message(json);
+        assert!(result.is_some());
+        let (direction, _) = result.unwrap();
+        assert_eq!(direction, MessageDirection::Response);
+    }
+
+    #[test]
+    fn test_message_filter() {
+        let request = InspectorMessage::new_request(
+            "echo",
+            serde_json::json!({"jsonrpc":"2.0","id":1,"method":"tools/call"}),
+        );
+        let error = InspectorMessage::new_response(
+            "echo",
+            serde_json::json!({"jsonrpc":"2.0","id":1,"error":{"code":-32601}}),
+        );
+
+        let tools = MessageFilter {
+            method_prefix: Some("tools/".to_string()),
+            ..Default::default()
+        };
+        assert!(tools.matches(&request));
+        assert!(!tools.matches(&error));
+
+        let errors = MessageFilter {
+            direction: Some(MessageDirection::Response),
+            errors_only: true,
+            ..Default::default()
+        };
+        assert!(errors.matches(&error));
+        assert!(!errors.matches(&request));
+
+        let later = MessageFilter {
+            from: Some(Utc::now() + chrono::Duration::minutes(1)),
+            ..Default::default()
+        };
+        assert!(!later.matches(&request));
+    }
+
+    #[test]
+    fn test_history_evicts_persisted_messages() {
+        let message =
+            |n: i64| InspectorMessage::new_response("echo", serde_json::json!({ "id": n }));
+        let mut history = InspectorHistory::default();
+
+        let first: Vec<_> = (0..3).map(message).collect();
+        for m in &first {
+            history.push(m.clone(), 2, true);
+        }
+        // Nothing persisted yet: kept past the cap
+        assert_eq!(history.len(), 3);
+
+        history.mark_persisted(first.iter().map(|m| m.id.as_str()));
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.iter().next().unwrap().id, first[1].id);
+
+        // Persistence stalled: bounded at twice the cap
+        for n in 3..10 {
+            history.push(message(n), 2, true);
+        }
+        assert_eq!(history.len(), 4);
+
+        let mut unlogged = InspectorHistory::default();
+        for n in 0..5 {
+            unlogged.push(message(n), 2, false);
+        }
+        assert_eq!(unlogged.len(), 2);
+    }
+}
@@ -0,0 +1,1048 @@
+//! Installing registry entries that have to be fetched and built first
+//!
+//! `npx` and `uvx` entries fetch their package when they start and remote
+//! ones need nothing, so those are only added to the config. A `git_clone`
+//! entry is shallow-cloned into `servers/<name>` under the Synaptic data
+//! directory (at the tag or branch given as its version), its build command
+//! is run in the checkout, and its default config is pointed at the result:
+//! relative paths that exist in the checkout (`dist/index.js`) become
+//! absolute and `cwd` is set to the checkout. A `binary` entry is downloaded
+//! into `bin/<name>`, checked against the SHA-256 its entry declares (and its
+//! Ed25519 signature when it has one, which must come from a key pinned in
+//! Synaptic or trusted in the settings) and made executable. That exact file
+//! is then allowed for that server alone, leaving the whitelist and the
+//! user's denials untouched, and its config runs it from there. Downloads stall out
+//! after `DOWNLOAD_STALL_TIMEOUT` and stop at `MAX_DOWNLOAD_BYTES`.
+//!
+//! An entry whose required env values weren't given (or don't pass their
+//! validation) isn't installed; the install reports what needs configuring.
+//! Otherwise every install runs as a job: its `install-progress` events carry the job
+//! ID, the stage, a percentage where one is known (git's object transfer, a
+//! download with a known size) and each line git and the build print, and
+//! `cancel_install` stops it, killing git or the build along with everything
+//! they started. The caller may pick the job ID so it can cancel before the
+//! install returns. A failed or cancelled install leaves no directory behind.
+//!
+//! Uninstalling is the inverse: the running process is stopped, the config
+//! entry goes (audited as `uninstall`), the managed directory and
+//! binary approval of an install are removed, and with `purge` the server's
+//! captured history and log files are deleted too.
+
+use crate::audit::AuditAction;
+use crate::config::{self, McpServer, ProcessPriority};
+use crate::database::Database;
+use crate::error::{SynapticError, SynapticResult};
+use crate::host::{EventEmitter, Host};
+use crate::metadata::SynapticMetadata;
+use crate::process_tree::{self, ProcessTree};
+use crate::registry::{self, InstallMethod, MissingEnv, RegistryServer};
+use crate::server_logs;
+use crate::settings::SynapticSettings;
+use crate::state::AppState;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use futures::StreamExt;
+use reqwest::{Client, Url};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio_util::codec::{AnyDelimiterCodec, FramedRead};
+use tokio_util::sync::CancellationToken;
+
+/// Bytes downloaded between two progress events
+const DOWNLOAD_PROGRESS_STEP: usize = 256 * 1024;
+
+/// Largest file a download accepts
+const MAX_DOWNLOAD_BYTES: u64 = 512 * 1024 * 1024;
+
+/// How long a download may take to connect
+const DOWNLOAD_CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// How long a download may go without receiving anything
+const DOWNLOAD_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Ed25519 public keys (base64) Synaptic trusts to sign binaries
+///
+/// The settings' `trusted_signing_keys` add to these. A registry entry's own
+/// `public_key` only picks one of them: a key that came with the entry proves
+/// nothing about who built the binary.
+pub const PINNED_SIGNING_KEYS: &[&str] = &[];
+
+/// Tables holding a server's captured history, cleared by a purge
+///
+/// The audit log stays: it records the uninstall itself.
+const HISTORY_TABLES: &[&str] = &[
+    "system_logs",
+    "sessions",
+    "process_crashes",
+    "server_capabilities",
+    "tool_stats",
+    "bookmarks",
+    "fuzz_reports",
+    "load_test_results",
+];
+
+/// Step an install is at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallStage {
+    Cloning,
+    Building,
+    Downloading,
+    Verifying,
+    Extracting,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+/// Progress of an install, emitted as `install-progress`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallProgress {
+    pub job_id: String,
+    pub server_name: String,
+    pub stage: InstallStage,
+    /// Output line, command being run, or the error of a failed install
+    pub message: Option<String>,
+    /// How far the stage is, when that's known
+    pub percent: Option<f64>,
+    /// Bytes downloaded so far, while downloading
+    pub downloaded: Option<u64>,
+    /// Size of the download, when the server sent one
+    pub total: Option<u64>,
+}
+
+/// Result of an install
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum InstallOutcome {
+    /// Added to the config under `name`
+    Installed { name: String },
+    /// Nothing was installed: these env values have to be given first
+    NeedsConfiguration { missing: Vec<MissingEnv> },
+}
+
+/// A binary Synaptic installed, allowed to be spawned by the server it was installed as
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedExecutable {
+    pub server_name: String,
+    /// Canonical path of the binary
+    pub path: PathBuf,
+}
+
+/// Installs in progress, by job ID
+#[derive(Default)]
+pub struct InstallJobs {
+    jobs: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl InstallJobs {
+    /// Track a new install under `id`, or a fresh ID when none is given
+    ///
+    /// A caller that picks the ID can cancel the install while it still runs.
+    pub fn start(&self, id: Option<String>) -> SynapticResult<InstallJob> {
+        let job = InstallJob {
+            id: id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            cancel: CancellationToken::new(),
+        };
+        let mut jobs = self.jobs.lock().unwrap();
+        if jobs.contains_key(&job.id) {
+            return Err(SynapticError::ProcessError(format!(
+                "Install job {} is already running",
+                job.id
+            )));
+        }
+        jobs.insert(job.id.clone(), job.cancel.clone());
+        Ok(job)
+    }
+
+    /// Cancel a running install; `false` when no such install is running
+    pub fn cancel(&self, job_id: &str) -> bool {
+        match self.jobs.lock().unwrap().get(job_id) {
+            Some(cancel) => {
+                cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn finish(&self, job_id: &str) {
+        self.jobs.lock().unwrap().remove(job_id);
+    }
+}
+
+/// One tracked install
+#[derive(Debug, Clone)]
+pub struct InstallJob {
+    pub id: String,
+    cancel: CancellationToken,
+}
+
+impl InstallJob {
+    /// Wait until the job is cancelled, for the error to end it with
+    pub(crate) async fn cancelled(&self) -> SynapticError {
+        self.cancel.cancelled().await;
+        SynapticError::ProcessError("Install cancelled".to_string())
+    }
+}
+
+/// Emits the `install-progress` events of one job
+pub struct Reporter<'a, E: EventEmitter> {
+    emitter: &'a E,
+    job_id: &'a str,
+    server_name: &'a str,
+}
+
+impl<'a, E: EventEmitter> Reporter<'a, E> {
+    pub fn new(emitter: &'a E, job_id: &'a str, server_name: &'a str) -> Self {
+        Self {
+            emitter,
+            job_id,
+            server_name,
+        }
+    }
+
+    pub(crate) fn stage(&self, stage: InstallStage, message: Option<String>) {
+        self.emit(stage, message, None, None, None);
+    }
+
+    /// Report how the job ended: done, failed, or cancelled
+    pub(crate) fn finish<T>(&self, job: &InstallJob, result: &SynapticResult<T>) {
+        match result {
+            Ok(_) => self.stage(InstallStage::Done, None),
+            Err(_) if job.cancel.is_cancelled() => {
+                self.stage(InstallStage::Cancelled, None);
+            }
+            Err(e) => self.stage(InstallStage::Failed, Some(e.to_string())),
+        }
+    }
+
+    fn emit(
+        &self,
+        stage: InstallStage,
+        message: Option<String>,
+        percent: Option<f64>,
+        downloaded: Option<u64>,
+        total: Option<u64>,
+    ) {
+        self.emitter.emit(
+            "install-progress",
+            InstallProgress {
+                job_id: self.job_id.to_string(),
+                server_name: self.server_name.to_string(),
+                stage,
+                message,
+                percent,
+                downloaded,
+                total,
+            },
+        );
+    }
+}
+
+/// What an uninstall removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UninstallReport {
+    pub server_name: String,
+    /// Whether the server was running and got stopped
+    pub stopped: bool,
+    /// Cloned repos and downloaded binaries that were deleted
+    pub removed_paths: Vec<PathBuf>,
+    /// Rows of captured history deleted by a purge
+    pub purged_entries: usize,
+}
+
+/// Directory servers are cloned into
+pub fn get_installs_dir() -> SynapticResult<PathBuf> {
+    Ok(config::get_synaptic_data_dir()?.join("servers"))
+}
+
+/// Directory server binaries are downloaded into
+pub fn get_bin_dir() -> SynapticResult<PathBuf> {
+    Ok(config::get_synaptic_data_dir()?.join("bin"))
+}
+
+/// Install a registry entry as `name` (by default the last part of its ID)
+/// with the `env` values given
+///
+/// `version` pins the package of `npx`/`uvx` entries and picks the tag or
+/// branch a `git_clone` entry is cloned at. Binaries have no versions. The
+/// install is tracked in `state.install_jobs` while it runs, under `job_id`
+/// when the caller gives one.
+pub async fn install<E: EventEmitter>(
+    emitter: &E,
+    state: &AppState,
+    server_id: &str,
+    name: Option<String>,
+    version: Option<&str>,
+    env: &HashMap<String, String>,
+    job_id: Option<String>,
+) -> SynapticResult<InstallOutcome> {
+    let entry = registry::find_server(state, server_id)
+        .ok_or_else(|| SynapticError::RegistryError(format!("Server not found: {}", server_id)))?;
+    let missing = entry.missing_env(env)?;
+    if !missing.is_empty() {
+        return Ok(InstallOutcome::NeedsConfiguration { missing });
+    }
+
+    let job = state.install_jobs.start(job_id)?;
+    let name = name.unwrap_or_else(|| entry.install_name());
+    let reporter = Reporter::new(emitter, &job.id, &name);
+    let installed = run_install(&reporter, state, &job, &entry, &name, version, env).await;
+    state.install_jobs.finish(&job.id);
+    reporter.finish(&job, &installed);
+    installed.map(|()| InstallOutcome::Installed { name })
+}
+
+async fn run_install<E: EventEmitter>(
+    reporter: &Reporter<'_, E>,
+    state: &AppState,
+    job: &InstallJob,
+    entry: &RegistryServer,
+    name: &str,
+    version: Option<&str>,
+    env: &HashMap<String, String>,
+) -> SynapticResult<()> {
+    let root = match &entry.install_method {
+        InstallMethod::GitClone { .. } => get_installs_dir()?,
+        InstallMethod::Binary { .. } if version.is_some() => {
+            return Err(SynapticError::RegistryError(format!(
+                "{} is a binary download, so it has no versions",
+                entry.name
+            )))
+        }
+        InstallMethod::Binary { .. } => get_bin_dir()?,
+        _ => {
+            registry::install(state, &entry.id, Some(name.to_string()), version, env)?;
+            return Ok(());
+        }
+    };
+
+    if state.get_config()?.mcp_servers.contains_key(name) {
+        return Err(SynapticError::ServerAlreadyExists(name.to_string()));
+    }
+    if !is_file_name(name) {
+        return Err(SynapticError::RegistryError(format!(
+            "{:?} can't be used as a directory name",
+            name
+        )));
+    }
+    let dir = root.join(name);
+    if dir.exists() {
+        return Err(SynapticError::IoError(format!(
+            "{} already exists; remove it or install under another name",
+            dir.display()
+        )));
+    }
+
+    let fetched = async {
+        let config = match &entry.install_method {
+            InstallMethod::GitClone { url, build_command } => {
+                fetch_checkout(reporter, url, build_command.as_deref(), version, &dir).await?;
+                checkout_config(&entry.default_config, &dir)
+            }
+            InstallMethod::Binary {
+                url,
+                sha256,
+                signature,
+                public_key,
+            } => {
+                let trusted_keys = trusted_signing_keys(&state.get_settings()?);
+                let binary = fetch_binary(
+                    reporter,
+                    url,
+                    sha256,
+                    signature.as_deref(),
+                    public_key.as_deref(),
+                    &trusted_keys,
+                    &dir,
+                )
+                .await?;
+                // Canonical, as that's the exact file the approval is for
+                McpServer {
+                    command: fs::canonicalize(&binary)?.to_string_lossy().into_owned(),
+                    ..entry.default_config.clone()
+                }
+            }
+            _ => entry.default_config.clone(),
+        };
+        let config = registry::with_env(config, env);
+        // Nothing awaits past this point, so a cancelled install never gets this far
+        let managed = matches!(entry.install_method, InstallMethod::Binary { .. });
+        if managed {
+            state.set_managed_executable(name, Some(PathBuf::from(&config.command)))?;
+        }
+        let added = registry::add_installed(state, name, &entry.id, version, Some(&dir), config);
+        if added.is_err() && managed {
+            if let Err(e) = state.set_managed_executable(name, None) {
+                eprintln!("Failed to drop the approval of {}'s binary: {}", name, e);
+            }
+        }
+        added
+    };
+    // Dropping the fetch kills git or the build mid-run
+    let installed = tokio::select! {
+        installed = fetched => installed,
+        cancelled = job.cancelled() => Err(cancelled),
+    };
+
+    if installed.is_err() {
+        if let Err(cleanup) = remove_dir(&dir) {
+            eprintln!("Failed to remove {}: {}", dir.display(), cleanup);
+        }
+    }
+    installed
+}
+
+/// Remove a server with everything it installed, and its history when `purge` is set
+pub async fn uninstall<H: Host>(
+    host: H,
+    name: &str,
+    purge: bool,
+) -> SynapticResult<UninstallReport> {
+    let state = host
+        .app_state()
+        .ok_or_else(|| SynapticError::ServerNotFound(name.to_string()))?;
+    if !state.get_config()?.mcp_servers.contains_key(name) {
+        return Err(SynapticError::ServerNotFound(name.to_string()));
+    }
+    // Read before the removal drops the server's metadata
+    let install_dir = SynapticMetadata::load()?.server(name).install_dir;
+
+    // Stopped first: once its config is gone nothing would know to stop it.
+    // A read-only config would refuse the removal, so it's left running then
+    state.ensure_writable("remove_server")?;
+    let mut stopped = false;
+    if let Some(pm) = host.process_manager() {
+        if pm.is_running(name).await {
+            let grace = state.get_settings()?.shutdown_grace();
+            pm.kill_process(name, grace).await?;
+            stopped = true;
+        }
+    }
+    state.remove_server_as(name, AuditAction::Uninstall)?;
+
+    let mut removed_paths = Vec::new();
+    state.set_managed_executable(name, None)?;
+    if let Some(dir) = install_dir.filter(|dir| is_managed(dir)) {
+        if remove_dir(&dir)? {
+            removed_paths.push(dir);
+        }
+    }
+
+    let mut purged_entries = 0;
+    if purge {
+        state.clear_inspector_messages(name);
+        if let Ok(db) = state.database() {
+            purged_entries = purge_history(db, name)?;
+        }
+        let log_dir = server_logs::get_server_logs_dir()?.join(server_logs::log_dir_name(name));
+        remove_dir(&log_dir)?;
+    }
+
+    Ok(UninstallReport {
+        server_name: name.to_string(),
+        stopped,
+        removed_paths,
+        purged_entries,
+    })
+}
+
+/// Delete a directory tree; `false` when there was none
+fn remove_dir(dir: &Path) -> std::io::Result<bool> {
+    match fs::remove_dir_all(dir) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Whether `dir` is one Synaptic installed into (metadata can be edited by hand)
+fn is_managed(dir: &Path) -> bool {
+    [get_installs_dir(), get_bin_dir()]
+        .into_iter()
+        .flatten()
+        .any(|root| dir.parent() == Some(root.as_path()))
+}
+
+/// Delete a server's rows from every history table, returning how many went
+pub fn purge_history(db: &Database, server_name: &str) -> SynapticResult<usize> {
+    let mut conn = db.conn();
+    let tx = conn.transaction()?;
+    let mut deleted = 0;
+    for table in HISTORY_TABLES {
+        deleted += tx.execute(
+            &format!("DELETE FROM {} WHERE server_name = ?1", table),
+            [server_name],
+        )?;
+    }
+    tx.commit()?;
+    Ok(deleted)
+}
+
+/// Shallow-clone `url` into `dir` (at `git_ref` when given) and run `build_command` there
+pub async fn fetch_checkout<E: EventEmitter>(
+    reporter: &Reporter<'_, E>,
+    url: &str,
+    build_command: Option<&str>,
+    git_ref: Option<&str>,
+    dir: &Path,
+) -> SynapticResult<()> {
+    if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    reporter.stage(InstallStage::Cloning, Some(format!("Cloning {}", url)));
+    let mut git = Command::new("git");
+    git.args(["clone", "--progress", "--depth", "1"]);
+    if let Some(git_ref) = git_ref {
+        git.arg("--branch").arg(git_ref);
+    }
+    // Private repos fail instead of waiting on a credential prompt nobody sees
+    git.arg("--")
+        .arg(url)
+        .arg(dir)
+        .env("GIT_TERMINAL_PROMPT", "0");
+    run_streamed(reporter, InstallStage::Cloning, git).await?;
+
+    if let Some(build_command) = build_command.filter(|command| !command.trim().is_empty()) {
+        reporter.stage(InstallStage::Building, Some(build_command.to_string()));
+        let mut build = shell(build_command);
+        build.current_dir(dir);
+        run_streamed(reporter, InstallStage::Building, build).await?;
+    }
+    Ok(())
+}
+
+/// Keys binary signatures are checked against: the pinned ones and the user's
+pub fn trusted_signing_keys(settings: &SynapticSettings) -> Vec<String> {
+    PINNED_SIGNING_KEYS
+        .iter()
+        .map(|key| key.to_string())
+        .chain(settings.trusted_signing_keys.iter().cloned())
+        .collect()
+}
+
+/// Download a binary into `dir`, verify it and make it executable
+pub async fn fetch_binary<E: EventEmitter>(
+    reporter: &Reporter<'_, E>,
+    url: &str,
+    sha256: &str,
+    signature: Option<&str>,
+    public_key: Option<&str>,
+    trusted_keys: &[String],
+    dir: &Path,
+) -> SynapticResult<PathBuf> {
+    let parsed = Url::parse(url).map_err(|e| {
+        SynapticError::RegistryError(format!("Invalid download URL {}: {}", url, e))
+    })?;
+    // Named after the URL's file so the whitelist shows what it is
+    let file_name = parsed
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| is_file_name(segment))
+        .unwrap_or(reporter.server_name)
+        .to_string();
+
+    let bytes = download(reporter, parsed).await?;
+    reporter.stage(InstallStage::Verifying, None);
+    verify_binary(&bytes, sha256, signature, public_key, trusted_keys)?;
+
+    fs::create_dir_all(dir)?;
+    let path = dir.join(file_name);
+    fs::write(&path, &bytes)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+/// Download a file into memory, reporting how far it got
+///
+/// Fails when the connection stalls or the file is larger than `MAX_DOWNLOAD_BYTES`.
+pub(crate) async fn download<E: EventEmitter>(
+    reporter: &Reporter<'_, E>,
+    url: Url,
+) -> SynapticResult<Vec<u8>> {
+    let download_error =
+        |e: reqwest::Error| SynapticError::RegistryError(format!("{}: {}", url, e));
+    let too_large = || {
+        SynapticError::RegistryError(format!(
+            "{} is larger than {} MiB",
+            url,
+            MAX_DOWNLOAD_BYTES / (1024 * 1024)
+        ))
+    };
+    reporter.stage(
+        InstallStage::Downloading,
+        Some(format!("Downloading {}", url)),
+    );
+    let response = Client::builder()
+        .connect_timeout(DOWNLOAD_CONNECT_TIMEOUT)
+        .read_timeout(DOWNLOAD_STALL_TIMEOUT)
+        .build()
+        .map_err(download_error)?
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(download_error)?;
+    let total = response.content_length();
+    if total.is_some_and(|total| total > MAX_DOWNLOAD_BYTES) {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::with_capacity(total.unwrap_or_default() as usize);
+    let mut reported = 0;
+    let mut chunks = response.bytes_stream();
+    while let Some(chunk) = chunks.next().await {
+        bytes.extend_from_slice(&chunk.map_err(download_error)?);
+        if bytes.len() as u64 > MAX_DOWNLOAD_BYTES {
+            return Err(too_large());
+        }
+        let finished = total == Some(bytes.len() as u64);
+        if bytes.len() - reported >= DOWNLOAD_PROGRESS_STEP || finished {
+            reported = bytes.len();
+            let downloaded = reported as u64;
+            let percent = total
+                .filter(|&total| total > 0)
+                .map(|total| downloaded as f64 * 100.0 / total as f64);
+            reporter.emit(
+                InstallStage::Downloading,
+                None,
+                percent,
+                Some(downloaded),
+                total,
+            );
+        }
+    }
+    Ok(bytes)
+}
+
+/// Check a download against its declared SHA-256 and, when given, its Ed25519 signature
+///
+/// The signature has to come from one of `trusted_keys`; `public_key`, when
+/// the entry names one, must be among them and is the only one tried.
+pub fn verify_binary(
+    bytes: &[u8],
+    sha256: &str,
+    signature: Option<&str>,
+    public_key: Option<&str>,
+    trusted_keys: &[String],
+) -> SynapticResult<()> {
+    let actual: String = Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if !actual.eq_ignore_ascii_case(sha256.trim()) {
+        return Err(SynapticError::RegistryError(format!(
+            "Checksum mismatch: expected {}, got {}",
+            sha256, actual
+        )));
+    }
+
+    let signature = match (signature, public_key) {
+        (None, None) => return Ok(()),
+        (Some(signature), _) => signature,
+        (None, Some(_)) => {
+            return Err(SynapticError::RegistryError(
+                "The entry names a signing key but has no signature".to_string(),
+            ))
+        }
+    };
+    let decode = |value: &str| {
+        STANDARD.decode(value.trim()).map_err(|e| {
+            SynapticError::RegistryError(format!("Invalid base64 in signature: {}", e))
+        })
+    };
+    let signature = decode(signature)?;
+    let mut keys: Vec<Vec<u8>> = trusted_keys
+        .iter()
+        .map(|key| decode(key))
+        .collect::<SynapticResult<_>>()?;
+    if let Some(public_key) = public_key {
+        let public_key = decode(public_key)?;
+        if !keys.contains(&public_key) {
+            return Err(SynapticError::RegistryError(
+                "The binary is signed with a key Synaptic doesn't trust".to_string(),
+            ));
+        }
+        keys = vec![public_key];
+    }
+    if keys.is_empty() {
+        return Err(SynapticError::RegistryError(
+            "The binary is signed, but no signing keys are trusted".to_string(),
+        ));
+    }
+    keys.iter()
+        .any(|key| {
+            UnparsedPublicKey::new(&ED25519, key)
+                .verify(bytes, &signature)
+                .is_ok()
+        })
+        .then_some(())
+        .ok_or_else(|| SynapticError::RegistryError("Signature verification failed".to_string()))
+}
+
+/// An entry's default config pointed at its checkout in `dir`
+pub fn checkout_config(default_config: &McpServer, dir: &Path) -> McpServer {
+    let mut config = default_config.clone();
+    let in_checkout = |value: &str| {
+        let path = Path::new(value);
+        (!value.starts_with('-') && path.is_relative() && dir.join(path).exists())
+            .then(|| dir.join(path).to_string_lossy().into_owned())
+    };
+    if let Some(command) = in_checkout(&config.command) {
+        config.command = command;
+    }
+    for arg in &mut config.args {
+        if let Some(path) = in_checkout(arg) {
+            *arg = path;
+        }
+    }
+    let cwd = match config.cwd.as_deref() {
+        Some(cwd) if Path::new(cwd).is_absolute() => cwd.to_string(),
+        Some(cwd) => dir.join(cwd).to_string_lossy().into_owned(),
+        None => dir.to_string_lossy().into_owned(),
+    };
+    config.cwd = Some(cwd);
+    config
+}
+
+/// Whether `value` names a single file, with no separators or `..`
+fn is_file_name(value: &str) -> bool {
+    let mut components = Path::new(value).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+/// A command line run by the platform shell
+fn shell(command_line: &str) -> Command {
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    };
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    command.arg(command_line);
+    command
+}
+
+/// Kills everything a command started when dropped before the command exited
+///
+/// `kill_on_drop` only reaches the command itself, while a cancelled build
+/// has to take the compilers and package managers it started down with it.
+struct TreeGuard {
+    tree: ProcessTree,
+    exited: bool,
+}
+
+impl Drop for TreeGuard {
+    fn drop(&mut self) {
+        if !self.exited {
+            self.tree.kill();
+        }
+    }
+}
+
+/// Run a command, emitting each line of its output, and fail when it exits unsuccessfully
+///
+/// Lines end at `\r` too, so git's in-place progress comes through as it updates.
+/// The command runs in its own process group (a job object on Windows), so
+/// dropping the run kills the whole tree.
+pub(crate) async fn run_streamed<E: EventEmitter>(
+    reporter: &Reporter<'_, E>,
+    stage: InstallStage,
+    mut command: Command,
+) -> SynapticResult<()> {
+    let program = command
+        .as_std()
+        .get_program()
+        .to_string_lossy()
+        .into_owned();
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    process_tree::configure(&mut command, ProcessPriority::Normal);
+    let mut child = command.spawn().map_err(|e| match e.kind() {
+        ErrorKind::NotFound => SynapticError::RuntimeNotFound(program.clone()),
+        _ => SynapticError::ProcessError(format!("Failed to run {}: {}", program, e)),
+    })?;
+    let mut tree = child.id().map(|pid| TreeGuard {
+        tree: ProcessTree::attach(&child, pid),
+        exited: false,
+    });
+
+    let lines = || AnyDelimiterCodec::new(b"\r\n".to_vec(), Vec::new());
+    let stdout = child.stdout.take().expect("Failed to capture stdout");
+    let stderr = child.stderr.take().expect("Failed to capture stderr");
+    let mut output = futures::stream::select(
+        FramedRead::new(stdout, lines()),
+        FramedRead::new(stderr, lines()),
+    );
+    let mut last_line = None;
+    while let Some(Ok(line)) = output.next().await {
+        let line = String::from_utf8_lossy(&line).trim_end().to_string();
+        if line.trim().is_empty() {
+            continue;
+        }
+        reporter.emit(stage, Some(line.clone()), git_percent(&line), None, None);
+        last_line = Some(line);
+    }
+
+    let status = child.wait().await?;
+    if let Some(tree) = &mut tree {
+        tree.exited = true;
+    }
+    if status.success() {
+        return Ok(());
+    }
+    Err(SynapticError::ProcessError(match last_line {
+        Some(line) => format!("{} failed ({}): {}", program, status, line),
+        None => format!("{} failed ({})", program, status),
+    }))
+}
+
+/// Percentage of a git `Receiving objects:  45% (450/1000)` progress line
+fn git_percent(line: &str) -> Option<f64> {
+    let rest = line.strip_prefix("Receiving objects:")?.trim_start();
+    rest.split_once('%')?.0.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkout_config_points_into_the_checkout() {
+        let dir = std::env::temp_dir().join(format!("synaptic-checkout-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("dist")).unwrap();
+        fs::write(dir.join("dist/index.js"), "").unwrap();
+
+        let config = checkout_config(
+            &McpServer {
+                command: "node".to_string(),
+                args: vec![
+                    "dist/index.js".to_string(),
+                    "--root".to_string(),
+                    "data".to_string(),
+                ],
+                ..Default::default()
+            },
+            &dir,
+        );
+        assert_eq!(config.command, "node");
+        assert_eq!(
+            config.args,
+            vec![
+                dir.join("dist/index.js").to_string_lossy().into_owned(),
+                "--root".to_string(),
+                "data".to_string()
+            ]
+        );
+        assert_eq!(config.cwd, Some(dir.to_string_lossy().into_owned()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reads_git_transfer_progress() {
+        assert_eq!(
+            git_percent("Receiving objects:  45% (450/1000), 1.2 MiB | 2.4 MiB/s"),
+            Some(45.0)
+        );
+        assert_eq!(git_percent("Resolving deltas: 100% (12/12)"), None);
+        assert_eq!(git_percent("Cloning into 'demo'..."), None);
+    }
+
+    #[test]
+    fn test_cancelling_unknown_jobs_does_nothing() {
+        let jobs = InstallJobs::default();
+        let job = jobs.start(None).unwrap();
+        assert!(!jobs.cancel("nope"));
+        assert!(jobs.cancel(&job.id));
+        assert!(job.cancel.is_cancelled());
+        jobs.finish(&job.id);
+        assert!(!jobs.cancel(&job.id));
+
+        // A caller-chosen ID can be cancelled, but not reused while it runs
+        let job = jobs.start(Some("mine".to_string())).unwrap();
+        assert_eq!(job.id, "mine");
+        assert!(jobs.start(Some("mine".to_string())).is_err());
+        assert!(jobs.cancel("mine"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_a_dropped_build_takes_what_it_started_with_it() {
+        struct Lines(tokio::sync::mpsc::UnboundedSender<String>);
+        impl EventEmitter for Lines {
+            fn emit_value(&self, _event: &str, payload: serde_json::Value) {
+                if let Some(line) = payload["message"].as_str() {
+                    let _ = self.0.send(line.to_string());
+                }
+            }
+        }
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let emitter = Lines(tx);
+        let reporter = Reporter::new(&emitter, "job", "demo");
+        // The build backgrounds a grandchild, which a plain child kill would miss
+        let build = run_streamed(
+            &reporter,
+            InstallStage::Building,
+            shell("sleep 30 & echo $!; wait"),
+        );
+        let grandchild: i32 = tokio::select! {
+            _ = build => panic!("the build should still be running"),
+            line = rx.recv() => line.unwrap().trim().parse().unwrap(),
+        };
+
+        // Gone, or a zombie waiting for whoever adopted it to reap it
+        let alive = || {
+            fs::read_to_string(format!("/proc/{}/stat", grandchild))
+                .map(|stat| !stat.contains(") Z "))
+                .unwrap_or(false)
+        };
+        for _ in 0..50 {
+            if !alive() {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("the build's grandchild survived the cancel");
+    }
+
+    #[test]
+    fn test_installing_a_binary_named_node_leaves_the_users_node_decisions_alone() {
+        use crate::process_manager::is_command_allowed_by;
+        use crate::settings::SynapticSettings;
+
+        let dir = std::env::temp_dir().join(format!("synaptic-bin-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let binary = {
+            fs::write(dir.join("node"), "").unwrap();
+            fs::canonicalize(dir.join("node")).unwrap()
+        };
+        let command = binary.to_string_lossy().into_owned();
+
+        for (allowed, denied) in [(vec!["node"], vec![]), (vec![], vec!["node"])] {
+            let mut settings = SynapticSettings {
+                allowed_executables: allowed.iter().map(|e| e.to_string()).collect(),
+                denied_executables: denied.iter().map(|e| e.to_string()).collect(),
+                ..SynapticSettings::default()
+            };
+            let before = settings.clone();
+
+            settings.set_managed_executable("tool", Some(binary.clone()));
+            assert_eq!(settings.allowed_executables, before.allowed_executables);
+            assert_eq!(settings.denied_executables, before.denied_executables);
+            // The installed file is allowed for its own server, and only there
+            let allowed_for = |command: &str, server: &str| {
+                is_command_allowed_by(command, &settings.managed_executables_of(server))
+            };
+            assert!(allowed_for(&command, "tool"));
+            assert!(!allowed_for(&command, "other"));
+            assert!(!allowed_for("/usr/bin/node", "tool"));
+
+            // Uninstalling drops the binary, not the user's `node` decision
+            settings.set_managed_executable("tool", None);
+            assert_eq!(settings, before);
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_purge_deletes_only_the_servers_history() {
+        let db = Database::open_in_memory().unwrap();
+        {
+            let conn = db.conn();
+            for server in ["gone", "kept"] {
+                conn.execute(
+                    "INSERT INTO system_logs (session_id, timestamp, server_name) VALUES ('s', 0, ?1)",
+                    [server],
+                )
+                .unwrap();
+                conn.execute(
+                    "INSERT INTO tool_stats (server_name, tool_name, started_at, duration_ms)
+                     VALUES (?1, 'read', 0, 5)",
+                    [server],
+                )
+                .unwrap();
+            }
+        }
+
+        assert_eq!(purge_history(&db, "gone").unwrap(), 2);
+        let left: i64 = db
+            .conn()
+            .query_row(
+                "SELECT COUNT(*) FROM system_logs WHERE server_name = 'kept'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(left, 1);
+    }
+
+    #[test]
+    fn test_binaries_must_match_their_checksum_and_signature() {
+        use ring::rand::SystemRandom;
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+
+        let binary = b"#!/bin/sh\necho server\n";
+        let sha256: String = Sha256::digest(binary)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert!(verify_binary(binary, &sha256.to_uppercase(), None, None, &[]).is_ok());
+        assert!(verify_binary(b"tampered", &sha256, None, None, &[]).is_err());
+
+        let generate = || {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+            Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+        };
+        let key = generate();
+        let public_key = STANDARD.encode(key.public_key().as_ref());
+        let signature = STANDARD.encode(key.sign(binary).as_ref());
+        let trusted = vec![public_key.clone()];
+        let verifies = |signature: Option<&str>, public_key: Option<&str>, trusted: &[String]| {
+            verify_binary(binary, &sha256, signature, public_key, trusted).is_ok()
+        };
+        assert!(verifies(Some(&signature), Some(&public_key), &trusted));
+        assert!(verifies(Some(&signature), None, &trusted));
+
+        let other = STANDARD.encode(key.sign(b"something else").as_ref());
+        assert!(!verifies(Some(&other), Some(&public_key), &trusted));
+        assert!(!verifies(None, Some(&public_key), &trusted));
+
+        // A key shipped with the entry isn't trusted just for being there
+        assert!(!verifies(Some(&signature), Some(&public_key), &[]));
+        let stranger = vec![STANDARD.encode(generate().public_key().as_ref())];
+        assert!(!verifies(Some(&signature), Some(&public_key), &stranger));
+        assert!(!verifies(Some(&signature), None, &stranger));
+    }
+}
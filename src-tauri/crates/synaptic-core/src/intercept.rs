@@ -0,0 +1,279 @@
+//! Breakpoints on MCP traffic
+//!
+//! A breakpoint matches messages by server, direction and method. The stdin
+//! writer and stdout reader hand every message to the [`Interceptor`]; a
+//! matching one is held, announced with a `traffic-intercepted` event, and
+//! only continues once `resolve_intercept` forwards it (as is or edited) or
+//! drops it. While a message is held, later messages in the same direction
+//! wait behind it, so ordering is preserved.
+
+use crate::error::{SynapticError, SynapticResult};
+use crate::host::EventEmitter;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+
+/// Which way a message travels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TrafficDirection {
+    /// Client -> server (stdin)
+    Outgoing,
+    /// Server -> client (stdout)
+    Incoming,
+}
+
+/// Pause matching messages until the user resolves them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Breakpoint {
+    /// Assigned when the breakpoint is added
+    #[serde(default)]
+    pub id: String,
+    /// Only this server (all servers when unset)
+    pub server_name: Option<String>,
+    /// Only this direction (both when unset)
+    pub direction: Option<TrafficDirection>,
+    /// Method name, or its start when it ends with `*` (`tools/*`); responses have no method
+    pub method: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Breakpoint {
+    pub fn matches(&self, server_name: &str, direction: TrafficDirection, message: &Value) -> bool {
+        let method = message.get("method").and_then(|m| m.as_str());
+        self.enabled
+            && self
+                .server_name
+                .as_deref()
+                .is_none_or(|name| name == server_name)
+            && self.direction.is_none_or(|d| d == direction)
+            && self.method.as_deref().is_none_or(|pattern| {
+                method.is_some_and(|method| match pattern.strip_suffix('*') {
+                    Some(prefix) => method.starts_with(prefix),
+                    None => method == pattern,
+                })
+            })
+    }
+}
+
+/// Payload of the `traffic-intercepted` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterceptedMessage {
+    pub id: String,
+    pub breakpoint_id: String,
+    pub server_name: String,
+    pub direction: TrafficDirection,
+    pub method: Option<String>,
+    pub payload: Value,
+    pub held_at: DateTime<Utc>,
+}
+
+/// What to do with a held message
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InterceptAction {
+    /// Pass it on unchanged
+    Forward,
+    /// Pass on the edited payload instead
+    Modify,
+    /// Discard it
+    Drop,
+}
+
+/// Decision sent to the task holding a message
+enum Release {
+    Original,
+    Replace(String),
+    Drop,
+}
+
+struct Held {
+    message: InterceptedMessage,
+    release: oneshot::Sender<Release>,
+}
+
+/// Breakpoints and the messages they currently hold
+#[derive(Default)]
+pub struct Interceptor {
+    breakpoints: Mutex<Vec<Breakpoint>>,
+    held: Mutex<HashMap<String, Held>>,
+}
+
+impl Interceptor {
+    /// Add a breakpoint, returning it with its id
+    pub fn add_breakpoint(&self, mut breakpoint: Breakpoint) -> Breakpoint {
+        breakpoint.id = uuid::Uuid::new_v4().to_string();
+        self.breakpoints.lock().unwrap().push(breakpoint.clone());
+        breakpoint
+    }
+
+    /// Remove a breakpoint; messages it already holds stay held
+    pub fn remove_breakpoint(&self, id: &str) -> bool {
+        let mut breakpoints = self.breakpoints.lock().unwrap();
+        let before = breakpoints.len();
+        breakpoints.retain(|b| b.id != id);
+        breakpoints.len() != before
+    }
+
+    /// Whether any enabled breakpoint could hold a message
+    pub fn is_active(&self) -> bool {
+        self.breakpoints.lock().unwrap().iter().any(|b| b.enabled)
+    }
+
+    pub fn list_breakpoints(&self) -> Vec<Breakpoint> {
+        self.breakpoints.lock().unwrap().clone()
+    }
+
+    /// Messages waiting for a decision, oldest first
+    pub fn list_held(&self) -> Vec<InterceptedMessage> {
+        let mut held: Vec<_> = self
+            .held
+            .lock()
+            .unwrap()
+            .values()
+            .map(|h| h.message.clone())
+            .collect();
+        held.sort_by_key(|m| m.held_at);
+        held
+    }
+
+    /// Hold `raw` if a breakpoint matches, until it is resolved
+    ///
+    /// Returns the text to pass on (`raw` itself when nothing matched) or
+    /// `None` when the message was dropped.
+    pub async fn hold<E: EventEmitter>(
+        &self,
+        emitter: &E,
+        server_name: &str,
+        direction: TrafficDirection,
+        raw: String,
+    ) -> Option<String> {
+        let (breakpoint_id, payload) = {
+            let breakpoints = self.breakpoints.lock().unwrap();
+            if breakpoints.is_empty() {
+                return Some(raw);
+            }
+            let Ok(payload) = serde_json::from_str::<Value>(&raw) else {
+                return Some(raw);
+            };
+            match breakpoints
+                .iter()
+                .find(|b| b.matches(server_name, direction, &payload))
+            {
+                Some(breakpoint) => (breakpoint.id.clone(), payload),
+                None => return Some(raw),
+            }
+        };
+
+        let message = InterceptedMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            breakpoint_id,
+            server_name: server_name.to_string(),
+            direction,
+            method: payload
+                .get("method")
+                .and_then(|m| m.as_str())
+                .map(String::from),
+            payload,
+            held_at: Utc::now(),
+        };
+        let (release, released) = oneshot::channel();
+        self.held.lock().unwrap().insert(
+            message.id.clone(),
+            Held {
+                message: message.clone(),
+                release,
+            },
+        );
+        emitter.emit("traffic-intercepted", &message);
+
+        match released.await {
+            Ok(Release::Original) => Some(raw),
+            Ok(Release::Replace(text)) => Some(text),
+            // Dropped, or released without a decision (server stopped)
+            Ok(Release::Drop) | Err(_) => None,
+        }
+    }
+
+    /// Forward, edit or drop a held message
+    pub fn resolve(
+        &self,
+        id: &str,
+        action: InterceptAction,
+        edited_payload: Option<Value>,
+    ) -> SynapticResult<()> {
+        let release = match (action, edited_payload) {
+            (InterceptAction::Forward, _) => Release::Original,
+            (InterceptAction::Modify, Some(payload)) => Release::Replace(payload.to_string()),
+            (InterceptAction::Modify, None) => {
+                return Err(SynapticError::InspectorError(
+                    "Modify needs an edited payload".to_string(),
+                ))
+            }
+            (InterceptAction::Drop, _) => Release::Drop,
+        };
+
+        let held = self.held.lock().unwrap().remove(id).ok_or_else(|| {
+            SynapticError::InspectorError(format!("No intercepted message {}", id))
+        })?;
+        let _ = held.release.send(release);
+        Ok(())
+    }
+
+    /// Forget messages held for a server that stopped
+    pub fn release_server(&self, server_name: &str) {
+        self.held
+            .lock()
+            .unwrap()
+            .retain(|_, held| held.message.server_name != server_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_breakpoint_matching() {
+        let tools = Breakpoint {
+            id: String::new(),
+            server_name: Some("fs".to_string()),
+            direction: Some(TrafficDirection::Outgoing),
+            method: Some("tools/*".to_string()),
+            enabled: true,
+        };
+        let call = json!({"jsonrpc":"2.0","id":1,"method":"tools/call"});
+        assert!(tools.matches("fs", TrafficDirection::Outgoing, &call));
+        assert!(!tools.matches("git", TrafficDirection::Outgoing, &call));
+        assert!(!tools.matches("fs", TrafficDirection::Incoming, &call));
+        assert!(!tools.matches(
+            "fs",
+            TrafficDirection::Outgoing,
+            &json!({"id":1,"result":{}})
+        ));
+
+        let exact = Breakpoint {
+            method: Some("tools/list".to_string()),
+            server_name: None,
+            ..tools.clone()
+        };
+        assert!(!exact.matches("git", TrafficDirection::Outgoing, &call));
+
+        let disabled = Breakpoint {
+            enabled: false,
+            ..tools
+        };
+        assert!(!disabled.matches("fs", TrafficDirection::Outgoing, &call));
+    }
+}
@@ -0,0 +1,88 @@
+//! Synaptic Core - Tauri-free MCP server management
+//!
+//! Configuration, registry, inspector and process lifecycle logic shared by
+//! the desktop app and any CLI/shim binaries. The embedding application plugs
+//! in through the [`host`] traits for event emission and state access.
+
+pub mod audit;
+pub mod auth;
+pub mod backup_archive;
+pub mod bookmarks;
+pub mod capabilities;
+pub mod chaos;
+pub mod config;
+pub mod conformance;
+pub mod crashes;
+pub mod database;
+pub mod docker;
+pub mod error;
+pub mod error_summary;
+pub mod framing;
+pub mod fuzz;
+pub mod gateway;
+pub mod health;
+pub mod history_retention;
+pub mod host;
+pub mod idle;
+pub mod inspector;
+pub mod installer;
+pub mod intercept;
+pub mod limits;
+pub mod live_stats;
+pub mod load_test;
+pub mod log_level;
+pub mod message_diff;
+pub mod metadata;
+pub mod metrics;
+pub mod mock_server;
+pub mod mocks;
+pub mod orphans;
+pub mod oversized;
+pub mod packages;
+pub mod paths;
+pub mod policy;
+pub mod preflight;
+pub mod preview;
+pub mod process_manager;
+pub mod probe;
+pub mod process_tree;
+pub mod progress;
+pub mod rate_limit;
+pub mod redaction;
+pub mod registry;
+pub mod request_templates;
+pub mod resend;
+pub mod resolve;
+pub mod resources;
+pub mod runtimes;
+pub mod sampling;
+pub mod schedules;
+pub mod secrets;
+pub mod session_archive;
+pub mod session_report;
+pub mod server_compare;
+pub mod server_logs;
+pub mod settings;
+pub mod shim;
+pub mod snippet;
+pub mod snapshots;
+pub mod ssh;
+pub mod state;
+pub mod stdin_queue;
+pub mod system_logs;
+pub mod timeouts;
+pub mod tool_stats;
+pub mod traffic;
+pub mod traffic_mirror;
+pub mod transport;
+pub mod updates;
+pub mod validation;
+
+// Re-exports for external use
+pub use config::{McpConfig, McpServer};
+pub use error::{SynapticError, SynapticResult};
+pub use host::{EventEmitter, Host, StateProvider};
+pub use inspector::{InspectorMessage, InspectorSession, MessageDirection};
+pub use process_manager::ProcessManager;
+pub use registry::{InstallMethod, RegistryServer, RuntimeStatus};
+pub use state::AppState;
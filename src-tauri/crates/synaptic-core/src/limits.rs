@@ -0,0 +1,566 @@
+//! Per-server CPU and memory caps
+//!
+//! A server with `limits` runs in its own cgroup v2 on Linux (`memory.max`,
+//! `cpu.max`) or a Job Object with memory and CPU-rate caps on Windows.
+//! The counters behind them are polled while the server runs, and throttling
+//! or an OOM kill caused by a limit is reported as `resource-limit-violated`.
+//!
+//! cgroup v2 only hands controllers to cgroups without processes of their
+//! own, so on first use Synaptic moves itself into an `app` leaf of its own
+//! cgroup and creates the server cgroups under a `servers` sibling. That needs
+//! the memory and cpu controllers delegated to Synaptic's cgroup; when it fails
+//! the server runs uncapped and `resource-limits-unavailable` is emitted instead.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::process::{Child, Command};
+
+/// How often the limit counters of a running server are checked
+pub const LIMIT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Resource caps of a server's process tree
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    /// Memory for the whole tree, in MiB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// CPU time for the whole tree; 100 = one full core
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_percent: Option<u32>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.memory_mb.is_none() && self.cpu_percent.is_none()
+    }
+}
+
+/// Which limit a server ran into
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    Memory,
+    Cpu,
+}
+
+/// Payload of the `resource-limit-violated` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitViolationEvent {
+    pub server_name: String,
+    pub kind: LimitKind,
+    /// The server's process tree was killed by the limit, not just slowed down
+    pub killed: bool,
+    pub message: String,
+}
+
+/// Payload of the `resource-limits-unavailable` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitsUnavailableEvent {
+    pub server_name: String,
+    pub error: String,
+}
+
+/// Cumulative enforcement counters of a limited process tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LimitCounters {
+    /// Processes killed for exceeding the memory limit
+    pub oom_kills: u64,
+    /// Times the tree hit the memory limit and had to be reclaimed
+    pub memory_max_hits: u64,
+    /// The tree's peak memory reached the limit (where hits aren't counted)
+    pub memory_limit_reached: bool,
+    /// Scheduler periods in which the tree was throttled
+    pub cpu_throttled: u64,
+}
+
+/// Violations between two readings of the counters
+pub fn violations(
+    server_name: &str,
+    limits: &ResourceLimits,
+    before: &LimitCounters,
+    after: &LimitCounters,
+) -> Vec<LimitViolationEvent> {
+    let event = |kind, killed, message| LimitViolationEvent {
+        server_name: server_name.to_string(),
+        kind,
+        killed,
+        message,
+    };
+
+    let mut found = Vec::new();
+    if let Some(mb) = limits.memory_mb {
+        if after.oom_kills > before.oom_kills {
+            found.push(event(
+                LimitKind::Memory,
+                true,
+                format!(
+                    "{} was OOM-killed by its {} MB memory limit",
+                    server_name, mb
+                ),
+            ));
+        } else if after.memory_max_hits > before.memory_max_hits
+            || (after.memory_limit_reached && !before.memory_limit_reached)
+        {
+            found.push(event(
+                LimitKind::Memory,
+                false,
+                format!("{} hit its {} MB memory limit", server_name, mb),
+            ));
+        }
+    }
+    if let Some(percent) = limits.cpu_percent {
+        if after.cpu_throttled > before.cpu_throttled {
+            found.push(event(
+                LimitKind::Cpu,
+                false,
+                format!(
+                    "{} was throttled by its {}% CPU limit",
+                    server_name, percent
+                ),
+            ));
+        }
+    }
+    found
+}
+
+/// Value of a `key value` line in a cgroup stat file (`memory.events`, `cpu.stat`)
+pub fn stat_value(content: &str, key: &str) -> u64 {
+    content
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .find(|(name, _)| *name == key)
+        .and_then(|(_, value)| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Limits applied to one server process tree
+pub struct LimitGuard {
+    server_name: String,
+    limits: ResourceLimits,
+    /// Counters at the last check, to report only new violations
+    seen: Mutex<LimitCounters>,
+    #[cfg(target_os = "linux")]
+    cgroup: linux::Cgroup,
+    #[cfg(windows)]
+    job: windows::LimitJob,
+}
+
+impl LimitGuard {
+    /// Prepare enforcement before `cmd` is spawned
+    pub fn prepare(
+        server_name: &str,
+        limits: &ResourceLimits,
+        cmd: &mut Command,
+    ) -> Result<Self, String> {
+        #[cfg(target_os = "linux")]
+        {
+            let cgroup = linux::Cgroup::create(server_name, limits)?;
+            cgroup.join_on_exec(cmd)?;
+            Ok(Self::new(server_name, limits, cgroup))
+        }
+
+        #[cfg(windows)]
+        {
+            let _ = cmd;
+            let job = windows::LimitJob::create(limits)?;
+            Ok(Self::new(server_name, limits, job))
+        }
+
+        #[cfg(not(any(target_os = "linux", windows)))]
+        {
+            let _ = (server_name, limits, cmd);
+            Err("resource limits are only supported on Linux and Windows".to_string())
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn new(server_name: &str, limits: &ResourceLimits, cgroup: linux::Cgroup) -> Self {
+        Self {
+            server_name: server_name.to_string(),
+            limits: *limits,
+            seen: Mutex::new(LimitCounters::default()),
+            cgroup,
+        }
+    }
+
+    #[cfg(windows)]
+    fn new(server_name: &str, limits: &ResourceLimits, job: windows::LimitJob) -> Self {
+        Self {
+            server_name: server_name.to_string(),
+            limits: *limits,
+            seen: Mutex::new(LimitCounters::default()),
+            job,
+        }
+    }
+
+    /// Put a freshly spawned child under the limits (Linux does this at exec)
+    pub fn attach(&self, child: &Child) -> Result<(), String> {
+        #[cfg(windows)]
+        {
+            self.job.assign(child)
+        }
+
+        #[cfg(not(windows))]
+        {
+            let _ = child;
+            Ok(())
+        }
+    }
+
+    /// Current enforcement counters
+    pub fn counters(&self) -> LimitCounters {
+        #[cfg(target_os = "linux")]
+        {
+            self.cgroup.counters()
+        }
+
+        #[cfg(windows)]
+        {
+            self.job.counters()
+        }
+
+        #[cfg(not(any(target_os = "linux", windows)))]
+        {
+            LimitCounters::default()
+        }
+    }
+
+    /// Violations since the previous check
+    pub fn new_violations(&self) -> Vec<LimitViolationEvent> {
+        let current = self.counters();
+        let mut seen = self.seen.lock().unwrap();
+        let found = violations(&self.server_name, &self.limits, &seen, &current);
+        *seen = current;
+        found
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{stat_value, LimitCounters, ResourceLimits};
+    use std::ffi::CString;
+    use std::fs;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::PathBuf;
+    use std::sync::OnceLock;
+    use tokio::process::Command;
+
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+    /// Controllers the server cgroups need
+    const CONTROLLERS: &str = "+memory +cpu";
+
+    /// Parent of the server cgroups, or why it couldn't be set up
+    static SERVERS_CGROUP: OnceLock<Result<PathBuf, String>> = OnceLock::new();
+
+    /// Move Synaptic into a leaf of its own cgroup and create the servers' parent
+    fn setup_servers_cgroup() -> Result<PathBuf, String> {
+        let own = fs::read_to_string("/proc/self/cgroup")
+            .map_err(|e| format!("cannot read /proc/self/cgroup: {}", e))?;
+        let own = own
+            .lines()
+            .find_map(|line| line.strip_prefix("0::"))
+            .ok_or("cgroup v2 is not available")?;
+        let root = PathBuf::from(CGROUP_ROOT).join(own.trim_start_matches('/'));
+        // Hybrid hierarchies mount v1 controllers at the root instead
+        if !root.join("cgroup.controllers").exists() {
+            return Err(format!("{} is not a cgroup v2 hierarchy", CGROUP_ROOT));
+        }
+
+        let create = |path: &PathBuf| match fs::create_dir(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::AlreadyExists => {
+                Err(format!("cannot create cgroup {}: {}", path.display(), e))
+            }
+            _ => Ok(()),
+        };
+        let enable = |path: &PathBuf| {
+            fs::write(path.join("cgroup.subtree_control"), CONTROLLERS).map_err(|e| {
+                format!(
+                    "cannot enable the memory and cpu controllers in {}: {}",
+                    path.display(),
+                    e
+                )
+            })
+        };
+
+        let app = root.join("app");
+        create(&app)?;
+        fs::write(app.join("cgroup.procs"), std::process::id().to_string())
+            .map_err(|e| format!("cannot move Synaptic to {}: {}", app.display(), e))?;
+        enable(&root)?;
+
+        let servers = root.join("servers");
+        create(&servers)?;
+        enable(&servers)?;
+        Ok(servers)
+    }
+
+    /// cpu.max period in microseconds
+    const CPU_PERIOD_US: u64 = 100_000;
+
+    /// A cgroup v2 created for one server process tree, removed on drop
+    pub struct Cgroup {
+        path: PathBuf,
+    }
+
+    impl Cgroup {
+        /// Create a leaf cgroup under the servers' parent with the limits applied
+        pub fn create(server_name: &str, limits: &ResourceLimits) -> Result<Self, String> {
+            let parent = SERVERS_CGROUP.get_or_init(setup_servers_cgroup).clone()?;
+
+            let slug: String = server_name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                .collect();
+            let path = parent.join(format!(
+                "synaptic-{}-{}",
+                slug,
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            ));
+            fs::create_dir(&path)
+                .map_err(|e| format!("cannot create cgroup {}: {}", path.display(), e))?;
+            let cgroup = Self { path };
+
+            if let Some(mb) = limits.memory_mb {
+                cgroup.write("memory.max", &(mb * 1024 * 1024).to_string())?;
+            }
+            if let Some(percent) = limits.cpu_percent {
+                let quota = (u64::from(percent) * CPU_PERIOD_US / 100).max(1_000);
+                cgroup.write("cpu.max", &format!("{} {}", quota, CPU_PERIOD_US))?;
+            }
+            Ok(cgroup)
+        }
+
+        fn write(&self, file: &str, value: &str) -> Result<(), String> {
+            fs::write(self.path.join(file), value)
+                .map_err(|e| format!("cannot set {} on {}: {}", file, self.path.display(), e))
+        }
+
+        /// Move the child into the cgroup between fork and exec, before it starts anything
+        pub fn join_on_exec(&self, cmd: &mut Command) -> Result<(), String> {
+            let procs = CString::new(self.path.join("cgroup.procs").as_os_str().as_bytes())
+                .map_err(|e| e.to_string())?;
+
+            // SAFETY: only async-signal-safe calls on memory allocated before the fork;
+            // writing "0" to cgroup.procs moves the writing process
+            unsafe {
+                cmd.pre_exec(move || {
+                    let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+                    if fd < 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    let written = libc::write(fd, b"0".as_ptr().cast(), 1);
+                    libc::close(fd);
+                    if written != 1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+            Ok(())
+        }
+
+        pub fn counters(&self) -> LimitCounters {
+            let read = |file: &str| fs::read_to_string(self.path.join(file)).unwrap_or_default();
+            let memory = read("memory.events");
+            LimitCounters {
+                oom_kills: stat_value(&memory, "oom_kill"),
+                memory_max_hits: stat_value(&memory, "max"),
+                cpu_throttled: stat_value(&read("cpu.stat"), "nr_throttled"),
+                memory_limit_reached: false,
+            }
+        }
+    }
+
+    impl Drop for Cgroup {
+        fn drop(&mut self) {
+            // Only succeeds once the tree is gone, which it is by the time the guard drops
+            let _ = fs::remove_dir(&self.path);
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::{LimitCounters, ResourceLimits};
+    use tokio::process::Child;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectCpuRateControlInformation,
+        JobObjectExtendedLimitInformation, QueryInformationJobObject, SetInformationJobObject,
+        JOBOBJECT_CPU_RATE_CONTROL_INFORMATION, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_CPU_RATE_CONTROL_ENABLE, JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+        JOB_OBJECT_LIMIT_JOB_MEMORY,
+    };
+
+    /// Job object carrying a server's caps (nested inside its kill-on-close job)
+    pub struct LimitJob {
+        handle: HANDLE,
+        memory_limit: Option<usize>,
+    }
+
+    // SAFETY: job handles may be used and closed from any thread
+    unsafe impl Send for LimitJob {}
+    unsafe impl Sync for LimitJob {}
+
+    impl LimitJob {
+        pub fn create(limits: &ResourceLimits) -> Result<Self, String> {
+            let memory_limit = limits.memory_mb.map(|mb| (mb * 1024 * 1024) as usize);
+
+            // SAFETY: FFI calls on a handle we own; the info structs outlive the calls
+            unsafe {
+                let handle = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+                if handle.is_null() {
+                    return Err(std::io::Error::last_os_error().to_string());
+                }
+                let job = Self {
+                    handle,
+                    memory_limit,
+                };
+
+                if let Some(bytes) = memory_limit {
+                    let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+                    info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_JOB_MEMORY;
+                    info.JobMemoryLimit = bytes;
+                    job.set(
+                        JobObjectExtendedLimitInformation,
+                        &info as *const _ as *const core::ffi::c_void,
+                        std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>(),
+                    )?;
+                }
+
+                if let Some(percent) = limits.cpu_percent {
+                    // CpuRate is in 1/100 % of the whole machine; ours is per core
+                    let cores = std::thread::available_parallelism().map_or(1, |n| n.get()) as u32;
+                    let mut info: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION = std::mem::zeroed();
+                    info.ControlFlags =
+                        JOB_OBJECT_CPU_RATE_CONTROL_ENABLE | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP;
+                    info.Anonymous.CpuRate = (percent * 100 / cores).clamp(1, 10_000);
+                    job.set(
+                        JobObjectCpuRateControlInformation,
+                        &info as *const _ as *const core::ffi::c_void,
+                        std::mem::size_of::<JOBOBJECT_CPU_RATE_CONTROL_INFORMATION>(),
+                    )?;
+                }
+
+                Ok(job)
+            }
+        }
+
+        /// # Safety
+        /// `info` must point to `size` bytes of the struct `class` expects
+        unsafe fn set(
+            &self,
+            class: i32,
+            info: *const core::ffi::c_void,
+            size: usize,
+        ) -> Result<(), String> {
+            if SetInformationJobObject(self.handle, class, info, size as u32) == 0 {
+                return Err(std::io::Error::last_os_error().to_string());
+            }
+            Ok(())
+        }
+
+        pub fn assign(&self, child: &Child) -> Result<(), String> {
+            let process = child.raw_handle().ok_or("process already exited")? as HANDLE;
+            // SAFETY: both handles are valid for the duration of the call
+            if unsafe { AssignProcessToJobObject(self.handle, process) } == 0 {
+                return Err(std::io::Error::last_os_error().to_string());
+            }
+            Ok(())
+        }
+
+        /// Windows doesn't count throttling or limit hits; reaching the memory cap
+        /// shows in the peak
+        pub fn counters(&self) -> LimitCounters {
+            let Some(limit) = self.memory_limit else {
+                return LimitCounters::default();
+            };
+            // SAFETY: the info struct is sized for the requested class
+            let peak = unsafe {
+                let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+                let queried = QueryInformationJobObject(
+                    self.handle,
+                    JobObjectExtendedLimitInformation,
+                    &mut info as *mut _ as *mut core::ffi::c_void,
+                    std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                    std::ptr::null_mut(),
+                );
+                if queried == 0 {
+                    return LimitCounters::default();
+                }
+                info.PeakJobMemoryUsed
+            };
+            LimitCounters {
+                memory_limit_reached: peak >= limit,
+                ..Default::default()
+            }
+        }
+    }
+
+    impl Drop for LimitJob {
+        fn drop(&mut self) {
+            // SAFETY: handle is owned and closed exactly once
+            unsafe {
+                CloseHandle(self.handle);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_violations_from_cgroup_counters() {
+        let memory_events = "low 0\nhigh 0\nmax 3\noom 1\noom_kill 1\n";
+        assert_eq!(stat_value(memory_events, "oom_kill"), 1);
+        assert_eq!(stat_value(memory_events, "max"), 3);
+        assert_eq!(
+            stat_value("nr_periods 10\nnr_throttled 4", "nr_throttled"),
+            4
+        );
+        assert_eq!(stat_value("", "oom_kill"), 0);
+
+        let limits = ResourceLimits {
+            memory_mb: Some(512),
+            cpu_percent: Some(50),
+        };
+        let before = LimitCounters {
+            memory_max_hits: 3,
+            ..Default::default()
+        };
+        let after = LimitCounters {
+            oom_kills: 1,
+            memory_max_hits: 5,
+            cpu_throttled: 2,
+            ..Default::default()
+        };
+
+        let found = violations("indexer", &limits, &before, &after);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].kind, LimitKind::Memory);
+        assert!(found[0].killed);
+        assert_eq!(found[1].kind, LimitKind::Cpu);
+        assert!(!found[1].killed);
+
+        // Unchanged counters and unset limits report nothing
+        assert!(violations("indexer", &limits, &after, &after).is_empty());
+        assert!(violations("indexer", &ResourceLimits::default(), &before, &after).is_empty());
+
+        // Reaching the peak (Windows) is reported once, when it first happens
+        let reached = LimitCounters {
+            memory_limit_reached: true,
+            ..Default::default()
+        };
+        let found = violations("indexer", &limits, &LimitCounters::default(), &reached);
+        assert_eq!(found.len(), 1);
+        assert!(!found[0].killed);
+        assert!(violations("indexer", &limits, &reached, &reached).is_empty());
+    }
+}
@@ -0,0 +1,248 @@
+//! Live per-method request statistics
+//!
+//! The traffic pipeline reports every client request and response here, so
+//! the inspector header can show current throughput and latency without
+//! querying `system_logs`. Rates and average latencies cover the last
+//! [`STATS_WINDOW`]; active requests are those still waiting for a response.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Span the rates and average latencies are computed over
+pub const STATS_WINDOW: Duration = Duration::from_secs(60);
+
+/// Unanswered requests remembered per server
+const MAX_PENDING_PER_SERVER: usize = 10_000;
+
+/// Statistics of one method over the window
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodStats {
+    pub method: String,
+    /// Requests sent within the window
+    pub requests_per_minute: u64,
+    pub requests_per_second: f64,
+    /// Mean latency of the responses received within the window
+    pub avg_latency_ms: Option<f64>,
+    /// Requests still waiting for a response
+    pub active: usize,
+}
+
+/// Statistics of a server, overall and per method
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveStats {
+    pub server_name: String,
+    pub requests_per_minute: u64,
+    pub requests_per_second: f64,
+    pub avg_latency_ms: Option<f64>,
+    pub active: usize,
+    /// Sorted by method name
+    pub methods: Vec<MethodStats>,
+}
+
+struct Pending {
+    method: String,
+    sent: Instant,
+}
+
+#[derive(Default)]
+struct ServerStats {
+    /// Unanswered requests by JSON text of their id
+    pending: HashMap<String, Pending>,
+    /// Requests sent within the window, oldest first
+    sent: VecDeque<(Instant, String)>,
+    /// Responses received within the window, oldest first
+    answered: VecDeque<(Instant, String, Duration)>,
+}
+
+impl ServerStats {
+    fn prune(&mut self, now: Instant) {
+        let Some(start) = now.checked_sub(STATS_WINDOW) else {
+            return;
+        };
+        while self.sent.front().is_some_and(|(at, _)| *at < start) {
+            self.sent.pop_front();
+        }
+        while self.answered.front().is_some_and(|(at, _, _)| *at < start) {
+            self.answered.pop_front();
+        }
+    }
+}
+
+/// Rolling request counters, per server and method
+#[derive(Default)]
+pub struct LiveStatsTracker {
+    servers: Mutex<HashMap<String, ServerStats>>,
+}
+
+impl LiveStatsTracker {
+    /// Count an outgoing request
+    pub fn observe_request(&self, server_name: &str, message: &Value) {
+        self.observe_request_at(server_name, message, Instant::now());
+    }
+
+    /// Time an incoming response against its request
+    pub fn observe_response(&self, server_name: &str, message: &Value) {
+        self.observe_response_at(server_name, message, Instant::now());
+    }
+
+    fn observe_request_at(&self, server_name: &str, message: &Value, now: Instant) {
+        let (Some(method), Some(id)) = (
+            message.get("method").and_then(|m| m.as_str()),
+            message.get("id").filter(|id| !id.is_null()),
+        ) else {
+            return;
+        };
+        let mut servers = self.servers.lock().unwrap();
+        let stats = servers.entry(server_name.to_string()).or_default();
+        stats.prune(now);
+        stats.sent.push_back((now, method.to_string()));
+        if stats.pending.len() < MAX_PENDING_PER_SERVER {
+            stats.pending.insert(
+                id.to_string(),
+                Pending {
+                    method: method.to_string(),
+                    sent: now,
+                },
+            );
+        }
+    }
+
+    fn observe_response_at(&self, server_name: &str, message: &Value, now: Instant) {
+        if message.get("method").is_some() {
+            return;
+        }
+        let Some(id) = message.get("id") else {
+            return;
+        };
+        let mut servers = self.servers.lock().unwrap();
+        let Some(stats) = servers.get_mut(server_name) else {
+            return;
+        };
+        if let Some(request) = stats.pending.remove(&id.to_string()) {
+            stats.prune(now);
+            let latency = now.saturating_duration_since(request.sent);
+            stats.answered.push_back((now, request.method, latency));
+        }
+    }
+
+    /// Current statistics of a server
+    pub fn snapshot(&self, server_name: &str) -> LiveStats {
+        self.snapshot_at(server_name, Instant::now())
+    }
+
+    fn snapshot_at(&self, server_name: &str, now: Instant) -> LiveStats {
+        let mut live = LiveStats {
+            server_name: server_name.to_string(),
+            ..Default::default()
+        };
+        let mut servers = self.servers.lock().unwrap();
+        let Some(stats) = servers.get_mut(server_name) else {
+            return live;
+        };
+        stats.prune(now);
+
+        // (requests, latencies, active) per method
+        let mut methods: BTreeMap<&str, (u64, Vec<Duration>, usize)> = BTreeMap::new();
+        for (_, method) in &stats.sent {
+            methods.entry(method).or_default().0 += 1;
+        }
+        for (_, method, latency) in &stats.answered {
+            methods.entry(method).or_default().1.push(*latency);
+        }
+        for request in stats.pending.values() {
+            methods.entry(&request.method).or_default().2 += 1;
+        }
+
+        let window = STATS_WINDOW.as_secs_f64();
+        let latencies: Vec<Duration> = stats.answered.iter().map(|(_, _, l)| *l).collect();
+        live.requests_per_minute = stats.sent.len() as u64;
+        live.requests_per_second = stats.sent.len() as f64 / window;
+        live.avg_latency_ms = average_ms(&latencies);
+        live.active = stats.pending.len();
+        live.methods = methods
+            .into_iter()
+            .map(|(method, (requests, latencies, active))| MethodStats {
+                method: method.to_string(),
+                requests_per_minute: requests,
+                requests_per_second: requests as f64 / window,
+                avg_latency_ms: average_ms(&latencies),
+                active,
+            })
+            .collect();
+        live
+    }
+
+    /// Drop the unanswered requests of a server that exited
+    pub fn forget_pending(&self, server_name: &str) {
+        if let Some(stats) = self.servers.lock().unwrap().get_mut(server_name) {
+            stats.pending.clear();
+        }
+    }
+}
+
+fn average_ms(latencies: &[Duration]) -> Option<f64> {
+    if latencies.is_empty() {
+        return None;
+    }
+    let total: f64 = latencies.iter().map(|l| l.as_micros() as f64 / 1000.0).sum();
+    Some(total / latencies.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_live_stats_roll_over_the_window() {
+        let tracker = LiveStatsTracker::default();
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let request = |id: u64, method: &str| json!({"jsonrpc":"2.0","id":id,"method":method});
+        let response = |id: u64| json!({"jsonrpc":"2.0","id":id,"result":{}});
+
+        tracker.observe_request_at("fs", &request(1, "tools/call"), at(0));
+        tracker.observe_request_at("fs", &request(2, "tools/call"), at(10));
+        tracker.observe_request_at("fs", &request(3, "tools/list"), at(20));
+        // Notifications aren't requests
+        tracker.observe_request_at(
+            "fs",
+            &json!({"jsonrpc":"2.0","method":"notifications/initialized"}),
+            at(30),
+        );
+        tracker.observe_response_at("fs", &response(1), at(100));
+        tracker.observe_response_at("fs", &response(2), at(310));
+
+        let live = tracker.snapshot_at("fs", at(400));
+        assert_eq!(live.requests_per_minute, 3);
+        assert_eq!(live.active, 1);
+        assert_eq!(live.avg_latency_ms, Some(200.0));
+        assert_eq!(live.methods.len(), 2);
+        assert_eq!(live.methods[0].method, "tools/call");
+        assert_eq!(live.methods[0].requests_per_minute, 2);
+        assert_eq!(live.methods[0].active, 0);
+        assert_eq!(live.methods[1].avg_latency_ms, None);
+        assert_eq!(live.methods[1].active, 1);
+
+        // A minute later only the unanswered request is left
+        let later = tracker.snapshot_at("fs", at(61_000));
+        assert_eq!(later.requests_per_minute, 0);
+        assert_eq!(later.avg_latency_ms, None);
+        assert_eq!(later.active, 1);
+
+        tracker.forget_pending("fs");
+        assert_eq!(tracker.snapshot_at("fs", at(61_000)).active, 0);
+        assert_eq!(
+            tracker.snapshot("git"),
+            LiveStats {
+                server_name: "git".to_string(),
+                ..Default::default()
+            }
+        );
+    }
+}
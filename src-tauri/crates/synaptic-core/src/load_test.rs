@@ -0,0 +1,385 @@
+//! Load testing MCP servers
+//!
+//! A load test keeps `concurrency` copies of one request in flight for a
+//! fixed duration, sending each through [`ProcessManager::send_request`] like
+//! the inspector's send box, and collects a latency histogram and error
+//! counts. Latencies are kept in microseconds since local servers often
+//! answer in under a millisecond. A worker whose send fails backs off before
+//! trying again, and the whole run ends early once the server is gone, so a
+//! dead server isn't hammered in a busy loop. Results are kept in the
+//! `load_test_results` table so runs can be compared across versions of a
+//! server.
+
+use crate::database::Database;
+use crate::error::{SynapticError, SynapticResult};
+use crate::process_manager::ProcessManager;
+use crate::tool_stats::percentile;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Most requests in flight at once
+pub const MAX_CONCURRENCY: usize = 256;
+
+/// Longest load test
+pub const MAX_DURATION: Duration = Duration::from_secs(600);
+
+/// First pause of a worker after a failed send; doubled on each further failure
+const SEND_FAILURE_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Longest pause after failed sends
+const MAX_SEND_FAILURE_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Upper bounds of the histogram buckets, in microseconds; slower responses go to a last, open bucket
+const BUCKET_BOUNDS_US: &[u64] = &[
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000,
+    1_000_000, 2_500_000, 5_000_000, 10_000_000,
+];
+
+/// What to send and for how long
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadTestPlan {
+    /// JSON-RPC request; every send gets a fresh id
+    pub request: Value,
+    /// Saved request the request was built from, if any
+    pub template: Option<String>,
+    pub concurrency: usize,
+    pub duration_ms: u64,
+    /// Time a request may take before it counts as timed out
+    pub timeout_ms: u64,
+}
+
+/// Responses that took at most `le_us` (any time, when unset)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyBucket {
+    pub le_us: Option<u64>,
+    pub count: u64,
+}
+
+/// Latency of the answered requests
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencySummary {
+    pub min_us: u64,
+    pub mean_us: u64,
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadTestResult {
+    pub id: String,
+    pub server_name: String,
+    pub method: String,
+    pub plan: LoadTestPlan,
+    pub started_at: DateTime<Utc>,
+    pub elapsed_ms: u64,
+    /// Requests sent
+    pub requests: u64,
+    /// Requests answered with a result
+    pub successes: u64,
+    /// Requests answered with a JSON-RPC error
+    pub errors: u64,
+    /// Requests not answered in time
+    pub timeouts: u64,
+    /// Requests that couldn't be sent, or whose server went away
+    #[serde(default)]
+    pub failures: u64,
+    /// The server stopped before the run's duration was up
+    #[serde(default)]
+    pub server_stopped: bool,
+    /// JSON-RPC errors by code
+    pub error_codes: BTreeMap<i64, u64>,
+    /// Answered requests per second
+    pub throughput: f64,
+    pub latency: LatencySummary,
+    pub histogram: Vec<LatencyBucket>,
+}
+
+/// How one request went
+enum Sample {
+    Success(Duration),
+    Error(Duration, i64),
+    TimedOut,
+    /// Not sent, or not answered because the server went away
+    Failed,
+}
+
+/// Run a load test against a running server
+pub async fn run_load_test(
+    pm: &ProcessManager,
+    server_name: &str,
+    plan: LoadTestPlan,
+) -> SynapticResult<LoadTestResult> {
+    if plan.concurrency == 0 || plan.concurrency > MAX_CONCURRENCY {
+        return Err(SynapticError::InspectorError(format!(
+            "Concurrency must be between 1 and {}",
+            MAX_CONCURRENCY
+        )));
+    }
+    let duration = Duration::from_millis(plan.duration_ms);
+    if duration.is_zero() || duration > MAX_DURATION {
+        return Err(SynapticError::InspectorError(format!(
+            "Duration must be between 1 ms and {} s",
+            MAX_DURATION.as_secs()
+        )));
+    }
+    let Some(method) = plan.request.get("method").and_then(|m| m.as_str()) else {
+        return Err(SynapticError::InspectorError(
+            "Request has no method".to_string(),
+        ));
+    };
+    let method = method.to_string();
+    if !pm.is_running(server_name).await {
+        return Err(SynapticError::ProcessError(format!(
+            "Process not found: {}",
+            server_name
+        )));
+    }
+
+    let mut request = plan.request.clone();
+    if let Some(message) = request.as_object_mut() {
+        message.remove("id");
+    }
+    let timeout = Duration::from_millis(plan.timeout_ms);
+    let started_at = Utc::now();
+    let started = Instant::now();
+    let deadline = started + duration;
+
+    let server_stopped = AtomicBool::new(false);
+    let workers = (0..plan.concurrency).map(|_| async {
+        let mut samples = Vec::new();
+        let mut backoff = SEND_FAILURE_BACKOFF;
+        while Instant::now() < deadline && !server_stopped.load(Ordering::Relaxed) {
+            let sent = Instant::now();
+            let sample = match pm.send_request(server_name, request.clone(), timeout).await {
+                Ok(response) => match response.get("error") {
+                    Some(error) => {
+                        let code = error.get("code").and_then(|c| c.as_i64()).unwrap_or(0);
+                        Sample::Error(sent.elapsed(), code)
+                    }
+                    None => Sample::Success(sent.elapsed()),
+                },
+                Err(_) if sent.elapsed() >= timeout => Sample::TimedOut,
+                // Failed before the timeout: not sent, or the server went away
+                Err(_) => Sample::Failed,
+            };
+            let failed = matches!(sample, Sample::Failed);
+            samples.push(sample);
+            if !failed {
+                backoff = SEND_FAILURE_BACKOFF;
+                continue;
+            }
+            if !pm.is_running(server_name).await {
+                server_stopped.store(true, Ordering::Relaxed);
+                break;
+            }
+            tokio::time::sleep(backoff.min(deadline.saturating_duration_since(Instant::now())))
+                .await;
+            backoff = (backoff * 2).min(MAX_SEND_FAILURE_BACKOFF);
+        }
+        samples
+    });
+    let samples: Vec<Sample> = futures::future::join_all(workers)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    let elapsed = started.elapsed();
+
+    let tally = tally(&samples, elapsed);
+    Ok(LoadTestResult {
+        id: uuid::Uuid::new_v4().to_string(),
+        server_name: server_name.to_string(),
+        method,
+        plan,
+        started_at,
+        elapsed_ms: elapsed.as_millis() as u64,
+        requests: samples.len() as u64,
+        successes: tally.successes,
+        errors: tally.errors,
+        timeouts: tally.timeouts,
+        failures: tally.failures,
+        server_stopped: server_stopped.into_inner(),
+        error_codes: tally.error_codes,
+        throughput: tally.throughput,
+        latency: tally.latency,
+        histogram: tally.histogram,
+    })
+}
+
+#[derive(Default)]
+struct Tally {
+    successes: u64,
+    errors: u64,
+    timeouts: u64,
+    failures: u64,
+    error_codes: BTreeMap<i64, u64>,
+    throughput: f64,
+    latency: LatencySummary,
+    histogram: Vec<LatencyBucket>,
+}
+
+/// Counts, latency summary and histogram of a run's samples
+fn tally(samples: &[Sample], elapsed: Duration) -> Tally {
+    let mut tally = Tally::default();
+    let mut latencies = Vec::new();
+    for sample in samples {
+        match sample {
+            Sample::Success(latency) => {
+                tally.successes += 1;
+                latencies.push(latency.as_micros() as u64);
+            }
+            Sample::Error(latency, code) => {
+                tally.errors += 1;
+                *tally.error_codes.entry(*code).or_insert(0) += 1;
+                latencies.push(latency.as_micros() as u64);
+            }
+            Sample::TimedOut => tally.timeouts += 1,
+            Sample::Failed => tally.failures += 1,
+        }
+    }
+    latencies.sort_unstable();
+
+    tally.histogram = BUCKET_BOUNDS_US
+        .iter()
+        .map(|bound| Some(*bound))
+        .chain([None])
+        .map(|le_us| LatencyBucket { le_us, count: 0 })
+        .collect();
+    for latency in &latencies {
+        let bucket = BUCKET_BOUNDS_US
+            .iter()
+            .position(|bound| latency <= bound)
+            .unwrap_or(BUCKET_BOUNDS_US.len());
+        tally.histogram[bucket].count += 1;
+    }
+
+    if let (Some(min), Some(max)) = (latencies.first(), latencies.last()) {
+        tally.latency = LatencySummary {
+            min_us: *min,
+            mean_us: latencies.iter().sum::<u64>() / latencies.len() as u64,
+            p50_us: percentile(&latencies, 50),
+            p90_us: percentile(&latencies, 90),
+            p99_us: percentile(&latencies, 99),
+            max_us: *max,
+        };
+    }
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        tally.throughput = latencies.len() as f64 / secs;
+    }
+    tally
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or_default()
+}
+
+pub fn save_result(db: &Database, result: &LoadTestResult) -> SynapticResult<()> {
+    db.conn().execute(
+        "INSERT OR REPLACE INTO load_test_results (id, server_name, started_at, result)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            result.id,
+            result.server_name,
+            result.started_at.timestamp_millis(),
+            serde_json::to_value(result)?,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Stored results, newest first, optionally for a single server
+pub fn list_results(
+    db: &Database,
+    server_name: Option<&str>,
+) -> SynapticResult<Vec<LoadTestResult>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT result, started_at FROM load_test_results
+         WHERE ?1 IS NULL OR server_name = ?1
+         ORDER BY started_at DESC",
+    )?;
+    let rows = stmt.query_map(params![server_name], |row| {
+        Ok((row.get::<_, Value>(0)?, row.get::<_, i64>(1)?))
+    })?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        let (result, started_at) = row?;
+        let mut result: LoadTestResult = serde_json::from_value(result)?;
+        result.started_at = from_millis(started_at);
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// Delete a stored result; returns whether it existed
+pub fn delete_result(db: &Database, id: &str) -> SynapticResult<bool> {
+    let removed = db
+        .conn()
+        .execute("DELETE FROM load_test_results WHERE id = ?1", params![id])?;
+    Ok(removed > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_buckets_latencies_and_counts_errors() {
+        let ms = Duration::from_millis;
+        let samples = vec![
+            Sample::Success(Duration::from_micros(80)),
+            Sample::Success(ms(1)),
+            Sample::Success(ms(3)),
+            Sample::Error(ms(40), -32601),
+            Sample::Error(ms(20_000), -32601),
+            Sample::TimedOut,
+            Sample::Failed,
+        ];
+        let result = tally(&samples, Duration::from_secs(1));
+
+        assert_eq!(
+            (
+                result.successes,
+                result.errors,
+                result.timeouts,
+                result.failures
+            ),
+            (3, 2, 1, 1)
+        );
+        assert_eq!(result.error_codes[&-32601], 2);
+        assert_eq!(result.throughput, 5.0);
+
+        let count = |le_us: Option<u64>| {
+            result
+                .histogram
+                .iter()
+                .find(|b| b.le_us == le_us)
+                .unwrap()
+                .count
+        };
+        assert_eq!(count(Some(100)), 1);
+        assert_eq!(count(Some(1_000)), 1);
+        assert_eq!(count(Some(5_000)), 1);
+        assert_eq!(count(Some(50_000)), 1);
+        assert_eq!(count(None), 1);
+
+        assert_eq!(result.latency.min_us, 80);
+        assert_eq!(result.latency.p50_us, 3_000);
+        assert_eq!(result.latency.max_us, 20_000_000);
+    }
+}
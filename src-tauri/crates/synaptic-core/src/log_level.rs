@@ -0,0 +1,199 @@
+//! Severity of stderr lines
+//!
+//! Servers log to stderr in all sorts of formats. The stderr reader picks
+//! the level out of the common ones and tags the traffic event and its
+//! `system_logs` row with it:
+//! - JSON logs: pino and bunyan numeric levels, winston-style `level` strings
+//!   (also `severity` or `lvl`)
+//! - logfmt: `level=info`
+//! - a level word near the start of the line: `[INFO] ...`, `WARN: ...`,
+//!   `2025-01-01T00:00:00Z ERROR ...`, Python's `WARNING:root:...`
+//!
+//! Lines without a recognizable level are logged as `WARN`, like before.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::str::FromStr;
+
+/// Tokens at the start of a line searched for a level word
+const LEADING_TOKENS: usize = 6;
+
+/// Severity of a logged line, least severe first
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    /// Name stored in `system_logs.level`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+            LogLevel::Fatal => "FATAL",
+        }
+    }
+
+    /// Position from least (0) to most severe
+    pub fn rank(self) -> i64 {
+        self as i64
+    }
+
+    /// Level named by a word, in any case (`warning`, `ERR`, `critical`, ...)
+    fn from_word(word: &str) -> Option<Self> {
+        let level = match word.to_ascii_lowercase().as_str() {
+            "trace" => LogLevel::Trace,
+            "debug" | "dbg" | "verbose" => LogLevel::Debug,
+            "info" | "information" | "notice" => LogLevel::Info,
+            "warn" | "warning" => LogLevel::Warn,
+            "error" | "err" => LogLevel::Error,
+            "fatal" | "critical" | "crit" | "panic" | "alert" | "emergency" => LogLevel::Fatal,
+            _ => return None,
+        };
+        Some(level)
+    }
+
+    /// Level of a pino or bunyan numeric level
+    fn from_number(level: i64) -> Self {
+        match level {
+            ..=10 => LogLevel::Trace,
+            11..=20 => LogLevel::Debug,
+            21..=30 => LogLevel::Info,
+            31..=40 => LogLevel::Warn,
+            41..=50 => LogLevel::Error,
+            _ => LogLevel::Fatal,
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LogLevel::from_word(s).ok_or_else(|| format!("Unknown log level: {}", s))
+    }
+}
+
+/// Level a stderr line states, if any
+pub fn parse_stderr_level(line: &str) -> Option<LogLevel> {
+    let line = line.trim();
+    if line.starts_with('{') {
+        if let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(line) {
+            return json_level(&fields);
+        }
+    }
+
+    for token in line.split_whitespace() {
+        if let Some(value) = token
+            .strip_prefix("level=")
+            .or_else(|| token.strip_prefix("lvl="))
+        {
+            return LogLevel::from_word(value.trim_matches('"'));
+        }
+    }
+
+    // Past the first token only bracketed or upper-case words count, so
+    // prose like "started in debug mode" isn't taken for a level
+    line.split(|c: char| c.is_whitespace() || c == ':' || c == '|')
+        .filter(|token| !token.is_empty())
+        .take(LEADING_TOKENS)
+        .enumerate()
+        .find_map(|(i, token)| {
+            let word = token.trim_matches(|c: char| !c.is_ascii_alphabetic());
+            let marked = i == 0
+                || token.starts_with(['[', '<', '('])
+                || word.chars().all(|c| c.is_ascii_uppercase());
+            marked.then(|| LogLevel::from_word(word)).flatten()
+        })
+}
+
+/// Level field of a JSON log line
+fn json_level(fields: &Map<String, Value>) -> Option<LogLevel> {
+    let value = ["level", "severity", "lvl"]
+        .iter()
+        .find_map(|key| fields.get(*key))?;
+    match value {
+        Value::Number(n) => n.as_i64().map(LogLevel::from_number),
+        // winston may leave terminal colour codes around the name
+        Value::String(name) => LogLevel::from_word(strip_ansi(name).trim()),
+        _ => None,
+    }
+}
+
+/// Text without ANSI escape sequences (`ESC [ ... m` and the like)
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Parameters run up to the first letter, which ends the sequence
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_common_formats() {
+        let cases = [
+            (
+                r#"{"level":30,"time":1,"msg":"listening"}"#,
+                Some(LogLevel::Info),
+            ),
+            (r#"{"level":50,"msg":"boom"}"#, Some(LogLevel::Error)),
+            (r#"{"level":"warn","message":"slow"}"#, Some(LogLevel::Warn)),
+            (
+                r#"{"level":"\u001b[31merror\u001b[39m"}"#,
+                Some(LogLevel::Error),
+            ),
+            (r#"{"severity":"DEBUG"}"#, Some(LogLevel::Debug)),
+            (r#"{"msg":"no level"}"#, None),
+            (
+                "time=2025-01-01 level=error msg=failed",
+                Some(LogLevel::Error),
+            ),
+            ("[INFO] Server ready", Some(LogLevel::Info)),
+            ("WARN: deprecated option", Some(LogLevel::Warn)),
+            ("WARNING:root:disk almost full", Some(LogLevel::Warn)),
+            (
+                "2025-01-01T00:00:00Z ERROR connection lost",
+                Some(LogLevel::Error),
+            ),
+            ("[2025-01-01 12:00:00] [debug] tick", Some(LogLevel::Debug)),
+            ("Error: Cannot find module 'x'", Some(LogLevel::Error)),
+            ("panic: runtime error", Some(LogLevel::Fatal)),
+            ("Server started in debug mode", None),
+            ("listening on stdio", None),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(parse_stderr_level(line), expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_levels_order_by_severity() {
+        assert!(LogLevel::Error > LogLevel::Warn);
+        assert_eq!(LogLevel::Trace.rank(), 0);
+        assert_eq!(LogLevel::Fatal.rank(), 5);
+        assert_eq!("warning".parse::<LogLevel>(), Ok(LogLevel::Warn));
+        assert_eq!(
+            serde_json::to_value(LogLevel::Info).unwrap(),
+            serde_json::json!("INFO")
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Structural diff of two logged messages
+//!
+//! Compares the payloads of two `system_logs` rows member by member and
+//! lists every added, removed or changed value by JSON pointer, e.g. to
+//! compare a response before and after a server upgrade, or a resent
+//! request's response with the recorded one. The top-level JSON-RPC `id`
+//! always differs between exchanges and is left out.
+
+use crate::database::Database;
+use crate::error::{SynapticError, SynapticResult};
+use crate::system_logs;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// How a value differs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffKind {
+    /// Only in the second message
+    Added,
+    /// Only in the first message
+    Removed,
+    Changed,
+}
+
+/// One differing value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiffEntry {
+    /// JSON pointer, e.g. `/result/tools/2/description`
+    pub path: String,
+    pub kind: DiffKind,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Differences between two logged messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageDiff {
+    pub id_a: i64,
+    pub id_b: i64,
+    pub identical: bool,
+    pub changes: Vec<DiffEntry>,
+}
+
+/// Differences from `before` to `after`, in path order
+pub fn diff_values(before: &Value, after: &Value) -> Vec<DiffEntry> {
+    let mut changes = Vec::new();
+    walk("", before, after, &mut changes);
+    changes
+}
+
+fn walk(path: &str, before: &Value, after: &Value, changes: &mut Vec<DiffEntry>) {
+    let child = |key: &str| format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+    match (before, after) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => walk(&child(key), x, y, changes),
+                    (Some(x), None) => changes.push(removed(child(key), x)),
+                    (None, Some(y)) => changes.push(added(child(key), y)),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for index in 0..a.len().max(b.len()) {
+                let path = child(&index.to_string());
+                match (a.get(index), b.get(index)) {
+                    (Some(x), Some(y)) => walk(&path, x, y, changes),
+                    (Some(x), None) => changes.push(removed(path, x)),
+                    (None, Some(y)) => changes.push(added(path, y)),
+                    (None, None) => {}
+                }
+            }
+        }
+        (a, b) if a != b => changes.push(DiffEntry {
+            path: path.to_string(),
+            kind: DiffKind::Changed,
+            before: Some(a.clone()),
+            after: Some(b.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn added(path: String, value: &Value) -> DiffEntry {
+    DiffEntry {
+        path,
+        kind: DiffKind::Added,
+        before: None,
+        after: Some(value.clone()),
+    }
+}
+
+fn removed(path: String, value: &Value) -> DiffEntry {
+    DiffEntry {
+        path,
+        kind: DiffKind::Removed,
+        before: Some(value.clone()),
+        after: None,
+    }
+}
+
+/// Diff the payloads of two logged messages
+pub fn diff_messages(db: &Database, id_a: i64, id_b: i64) -> SynapticResult<MessageDiff> {
+    let payload = |id: i64| -> SynapticResult<Value> {
+        let entry = system_logs::get_entry(db, id)?
+            .ok_or_else(|| SynapticError::InspectorError(format!("No logged message {}", id)))?;
+        let mut payload = entry.payload.unwrap_or(Value::Null);
+        if let Some(message) = payload.as_object_mut() {
+            message.remove("id");
+        }
+        Ok(payload)
+    };
+    let changes = diff_values(&payload(id_a)?, &payload(id_b)?);
+    Ok(MessageDiff {
+        id_a,
+        id_b,
+        identical: changes.is_empty(),
+        changes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_lists_changed_paths() {
+        let before = json!({
+            "result": {
+                "tools": [
+                    { "name": "read", "description": "Read a file" },
+                    { "name": "write" }
+                ],
+                "a/b": 1
+            }
+        });
+        let after = json!({
+            "result": {
+                "tools": [
+                    { "name": "read", "description": "Read a text file", "annotations": {} }
+                ],
+                "a/b": 1
+            }
+        });
+
+        let changes = diff_values(&before, &after);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|c| (c.path.as_str(), c.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                ("/result/tools/0/annotations", DiffKind::Added),
+                ("/result/tools/0/description", DiffKind::Changed),
+                ("/result/tools/1", DiffKind::Removed),
+            ]
+        );
+        assert_eq!(changes[1].after, Some(json!("Read a text file")));
+        assert!(diff_values(&before, &before).is_empty());
+        assert_eq!(diff_values(&json!(1), &json!("1"))[0].path, "");
+    }
+}
@@ -0,0 +1,174 @@
+//! Synaptic's own metadata store
+//!
+//! Per-server data that has no place in a client's config file (per-target
+//! enable state, install provenance, ...) lives in `metadata.json` under the
+//! Synaptic data directory.
+
+use crate::config::get_synaptic_data_dir;
+use crate::error::{SynapticError, SynapticResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Target id of the Claude Desktop config (whose `enabled` flag is authoritative)
+pub const CLAUDE_DESKTOP_TARGET: &str = "claude_desktop";
+
+/// Root of `metadata.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SynapticMetadata {
+    #[serde(default)]
+    pub servers: HashMap<String, ServerMetadata>,
+}
+
+/// Metadata tracked for a single server definition
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerMetadata {
+    /// Explicit enable state per target client; missing targets inherit `enabled`
+    #[serde(default)]
+    pub targets: BTreeMap<String, bool>,
+
+    /// Registry entry the server was installed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry_id: Option<String>,
+
+    /// Package version (or git ref) the server was pinned to on install (none runs the latest)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_version: Option<String>,
+
+    /// Managed directory the server was cloned or downloaded into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_dir: Option<PathBuf>,
+}
+
+/// Get the metadata file path
+pub fn get_metadata_path() -> SynapticResult<PathBuf> {
+    Ok(get_synaptic_data_dir()?.join("metadata.json"))
+}
+
+impl SynapticMetadata {
+    /// Load metadata from a file, returning empty metadata if it doesn't exist
+    pub fn load_from(path: &Path) -> SynapticResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| {
+            SynapticError::ConfigParseError(format!("Failed to parse {}: {}", path.display(), e))
+        })
+    }
+
+    /// Write metadata to a file
+    pub fn save_to(&self, path: &Path) -> SynapticResult<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).map_err(|e| {
+            SynapticError::ConfigWriteError(format!("Failed to write {}: {}", path.display(), e))
+        })
+    }
+
+    /// Load metadata from the default location
+    pub fn load() -> SynapticResult<Self> {
+        Self::load_from(&get_metadata_path()?)
+    }
+
+    /// Save metadata to the default location
+    pub fn save(&self) -> SynapticResult<()> {
+        self.save_to(&get_metadata_path()?)
+    }
+
+    /// Get metadata for a server (default if none recorded)
+    pub fn server(&self, name: &str) -> ServerMetadata {
+        self.servers.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Set the enable state of a server for one target client
+    pub fn set_target_enabled(&mut self, name: &str, target: &str, enabled: bool) {
+        self.servers
+            .entry(name.to_string())
+            .or_default()
+            .targets
+            .insert(target.to_string(), enabled);
+    }
+
+    /// Record the registry entry and pinned version a server was installed with
+    pub fn set_install(&mut self, name: &str, registry_id: &str, pinned_version: Option<&str>) {
+        let server = self.servers.entry(name.to_string()).or_default();
+        server.registry_id = Some(registry_id.to_string());
+        server.pinned_version = pinned_version.map(String::from);
+    }
+
+    /// Record the package version a server is pinned to
+    pub fn set_pinned_version(&mut self, name: &str, pinned_version: Option<&str>) {
+        self.servers
+            .entry(name.to_string())
+            .or_default()
+            .pinned_version = pinned_version.map(String::from);
+    }
+
+    /// Record the managed directory a server was installed into
+    pub fn set_install_dir(&mut self, name: &str, install_dir: &Path) {
+        self.servers.entry(name.to_string()).or_default().install_dir =
+            Some(install_dir.to_path_buf());
+    }
+
+    /// Drop all metadata for a server
+    pub fn remove_server(&mut self, name: &str) -> bool {
+        self.servers.remove(name).is_some()
+    }
+}
+
+impl ServerMetadata {
+    /// Whether the server is exposed to a target, falling back to the config flag
+    pub fn is_enabled_for(&self, target: &str, default_enabled: bool) -> bool {
+        self.targets.get(target).copied().unwrap_or(default_enabled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_fallback() {
+        let mut metadata = SynapticMetadata::default();
+        metadata.set_target_enabled("github", "cursor", false);
+
+        let server = metadata.server("github");
+        assert!(!server.is_enabled_for("cursor", true));
+        assert!(server.is_enabled_for("windsurf", true));
+        assert!(!metadata.server("unknown").is_enabled_for("cursor", false));
+    }
+
+    #[test]
+    fn test_roundtrip_file() {
+        let path = std::env::temp_dir()
+            .join(format!("synaptic-metadata-{}", uuid::Uuid::new_v4()))
+            .join("metadata.json");
+
+        assert!(SynapticMetadata::load_from(&path)
+            .unwrap()
+            .servers
+            .is_empty());
+
+        let mut metadata = SynapticMetadata::default();
+        metadata.set_target_enabled("github", "cursor", true);
+        metadata.set_install("github", "github", Some("1.4.2"));
+        metadata.save_to(&path).unwrap();
+
+        let loaded = SynapticMetadata::load_from(&path).unwrap();
+        assert_eq!(loaded.server("github").targets.get("cursor"), Some(&true));
+        assert_eq!(
+            loaded.server("github").pinned_version.as_deref(),
+            Some("1.4.2")
+        );
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,200 @@
+//! CPU and memory monitoring for managed server processes
+//!
+//! A background sampler walks each server's process tree (launchers like
+//! `npx` do the real work in a grandchild), caches the totals on the
+//! `ProcessManager` and emits them as `process-metrics` events.
+
+use crate::host::Host;
+use crate::process_manager::ProcessManager;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// How often process metrics are sampled
+pub const METRICS_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Resource usage of one server's process tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessMetrics {
+    pub server_name: String,
+    pub pid: u32,
+    /// Summed over the tree; 100 means one full core
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    /// Only available on Linux
+    pub thread_count: Option<usize>,
+    /// Processes in the tree, including the root
+    pub process_count: usize,
+    pub sampled_at: DateTime<Utc>,
+}
+
+/// Keeps the `sysinfo` state between samples (CPU usage is a delta)
+pub struct MetricsSampler {
+    system: System,
+}
+
+impl MetricsSampler {
+    pub fn new() -> Self {
+        Self {
+            system: System::new(),
+        }
+    }
+
+    /// Sample the process trees rooted at the given `(server_name, pid)` pairs
+    pub fn sample(&mut self, roots: &[(String, u32)]) -> Vec<ProcessMetrics> {
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing().with_cpu().with_memory(),
+        );
+
+        let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+        for (pid, process) in self.system.processes() {
+            // Threads show up as processes on Linux; count them via `tasks` instead
+            if process.thread_kind().is_some() {
+                continue;
+            }
+            if let Some(parent) = process.parent() {
+                children.entry(parent).or_default().push(*pid);
+            }
+        }
+
+        let now = Utc::now();
+        roots
+            .iter()
+            .filter_map(|(name, pid)| self.sample_tree(name, *pid, &children, now))
+            .collect()
+    }
+
+    fn sample_tree(
+        &self,
+        server_name: &str,
+        root: u32,
+        children: &HashMap<Pid, Vec<Pid>>,
+        sampled_at: DateTime<Utc>,
+    ) -> Option<ProcessMetrics> {
+        let root_pid = Pid::from_u32(root);
+        self.system.process(root_pid)?;
+
+        let mut metrics = ProcessMetrics {
+            server_name: server_name.to_string(),
+            pid: root,
+            cpu_percent: 0.0,
+            memory_bytes: 0,
+            thread_count: Some(0),
+            process_count: 0,
+            sampled_at,
+        };
+
+        let mut seen = HashSet::new();
+        let mut stack = vec![root_pid];
+        while let Some(pid) = stack.pop() {
+            if !seen.insert(pid) {
+                continue;
+            }
+            let Some(process) = self.system.process(pid) else {
+                continue;
+            };
+
+            metrics.cpu_percent += process.cpu_usage();
+            metrics.memory_bytes += process.memory();
+            metrics.process_count += 1;
+            metrics.thread_count = match (metrics.thread_count, process.tasks()) {
+                (Some(count), Some(tasks)) => Some(count + tasks.len().max(1)),
+                _ => None,
+            };
+
+            if let Some(kids) = children.get(&pid) {
+                stack.extend(kids.iter().copied());
+            }
+        }
+
+        Some(metrics)
+    }
+}
+
+impl Default for MetricsSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Background loop sampling every running server
+pub async fn run_metrics_sampler<H: Host>(host: H) {
+    let mut sampler = MetricsSampler::new();
+    let mut ticker = tokio::time::interval(METRICS_SAMPLE_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        let Some(pm) = host.process_manager() else {
+            continue;
+        };
+
+        let roots: Vec<(String, u32)> = {
+            let processes = pm.processes.lock().await;
+            // Remote servers have no local process to sample
+            processes
+                .values()
+                .filter(|p| !p.server.is_remote())
+                .map(|p| (p.server_name.clone(), p.pid))
+                .collect()
+        };
+
+        // Walking the process table is blocking work
+        let result = tokio::task::spawn_blocking(move || {
+            let samples = sampler.sample(&roots);
+            (sampler, samples)
+        })
+        .await;
+
+        let samples = match result {
+            Ok((returned, samples)) => {
+                sampler = returned;
+                samples
+            }
+            Err(e) => {
+                eprintln!("Process metrics sampling failed: {}", e);
+                sampler = MetricsSampler::new();
+                continue;
+            }
+        };
+
+        store_samples(pm, &samples).await;
+
+        if !samples.is_empty() {
+            host.emit("process-metrics", &samples);
+        }
+    }
+}
+
+/// Replace the cached metrics with the latest samples
+async fn store_samples(pm: &ProcessManager, samples: &[ProcessMetrics]) {
+    let mut metrics = pm.metrics.lock().await;
+    metrics.clear();
+    for sample in samples {
+        metrics.insert(sample.server_name.clone(), sample.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_own_process() {
+        let mut sampler = MetricsSampler::new();
+        let own = ("self".to_string(), std::process::id());
+        let missing = ("gone".to_string(), u32::MAX - 1);
+
+        let samples = sampler.sample(&[own, missing]);
+
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].server_name, "self");
+        assert!(samples[0].memory_bytes > 0);
+        assert!(samples[0].process_count >= 1);
+    }
+}
@@ -0,0 +1,532 @@
+//! Built-in mock MCP server
+//!
+//! `synaptic-mock-server <definition.json>` speaks MCP over stdio from a
+//! definition file listing tools, resources and prompts with the answers
+//! they give, so client and frontend work can run against a deterministic
+//! server without installing a real one. It is spawned and inspected like
+//! any other server; [`client_entry`] builds its config entry.
+//!
+//! A tool answers with the `result` of the first of its `cases` whose
+//! `arguments` are contained in the call's arguments, or else with its own
+//! `result` (or `error`). A tool with neither echoes its arguments back as
+//! text. `{{name}}` in prompt messages is filled in from the prompt's
+//! arguments.
+
+use crate::config::McpServer;
+use crate::error::{SynapticError, SynapticResult};
+use crate::probe::PROBE_PROTOCOL_VERSION;
+use crate::resolve;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// File name of the mock server binary (without the platform's executable suffix)
+pub const MOCK_SERVER_BINARY: &str = "synaptic-mock-server";
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const PARSE_ERROR: i64 = -32700;
+/// Code the MCP spec gives unknown resources
+const RESOURCE_NOT_FOUND: i64 = -32002;
+
+/// What the mock server offers
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MockDefinition {
+    /// `serverInfo.name` (`synaptic-mock` when unset)
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub instructions: Option<String>,
+    /// Version to answer `initialize` with instead of the client's
+    pub protocol_version: Option<String>,
+    pub tools: Vec<MockTool>,
+    pub resources: Vec<MockResource>,
+    pub prompts: Vec<MockPrompt>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MockTool {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default = "default_input_schema")]
+    pub input_schema: Value,
+    /// Answers for specific arguments, checked in order
+    #[serde(default)]
+    pub cases: Vec<MockCase>,
+    /// `CallToolResult` of calls no case matches
+    pub result: Option<Value>,
+    /// JSON-RPC error of calls no case matches, instead of a result
+    pub error: Option<MockError>,
+    /// Time every call takes
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+fn default_input_schema() -> Value {
+    json!({ "type": "object" })
+}
+
+/// Answer to calls whose arguments contain `arguments`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MockCase {
+    pub arguments: Value,
+    pub result: Option<Value>,
+    pub error: Option<MockError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MockError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MockResource {
+    pub uri: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+    pub text: Option<String>,
+    /// Base64 content, for binary resources
+    pub blob: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MockPrompt {
+    pub name: String,
+    pub description: Option<String>,
+    /// Prompt arguments as listed (`name`, `description`, `required`)
+    #[serde(default)]
+    pub arguments: Vec<Value>,
+    /// `PromptMessage`s, with `{{name}}` filled in from the arguments
+    #[serde(default)]
+    pub messages: Vec<Value>,
+}
+
+/// Read and check a definition file
+pub fn load_definition(path: &Path) -> SynapticResult<MockDefinition> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| SynapticError::ConfigReadError(format!("{}: {}", path.display(), e)))?;
+    let definition: MockDefinition = serde_json::from_str(&content).map_err(|e| {
+        SynapticError::ConfigParseError(format!(
+            "Invalid mock definition {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    definition.check()?;
+    Ok(definition)
+}
+
+/// Config entry running the bundled mock server with a definition file
+pub fn client_entry(definition_path: &Path) -> SynapticResult<McpServer> {
+    load_definition(definition_path)?;
+    Ok(McpServer {
+        command: resolve::bundled_binary(MOCK_SERVER_BINARY)?
+            .to_string_lossy()
+            .to_string(),
+        args: vec![definition_path.to_string_lossy().to_string()],
+        ..Default::default()
+    })
+}
+
+/// Whether a command is the mock server shipped with Synaptic
+pub fn is_bundled(command: &str) -> bool {
+    resolve::bundled_binary(MOCK_SERVER_BINARY).is_ok_and(|bundled| Path::new(command) == bundled)
+}
+
+impl MockDefinition {
+    /// Names must be unique for the server to be deterministic
+    fn check(&self) -> SynapticResult<()> {
+        let found = duplicate(self.tools.iter().map(|t| t.name.as_str()))
+            .or_else(|| duplicate(self.resources.iter().map(|r| r.uri.as_str())))
+            .or_else(|| duplicate(self.prompts.iter().map(|p| p.name.as_str())));
+        match found {
+            Some(name) => Err(SynapticError::ConfigParseError(format!(
+                "Mock definition lists {} more than once",
+                name
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Answer one line from the client; notifications get no answer
+    pub async fn handle_line(&self, line: &str) -> Option<Value> {
+        match serde_json::from_str::<Value>(line) {
+            Ok(message) => self.handle(message).await,
+            Err(e) => Some(error_response(
+                Value::Null,
+                PARSE_ERROR,
+                format!("Parse error: {}", e),
+            )),
+        }
+    }
+
+    /// Answer one JSON-RPC message; notifications get no answer
+    pub async fn handle(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned()?;
+        let Some(method) = message.get("method").and_then(|m| m.as_str()) else {
+            return Some(error_response(id, INVALID_PARAMS, "Message has no method"));
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        // Methods of capabilities the definition doesn't use don't exist
+        let outcome = match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" if !self.tools.is_empty() => {
+                Ok(json!({ "tools": self.tools.iter().map(listed_tool).collect::<Vec<_>>() }))
+            }
+            "tools/call" if !self.tools.is_empty() => self.call_tool(&params).await,
+            "resources/list" if !self.resources.is_empty() => Ok(json!({
+                "resources": self.resources.iter().map(listed_resource).collect::<Vec<_>>()
+            })),
+            "resources/read" if !self.resources.is_empty() => self.read_resource(&params),
+            "prompts/list" if !self.prompts.is_empty() => Ok(json!({
+                "prompts": self.prompts.iter().map(listed_prompt).collect::<Vec<_>>()
+            })),
+            "prompts/get" if !self.prompts.is_empty() => self.get_prompt(&params),
+            _ => Err(MockError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Method not found: {}", method),
+            }),
+        };
+        Some(match outcome {
+            Ok(result) => result_response(id, result),
+            Err(error) => error_response(id, error.code, error.message),
+        })
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let version = self
+            .protocol_version
+            .as_deref()
+            .or_else(|| params.get("protocolVersion").and_then(|v| v.as_str()))
+            .unwrap_or(PROBE_PROTOCOL_VERSION);
+        let mut capabilities = json!({});
+        if !self.tools.is_empty() {
+            capabilities["tools"] = json!({});
+        }
+        if !self.resources.is_empty() {
+            capabilities["resources"] = json!({});
+        }
+        if !self.prompts.is_empty() {
+            capabilities["prompts"] = json!({});
+        }
+        let mut result = json!({
+            "protocolVersion": version,
+            "capabilities": capabilities,
+            "serverInfo": {
+                "name": self.name.as_deref().unwrap_or("synaptic-mock"),
+                "version": self.version.as_deref().unwrap_or(env!("CARGO_PKG_VERSION")),
+            },
+        });
+        if let Some(instructions) = &self.instructions {
+            result["instructions"] = json!(instructions);
+        }
+        result
+    }
+
+    async fn call_tool(&self, params: &Value) -> Result<Value, MockError> {
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .unwrap_or_default();
+        let Some(tool) = self.tools.iter().find(|t| t.name == name) else {
+            return Err(MockError {
+                code: INVALID_PARAMS,
+                message: format!("Unknown tool: {}", name),
+            });
+        };
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+        if tool.delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(tool.delay_ms)).await;
+        }
+
+        let (result, error) = match tool
+            .cases
+            .iter()
+            .find(|c| contains(&arguments, &c.arguments))
+        {
+            Some(case) => (&case.result, &case.error),
+            None => (&tool.result, &tool.error),
+        };
+        match (result, error) {
+            (_, Some(error)) => Err(error.clone()),
+            (Some(result), None) => Ok(result.clone()),
+            (None, None) => Ok(json!({
+                "content": [{ "type": "text", "text": arguments.to_string() }],
+                "isError": false,
+            })),
+        }
+    }
+
+    fn read_resource(&self, params: &Value) -> Result<Value, MockError> {
+        let uri = params
+            .get("uri")
+            .and_then(|u| u.as_str())
+            .unwrap_or_default();
+        let Some(resource) = self.resources.iter().find(|r| r.uri == uri) else {
+            return Err(MockError {
+                code: RESOURCE_NOT_FOUND,
+                message: format!("Resource not found: {}", uri),
+            });
+        };
+        let mut contents = json!({ "uri": resource.uri });
+        if let Some(mime_type) = &resource.mime_type {
+            contents["mimeType"] = json!(mime_type);
+        }
+        match &resource.blob {
+            Some(blob) => contents["blob"] = json!(blob),
+            None => contents["text"] = json!(resource.text.as_deref().unwrap_or_default()),
+        }
+        Ok(json!({ "contents": [contents] }))
+    }
+
+    fn get_prompt(&self, params: &Value) -> Result<Value, MockError> {
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .unwrap_or_default();
+        let Some(prompt) = self.prompts.iter().find(|p| p.name == name) else {
+            return Err(MockError {
+                code: INVALID_PARAMS,
+                message: format!("Unknown prompt: {}", name),
+            });
+        };
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+        let missing: Vec<&str> = prompt
+            .arguments
+            .iter()
+            .filter(|a| a.get("required").and_then(|r| r.as_bool()) == Some(true))
+            .filter_map(|a| a.get("name").and_then(|n| n.as_str()))
+            .filter(|name| arguments.get(name).is_none())
+            .collect();
+        if !missing.is_empty() {
+            return Err(MockError {
+                code: INVALID_PARAMS,
+                message: format!("Missing prompt arguments: {}", missing.join(", ")),
+            });
+        }
+
+        let messages: Vec<Value> = prompt
+            .messages
+            .iter()
+            .map(|message| fill_in(message, &arguments))
+            .collect();
+        let mut result = json!({ "messages": messages });
+        if let Some(description) = &prompt.description {
+            result["description"] = json!(description);
+        }
+        Ok(result)
+    }
+}
+
+/// First name listed twice
+fn duplicate<'a>(mut names: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let mut seen = HashSet::new();
+    names.find(|name| !seen.insert(*name))
+}
+
+fn listed_tool(tool: &MockTool) -> Value {
+    let mut listed = json!({ "name": tool.name, "inputSchema": tool.input_schema });
+    if let Some(description) = &tool.description {
+        listed["description"] = json!(description);
+    }
+    listed
+}
+
+fn listed_resource(resource: &MockResource) -> Value {
+    let mut listed = json!({ "uri": resource.uri, "name": resource.name });
+    if let Some(description) = &resource.description {
+        listed["description"] = json!(description);
+    }
+    if let Some(mime_type) = &resource.mime_type {
+        listed["mimeType"] = json!(mime_type);
+    }
+    listed
+}
+
+fn listed_prompt(prompt: &MockPrompt) -> Value {
+    let mut listed = json!({ "name": prompt.name, "arguments": prompt.arguments });
+    if let Some(description) = &prompt.description {
+        listed["description"] = json!(description);
+    }
+    listed
+}
+
+/// Whether `value` contains everything in `pattern` (objects may have more keys)
+fn contains(value: &Value, pattern: &Value) -> bool {
+    match (value, pattern) {
+        (Value::Object(value), Value::Object(pattern)) => pattern
+            .iter()
+            .all(|(key, expected)| value.get(key).is_some_and(|v| contains(v, expected))),
+        _ => value == pattern,
+    }
+}
+
+/// Replace `{{name}}` in the strings of `value` with prompt arguments
+fn fill_in(value: &Value, arguments: &Value) -> Value {
+    match value {
+        Value::String(text) => {
+            let mut filled = text.clone();
+            if let Some(arguments) = arguments.as_object() {
+                for (name, argument) in arguments {
+                    let replacement = match argument {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    filled = filled.replace(&format!("{{{{{}}}}}", name), &replacement);
+                }
+            }
+            Value::String(filled)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|i| fill_in(i, arguments)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| (key.clone(), fill_in(item, arguments)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn error_response(id: Value, code: i64, message: impl Into<String>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.into() },
+    })
+}
+
+fn result_response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+/// Serve a definition file over stdin/stdout until stdin closes
+pub async fn run(definition_path: PathBuf) -> SynapticResult<()> {
+    let definition = load_definition(&definition_path)?;
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = definition.handle_line(&line).await {
+            stdout.write_all(format!("{}\n", reply).as_bytes()).await?;
+            stdout.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition() -> MockDefinition {
+        serde_json::from_value(json!({
+            "name": "weather",
+            "tools": [{
+                "name": "forecast",
+                "inputSchema": { "type": "object", "properties": { "city": { "type": "string" } } },
+                "cases": [
+                    { "arguments": { "city": "Oslo" }, "result": { "content": [{ "type": "text", "text": "Snow" }] } },
+                    { "arguments": { "city": "Nowhere" }, "error": { "code": -32602, "message": "Unknown city" } },
+                ],
+                "result": { "content": [{ "type": "text", "text": "Sunny" }] },
+            }, { "name": "echo" }],
+            "prompts": [{
+                "name": "greet",
+                "arguments": [{ "name": "who", "required": true }],
+                "messages": [{ "role": "user", "content": { "type": "text", "text": "Hello {{who}}" } }],
+            }],
+        }))
+        .unwrap()
+    }
+
+    async fn call(definition: &MockDefinition, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        definition.handle(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tools_answer_from_matching_cases() {
+        let mock = definition();
+        let initialized = call(
+            &mock,
+            "initialize",
+            json!({ "protocolVersion": "2025-03-26" }),
+        )
+        .await;
+        assert_eq!(initialized["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(
+            initialized["result"]["capabilities"],
+            json!({ "tools": {}, "prompts": {} })
+        );
+
+        let forecast =
+            |city: &str| json!({ "name": "forecast", "arguments": { "city": city, "days": 3 } });
+        let snow = call(&mock, "tools/call", forecast("Oslo")).await;
+        assert_eq!(snow["result"]["content"][0]["text"], "Snow");
+        let sunny = call(&mock, "tools/call", forecast("Rome")).await;
+        assert_eq!(sunny["result"]["content"][0]["text"], "Sunny");
+        let unknown = call(&mock, "tools/call", forecast("Nowhere")).await;
+        assert_eq!(unknown["error"]["message"], "Unknown city");
+
+        let echoed = call(
+            &mock,
+            "tools/call",
+            json!({ "name": "echo", "arguments": { "x": 1 } }),
+        )
+        .await;
+        assert_eq!(echoed["result"]["content"][0]["text"], r#"{"x":1}"#);
+        let listed = call(&mock, "tools/list", Value::Null).await;
+        assert_eq!(
+            listed["result"]["tools"][1]["inputSchema"],
+            json!({ "type": "object" })
+        );
+
+        // No resources, no resource methods
+        assert_eq!(
+            call(&mock, "resources/read", json!({ "uri": "x" })).await["error"]["code"],
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            mock.handle_line("{").await.unwrap()["error"]["code"],
+            PARSE_ERROR
+        );
+        assert!(mock
+            .handle(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_prompts_fill_in_arguments() {
+        let mock = definition();
+        let greeting = call(
+            &mock,
+            "prompts/get",
+            json!({ "name": "greet", "arguments": { "who": "Ada" } }),
+        )
+        .await;
+        assert_eq!(
+            greeting["result"]["messages"][0]["content"]["text"],
+            "Hello Ada"
+        );
+        let missing = call(&mock, "prompts/get", json!({ "name": "greet" })).await;
+        assert_eq!(missing["error"]["message"], "Missing prompt arguments: who");
+
+        let mut duplicated = definition();
+        duplicated.tools.push(duplicated.tools[0].clone());
+        assert!(duplicated.check().is_err());
+    }
+}
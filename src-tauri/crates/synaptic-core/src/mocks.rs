@@ -0,0 +1,190 @@
+//! Canned responses for selected requests
+//!
+//! A mock rule answers matching requests itself, e.g. `tools/call` for the
+//! tool `fetch_url`, so the real server never sees them. The stdin writer
+//! asks the [`MockEngine`] about every outgoing request; a mocked one still
+//! shows up as outgoing traffic, and its canned response is fed through the
+//! stdout path like any other server output.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Answer matching requests with a fixed result or error
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MockRule {
+    /// Assigned when the rule is added
+    #[serde(default)]
+    pub id: String,
+    /// Only this server (all servers when unset)
+    pub server_name: Option<String>,
+    /// Method name, or its start when it ends with `*`
+    pub method: String,
+    /// Fields the request params must contain, e.g. `{"name": "fetch_url"}`
+    pub params: Option<Value>,
+    /// `result` of the canned response
+    pub result: Option<Value>,
+    /// `error` of the canned response; takes precedence over `result`
+    pub error: Option<Value>,
+    /// Milliseconds to wait before answering
+    #[serde(default)]
+    pub delay_ms: u64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl MockRule {
+    pub fn matches(&self, server_name: &str, request: &Value) -> bool {
+        let Some(method) = request.get("method").and_then(|m| m.as_str()) else {
+            return false;
+        };
+        let method_matches = match self.method.strip_suffix('*') {
+            Some(prefix) => method.starts_with(prefix),
+            None => method == self.method,
+        };
+
+        self.enabled
+            && method_matches
+            && self
+                .server_name
+                .as_deref()
+                .is_none_or(|name| name == server_name)
+            && self.params.as_ref().is_none_or(|expected| {
+                request
+                    .get("params")
+                    .is_some_and(|actual| contains(actual, expected))
+            })
+    }
+
+    /// Canned response to `request`
+    pub fn response(&self, request: &Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        match &self.error {
+            Some(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": self.result.clone().unwrap_or_else(|| json!({})),
+            }),
+        }
+    }
+}
+
+/// Whether `actual` has every field of `expected` (recursively for objects)
+fn contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected
+            .iter()
+            .all(|(key, value)| actual.get(key).is_some_and(|a| contains(a, value))),
+        _ => actual == expected,
+    }
+}
+
+/// Mock rules, checked in the order they were added
+#[derive(Default)]
+pub struct MockEngine {
+    rules: Mutex<Vec<MockRule>>,
+}
+
+impl MockEngine {
+    /// Add a rule, returning it with its id
+    pub fn add_rule(&self, mut rule: MockRule) -> MockRule {
+        rule.id = uuid::Uuid::new_v4().to_string();
+        self.rules.lock().unwrap().push(rule.clone());
+        rule
+    }
+
+    /// Remove a rule; returns whether it existed
+    pub fn remove_rule(&self, id: &str) -> bool {
+        let mut rules = self.rules.lock().unwrap();
+        let before = rules.len();
+        rules.retain(|r| r.id != id);
+        rules.len() != before
+    }
+
+    pub fn list_rules(&self) -> Vec<MockRule> {
+        self.rules.lock().unwrap().clone()
+    }
+
+    /// Whether any enabled rule could answer a request
+    pub fn is_active(&self) -> bool {
+        self.rules.lock().unwrap().iter().any(|r| r.enabled)
+    }
+
+    /// Canned response and delay for a request, if a rule answers it
+    ///
+    /// Notifications (no `id`) are never mocked.
+    pub fn respond(&self, server_name: &str, request: &Value) -> Option<(Value, Duration)> {
+        request.get("id")?;
+        self.rules
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|rule| rule.matches(server_name, request))
+            .map(|rule| (rule.response(request), Duration::from_millis(rule.delay_ms)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_rules_answer_matching_requests() {
+        let engine = MockEngine::default();
+        engine.add_rule(MockRule {
+            id: String::new(),
+            server_name: None,
+            method: "tools/call".to_string(),
+            params: Some(json!({ "name": "fetch_url" })),
+            result: Some(json!({ "content": [{ "type": "text", "text": "cached" }] })),
+            error: None,
+            delay_ms: 0,
+            enabled: true,
+        });
+        let failing = engine.add_rule(MockRule {
+            id: String::new(),
+            server_name: Some("paid".to_string()),
+            method: "resources/*".to_string(),
+            params: None,
+            result: None,
+            error: Some(json!({ "code": -32000, "message": "quota exceeded" })),
+            delay_ms: 250,
+            enabled: true,
+        });
+
+        let fetch = json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "tools/call",
+            "params": { "name": "fetch_url", "arguments": { "url": "https://example.com" } }
+        });
+        let (response, delay) = engine.respond("any", &fetch).unwrap();
+        assert_eq!(response["id"], 4);
+        assert_eq!(response["result"]["content"][0]["text"], "cached");
+        assert_eq!(delay, Duration::ZERO);
+
+        let other_tool = json!({ "id": 5, "method": "tools/call", "params": { "name": "search" } });
+        assert!(engine.respond("any", &other_tool).is_none());
+
+        let read = json!({ "id": 6, "method": "resources/read" });
+        assert!(engine.respond("free", &read).is_none());
+        let (response, delay) = engine.respond("paid", &read).unwrap();
+        assert_eq!(response["error"]["code"], -32000);
+        assert_eq!(delay, Duration::from_millis(250));
+
+        // Notifications are never answered
+        assert!(engine
+            .respond("paid", &json!({ "method": "resources/updated" }))
+            .is_none());
+
+        assert!(engine.remove_rule(&failing.id));
+        assert!(engine.respond("paid", &read).is_none());
+    }
+}
@@ -0,0 +1,251 @@
+//! Orphaned server processes
+//!
+//! Every spawned server is recorded in the `spawned_processes` table with its
+//! PID, OS start time and the Synaptic session that started it, and forgotten
+//! again once it exits. Records of earlier sessions are left over after a
+//! crash and point at servers that may still be running; a PID only counts as
+//! an orphan if the process start time still matches, so recycled PIDs are
+//! ignored.
+
+use crate::database::Database;
+use crate::error::SynapticResult;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// Allowed difference between recorded and current process start times
+const START_TIME_TOLERANCE_SECS: u64 = 2;
+
+/// Identifies the current Synaptic run in spawn records
+static SESSION_ID: LazyLock<String> = LazyLock::new(|| uuid::Uuid::new_v4().to_string());
+
+/// A server process left running by a previous Synaptic session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanProcess {
+    pub server_name: String,
+    pub pid: u32,
+    pub command: String,
+    pub started_at: DateTime<Utc>,
+}
+
+/// What to do with orphaned processes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanAction {
+    /// Leave the process running and track it as one of this session's
+    Adopt,
+    /// Kill the process and everything it started
+    Kill,
+}
+
+/// Remember a freshly spawned server process
+pub fn record_spawn(
+    db: &Database,
+    server_name: &str,
+    pid: u32,
+    command: &str,
+) -> SynapticResult<()> {
+    let started_at = process_start_time(pid).unwrap_or_else(|| Utc::now().timestamp() as u64);
+    db.conn().execute(
+        "INSERT OR REPLACE INTO spawned_processes (pid, server_name, command, started_at, session_id)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![pid, server_name, command, started_at as i64, *SESSION_ID],
+    )?;
+    Ok(())
+}
+
+/// Forget a server process that has exited
+pub fn record_exit(db: &Database, pid: u32) -> SynapticResult<()> {
+    db.conn()
+        .execute("DELETE FROM spawned_processes WHERE pid = ?1", params![pid])?;
+    Ok(())
+}
+
+/// Processes of earlier sessions that are still alive
+///
+/// Records of processes that are gone are dropped.
+pub fn find_orphans(db: &Database) -> SynapticResult<Vec<OrphanProcess>> {
+    let records = {
+        let conn = db.conn();
+        let mut stmt = conn.prepare(
+            "SELECT server_name, pid, command, started_at FROM spawned_processes
+             WHERE session_id != ?1
+             ORDER BY started_at",
+        )?;
+        let rows = stmt.query_map(params![*SESSION_ID], |row| {
+            let started_at: i64 = row.get(3)?;
+            Ok(OrphanProcess {
+                server_name: row.get(0)?,
+                pid: row.get(1)?,
+                command: row.get(2)?,
+                started_at: Utc
+                    .timestamp_opt(started_at, 0)
+                    .single()
+                    .unwrap_or_default(),
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+
+    let mut orphans = Vec::new();
+    for record in records {
+        let alive = system
+            .process(Pid::from_u32(record.pid))
+            .is_some_and(|process| {
+                process
+                    .start_time()
+                    .abs_diff(record.started_at.timestamp() as u64)
+                    <= START_TIME_TOLERANCE_SECS
+            });
+        if alive {
+            orphans.push(record);
+        } else {
+            record_exit(db, record.pid)?;
+        }
+    }
+    Ok(orphans)
+}
+
+/// Adopt or kill orphans (all of them, or those of the named servers)
+///
+/// Adopted orphans keep their spawn record, moved to the current session, so
+/// they are found again if this session crashes too. Returns the orphans acted on.
+pub fn adopt_or_kill_orphans(
+    db: &Database,
+    action: OrphanAction,
+    server_names: Option<&[String]>,
+) -> SynapticResult<Vec<OrphanProcess>> {
+    let selected: Vec<OrphanProcess> = find_orphans(db)?
+        .into_iter()
+        .filter(|o| server_names.is_none_or(|names| names.contains(&o.server_name)))
+        .collect();
+
+    if action == OrphanAction::Kill && !selected.is_empty() {
+        let mut system = System::new();
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+        for orphan in &selected {
+            kill_tree(&system, orphan.pid);
+        }
+    }
+
+    for orphan in &selected {
+        match action {
+            OrphanAction::Adopt => adopt(db, orphan.pid)?,
+            OrphanAction::Kill => record_exit(db, orphan.pid)?,
+        }
+    }
+    Ok(selected)
+}
+
+/// Move a spawn record to the current session
+fn adopt(db: &Database, pid: u32) -> SynapticResult<()> {
+    db.conn().execute(
+        "UPDATE spawned_processes SET session_id = ?1 WHERE pid = ?2",
+        params![*SESSION_ID, pid],
+    )?;
+    Ok(())
+}
+
+/// OS start time of a process, in seconds since the epoch
+fn process_start_time(pid: u32) -> Option<u64> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing(),
+    );
+    system.process(pid).map(|process| process.start_time())
+}
+
+/// Kill a process and its descendants
+///
+/// The orphan's job object (Windows) is gone with the old session, so the
+/// tree is walked by parent PID on every platform.
+fn kill_tree(system: &System, root: u32) {
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, process) in system.processes() {
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(*pid);
+        }
+    }
+
+    // Servers lead their own process group on Unix
+    #[cfg(unix)]
+    // SAFETY: kill(2) has no memory-safety preconditions
+    unsafe {
+        libc::kill(-(root as libc::pid_t), libc::SIGKILL);
+    }
+
+    let mut stack = vec![Pid::from_u32(root)];
+    while let Some(pid) = stack.pop() {
+        if let Some(process) = system.process(pid) {
+            process.kill();
+        }
+        stack.extend(children.get(&pid).into_iter().flatten().copied());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_live_orphans_and_prunes_dead_records() {
+        let db = Database::open_in_memory().unwrap();
+        let own_pid = std::process::id();
+
+        // Processes of the current session are not orphans
+        record_spawn(&db, "current", own_pid, "cargo").unwrap();
+        assert!(find_orphans(&db).unwrap().is_empty());
+
+        let previous_session = |pid: u32, name: &str, started_at: u64| {
+            db.conn()
+                .execute(
+                    "INSERT OR REPLACE INTO spawned_processes
+                        (pid, server_name, command, started_at, session_id)
+                     VALUES (?1, ?2, 'node', ?3, 'crashed-session')",
+                    params![pid, name, started_at as i64],
+                )
+                .unwrap();
+        };
+        previous_session(own_pid, "survivor", process_start_time(own_pid).unwrap());
+        // Recycled PID: alive, but started at a different time
+        previous_session(1, "recycled", 0);
+
+        let orphans = find_orphans(&db).unwrap();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].server_name, "survivor");
+        assert_eq!(orphans[0].pid, own_pid);
+
+        let adopted =
+            adopt_or_kill_orphans(&db, OrphanAction::Adopt, Some(&["other".to_string()])).unwrap();
+        assert!(adopted.is_empty());
+
+        let adopted = adopt_or_kill_orphans(&db, OrphanAction::Adopt, None).unwrap();
+        assert_eq!(adopted.len(), 1);
+        assert!(find_orphans(&db).unwrap().is_empty());
+
+        // Still tracked, now as a process of this session
+        let session: String = db
+            .conn()
+            .query_row(
+                "SELECT session_id FROM spawned_processes WHERE pid = ?1",
+                params![own_pid],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(session, *SESSION_ID);
+    }
+}
@@ -0,0 +1,212 @@
+//! Messages over the size limit
+//!
+//! `maxMessageBytes` caps the messages Synaptic handles whole, so a server
+//! answering with hundreds of megabytes of base64 can't swamp the app or
+//! the traffic database. What happens to a larger message is up to the
+//! `oversizedPolicy` setting: server output is cut down to the limit, or
+//! replaced with a JSON-RPC error, or passed on whole. Outgoing messages are
+//! never cut (the server couldn't parse them); they are rejected or passed on.
+//! Whatever the policy, the copy recorded as traffic is cut to the limit,
+//! and every oversized message is counted per server and announced with a
+//! `message-oversized` event.
+
+use crate::framing;
+use crate::host::Host;
+use crate::intercept::TrafficDirection;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// JSON-RPC error code standing in for a rejected oversized message
+pub const MESSAGE_TOO_LARGE_CODE: i64 = -32003;
+
+/// What the recipient gets of a message over the size limit
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum OversizedPolicy {
+    /// Server output is cut to the limit and flagged with a truncation marker
+    #[default]
+    Truncate,
+    /// Requests and responses are answered with an error; other messages are dropped
+    Reject,
+    /// Delivered whole; only the recorded copy is cut
+    PassThrough,
+}
+
+/// Payload of the `message-oversized` event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OversizedMessage {
+    pub server_name: String,
+    pub direction: TrafficDirection,
+    /// Size of the whole message
+    pub size_bytes: usize,
+    pub limit_bytes: usize,
+    pub policy: OversizedPolicy,
+    pub method: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+/// Oversized messages of one server since Synaptic started
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OversizedStats {
+    pub truncated: u64,
+    pub rejected: u64,
+    pub passed_through: u64,
+    pub largest_bytes: usize,
+    pub total_bytes: u64,
+    pub last_at: Option<DateTime<Utc>>,
+}
+
+/// Oversized message counts, by server name
+#[derive(Default)]
+pub struct OversizedTracker {
+    stats: Mutex<HashMap<String, OversizedStats>>,
+}
+
+impl OversizedTracker {
+    pub fn record(&self, message: &OversizedMessage) {
+        let mut stats = self.stats.lock().unwrap();
+        let server = stats.entry(message.server_name.clone()).or_default();
+        match message.policy {
+            OversizedPolicy::Truncate => server.truncated += 1,
+            OversizedPolicy::Reject => server.rejected += 1,
+            OversizedPolicy::PassThrough => server.passed_through += 1,
+        }
+        server.largest_bytes = server.largest_bytes.max(message.size_bytes);
+        server.total_bytes += message.size_bytes as u64;
+        server.last_at = Some(message.at);
+    }
+
+    pub fn stats(&self) -> HashMap<String, OversizedStats> {
+        self.stats.lock().unwrap().clone()
+    }
+}
+
+/// Count and announce a message over `limit_bytes`, which may already have been cut
+pub fn observe<H: Host>(
+    host: &H,
+    server_name: &str,
+    direction: TrafficDirection,
+    message: &str,
+    limit_bytes: usize,
+    policy: OversizedPolicy,
+) -> OversizedMessage {
+    let oversized = OversizedMessage {
+        server_name: server_name.to_string(),
+        direction,
+        size_bytes: framing::truncated_size(message).unwrap_or(message.len()),
+        limit_bytes,
+        policy,
+        method: head_field(message, "method").and_then(|m| m.as_str().map(String::from)),
+        at: Utc::now(),
+    };
+    if let Some(pm) = host.process_manager() {
+        pm.oversized.record(&oversized);
+    }
+    host.emit("message-oversized", &oversized);
+    oversized
+}
+
+/// Error response standing in for the oversized message with `id`
+pub fn rejection(id: &Value, size_bytes: usize, limit_bytes: usize) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": MESSAGE_TOO_LARGE_CODE,
+            "message": format!(
+                "Message of {} bytes exceeds the limit of {} bytes",
+                size_bytes, limit_bytes
+            ),
+        },
+    })
+}
+
+/// A top-level field of a JSON object, read from as much of it as is there
+///
+/// Lets the id of a response be found after its body was cut off.
+pub fn head_field(head: &str, key: &str) -> Option<Value> {
+    let bytes = head.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b'"' => {
+                let end = string_end(bytes, i)?;
+                if depth == 1 && head[i + 1..end] == *key {
+                    if let Some(value) = head[end + 1..].trim_start().strip_prefix(':') {
+                        return serde_json::Deserializer::from_str(value)
+                            .into_iter::<Value>()
+                            .next()?
+                            .ok();
+                    }
+                }
+                i = end;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Index of the quote closing the string opened at `start`
+fn string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return Some(i),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_field_reads_cut_messages() {
+        let head = r#"{"jsonrpc":"2.0","result":{"id":"inner","data":"aGVsbG8\"x"},"id":7,"more":"#;
+        assert_eq!(head_field(head, "id"), Some(json!(7)));
+        assert_eq!(head_field(head, "jsonrpc"), Some(json!("2.0")));
+
+        let cut = r#"{"jsonrpc":"2.0","id":"req-1","result":{"blob":"AAAA…[truncated 9 bytes]"#;
+        assert_eq!(head_field(cut, "id"), Some(json!("req-1")));
+        assert_eq!(head_field(cut, "method"), None);
+        // A value equal to the key isn't the key
+        assert_eq!(head_field(r#"{"method":"id"}"#, "id"), None);
+    }
+
+    #[test]
+    fn test_stats_count_by_policy() {
+        let tracker = OversizedTracker::default();
+        let message = |size_bytes, policy| OversizedMessage {
+            server_name: "big".to_string(),
+            direction: TrafficDirection::Incoming,
+            size_bytes,
+            limit_bytes: 10,
+            policy,
+            method: None,
+            at: Utc::now(),
+        };
+        tracker.record(&message(20, OversizedPolicy::Reject));
+        tracker.record(&message(50, OversizedPolicy::Truncate));
+
+        let stats = &tracker.stats()["big"];
+        assert_eq!(
+            (stats.rejected, stats.truncated, stats.passed_through),
+            (1, 1, 0)
+        );
+        assert_eq!(stats.largest_bytes, 50);
+        assert_eq!(stats.total_bytes, 70);
+    }
+}
@@ -0,0 +1,485 @@
+//! Published versions of npm and PyPI packages
+//!
+//! Registry entries run through `npx` or `uvx` install whatever version is
+//! latest when the server starts. To pin one instead, the versions a package
+//! has are read from the npm registry (abbreviated metadata) or PyPI's JSON
+//! API, newest first, and the chosen one is written into the server's args as
+//! `pkg@1.4.2`.
+//!
+//! Package details (publish date, maintainers, license and weekly downloads)
+//! come from the full npm document and npm's download counts, or PyPI's JSON
+//! API and pypistats.org. Download counts are best effort: a package is still
+//! described when its counts can't be fetched.
+
+use crate::error::{SynapticError, SynapticResult};
+use crate::registry::InstallMethod;
+use chrono::{DateTime, Utc};
+use reqwest::header::ACCEPT;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::time::Duration;
+
+pub const NPM_REGISTRY_URL: &str = "https://registry.npmjs.org";
+pub const PYPI_URL: &str = "https://pypi.org";
+pub const NPM_DOWNLOADS_URL: &str = "https://api.npmjs.org/downloads/point/last-week";
+pub const PYPI_STATS_URL: &str = "https://pypistats.org/api/packages";
+
+/// npm's abbreviated package document, enough to list versions
+const NPM_ABBREVIATED: &str = "application/vnd.npm.install-v1+json";
+
+const PACKAGE_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Package index a package is published to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Npm,
+    Pypi,
+}
+
+/// One published version
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageVersion {
+    pub version: String,
+    /// Upload time (PyPI only; npm's abbreviated metadata has none)
+    pub published_at: Option<DateTime<Utc>>,
+    /// Deprecated on npm or yanked on PyPI
+    pub withdrawn: bool,
+    /// Pre-release such as `2.0.0-beta.1` or `2.0rc1`
+    pub prerelease: bool,
+}
+
+/// Versions of a package, newest first
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageVersions {
+    pub package: String,
+    pub ecosystem: Ecosystem,
+    /// What an unpinned install runs (npm's `latest` tag, PyPI's current release)
+    pub latest: Option<String>,
+    pub versions: Vec<PackageVersion>,
+}
+
+/// What a package's indexes say about it, to judge its health before installing
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageDetails {
+    pub package: String,
+    pub ecosystem: Ecosystem,
+    pub latest: Option<String>,
+    /// When the latest version was published
+    pub published_at: Option<DateTime<Utc>>,
+    /// Downloads over the last week; `None` when the counts couldn't be fetched
+    pub weekly_downloads: Option<u64>,
+    pub maintainers: Vec<String>,
+    pub license: Option<String>,
+}
+
+/// Package and index an install method runs from, if it runs one
+pub fn package_of(method: &InstallMethod) -> Option<(Ecosystem, &str)> {
+    match method {
+        InstallMethod::Npx { package } => Some((Ecosystem::Npm, package)),
+        InstallMethod::Uvx { package } => Some((Ecosystem::Pypi, package)),
+        _ => None,
+    }
+}
+
+/// Published versions of the package an install method runs
+pub async fn fetch_versions(method: &InstallMethod) -> SynapticResult<PackageVersions> {
+    let (ecosystem, package) = package_of(method).ok_or_else(|| {
+        SynapticError::RegistryError("Only npx and uvx servers have versions".to_string())
+    })?;
+    fetch_package(ecosystem, package).await
+}
+
+/// Published versions of a package
+pub async fn fetch_package(ecosystem: Ecosystem, package: &str) -> SynapticResult<PackageVersions> {
+    let url = match ecosystem {
+        Ecosystem::Npm => format!("{}/{}", NPM_REGISTRY_URL, package.replace('/', "%2f")),
+        Ecosystem::Pypi => format!("{}/pypi/{}/json", PYPI_URL, package),
+    };
+    let document = fetch_document(&url, NPM_ABBREVIATED).await?;
+    let versions = match ecosystem {
+        Ecosystem::Npm => parse_npm(package, &document),
+        Ecosystem::Pypi => parse_pypi(package, &document),
+    };
+    Ok(versions)
+}
+
+/// Latest release, maintainers, license and weekly downloads of a package
+pub async fn fetch_details(ecosystem: Ecosystem, package: &str) -> SynapticResult<PackageDetails> {
+    let (url, downloads_url) = match ecosystem {
+        Ecosystem::Npm => (
+            format!("{}/{}", NPM_REGISTRY_URL, package.replace('/', "%2f")),
+            format!("{}/{}", NPM_DOWNLOADS_URL, package),
+        ),
+        Ecosystem::Pypi => (
+            format!("{}/pypi/{}/json", PYPI_URL, package),
+            format!("{}/{}/recent", PYPI_STATS_URL, package.to_lowercase()),
+        ),
+    };
+    let (document, downloads) = futures::join!(
+        fetch_document(&url, "application/json"),
+        fetch_document(&downloads_url, "application/json")
+    );
+    let mut details = match ecosystem {
+        Ecosystem::Npm => parse_npm_details(package, &document?),
+        Ecosystem::Pypi => parse_pypi_details(package, &document?),
+    };
+    details.weekly_downloads = match downloads {
+        Ok(downloads) => weekly_downloads(&downloads),
+        Err(e) => {
+            eprintln!("Download counts unavailable for {}: {}", package, e);
+            None
+        }
+    };
+    Ok(details)
+}
+
+/// GET a JSON document, preferring `accept` (npm serves its abbreviated form on request)
+pub(crate) async fn fetch_document(url: &str, accept: &str) -> SynapticResult<Value> {
+    let package_error = |e: reqwest::Error| SynapticError::RegistryError(format!("{}: {}", url, e));
+    let client = Client::builder()
+        .timeout(PACKAGE_TIMEOUT)
+        .build()
+        .map_err(package_error)?;
+    let response = client
+        .get(url)
+        .header(ACCEPT, format!("{}, application/json", accept))
+        .send()
+        .await
+        .map_err(package_error)?;
+    if !response.status().is_success() {
+        return Err(SynapticError::RegistryError(format!(
+            "{} returned {}",
+            url,
+            response.status()
+        )));
+    }
+    response.json().await.map_err(package_error)
+}
+
+/// Versions listed in an npm package document
+pub fn parse_npm(package: &str, document: &Value) -> PackageVersions {
+    let mut versions: Vec<PackageVersion> = document
+        .get("versions")
+        .and_then(|v| v.as_object())
+        .into_iter()
+        .flatten()
+        .map(|(version, manifest)| PackageVersion {
+            version: version.clone(),
+            published_at: document
+                .pointer(&format!("/time/{}", version))
+                .and_then(|t| t.as_str())
+                .and_then(parse_time),
+            withdrawn: manifest.get("deprecated").is_some_and(|d| d.is_string()),
+            prerelease: is_prerelease(version),
+        })
+        .collect();
+    versions.sort_by(|a, b| compare_versions(&b.version, &a.version));
+    PackageVersions {
+        package: package.to_string(),
+        ecosystem: Ecosystem::Npm,
+        latest: document
+            .pointer("/dist-tags/latest")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        versions,
+    }
+}
+
+/// Versions listed in a PyPI JSON API document (releases without files are skipped)
+pub fn parse_pypi(package: &str, document: &Value) -> PackageVersions {
+    let mut versions: Vec<PackageVersion> = document
+        .get("releases")
+        .and_then(|r| r.as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(version, files)| {
+            let files = files.as_array().filter(|files| !files.is_empty())?;
+            Some(PackageVersion {
+                version: version.clone(),
+                published_at: files
+                    .iter()
+                    .filter_map(|f| f.get("upload_time_iso_8601")?.as_str())
+                    .filter_map(parse_time)
+                    .min(),
+                withdrawn: files
+                    .iter()
+                    .all(|f| f.get("yanked").and_then(|y| y.as_bool()) == Some(true)),
+                prerelease: is_prerelease(version),
+            })
+        })
+        .collect();
+    versions.sort_by(|a, b| compare_versions(&b.version, &a.version));
+    PackageVersions {
+        package: package.to_string(),
+        ecosystem: Ecosystem::Pypi,
+        latest: document
+            .pointer("/info/version")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        versions,
+    }
+}
+
+/// Details in a full npm package document
+pub fn parse_npm_details(package: &str, document: &Value) -> PackageDetails {
+    let latest = document
+        .pointer("/dist-tags/latest")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    let manifest = latest
+        .as_ref()
+        .and_then(|latest| document.get("versions")?.get(latest));
+    PackageDetails {
+        package: package.to_string(),
+        ecosystem: Ecosystem::Npm,
+        published_at: latest
+            .as_ref()
+            .and_then(|latest| document.get("time")?.get(latest)?.as_str())
+            .and_then(parse_time),
+        weekly_downloads: None,
+        maintainers: document
+            .get("maintainers")
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m.get("name").or(Some(m))?.as_str().map(String::from))
+            .collect(),
+        // Older packages give `{"type": "MIT"}` instead of an SPDX string
+        license: [
+            manifest.and_then(|m| m.get("license")),
+            document.get("license"),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|license| license.as_str().or_else(|| license.get("type")?.as_str()))
+        .map(String::from),
+        latest,
+    }
+}
+
+/// Details in a PyPI JSON API document
+pub fn parse_pypi_details(package: &str, document: &Value) -> PackageDetails {
+    let info = document.get("info");
+    let text = |field: &str| {
+        info.and_then(|info| info.get(field)?.as_str())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+    };
+    // `license` may hold a whole license text; the classifier names it
+    let classifier = info
+        .and_then(|info| info.get("classifiers")?.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|c| c.as_str()?.strip_prefix("License :: "))
+        .map(|c| c.rsplit(" :: ").next().unwrap_or(c))
+        .next();
+    PackageDetails {
+        package: package.to_string(),
+        ecosystem: Ecosystem::Pypi,
+        latest: text("version").map(String::from),
+        published_at: document
+            .get("urls")
+            .and_then(|urls| urls.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|f| f.get("upload_time_iso_8601")?.as_str())
+            .filter_map(parse_time)
+            .min(),
+        weekly_downloads: None,
+        maintainers: [text("maintainer"), text("author")]
+            .into_iter()
+            .flatten()
+            .map(String::from)
+            .fold(Vec::new(), |mut names, name| {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+                names
+            }),
+        license: text("license_expression")
+            .or(text("license").filter(|license| !license.contains('\n')))
+            .or(classifier)
+            .map(String::from),
+    }
+}
+
+/// Last week's count in an npm downloads or pypistats document
+fn weekly_downloads(document: &Value) -> Option<u64> {
+    document
+        .get("downloads")
+        .or_else(|| document.pointer("/data/last_week"))?
+        .as_u64()
+}
+
+fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Whether a version has a pre-release part (`-beta.1`, `rc1`, `a2`, `.dev0`)
+fn is_prerelease(version: &str) -> bool {
+    let (release, suffix) = split_release(version);
+    !suffix.is_empty() || release.is_empty()
+}
+
+/// Leading numeric release (`1.4.2`) and whatever follows it
+fn split_release(version: &str) -> (Vec<u64>, &str) {
+    let version = version.trim_start_matches('v');
+    let mut release = Vec::new();
+    let mut rest = version;
+    loop {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits == 0 {
+            break;
+        }
+        release.push(rest[..digits].parse().unwrap_or(u64::MAX));
+        rest = &rest[digits..];
+        match rest.strip_prefix('.') {
+            Some(next) if next.starts_with(|c: char| c.is_ascii_digit()) => rest = next,
+            _ => break,
+        }
+    }
+    // Build metadata (`+build.5`, `+local`) doesn't make a pre-release
+    let suffix = rest.split('+').next().unwrap_or_default();
+    (release, suffix)
+}
+
+/// Order of two versions: release numbers first, then a release above its pre-releases
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (release_a, suffix_a) = split_release(a);
+    let (release_b, suffix_b) = split_release(b);
+    let width = release_a.len().max(release_b.len());
+    let padded = |release: &[u64]| {
+        let mut release = release.to_vec();
+        release.resize(width, 0);
+        release
+    };
+    padded(&release_a).cmp(&padded(&release_b)).then_with(|| {
+        match (suffix_a.is_empty(), suffix_b.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => suffix_a.cmp(suffix_b),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_versions_order_like_semver() {
+        let mut versions = vec!["1.10.0", "1.2.0", "2.0.0-beta.1", "1.9", "2.0.0", "0.9.1"];
+        versions.sort_by(|a, b| compare_versions(b, a));
+        assert_eq!(
+            versions,
+            vec!["2.0.0", "2.0.0-beta.1", "1.10.0", "1.9", "1.2.0", "0.9.1"]
+        );
+        assert!(is_prerelease("2.0rc1"));
+        assert!(is_prerelease("1.0.0.dev0"));
+        assert!(!is_prerelease("1.0.0+build.5"));
+    }
+
+    #[test]
+    fn test_parses_npm_and_pypi_documents() {
+        let npm = parse_npm(
+            "@acme/weather",
+            &json!({
+                "dist-tags": {"latest": "1.1.0"},
+                "versions": {
+                    "1.0.0": {},
+                    "1.1.0": {},
+                    "1.2.0-next.0": {},
+                    "0.1.0": {"deprecated": "use 1.x"}
+                }
+            }),
+        );
+        assert_eq!(npm.latest.as_deref(), Some("1.1.0"));
+        let listed: Vec<_> = npm
+            .versions
+            .iter()
+            .map(|v| (v.version.as_str(), v.prerelease, v.withdrawn))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("1.2.0-next.0", true, false),
+                ("1.1.0", false, false),
+                ("1.0.0", false, false),
+                ("0.1.0", false, true)
+            ]
+        );
+
+        let pypi = parse_pypi(
+            "mcp-server-sqlite",
+            &json!({
+                "info": {"version": "0.6.2"},
+                "releases": {
+                    "0.6.2": [{"upload_time_iso_8601": "2025-01-02T00:00:00.000000Z", "yanked": false}],
+                    "0.6.1": [{"upload_time_iso_8601": "2024-12-01T00:00:00Z", "yanked": true}],
+                    "0.5.0": []
+                }
+            }),
+        );
+        assert_eq!(pypi.latest.as_deref(), Some("0.6.2"));
+        assert_eq!(pypi.versions.len(), 2);
+        assert!(pypi.versions[0].published_at.is_some());
+        assert!(pypi.versions[1].withdrawn);
+    }
+
+    #[test]
+    fn test_parses_package_details() {
+        let npm = parse_npm_details(
+            "@acme/weather",
+            &json!({
+                "dist-tags": {"latest": "1.1.0"},
+                "time": {"1.0.0": "2024-06-01T00:00:00Z", "1.1.0": "2025-02-03T04:05:06.789Z"},
+                "maintainers": [{"name": "ana", "email": "ana@example.com"}, {"name": "bo"}],
+                "license": {"type": "ISC"},
+                "versions": {"1.1.0": {"license": "MIT"}}
+            }),
+        );
+        assert_eq!(npm.latest.as_deref(), Some("1.1.0"));
+        assert_eq!(
+            npm.published_at.unwrap().to_rfc3339(),
+            "2025-02-03T04:05:06.789+00:00"
+        );
+        assert_eq!(npm.maintainers, vec!["ana", "bo"]);
+        assert_eq!(npm.license.as_deref(), Some("MIT"));
+
+        let pypi = parse_pypi_details(
+            "mcp-server-sqlite",
+            &json!({
+                "info": {
+                    "version": "0.6.2",
+                    "author": "Ana",
+                    "maintainer": "",
+                    "license": "MIT License\n\nPermission is hereby granted...",
+                    "classifiers": ["License :: OSI Approved :: MIT License"]
+                },
+                "urls": [{"upload_time_iso_8601": "2025-01-02T00:00:00.000000Z"}]
+            }),
+        );
+        assert_eq!(pypi.maintainers, vec!["Ana"]);
+        assert_eq!(pypi.license.as_deref(), Some("MIT License"));
+        assert!(pypi.published_at.is_some());
+
+        assert_eq!(
+            weekly_downloads(&json!({"downloads": 1200, "package": "x"})),
+            Some(1200)
+        );
+        assert_eq!(
+            weekly_downloads(&json!({"data": {"last_day": 3, "last_week": 40}})),
+            Some(40)
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Filesystem path validation and normalization for server args
+//!
+//! Detects path-like args (plus `cwd` and `pathPrepend`), expands `~`,
+//! normalizes separators and checks existence so broken paths surface when
+//! the server is saved rather than when it fails at runtime.
+
+use crate::config::McpServer;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Result of checking one path-like value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PathCheck {
+    /// Where the path was found: `args[2]`, `cwd` or `pathPrepend[0]`
+    pub location: String,
+    pub original: String,
+    pub normalized: String,
+    pub exists: bool,
+}
+
+/// Normalized server plus everything noticed along the way
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathValidation {
+    /// Server with `~` expanded and separators normalized
+    pub server: McpServer,
+    pub paths: Vec<PathCheck>,
+    /// Human-readable problems (missing paths); never fatal
+    pub warnings: Vec<String>,
+}
+
+/// Validate and normalize every path-like value of a server definition
+pub fn validate_server_paths(server: &McpServer) -> PathValidation {
+    // Paths of remote servers refer to the other machine
+    if server.ssh.is_some() {
+        return PathValidation {
+            server: server.clone(),
+            paths: Vec::new(),
+            warnings: Vec::new(),
+        };
+    }
+
+    let mut normalized = server.clone();
+    let mut paths = Vec::new();
+
+    for (index, arg) in server.args.iter().enumerate() {
+        // `--root=/some/dir` style args carry the path after the `=`
+        let (prefix, value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with('-') => (format!("{}=", flag), value),
+            _ => (String::new(), arg.as_str()),
+        };
+
+        if !looks_like_path(value) {
+            continue;
+        }
+
+        let check = check_path(format!("args[{}]", index), value);
+        normalized.args[index] = format!("{}{}", prefix, check.normalized);
+        paths.push(check);
+    }
+
+    if let Some(cwd) = &server.cwd {
+        let check = check_path("cwd".to_string(), cwd);
+        normalized.cwd = Some(check.normalized.clone());
+        paths.push(check);
+    }
+
+    // Relative PATH dirs are checked against the server's cwd
+    let resolved_dirs = server.path_prepend_dirs();
+    for (index, dir) in server.path_prepend.iter().enumerate() {
+        let check = PathCheck {
+            location: format!("pathPrepend[{}]", index),
+            original: dir.clone(),
+            normalized: normalize_path(dir),
+            exists: resolved_dirs[index].is_dir(),
+        };
+        normalized.path_prepend[index] = check.normalized.clone();
+        paths.push(check);
+    }
+
+    let warnings = paths
+        .iter()
+        .filter(|c| !c.exists)
+        .map(|c| format!("{}: path does not exist: {}", c.location, c.normalized))
+        .collect();
+
+    PathValidation {
+        server: normalized,
+        paths,
+        warnings,
+    }
+}
+
+/// Heuristic: absolute, home-relative, explicitly relative, or drive-letter paths
+pub fn looks_like_path(value: &str) -> bool {
+    if value.is_empty() || value.starts_with('-') || value.starts_with('@') {
+        return false;
+    }
+
+    // URLs are handled by the server, not the filesystem
+    if value.contains("://") {
+        return false;
+    }
+
+    let bytes = value.as_bytes();
+    let drive_letter = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/');
+
+    value.starts_with('/')
+        || value.starts_with('~')
+        || value.starts_with("./")
+        || value.starts_with("../")
+        || value.starts_with(".\\")
+        || value.starts_with("..\\")
+        || value.starts_with("\\\\")
+        || drive_letter
+}
+
+/// Expand `~` to the home directory
+pub fn expand_tilde(value: &str) -> String {
+    let rest = if value == "~" {
+        Some("")
+    } else {
+        value
+            .strip_prefix("~/")
+            .or_else(|| value.strip_prefix("~\\"))
+    };
+
+    match (rest, dirs::home_dir()) {
+        (Some(""), Some(home)) => home.to_string_lossy().to_string(),
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Expand `~` and convert separators to the platform's native form
+pub fn normalize_path(value: &str) -> String {
+    let expanded = expand_tilde(value);
+
+    if cfg!(windows) {
+        expanded.replace('/', "\\")
+    } else {
+        expanded
+    }
+}
+
+fn check_path(location: String, value: &str) -> PathCheck {
+    let normalized = normalize_path(value);
+    let exists = Path::new(&normalized).exists();
+
+    PathCheck {
+        location,
+        original: value.to_string(),
+        normalized,
+        exists,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn server(args: Vec<&str>) -> McpServer {
+        McpServer {
+            command: "npx".to_string(),
+            args: args.into_iter().map(String::from).collect(),
+            env: HashMap::new(),
+            cwd: None,
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_looks_like_path() {
+        assert!(looks_like_path("/tmp"));
+        assert!(looks_like_path("~/projects"));
+        assert!(looks_like_path("./data"));
+        assert!(looks_like_path("C:\\Users"));
+        assert!(looks_like_path("D:/data"));
+
+        assert!(!looks_like_path("-y"));
+        assert!(!looks_like_path("@modelcontextprotocol/server-filesystem"));
+        assert!(!looks_like_path("database.db"));
+        assert!(!looks_like_path("https://example.com/path"));
+    }
+
+    #[test]
+    fn test_expand_tilde() {
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(expand_tilde("~"), home.to_string_lossy());
+            assert_eq!(
+                expand_tilde("~/x"),
+                home.join("x").to_string_lossy().to_string()
+            );
+        }
+        assert_eq!(expand_tilde("/abs"), "/abs");
+        assert_eq!(expand_tilde("~user/x"), "~user/x");
+    }
+
+    #[test]
+    fn test_validate_reports_missing_and_keeps_existing() {
+        let existing = std::env::temp_dir().to_string_lossy().to_string();
+        let missing = std::env::temp_dir()
+            .join(format!("synaptic-missing-{}", uuid::Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string();
+        let flag = format!("--root={}", missing);
+
+        let result = validate_server_paths(&server(vec!["-y", "pkg", &existing, &flag]));
+
+        assert_eq!(result.paths.len(), 2);
+        assert!(result.paths[0].exists);
+        assert!(!result.paths[1].exists);
+        assert_eq!(result.paths[1].location, "args[3]");
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.server.args[3].starts_with("--root="));
+    }
+}
@@ -0,0 +1,396 @@
+//! Tool-level allow/deny policies
+//!
+//! A policy decides what happens to a `tools/call` on its way to a server:
+//! it passes, is answered with an error instead of reaching the server, or
+//! waits until the user approves it (e.g. for tools that run shell
+//! commands). Policies live in the settings and are checked by the stdin
+//! writer in order; the first matching one decides, and calls none matches
+//! pass. Every decision a policy makes is written to the audit log, and
+//! calls waiting for approval are announced with a `tool-approval-requested`
+//! event. A call waiting for approval is held aside: the requests sent after
+//! it go on to the server meanwhile, and the call follows once approved.
+
+use crate::audit::{self, AuditAction};
+use crate::error::{SynapticError, SynapticResult};
+use crate::host::Host;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// JSON-RPC error code of a call a policy refused
+pub const POLICY_DENIED_CODE: i64 = -32001;
+
+/// Time the user has to approve a call before it is denied
+pub const APPROVAL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// What a policy does with the calls it matches
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    Allow,
+    Deny,
+    /// Hold the call until the user approves or denies it
+    RequireApproval,
+}
+
+/// Decide the fate of calls to matching tools
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolPolicy {
+    /// Only this server (all servers when unset)
+    pub server_name: Option<String>,
+    /// Tool name, where `*` matches any run of characters (`delete_*`, `*shell*`)
+    pub tool: String,
+    pub action: PolicyAction,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ToolPolicy {
+    pub fn matches(&self, server_name: &str, tool: &str) -> bool {
+        self.enabled
+            && self
+                .server_name
+                .as_deref()
+                .is_none_or(|name| name == server_name)
+            && wildcard_matches(&self.tool, tool)
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` stands for any run of characters
+pub(crate) fn wildcard_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*`: the whole name
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// First enabled policy matching a call, if any
+pub fn find_policy<'a>(
+    policies: &'a [ToolPolicy],
+    server_name: &str,
+    tool: &str,
+) -> Option<&'a ToolPolicy> {
+    policies.iter().find(|p| p.matches(server_name, tool))
+}
+
+/// Who made a decision
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DecidedBy {
+    Policy,
+    User,
+    /// Nobody answered within [`APPROVAL_TIMEOUT`], or the server stopped
+    Timeout,
+}
+
+/// Payload of the `tool-approval-requested` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolApproval {
+    pub id: String,
+    pub server_name: String,
+    pub tool: String,
+    /// Arguments of the call, with secrets redacted
+    pub arguments: Value,
+    pub requested_at: DateTime<Utc>,
+}
+
+/// Payload of the `tool-policy-decision` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolDecision {
+    pub server_name: String,
+    pub tool: String,
+    pub allowed: bool,
+    pub decided_by: DecidedBy,
+    pub policy: ToolPolicy,
+}
+
+struct Pending {
+    approval: ToolApproval,
+    reply: oneshot::Sender<bool>,
+}
+
+/// What the policies make of an outgoing message
+pub enum PolicyCheck {
+    /// Send it on
+    Pass,
+    /// Answer it with this error instead of sending it
+    Refuse(Value),
+    /// Hold it until the user decides
+    AwaitApproval(ApprovalWait),
+}
+
+/// A call waiting for the user's decision
+pub struct ApprovalWait {
+    id: Value,
+    approval: ToolApproval,
+    policy: ToolPolicy,
+    replied: oneshot::Receiver<bool>,
+}
+
+impl ApprovalWait {
+    /// Wait for the user (or [`APPROVAL_TIMEOUT`]); `None` means the call may go on
+    ///
+    /// Returns the error response standing in for a denied call.
+    pub async fn decision<H: Host>(self, host: &H) -> Option<Value> {
+        let (allowed, decided_by) = match tokio::time::timeout(APPROVAL_TIMEOUT, self.replied).await
+        {
+            Ok(Ok(approved)) => (approved, DecidedBy::User),
+            _ => {
+                if let Some(pm) = host.process_manager() {
+                    pm.policies
+                        .pending
+                        .lock()
+                        .unwrap()
+                        .remove(&self.approval.id);
+                }
+                (false, DecidedBy::Timeout)
+            }
+        };
+        let ToolApproval {
+            server_name,
+            tool,
+            arguments,
+            ..
+        } = self.approval;
+        decide(
+            host,
+            &self.id,
+            ToolDecision {
+                server_name,
+                tool,
+                allowed,
+                decided_by,
+                policy: self.policy,
+            },
+            arguments,
+        )
+    }
+}
+
+/// Calls waiting for the user's approval
+#[derive(Default)]
+pub struct PolicyEngine {
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl PolicyEngine {
+    /// Calls waiting for approval, oldest first
+    pub fn list_pending(&self) -> Vec<ToolApproval> {
+        let mut pending: Vec<_> = self
+            .pending
+            .lock()
+            .unwrap()
+            .values()
+            .map(|p| p.approval.clone())
+            .collect();
+        pending.sort_by_key(|a| a.requested_at);
+        pending
+    }
+
+    /// Approve or deny a waiting call
+    pub fn resolve(&self, id: &str, approved: bool) -> SynapticResult<()> {
+        let pending = self.pending.lock().unwrap().remove(id).ok_or_else(|| {
+            SynapticError::InspectorError(format!("No tool call {} is waiting for approval", id))
+        })?;
+        let _ = pending.reply.send(approved);
+        Ok(())
+    }
+
+    /// Forget calls waiting for a server that stopped
+    pub fn release_server(&self, server_name: &str) {
+        self.pending
+            .lock()
+            .unwrap()
+            .retain(|_, p| p.approval.server_name != server_name);
+    }
+
+    /// Apply the settings' policies to an outgoing message
+    ///
+    /// Calls no policy matches pass; calls waiting for approval are announced
+    /// right away and decided by [`ApprovalWait::decision`].
+    pub fn check<H: Host>(&self, host: &H, server_name: &str, request: &Value) -> PolicyCheck {
+        if request.get("method").and_then(|m| m.as_str()) != Some("tools/call") {
+            return PolicyCheck::Pass;
+        }
+        let Some(id) = request.get("id") else {
+            return PolicyCheck::Pass;
+        };
+        let params = request.get("params");
+        let tool = params
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .unwrap_or_default();
+        let Some(settings) = host.app_state().and_then(|state| state.get_settings().ok()) else {
+            return PolicyCheck::Pass;
+        };
+        let Some(policy) = find_policy(&settings.tool_policies, server_name, tool).cloned() else {
+            return PolicyCheck::Pass;
+        };
+        let arguments = params
+            .and_then(|p| p.get("arguments"))
+            .cloned()
+            .unwrap_or(Value::Null);
+
+        let allowed = match policy.action {
+            PolicyAction::Allow => true,
+            PolicyAction::Deny => false,
+            PolicyAction::RequireApproval => {
+                let approval = ToolApproval {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    server_name: server_name.to_string(),
+                    tool: tool.to_string(),
+                    arguments,
+                    requested_at: Utc::now(),
+                };
+                let (reply, replied) = oneshot::channel();
+                self.pending.lock().unwrap().insert(
+                    approval.id.clone(),
+                    Pending {
+                        approval: approval.clone(),
+                        reply,
+                    },
+                );
+                host.emit("tool-approval-requested", &approval);
+                return PolicyCheck::AwaitApproval(ApprovalWait {
+                    id: id.clone(),
+                    approval,
+                    policy,
+                    replied,
+                });
+            }
+        };
+
+        let decision = ToolDecision {
+            server_name: server_name.to_string(),
+            tool: tool.to_string(),
+            allowed,
+            decided_by: DecidedBy::Policy,
+            policy,
+        };
+        match decide(host, id, decision, arguments) {
+            Some(error) => PolicyCheck::Refuse(error),
+            None => PolicyCheck::Pass,
+        }
+    }
+}
+
+/// Record and announce a decision, returning the error response of a refused call
+fn decide<H: Host>(
+    host: &H,
+    id: &Value,
+    decision: ToolDecision,
+    arguments: Value,
+) -> Option<Value> {
+    record(host, &decision, arguments);
+    host.emit("tool-policy-decision", &decision);
+
+    (!decision.allowed).then(|| {
+        let tool = &decision.tool;
+        let message = match decision.decided_by {
+            DecidedBy::Policy => format!("Tool {} is denied by policy", tool),
+            DecidedBy::User => format!("Call to tool {} was denied by the user", tool),
+            DecidedBy::Timeout => format!("Call to tool {} was not approved in time", tool),
+        };
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": POLICY_DENIED_CODE, "message": message },
+        })
+    })
+}
+
+/// Write a decision to the audit log
+fn record<H: Host>(host: &H, decision: &ToolDecision, arguments: Value) {
+    let Some(db) = host.app_state().and_then(|state| state.database().ok()) else {
+        return;
+    };
+    let action = if decision.allowed {
+        AuditAction::ToolAllowed
+    } else {
+        AuditAction::ToolDenied
+    };
+    let after = json!({
+        "tool": decision.tool,
+        "arguments": arguments,
+        "decidedBy": decision.decided_by,
+        "policy": decision.policy,
+    });
+    if let Err(e) = audit::record_change(db, action, Some(&decision.server_name), None, Some(after))
+    {
+        eprintln!("Failed to audit tool policy decision: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(server_name: Option<&str>, tool: &str, action: PolicyAction) -> ToolPolicy {
+        ToolPolicy {
+            server_name: server_name.map(String::from),
+            tool: tool.to_string(),
+            action,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert!(wildcard_matches("delete_file", "delete_file"));
+        assert!(!wildcard_matches("delete_file", "delete_files"));
+        assert!(wildcard_matches("delete_*", "delete_file"));
+        assert!(wildcard_matches("*shell*", "run_shell_command"));
+        assert!(wildcard_matches("*", "anything"));
+        assert!(wildcard_matches("a*b*c", "abc"));
+        assert!(!wildcard_matches("a*bc", "abc_"));
+        assert!(!wildcard_matches("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn test_first_matching_policy_decides() {
+        let policies = vec![
+            policy(Some("fs"), "read_*", PolicyAction::Allow),
+            policy(Some("fs"), "delete_file", PolicyAction::Deny),
+            ToolPolicy {
+                enabled: false,
+                ..policy(None, "*", PolicyAction::Deny)
+            },
+            policy(None, "*shell*", PolicyAction::RequireApproval),
+        ];
+        let action =
+            |server: &str, tool: &str| find_policy(&policies, server, tool).map(|p| p.action);
+
+        assert_eq!(action("fs", "read_file"), Some(PolicyAction::Allow));
+        assert_eq!(action("fs", "delete_file"), Some(PolicyAction::Deny));
+        assert_eq!(action("git", "delete_file"), None);
+        assert_eq!(
+            action("git", "exec_shell"),
+            Some(PolicyAction::RequireApproval)
+        );
+        assert_eq!(action("fs", "write_file"), None);
+    }
+}
@@ -0,0 +1,248 @@
+//! Environment preflight before spawning a server
+//!
+//! Catches the usual reasons a server dies right after launch, before it is
+//! launched: the runtime isn't installed, an env var the registry entry needs
+//! is empty, or the working directory doesn't exist. Servers reached over
+//! HTTP only have their url checked. Failures block the spawn; warnings are
+//! only reported.
+
+use crate::config::McpServer;
+use crate::paths;
+use crate::registry;
+use crate::resolve;
+use crate::state::AppState;
+use crate::transport;
+use serde::{Deserialize, Serialize};
+
+/// What a preflight check looked at
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightCheckKind {
+    Runtime,
+    Env,
+    Path,
+    Url,
+}
+
+/// Outcome of one preflight check
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    /// Worth a look, but doesn't block the spawn
+    Warning,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightCheck {
+    pub kind: PreflightCheckKind,
+    /// Executable, env var name or path location (`cwd`, `args[2]`, ...)
+    pub subject: String,
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+/// Everything checked before spawning a server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightReport {
+    pub server_name: String,
+    /// No check failed
+    pub ok: bool,
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    /// Messages of the failed checks, joined for an error message
+    pub fn summary(&self) -> String {
+        self.checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Failed)
+            .map(|c| c.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Check that a server has what it needs to start
+///
+/// With app state, the env vars required by entries of the synced registry are checked too.
+pub fn preflight_server(
+    state: Option<&AppState>,
+    server_name: &str,
+    server: &McpServer,
+) -> PreflightReport {
+    let checks = if server.is_remote() {
+        vec![check_url(server)]
+    } else {
+        let mut checks = vec![check_runtime(server)];
+        checks.extend(check_env(state, server));
+        checks.extend(check_paths(server));
+        checks
+    };
+
+    PreflightReport {
+        server_name: server_name.to_string(),
+        ok: checks.iter().all(|c| c.status != CheckStatus::Failed),
+        checks,
+    }
+}
+
+fn check_runtime(server: &McpServer) -> PreflightCheck {
+    // Remote servers only need ssh here; their runtime lives on the other host
+    let launched = server.wrapped();
+    let (command, resolved) = match &server.ssh {
+        Some(_) => ("ssh", resolve::resolve_command("ssh")),
+        None => (
+            launched.command.as_str(),
+            resolve::resolve_server_command(&launched),
+        ),
+    };
+
+    let (status, message) = match resolved {
+        Ok(path) => (
+            CheckStatus::Ok,
+            format!("{} found at {}", command, path.display()),
+        ),
+        Err(e) => (CheckStatus::Failed, e.to_string()),
+    };
+    PreflightCheck {
+        kind: PreflightCheckKind::Runtime,
+        subject: command.to_string(),
+        status,
+        message,
+    }
+}
+
+fn check_url(server: &McpServer) -> PreflightCheck {
+    let (status, message) = match transport::remote_url(server) {
+        Ok(url) => (CheckStatus::Ok, format!("{} is a valid endpoint", url)),
+        Err(e) => (CheckStatus::Failed, e.to_string()),
+    };
+    PreflightCheck {
+        kind: PreflightCheckKind::Url,
+        subject: server.url.clone().unwrap_or_default(),
+        status,
+        message,
+    }
+}
+
+fn check_env(state: Option<&AppState>, server: &McpServer) -> Vec<PreflightCheck> {
+    let required = registry::required_env(state, server);
+
+    let mut names: Vec<&String> = required.iter().chain(server.env.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let is_required = required.contains(name);
+            // Unset in the config means inherited from Synaptic's environment
+            let value = match server.env.get(name) {
+                Some(value) => value.clone(),
+                None => std::env::var(name).unwrap_or_default(),
+            };
+
+            let (status, message) = match (value.trim().is_empty(), is_required) {
+                (false, _) => (CheckStatus::Ok, format!("{} is set", name)),
+                (true, true) => (
+                    CheckStatus::Failed,
+                    format!("{} is required but empty", name),
+                ),
+                (true, false) => (CheckStatus::Warning, format!("{} is empty", name)),
+            };
+            // Only report plain config env vars when something is off
+            if status == CheckStatus::Ok && !is_required {
+                return None;
+            }
+            Some(PreflightCheck {
+                kind: PreflightCheckKind::Env,
+                subject: name.clone(),
+                status,
+                message,
+            })
+        })
+        .collect()
+}
+
+fn check_paths(server: &McpServer) -> Vec<PreflightCheck> {
+    paths::validate_server_paths(server)
+        .paths
+        .into_iter()
+        .map(|check| {
+            // Missing arg paths may be files the server creates; dirs it runs in may not
+            let status = match (check.exists, check.location.starts_with("args")) {
+                (true, _) => CheckStatus::Ok,
+                (false, true) => CheckStatus::Warning,
+                (false, false) => CheckStatus::Failed,
+            };
+            let message = if check.exists {
+                format!("{} exists", check.normalized)
+            } else {
+                format!(
+                    "{}: path does not exist: {}",
+                    check.location, check.normalized
+                )
+            };
+            PreflightCheck {
+                kind: PreflightCheckKind::Path,
+                subject: check.location,
+                status,
+                message,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preflight_reports_missing_requirements() {
+        let missing_dir = std::env::temp_dir()
+            .join(format!("synaptic-missing-{}", uuid::Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string();
+        let server = McpServer {
+            command: "definitely-not-a-real-runtime-xyz".to_string(),
+            args: vec!["@modelcontextprotocol/server-github".to_string()],
+            env: [
+                ("GITHUB_PERSONAL_ACCESS_TOKEN".to_string(), "".to_string()),
+                ("OPTIONAL".to_string(), "".to_string()),
+                ("SET".to_string(), "x".to_string()),
+            ]
+            .into(),
+            cwd: Some(missing_dir),
+            ..Default::default()
+        };
+
+        let report = preflight_server(None, "github", &server);
+        assert!(!report.ok);
+
+        let status = |subject: &str| {
+            report
+                .checks
+                .iter()
+                .find(|c| c.subject == subject)
+                .map(|c| c.status)
+        };
+        assert_eq!(
+            status("definitely-not-a-real-runtime-xyz"),
+            Some(CheckStatus::Failed)
+        );
+        assert_eq!(
+            status("GITHUB_PERSONAL_ACCESS_TOKEN"),
+            Some(CheckStatus::Failed)
+        );
+        assert_eq!(status("OPTIONAL"), Some(CheckStatus::Warning));
+        assert_eq!(status("SET"), None);
+        assert_eq!(status("cwd"), Some(CheckStatus::Failed));
+        assert!(report
+            .summary()
+            .contains("GITHUB_PERSONAL_ACCESS_TOKEN is required"));
+    }
+}
@@ -0,0 +1,143 @@
+//! Display-ready copies of captured payloads
+//!
+//! A list row in the inspector doesn't need a server's full payload, which
+//! may carry megabytes of base64 images or file contents. Every captured
+//! message therefore gets a [`PayloadPreview`]: the payload pretty-printed
+//! with sorted keys, long strings cut to [`MAX_PREVIEW_CHARS`] characters and
+//! base64 blobs replaced by a marker, along with the size of the original.
+//! The raw payload stays on the message for the detail view.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Characters of a string kept in a preview
+pub const MAX_PREVIEW_CHARS: usize = 200;
+
+/// Normalized, size-annotated rendering of a payload
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadPreview {
+    /// Pretty-printed payload with long strings shortened
+    pub pretty: String,
+    /// Size of the payload as compact JSON
+    pub size_bytes: usize,
+    /// JSON pointers of the strings that were shortened
+    pub truncated: Vec<String>,
+}
+
+impl PayloadPreview {
+    pub fn of(payload: &Value) -> Self {
+        let mut truncated = Vec::new();
+        let shortened = shorten(payload, &mut String::new(), &mut truncated);
+        Self {
+            pretty: serde_json::to_string_pretty(&shortened).unwrap_or_default(),
+            size_bytes: payload.to_string().len(),
+            truncated,
+        }
+    }
+}
+
+/// Copy of `value` with long strings shortened, recording where
+fn shorten(value: &Value, pointer: &mut String, truncated: &mut Vec<String>) -> Value {
+    match value {
+        Value::String(text) => match shortened(text) {
+            Some(short) => {
+                truncated.push(pointer.clone());
+                Value::String(short)
+            }
+            None => value.clone(),
+        },
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    within(pointer, &index.to_string(), |pointer| {
+                        shorten(item, pointer, truncated)
+                    })
+                })
+                .collect(),
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, field)| {
+                    let escaped = key.replace('~', "~0").replace('/', "~1");
+                    let field = within(pointer, &escaped, |pointer| {
+                        shorten(field, pointer, truncated)
+                    });
+                    (key.clone(), field)
+                })
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+/// Run `f` with `segment` appended to the pointer
+fn within<T>(pointer: &mut String, segment: &str, f: impl FnOnce(&mut String) -> T) -> T {
+    let len = pointer.len();
+    pointer.push('/');
+    pointer.push_str(segment);
+    let result = f(pointer);
+    pointer.truncate(len);
+    result
+}
+
+/// Marked replacement of a string too long to preview
+fn shortened(text: &str) -> Option<String> {
+    let (cut, _) = text.char_indices().nth(MAX_PREVIEW_CHARS)?;
+    if looks_like_base64(text) {
+        return Some(format!("[base64, {} bytes]", text.len()));
+    }
+    Some(format!(
+        "{}… [truncated, {} bytes]",
+        &text[..cut],
+        text.len()
+    ))
+}
+
+fn looks_like_base64(text: &str) -> bool {
+    let data = text.split_once(";base64,").map_or(text, |(_, data)| data);
+    data.bytes()
+        .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=' | b'-' | b'_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_preview_shortens_long_strings() {
+        let image = "iVBORw0KGgo".repeat(100);
+        let text = "word ".repeat(100);
+        let payload = json!({
+            "id": 1,
+            "result": { "content": [
+                { "type": "image", "data": image },
+                { "type": "text", "text": text },
+                { "type": "text", "text": "short" },
+            ] },
+        });
+
+        let preview = PayloadPreview::of(&payload);
+        assert_eq!(preview.size_bytes, payload.to_string().len());
+        assert_eq!(
+            preview.truncated,
+            ["/result/content/0/data", "/result/content/1/text"]
+        );
+        let shown: Value = serde_json::from_str(&preview.pretty).unwrap();
+        assert_eq!(
+            shown["result"]["content"][0]["data"],
+            "[base64, 1100 bytes]"
+        );
+        let cut = shown["result"]["content"][1]["text"].as_str().unwrap();
+        assert!(cut.ends_with("… [truncated, 500 bytes]"));
+        assert_eq!(shown["result"]["content"][2]["text"], "short");
+
+        assert!(PayloadPreview::of(&json!({"a/b": "x"}))
+            .truncated
+            .is_empty());
+    }
+}
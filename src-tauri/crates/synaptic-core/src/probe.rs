@@ -0,0 +1,380 @@
+//! Catalog of what a server offers, without attaching a client
+//!
+//! Probing a stopped server starts it just for the probe: Synaptic performs
+//! the `initialize` handshake itself, lists tools, resources, resource
+//! templates and prompts (following `nextCursor` pages), and stops it again.
+//! A running server has already been initialized by its client, so it is
+//! only asked for the lists. The probe's own requests use internal ids and
+//! stay out of the inspector.
+//!
+//! [`call_tool`] and [`get_prompt`] run a single `tools/call` or
+//! `prompts/get` the same way, for trying out a server's tools and prompts
+//! from the manager. Unlike the probe's lists, these requests are ordinary
+//! traffic and show up in the inspector.
+
+use crate::config::McpServer;
+use crate::error::{SynapticError, SynapticResult};
+use crate::host::Host;
+use crate::process_manager::{spawn_mcp_server, ProcessManager};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Protocol version Synaptic asks for when it initializes a server itself
+pub const PROBE_PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Time each probe request may take
+const PROBE_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Most pages followed per list
+const MAX_LIST_PAGES: usize = 50;
+
+/// Everything a server reported about itself
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerCatalog {
+    pub server_name: String,
+    /// Whether the server was started for the probe (and stopped after it)
+    pub spawned: bool,
+    pub protocol_version: Option<String>,
+    pub server_info: Value,
+    /// Declared capabilities (null when the handshake wasn't seen)
+    pub capabilities: Value,
+    pub instructions: Option<String>,
+    pub tools: Vec<Value>,
+    pub resources: Vec<Value>,
+    pub resource_templates: Vec<Value>,
+    pub prompts: Vec<Value>,
+    /// Lists that failed, e.g. `prompts/list: Method not found`
+    pub errors: Vec<String>,
+}
+
+/// Outcome of a `tools/call` round trip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCallResult {
+    pub server_name: String,
+    pub tool_name: String,
+    /// Content blocks (`text`, `image`, `resource`, ...)
+    pub content: Vec<Value>,
+    pub structured_content: Option<Value>,
+    /// Whether the tool reported a failure (`isError`)
+    pub is_error: bool,
+    /// Whether the server was started for the call (and stopped after it)
+    pub spawned: bool,
+    pub duration_ms: u64,
+}
+
+/// Messages of a prompt rendered by its server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptResult {
+    pub server_name: String,
+    pub prompt_name: String,
+    pub description: Option<String>,
+    /// `{ role, content }` messages
+    pub messages: Vec<Value>,
+    /// Whether the server was started for the request (and stopped after it)
+    pub spawned: bool,
+}
+
+/// Probe a server, starting it temporarily when it isn't running
+pub async fn probe_server<H: Host>(
+    host: H,
+    server_name: &str,
+    server: McpServer,
+) -> SynapticResult<ServerCatalog> {
+    let Some(pm) = host.process_manager() else {
+        return Err(SynapticError::ProcessError(
+            "Process manager unavailable".to_string(),
+        ));
+    };
+
+    if pm.is_running(server_name).await {
+        return Ok(catalog_of_running(pm, server_name).await);
+    }
+
+    spawn_mcp_server(host.clone(), pm, server_name.to_string(), server).await?;
+    let result = initialize_and_list(pm, server_name).await;
+    stop_temporary(&host, pm, server_name).await;
+    result
+}
+
+/// Params of the `initialize` request Synaptic sends as a client
+pub(crate) fn initialize_params() -> Value {
+    json!({
+        "protocolVersion": PROBE_PROTOCOL_VERSION,
+        "capabilities": {},
+        "clientInfo": { "name": "wear-synaptic", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+/// Perform the `initialize` handshake on Synaptic's behalf
+pub(crate) async fn initialize(pm: &ProcessManager, server_name: &str) -> SynapticResult<Value> {
+    let result = pm
+        .request(
+            server_name,
+            "initialize",
+            Some(initialize_params()),
+            PROBE_REQUEST_TIMEOUT,
+        )
+        .await?;
+    let initialized = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+    pm.send_to_stdin(server_name, initialized.to_string())
+        .await?;
+    Ok(result)
+}
+
+/// Stop a server started only for a probe or tool call
+pub(crate) async fn stop_temporary<H: Host>(host: &H, pm: &ProcessManager, server_name: &str) {
+    let grace = host
+        .app_state()
+        .and_then(|state| state.get_settings().ok())
+        .unwrap_or_default()
+        .shutdown_grace();
+    let _ = pm.kill_process(server_name, grace).await;
+}
+
+/// Catalog of a server its client already initialized
+pub(crate) async fn catalog_of_running(pm: &ProcessManager, server_name: &str) -> ServerCatalog {
+    let mut catalog = ServerCatalog {
+        server_name: server_name.to_string(),
+        ..Default::default()
+    };
+    if let Some(negotiated) = pm.capabilities.get(server_name) {
+        catalog.protocol_version = negotiated.protocol_version;
+        catalog.server_info = negotiated.server_info;
+        catalog.capabilities = negotiated.capabilities;
+        catalog.instructions = negotiated.instructions;
+    }
+    list_catalog(pm, &mut catalog).await;
+    catalog
+}
+
+/// Catalog of a server Synaptic just started, initializing it first
+pub(crate) async fn initialize_and_list(
+    pm: &ProcessManager,
+    server_name: &str,
+) -> SynapticResult<ServerCatalog> {
+    let result = initialize(pm, server_name).await?;
+    let mut catalog = ServerCatalog {
+        server_name: server_name.to_string(),
+        spawned: true,
+        protocol_version: result
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        server_info: result.get("serverInfo").cloned().unwrap_or(Value::Null),
+        capabilities: result.get("capabilities").cloned().unwrap_or(Value::Null),
+        instructions: result
+            .get("instructions")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        ..Default::default()
+    };
+    list_catalog(pm, &mut catalog).await;
+    Ok(catalog)
+}
+
+/// Call one of a server's tools, starting the server temporarily when it isn't running
+///
+/// JSON-RPC errors (unknown tool, invalid arguments) fail the call; errors
+/// the tool itself reports come back with `is_error` set.
+pub async fn call_tool<H: Host>(
+    host: H,
+    server_name: &str,
+    server: McpServer,
+    tool_name: &str,
+    arguments: Value,
+    timeout: Duration,
+) -> SynapticResult<ToolCallResult> {
+    if !arguments.is_object() && !arguments.is_null() {
+        return Err(SynapticError::ProcessError(
+            "Tool arguments must be a JSON object".to_string(),
+        ));
+    }
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": "tools/call",
+        "params": { "name": tool_name, "arguments": arguments },
+    });
+    let exchange = exchange(&host, server_name, server, request, timeout).await?;
+    let result = into_result(tool_name, exchange.response)?;
+    Ok(ToolCallResult {
+        server_name: server_name.to_string(),
+        tool_name: tool_name.to_string(),
+        content: result
+            .get("content")
+            .and_then(|c| c.as_array())
+            .cloned()
+            .unwrap_or_default(),
+        structured_content: result.get("structuredContent").cloned(),
+        is_error: result
+            .get("isError")
+            .and_then(|e| e.as_bool())
+            .unwrap_or(false),
+        spawned: exchange.spawned,
+        duration_ms: exchange.elapsed.as_millis() as u64,
+    })
+}
+
+/// Fetch one of a server's prompts with its arguments filled in
+///
+/// Starts the server temporarily when it isn't running, like [`call_tool`].
+pub async fn get_prompt<H: Host>(
+    host: H,
+    server_name: &str,
+    server: McpServer,
+    prompt_name: &str,
+    arguments: HashMap<String, String>,
+    timeout: Duration,
+) -> SynapticResult<PromptResult> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": "prompts/get",
+        "params": { "name": prompt_name, "arguments": arguments },
+    });
+    let exchange = exchange(&host, server_name, server, request, timeout).await?;
+    let result = into_result(prompt_name, exchange.response)?;
+    Ok(PromptResult {
+        server_name: server_name.to_string(),
+        prompt_name: prompt_name.to_string(),
+        description: result
+            .get("description")
+            .and_then(|d| d.as_str())
+            .map(String::from),
+        messages: result
+            .get("messages")
+            .and_then(|m| m.as_array())
+            .cloned()
+            .unwrap_or_default(),
+        spawned: exchange.spawned,
+    })
+}
+
+/// A request sent like a client would, and its response
+struct Exchange {
+    response: Value,
+    /// Whether the server was started (and stopped again) for it
+    spawned: bool,
+    elapsed: Duration,
+}
+
+/// Send a request through the inspector, starting the server temporarily if needed
+async fn exchange<H: Host>(
+    host: &H,
+    server_name: &str,
+    server: McpServer,
+    request: Value,
+    timeout: Duration,
+) -> SynapticResult<Exchange> {
+    let Some(pm) = host.process_manager() else {
+        return Err(SynapticError::ProcessError(
+            "Process manager unavailable".to_string(),
+        ));
+    };
+
+    let spawned = !pm.is_running(server_name).await;
+    if spawned {
+        spawn_mcp_server(host.clone(), pm, server_name.to_string(), server).await?;
+    }
+    let result = async {
+        if spawned {
+            initialize(pm, server_name).await?;
+        }
+        let started = Instant::now();
+        let response = pm.send_request(server_name, request, timeout).await?;
+        Ok(Exchange {
+            response,
+            spawned,
+            elapsed: started.elapsed(),
+        })
+    }
+    .await;
+    if spawned {
+        stop_temporary(host, pm, server_name).await;
+    }
+    result
+}
+
+/// The `result` of a response; JSON-RPC errors become `ProcessError`
+pub(crate) fn into_result(what: &str, response: Value) -> SynapticResult<Value> {
+    if let Some(error) = response.get("error") {
+        return Err(SynapticError::ProcessError(format!(
+            "{} returned an error: {}",
+            what,
+            error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown error")
+        )));
+    }
+    Ok(response.get("result").cloned().unwrap_or_default())
+}
+
+/// Fill in the lists the server declares (all of them when its capabilities are unknown)
+async fn list_catalog(pm: &ProcessManager, catalog: &mut ServerCatalog) {
+    let declares = |capability: &str| {
+        !catalog.capabilities.is_object()
+            || catalog
+                .capabilities
+                .get(capability)
+                .is_some_and(|c| !c.is_null())
+    };
+    let (tools, resources, prompts) = (
+        declares("tools"),
+        declares("resources"),
+        declares("prompts"),
+    );
+
+    let lists = [
+        (tools, "tools/list", "tools"),
+        (resources, "resources/list", "resources"),
+        (resources, "resources/templates/list", "resourceTemplates"),
+        (prompts, "prompts/list", "prompts"),
+    ];
+    for (declared, method, key) in lists {
+        if !declared {
+            continue;
+        }
+        match list_all(pm, &catalog.server_name, method, key).await {
+            Ok(items) => match key {
+                "tools" => catalog.tools = items,
+                "resources" => catalog.resources = items,
+                "resourceTemplates" => catalog.resource_templates = items,
+                _ => catalog.prompts = items,
+            },
+            Err(e) => catalog.errors.push(format!("{}: {}", method, e)),
+        }
+    }
+}
+
+/// Every page of a list method
+pub(crate) async fn list_all(
+    pm: &ProcessManager,
+    server_name: &str,
+    method: &str,
+    key: &str,
+) -> SynapticResult<Vec<Value>> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    for _ in 0..MAX_LIST_PAGES {
+        let params = cursor.as_ref().map(|cursor| json!({ "cursor": cursor }));
+        let page = pm
+            .request(server_name, method, params, PROBE_REQUEST_TIMEOUT)
+            .await?;
+        if let Some(Value::Array(page_items)) = page.get(key) {
+            items.extend(page_items.iter().cloned());
+        }
+        cursor = page
+            .get("nextCursor")
+            .and_then(|c| c.as_str())
+            .map(String::from);
+        if cursor.is_none() {
+            break;
+        }
+    }
+    Ok(items)
+}
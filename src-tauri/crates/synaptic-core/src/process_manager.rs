@@ -0,0 +1,2728 @@
+//! Process Manager for MCP Server Lifecycle with MITM Inspection
+//!
+//! This module handles spawning MCP server processes, piping their stdin/stdout,
+//! and emitting intercepted traffic to the frontend for inspection.
+
+use crate::capabilities::{self, CapabilityTracker, ServerCapabilities};
+use crate::chaos::ChaosEngine;
+use crate::config::{McpConfig, McpServer};
+use crate::crashes::{self, CrashReport};
+use crate::docker::{self, Container};
+use crate::error::{SynapticError, SynapticResult};
+use crate::framing::{self, Framing, MessageDecoder, SharedFraming};
+use crate::health::HealthStatus;
+use crate::host::Host;
+use crate::inspector::InspectorMessage;
+use crate::intercept::{Interceptor, TrafficDirection};
+use crate::limits::{self, LimitGuard, LimitsUnavailableEvent};
+use crate::log_level::{self, LogLevel};
+use crate::metrics::ProcessMetrics;
+use crate::mock_server;
+use crate::mocks::MockEngine;
+use crate::orphans;
+use crate::oversized::{self, OversizedPolicy, OversizedTracker};
+use crate::policy::{PolicyCheck, PolicyEngine};
+use crate::preflight;
+use crate::process_tree::{self, ProcessTree};
+use crate::progress::ProgressTracker;
+use crate::rate_limit::RateLimiter;
+use crate::redaction::{RedactionMode, RedactionRule, Redactor};
+use crate::resolve;
+use crate::resources::ResourceSubscriptions;
+use crate::sampling::{self, SamplingInbox, SamplingReply};
+use crate::secrets;
+use crate::server_logs::{self, LogStream, ServerLog};
+use crate::settings::SynapticSettings;
+use crate::shim;
+use crate::stdin_queue::StdinQueue;
+use crate::system_logs::{LogRecord, LogSink};
+use crate::timeouts::RequestWatch;
+use crate::tool_stats::ToolCallTracker;
+use crate::traffic;
+use crate::transport::{self, RemoteConnection};
+use crate::validation::ProtocolValidator;
+use futures::StreamExt;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tokio_util::codec::FramedRead;
+
+// ============================================
+// DATA STRUCTURES
+// ============================================
+
+/// Represents an active MCP server process
+pub struct ActiveProcess {
+    /// Server name identifier
+    pub server_name: String,
+    /// Queue feeding the process stdin
+    pub stdin: Arc<StdinQueue>,
+    /// Channel to request process termination
+    pub kill_tx: Sender<StopRequest>,
+    /// OS process ID
+    pub pid: u32,
+    /// Config entry the process was launched from
+    pub server: McpServer,
+}
+
+/// Traffic event emitted to the frontend (in `mcp-traffic-batch` arrays)
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpTrafficEvent {
+    pub server_id: String,
+    pub timestamp: String,
+    pub direction: String,
+    pub content: String,
+    pub message_id: String,
+    /// Level a stderr line states (see `log_level`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<LogLevel>,
+}
+
+/// Payload of the `request-cancelled` event, sent for every `notifications/cancelled`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestCancelled {
+    pub server_name: String,
+    pub request_id: serde_json::Value,
+    pub reason: Option<String>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// JSON-RPC error code answering a `send_request` caller whose request was cancelled
+pub const REQUEST_CANCELLED_CODE: i64 = -32800;
+
+/// Request to stop a process, answered with the stage that ended it
+pub struct StopRequest {
+    /// How long each shutdown stage may take before escalating
+    pub grace: Duration,
+    pub reply: oneshot::Sender<ShutdownStage>,
+}
+
+/// Shutdown stage that made the process exit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownStage {
+    /// The process had already exited
+    AlreadyExited,
+    /// Exited on its own after stdin was closed
+    StdinClosed,
+    /// Exited after SIGTERM (CTRL_BREAK on Windows)
+    Terminated,
+    /// Had to be force-killed
+    Killed,
+}
+
+/// Lifecycle state of a managed server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessState {
+    /// Waiting for a free start slot (see `max_concurrent_starts`)
+    Queued,
+    /// Being spawned, or waiting out a restart backoff
+    Starting,
+    Running,
+    /// Stop requested, shutdown sequence in progress
+    Stopping,
+    /// Exited with a failure and will not be restarted
+    Crashed,
+}
+
+/// Status snapshot of a managed server
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessStatus {
+    pub server_name: String,
+    pub state: ProcessState,
+    /// PID of the current (or last restarted) process; none once crashed
+    pub pid: Option<u32>,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Seconds since `started_at` while the process is alive
+    pub uptime_secs: u64,
+    pub restart_count: u32,
+    pub last_exit_code: Option<i32>,
+    /// Container of a `docker run` server, once docker reported it
+    pub container_id: Option<String>,
+    /// Container state from the docker daemon (`running`, `exited`, ...)
+    pub container_state: Option<String>,
+    /// Messages waiting to be written to stdin
+    pub stdin_queue_depth: usize,
+    /// Last JSON-RPC message to or from the server (Synaptic's own pings don't count)
+    pub last_traffic_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Event emitted when a server moves through queued -> starting -> running
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessStateEvent {
+    pub server_name: String,
+    pub state: ProcessState,
+}
+
+/// Limits how many servers are starting at the same time
+///
+/// A start holds its slot until the server is ready, since that is when
+/// launchers like `npx` and `uvx` download packages.
+#[derive(Default)]
+pub struct StartQueue {
+    active: std::sync::Mutex<usize>,
+    released: tokio::sync::Notify,
+}
+
+/// A taken start slot, given back on drop
+pub struct StartSlot<'a> {
+    queue: &'a StartQueue,
+}
+
+impl StartQueue {
+    /// Take a slot if fewer than `limit` are taken (0 means unlimited)
+    pub fn try_acquire(&self, limit: usize) -> Option<StartSlot<'_>> {
+        let mut active = self.active.lock().unwrap();
+        if limit > 0 && *active >= limit {
+            return None;
+        }
+        *active += 1;
+        Some(StartSlot { queue: self })
+    }
+
+    /// Wait for a free slot
+    pub async fn acquire(&self, limit: usize) -> StartSlot<'_> {
+        loop {
+            // Registered before checking, so a release in between isn't missed
+            let released = self.released.notified();
+            if let Some(slot) = self.try_acquire(limit) {
+                return slot;
+            }
+            released.await;
+        }
+    }
+
+    /// Number of slots currently taken
+    pub fn active(&self) -> usize {
+        *self.active.lock().unwrap()
+    }
+}
+
+impl Drop for StartSlot<'_> {
+    fn drop(&mut self) {
+        *self.queue.active.lock().unwrap() -= 1;
+        self.queue.released.notify_waiters();
+    }
+}
+
+/// Clears a server's Queued status if its start gives up or is dropped before starting
+struct QueuedStatus<H: Host> {
+    host: H,
+    server_name: String,
+}
+
+impl<H: Host> Drop for QueuedStatus<H> {
+    fn drop(&mut self) {
+        fn clear(statuses: &mut HashMap<String, ProcessStatus>, server_name: &str) {
+            if statuses
+                .get(server_name)
+                .is_some_and(|s| s.state == ProcessState::Queued)
+            {
+                statuses.remove(server_name);
+            }
+        }
+
+        let Some(pm) = self.host.process_manager() else {
+            return;
+        };
+        if let Ok(mut statuses) = pm.statuses.try_lock() {
+            clear(&mut statuses, &self.server_name);
+            return;
+        }
+
+        // Contended; finish on the runtime instead of blocking in drop
+        let host = self.host.clone();
+        let server_name = std::mem::take(&mut self.server_name);
+        tokio::spawn(async move {
+            if let Some(pm) = host.process_manager() {
+                clear(&mut *pm.statuses.lock().await, &server_name);
+            }
+        });
+    }
+}
+
+/// Stderr lines kept per server for after-the-fact diagnostics
+pub const STDERR_BUFFER_LINES: usize = 500;
+
+/// Recent stderr lines of a server, across its runs
+#[derive(Debug, Default)]
+pub struct StderrBuffer {
+    lines: VecDeque<String>,
+    /// How many of the newest lines the current (or last) run printed
+    run_lines: usize,
+}
+
+impl StderrBuffer {
+    /// Last `lines` of the newest `within` lines
+    fn tail(&self, within: usize, lines: usize) -> Vec<String> {
+        self.lines
+            .iter()
+            .skip(self.lines.len() - within.min(lines))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Process manager state
+pub struct ProcessManager {
+    /// Currently active processes
+    pub processes: Mutex<HashMap<String, ActiveProcess>>,
+    /// Lifecycle status by server name (kept after a crash until respawned)
+    pub statuses: Mutex<HashMap<String, ProcessStatus>>,
+    /// Ping health by server name (filled by the health checker)
+    pub health: Mutex<HashMap<String, HealthStatus>>,
+    /// Waiters for responses to Synaptic's own requests, by JSON-RPC id
+    pub pending: Mutex<HashMap<String, oneshot::Sender<serde_json::Value>>>,
+    /// Waiters for responses to `send_request`, by server name and JSON-RPC id
+    pub awaiting: Mutex<HashMap<(String, String), oneshot::Sender<serde_json::Value>>>,
+    /// Latest resource samples by server name (filled by the metrics sampler)
+    pub metrics: Mutex<HashMap<String, ProcessMetrics>>,
+    /// Most recent stderr lines by server name (kept after exit for diagnostics)
+    pub stderr: Mutex<HashMap<String, StderrBuffer>>,
+    /// Secret values to redact from logs
+    pub secrets: Mutex<Vec<String>>,
+    /// Secrets and redaction rules applied to all captured traffic
+    redactor: std::sync::RwLock<Arc<Redactor>>,
+    /// Throttles concurrent server starts
+    pub start_queue: StartQueue,
+    /// Servers stopped for inactivity, with the entry to start them again from
+    pub idle_stopped: Mutex<HashMap<String, McpServer>>,
+    /// Ids of requests sent to each server that haven't been answered or cancelled
+    pub in_flight: Mutex<HashMap<String, HashSet<String>>>,
+    /// Wakes stops waiting for in-flight requests to settle
+    settled: tokio::sync::Notify,
+    /// Root of the per-server log files (none disables them)
+    pub log_dir: Option<PathBuf>,
+    /// Channel to the traffic logging service, once it runs
+    pub log_sink: std::sync::OnceLock<LogSink>,
+    /// Traffic breakpoints and the messages they hold
+    pub interceptor: Interceptor,
+    /// Rules answering selected requests instead of the server
+    pub mocks: MockEngine,
+    /// Latency and faults injected per server
+    pub chaos: ChaosEngine,
+    /// Tool calls waiting for the user's approval under a tool policy
+    pub policies: PolicyEngine,
+    /// Recent tool calls, counted against the rate limits
+    pub rate_limits: RateLimiter,
+    /// Protocol checks of all traffic and the violations found
+    pub validator: ProtocolValidator,
+    /// Outcome of each server's `initialize` handshake
+    pub capabilities: CapabilityTracker,
+    /// Open `tools/call` requests, for tool analytics
+    pub tool_calls: ToolCallTracker,
+    /// Resources subscribed to from the resource browser
+    pub resource_subscriptions: ResourceSubscriptions,
+    /// Progress notifications and the requests they belong to
+    pub progress: ProgressTracker,
+    /// Sampling requests from servers waiting for the user's answer
+    pub sampling: SamplingInbox,
+    /// Messages over the size limit, counted per server
+    pub oversized: OversizedTracker,
+
+    /// Client requests waiting for a response, flagged when they take too long
+    pub timeouts: RequestWatch,
+}
+
+impl ProcessManager {
+    pub fn new() -> Self {
+        Self::with_log_dir(server_logs::get_server_logs_dir().ok())
+    }
+
+    /// Process manager writing server output below `log_dir`
+    pub fn with_log_dir(log_dir: Option<PathBuf>) -> Self {
+        Self {
+            processes: Mutex::new(HashMap::new()),
+            statuses: Mutex::new(HashMap::new()),
+            metrics: Mutex::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+            awaiting: Mutex::new(HashMap::new()),
+            health: Mutex::new(HashMap::new()),
+            stderr: Mutex::new(HashMap::new()),
+            secrets: Mutex::new(Vec::new()),
+            redactor: std::sync::RwLock::new(Arc::new(Redactor::default())),
+            start_queue: StartQueue::default(),
+            idle_stopped: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            settled: tokio::sync::Notify::new(),
+            log_dir,
+            log_sink: std::sync::OnceLock::new(),
+            interceptor: Interceptor::default(),
+            mocks: MockEngine::default(),
+            chaos: ChaosEngine::default(),
+            policies: PolicyEngine::default(),
+            rate_limits: RateLimiter::default(),
+            validator: ProtocolValidator::default(),
+            capabilities: CapabilityTracker::default(),
+            tool_calls: ToolCallTracker::default(),
+            resource_subscriptions: ResourceSubscriptions::default(),
+            progress: ProgressTracker::default(),
+            sampling: SamplingInbox::default(),
+            oversized: OversizedTracker::default(),
+            timeouts: RequestWatch::default(),
+        }
+    }
+
+    /// Register secret values that should be redacted from logs
+    pub async fn register_secrets(&self, secrets: Vec<String>) {
+        let mut current = self.secrets.lock().await;
+        for secret in secrets {
+            if !secret.is_empty() && !current.contains(&secret) {
+                current.push(secret);
+            }
+        }
+        let mut redactor = self.redactor.write().unwrap();
+        *redactor = Arc::new(redactor.with_secrets(current.clone()));
+    }
+
+    /// Replace the regex redaction rules and mode, for traffic from now on
+    pub fn set_redaction_rules(&self, rules: Vec<RedactionRule>, mode: RedactionMode) {
+        let mut redactor = self.redactor.write().unwrap();
+        if redactor.rules() != rules.as_slice() || redactor.mode() != mode {
+            *redactor = Arc::new(redactor.with_rules(rules, mode));
+        }
+    }
+
+    /// Redact secrets and rule matches from a string
+    pub fn redact(&self, content: &str) -> String {
+        self.redactor.read().unwrap().redact(content).into_owned()
+    }
+
+    /// Current secrets and rules, e.g. to redact an export
+    pub fn redactor(&self) -> Arc<Redactor> {
+        self.redactor.read().unwrap().clone()
+    }
+
+    /// Check if a process is running
+    pub async fn is_running(&self, server_name: &str) -> bool {
+        let processes = self.processes.lock().await;
+        processes.contains_key(server_name)
+    }
+
+    /// Stop a specific process, escalating from closing stdin to SIGKILL
+    ///
+    /// Each stage gets `grace` to take effect before the next one is tried.
+    pub async fn kill_process(
+        &self,
+        server_name: &str,
+        grace: Duration,
+    ) -> SynapticResult<ShutdownStage> {
+        self.drain_and_kill(server_name, Duration::ZERO, grace)
+            .await
+    }
+
+    /// Stop a process once its in-flight requests settled, waiting at most `drain`
+    ///
+    /// New messages are refused while draining, but responses to requests
+    /// already sent still reach the inspector and their waiters.
+    pub async fn drain_and_kill(
+        &self,
+        server_name: &str,
+        drain: Duration,
+        grace: Duration,
+    ) -> SynapticResult<ShutdownStage> {
+        // Release the lock before waiting: the watchdog needs it to clean up
+        let process = self.processes.lock().await.remove(server_name);
+
+        match process {
+            Some(process) => {
+                self.set_state(server_name, process.pid, ProcessState::Stopping)
+                    .await;
+                self.drain_requests(server_name, drain).await;
+                Ok(request_stop(&process, grace).await)
+            }
+            None => Err(SynapticError::ProcessError(format!(
+                "Process not found: {}",
+                server_name
+            ))),
+        }
+    }
+
+    /// Stop all running processes
+    pub async fn kill_all(&self, grace: Duration) -> Vec<StopProgress> {
+        self.kill_all_with(Duration::ZERO, grace, |_| {}).await
+    }
+
+    /// Stop all running processes concurrently, reporting each one as it exits
+    ///
+    /// Each server first gets up to `drain` to answer its in-flight requests.
+    pub async fn kill_all_with(
+        &self,
+        drain: Duration,
+        grace: Duration,
+        on_progress: impl Fn(&StopProgress),
+    ) -> Vec<StopProgress> {
+        let processes: Vec<ActiveProcess> = {
+            let mut processes = self.processes.lock().await;
+            processes.drain().map(|(_, process)| process).collect()
+        };
+
+        for process in &processes {
+            self.set_state(&process.server_name, process.pid, ProcessState::Stopping)
+                .await;
+        }
+
+        let total = processes.len();
+        let completed = AtomicUsize::new(0);
+        futures::future::join_all(processes.iter().map(|process| async {
+            self.drain_requests(&process.server_name, drain).await;
+            let stage = request_stop(process, grace).await;
+            let progress = StopProgress {
+                server_name: process.server_name.clone(),
+                completed: completed.fetch_add(1, Ordering::SeqCst) + 1,
+                total,
+                stage,
+            };
+            on_progress(&progress);
+            progress
+        }))
+        .await
+    }
+
+    /// Send data to a process stdin
+    pub async fn send_to_stdin(&self, server_name: &str, data: String) -> SynapticResult<()> {
+        // Don't hold the registry lock while a full queue makes us wait
+        let stdin = self
+            .processes
+            .lock()
+            .await
+            .get(server_name)
+            .map(|process| process.stdin.clone());
+
+        match stdin {
+            Some(stdin) => stdin.push(data).await,
+            None => Err(SynapticError::ProcessError(format!(
+                "Process not found: {}",
+                server_name
+            ))),
+        }
+    }
+
+    /// Send a JSON-RPC request on Synaptic's behalf and wait for its response
+    ///
+    /// Returns the `result` member; JSON-RPC errors become `ProcessError`.
+    pub async fn request(
+        &self,
+        server_name: &str,
+        method: &str,
+        params: Option<serde_json::Value>,
+        timeout: Duration,
+    ) -> SynapticResult<serde_json::Value> {
+        let id = format!("{}{}", INTERNAL_ID_PREFIX, uuid::Uuid::new_v4());
+        let mut message = serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method });
+        if let Some(params) = params {
+            message["params"] = params;
+        }
+
+        let (waiter, response) = oneshot::channel();
+        self.pending.lock().await.insert(id.clone(), waiter);
+
+        if let Err(e) = self.send_to_stdin(server_name, message.to_string()).await {
+            self.pending.lock().await.remove(&id);
+            return Err(e);
+        }
+
+        let response = match tokio::time::timeout(timeout, response).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) | Err(_) => {
+                self.pending.lock().await.remove(&id);
+                return Err(SynapticError::ProcessError(format!(
+                    "No response to {} from {} within {} ms",
+                    method,
+                    server_name,
+                    timeout.as_millis()
+                )));
+            }
+        };
+
+        if let Some(error) = response.get("error") {
+            return Err(SynapticError::ProcessError(format!(
+                "{} returned an error: {}",
+                method,
+                error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("unknown error")
+            )));
+        }
+        Ok(response.get("result").cloned().unwrap_or_default())
+    }
+
+    /// Send a JSON-RPC request and wait for the response with the same id
+    ///
+    /// An id is injected when `payload` has none. Unlike [`Self::request`], the
+    /// exchange shows up in the inspector like any other traffic. Returns the
+    /// whole response, so JSON-RPC errors are left to the caller.
+    pub async fn send_request(
+        &self,
+        server_name: &str,
+        mut payload: serde_json::Value,
+        timeout: Duration,
+    ) -> SynapticResult<serde_json::Value> {
+        let Some(message) = payload.as_object_mut() else {
+            return Err(SynapticError::ProcessError(
+                "Request must be a JSON object".to_string(),
+            ));
+        };
+        let Some(method) = message.get("method").and_then(|m| m.as_str()) else {
+            return Err(SynapticError::ProcessError(
+                "Request has no method".to_string(),
+            ));
+        };
+        let method = method.to_string();
+
+        message
+            .entry("jsonrpc")
+            .or_insert_with(|| serde_json::json!("2.0"));
+        let id = message.entry("id").or_insert(serde_json::Value::Null);
+        if id.is_null() {
+            *id = serde_json::json!(uuid::Uuid::new_v4().to_string());
+        }
+        let key = (server_name.to_string(), id.to_string());
+
+        let (waiter, response) = oneshot::channel();
+        {
+            let mut awaiting = self.awaiting.lock().await;
+            if awaiting.contains_key(&key) {
+                return Err(SynapticError::ProcessError(format!(
+                    "A request with id {} is already waiting for {}",
+                    key.1, server_name
+                )));
+            }
+            awaiting.insert(key.clone(), waiter);
+        }
+
+        if let Err(e) = self.send_to_stdin(server_name, payload.to_string()).await {
+            self.awaiting.lock().await.remove(&key);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(timeout, response).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) | Err(_) => {
+                self.awaiting.lock().await.remove(&key);
+                Err(SynapticError::ProcessError(format!(
+                    "No response to {} from {} within {} ms",
+                    method,
+                    server_name,
+                    timeout.as_millis()
+                )))
+            }
+        }
+    }
+
+    /// Cancel a request that is still waiting for its response
+    ///
+    /// Sends `notifications/cancelled` to the server. A `send_request` caller
+    /// waiting for the response gets a JSON-RPC error with
+    /// [`REQUEST_CANCELLED_CODE`] instead.
+    pub async fn cancel_request(
+        &self,
+        server_name: &str,
+        request_id: &serde_json::Value,
+        reason: Option<String>,
+    ) -> SynapticResult<()> {
+        let id = request_id.to_string();
+        let in_flight = self
+            .in_flight
+            .lock()
+            .await
+            .get(server_name)
+            .is_some_and(|ids| ids.contains(&id));
+        if !in_flight {
+            return Err(SynapticError::ProcessError(format!(
+                "No request {} is waiting for a response from {}",
+                id, server_name
+            )));
+        }
+
+        let mut params = serde_json::json!({ "requestId": request_id });
+        if let Some(reason) = reason {
+            params["reason"] = serde_json::Value::String(reason);
+        }
+        let notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/cancelled",
+            "params": params,
+        });
+        self.send_to_stdin(server_name, notification.to_string())
+            .await?;
+
+        let key = (server_name.to_string(), id);
+        if let Some(waiter) = self.awaiting.lock().await.remove(&key) {
+            let _ = waiter.send(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request_id,
+                "error": { "code": REQUEST_CANCELLED_CODE, "message": "Request cancelled" },
+            }));
+        }
+        Ok(())
+    }
+
+    /// Get list of running process names
+    pub async fn list_running(&self) -> Vec<String> {
+        let processes = self.processes.lock().await;
+        processes.keys().cloned().collect()
+    }
+
+    /// Last `lines` stderr lines of a server, oldest first
+    pub async fn get_recent_stderr(&self, server_name: &str, lines: usize) -> Vec<String> {
+        let stderr = self.stderr.lock().await;
+        let Some(buffer) = stderr.get(server_name) else {
+            return Vec::new();
+        };
+        buffer.tail(buffer.lines.len(), lines)
+    }
+
+    /// Last `lines` stderr lines the server's current (or last) run printed, oldest first
+    pub async fn get_run_stderr(&self, server_name: &str, lines: usize) -> Vec<String> {
+        let stderr = self.stderr.lock().await;
+        let Some(buffer) = stderr.get(server_name) else {
+            return Vec::new();
+        };
+        buffer.tail(buffer.run_lines, lines)
+    }
+
+    /// Remember a stderr line, dropping the oldest beyond [`STDERR_BUFFER_LINES`]
+    async fn push_stderr(&self, server_name: &str, line: &str) {
+        let mut stderr = self.stderr.lock().await;
+        let buffer = stderr.entry(server_name.to_string()).or_default();
+        if buffer.lines.len() >= STDERR_BUFFER_LINES {
+            buffer.lines.pop_front();
+        }
+        buffer.lines.push_back(line.to_string());
+        buffer.run_lines = (buffer.run_lines + 1).min(buffer.lines.len());
+    }
+
+    /// Count stderr lines from here on as the output of a new run
+    async fn start_stderr_run(&self, server_name: &str) {
+        if let Some(buffer) = self.stderr.lock().await.get_mut(server_name) {
+            buffer.run_lines = 0;
+        }
+    }
+
+    /// Get the lifecycle status of a server, if it is managed or has crashed
+    pub async fn get_status(&self, server_name: &str) -> Option<ProcessStatus> {
+        let mut status = self
+            .statuses
+            .lock()
+            .await
+            .get(server_name)
+            .map(with_uptime)?;
+        status.stdin_queue_depth = self
+            .stdin_queue_depths()
+            .await
+            .remove(server_name)
+            .unwrap_or(0);
+        Some(with_container_state(status).await)
+    }
+
+    /// Get the lifecycle status of every managed or crashed server
+    pub async fn all_statuses(&self) -> Vec<ProcessStatus> {
+        let depths = self.stdin_queue_depths().await;
+        let snapshot: Vec<ProcessStatus> = self
+            .statuses
+            .lock()
+            .await
+            .values()
+            .map(|status| ProcessStatus {
+                stdin_queue_depth: depths.get(&status.server_name).copied().unwrap_or(0),
+                ..with_uptime(status)
+            })
+            .collect();
+        let mut all =
+            futures::future::join_all(snapshot.into_iter().map(with_container_state)).await;
+        all.sort_by(|a, b| a.server_name.cmp(&b.server_name));
+        all
+    }
+
+    /// Stdin queue depth of every running server
+    async fn stdin_queue_depths(&self) -> HashMap<String, usize> {
+        self.processes
+            .lock()
+            .await
+            .iter()
+            .map(|(name, process)| (name.clone(), process.stdin.depth()))
+            .collect()
+    }
+
+    /// Get the latest resource sample of a running server
+    pub async fn get_metrics(&self, server_name: &str) -> Option<ProcessMetrics> {
+        let metrics = self.metrics.lock().await;
+        metrics.get(server_name).cloned()
+    }
+
+    /// Get the latest ping health of a running server
+    pub async fn get_health(&self, server_name: &str) -> Option<HealthStatus> {
+        let health = self.health.lock().await;
+        health.get(server_name).cloned()
+    }
+
+    /// Wait up to `timeout` for a server's in-flight requests to settle
+    ///
+    /// Returns how many were still unanswered when the wait ended.
+    pub async fn drain_requests(&self, server_name: &str, timeout: Duration) -> usize {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Created before checking, so a response in between isn't missed
+            let settled = self.settled.notified();
+            let remaining = self.in_flight_count(server_name).await;
+            if remaining == 0 {
+                return 0;
+            }
+            if tokio::time::timeout_at(deadline, settled).await.is_err() {
+                let remaining = self.in_flight_count(server_name).await;
+                if !timeout.is_zero() {
+                    eprintln!(
+                        "Stopping {} with {} request(s) still in flight",
+                        server_name, remaining
+                    );
+                }
+                return remaining;
+            }
+        }
+    }
+
+    /// Number of requests sent to a server that are still unanswered
+    pub async fn in_flight_count(&self, server_name: &str) -> usize {
+        self.in_flight
+            .lock()
+            .await
+            .get(server_name)
+            .map_or(0, HashSet::len)
+    }
+
+    /// Track a message on its way to a server: requests start, cancellations settle
+    async fn track_outgoing(&self, server_name: &str, message: &serde_json::Value) {
+        let method = message.get("method").and_then(|m| m.as_str());
+        if method == Some("notifications/cancelled") {
+            if let Some(id) = message.pointer("/params/requestId") {
+                self.settle(server_name, &id.to_string()).await;
+            }
+        } else if let (Some(_), Some(id)) = (method, message.get("id").filter(|id| !id.is_null())) {
+            self.in_flight
+                .lock()
+                .await
+                .entry(server_name.to_string())
+                .or_default()
+                .insert(id.to_string());
+        }
+    }
+
+    /// Mark a request as answered or cancelled
+    async fn settle(&self, server_name: &str, id: &str) {
+        let mut in_flight = self.in_flight.lock().await;
+        if let Some(ids) = in_flight.get_mut(server_name) {
+            if ids.remove(id) {
+                self.settled.notify_waiters();
+            }
+        }
+    }
+
+    /// Drop every in-flight request of a server whose process exited
+    async fn forget_in_flight(&self, server_name: &str) {
+        if self.in_flight.lock().await.remove(server_name).is_some() {
+            self.settled.notify_waiters();
+        }
+    }
+
+    /// Note JSON-RPC traffic to or from a server
+    async fn touch(&self, server_name: &str) {
+        if let Some(status) = self.statuses.lock().await.get_mut(server_name) {
+            status.last_traffic_at = Some(chrono::Utc::now());
+        }
+    }
+
+    /// Change the state of a server if its status still belongs to `pid`
+    async fn set_state(&self, server_name: &str, pid: u32, state: ProcessState) {
+        let mut statuses = self.statuses.lock().await;
+        if let Some(status) = statuses.get_mut(server_name) {
+            if status.pid == Some(pid) {
+                status.state = state;
+            }
+        }
+    }
+}
+
+impl Default for ProcessManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Prefix of JSON-RPC ids used for Synaptic's own requests
+pub const INTERNAL_ID_PREFIX: &str = "synaptic:";
+
+/// Id of a message if it is a JSON-RPC response (serialized, so `1` and `"1"` differ)
+fn response_id(message: &serde_json::Value) -> Option<String> {
+    if message.get("method").is_some() {
+        return None;
+    }
+    message
+        .get("id")
+        .filter(|id| !id.is_null())
+        .map(|id| id.to_string())
+}
+
+/// The cancellation a `notifications/cancelled` message announces
+fn cancelled_request(server_name: &str, message: &serde_json::Value) -> Option<RequestCancelled> {
+    if message.get("method").and_then(|m| m.as_str()) != Some("notifications/cancelled") {
+        return None;
+    }
+    Some(RequestCancelled {
+        server_name: server_name.to_string(),
+        request_id: message.pointer("/params/requestId")?.clone(),
+        reason: message
+            .pointer("/params/reason")
+            .and_then(|r| r.as_str())
+            .map(String::from),
+        timestamp: chrono::Utc::now(),
+    })
+}
+
+/// Id of a message if it belongs to one of Synaptic's own requests
+fn internal_request_id(message: &serde_json::Value) -> Option<String> {
+    message
+        .get("id")
+        .and_then(|id| id.as_str())
+        .filter(|id| id.starts_with(INTERNAL_ID_PREFIX))
+        .map(String::from)
+}
+
+impl ProcessStatus {
+    /// Status of a server waiting for a start slot
+    fn queued(server_name: &str) -> Self {
+        Self {
+            server_name: server_name.to_string(),
+            state: ProcessState::Queued,
+            pid: None,
+            started_at: None,
+            uptime_secs: 0,
+            restart_count: 0,
+            last_exit_code: None,
+            container_id: None,
+            container_state: None,
+            stdin_queue_depth: 0,
+            last_traffic_at: None,
+        }
+    }
+}
+
+/// Fill in the uptime of a status snapshot
+fn with_uptime(status: &ProcessStatus) -> ProcessStatus {
+    let mut status = status.clone();
+    status.uptime_secs = match (status.state, status.started_at) {
+        (ProcessState::Running | ProcessState::Stopping, Some(started)) => {
+            (chrono::Utc::now() - started).num_seconds().max(0) as u64
+        }
+        _ => 0,
+    };
+    status
+}
+
+/// Ask the docker daemon for the state of a status's container
+async fn with_container_state(mut status: ProcessStatus) -> ProcessStatus {
+    if let Some(id) = &status.container_id {
+        status.container_state = docker::inspect_state(id).await;
+    }
+    status
+}
+
+/// Ask a process's watchdog to stop it and wait for the outcome
+async fn request_stop(process: &ActiveProcess, grace: Duration) -> ShutdownStage {
+    let (reply, outcome) = oneshot::channel();
+
+    if process
+        .kill_tx
+        .send(StopRequest { grace, reply })
+        .await
+        .is_err()
+    {
+        // Watchdog already finished
+        return ShutdownStage::AlreadyExited;
+    }
+
+    outcome.await.unwrap_or(ShutdownStage::AlreadyExited)
+}
+
+// ============================================
+// WHITELISTED EXECUTABLES
+// ============================================
+
+/// Executables allowed out of the box (the user-editable list lives in settings)
+pub const DEFAULT_ALLOWED_EXECUTABLES: &[&str] = &[
+    "npx", "node", "npm", "uvx", "uv", "python", "python3", "pip", "pip3", "docker", "deno", "bun",
+];
+
+/// Event emitted when a server needs a non-whitelisted executable
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutableApprovalRequest {
+    pub server_name: String,
+    pub command: String,
+    /// Normalized name the decision is recorded under
+    pub executable: String,
+}
+
+/// Normalized executable name: lowercase base name without Windows extensions
+pub fn executable_name(command: &str) -> String {
+    let cmd_lower = command.to_lowercase();
+    cmd_lower
+        .split(['/', '\\'])
+        .next_back()
+        .unwrap_or(&cmd_lower)
+        .trim_end_matches(".exe")
+        .trim_end_matches(".cmd")
+        .trim_end_matches(".bat")
+        .to_string()
+}
+
+/// Check if a command is in the default whitelist
+pub fn is_command_allowed(command: &str) -> bool {
+    DEFAULT_ALLOWED_EXECUTABLES.contains(&executable_name(command).as_str())
+}
+
+/// Check if a command is in the given whitelist
+///
+/// Bare names match any command with that name; absolute paths (the binaries
+/// Synaptic installed) match only that very file.
+pub fn is_command_allowed_by(command: &str, allowed: &[String]) -> bool {
+    let name = executable_name(command);
+    allowed.iter().any(|entry| {
+        if Path::new(entry).is_absolute() {
+            is_same_file(command, entry)
+        } else {
+            executable_name(entry) == name
+        }
+    })
+}
+
+/// Whether two paths name the same file, resolving symlinks when both exist
+fn is_same_file(a: &str, b: &str) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Settings of the host's app state, or the defaults without one
+fn current_settings<H: Host>(host: &H) -> SynapticSettings {
+    host.app_state()
+        .and_then(|state| state.get_settings().ok())
+        .unwrap_or_default()
+}
+
+/// Enforce the whitelist, asking the user about unknown executables
+fn check_executable<H: Host>(
+    host: &H,
+    settings: &SynapticSettings,
+    server_name: &str,
+    command: &str,
+) -> SynapticResult<()> {
+    if is_command_allowed_by(command, &settings.managed_executables_of(server_name))
+        || is_command_allowed_by(command, &settings.allowed_executables)
+        || mock_server::is_bundled(command)
+    {
+        return Ok(());
+    }
+
+    let executable = executable_name(command);
+    if is_command_allowed_by(command, &settings.denied_executables) {
+        return Err(SynapticError::ProcessError(format!(
+            "Command not allowed: {} (denied by user)",
+            executable
+        )));
+    }
+
+    host.emit(
+        "executable-approval-requested",
+        ExecutableApprovalRequest {
+            server_name: server_name.to_string(),
+            command: command.to_string(),
+            executable: executable.clone(),
+        },
+    );
+    Err(SynapticError::ApprovalRequired(format!(
+        "{} is not whitelisted; approve it to run {}",
+        executable, server_name
+    )))
+}
+
+// ============================================
+// PROCESS SPAWNING
+// ============================================
+
+/// Grace period per shutdown stage when none is given
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(3);
+
+/// How long reader tasks get to drain a pipe after the process exited
+///
+/// Grandchildren that inherited the pipes can keep them open indefinitely.
+const READER_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Queued stdin messages written with a single flush at most
+const STDIN_BATCH_MAX: usize = 64;
+
+/// A process that stays up this long resets the restart attempt counter
+pub const RESTART_RESET_AFTER: Duration = Duration::from_secs(60);
+
+/// Event emitted before an exited server is restarted
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessRestartingEvent {
+    pub server_name: String,
+    pub attempt: u32,
+    pub max_retries: u32,
+    pub delay_ms: u64,
+    pub exit_code: Option<i32>,
+}
+
+/// A running child together with its I/O pump tasks
+struct RunningChild {
+    endpoint: Endpoint,
+    /// PID of the process, or the id a remote connection is tracked under
+    pid: u32,
+    stdin: Arc<StdinQueue>,
+    /// Owns the child's stdin; aborting it closes the pipe
+    stdin_task: JoinHandle<()>,
+    reader_tasks: Vec<JoinHandle<()>>,
+    /// Fires on the first line the server writes to stdout
+    ready: Option<oneshot::Receiver<()>>,
+    /// Container behind a `docker run` server
+    container: Option<Container>,
+    /// CPU/memory caps, watched by `limit_task`
+    limits: Option<Arc<LimitGuard>>,
+    limit_task: Option<JoinHandle<()>>,
+}
+
+/// What a running server is: a local process or a connection to a remote one
+enum Endpoint {
+    Process {
+        child: Child,
+        /// The child plus everything it started
+        tree: ProcessTree,
+    },
+    Remote(RemoteConnection),
+}
+
+/// How a process exited or a connection ended
+struct ExitOutcome {
+    code: Option<i32>,
+    signal: Option<i32>,
+    success: bool,
+}
+
+impl Endpoint {
+    /// Wait for the process to exit or the connection to end
+    async fn wait(&mut self) -> ExitOutcome {
+        match self {
+            Endpoint::Process { child, .. } => {
+                let status = child.wait().await;
+                ExitOutcome {
+                    code: status.as_ref().ok().and_then(|s| s.code()),
+                    signal: status.as_ref().ok().and_then(crashes::exit_signal),
+                    success: status.as_ref().is_ok_and(|s| s.success()),
+                }
+            }
+            Endpoint::Remote(connection) => ExitOutcome {
+                code: None,
+                signal: None,
+                success: connection.wait().await.is_ok(),
+            },
+        }
+    }
+
+    fn has_exited(&mut self) -> bool {
+        match self {
+            Endpoint::Process { child, .. } => matches!(child.try_wait(), Ok(Some(_))),
+            Endpoint::Remote(connection) => connection.is_finished(),
+        }
+    }
+
+    /// Ask to stop: SIGTERM/CTRL_BREAK the tree, or end the remote session
+    fn terminate(&mut self) -> bool {
+        match self {
+            Endpoint::Process { tree, .. } => tree.terminate(),
+            Endpoint::Remote(connection) => {
+                connection.close();
+                true
+            }
+        }
+    }
+
+    async fn kill(&mut self) {
+        match self {
+            Endpoint::Process { child, .. } => {
+                let _ = child.kill().await;
+            }
+            Endpoint::Remote(connection) => connection.abort(),
+        }
+    }
+
+    /// Kill whatever the process left behind
+    fn kill_tree(&self) {
+        if let Endpoint::Process { tree, .. } = self {
+            tree.kill();
+        }
+    }
+
+    fn is_remote(&self) -> bool {
+        matches!(self, Endpoint::Remote(_))
+    }
+}
+
+impl RunningChild {
+    fn abort_tasks(&self) {
+        self.stdin.close();
+        self.stdin_task.abort();
+        for task in &self.reader_tasks {
+            task.abort();
+        }
+        if let Some(task) = &self.limit_task {
+            task.abort();
+        }
+    }
+
+    /// Report limit violations not seen by the watcher yet (e.g. the OOM kill that ended it)
+    fn report_limit_violations<H: Host>(&self, host: &H) {
+        if let Some(guard) = &self.limits {
+            for violation in guard.new_violations() {
+                host.emit("resource-limit-violated", &violation);
+            }
+        }
+    }
+
+    /// Wait up to `timeout` for the readers to reach end of output, then abort
+    async fn drain_tasks(&mut self, timeout: Duration) {
+        self.stdin.close();
+        self.stdin_task.abort();
+        let readers = futures::future::join_all(self.reader_tasks.iter_mut());
+        let _ = tokio::time::timeout(timeout, readers).await;
+        self.abort_tasks();
+    }
+
+    /// Wait up to `grace` for the process to exit
+    async fn exited_within(&mut self, grace: Duration) -> bool {
+        tokio::time::timeout(grace, self.endpoint.wait()).await.is_ok()
+    }
+
+    /// Stop the process tree: close stdin, then terminate, then kill
+    ///
+    /// MCP's stdio transport has no shutdown request; closing stdin is the
+    /// spec's way of asking a server to exit. Descendants that outlive the
+    /// direct child are killed whichever stage succeeded.
+    async fn shutdown(&mut self, grace: Duration) -> ShutdownStage {
+        let stage = self.shutdown_child(grace).await;
+        self.endpoint.kill_tree();
+        self.remove_container().await;
+        stage
+    }
+
+    /// Remove the container of a docker server (it may outlive the CLI)
+    async fn remove_container(&self) {
+        if let Some(container) = &self.container {
+            container.remove().await;
+        }
+    }
+
+    async fn shutdown_child(&mut self, grace: Duration) -> ShutdownStage {
+        if self.endpoint.has_exited() {
+            return ShutdownStage::AlreadyExited;
+        }
+
+        self.stdin.close();
+        self.stdin_task.abort();
+        if self.exited_within(grace).await {
+            return ShutdownStage::StdinClosed;
+        }
+
+        // Signalling the docker CLI doesn't reliably stop the container
+        let terminated = match &self.container {
+            Some(container) => container.stop(grace).await,
+            None => self.endpoint.terminate(),
+        };
+        if terminated && self.exited_within(grace).await {
+            return ShutdownStage::Terminated;
+        }
+
+        self.endpoint.kill().await;
+        ShutdownStage::Killed
+    }
+}
+
+/// Spawn an MCP server process with MITM interception
+///
+/// The server's restart policy is applied by the supervisor task when the
+/// process exits without being killed.
+pub async fn spawn_mcp_server<H: Host>(
+    host: H,
+    process_manager: &ProcessManager,
+    server_name: String,
+    server: McpServer,
+) -> SynapticResult<u32> {
+    let settings = current_settings(&host);
+
+    // Validate command is whitelisted (or ask the user to approve it); remote servers run nothing here
+    let launched = shim::without_shim(&server);
+    if !server.is_remote() {
+        check_executable(&host, &settings, &server_name, &launched.command)?;
+        if let Some(wrapper) = &server.wrapper {
+            check_executable(&host, &settings, &server_name, &wrapper.command)?;
+        }
+        if server.ssh.is_some() {
+            check_executable(&host, &settings, &server_name, "ssh")?;
+        }
+    }
+
+    // Missing runtimes, empty required env vars and missing dirs fail here, with a report
+    let report = preflight::preflight_server(host.app_state(), &server_name, &launched);
+    if !report.ok {
+        return Err(SynapticError::PreflightFailed(Box::new(report)));
+    }
+    // Keychain references become their secrets only in the spawned process
+    let resolved = secrets::resolve_references(&server)?;
+
+    // Check if already running
+    if process_manager.is_running(&server_name).await {
+        return Err(SynapticError::ProcessError(format!(
+            "Server already running: {}",
+            server_name
+        )));
+    }
+    process_manager
+        .idle_stopped
+        .lock()
+        .await
+        .remove(&server_name);
+
+    // Wait for a start slot when too many servers are starting already
+    let limit = settings.max_concurrent_starts;
+    let mut queued = None;
+    let _slot = match process_manager.start_queue.try_acquire(limit) {
+        Some(slot) => slot,
+        None => {
+            {
+                let mut statuses = process_manager.statuses.lock().await;
+                if statuses
+                    .get(&server_name)
+                    .is_some_and(|s| s.state == ProcessState::Queued)
+                {
+                    return Err(SynapticError::ProcessError(format!(
+                        "Server already queued: {}",
+                        server_name
+                    )));
+                }
+                statuses.insert(server_name.clone(), ProcessStatus::queued(&server_name));
+            }
+            queued = Some(QueuedStatus {
+                host: host.clone(),
+                server_name: server_name.clone(),
+            });
+            emit_state(&host, &server_name, ProcessState::Queued);
+            let slot = process_manager.start_queue.acquire(limit).await;
+
+            // Another start may have got in while this one waited
+            if process_manager.is_running(&server_name).await {
+                return Err(SynapticError::ProcessError(format!(
+                    "Server already running: {}",
+                    server_name
+                )));
+            }
+            slot
+        }
+    };
+    emit_state(&host, &server_name, ProcessState::Starting);
+
+    // Register environment variable values as secrets
+    let secrets: Vec<String> = resolved.env.values().cloned().collect();
+    process_manager.register_secrets(secrets).await;
+    process_manager.set_redaction_rules(settings.redaction_rules.clone(), settings.redaction_mode);
+
+    process_manager.start_stderr_run(&server_name).await;
+    let mut running = start_child(&host, &server_name, &resolved)?;
+    let pid = running.pid;
+    let startup_timeout = settings.startup_timeout();
+    let ready = running.ready.take();
+    let (kill_tx, kill_rx): (Sender<StopRequest>, Receiver<StopRequest>) = mpsc::channel(1);
+
+    // Store the process
+    {
+        let mut processes = process_manager.processes.lock().await;
+        processes.insert(
+            server_name.clone(),
+            ActiveProcess {
+                server_name: server_name.clone(),
+                stdin: running.stdin.clone(),
+                kill_tx,
+                pid,
+                server: server.clone(),
+            },
+        );
+    }
+    process_manager.statuses.lock().await.insert(
+        server_name.clone(),
+        ProcessStatus {
+            server_name: server_name.clone(),
+            state: if startup_timeout.is_some() {
+                ProcessState::Starting
+            } else {
+                ProcessState::Running
+            },
+            pid: Some(pid),
+            started_at: Some(chrono::Utc::now()),
+            uptime_secs: 0,
+            restart_count: 0,
+            last_exit_code: None,
+            container_id: None,
+            container_state: None,
+            stdin_queue_depth: 0,
+            // Idle time counts from the start
+            last_traffic_at: Some(chrono::Utc::now()),
+        },
+    );
+    // The status has moved on from Queued
+    drop(queued);
+    watch_container(&host, &server_name, &running);
+
+    // Restarts after a crash run the resolved entry too
+    tokio::spawn(supervise(
+        host.clone(),
+        server_name.clone(),
+        resolved,
+        running,
+        kill_rx,
+    ));
+
+    if let (Some(limit), Some(ready)) = (startup_timeout, ready) {
+        let grace = settings.shutdown_grace();
+        wait_until_ready(process_manager, &server_name, pid, ready, limit, grace).await?;
+    }
+    emit_state(&host, &server_name, ProcessState::Running);
+
+    Ok(pid)
+}
+
+/// Wait for the first output of a freshly spawned server
+///
+/// Stdio servers stay silent until spoken to, so a `ping` is sent to draw a
+/// reply out of them; an error response counts as well. A server that never
+/// answers or exits first is stopped and reported as failed.
+async fn wait_until_ready(
+    process_manager: &ProcessManager,
+    server_name: &str,
+    pid: u32,
+    ready: oneshot::Receiver<()>,
+    limit: Duration,
+    grace: Duration,
+) -> SynapticResult<()> {
+    let probe = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": format!("{}startup", INTERNAL_ID_PREFIX),
+        "method": "ping",
+    });
+    let _ = process_manager
+        .send_to_stdin(server_name, probe.to_string())
+        .await;
+
+    let error = match tokio::time::timeout(limit, ready).await {
+        Ok(Ok(())) => {
+            process_manager
+                .set_state(server_name, pid, ProcessState::Running)
+                .await;
+            return Ok(());
+        }
+        Ok(Err(_)) => SynapticError::ProcessError(format!(
+            "{} exited before producing any output",
+            server_name
+        )),
+        Err(_) => SynapticError::StartupTimeout(format!(
+            "{} produced no output within {} ms",
+            server_name,
+            limit.as_millis()
+        )),
+    };
+
+    // Also cancels any restart the policy scheduled
+    let _ = process_manager.kill_process(server_name, grace).await;
+    Err(error)
+}
+
+/// Progress of `spawn_all_enabled`, emitted as `spawn-all-progress` per server
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnProgress {
+    pub server_name: String,
+    /// Servers finished so far, including this one
+    pub completed: usize,
+    pub total: usize,
+    pub pid: Option<u32>,
+    pub error: Option<String>,
+}
+
+/// Progress of stopping every server, emitted as `kill-all-progress` per server
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopProgress {
+    pub server_name: String,
+    /// Servers finished so far, including this one
+    pub completed: usize,
+    pub total: usize,
+    pub stage: ShutdownStage,
+}
+
+/// Start every enabled server that isn't already running
+///
+/// Servers are started concurrently; one failing doesn't stop the others.
+pub async fn spawn_all_enabled<H: Host>(
+    host: H,
+    process_manager: &ProcessManager,
+    config: &McpConfig,
+) -> Vec<SpawnProgress> {
+    let running = process_manager.list_running().await;
+    let mut servers: Vec<(&String, &McpServer)> = config
+        .mcp_servers
+        .iter()
+        .filter(|(name, server)| server.enabled && !running.contains(name))
+        .collect();
+    servers.sort_by_key(|(name, _)| name.as_str());
+
+    let total = servers.len();
+    let completed = AtomicUsize::new(0);
+    futures::future::join_all(servers.into_iter().map(|(name, server)| {
+        let host = host.clone();
+        let completed = &completed;
+        async move {
+            let result =
+                spawn_mcp_server(host.clone(), process_manager, name.clone(), server.clone()).await;
+            let progress = SpawnProgress {
+                server_name: name.clone(),
+                completed: completed.fetch_add(1, Ordering::SeqCst) + 1,
+                total,
+                pid: result.as_ref().ok().copied(),
+                error: result.err().map(|e| e.to_string()),
+            };
+            host.emit("spawn-all-progress", &progress);
+            progress
+        }
+    }))
+    .await
+}
+
+/// Outcome of restarting one server
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartResult {
+    pub server_name: String,
+    /// PID of the new process when the restart succeeded
+    pub pid: Option<u32>,
+    pub error: Option<String>,
+}
+
+/// Stop a server gracefully (if running) and spawn it again from `server`
+///
+/// Pass the current config entry so config and env edits take effect.
+pub async fn restart_mcp_server<H: Host>(
+    host: H,
+    process_manager: &ProcessManager,
+    server_name: String,
+    server: McpServer,
+    grace: Duration,
+) -> SynapticResult<u32> {
+    if process_manager.is_running(&server_name).await {
+        process_manager.kill_process(&server_name, grace).await?;
+    }
+    spawn_mcp_server(host, process_manager, server_name, server).await
+}
+
+/// Event emitted when a running server no longer matches its config entry
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerConfigStaleEvent {
+    pub server_name: String,
+}
+
+/// Bring a running server in line with its edited config entry
+///
+/// When the launch settings changed, the server is restarted if
+/// `auto_restart` is set and a `server-config-stale` event is emitted
+/// otherwise. Returns the new PID after a restart.
+pub async fn apply_config_change<H: Host>(
+    host: H,
+    process_manager: &ProcessManager,
+    server_name: String,
+    server: McpServer,
+    auto_restart: bool,
+    grace: Duration,
+) -> SynapticResult<Option<u32>> {
+    let changed = process_manager
+        .processes
+        .lock()
+        .await
+        .get(&server_name)
+        .is_some_and(|process| process.server.launch_differs(&server));
+    if !changed {
+        return Ok(None);
+    }
+
+    if auto_restart {
+        let pid = restart_mcp_server(host, process_manager, server_name, server, grace).await?;
+        return Ok(Some(pid));
+    }
+
+    host.emit(
+        "server-config-stale",
+        ServerConfigStaleEvent { server_name },
+    );
+    Ok(None)
+}
+
+/// Restart every running server one at a time from the given config
+///
+/// Servers no longer in the config are left running and reported as failed.
+pub async fn restart_all_running<H: Host>(
+    host: H,
+    process_manager: &ProcessManager,
+    config: &McpConfig,
+    grace: Duration,
+) -> Vec<RestartResult> {
+    let mut names = process_manager.list_running().await;
+    names.sort();
+
+    let mut results = Vec::with_capacity(names.len());
+    for name in names {
+        let result = match config.mcp_servers.get(&name) {
+            Some(server) => {
+                restart_mcp_server(
+                    host.clone(),
+                    process_manager,
+                    name.clone(),
+                    server.clone(),
+                    grace,
+                )
+                .await
+            }
+            None => Err(SynapticError::ServerNotFound(name.clone())),
+        };
+
+        results.push(RestartResult {
+            server_name: name,
+            pid: result.as_ref().ok().copied(),
+            error: result.err().map(|e| e.to_string()),
+        });
+    }
+    results
+}
+
+/// Command that runs a server on this machine, plus its container for `docker run`
+fn local_command(server: &McpServer) -> SynapticResult<(Command, Option<Container>)> {
+    // Resolve against the server's cwd and own PATH dirs, PATH and well-known runtime install locations
+    let path_prepend = server.path_prepend_dirs();
+    let program = resolve::resolve_server_command(server)?;
+
+    // Track the container of `docker run` servers
+    let (container, args) = if docker::is_docker_command(&server.command) {
+        match Container::prepare(program.clone(), &server.args) {
+            Some((container, args)) => (Some(container), args),
+            None => (None, server.args.clone()),
+        }
+    } else {
+        (None, server.args.clone())
+    };
+
+    let mut cmd = Command::new(&program);
+    cmd.args(&args);
+
+    // Set environment variables
+    for (key, value) in &server.env {
+        cmd.env(key, value);
+    }
+
+    // Launcher scripts (`#!/usr/bin/env node`) must find the runtime next to them
+    let inherited_path = server
+        .env
+        .get("PATH")
+        .map(OsString::from)
+        .or_else(|| std::env::var_os("PATH"));
+    let path = resolve::path_with_dir(&program, inherited_path.clone()).or(inherited_path);
+    if let Some(path) = resolve::path_with_dirs(&path_prepend, path.clone()).or(path) {
+        cmd.env("PATH", path);
+    }
+
+    // Set working directory if provided
+    if let Some(ref dir) = server.cwd {
+        cmd.current_dir(dir);
+    }
+
+    Ok((cmd, container))
+}
+
+/// Start the server (spawn its process, or connect to it) and the tasks pumping its I/O
+fn start_child<H: Host>(
+    host: &H,
+    server_name: &str,
+    server: &McpServer,
+) -> SynapticResult<RunningChild> {
+    if server.is_remote() {
+        start_remote(host, server_name, server)
+    } else {
+        start_process(host, server_name, server)
+    }
+}
+
+/// Connect to a server reached over HTTP; its messages are pumped like a process's stdio
+fn start_remote<H: Host>(
+    host: &H,
+    server_name: &str,
+    server: &McpServer,
+) -> SynapticResult<RunningChild> {
+    let (connection, pipes) = transport::connect(server_name, server)?;
+    // The connection hands over one message per line
+    let pumps = spawn_io(
+        host,
+        server_name,
+        Framing::Newline,
+        pipes.stdin,
+        pipes.stdout,
+        pipes.stderr,
+    );
+
+    Ok(RunningChild {
+        endpoint: Endpoint::Remote(connection),
+        pid: transport::next_connection_id(),
+        stdin: pumps.stdin,
+        stdin_task: pumps.stdin_task,
+        reader_tasks: pumps.reader_tasks,
+        ready: Some(pumps.ready),
+        container: None,
+        limits: None,
+        limit_task: None,
+    })
+}
+
+/// Spawn the child process and the tasks pumping its stdio
+fn start_process<H: Host>(
+    host: &H,
+    server_name: &str,
+    server: &McpServer,
+) -> SynapticResult<RunningChild> {
+    // Synaptic captures its own children; the client capture shim isn't needed
+    let server = &shim::without_shim(server).wrapped();
+
+    // Build the command
+    let (mut cmd, container) = match &server.ssh {
+        // cwd, env and PATH are applied on the remote side
+        Some(target) => {
+            let mut cmd = Command::new(resolve::resolve_command("ssh")?);
+            cmd.args(target.ssh_args(server)?);
+            (cmd, None)
+        }
+        None => local_command(server)?,
+    };
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    // Own process group / job so the whole tree can be stopped
+    process_tree::configure(&mut cmd, server.priority);
+
+    // Remote servers are capped on their own host, if at all
+    let mut limit_guard = None;
+    if !server.limits.is_empty() && server.ssh.is_none() {
+        match LimitGuard::prepare(server_name, &server.limits, &mut cmd) {
+            Ok(guard) => limit_guard = Some(guard),
+            Err(error) => limits_unavailable(host, server_name, error),
+        }
+    }
+
+    // Spawn the process
+    let mut child: Child = cmd
+        .spawn()
+        .map_err(|e| SynapticError::ProcessError(format!("Failed to spawn: {}", e)))?;
+
+    let pid = child
+        .id()
+        .ok_or_else(|| SynapticError::ProcessError("Failed to get PID".to_string()))?;
+    let tree = ProcessTree::attach(&child, pid);
+    track_spawn(host, server_name, pid, &server.command);
+
+    let limit_guard = limit_guard.and_then(|guard| match guard.attach(&child) {
+        Ok(()) => Some(Arc::new(guard)),
+        Err(error) => {
+            limits_unavailable(host, server_name, error);
+            None
+        }
+    });
+    let limit_task = limit_guard.clone().map(|guard| {
+        let host = host.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(limits::LIMIT_CHECK_INTERVAL).await;
+                for violation in guard.new_violations() {
+                    host.emit("resource-limit-violated", &violation);
+                }
+            }
+        })
+    });
+
+    // Take ownership of stdio handles
+    let stdin = child.stdin.take().expect("Failed to capture stdin");
+    let stdout = child.stdout.take().expect("Failed to capture stdout");
+    let stderr = child.stderr.take().expect("Failed to capture stderr");
+    let pumps = spawn_io(host, server_name, server.framing, stdin, stdout, stderr);
+
+    Ok(RunningChild {
+        endpoint: Endpoint::Process { child, tree },
+        pid,
+        stdin: pumps.stdin,
+        stdin_task: pumps.stdin_task,
+        reader_tasks: pumps.reader_tasks,
+        ready: Some(pumps.ready),
+        container,
+        limits: limit_guard,
+        limit_task,
+    })
+}
+
+/// The tasks moving a server's messages, plus the queue feeding them
+struct IoPumps {
+    stdin: Arc<StdinQueue>,
+    stdin_task: JoinHandle<()>,
+    reader_tasks: Vec<JoinHandle<()>>,
+    ready: oneshot::Receiver<()>,
+}
+
+/// Spawn the stdin writer and the stdout/stderr readers of a server
+fn spawn_io<H, W, R, E>(
+    host: &H,
+    server_name: &str,
+    framing: Framing,
+    stdin: W,
+    stdout: R,
+    stderr: E,
+) -> IoPumps
+where
+    H: Host,
+    W: AsyncWrite + Unpin + Send + 'static,
+    R: AsyncRead + Unpin + Send + 'static,
+    E: AsyncRead + Unpin + Send + 'static,
+{
+    let settings = current_settings(host);
+
+    // Create stdin queue
+    let stdin_queue = Arc::new(StdinQueue::new(
+        settings.stdin_queue_capacity,
+        settings.stdin_overflow,
+    ));
+    let stdin_writer_queue = stdin_queue.clone();
+    let stdin_reply_queue = stdin_queue.clone();
+
+    // Clone host handle for all tasks
+    let host_stdin = host.clone();
+    let host_stdout = host.clone();
+    let host_stderr = host.clone();
+
+    // Clone server name for each task
+    let server_name_stdin = server_name.to_string();
+    let server_name_stdout = server_name.to_string();
+    let server_name_stderr = server_name.to_string();
+
+    // Output is also appended to the server's rolling log files
+    let log = host
+        .process_manager()
+        .and_then(|pm| pm.log_dir.as_ref())
+        .map(|dir| {
+            Arc::new(std::sync::Mutex::new(ServerLog::new(
+                dir,
+                server_name,
+                server_logs::MAX_LOG_FILE_BYTES,
+            )))
+        });
+    let log_stdout = log.clone();
+    let log_stderr = log;
+
+    // Detected from stdout (unless pinned) and followed by the stdin writer
+    let framing = SharedFraming::new(framing);
+    let framing_stdin = framing.clone();
+    let max_message_bytes = settings.max_message_bytes;
+    let oversized_policy = settings.oversized_policy;
+
+    let (ready_tx, ready_rx) = oneshot::channel();
+
+    // Mocked responses, fed from the stdin writer to the stdout reader
+    let (injected_tx, injected_rx) = mpsc::unbounded_channel::<String>();
+
+    // Tool calls the user approved after the writer moved on
+    let (approved_tx, mut approved_rx) = mpsc::unbounded_channel::<String>();
+
+    // One ordered batch stream of traffic events for all three tasks
+    let (traffic_stdin, _) = traffic::spawn_batcher(host.clone(), server_name);
+    let traffic_stdout = traffic_stdin.clone();
+    let traffic_stderr = traffic_stdin.clone();
+
+    // Spawn stdin writer task
+    let stdin_handle = tokio::spawn(async move {
+        let mut stdin = stdin;
+        let queue = stdin_writer_queue;
+
+        // Whatever queued up meanwhile goes out in one write and flush
+        loop {
+            let (batch, approved) = tokio::select! {
+                batch = queue.pop_batch(STDIN_BATCH_MAX) => match batch {
+                    Some(batch) => (batch, None),
+                    None => break,
+                },
+                Some(data) = approved_rx.recv() => (Vec::new(), Some(data)),
+            };
+            let mut bytes = Vec::new();
+            // An approved call went through everything below when it came in
+            if let Some(data) = approved {
+                bytes.extend(framing_stdin.encode(&data));
+            }
+            for data in batch {
+                let mut data = data;
+                let mut parsed: Option<serde_json::Value> = serde_json::from_str(&data).ok();
+                let internal = parsed.as_ref().and_then(internal_request_id).is_some();
+
+                // Held at a breakpoint until forwarded, edited or dropped
+                if let Some(pm) = host_stdin.process_manager() {
+                    if !internal && pm.interceptor.is_active() {
+                        let direction = TrafficDirection::Outgoing;
+                        match pm
+                            .interceptor
+                            .hold(&host_stdin, &server_name_stdin, direction, data)
+                            .await
+                        {
+                            Some(released) => data = released,
+                            None => continue,
+                        }
+                        parsed = serde_json::from_str(&data).ok();
+                    }
+                }
+
+                // Redact secrets
+                let mut redacted = match host_stdin.process_manager() {
+                    Some(pm) => pm.redact(&data),
+                    None => data.clone(),
+                };
+                // What gets stored about the message (the server still receives `data`)
+                let mut stored = if redacted == data {
+                    parsed.clone()
+                } else {
+                    serde_json::from_str(&redacted).ok()
+                };
+
+                // Oversized messages are only recorded cut; rejected ones never reach the server
+                let mut too_large = None;
+                if !internal && max_message_bytes > 0 && data.len() > max_message_bytes {
+                    let note = oversized::observe(
+                        &host_stdin,
+                        &server_name_stdin,
+                        TrafficDirection::Outgoing,
+                        &data,
+                        max_message_bytes,
+                        oversized_policy,
+                    );
+                    redacted = framing::truncate(&redacted, max_message_bytes).into_owned();
+                    stored = None;
+                    if oversized_policy == OversizedPolicy::Reject {
+                        match parsed.as_ref().and_then(|m| m.get("id")) {
+                            Some(id) => {
+                                too_large = Some(oversized::rejection(
+                                    id,
+                                    note.size_bytes,
+                                    note.limit_bytes,
+                                ))
+                            }
+                            None => continue,
+                        }
+                    }
+                }
+
+                // Tool calls refused by a tool policy or over a rate limit never reach the
+                // server; calls waiting for the user's approval are held aside
+                let mut awaiting = None;
+                let refused = match (
+                    stored.as_ref().or(parsed.as_ref()),
+                    host_stdin.process_manager(),
+                ) {
+                    (Some(message), Some(pm)) if !internal && too_large.is_none() => {
+                        match pm.policies.check(&host_stdin, &server_name_stdin, message) {
+                            PolicyCheck::Pass => {
+                                pm.rate_limits
+                                    .check(&host_stdin, &server_name_stdin, message)
+                            }
+                            PolicyCheck::Refuse(denied) => Some(denied),
+                            PolicyCheck::AwaitApproval(wait) => {
+                                awaiting = Some((wait, message.clone()));
+                                None
+                            }
+                        }
+                    }
+                    _ => None,
+                };
+                let refused = too_large.or(refused);
+
+                // Nor do requests failed by chaos settings or answered by a mock rule
+                let mocked = match (&parsed, host_stdin.process_manager()) {
+                    (Some(message), Some(pm)) if !internal && refused.is_none() => pm
+                        .chaos
+                        .get(&server_name_stdin)
+                        .and_then(|chaos| chaos.injected_error(message))
+                        .map(|error| (error, Duration::ZERO))
+                        .or_else(|| {
+                            pm.mocks
+                                .is_active()
+                                .then(|| pm.mocks.respond(&server_name_stdin, message))
+                                .flatten()
+                        }),
+                    _ => None,
+                };
+                let mocked = refused.map(|error| (error, Duration::ZERO)).or(mocked);
+
+                // Emit outgoing traffic event (Synaptic's own requests stay out of the inspector)
+                if !internal {
+                    let message_id = uuid::Uuid::new_v4().to_string();
+                    if let Some(pm) = host_stdin.process_manager() {
+                        pm.touch(&server_name_stdin).await;
+                        if let Some(message) = &parsed {
+                            pm.track_outgoing(&server_name_stdin, message).await;
+                            if let Some(stored) = &stored {
+                                pm.capabilities.observe_request(&server_name_stdin, stored);
+                            }
+                            pm.tool_calls.observe_request(&server_name_stdin, message);
+                            pm.timeouts.observe_request(&server_name_stdin, message);
+                            if let Some(state) = host_stdin.app_state() {
+                                state
+                                    .live_stats
+                                    .observe_request(&server_name_stdin, message);
+                            }
+                            pm.progress.observe_request(&server_name_stdin, message);
+                            if let Some(cancelled) = cancelled_request(&server_name_stdin, message)
+                            {
+                                // The logged request row is marked when the batch is written
+                                if let (Some(state), Some(stored)) =
+                                    (host_stdin.app_state(), &stored)
+                                {
+                                    state.mark_inspector_cancelled(
+                                        &server_name_stdin,
+                                        &cancelled.request_id,
+                                    );
+                                    let mut msg = InspectorMessage::new_request(
+                                        &server_name_stdin,
+                                        stored.clone(),
+                                    );
+                                    msg.id = message_id.clone();
+                                    state.add_inspector_message(&server_name_stdin, msg);
+                                }
+                                host_stdin.emit("request-cancelled", &cancelled);
+                            }
+                        }
+                        pm.validator.check(
+                            &server_name_stdin,
+                            TrafficDirection::Outgoing,
+                            &redacted,
+                        );
+                    }
+                    traffic_stdin.send(McpTrafficEvent {
+                        server_id: server_name_stdin.clone(),
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                        direction: "OUTGOING".to_string(),
+                        content: redacted,
+                        message_id,
+                        level: None,
+                    });
+                }
+
+                // Decided without holding back the messages after it; once approved it
+                // still has to pass the rate limit
+                if let Some((wait, message)) = awaiting {
+                    let host = host_stdin.clone();
+                    let server_name = server_name_stdin.clone();
+                    let injected = injected_tx.clone();
+                    let approved = approved_tx.clone();
+                    tokio::spawn(async move {
+                        let refused = match wait.decision(&host).await {
+                            Some(denied) => Some(denied),
+                            None => host
+                                .process_manager()
+                                .and_then(|pm| pm.rate_limits.check(&host, &server_name, &message)),
+                        };
+                        match refused.map(|error| (error, Duration::ZERO)).or(mocked) {
+                            Some((response, delay)) => {
+                                tokio::time::sleep(delay).await;
+                                let _ = injected.send(response.to_string());
+                            }
+                            None => {
+                                let _ = approved.send(data);
+                            }
+                        }
+                    });
+                    continue;
+                }
+
+                // The canned response comes back through the stdout path
+                if let Some((response, delay)) = mocked {
+                    let injected = injected_tx.clone();
+                    tokio::spawn(async move {
+                        tokio::time::sleep(delay).await;
+                        let _ = injected.send(response.to_string());
+                    });
+                    continue;
+                }
+
+                // Encode in the server's framing
+                bytes.extend(framing_stdin.encode(&data));
+            }
+
+            if let Err(e) = stdin.write_all(&bytes).await {
+                eprintln!("Error writing to stdin: {}", e);
+                break;
+            }
+            if let Err(e) = stdin.flush().await {
+                eprintln!("Error flushing stdin: {}", e);
+                break;
+            }
+        }
+        queue.close();
+    });
+
+    // Spawn stdout reader task
+    let stdout_handle = tokio::spawn(async move {
+        // Passed-through messages are read whole and only cut for the record
+        let read_limit = match oversized_policy {
+            OversizedPolicy::PassThrough => 0,
+            _ => max_message_bytes,
+        };
+        let mut reader = FramedRead::new(stdout, MessageDecoder::new(framing, read_limit));
+        let mut ready_tx = Some(ready_tx);
+        let mut injected_rx = injected_rx;
+        // Messages a chaos delay held back, each with whether it was oversized
+        let (delayed_tx, mut delayed_rx) = mpsc::unbounded_channel::<(String, bool)>();
+
+        loop {
+            // Server output, a mocked response standing in for it, or a delayed message
+            let (line_result, delayed) = tokio::select! {
+                line = reader.next() => match line {
+                    Some(line) => (line, None),
+                    None => break,
+                },
+                Some(line) = injected_rx.recv() => (Ok(line), None),
+                Some((line, oversized)) = delayed_rx.recv() => (Ok(line), Some(oversized)),
+            };
+            match line_result {
+                Ok(line) => {
+                    if let Some(ready_tx) = ready_tx.take() {
+                        let _ = ready_tx.send(());
+                    }
+
+                    let mut line = line;
+                    let oversized =
+                        delayed.unwrap_or(max_message_bytes > 0 && line.len() > max_message_bytes);
+                    if oversized && delayed.is_none() {
+                        let note = oversized::observe(
+                            &host_stdout,
+                            &server_name_stdout,
+                            TrafficDirection::Incoming,
+                            &line,
+                            max_message_bytes,
+                            oversized_policy,
+                        );
+                        // A rejected response still answers its request, with an error; a
+                        // rejected request of the server gets that error back in its place
+                        if oversized_policy == OversizedPolicy::Reject {
+                            let from_server = oversized::head_field(&line, "method").is_some();
+                            let rejection = oversized::head_field(&line, "id").map(|id| {
+                                oversized::rejection(&id, note.size_bytes, note.limit_bytes)
+                            });
+                            match rejection {
+                                Some(rejection) if from_server => {
+                                    let queue = stdin_reply_queue.clone();
+                                    tokio::spawn(async move {
+                                        let _ = queue.push(rejection.to_string()).await;
+                                    });
+                                    continue;
+                                }
+                                Some(rejection) => line = rejection.to_string(),
+                                None => continue,
+                            }
+                        }
+                    }
+                    let mut parsed: Option<serde_json::Value> = serde_json::from_str(&line).ok();
+
+                    // Responses to Synaptic's own requests go to their waiter only
+                    if let Some(id) = parsed.as_ref().and_then(internal_request_id) {
+                        if let Some(pm) = host_stdout.process_manager() {
+                            if let Some(waiter) = pm.pending.lock().await.remove(&id) {
+                                let _ = waiter.send(parsed.unwrap_or_default());
+                            }
+                        }
+                        continue;
+                    }
+
+                    // Chaos settings make server messages late or lose them; a late
+                    // one comes back round on its own while the reader keeps reading
+                    if let Some(chaos) = host_stdout
+                        .process_manager()
+                        .and_then(|pm| pm.chaos.get(&server_name_stdout))
+                        .filter(|_| delayed.is_none())
+                    {
+                        if chaos.should_drop() {
+                            continue;
+                        }
+                        let delay = chaos.delay();
+                        if !delay.is_zero() {
+                            let delayed = delayed_tx.clone();
+                            tokio::spawn(async move {
+                                tokio::time::sleep(delay).await;
+                                let _ = delayed.send((line, oversized));
+                            });
+                            continue;
+                        }
+                    }
+
+                    // Held at a breakpoint until forwarded, edited or dropped
+                    if let Some(pm) = host_stdout.process_manager() {
+                        if pm.interceptor.is_active() {
+                            let direction = TrafficDirection::Incoming;
+                            match pm
+                                .interceptor
+                                .hold(&host_stdout, &server_name_stdout, direction, line)
+                                .await
+                            {
+                                Some(released) => line = released,
+                                None => continue,
+                            }
+                            parsed = serde_json::from_str(&line).ok();
+                        }
+                    }
+
+                    // Redact secrets
+                    let mut redacted = match host_stdout.process_manager() {
+                        Some(pm) => pm.redact(&line),
+                        None => line.clone(),
+                    };
+                    if oversized && oversized_policy == OversizedPolicy::PassThrough {
+                        redacted = framing::truncate(&redacted, max_message_bytes).into_owned();
+                    }
+                    // What gets stored about the message (bookkeeping still uses `parsed`)
+                    let stored: Option<serde_json::Value> = if redacted == line {
+                        parsed.clone()
+                    } else {
+                        serde_json::from_str(&redacted).ok()
+                    };
+
+                    append_log(&log_stdout, LogStream::Stdout, &redacted);
+                    let mut validation_errors = Vec::new();
+                    if let Some(pm) = host_stdout.process_manager() {
+                        pm.touch(&server_name_stdout).await;
+                        validation_errors = pm.validator.check(
+                            &server_name_stdout,
+                            TrafficDirection::Incoming,
+                            &redacted,
+                        );
+                        if let Some(negotiated) = stored.as_ref().and_then(|message| {
+                            pm.capabilities
+                                .observe_response(&server_name_stdout, message)
+                        }) {
+                            record_capabilities(&host_stdout, &negotiated);
+                        }
+                        if let Some(message) = &parsed {
+                            pm.timeouts.observe_response(&server_name_stdout, message);
+                        }
+                        if let Some(call) = parsed.as_ref().and_then(|message| {
+                            pm.tool_calls.observe_response(&server_name_stdout, message)
+                        }) {
+                            if let Some(sink) = pm.log_sink.get() {
+                                let _ = sink.send(LogRecord::ToolCall(call));
+                            }
+                        }
+                        if let Some(update) = parsed.as_ref().and_then(|message| {
+                            pm.resource_subscriptions
+                                .observe(&server_name_stdout, message)
+                        }) {
+                            host_stdout.emit("resource-updated", &update);
+                        }
+                        if let Some(update) = parsed.as_ref().and_then(|message| {
+                            pm.progress.observe_response(&server_name_stdout, message)
+                        }) {
+                            host_stdout.emit("request-progress", &update);
+                        }
+                        if let Some(mut request) = parsed
+                            .as_ref()
+                            .and_then(|message| pm.sampling.observe(&server_name_stdout, message))
+                        {
+                            let auto_deny = host_stdout
+                                .app_state()
+                                .and_then(|state| state.get_settings().ok())
+                                .is_some_and(|settings| settings.sampling_auto_deny);
+                            if auto_deny {
+                                let reply = SamplingReply::Deny { reason: None };
+                                request.auto_denied = sampling::respond_to_sampling(
+                                    pm,
+                                    &server_name_stdout,
+                                    &request.request_id,
+                                    &reply,
+                                )
+                                .await
+                                .is_ok();
+                            }
+                            host_stdout.emit("sampling-request", &request);
+                        }
+                    }
+
+                    if let (Some(message), Some(state)) = (&parsed, host_stdout.app_state()) {
+                        state
+                            .live_stats
+                            .observe_response(&server_name_stdout, message);
+                    }
+
+                    // Answer a `send_request` caller; the response is still traffic
+                    if let (Some(id), Some(pm)) = (
+                        parsed.as_ref().and_then(response_id),
+                        host_stdout.process_manager(),
+                    ) {
+                        pm.settle(&server_name_stdout, &id).await;
+                        let key = (server_name_stdout.clone(), id);
+                        if let Some(waiter) = pm.awaiting.lock().await.remove(&key) {
+                            let response = serde_json::from_str(&redacted)
+                                .unwrap_or_else(|_| parsed.clone().unwrap_or_default());
+                            let _ = waiter.send(response);
+                        }
+                    }
+
+                    let message_id = uuid::Uuid::new_v4().to_string();
+                    traffic_stdout.send(McpTrafficEvent {
+                        server_id: server_name_stdout.clone(),
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                        direction: "INCOMING".to_string(),
+                        content: redacted,
+                        message_id: message_id.clone(),
+                        level: None,
+                    });
+
+                    // Also store in inspector state if available (same id as the logged row)
+                    if let Some(state) = host_stdout.app_state() {
+                        if let Some(payload) = stored {
+                            let mut msg =
+                                InspectorMessage::new_response(&server_name_stdout, payload);
+                            msg.id = message_id;
+                            msg.validation_errors = validation_errors;
+                            state.add_inspector_message(&server_name_stdout, msg);
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error reading stdout from {}: {}", server_name_stdout, e);
+                    break;
+                }
+            }
+        }
+    });
+
+    // Spawn stderr reader task (for debugging)
+    let stderr_handle = tokio::spawn(async move {
+        let mut reader = FramedRead::new(
+            stderr,
+            MessageDecoder::new(SharedFraming::new(Framing::Newline), max_message_bytes),
+        );
+
+        while let Some(line_result) = reader.next().await {
+            match line_result {
+                Ok(line) => {
+                    let line = match host_stderr.process_manager() {
+                        Some(pm) => pm.redact(&line),
+                        None => line,
+                    };
+                    append_log(&log_stderr, LogStream::Stderr, &line);
+                    if let Some(pm) = host_stderr.process_manager() {
+                        pm.push_stderr(&server_name_stderr, &line).await;
+                    }
+
+                    traffic_stderr.send(McpTrafficEvent {
+                        server_id: server_name_stderr.clone(),
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                        direction: "STDERR".to_string(),
+                        level: log_level::parse_stderr_level(&line),
+                        content: line,
+                        message_id: uuid::Uuid::new_v4().to_string(),
+                    });
+                }
+                Err(e) => {
+                    eprintln!("Error reading stderr: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+
+    IoPumps {
+        stdin: stdin_queue,
+        stdin_task: stdin_handle,
+        reader_tasks: vec![stdout_handle, stderr_handle],
+        ready: ready_rx,
+    }
+}
+
+/// Emit `resource-limits-unavailable` for a server that runs uncapped
+fn limits_unavailable<H: Host>(host: &H, server_name: &str, error: String) {
+    eprintln!(
+        "Running {} without its resource limits: {}",
+        server_name, error
+    );
+    host.emit(
+        "resource-limits-unavailable",
+        LimitsUnavailableEvent {
+            server_name: server_name.to_string(),
+            error,
+        },
+    );
+}
+
+/// Emit a `process-state-changed` event
+fn emit_state<H: Host>(host: &H, server_name: &str, state: ProcessState) {
+    host.emit(
+        "process-state-changed",
+        ProcessStateEvent {
+            server_name: server_name.to_string(),
+            state,
+        },
+    );
+}
+
+/// Record the container ID of a docker server in its status once known, then
+/// stream the container's logs until it stops
+fn watch_container<H: Host>(host: &H, server_name: &str, running: &RunningChild) {
+    let Some(container) = running.container.clone() else {
+        return;
+    };
+    let host = host.clone();
+    let server_name = server_name.to_string();
+    let pid = running.pid;
+
+    tokio::spawn(async move {
+        let Some(id) = container.wait_for_id(docker::CIDFILE_WAIT).await else {
+            return;
+        };
+        let Some(pm) = host.process_manager() else {
+            return;
+        };
+        if let Some(status) = pm.statuses.lock().await.get_mut(&server_name) {
+            if status.pid == Some(pid) {
+                status.container_id = Some(id.clone());
+            }
+        }
+        if let Err(e) = docker::follow_logs(&host, &server_name, &id).await {
+            eprintln!("Failed to follow logs of {}: {}", server_name, e);
+        }
+    });
+}
+
+/// Persist a server's negotiated capabilities (when a database is available)
+fn record_capabilities<H: Host>(host: &H, negotiated: &ServerCapabilities) {
+    if let Some(db) = host.app_state().and_then(|state| state.database.as_ref()) {
+        if let Err(e) = capabilities::record(db, negotiated) {
+            eprintln!(
+                "Failed to record capabilities of {}: {}",
+                negotiated.server_name, e
+            );
+        }
+    }
+}
+
+/// Persist a crash report (when a database is available) and emit `process-crashed`
+fn report_crash<H: Host>(host: &H, mut report: CrashReport) {
+    if let Some(db) = host.app_state().and_then(|state| state.database.as_ref()) {
+        match crashes::record(db, &report) {
+            Ok(id) => report.id = Some(id),
+            Err(e) => eprintln!("Failed to record crash of {}: {}", report.server_name, e),
+        }
+    }
+    host.emit("process-crashed", &report);
+}
+
+/// Record a spawned process so it can be found again if Synaptic crashes
+fn track_spawn<H: Host>(host: &H, server_name: &str, pid: u32, command: &str) {
+    if let Some(db) = host.app_state().and_then(|state| state.database.as_ref()) {
+        if let Err(e) = orphans::record_spawn(db, server_name, pid, command) {
+            eprintln!("Failed to record spawn of {}: {}", server_name, e);
+        }
+    }
+}
+
+/// Forget a process recorded by [`track_spawn`]
+fn track_exit<H: Host>(host: &H, pid: u32) {
+    if let Some(db) = host.app_state().and_then(|state| state.database.as_ref()) {
+        if let Err(e) = orphans::record_exit(db, pid) {
+            eprintln!("Failed to record exit of process {}: {}", pid, e);
+        }
+    }
+}
+
+/// Append a line to the server's log file, if file logging is enabled
+fn append_log(log: &Option<Arc<std::sync::Mutex<ServerLog>>>, stream: LogStream, line: &str) {
+    let Some(log) = log else { return };
+    let Ok(mut log) = log.lock() else { return };
+    if let Err(e) = log.write_line(stream, line) {
+        eprintln!("Failed to write server log: {}", e);
+    }
+}
+
+/// Process watchdog: waits for exit or kill and applies the restart policy
+async fn supervise<H: Host>(
+    host: H,
+    server_name: String,
+    server: McpServer,
+    mut running: RunningChild,
+    mut kill_rx: Receiver<StopRequest>,
+) {
+    let policy = &server.restart;
+    let mut attempt: u32 = 0;
+    let mut restart_count: u32 = 0;
+    let mut crashed = false;
+    let mut last_exit_code = None;
+
+    loop {
+        let started = Instant::now();
+
+        let status = tokio::select! {
+            // Wait for stop request
+            request = kill_rx.recv() => {
+                match request {
+                    Some(request) => {
+                        let stage = running.shutdown(request.grace).await;
+                        let _ = request.reply.send(stage);
+                    }
+                    None => {
+                        running.shutdown(DEFAULT_SHUTDOWN_GRACE).await;
+                    }
+                }
+                None
+            }
+            // Wait for process to exit naturally
+            status = running.endpoint.wait() => {
+                // Don't leave its children (or its container) behind
+                running.endpoint.kill_tree();
+                running.remove_container().await;
+                Some(status)
+            }
+        };
+
+        // Cleanup, letting the readers pick up output written right before exit
+        // (messages held at breakpoints are dropped; nobody would receive them)
+        if let Some(pm) = host.process_manager() {
+            pm.interceptor.release_server(&server_name);
+            pm.policies.release_server(&server_name);
+        }
+        running.drain_tasks(READER_DRAIN_TIMEOUT).await;
+        running.report_limit_violations(&host);
+        if !running.endpoint.is_remote() {
+            track_exit(&host, running.pid);
+        }
+        // Nothing will answer them any more
+        if let Some(pm) = host.process_manager() {
+            pm.forget_in_flight(&server_name).await;
+            pm.validator.forget_requests(&server_name);
+            pm.capabilities.forget_pending(&server_name);
+            pm.tool_calls.forget_server(&server_name);
+            pm.resource_subscriptions.forget_server(&server_name);
+            pm.progress.forget_server(&server_name);
+            pm.sampling.forget_server(&server_name);
+            pm.timeouts.forget_server(&server_name);
+        }
+        if let Some(state) = host.app_state() {
+            state.live_stats.forget_pending(&server_name);
+            if state.history_retention(&server_name).clear_on_disconnect {
+                state.clear_inspector_messages(&server_name);
+            }
+        }
+
+        // Killed on request: never restart
+        let Some(status) = status else { break };
+
+        let exit_code = status.code;
+        let success = status.success;
+        last_exit_code = exit_code;
+
+        if started.elapsed() >= RESTART_RESET_AFTER {
+            attempt = 0;
+        }
+        let will_restart = policy.should_restart(success, attempt);
+
+        if !success {
+            let report = CrashReport {
+                id: None,
+                server_name: server_name.clone(),
+                pid: running.pid,
+                exit_code,
+                signal: status.signal,
+                uptime_secs: started.elapsed().as_secs(),
+                stderr_tail: match host.process_manager() {
+                    Some(pm) => {
+                        pm.get_run_stderr(&server_name, crashes::CRASH_STDERR_LINES)
+                            .await
+                    }
+                    None => Vec::new(),
+                },
+                will_restart,
+                occurred_at: chrono::Utc::now(),
+            };
+            report_crash(&host, report);
+        }
+
+        if !will_restart {
+            crashed = !success;
+            break;
+        }
+
+        let delay = policy.backoff_delay(attempt);
+        attempt += 1;
+        restart_count += 1;
+
+        if let Some(pm) = host.process_manager() {
+            if let Some(status) = pm.statuses.lock().await.get_mut(&server_name) {
+                if status.pid == Some(running.pid) {
+                    status.state = ProcessState::Starting;
+                    status.restart_count = restart_count;
+                    status.last_exit_code = exit_code;
+                }
+            }
+        }
+
+        host.emit(
+            "process-restarting",
+            ProcessRestartingEvent {
+                server_name: server_name.clone(),
+                attempt,
+                max_retries: policy.max_retries,
+                delay_ms: delay.as_millis() as u64,
+                exit_code,
+            },
+        );
+
+        // A stop request during the backoff cancels the restart
+        tokio::select! {
+            request = kill_rx.recv() => {
+                if let Some(request) = request {
+                    let _ = request.reply.send(ShutdownStage::AlreadyExited);
+                }
+                break;
+            }
+            _ = tokio::time::sleep(delay) => {}
+        }
+
+        let previous_pid = running.pid;
+        if let Some(pm) = host.process_manager() {
+            pm.start_stderr_run(&server_name).await;
+        }
+        match start_child(&host, &server_name, &server) {
+            Ok(next) => running = next,
+            Err(e) => {
+                eprintln!("Failed to restart {}: {}", server_name, e);
+                crashed = true;
+                break;
+            }
+        }
+
+        // Point the registry entry at the new process
+        if let Some(pm) = host.process_manager() {
+            if let Some(status) = pm.statuses.lock().await.get_mut(&server_name) {
+                if status.pid == Some(previous_pid) {
+                    status.state = ProcessState::Running;
+                    status.pid = Some(running.pid);
+                    status.started_at = Some(chrono::Utc::now());
+                    status.last_traffic_at = status.started_at;
+                    status.container_id = None;
+                }
+            }
+            watch_container(&host, &server_name, &running);
+
+            let mut processes = pm.processes.lock().await;
+            match processes.get_mut(&server_name) {
+                Some(process) => {
+                    process.pid = running.pid;
+                    process.stdin = running.stdin.clone();
+                }
+                None => {
+                    // Stopped while restarting
+                    running.shutdown(DEFAULT_SHUTDOWN_GRACE).await;
+                    running.abort_tasks();
+                    if !running.endpoint.is_remote() {
+                        track_exit(&host, running.pid);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    // Remove from process manager unless a new instance already took the name
+    if let Some(pm) = host.process_manager() {
+        let mut processes = pm.processes.lock().await;
+        if processes.get(&server_name).map(|p| p.pid) == Some(running.pid) {
+            processes.remove(&server_name);
+        }
+        drop(processes);
+
+        // Crashed servers keep their status so the failure stays visible
+        let mut statuses = pm.statuses.lock().await;
+        if statuses.get(&server_name).and_then(|s| s.pid) == Some(running.pid) {
+            if crashed {
+                if let Some(status) = statuses.get_mut(&server_name) {
+                    status.state = ProcessState::Crashed;
+                    status.pid = None;
+                    status.restart_count = restart_count;
+                    status.last_exit_code = last_exit_code;
+                }
+            } else {
+                statuses.remove(&server_name);
+            }
+        }
+    }
+
+    // Emit process stopped event
+    host.emit("process-stopped", &server_name);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_whitelist() {
+        assert!(is_command_allowed("npx"));
+        assert!(is_command_allowed("node"));
+        assert!(is_command_allowed("python"));
+        assert!(is_command_allowed("uvx"));
+        assert!(is_command_allowed("docker"));
+
+        // With path
+        assert!(is_command_allowed("/usr/bin/node"));
+        assert!(is_command_allowed("C:\\Program Files\\node"));
+
+        // With extension
+        assert!(is_command_allowed("node.exe"));
+        assert!(is_command_allowed("npx.cmd"));
+
+        // Not allowed
+        assert!(!is_command_allowed("bash"));
+        assert!(!is_command_allowed("sh"));
+        assert!(!is_command_allowed("cmd"));
+        assert!(!is_command_allowed("powershell"));
+        assert!(!is_command_allowed("rm"));
+    }
+}
@@ -0,0 +1,177 @@
+//! Whole-tree process control for spawned MCP servers
+//!
+//! Launchers like `npx` and `uvx` start the real server as a grandchild that
+//! survives a plain `child.kill()`. Each server therefore gets its own process
+//! group on Unix and a kill-on-close job object on Windows, and signals are
+//! sent to the whole tree.
+
+use crate::config::ProcessPriority;
+use tokio::process::{Child, Command};
+
+/// Put the command in its own process group so the tree can be signalled,
+/// running at the given priority (inherited by everything it starts)
+pub fn configure(cmd: &mut Command, priority: ProcessPriority) {
+    #[cfg(unix)]
+    {
+        cmd.process_group(0);
+
+        let nice = match priority {
+            ProcessPriority::Low => 10,
+            ProcessPriority::Normal => 0,
+            ProcessPriority::High => -5,
+        };
+        if nice != 0 {
+            // SAFETY: setpriority(2) is async-signal-safe; failing to raise
+            // the priority without privileges is ignored, not fatal
+            unsafe {
+                cmd.pre_exec(move || {
+                    libc::setpriority(libc::PRIO_PROCESS, 0, nice);
+                    Ok(())
+                });
+            }
+        }
+    }
+
+    // Own process group so CTRL_BREAK reaches only this server
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::System::Threading::{
+            ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS, CREATE_NEW_PROCESS_GROUP,
+        };
+
+        let class = match priority {
+            ProcessPriority::Low => BELOW_NORMAL_PRIORITY_CLASS,
+            ProcessPriority::Normal => 0,
+            ProcessPriority::High => ABOVE_NORMAL_PRIORITY_CLASS,
+        };
+        cmd.creation_flags(CREATE_NEW_PROCESS_GROUP | class);
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    let _ = priority;
+}
+
+/// Handle on a spawned process and everything it starts
+pub struct ProcessTree {
+    pid: u32,
+    #[cfg(windows)]
+    job: Option<windows::Job>,
+}
+
+impl ProcessTree {
+    /// Track the tree rooted at a freshly spawned child
+    pub fn attach(child: &Child, pid: u32) -> Self {
+        #[cfg(windows)]
+        {
+            Self {
+                pid,
+                job: windows::Job::assign(child),
+            }
+        }
+
+        #[cfg(not(windows))]
+        {
+            let _ = child;
+            Self { pid }
+        }
+    }
+
+    /// Ask the whole tree to terminate (SIGTERM / CTRL_BREAK)
+    pub fn terminate(&self) -> bool {
+        #[cfg(unix)]
+        {
+            // SAFETY: kill(2) has no memory-safety preconditions
+            unsafe { libc::kill(-(self.pid as libc::pid_t), libc::SIGTERM) == 0 }
+        }
+
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::System::Console::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+
+            // SAFETY: plain FFI call with integer arguments
+            unsafe { GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, self.pid) != 0 }
+        }
+
+        #[cfg(not(any(unix, windows)))]
+        {
+            false
+        }
+    }
+
+    /// Force-kill every process still alive in the tree
+    pub fn kill(&self) {
+        #[cfg(unix)]
+        // SAFETY: kill(2) has no memory-safety preconditions
+        unsafe {
+            libc::kill(-(self.pid as libc::pid_t), libc::SIGKILL);
+        }
+
+        #[cfg(windows)]
+        if let Some(job) = &self.job {
+            job.terminate();
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use tokio::process::Child;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, TerminateJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    /// Job object that kills its processes when the last handle closes
+    pub struct Job(HANDLE);
+
+    // SAFETY: job handles may be used and closed from any thread
+    unsafe impl Send for Job {}
+    unsafe impl Sync for Job {}
+
+    impl Job {
+        pub fn assign(child: &Child) -> Option<Self> {
+            let process = child.raw_handle()? as HANDLE;
+
+            // SAFETY: FFI calls on handles we own; the limit struct outlives the call
+            unsafe {
+                let handle = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+                if handle.is_null() {
+                    return None;
+                }
+                let job = Job(handle);
+
+                let mut limits: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+                limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+                let configured = SetInformationJobObject(
+                    job.0,
+                    JobObjectExtendedLimitInformation,
+                    &limits as *const _ as *const core::ffi::c_void,
+                    std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                );
+
+                if configured == 0 || AssignProcessToJobObject(job.0, process) == 0 {
+                    return None;
+                }
+                Some(job)
+            }
+        }
+
+        pub fn terminate(&self) {
+            // SAFETY: handle is valid until drop
+            unsafe {
+                TerminateJobObject(self.0, 1);
+            }
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            // SAFETY: handle is owned and closed exactly once
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+}
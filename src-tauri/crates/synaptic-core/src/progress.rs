@@ -0,0 +1,193 @@
+//! Progress of long-running requests
+//!
+//! A request asks for progress by carrying `params._meta.progressToken`; the
+//! server then sends `notifications/progress` with that token. The tracker
+//! maps tokens back to the request that introduced them, so each update can
+//! be shown against its request (`request-progress` event) and the updates
+//! of a request can be fetched later. A token stops being tracked once its
+//! request is answered; the updates are kept for the most recent
+//! [`MAX_TRACKED_REQUESTS`] requests.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Requests whose progress updates are kept
+pub const MAX_TRACKED_REQUESTS: usize = 200;
+
+/// Updates kept per request
+const MAX_UPDATES_PER_REQUEST: usize = 1000;
+
+/// One `notifications/progress` message, tied to its request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressUpdate {
+    pub server_name: String,
+    /// JSON-RPC id of the request the progress belongs to
+    pub request_id: Value,
+    pub method: String,
+    pub progress_token: Value,
+    pub progress: f64,
+    pub total: Option<f64>,
+    pub message: Option<String>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// Request that introduced a progress token
+struct TokenOwner {
+    request_id: Value,
+    method: String,
+}
+
+/// (server, JSON text of a token or request id)
+type Key = (String, String);
+
+#[derive(Default)]
+struct Tracked {
+    tokens: HashMap<Key, TokenOwner>,
+    updates: HashMap<Key, Vec<ProgressUpdate>>,
+    /// Keys of `updates`, oldest first
+    order: VecDeque<Key>,
+}
+
+/// Ties progress notifications to requests, per server
+#[derive(Default)]
+pub struct ProgressTracker {
+    tracked: Mutex<Tracked>,
+}
+
+impl ProgressTracker {
+    /// Look at an outgoing message; remembers the progress token of a request
+    pub fn observe_request(&self, server_name: &str, message: &Value) {
+        let (Some(method), Some(id), Some(token)) = (
+            message.get("method").and_then(|m| m.as_str()),
+            message.get("id").filter(|id| !id.is_null()),
+            message.pointer("/params/_meta/progressToken"),
+        ) else {
+            return;
+        };
+        self.tracked.lock().unwrap().tokens.insert(
+            (server_name.to_string(), token.to_string()),
+            TokenOwner {
+                request_id: id.clone(),
+                method: method.to_string(),
+            },
+        );
+    }
+
+    /// Look at an incoming message; returns the progress update it carries
+    ///
+    /// A response to a request with a token stops tracking that token.
+    pub fn observe_response(&self, server_name: &str, message: &Value) -> Option<ProgressUpdate> {
+        let mut tracked = self.tracked.lock().unwrap();
+        let Some(method) = message.get("method").and_then(|m| m.as_str()) else {
+            let id = message.get("id")?;
+            tracked
+                .tokens
+                .retain(|(server, _), owner| server != server_name || &owner.request_id != id);
+            return None;
+        };
+        if method != "notifications/progress" {
+            return None;
+        }
+
+        let params = message.get("params")?;
+        let token = params.get("progressToken")?;
+        let owner = tracked
+            .tokens
+            .get(&(server_name.to_string(), token.to_string()))?;
+        let update = ProgressUpdate {
+            server_name: server_name.to_string(),
+            request_id: owner.request_id.clone(),
+            method: owner.method.clone(),
+            progress_token: token.clone(),
+            progress: params
+                .get("progress")
+                .and_then(|p| p.as_f64())
+                .unwrap_or(0.0),
+            total: params.get("total").and_then(|t| t.as_f64()),
+            message: params
+                .get("message")
+                .and_then(|m| m.as_str())
+                .map(String::from),
+            timestamp: chrono::Utc::now(),
+        };
+
+        let key = (server_name.to_string(), update.request_id.to_string());
+        if !tracked.updates.contains_key(&key) {
+            tracked.order.push_back(key.clone());
+            while tracked.order.len() > MAX_TRACKED_REQUESTS {
+                if let Some(oldest) = tracked.order.pop_front() {
+                    tracked.updates.remove(&oldest);
+                }
+            }
+        }
+        let updates = tracked.updates.entry(key).or_default();
+        if updates.len() >= MAX_UPDATES_PER_REQUEST {
+            updates.remove(0);
+        }
+        updates.push(update.clone());
+        Some(update)
+    }
+
+    /// Progress updates of a request, oldest first
+    ///
+    /// Without a server name, the request id is looked up on every server.
+    pub fn updates(&self, server_name: Option<&str>, request_id: &Value) -> Vec<ProgressUpdate> {
+        let id = request_id.to_string();
+        let tracked = self.tracked.lock().unwrap();
+        tracked
+            .order
+            .iter()
+            .filter(|(server, key_id)| {
+                *key_id == id && server_name.is_none_or(|name| name == server)
+            })
+            .flat_map(|key| tracked.updates[key].iter().cloned())
+            .collect()
+    }
+
+    /// Stop tracking the tokens of a server that exited
+    pub fn forget_server(&self, server_name: &str) {
+        self.tracked
+            .lock()
+            .unwrap()
+            .tokens
+            .retain(|(server, _), _| server != server_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_progress_is_tied_to_its_request() {
+        let tracker = ProgressTracker::default();
+        tracker.observe_request(
+            "fs",
+            &json!({"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"scan","_meta":{"progressToken":"t1"}}}),
+        );
+        let progress = |value: u64| json!({"jsonrpc":"2.0","method":"notifications/progress","params":{"progressToken":"t1","progress":value,"total":10,"message":"scanning"}});
+
+        let update = tracker.observe_response("fs", &progress(3)).unwrap();
+        assert_eq!(update.request_id, json!(4));
+        assert_eq!(update.method, "tools/call");
+        assert_eq!(update.total, Some(10.0));
+        // Unknown server or token
+        assert!(tracker.observe_response("git", &progress(4)).is_none());
+
+        tracker.observe_response("fs", &progress(7));
+        tracker.observe_response("fs", &json!({"jsonrpc":"2.0","id":4,"result":{}}));
+        // The token is done with its request; the history stays
+        assert!(tracker.observe_response("fs", &progress(9)).is_none());
+        let history = tracker.updates(None, &json!(4));
+        assert_eq!(
+            history.iter().map(|u| u.progress).collect::<Vec<_>>(),
+            vec![3.0, 7.0]
+        );
+        assert!(tracker.updates(Some("git"), &json!(4)).is_empty());
+        assert!(tracker.updates(None, &json!("4")).is_empty());
+    }
+}
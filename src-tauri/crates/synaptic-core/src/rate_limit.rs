@@ -0,0 +1,217 @@
+//! Rate limits on tool calls
+//!
+//! A rate limit caps the `tools/call` requests a server receives per
+//! minute, either in total or per tool, so a runaway agent loop can't run up
+//! the bill of a paid API behind a server. Limits live in the settings; the
+//! stdin writer checks each call against them and answers one over a limit
+//! with a JSON-RPC error instead of passing it on. Calls are counted over a
+//! sliding minute that carries over server restarts.
+
+use crate::host::Host;
+use crate::policy::wildcard_matches;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// JSON-RPC error code of a call over a rate limit
+pub const RATE_LIMITED_CODE: i64 = -32002;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// At most `calls_per_minute` tool calls to matching servers and tools
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    /// Only this server (each server counted on its own when unset)
+    pub server_name: Option<String>,
+    /// Count each matching tool on its own, `*` matching any run of characters;
+    /// when unset, all of a server's tool calls count together
+    pub tool: Option<String>,
+    pub calls_per_minute: u32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl RateLimit {
+    pub fn matches(&self, server_name: &str, tool: &str) -> bool {
+        self.enabled
+            && self
+                .server_name
+                .as_deref()
+                .is_none_or(|name| name == server_name)
+            && self
+                .tool
+                .as_deref()
+                .is_none_or(|pattern| wildcard_matches(pattern, tool))
+    }
+
+    /// Which counter a call to `tool` of `server_name` goes to
+    fn bucket(&self, server_name: &str, tool: &str) -> Bucket {
+        Bucket {
+            server_name: server_name.to_string(),
+            pattern: self.tool.clone(),
+            tool: self.tool.as_ref().map(|_| tool.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Bucket {
+    server_name: String,
+    pattern: Option<String>,
+    tool: Option<String>,
+}
+
+/// Payload of the `rate-limited` event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimited {
+    pub server_name: String,
+    pub tool: String,
+    pub limit: RateLimit,
+    /// Milliseconds until the call would be let through
+    pub retry_after_ms: u64,
+}
+
+/// Recent tool calls, per counter
+#[derive(Default)]
+pub struct RateLimiter {
+    calls: Mutex<HashMap<Bucket, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    /// Count a call made at `now`, unless it goes over one of `limits`
+    pub fn admit(
+        &self,
+        limits: &[RateLimit],
+        server_name: &str,
+        tool: &str,
+        now: Instant,
+    ) -> Result<(), RateLimited> {
+        let mut calls = self.calls.lock().unwrap();
+        let matching: Vec<&RateLimit> = limits
+            .iter()
+            .filter(|limit| limit.matches(server_name, tool))
+            .collect();
+
+        for limit in &matching {
+            let recent = calls.entry(limit.bucket(server_name, tool)).or_default();
+            while recent
+                .front()
+                .is_some_and(|at| now.duration_since(*at) >= WINDOW)
+            {
+                recent.pop_front();
+            }
+            if recent.len() >= limit.calls_per_minute as usize {
+                let retry_after = recent
+                    .front()
+                    .map(|oldest| WINDOW.saturating_sub(now.duration_since(*oldest)))
+                    .unwrap_or(WINDOW);
+                return Err(RateLimited {
+                    server_name: server_name.to_string(),
+                    tool: tool.to_string(),
+                    limit: (*limit).clone(),
+                    retry_after_ms: retry_after.as_millis() as u64,
+                });
+            }
+        }
+        for limit in matching {
+            calls
+                .entry(limit.bucket(server_name, tool))
+                .or_default()
+                .push_back(now);
+        }
+        Ok(())
+    }
+
+    /// Apply the settings' rate limits to an outgoing message
+    ///
+    /// Returns the error response standing in for a call over a limit.
+    pub fn check<H: Host>(&self, host: &H, server_name: &str, request: &Value) -> Option<Value> {
+        if request.get("method").and_then(|m| m.as_str()) != Some("tools/call") {
+            return None;
+        }
+        let id = request.get("id")?;
+        let tool = request
+            .get("params")
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .unwrap_or_default();
+        let settings = host.app_state()?.get_settings().ok()?;
+        if settings.rate_limits.is_empty() {
+            return None;
+        }
+
+        let limited = self
+            .admit(&settings.rate_limits, server_name, tool, Instant::now())
+            .err()?;
+        host.emit("rate-limited", &limited);
+        Some(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": RATE_LIMITED_CODE,
+                "message": format!(
+                    "Rate limit of {} calls per minute exceeded for {}",
+                    limited.limit.calls_per_minute, tool
+                ),
+                "data": { "retryAfterMs": limited.retry_after_ms },
+            },
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(server_name: Option<&str>, tool: Option<&str>, calls_per_minute: u32) -> RateLimit {
+        RateLimit {
+            server_name: server_name.map(String::from),
+            tool: tool.map(String::from),
+            calls_per_minute,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_per_tool_limits_count_each_tool() {
+        let limiter = RateLimiter::default();
+        let limits = vec![limit(Some("search"), Some("*"), 2)];
+        let start = Instant::now();
+
+        assert!(limiter.admit(&limits, "search", "web", start).is_ok());
+        assert!(limiter.admit(&limits, "search", "web", start).is_ok());
+        let limited = limiter.admit(&limits, "search", "web", start + Duration::from_secs(20));
+        assert_eq!(limited.unwrap_err().retry_after_ms, 40_000);
+        // Other tools and servers have their own counts
+        assert!(limiter.admit(&limits, "search", "news", start).is_ok());
+        assert!(limiter.admit(&limits, "other", "web", start).is_ok());
+
+        // The window slides
+        assert!(limiter
+            .admit(&limits, "search", "web", start + WINDOW)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_server_limits_count_all_tools_together() {
+        let limiter = RateLimiter::default();
+        let limits = vec![limit(None, None, 3), limit(None, Some("expensive"), 1)];
+        let now = Instant::now();
+
+        assert!(limiter.admit(&limits, "api", "expensive", now).is_ok());
+        // Refused calls don't count against the other limits
+        assert!(limiter.admit(&limits, "api", "expensive", now).is_err());
+        assert!(limiter.admit(&limits, "api", "cheap", now).is_ok());
+        assert!(limiter.admit(&limits, "api", "cheap", now).is_ok());
+        let limited = limiter.admit(&limits, "api", "cheap", now).unwrap_err();
+        assert_eq!(limited.limit.calls_per_minute, 3);
+    }
+}
@@ -0,0 +1,267 @@
+//! Redaction of captured traffic
+//!
+//! Everything a server reads or writes is redacted once, before it reaches
+//! logs, events or the inspector. Two kinds of matches are replaced: the
+//! values of servers' env vars (registered when they spawn) and the
+//! user-defined regex rules in `redactionRules`, e.g. bearer tokens, API key
+//! shapes or email addresses. Logs, the database, crash reports and exports
+//! therefore only ever hold redacted text; in `hash` mode not even a mask
+//! but a truncated hash of each value, enough to tell values apart.
+//!
+//! The hash is an HMAC keyed with a random per-install key kept in the data
+//! directory. A plain hash of a short secret could be confirmed by hashing
+//! guesses; without the key, a fingerprint in an exported log gives nothing
+//! away.
+
+use crate::config;
+use crate::error::{SynapticError, SynapticResult};
+use regex::Regex;
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+/// File in the data directory holding the key behind [`fingerprint`]
+const FINGERPRINT_KEY_FILE: &str = "redaction.key";
+
+/// Length of the fingerprint key, in bytes
+const FINGERPRINT_KEY_LEN: usize = 32;
+
+/// Per-install fingerprint key, shared with the shim through its file
+///
+/// When the file can't be used the key is random for this run only, so
+/// fingerprints still can't be guessed but won't match across runs.
+static FINGERPRINT_KEY: LazyLock<hmac::Key> = LazyLock::new(|| {
+    let bytes = config::load_or_create_secret(FINGERPRINT_KEY_FILE, FINGERPRINT_KEY_LEN)
+        .unwrap_or_else(|e| {
+            eprintln!(
+                "Failed to load the redaction key, using one for this run: {}",
+                e
+            );
+            config::random_secret(FINGERPRINT_KEY_LEN)
+        });
+    hmac::Key::new(hmac::HMAC_SHA256, &bytes)
+});
+
+/// What a redacted value is replaced with unless a rule says otherwise
+pub const REDACTED: &str = "[REDACTED]";
+
+/// A user-defined pattern to redact
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionRule {
+    pub name: String,
+    /// Regular expression (Rust `regex` syntax)
+    pub pattern: String,
+    /// Replacement text, may refer to groups as `$1` (defaults to `[REDACTED]`)
+    #[serde(default)]
+    pub replacement: Option<String>,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Ready-made rules the settings UI offers
+pub fn presets() -> Vec<RedactionRule> {
+    let rule = |name: &str, pattern: &str, replacement: Option<&str>| RedactionRule {
+        name: name.to_string(),
+        pattern: pattern.to_string(),
+        replacement: replacement.map(String::from),
+        enabled: true,
+    };
+    vec![
+        rule(
+            "Bearer tokens",
+            r"(?i)(bearer\s+)[A-Za-z0-9\-._~+/]+=*",
+            Some("${1}[REDACTED]"),
+        ),
+        rule(
+            "API keys",
+            r"\b(?:sk-(?:ant-)?[A-Za-z0-9_\-]{20,}|gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{22,}|xox[abposr]-[A-Za-z0-9\-]{10,}|AKIA[0-9A-Z]{16})\b",
+            None,
+        ),
+        rule(
+            "Email addresses",
+            r"\b[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}\b",
+            Some("[EMAIL]"),
+        ),
+    ]
+}
+
+/// Check that every rule compiles
+pub fn validate(rules: &[RedactionRule]) -> SynapticResult<()> {
+    for rule in rules {
+        Regex::new(&rule.pattern).map_err(|e| {
+            SynapticError::ConfigParseError(format!(
+                "Invalid redaction rule '{}': {}",
+                rule.name, e
+            ))
+        })?;
+    }
+    Ok(())
+}
+
+/// How redacted values are stored
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionMode {
+    /// Replace with `[REDACTED]` (or the rule's replacement)
+    #[default]
+    Mask,
+    /// Replace with `[REDACTED:<hash>]`, a truncated keyed hash of the value, so
+    /// equal secrets can be recognised without being stored
+    Hash,
+}
+
+/// Truncated HMAC-SHA256 standing in for a redacted value in [`RedactionMode::Hash`]
+pub fn fingerprint(value: &str) -> String {
+    let tag = hmac::sign(&FINGERPRINT_KEY, value.as_bytes());
+    let hex: String = tag
+        .as_ref()
+        .iter()
+        .take(6)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("[REDACTED:{}]", hex)
+}
+
+/// Secret values and compiled rules, applied together
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    secrets: Vec<String>,
+    rules: Vec<RedactionRule>,
+    mode: RedactionMode,
+    /// Enabled rules and their explicit replacements
+    compiled: Vec<(Regex, Option<String>)>,
+}
+
+impl Redactor {
+    pub fn new(secrets: Vec<String>, rules: Vec<RedactionRule>, mode: RedactionMode) -> Self {
+        let compiled = rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| {
+                Regex::new(&rule.pattern)
+                    .ok()
+                    .map(|regex| (regex, rule.replacement.clone()))
+            })
+            .collect();
+        let secrets = secrets.into_iter().filter(|s| !s.is_empty()).collect();
+        Self {
+            secrets,
+            rules,
+            mode,
+            compiled,
+        }
+    }
+
+    /// Same rules, different secrets
+    pub fn with_secrets(&self, secrets: Vec<String>) -> Self {
+        Self::new(secrets, self.rules.clone(), self.mode)
+    }
+
+    /// Same secrets, different rules or mode
+    pub fn with_rules(&self, rules: Vec<RedactionRule>, mode: RedactionMode) -> Self {
+        Self::new(self.secrets.clone(), rules, mode)
+    }
+
+    pub fn rules(&self) -> &[RedactionRule] {
+        &self.rules
+    }
+
+    pub fn mode(&self) -> RedactionMode {
+        self.mode
+    }
+
+    fn stand_in(&self, value: &str) -> String {
+        match self.mode {
+            RedactionMode::Mask => REDACTED.to_string(),
+            RedactionMode::Hash => fingerprint(value),
+        }
+    }
+
+    /// Replace secret values, then rule matches
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for secret in &self.secrets {
+            if text.contains(secret.as_str()) {
+                text = Cow::Owned(text.replace(secret.as_str(), &self.stand_in(secret)));
+            }
+        }
+        for (regex, replacement) in &self.compiled {
+            let replaced = match replacement {
+                Some(replacement) => regex.replace_all(&text, replacement.as_str()),
+                None => regex.replace_all(&text, |caps: &regex::Captures| self.stand_in(&caps[0])),
+            };
+            if let Cow::Owned(replaced) = replaced {
+                text = Cow::Owned(replaced);
+            }
+        }
+        text
+    }
+
+    /// Redact a JSON value through its serialized form
+    ///
+    /// A replacement that breaks the JSON leaves the redacted text as a string.
+    pub fn redact_value(&self, value: &Value) -> Value {
+        let text = value.to_string();
+        match self.redact(&text) {
+            Cow::Borrowed(_) => value.clone(),
+            Cow::Owned(redacted) => {
+                serde_json::from_str(&redacted).unwrap_or(Value::String(redacted))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secrets_and_rules_are_redacted() {
+        let redactor = Redactor::new(
+            vec!["hunter2".to_string(), String::new()],
+            presets(),
+            RedactionMode::Mask,
+        );
+        let line = r#"{"auth":"Bearer abc.def-123","key":"sk-ant-REDACTED","pw":"hunter2","to":"ada@example.com"}"#;
+        assert_eq!(
+            redactor.redact(line),
+            r#"{"auth":"Bearer [REDACTED]","key":"[REDACTED]","pw":"[REDACTED]","to":"[EMAIL]"}"#
+        );
+        // Nothing to redact: no copy
+        assert!(matches!(redactor.redact("{}"), Cow::Borrowed(_)));
+
+        let mut disabled = presets();
+        disabled.iter_mut().for_each(|rule| rule.enabled = false);
+        let redactor = redactor.with_rules(disabled, RedactionMode::Mask);
+        assert_eq!(
+            redactor.redact("hunter2 ada@example.com"),
+            "[REDACTED] ada@example.com"
+        );
+
+        // Hash mode keeps equal values recognisable
+        let hashed = redactor.with_rules(presets(), RedactionMode::Hash);
+        let stored = hashed.redact("hunter2 hunter2 ada@example.com");
+        let hash = fingerprint("hunter2");
+        assert_eq!(stored, format!("{hash} {hash} [EMAIL]"));
+        assert_eq!(
+            hashed.redact_value(&serde_json::json!({ "pw": "hunter2" })),
+            serde_json::json!({ "pw": hash })
+        );
+
+        let broken = RedactionRule {
+            name: "broken".to_string(),
+            pattern: "(".to_string(),
+            replacement: None,
+            enabled: true,
+        };
+        assert!(validate(&[broken]).is_err());
+        assert!(validate(&presets()).is_ok());
+    }
+}
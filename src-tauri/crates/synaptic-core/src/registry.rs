@@ -0,0 +1,1963 @@
+//! Registry module for MCP server catalog and installation
+//!
+//! Servers are listed from the official MCP registry (or the one set in
+//! `registryUrl`), paged through with its cursors. After the first full
+//! listing only entries updated since the last sync are fetched and merged
+//! in. Listings are cached in the `registry_cache` table and served from
+//! there until they are `registryTtlMins` old, so browsing is instant and
+//! works offline. When the registry can't be reached the cached list is
+//! used, and the builtin list before anything was synced. After a sync the
+//! weekly downloads of npm and PyPI entries are filled in in the background,
+//! a few packages at a time, so sorting by popularity covers the listing.
+
+use crate::config::McpServer;
+use crate::database::Database;
+use crate::error::{SynapticError, SynapticResult};
+use crate::host::Host;
+use crate::metadata::SynapticMetadata;
+use crate::packages::{self, PackageDetails};
+use crate::state::AppState;
+use crate::transport::Transport;
+use chrono::{DateTime, TimeZone, Utc};
+use futures::StreamExt;
+use regex::Regex;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::{Client, StatusCode, Url};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Base URL of the official MCP registry
+pub const OFFICIAL_REGISTRY_URL: &str = "https://registry.modelcontextprotocol.io";
+
+/// Entries asked for per page
+const REGISTRY_PAGE_SIZE: usize = 100;
+
+/// Pages fetched at most in one sync, in case a registry keeps handing out cursors
+const MAX_REGISTRY_PAGES: usize = 500;
+
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Packages looked up at once while filling in download counts
+const ENRICH_CONCURRENCY: usize = 4;
+
+/// Set while download counts are being filled in
+static ENRICHING: AtomicBool = AtomicBool::new(false);
+
+/// `_meta` key of the official registry's own metadata on an entry
+const OFFICIAL_META: &str = "io.modelcontextprotocol.registry/official";
+
+// ============================================
+// REGISTRY DATA MODELS
+// ============================================
+
+/// Server entry from the registry (for installation)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryServer {
+    /// Unique identifier
+    pub id: String,
+
+    /// Human-readable name
+    pub name: String,
+
+    /// Description of functionality
+    pub description: String,
+
+    /// Icon URL or embedded base64
+    pub icon: Option<String>,
+
+    /// Installation method
+    pub install_method: InstallMethod,
+
+    /// Default configuration template
+    pub default_config: McpServer,
+
+    /// Repository URL for source
+    pub repo_url: Option<String>,
+
+    /// Tags for categorization
+    pub tags: Vec<String>,
+
+    /// Env vars the server can't start without
+    #[serde(default)]
+    pub required_env: Vec<String>,
+
+    /// Env vars the server takes, described for guided setup
+    #[serde(default)]
+    pub env_schema: Vec<EnvVarSpec>,
+
+    /// Latest published version, for entries from a remote registry
+    #[serde(default)]
+    pub version: Option<String>,
+
+    /// When the entry was last updated in the registry
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+
+    /// Package downloads over the last week, when known
+    #[serde(default)]
+    pub downloads: Option<u64>,
+}
+
+/// An env var a registry entry takes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvVarSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// Whether the value is a credential, to be masked while entered
+    #[serde(default)]
+    pub secret: bool,
+    /// Regex the whole value has to match
+    #[serde(default)]
+    pub validation: Option<String>,
+}
+
+impl EnvVarSpec {
+    /// Spec of a required env var nothing else is known about
+    fn required(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: None,
+            required: true,
+            secret: false,
+            validation: None,
+        }
+    }
+}
+
+/// An env value an install can't go ahead without
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingEnv {
+    pub spec: EnvVarSpec,
+    /// Why the value given was rejected; `None` when none was given
+    pub error: Option<String>,
+}
+
+/// Installation method for registry servers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InstallMethod {
+    /// Run via npx (Node.js)
+    Npx { package: String },
+
+    /// Run via uvx (Python/uv)
+    Uvx { package: String },
+
+    /// Clone from git repository
+    GitClone {
+        url: String,
+        build_command: Option<String>,
+    },
+
+    /// Direct binary download, checked against its SHA-256 (and signature when given)
+    Binary {
+        url: String,
+        /// Hex SHA-256 of the file
+        sha256: String,
+        /// Base64 Ed25519 signature of the file
+        #[serde(default)]
+        signature: Option<String>,
+        /// Base64 Ed25519 public key the signature was made with; it must be a trusted key
+        #[serde(default)]
+        public_key: Option<String>,
+    },
+
+    /// Nothing to install: the server is reached over HTTP
+    Remote { url: String },
+}
+
+/// Runtime status check result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeStatus {
+    pub runtime: String,
+    pub available: bool,
+    pub version: Option<String>,
+    pub path: Option<String>,
+}
+
+// ============================================
+// BUILTIN REGISTRY
+// ============================================
+
+/// Get the hardcoded list of popular MCP servers
+pub fn get_builtin_registry() -> Vec<RegistryServer> {
+    vec![
+        RegistryServer {
+            id: "filesystem".into(),
+            name: "Filesystem".into(),
+            description: "Read/write access to local filesystem. Allows Claude to browse, read, and write files in specified directories.".into(),
+            icon: None,
+            install_method: InstallMethod::Npx {
+                package: "@modelcontextprotocol/server-filesystem".into(),
+            },
+            default_config: McpServer {
+                command: "npx".into(),
+                args: vec![
+                    "-y".into(),
+                    "@modelcontextprotocol/server-filesystem".into(),
+                    "C:\\Users".into(), // Placeholder path
+                ],
+                env: HashMap::new(),
+                cwd: None,
+                enabled: true,
+                ..Default::default()
+            },
+            repo_url: Some("https://github.com/modelcontextprotocol/servers".into()),
+            tags: vec!["filesystem".into(), "official".into(), "core".into()],
+            required_env: Vec::new(),
+            env_schema: Vec::new(),
+            version: None,
+            updated_at: None,
+            downloads: None,
+        },
+        RegistryServer {
+            id: "sqlite".into(),
+            name: "SQLite".into(),
+            description: "Query and manage SQLite databases. Enables Claude to run SQL queries and explore database schemas.".into(),
+            icon: None,
+            install_method: InstallMethod::Uvx {
+                package: "mcp-server-sqlite".into(),
+            },
+            default_config: McpServer {
+                command: "uvx".into(),
+                args: vec![
+                    "mcp-server-sqlite".into(),
+                    "--db-path".into(),
+                    "database.db".into(),
+                ],
+                env: HashMap::new(),
+                cwd: None,
+                enabled: true,
+                ..Default::default()
+            },
+            repo_url: Some("https://github.com/modelcontextprotocol/servers".into()),
+            tags: vec!["database".into(), "sql".into(), "official".into()],
+            required_env: Vec::new(),
+            env_schema: Vec::new(),
+            version: None,
+            updated_at: None,
+            downloads: None,
+        },
+        RegistryServer {
+            id: "github".into(),
+            name: "GitHub".into(),
+            description: "Interact with GitHub repositories. Create issues, PRs, search code, and manage repositories.".into(),
+            icon: None,
+            install_method: InstallMethod::Npx {
+                package: "@modelcontextprotocol/server-github".into(),
+            },
+            default_config: McpServer {
+                command: "npx".into(),
+                args: vec![
+                    "-y".into(),
+                    "@modelcontextprotocol/server-github".into(),
+                ],
+                env: HashMap::from([("GITHUB_PERSONAL_ACCESS_TOKEN".into(), "".into())]),
+                cwd: None,
+                enabled: true,
+                ..Default::default()
+            },
+            repo_url: Some("https://github.com/modelcontextprotocol/servers".into()),
+            tags: vec!["git".into(), "vcs".into(), "official".into()],
+            required_env: vec!["GITHUB_PERSONAL_ACCESS_TOKEN".into()],
+            env_schema: vec![EnvVarSpec {
+                name: "GITHUB_PERSONAL_ACCESS_TOKEN".into(),
+                description: Some("Personal access token with access to the repositories to work on".into()),
+                required: true,
+                secret: true,
+                validation: Some("(ghp_|github_pat_)[A-Za-z0-9_]+".into()),
+            }],
+            version: None,
+            updated_at: None,
+            downloads: None,
+        },
+        RegistryServer {
+            id: "memory".into(),
+            name: "Memory".into(),
+            description: "Persistent memory and knowledge graph. Allows Claude to remember information across conversations.".into(),
+            icon: None,
+            install_method: InstallMethod::Npx {
+                package: "@modelcontextprotocol/server-memory".into(),
+            },
+            default_config: McpServer {
+                command: "npx".into(),
+                args: vec![
+                    "-y".into(),
+                    "@modelcontextprotocol/server-memory".into(),
+                ],
+                env: HashMap::new(),
+                cwd: None,
+                enabled: true,
+                ..Default::default()
+            },
+            repo_url: Some("https://github.com/modelcontextprotocol/servers".into()),
+            tags: vec!["memory".into(), "knowledge".into(), "official".into()],
+            required_env: Vec::new(),
+            env_schema: Vec::new(),
+            version: None,
+            updated_at: None,
+            downloads: None,
+        },
+        RegistryServer {
+            id: "brave-search".into(),
+            name: "Brave Search".into(),
+            description: "Web search powered by Brave. Search the web and get summarized results.".into(),
+            icon: None,
+            install_method: InstallMethod::Npx {
+                package: "@modelcontextprotocol/server-brave-search".into(),
+            },
+            default_config: McpServer {
+                command: "npx".into(),
+                args: vec![
+                    "-y".into(),
+                    "@modelcontextprotocol/server-brave-search".into(),
+                ],
+                env: HashMap::from([("BRAVE_API_KEY".into(), "".into())]),
+                cwd: None,
+                enabled: true,
+                ..Default::default()
+            },
+            repo_url: Some("https://github.com/modelcontextprotocol/servers".into()),
+            tags: vec!["search".into(), "web".into(), "official".into()],
+            required_env: vec!["BRAVE_API_KEY".into()],
+            env_schema: vec![EnvVarSpec {
+                name: "BRAVE_API_KEY".into(),
+                description: Some("Brave Search API key".into()),
+                required: true,
+                secret: true,
+                validation: None,
+            }],
+            version: None,
+            updated_at: None,
+            downloads: None,
+        },
+    ]
+}
+
+/// Get a registry server by ID
+pub fn get_registry_server(id: &str) -> Option<RegistryServer> {
+    get_builtin_registry().into_iter().find(|s| s.id == id)
+}
+
+impl RegistryServer {
+    /// Whether a configured server runs this entry's package
+    pub fn matches(&self, server: &McpServer) -> bool {
+        match &self.install_method {
+            InstallMethod::Npx { package } | InstallMethod::Uvx { package } => {
+                let versioned = format!("{}@", package);
+                server
+                    .args
+                    .iter()
+                    .any(|arg| arg == package || arg.starts_with(&versioned))
+            }
+            _ => false,
+        }
+    }
+
+    /// Env vars the entry takes: its schema, plus any required ones the schema leaves out
+    pub fn env_specs(&self) -> Vec<EnvVarSpec> {
+        let mut specs = self.env_schema.clone();
+        for name in &self.required_env {
+            if !specs.iter().any(|spec| &spec.name == name) {
+                specs.push(EnvVarSpec::required(name));
+            }
+        }
+        specs
+    }
+
+    /// Required env values that `env` (over the default config's) leaves
+    /// empty, and values that don't pass their validation
+    pub fn missing_env(&self, env: &HashMap<String, String>) -> SynapticResult<Vec<MissingEnv>> {
+        let mut missing = Vec::new();
+        for spec in self.env_specs() {
+            let value = env
+                .get(&spec.name)
+                .or_else(|| self.default_config.env.get(&spec.name))
+                .map(|value| value.trim())
+                .unwrap_or_default();
+            if value.is_empty() {
+                if spec.required {
+                    missing.push(MissingEnv { spec, error: None });
+                }
+                continue;
+            }
+            let Some(pattern) = &spec.validation else {
+                continue;
+            };
+            let validation = Regex::new(&format!("^(?:{})$", pattern))
+                .map_err(|e| registry_error(&format!("Invalid validation for {}", spec.name), e))?;
+            if !validation.is_match(value) {
+                let error = format!("{} doesn't have the expected format", spec.name);
+                missing.push(MissingEnv {
+                    spec,
+                    error: Some(error),
+                });
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Config name an install gets by default: the last part of the ID
+    pub fn install_name(&self) -> String {
+        self.id.rsplit('/').next().unwrap_or(&self.id).to_string()
+    }
+
+    /// Config to install the entry with, its package pinned to `version` when given
+    pub fn config_for_version(&self, version: Option<&str>) -> SynapticResult<McpServer> {
+        let mut config = self.default_config.clone();
+        let Some(version) = version else {
+            return Ok(config);
+        };
+        if version.is_empty() || version.contains(char::is_whitespace) {
+            return Err(SynapticError::RegistryError(format!(
+                "Invalid version: {:?}",
+                version
+            )));
+        }
+        let Some((_, package)) = packages::package_of(&self.install_method) else {
+            return Err(SynapticError::RegistryError(format!(
+                "{} isn't installed from a package, so it has no versions",
+                self.name
+            )));
+        };
+        let versioned = format!("{}@", package);
+        let arg = config
+            .args
+            .iter_mut()
+            .find(|arg| *arg == package || arg.starts_with(&versioned))
+            .ok_or_else(|| {
+                SynapticError::RegistryError(format!("{} isn't in the server's args", package))
+            })?;
+        *arg = format!("{}@{}", package, version);
+        Ok(config)
+    }
+}
+
+/// Add a registry entry to the config as `name` (by default the last part of
+/// its ID), pinned to `version` and with the `env` values given, and record
+/// where it came from
+///
+/// Fails when a required env value is missing or one doesn't pass validation.
+pub fn install(
+    state: &AppState,
+    server_id: &str,
+    name: Option<String>,
+    version: Option<&str>,
+    env: &HashMap<String, String>,
+) -> SynapticResult<String> {
+    let entry = find_server(state, server_id)
+        .ok_or_else(|| SynapticError::RegistryError(format!("Server not found: {}", server_id)))?;
+    check_env(&entry, env)?;
+    let config = with_env(entry.config_for_version(version)?, env);
+    let name = name.unwrap_or_else(|| entry.install_name());
+    add_installed(state, &name, server_id, version, None, config)?;
+    Ok(name)
+}
+
+/// Fail with the names of the env values `env` leaves missing or invalid
+pub fn check_env(entry: &RegistryServer, env: &HashMap<String, String>) -> SynapticResult<()> {
+    let missing = entry.missing_env(env)?;
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let names: Vec<&str> = missing.iter().map(|m| m.spec.name.as_str()).collect();
+    Err(SynapticError::RegistryError(format!(
+        "{} needs configuring: {}",
+        entry.name,
+        names.join(", ")
+    )))
+}
+
+/// The config with the non-empty `env` values set
+pub fn with_env(mut config: McpServer, env: &HashMap<String, String>) -> McpServer {
+    for (name, value) in env {
+        if !value.trim().is_empty() {
+            config.env.insert(name.clone(), value.trim().to_string());
+        }
+    }
+    config
+}
+
+/// Add an installed entry's config as `name` and record where it came from,
+/// along with the directory it was installed into
+///
+/// A server whose record couldn't be saved is taken out again: without it the
+/// pin would be lost and the server would look hand-added.
+pub fn add_installed(
+    state: &AppState,
+    name: &str,
+    server_id: &str,
+    version: Option<&str>,
+    install_dir: Option<&Path>,
+    config: McpServer,
+) -> SynapticResult<()> {
+    state.add_server(name.to_string(), config)?;
+
+    let recorded = SynapticMetadata::load().and_then(|mut metadata| {
+        metadata.set_install(name, server_id, version);
+        if let Some(dir) = install_dir {
+            metadata.set_install_dir(name, dir);
+        }
+        metadata.save()
+    });
+    if let Err(e) = recorded {
+        if let Err(rollback) = state.remove_server(name) {
+            eprintln!("Failed to take {} out again: {}", name, rollback);
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Env vars a configured server needs, per the registry entry it runs
+///
+/// The synced listing is searched first when there's app state, then the builtin one.
+pub fn required_env(state: Option<&AppState>, server: &McpServer) -> Vec<String> {
+    let listed = state.and_then(|state| {
+        let registry_url = state.get_settings().ok()?.registry_url;
+        cached_snapshot(state, &registry_url)?
+            .servers
+            .into_iter()
+            .find(|entry| entry.matches(server))
+    });
+    listed
+        .or_else(|| {
+            get_builtin_registry()
+                .into_iter()
+                .find(|entry| entry.matches(server))
+        })
+        .map(|entry| entry.required_env)
+        .unwrap_or_default()
+}
+
+// ============================================
+// REMOTE REGISTRY
+// ============================================
+
+/// Where a registry listing came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrySource {
+    Remote,
+    Builtin,
+}
+
+/// Servers listed by a registry as of its last sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrySnapshot {
+    pub source: RegistrySource,
+    /// Base URL of the registry (empty for the builtin list)
+    pub registry_url: String,
+    /// When the sync that produced this listing started
+    pub synced_at: Option<DateTime<Utc>>,
+    /// `ETag` the registry sent with the listing's first page
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// Served from the cache past its TTL, because the registry couldn't be reached or offline mode is on
+    #[serde(default)]
+    pub stale: bool,
+    pub servers: Vec<RegistryServer>,
+}
+
+impl RegistrySnapshot {
+    /// The builtin list, used before a registry could be reached
+    pub fn builtin() -> Self {
+        Self {
+            source: RegistrySource::Builtin,
+            registry_url: String::new(),
+            synced_at: None,
+            etag: None,
+            stale: false,
+            servers: get_builtin_registry(),
+        }
+    }
+
+    /// Entry with the given ID
+    pub fn find(&self, id: &str) -> Option<&RegistryServer> {
+        self.servers.iter().find(|s| s.id == id)
+    }
+}
+
+/// A listed entry: the normalized server, or `None` when it was deleted or can't be installed
+struct RegistryUpdate {
+    id: String,
+    server: Option<RegistryServer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerListResponse {
+    #[serde(default)]
+    servers: Vec<Value>,
+    #[serde(default)]
+    metadata: ListMetadata,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ListMetadata {
+    #[serde(rename = "nextCursor", alias = "next_cursor")]
+    next_cursor: Option<String>,
+}
+
+/// `server.json` as published to the registry (older snake_case field names are accepted)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerJson {
+    name: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    repository: Option<Repository>,
+    #[serde(default, alias = "website_url")]
+    website_url: Option<String>,
+    #[serde(default)]
+    icons: Vec<Icon>,
+    #[serde(default)]
+    packages: Vec<Package>,
+    #[serde(default)]
+    remotes: Vec<Remote>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Repository {
+    #[serde(default)]
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Icon {
+    src: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Package {
+    #[serde(alias = "registry_type", alias = "registry_name")]
+    registry_type: String,
+    #[serde(alias = "name")]
+    identifier: String,
+    #[serde(default)]
+    transport: Option<PackageTransport>,
+    #[serde(default, alias = "runtime_arguments")]
+    runtime_arguments: Vec<Argument>,
+    #[serde(default, alias = "package_arguments")]
+    package_arguments: Vec<Argument>,
+    #[serde(default, alias = "environment_variables")]
+    environment_variables: Vec<Input>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageTransport {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Argument {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    default: Option<String>,
+}
+
+/// An env var or header the server takes
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Input {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default, alias = "is_required")]
+    is_required: bool,
+    #[serde(default, alias = "is_secret")]
+    is_secret: bool,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    choices: Vec<String>,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    default: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Remote {
+    #[serde(rename = "type", alias = "transport_type")]
+    kind: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<Input>,
+}
+
+fn registry_error(context: &str, e: impl std::fmt::Display) -> SynapticError {
+    SynapticError::RegistryError(format!("{}: {}", context, e))
+}
+
+/// Servers of the configured registry
+///
+/// A listing synced less than `registryTtlMins` ago (from memory, else the
+/// database) is returned as is unless `force` is set; otherwise the changes
+/// since the last sync are fetched, or everything when forced. Falls back to
+/// the cached listing, or the builtin one, when the registry can't be reached
+/// or `registryOffline` is set; an empty `registryUrl` always uses the
+/// builtin list.
+pub async fn refresh(state: &AppState, force: bool) -> RegistrySnapshot {
+    sync_if_due(state, force).await.0
+}
+
+/// [`refresh`], then fill in download counts in the background when the listing was synced
+pub async fn refresh_and_enrich<H: Host>(host: &H, force: bool) -> RegistrySnapshot {
+    let Some(state) = host.app_state() else {
+        return RegistrySnapshot::builtin();
+    };
+    let (snapshot, synced) = sync_if_due(state, force).await;
+    if synced {
+        spawn_enrichment(host.clone());
+    }
+    snapshot
+}
+
+/// The listing [`refresh`] serves, and whether it was just synced
+async fn sync_if_due(state: &AppState, force: bool) -> (RegistrySnapshot, bool) {
+    let settings = state.get_settings().unwrap_or_default();
+    if settings.registry_url.is_empty() {
+        return (RegistrySnapshot::builtin(), false);
+    }
+
+    let cached = cached_snapshot(state, &settings.registry_url);
+    let ttl = chrono::Duration::minutes(settings.registry_ttl_mins as i64);
+    let fresh = cached
+        .as_ref()
+        .and_then(|snapshot| snapshot.synced_at)
+        .is_some_and(|synced_at| Utc::now() - synced_at < ttl);
+    if settings.registry_offline || (fresh && !force) {
+        return (stale_or_builtin(cached, !fresh), false);
+    }
+
+    let previous = if force { None } else { cached.as_ref() };
+    match sync_registry(&settings.registry_url, previous).await {
+        Ok(snapshot) => {
+            if let Ok(db) = state.database() {
+                if let Err(e) = save_cache(db, &snapshot) {
+                    eprintln!("Failed to cache registry listing: {}", e);
+                }
+            }
+            *state.registry_cache.lock().unwrap() = Some(snapshot.clone());
+            (snapshot, true)
+        }
+        Err(e) => {
+            eprintln!("Registry sync failed: {}", e);
+            (stale_or_builtin(cached, true), false)
+        }
+    }
+}
+
+/// Cached listing, marked stale when `stale`, or the builtin one without a cache
+fn stale_or_builtin(cached: Option<RegistrySnapshot>, stale: bool) -> RegistrySnapshot {
+    match cached {
+        Some(snapshot) => RegistrySnapshot { stale, ..snapshot },
+        None => RegistrySnapshot::builtin(),
+    }
+}
+
+/// Last listing synced from `registry_url`, loading it from the database on first use
+fn cached_snapshot(state: &AppState, registry_url: &str) -> Option<RegistrySnapshot> {
+    let mut cache = state.registry_cache.lock().unwrap();
+    if cache
+        .as_ref()
+        .is_none_or(|snapshot| snapshot.registry_url != registry_url)
+    {
+        let db = state.database().ok()?;
+        match load_cache(db, registry_url) {
+            Ok(loaded) => *cache = loaded,
+            Err(e) => eprintln!("Failed to load cached registry listing: {}", e),
+        }
+    }
+    cache
+        .clone()
+        .filter(|snapshot| snapshot.registry_url == registry_url)
+}
+
+/// Entry with the given ID in the cached listing, or the builtin list
+pub fn find_server(state: &AppState, id: &str) -> Option<RegistryServer> {
+    let registry_url = state
+        .get_settings()
+        .map(|settings| settings.registry_url)
+        .unwrap_or_default();
+    cached_snapshot(state, &registry_url)
+        .and_then(|snapshot| snapshot.find(id).cloned())
+        .or_else(|| get_registry_server(id))
+}
+
+/// A registry entry with what its package index says about it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryServerDetails {
+    pub server: RegistryServer,
+    /// `None` for entries that don't run an npm or PyPI package
+    pub package: Option<PackageDetails>,
+}
+
+/// Entry with the given ID and its package's details
+///
+/// The weekly download count is kept on the cached entry, so sorting by
+/// popularity covers every entry looked at.
+pub async fn server_details(state: &AppState, id: &str) -> SynapticResult<RegistryServerDetails> {
+    let mut server = find_server(state, id)
+        .ok_or_else(|| SynapticError::RegistryError(format!("Server not found: {}", id)))?;
+    let package = match packages::package_of(&server.install_method) {
+        Some((ecosystem, package)) => Some(packages::fetch_details(ecosystem, package).await?),
+        None => None,
+    };
+    if let Some(downloads) = package.as_ref().and_then(|p| p.weekly_downloads) {
+        server.downloads = Some(downloads);
+        if let Err(e) = cache_downloads(state, id, downloads) {
+            eprintln!("Failed to cache download count of {}: {}", id, e);
+        }
+    }
+    Ok(RegistryServerDetails { server, package })
+}
+
+/// Set the download count of an entry of the cached listing
+fn cache_downloads(state: &AppState, id: &str, downloads: u64) -> SynapticResult<()> {
+    let mut cache = state.registry_cache.lock().unwrap();
+    let Some(snapshot) = cache.as_mut() else {
+        return Ok(());
+    };
+    let Some(entry) = snapshot.servers.iter_mut().find(|s| s.id == id) else {
+        return Ok(());
+    };
+    entry.downloads = Some(downloads);
+    if snapshot.source == RegistrySource::Remote {
+        state.database()?.conn().execute(
+            "UPDATE registry_cache SET entry = ?3 WHERE registry_url = ?1 AND id = ?2",
+            params![snapshot.registry_url, id, serde_json::to_value(&*entry)?],
+        )?;
+    }
+    Ok(())
+}
+
+/// Fill in the weekly downloads of the cached listing's npm and PyPI entries in the background
+///
+/// Entries that have a count already are skipped. Emits `registry-enriched`
+/// with the number of entries updated once done; a second call while one
+/// runs does nothing.
+pub fn spawn_enrichment<H: Host>(host: H) {
+    if ENRICHING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::spawn(async move {
+        let enriched = enrich(&host).await;
+        ENRICHING.store(false, Ordering::SeqCst);
+        if enriched > 0 {
+            host.emit("registry-enriched", enriched);
+        }
+    });
+}
+
+async fn enrich<H: Host>(host: &H) -> usize {
+    let Some(state) = host.app_state() else {
+        return 0;
+    };
+    let pending: Vec<RegistryServer> = match state.registry_cache.lock().unwrap().as_ref() {
+        Some(snapshot) => snapshot
+            .servers
+            .iter()
+            .filter(|s| s.downloads.is_none() && packages::package_of(&s.install_method).is_some())
+            .cloned()
+            .collect(),
+        None => return 0,
+    };
+
+    let mut lookups = futures::stream::iter(pending)
+        .map(|server| async move {
+            let (ecosystem, package) = packages::package_of(&server.install_method)?;
+            let details = packages::fetch_details(ecosystem, package).await.ok()?;
+            Some((server.id.clone(), details.weekly_downloads?))
+        })
+        .buffer_unordered(ENRICH_CONCURRENCY);
+    let mut enriched = 0;
+    while let Some(found) = lookups.next().await {
+        let Some((id, downloads)) = found else {
+            continue;
+        };
+        match cache_downloads(state, &id, downloads) {
+            Ok(()) => enriched += 1,
+            Err(e) => eprintln!("Failed to cache download count of {}: {}", id, e),
+        }
+    }
+    enriched
+}
+
+/// Fetch a registry's listing, only the changes since `previous` when it came from the same registry
+pub async fn sync_registry(
+    registry_url: &str,
+    previous: Option<&RegistrySnapshot>,
+) -> SynapticResult<RegistrySnapshot> {
+    let started = Utc::now();
+    let previous = previous.filter(|snapshot| {
+        snapshot.source == RegistrySource::Remote && snapshot.registry_url == registry_url
+    });
+    let since = previous.and_then(|snapshot| snapshot.synced_at);
+    let etag = previous.and_then(|snapshot| snapshot.etag.as_deref());
+
+    let (servers, etag) = match fetch_registry(registry_url, since, etag).await? {
+        Fetched::NotModified => {
+            let previous = previous.expect("only conditional requests come back unmodified");
+            (previous.servers.clone(), previous.etag.clone())
+        }
+        // A delta's ETag isn't the listing's: the full listing's one is kept
+        Fetched::Updates { updates, etag } => match previous {
+            Some(snapshot) if since.is_some() => (
+                merge(snapshot.servers.clone(), updates),
+                snapshot.etag.clone(),
+            ),
+            _ => (merge(Vec::new(), updates), etag),
+        },
+    };
+    Ok(RegistrySnapshot {
+        source: RegistrySource::Remote,
+        registry_url: registry_url.to_string(),
+        synced_at: Some(started),
+        etag,
+        stale: false,
+        servers,
+    })
+}
+
+/// Outcome of fetching a registry's listing
+enum Fetched {
+    /// The registry answered the first page with `304 Not Modified`
+    NotModified,
+    Updates {
+        updates: Vec<RegistryUpdate>,
+        /// `ETag` of the first page
+        etag: Option<String>,
+    },
+}
+
+/// Every page of `/v0/servers`, only entries updated after `updated_since` when given
+///
+/// The first page is requested with `If-None-Match: etag` when an ETag is known.
+/// A listing longer than `MAX_REGISTRY_PAGES` fails rather than being cut short.
+async fn fetch_registry(
+    registry_url: &str,
+    updated_since: Option<DateTime<Utc>>,
+    etag: Option<&str>,
+) -> SynapticResult<Fetched> {
+    let client = Client::builder()
+        .timeout(REGISTRY_TIMEOUT)
+        .build()
+        .map_err(|e| registry_error("HTTP client", e))?;
+    let endpoint = Url::parse(&format!(
+        "{}/v0/servers",
+        registry_url.trim_end_matches('/')
+    ))
+    .map_err(|e| registry_error("Registry URL", e))?;
+
+    let mut updates = Vec::new();
+    let mut first_etag = None;
+    let mut cursor: Option<String> = None;
+    let mut complete = false;
+    for _ in 0..MAX_REGISTRY_PAGES {
+        let mut url = endpoint.clone();
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("limit", &REGISTRY_PAGE_SIZE.to_string());
+            if let Some(since) = updated_since {
+                query.append_pair("updated_since", &since.to_rfc3339());
+            }
+            if let Some(cursor) = &cursor {
+                query.append_pair("cursor", cursor);
+            }
+        }
+
+        let mut request = client.get(url);
+        if let (None, Some(etag)) = (&cursor, etag) {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| registry_error("Registry request", e))?;
+        if cursor.is_none() {
+            if response.status() == StatusCode::NOT_MODIFIED && etag.is_some() {
+                return Ok(Fetched::NotModified);
+            }
+            first_etag = response
+                .headers()
+                .get(ETAG)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from);
+        }
+        if !response.status().is_success() {
+            return Err(SynapticError::RegistryError(format!(
+                "Registry returned {}",
+                response.status()
+            )));
+        }
+        let page: ServerListResponse = response
+            .json()
+            .await
+            .map_err(|e| registry_error("Registry response", e))?;
+
+        updates.extend(page.servers.iter().filter_map(normalize));
+        match page.metadata.next_cursor.filter(|next| !next.is_empty()) {
+            Some(next) if !page.servers.is_empty() && cursor.as_ref() != Some(&next) => {
+                cursor = Some(next)
+            }
+            _ => {
+                complete = true;
+                break;
+            }
+        }
+    }
+    if !complete {
+        return Err(SynapticError::RegistryError(format!(
+            "Registry listing is longer than {} pages",
+            MAX_REGISTRY_PAGES
+        )));
+    }
+    Ok(Fetched::Updates {
+        updates,
+        etag: first_etag,
+    })
+}
+
+/// Listing last synced from `registry_url` and stored in `registry_cache`
+pub fn load_cache(db: &Database, registry_url: &str) -> SynapticResult<Option<RegistrySnapshot>> {
+    let conn = db.conn();
+    let sync = conn
+        .query_row(
+            "SELECT synced_at, etag FROM registry_sync WHERE registry_url = ?1",
+            params![registry_url],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?)),
+        )
+        .optional()?;
+    let Some((synced_at, etag)) = sync else {
+        return Ok(None);
+    };
+
+    let mut stmt =
+        conn.prepare("SELECT entry FROM registry_cache WHERE registry_url = ?1 ORDER BY position")?;
+    let entries = stmt.query_map(params![registry_url], |row| row.get::<_, Value>(0))?;
+    let mut servers = Vec::new();
+    for entry in entries {
+        servers.push(serde_json::from_value(entry?)?);
+    }
+    Ok(Some(RegistrySnapshot {
+        source: RegistrySource::Remote,
+        registry_url: registry_url.to_string(),
+        synced_at: Utc.timestamp_millis_opt(synced_at).single(),
+        etag,
+        stale: false,
+        servers,
+    }))
+}
+
+/// Replace the cached listing of the snapshot's registry
+pub fn save_cache(db: &Database, snapshot: &RegistrySnapshot) -> SynapticResult<()> {
+    let mut conn = db.conn();
+    let tx = conn.transaction()?;
+    tx.execute(
+        "DELETE FROM registry_cache WHERE registry_url = ?1",
+        params![snapshot.registry_url],
+    )?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT OR REPLACE INTO registry_cache (registry_url, id, position, entry)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for (position, server) in snapshot.servers.iter().enumerate() {
+            insert.execute(params![
+                snapshot.registry_url,
+                server.id,
+                position as i64,
+                serde_json::to_value(server)?,
+            ])?;
+        }
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO registry_sync (registry_url, synced_at, etag) VALUES (?1, ?2, ?3)",
+        params![
+            snapshot.registry_url,
+            snapshot
+                .synced_at
+                .unwrap_or_else(Utc::now)
+                .timestamp_millis(),
+            snapshot.etag,
+        ],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Apply updates to a listing: changed entries are replaced in place, new ones appended
+fn merge(mut servers: Vec<RegistryServer>, updates: Vec<RegistryUpdate>) -> Vec<RegistryServer> {
+    for update in updates {
+        let existing = servers.iter().position(|s| s.id == update.id);
+        match (existing, update.server) {
+            (Some(i), Some(server)) => servers[i] = server,
+            (None, Some(server)) => servers.push(server),
+            (Some(i), None) => {
+                servers.remove(i);
+            }
+            (None, None) => {}
+        }
+    }
+    servers
+}
+
+/// Registry entry as a [`RegistryServer`]; `None` for older versions of a server
+fn normalize(entry: &Value) -> Option<RegistryUpdate> {
+    // Current responses wrap `server.json` in `server`, older ones inline it
+    let server = entry.get("server").unwrap_or(entry);
+    let official = entry
+        .get("_meta")
+        .or_else(|| server.get("_meta"))
+        .and_then(|meta| meta.get(OFFICIAL_META));
+    let latest = official
+        .and_then(|meta| meta.get("isLatest").or_else(|| meta.get("is_latest")))
+        .and_then(|v| v.as_bool());
+    if latest == Some(false) {
+        return None;
+    }
+    let server: ServerJson = serde_json::from_value(server.clone()).ok()?;
+    let deleted = official
+        .and_then(|meta| meta.get("status"))
+        .and_then(|status| status.as_str())
+        == Some("deleted");
+    let updated_at = official
+        .and_then(|meta| {
+            ["updatedAt", "updated_at", "publishedAt", "published_at"]
+                .iter()
+                .find_map(|key| meta.get(*key))
+        })
+        .and_then(|at| at.as_str())
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .map(|at| at.with_timezone(&Utc));
+    let id = server.name.clone();
+    let server = if deleted { None } else { installable(server) };
+    Some(RegistryUpdate {
+        server: server.map(|server| RegistryServer {
+            updated_at,
+            ..server
+        }),
+        id,
+    })
+}
+
+/// Regex for an input's choices or format, when it has either
+fn input_validation(input: &Input) -> Option<String> {
+    if !input.choices.is_empty() {
+        let choices: Vec<String> = input.choices.iter().map(|c| regex::escape(c)).collect();
+        return Some(choices.join("|"));
+    }
+    match input.format.as_deref() {
+        Some("number") => Some(r"-?\d+(\.\d+)?".to_string()),
+        Some("boolean") => Some("true|false".to_string()),
+        _ => None,
+    }
+}
+
+/// Entry for the first package Synaptic can run (npm or PyPI over stdio), else the first remote
+fn installable(server: ServerJson) -> Option<RegistryServer> {
+    let stdio = |package: &&Package| {
+        package
+            .transport
+            .as_ref()
+            .is_none_or(|transport| transport.kind == "stdio")
+    };
+    let package = server
+        .packages
+        .iter()
+        .filter(stdio)
+        .find(|package| matches!(package.registry_type.as_str(), "npm" | "pypi"));
+
+    let mut required_env = Vec::new();
+    let mut env_schema = Vec::new();
+    let (install_method, default_config, tag) = if let Some(package) = package {
+        let mut env = HashMap::new();
+        for input in package.environment_variables.iter() {
+            let value = input.value.clone().or_else(|| input.default.clone());
+            if input.is_required || value.is_some() {
+                env.insert(input.name.clone(), value.unwrap_or_default());
+            }
+            if input.is_required {
+                required_env.push(input.name.clone());
+            }
+            env_schema.push(EnvVarSpec {
+                name: input.name.clone(),
+                description: input.description.clone(),
+                required: input.is_required,
+                secret: input.is_secret,
+                validation: input_validation(input),
+            });
+        }
+        let identifier = package.identifier.clone();
+        let (method, command, mut args) = if package.registry_type == "npm" {
+            (
+                InstallMethod::Npx {
+                    package: identifier.clone(),
+                },
+                "npx",
+                vec!["-y".to_string()],
+            )
+        } else {
+            (
+                InstallMethod::Uvx {
+                    package: identifier.clone(),
+                },
+                "uvx",
+                Vec::new(),
+            )
+        };
+        args.extend(arguments(&package.runtime_arguments));
+        args.push(identifier);
+        args.extend(arguments(&package.package_arguments));
+        let config = McpServer {
+            command: command.to_string(),
+            args,
+            env,
+            ..Default::default()
+        };
+        (method, config, package.registry_type.clone())
+    } else {
+        let (remote, transport) = server.remotes.iter().find_map(|remote| {
+            let transport = match remote.kind.as_str() {
+                "streamable-http" | "http" => Transport::Http,
+                "sse" => Transport::Sse,
+                _ => return None,
+            };
+            Some((remote, transport))
+        })?;
+        let headers = remote
+            .headers
+            .iter()
+            .filter_map(|header| {
+                let value = header.value.clone().or_else(|| header.default.clone());
+                (header.is_required || value.is_some())
+                    .then(|| (header.name.clone(), value.unwrap_or_default()))
+            })
+            .collect();
+        let config = McpServer {
+            url: Some(remote.url.clone()),
+            transport,
+            headers,
+            ..Default::default()
+        };
+        let method = InstallMethod::Remote {
+            url: remote.url.clone(),
+        };
+        (method, config, "remote".to_string())
+    };
+
+    let display_name = server.title.clone().unwrap_or_else(|| {
+        server
+            .name
+            .rsplit('/')
+            .next()
+            .unwrap_or(&server.name)
+            .to_string()
+    });
+    Some(RegistryServer {
+        id: server.name,
+        name: display_name,
+        description: server.description,
+        icon: server.icons.into_iter().next().map(|icon| icon.src),
+        install_method,
+        default_config,
+        repo_url: server
+            .repository
+            .and_then(|repo| repo.url)
+            .filter(|url| !url.is_empty())
+            .or(server.website_url),
+        tags: vec![tag],
+        required_env,
+        env_schema,
+        version: server.version,
+        updated_at: None,
+        downloads: None,
+    })
+}
+
+/// Command-line arguments with a fixed or default value; ones the user must fill in are left out
+fn arguments(arguments: &[Argument]) -> Vec<String> {
+    let mut args = Vec::new();
+    for argument in arguments {
+        let Some(value) = argument.value.clone().or_else(|| argument.default.clone()) else {
+            continue;
+        };
+        if let Some(name) = argument.name.as_ref().filter(|_| argument.kind == "named") {
+            args.push(name.clone());
+        }
+        args.push(value);
+    }
+    args
+}
+
+// ============================================
+// SEARCH
+// ============================================
+
+/// Order of search results
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrySort {
+    /// Best matches of the query first; the registry's own order without one
+    #[default]
+    Relevance,
+    /// Most weekly downloads first
+    Popularity,
+    /// Most recently updated first
+    Recency,
+    Name,
+}
+
+/// Filters and order of a registry search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RegistryQuery {
+    /// Words that must all appear in the name, ID, description or tags
+    pub query: Option<String>,
+    /// Tags an entry must all have
+    pub tags: Vec<String>,
+    /// Install method type, e.g. `npx` or `remote`
+    pub install_method: Option<String>,
+    pub sort: RegistrySort,
+    pub offset: usize,
+    /// Results returned at most (all when unset)
+    pub limit: Option<usize>,
+}
+
+/// How many matching entries carry a tag
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// One page of search results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrySearchResult {
+    /// Matching entries before `offset` and `limit` are applied
+    pub total: usize,
+    pub servers: Vec<RegistryServer>,
+    /// Tags of the matching entries, most common first
+    pub tags: Vec<TagCount>,
+}
+
+impl InstallMethod {
+    /// The `type` the method is serialized with
+    pub fn kind(&self) -> &'static str {
+        match self {
+            InstallMethod::Npx { .. } => "npx",
+            InstallMethod::Uvx { .. } => "uvx",
+            InstallMethod::GitClone { .. } => "git_clone",
+            InstallMethod::Binary { .. } => "binary",
+            InstallMethod::Remote { .. } => "remote",
+        }
+    }
+}
+
+/// Search a listing's entries
+pub fn search(servers: &[RegistryServer], query: &RegistryQuery) -> RegistrySearchResult {
+    let terms: Vec<String> = query
+        .query
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect();
+    let tags: Vec<String> = query.tags.iter().map(|tag| tag.to_lowercase()).collect();
+
+    let mut matches: Vec<(u32, usize, &RegistryServer)> = servers
+        .iter()
+        .enumerate()
+        .filter(|(_, server)| {
+            query
+                .install_method
+                .as_deref()
+                .is_none_or(|kind| server.install_method.kind() == kind)
+        })
+        .filter(|(_, server)| {
+            tags.iter()
+                .all(|tag| server.tags.iter().any(|t| t.to_lowercase() == *tag))
+        })
+        .filter_map(|(position, server)| Some((relevance(server, &terms)?, position, server)))
+        .collect();
+
+    matches.sort_by(|(score_a, position_a, a), (score_b, position_b, b)| {
+        let order = match query.sort {
+            RegistrySort::Relevance => score_b.cmp(score_a),
+            // Entries without downloads or dates go last
+            RegistrySort::Popularity => b.downloads.cmp(&a.downloads),
+            RegistrySort::Recency => b.updated_at.cmp(&a.updated_at),
+            RegistrySort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        };
+        order.then(position_a.cmp(position_b))
+    });
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (_, _, server) in &matches {
+        for tag in &server.tags {
+            *counts.entry(tag.as_str()).or_default() += 1;
+        }
+    }
+    let mut tag_counts: Vec<TagCount> = counts
+        .into_iter()
+        .map(|(tag, count)| TagCount {
+            tag: tag.to_string(),
+            count,
+        })
+        .collect();
+    tag_counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+
+    RegistrySearchResult {
+        total: matches.len(),
+        servers: matches
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(usize::MAX))
+            .map(|(_, _, server)| server.clone())
+            .collect(),
+        tags: tag_counts,
+    }
+}
+
+/// How well an entry matches every term (name over tags over description), `None` when one is missing
+fn relevance(server: &RegistryServer, terms: &[String]) -> Option<u32> {
+    let name = server.name.to_lowercase();
+    let id = server.id.to_lowercase();
+    let description = server.description.to_lowercase();
+    let mut score = 0;
+    for term in terms {
+        score += if name == *term || id.rsplit('/').next() == Some(term) {
+            8
+        } else if name.contains(term.as_str()) || id.contains(term.as_str()) {
+            4
+        } else if server.tags.iter().any(|tag| tag.to_lowercase() == *term) {
+            2
+        } else if description.contains(term.as_str()) {
+            1
+        } else {
+            return None;
+        };
+    }
+    Some(score)
+}
+
+// ============================================
+// RUNTIME CHECKS
+// ============================================
+
+/// Check if a runtime (node, python, etc.) is available
+pub async fn check_runtime_availability(runtime: &str) -> SynapticResult<RuntimeStatus> {
+    let cmd = match runtime {
+        "node" | "npx" => "node",
+        "uvx" | "uv" => "uv",
+        "python" | "python3" => "python",
+        _ => {
+            return Err(SynapticError::RuntimeNotFound(format!(
+                "Unknown runtime: {}",
+                runtime
+            )))
+        }
+    };
+
+    let version_arg = "--version";
+
+    // Find the executable the same way spawning does
+    let path = match crate::resolve::resolve_command(cmd) {
+        Ok(path) => path,
+        Err(_) => {
+            return Ok(RuntimeStatus {
+                runtime: runtime.to_string(),
+                available: false,
+                version: None,
+                path: None,
+            })
+        }
+    };
+
+    // Try to run the command
+    let output = tokio::process::Command::new(&path)
+        .arg(version_arg)
+        .output()
+        .await;
+
+    match output {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .to_string();
+
+            Ok(RuntimeStatus {
+                runtime: runtime.to_string(),
+                available: true,
+                version: Some(version),
+                path: Some(path.to_string_lossy().to_string()),
+            })
+        }
+        _ => Ok(RuntimeStatus {
+            runtime: runtime.to_string(),
+            available: false,
+            version: None,
+            path: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn entry(name: &str, server: Value, status: &str) -> Value {
+        let mut server = server;
+        server["name"] = json!(name);
+        json!({
+            "server": server,
+            "_meta": {OFFICIAL_META: {
+                "status": status,
+                "isLatest": true,
+                "updatedAt": "2025-09-01T12:00:00Z"
+            }}
+        })
+    }
+
+    #[test]
+    fn test_normalizes_packages_and_remotes() {
+        let npm = entry(
+            "io.github.acme/weather",
+            json!({
+                "title": "Weather",
+                "description": "Forecasts",
+                "version": "1.2.0",
+                "repository": {"url": "https://github.com/acme/weather", "source": "github"},
+                "packages": [{
+                    "registryType": "npm",
+                    "identifier": "@acme/weather-mcp",
+                    "transport": {"type": "stdio"},
+                    "packageArguments": [
+                        {"type": "named", "name": "--units", "default": "metric"},
+                        {"type": "positional", "valueHint": "city"}
+                    ],
+                    "environmentVariables": [
+                        {"name": "WEATHER_API_KEY", "isRequired": true, "isSecret": true},
+                        {"name": "WEATHER_DEBUG"}
+                    ]
+                }]
+            }),
+            "active",
+        );
+        let server = normalize(&npm).unwrap().server.unwrap();
+        assert_eq!(server.id, "io.github.acme/weather");
+        assert_eq!(server.name, "Weather");
+        assert_eq!(server.version.as_deref(), Some("1.2.0"));
+        assert_eq!(
+            server.updated_at.unwrap().to_rfc3339(),
+            "2025-09-01T12:00:00+00:00"
+        );
+        assert!(
+            matches!(server.install_method, InstallMethod::Npx { ref package } if package == "@acme/weather-mcp")
+        );
+        assert_eq!(server.default_config.command, "npx");
+        assert_eq!(
+            server.default_config.args,
+            vec!["-y", "@acme/weather-mcp", "--units", "metric"]
+        );
+        assert_eq!(server.required_env, vec!["WEATHER_API_KEY"]);
+        assert_eq!(server.env_schema.len(), 2);
+        assert!(server.env_schema[0].required && server.env_schema[0].secret);
+        assert_eq!(server.default_config.env.len(), 1);
+        assert_eq!(
+            server.repo_url.as_deref(),
+            Some("https://github.com/acme/weather")
+        );
+
+        let remote = entry(
+            "com.example/search",
+            json!({
+                "description": "Hosted search",
+                "remotes": [{
+                    "type": "streamable-http",
+                    "url": "https://mcp.example.com/mcp",
+                    "headers": [{"name": "Authorization", "isRequired": true}]
+                }]
+            }),
+            "active",
+        );
+        let server = normalize(&remote).unwrap().server.unwrap();
+        assert_eq!(server.name, "search");
+        assert!(server.default_config.is_remote());
+        assert_eq!(server.default_config.transport, Transport::Http);
+        assert!(server.default_config.headers.contains_key("Authorization"));
+
+        // Deleted entries and ones Synaptic can't run are removed from the listing
+        let deleted = entry("gone", json!({"remotes": []}), "deleted");
+        assert!(normalize(&deleted).unwrap().server.is_none());
+        let docker = entry(
+            "oci-only",
+            json!({"packages": [{"registryType": "oci", "identifier": "acme/mcp"}]}),
+            "active",
+        );
+        assert!(normalize(&docker).unwrap().server.is_none());
+
+        // Older versions of a server are skipped
+        let mut old = entry("io.github.acme/weather", json!({}), "active");
+        old["_meta"][OFFICIAL_META]["isLatest"] = json!(false);
+        assert!(normalize(&old).is_none());
+    }
+
+    #[test]
+    fn test_missing_env_lists_empty_and_invalid_values() {
+        let github = get_registry_server("github").unwrap();
+        let missing = github.missing_env(&HashMap::new()).unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].spec.name, "GITHUB_PERSONAL_ACCESS_TOKEN");
+        assert!(missing[0].spec.secret);
+        assert_eq!(missing[0].error, None);
+
+        let token = |value: &str| {
+            HashMap::from([(
+                "GITHUB_PERSONAL_ACCESS_TOKEN".to_string(),
+                value.to_string(),
+            )])
+        };
+        let invalid = github.missing_env(&token("  password ")).unwrap();
+        assert!(invalid[0].error.is_some());
+        assert!(github.missing_env(&token("ghp_abc123")).unwrap().is_empty());
+        assert_eq!(
+            with_env(github.default_config.clone(), &token("ghp_abc123")).env
+                ["GITHUB_PERSONAL_ACCESS_TOKEN"],
+            "ghp_abc123"
+        );
+
+        // Entries cached before they had a schema still require their env vars
+        let cached = RegistryServer {
+            env_schema: Vec::new(),
+            ..get_registry_server("brave-search").unwrap()
+        };
+        assert_eq!(
+            cached.missing_env(&HashMap::new()).unwrap()[0].spec,
+            EnvVarSpec::required("BRAVE_API_KEY")
+        );
+        assert!(get_registry_server("memory")
+            .unwrap()
+            .missing_env(&HashMap::new())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_pinned_versions_replace_the_package_arg() {
+        let github = get_registry_server("github").unwrap();
+        let pinned = github.config_for_version(Some("2025.4.8")).unwrap();
+        assert_eq!(
+            pinned.args,
+            vec!["-y", "@modelcontextprotocol/server-github@2025.4.8"]
+        );
+        assert!(github.matches(&pinned));
+        assert_eq!(
+            github.config_for_version(None).unwrap().args,
+            github.default_config.args
+        );
+        assert!(github.config_for_version(Some("1 2")).is_err());
+
+        let sqlite = get_registry_server("sqlite").unwrap();
+        let pinned = sqlite.config_for_version(Some("0.6.2")).unwrap();
+        assert_eq!(pinned.args[0], "mcp-server-sqlite@0.6.2");
+    }
+
+    #[test]
+    fn test_search_filters_and_sorts() {
+        let mut servers = get_builtin_registry();
+        servers[3].downloads = Some(900);
+        servers[2].downloads = Some(5_000);
+
+        let found = search(
+            &servers,
+            &RegistryQuery {
+                query: Some("git".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(found.servers[0].id, "github");
+
+        // Every word must match somewhere
+        let none = search(
+            &servers,
+            &RegistryQuery {
+                query: Some("github sql".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(none.total, 0);
+
+        let filtered = search(
+            &servers,
+            &RegistryQuery {
+                tags: vec!["Official".to_string(), "database".to_string()],
+                ..Default::default()
+            },
+        );
+        assert_eq!(filtered.total, 1);
+        assert_eq!(filtered.servers[0].id, "sqlite");
+        let uvx = search(
+            &servers,
+            &RegistryQuery {
+                install_method: Some("uvx".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(uvx.servers[0].id, "sqlite");
+
+        let popular = search(
+            &servers,
+            &RegistryQuery {
+                sort: RegistrySort::Popularity,
+                offset: 1,
+                limit: Some(2),
+                ..Default::default()
+            },
+        );
+        assert_eq!(popular.total, 5);
+        let ids: Vec<_> = popular.servers.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["memory", "filesystem"]);
+        assert_eq!(
+            popular.tags[0],
+            TagCount {
+                tag: "official".to_string(),
+                count: 5
+            }
+        );
+    }
+
+    /// Serves the given pages in order (`None` as `304 Not Modified`), recording each request's head
+    async fn fake_registry(
+        pages: Vec<(Option<Value>, Option<&'static str>)>,
+    ) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            for (page, etag) in pages {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = vec![0; 8192];
+                let n = socket.read(&mut request).await.unwrap();
+                let head = String::from_utf8_lossy(&request[..n]).to_lowercase();
+                seen.lock().unwrap().push(head);
+                let etag = etag
+                    .map(|etag| format!("ETag: {}\r\n", etag))
+                    .unwrap_or_default();
+                let response = match page {
+                    Some(page) => {
+                        let body = page.to_string();
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            etag,
+                            body.len(),
+                            body
+                        )
+                    }
+                    None => "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string(),
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn npm(name: &str, version: &str) -> Value {
+        entry(
+            name,
+            json!({
+                "version": version,
+                "packages": [{"registryType": "npm", "identifier": format!("@acme/{}", name)}]
+            }),
+            "active",
+        )
+    }
+
+    #[tokio::test]
+    async fn test_syncs_pages_then_only_updates() {
+        let (url, requests) = fake_registry(vec![
+            (
+                Some(json!({"servers": [npm("a", "1.0.0")], "metadata": {"nextCursor": "page-2"}})),
+                Some("\"full\""),
+            ),
+            (Some(json!({"servers": [npm("b", "1.0.0")], "metadata": {}})), None),
+            (
+                Some(json!({
+                    "servers": [npm("a", "1.1.0"), entry("b", json!({}), "deleted"), npm("c", "0.1.0")],
+                    "metadata": {}
+                })),
+                Some("\"delta\""),
+            ),
+        ])
+        .await;
+
+        let first = sync_registry(&url, None).await.unwrap();
+        let ids: Vec<_> = first.servers.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+        assert_eq!(first.source, RegistrySource::Remote);
+        assert_eq!(first.etag.as_deref(), Some("\"full\""));
+
+        let second = sync_registry(&url, Some(&first)).await.unwrap();
+        let listed: Vec<_> = second
+            .servers
+            .iter()
+            .map(|s| (s.id.as_str(), s.version.as_deref().unwrap()))
+            .collect();
+        assert_eq!(listed, vec![("a", "1.1.0"), ("c", "0.1.0")]);
+        // Only the delta came back, so it isn't what a full listing would match
+        assert_eq!(second.etag.as_deref(), Some("\"full\""));
+
+        let requests = requests.lock().unwrap();
+        assert!(!requests[0].contains("cursor"));
+        assert!(requests[1].contains("cursor=page-2"));
+        assert!(requests[2].contains("updated_since="));
+    }
+
+    #[test]
+    fn test_required_env_comes_from_the_synced_listing() {
+        let weather = json!({
+            "packages": [{
+                "registryType": "npm",
+                "identifier": "@acme/weather-mcp",
+                "environmentVariables": [{"name": "WEATHER_API_KEY", "isRequired": true}]
+            }]
+        });
+        let listed = normalize(&entry("io.github.acme/weather", weather, "active"))
+            .unwrap()
+            .server
+            .unwrap();
+        let server = listed.default_config.clone();
+        let state = AppState::with_database(Database::open_in_memory().unwrap());
+        *state.settings_cache.lock().unwrap() = Some(crate::settings::SynapticSettings {
+            registry_url: "https://registry.example.com".to_string(),
+            ..Default::default()
+        });
+        assert!(required_env(Some(&state), &server).is_empty());
+
+        *state.registry_cache.lock().unwrap() = Some(RegistrySnapshot {
+            source: RegistrySource::Remote,
+            registry_url: "https://registry.example.com".to_string(),
+            synced_at: Some(Utc::now()),
+            etag: None,
+            stale: false,
+            servers: vec![listed],
+        });
+        assert_eq!(required_env(Some(&state), &server), vec!["WEATHER_API_KEY"]);
+        assert!(required_env(None, &server).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_listing_past_the_page_limit_fails() {
+        let pages = (0..MAX_REGISTRY_PAGES)
+            .map(|page| {
+                let next = format!("page-{}", page + 1);
+                (
+                    Some(
+                        json!({"servers": [npm(&next, "1.0.0")], "metadata": {"nextCursor": next}}),
+                    ),
+                    None,
+                )
+            })
+            .collect();
+        let (url, requests) = fake_registry(pages).await;
+
+        assert!(sync_registry(&url, None).await.is_err());
+        assert_eq!(requests.lock().unwrap().len(), MAX_REGISTRY_PAGES);
+    }
+
+    #[tokio::test]
+    async fn test_cached_listing_is_served_within_ttl_and_offline() {
+        let (url, requests) = fake_registry(vec![
+            (
+                Some(json!({"servers": [npm("a", "1.0.0")], "metadata": {}})),
+                Some("\"v1\""),
+            ),
+            (None, None),
+        ])
+        .await;
+        let state = AppState::with_database(Database::open_in_memory().unwrap());
+        let settings = crate::settings::SynapticSettings {
+            registry_url: url,
+            ..Default::default()
+        };
+        *state.settings_cache.lock().unwrap() = Some(settings.clone());
+
+        let first = refresh(&state, false).await;
+        assert_eq!(first.etag.as_deref(), Some("\"v1\""));
+        // Fresh listings aren't synced again
+        refresh(&state, false).await;
+        assert_eq!(requests.lock().unwrap().len(), 1);
+
+        // Past the TTL the registry is asked whether anything changed
+        let expired = first.synced_at.unwrap() - chrono::Duration::hours(2);
+        state
+            .registry_cache
+            .lock()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .synced_at = Some(expired);
+        let second = refresh(&state, false).await;
+        assert!(requests.lock().unwrap()[1].contains("if-none-match: \"v1\""));
+        assert_eq!(second.servers.len(), 1);
+        assert!(second.synced_at.unwrap() > expired);
+
+        // Offline, the listing comes from the database without a request
+        *state.registry_cache.lock().unwrap() = None;
+        *state.settings_cache.lock().unwrap() = Some(crate::settings::SynapticSettings {
+            registry_offline: true,
+            ..settings
+        });
+        let offline = refresh(&state, true).await;
+        assert_eq!(offline.source, RegistrySource::Remote);
+        assert_eq!(offline.servers[0].id, "a");
+        assert!(find_server(&state, "a").is_some());
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_registry_falls_back_to_builtin_list() {
+        let state = AppState::new();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        *state.settings_cache.lock().unwrap() = Some(crate::settings::SynapticSettings {
+            registry_url: url,
+            ..Default::default()
+        });
+
+        let snapshot = refresh(&state, false).await;
+        assert_eq!(snapshot.source, RegistrySource::Builtin);
+        assert!(find_server(&state, "filesystem").is_some());
+    }
+}
@@ -0,0 +1,283 @@
+//! Saved request library
+//!
+//! A template is a named JSON-RPC payload, e.g. a `tools/call` with specific
+//! arguments, stored in the `request_templates` table. String values may
+//! contain `{{variable}}` placeholders that are filled in when the template
+//! is sent. A string that is nothing but one placeholder is replaced by the
+//! variable's JSON value, so numbers, booleans and objects keep their type.
+
+use crate::database::Database;
+use crate::error::{SynapticError, SynapticResult};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+/// A named request payload with `{{variable}}` placeholders
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestTemplate {
+    pub name: String,
+    /// Server the template is sent to unless another one is given
+    pub server_name: Option<String>,
+    pub description: Option<String>,
+    /// JSON-RPC request; an `id` is assigned on every send
+    pub payload: Value,
+    /// Placeholders used in `payload`, filled in on save
+    #[serde(default)]
+    pub variables: Vec<String>,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// ============================================
+// PLACEHOLDERS
+// ============================================
+
+/// `{{ variable }}` placeholders in a string, as (whole placeholder, variable name)
+fn placeholders(text: &str) -> Vec<(&str, &str)> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}").map(|end| end + 2) else {
+            break;
+        };
+        let placeholder = &rest[start..start + len];
+        found.push((placeholder, placeholder[2..len - 2].trim()));
+        rest = &rest[start + len..];
+    }
+    found
+}
+
+/// Every placeholder used in a payload, sorted
+pub fn variables(payload: &Value) -> Vec<String> {
+    fn collect(value: &Value, names: &mut BTreeSet<String>) {
+        match value {
+            Value::String(text) => names.extend(
+                placeholders(text)
+                    .into_iter()
+                    .map(|(_, name)| name.to_string()),
+            ),
+            Value::Array(items) => items.iter().for_each(|item| collect(item, names)),
+            Value::Object(map) => map.values().for_each(|item| collect(item, names)),
+            _ => {}
+        }
+    }
+    let mut names = BTreeSet::new();
+    collect(payload, &mut names);
+    names.into_iter().collect()
+}
+
+/// Fill in a payload's placeholders
+///
+/// Fails naming every variable that has no value.
+pub fn render(payload: &Value, vars: &HashMap<String, Value>) -> SynapticResult<Value> {
+    let missing: Vec<String> = variables(payload)
+        .into_iter()
+        .filter(|name| !vars.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(SynapticError::InspectorError(format!(
+            "Missing template variables: {}",
+            missing.join(", ")
+        )));
+    }
+    Ok(substitute(payload, vars))
+}
+
+/// Payload to send for a template, with a fresh id assigned by the sender
+pub fn build_request(
+    template: &RequestTemplate,
+    vars: &HashMap<String, Value>,
+) -> SynapticResult<Value> {
+    let mut payload = render(&template.payload, vars)?;
+    if let Some(message) = payload.as_object_mut() {
+        message.remove("id");
+    }
+    Ok(payload)
+}
+
+fn substitute(value: &Value, vars: &HashMap<String, Value>) -> Value {
+    match value {
+        Value::String(text) => {
+            let found = placeholders(text);
+            if let [(placeholder, name)] = found.as_slice() {
+                if text.trim() == *placeholder {
+                    return vars[*name].clone();
+                }
+            }
+            let mut rendered = text.clone();
+            for (placeholder, name) in found {
+                let replacement = match &vars[name] {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                rendered = rendered.replace(placeholder, &replacement);
+            }
+            Value::String(rendered)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|i| substitute(i, vars)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| (key.clone(), substitute(item, vars)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+// ============================================
+// PERSISTENCE
+// ============================================
+
+/// Create or replace a template; `created_at` of an existing one is kept
+pub fn save(db: &Database, template: &RequestTemplate) -> SynapticResult<RequestTemplate> {
+    if template.name.trim().is_empty() {
+        return Err(SynapticError::InspectorError(
+            "Template name must not be empty".to_string(),
+        ));
+    }
+    if template
+        .payload
+        .get("method")
+        .and_then(|m| m.as_str())
+        .is_none()
+    {
+        return Err(SynapticError::InspectorError(
+            "Template payload needs a method".to_string(),
+        ));
+    }
+
+    // Stored with millisecond precision
+    let now = Utc
+        .timestamp_millis_opt(Utc::now().timestamp_millis())
+        .single()
+        .unwrap_or_default();
+    let created_at = get(db, &template.name)?.map_or(now, |existing| existing.created_at);
+    let saved = RequestTemplate {
+        variables: variables(&template.payload),
+        created_at,
+        updated_at: now,
+        ..template.clone()
+    };
+    db.conn().execute(
+        "INSERT OR REPLACE INTO request_templates
+            (name, server_name, description, payload, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            saved.name,
+            saved.server_name,
+            saved.description,
+            saved.payload,
+            saved.created_at.timestamp_millis(),
+            saved.updated_at.timestamp_millis(),
+        ],
+    )?;
+    Ok(saved)
+}
+
+/// All templates by name
+pub fn list(db: &Database) -> SynapticResult<Vec<RequestTemplate>> {
+    select(db, None)
+}
+
+pub fn get(db: &Database, name: &str) -> SynapticResult<Option<RequestTemplate>> {
+    Ok(select(db, Some(name))?.pop())
+}
+
+/// Remove a template; returns whether it existed
+pub fn delete(db: &Database, name: &str) -> SynapticResult<bool> {
+    let removed = db.conn().execute(
+        "DELETE FROM request_templates WHERE name = ?1",
+        params![name],
+    )?;
+    Ok(removed > 0)
+}
+
+fn select(db: &Database, name: Option<&str>) -> SynapticResult<Vec<RequestTemplate>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT name, server_name, description, payload, created_at, updated_at
+         FROM request_templates
+         WHERE (?1 IS NULL OR name = ?1)
+         ORDER BY name",
+    )?;
+    let millis = |millis: i64| {
+        Utc.timestamp_millis_opt(millis)
+            .single()
+            .unwrap_or_default()
+    };
+    let rows = stmt.query_map(params![name], |row| {
+        let payload: Value = row.get(3)?;
+        Ok(RequestTemplate {
+            name: row.get(0)?,
+            server_name: row.get(1)?,
+            description: row.get(2)?,
+            variables: variables(&payload),
+            payload,
+            created_at: millis(row.get(4)?),
+            updated_at: millis(row.get(5)?),
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_templates_are_saved_and_rendered() {
+        let db = Database::open_in_memory().unwrap();
+        let template = RequestTemplate {
+            name: "read".to_string(),
+            server_name: Some("fs".to_string()),
+            description: None,
+            payload: json!({
+                "jsonrpc": "2.0",
+                "method": "tools/call",
+                "params": {
+                    "name": "read_file",
+                    "arguments": { "path": "{{dir}}/{{file}}", "limit": "{{ limit }}" }
+                }
+            }),
+            variables: Vec::new(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let saved = save(&db, &template).unwrap();
+        assert_eq!(saved.variables, vec!["dir", "file", "limit"]);
+        let resaved = save(&db, &template).unwrap();
+        assert_eq!(resaved.created_at, saved.created_at);
+        assert_eq!(list(&db).unwrap().len(), 1);
+
+        let vars: HashMap<String, Value> = [
+            ("dir".to_string(), json!("/tmp")),
+            ("file".to_string(), json!("a.txt")),
+            ("limit".to_string(), json!(10)),
+        ]
+        .into();
+        let payload = get(&db, "read").unwrap().unwrap().payload;
+        let rendered = render(&payload, &vars).unwrap();
+        assert_eq!(rendered["params"]["arguments"]["path"], "/tmp/a.txt");
+        assert_eq!(rendered["params"]["arguments"]["limit"], 10);
+
+        let err = render(&payload, &HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("dir, file, limit"));
+
+        assert!(save(
+            &db,
+            &RequestTemplate {
+                payload: json!({}),
+                ..template
+            }
+        )
+        .is_err());
+        assert!(delete(&db, "read").unwrap());
+        assert!(get(&db, "read").unwrap().is_none());
+    }
+}
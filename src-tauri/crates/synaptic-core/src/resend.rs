@@ -0,0 +1,249 @@
+//! Resending captured requests from the traffic log
+//!
+//! A logged request can be sent again as is or after JSON-patch style edits
+//! (`add`, `replace`, `remove` at a JSON pointer). The copy gets a fresh id
+//! of the form `resend-<row>-<suffix>`, so it stays recognisable in the log,
+//! and goes through the correlated-send path; the result carries both
+//! exchanges for comparison. Logged payloads have secrets redacted, so a
+//! resend carries the redacted values unless edits put them back.
+
+use crate::database::Database;
+use crate::error::{SynapticError, SynapticResult};
+use crate::process_manager::ProcessManager;
+use crate::system_logs::{self, entry_from_row, LogEntry, ENTRY_COLUMNS};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Kind of a [`PatchOp`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchKind {
+    Add,
+    Replace,
+    Remove,
+}
+
+/// One edit of a resent payload
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PatchOp {
+    pub op: PatchKind,
+    /// JSON pointer, e.g. `/params/arguments/path`
+    pub path: String,
+    #[serde(default)]
+    pub value: Value,
+}
+
+/// A resent request next to the one it was copied from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResendResult {
+    /// Row id of the original request in `system_logs`
+    pub original_id: i64,
+    pub server_name: String,
+    pub original_request: Option<Value>,
+    /// Logged response to the original, if one arrived
+    pub original_response: Option<Value>,
+    pub request: Value,
+    pub response: Value,
+}
+
+fn patch_error(path: &str, reason: &str) -> SynapticError {
+    SynapticError::InspectorError(format!("Cannot apply edit at {}: {}", path, reason))
+}
+
+/// Apply edits in order
+pub fn apply_patch(target: &mut Value, edits: &[PatchOp]) -> SynapticResult<()> {
+    for edit in edits {
+        let (parent_path, key) = match edit.path.rfind('/') {
+            Some(slash) => (&edit.path[..slash], &edit.path[slash + 1..]),
+            None => return Err(patch_error(&edit.path, "not a JSON pointer")),
+        };
+        let key = key.replace("~1", "/").replace("~0", "~");
+        let parent = target
+            .pointer_mut(parent_path)
+            .ok_or_else(|| patch_error(&edit.path, "parent does not exist"))?;
+
+        match (parent, edit.op) {
+            (Value::Object(map), PatchKind::Add) => {
+                map.insert(key, edit.value.clone());
+            }
+            (Value::Object(map), PatchKind::Replace) => match map.get_mut(&key) {
+                Some(value) => *value = edit.value.clone(),
+                None => return Err(patch_error(&edit.path, "nothing to replace")),
+            },
+            (Value::Object(map), PatchKind::Remove) => {
+                map.remove(&key)
+                    .ok_or_else(|| patch_error(&edit.path, "nothing to remove"))?;
+            }
+            (Value::Array(items), op) => {
+                let index = if key == "-" && op == PatchKind::Add {
+                    items.len()
+                } else {
+                    key.parse::<usize>()
+                        .map_err(|_| patch_error(&edit.path, "not an array index"))?
+                };
+                let in_bounds = match op {
+                    PatchKind::Add => index <= items.len(),
+                    _ => index < items.len(),
+                };
+                if !in_bounds {
+                    return Err(patch_error(&edit.path, "index out of bounds"));
+                }
+                match op {
+                    PatchKind::Add => items.insert(index, edit.value.clone()),
+                    PatchKind::Replace => items[index] = edit.value.clone(),
+                    PatchKind::Remove => {
+                        items.remove(index);
+                    }
+                }
+            }
+            _ => return Err(patch_error(&edit.path, "parent is not an object or array")),
+        }
+    }
+    Ok(())
+}
+
+/// A logged request and the logged response to it
+pub fn original_exchange(
+    db: &Database,
+    message_id: i64,
+) -> SynapticResult<(LogEntry, Option<LogEntry>)> {
+    let request = system_logs::get_entry(db, message_id)?.ok_or_else(|| {
+        SynapticError::InspectorError(format!("No logged message {}", message_id))
+    })?;
+    if request.direction.as_deref() != Some("OUTGOING") || request.message.is_none() {
+        return Err(SynapticError::InspectorError(format!(
+            "Message {} is not a request sent to a server",
+            message_id
+        )));
+    }
+
+    let conn = db.conn();
+    let response = match &request.trace_id {
+        Some(trace_id) => conn
+            .query_row(
+                &format!(
+                    "SELECT {ENTRY_COLUMNS} FROM system_logs l
+                     WHERE l.session_id = ?1 AND l.trace_id = ?2 AND l.id > ?3
+                       AND l.direction = 'INCOMING' AND l.message IS NULL
+                     ORDER BY l.id
+                     LIMIT 1"
+                ),
+                params![request.session_id, trace_id, request.id],
+                entry_from_row,
+            )
+            .optional()?,
+        None => None,
+    };
+    Ok((request, response))
+}
+
+/// Copy of a logged request with edits applied and a fresh id
+pub fn prepare(request: &LogEntry, edits: &[PatchOp]) -> SynapticResult<Value> {
+    let mut payload = request.payload.clone().ok_or_else(|| {
+        SynapticError::InspectorError(format!("Message {} has no payload", request.id))
+    })?;
+    apply_patch(&mut payload, edits)?;
+
+    let Some(message) = payload.as_object_mut() else {
+        return Err(SynapticError::InspectorError(format!(
+            "Message {} is not a JSON object",
+            request.id
+        )));
+    };
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    message.insert(
+        "id".to_string(),
+        Value::String(format!("resend-{}-{}", request.id, &suffix[..8])),
+    );
+    Ok(payload)
+}
+
+/// Send an edited copy of a logged request and wait for its response
+pub async fn resend(
+    pm: &ProcessManager,
+    original: (LogEntry, Option<LogEntry>),
+    edits: &[PatchOp],
+    timeout: Duration,
+) -> SynapticResult<ResendResult> {
+    let (request, response) = original;
+    let server_name = request.server_name.clone().ok_or_else(|| {
+        SynapticError::InspectorError(format!("Message {} has no server", request.id))
+    })?;
+    let payload = prepare(&request, edits)?;
+    let answer = pm
+        .send_request(&server_name, payload.clone(), timeout)
+        .await?;
+
+    Ok(ResendResult {
+        original_id: request.id,
+        server_name,
+        original_request: request.payload,
+        original_response: response.and_then(|r| r.payload),
+        request: payload,
+        response: answer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_manager::McpTrafficEvent;
+    use serde_json::json;
+
+    #[test]
+    fn test_logged_request_is_prepared_for_resend() {
+        let db = Database::open_in_memory().unwrap();
+        let event = |direction: &str, content: &str| McpTrafficEvent {
+            server_id: "fs".to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            direction: direction.to_string(),
+            content: content.to_string(),
+            message_id: uuid::Uuid::new_v4().to_string(),
+            level: None,
+        };
+        system_logs::insert_traffic(
+            &db,
+            "s1",
+            &[
+                event(
+                    "OUTGOING",
+                    r#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"read","arguments":{"path":"/a","tags":["x"]}}}"#,
+                ),
+                event("INCOMING", r#"{"jsonrpc":"2.0","id":7,"result":{"content":[]}}"#),
+            ],
+        )
+        .unwrap();
+        let rows = system_logs::session_entries(&db, "s1").unwrap();
+
+        let (request, response) = original_exchange(&db, rows[0].id).unwrap();
+        assert_eq!(response.unwrap().id, rows[1].id);
+        assert!(original_exchange(&db, rows[1].id).is_err());
+
+        let edits: Vec<PatchOp> = serde_json::from_value(json!([
+            { "op": "replace", "path": "/params/arguments/path", "value": "/b" },
+            { "op": "add", "path": "/params/arguments/tags/-", "value": "y" },
+            { "op": "remove", "path": "/params/name" },
+        ]))
+        .unwrap();
+        let payload = prepare(&request, &edits).unwrap();
+        assert_eq!(
+            payload["params"]["arguments"],
+            json!({ "path": "/b", "tags": ["x", "y"] })
+        );
+        assert!(payload["params"].get("name").is_none());
+        assert!(payload["id"]
+            .as_str()
+            .unwrap()
+            .starts_with(&format!("resend-{}-", rows[0].id)));
+
+        let missing = [PatchOp {
+            op: PatchKind::Replace,
+            path: "/params/missing".to_string(),
+            value: Value::Null,
+        }];
+        assert!(prepare(&request, &missing).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+//! Executable resolution before spawning
+//!
+//! Desktop apps often start with a minimal `PATH` (no shell profile), so
+//! runtimes installed through nvm, volta, asdf or per-user npm are invisible.
+//! Commands are looked up on `PATH` first and then in the runtimes Synaptic
+//! installed and those well-known install locations, and a miss lists every
+//! place that was searched.
+
+use crate::config::McpServer;
+use crate::error::{SynapticError, SynapticResult};
+use crate::paths;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// A helper binary shipped next to the running executable (`synaptic-shim`, ...)
+pub fn bundled_binary(name: &str) -> SynapticResult<PathBuf> {
+    let exe = std::env::current_exe()?;
+    let binary = exe.with_file_name(format!("{}{}", name, std::env::consts::EXE_SUFFIX));
+    if !binary.is_file() {
+        return Err(SynapticError::RuntimeNotFound(format!(
+            "{} not found next to {}",
+            name,
+            exe.display()
+        )));
+    }
+    Ok(binary)
+}
+
+/// Resolve a command to an absolute executable path
+pub fn resolve_command(command: &str) -> SynapticResult<PathBuf> {
+    // Explicit paths are used as-is
+    if command.contains('/') || command.contains('\\') {
+        let path = PathBuf::from(command);
+        return if path.is_file() {
+            Ok(path)
+        } else {
+            Err(SynapticError::RuntimeNotFound(format!(
+                "{} not found",
+                command
+            )))
+        };
+    }
+
+    if let Ok(path) = which::which(command) {
+        return Ok(path);
+    }
+
+    let fallback = fallback_dirs();
+    if let Some(path) = find_in(command, &fallback) {
+        return Ok(path);
+    }
+
+    let mut searched = vec!["PATH".to_string()];
+    searched.extend(fallback.iter().map(|d| d.display().to_string()));
+    Err(SynapticError::RuntimeNotFound(format!(
+        "{} not found, searched: {}",
+        command,
+        searched.join(", ")
+    )))
+}
+
+/// Resolve a server's command the way its process will see it
+///
+/// A relative path is taken from the server's `cwd`; a bare name is looked up
+/// in its `pathPrepend` dirs and the `PATH` set in its env before anywhere
+/// `resolve_command` looks.
+pub fn resolve_server_command(server: &McpServer) -> SynapticResult<PathBuf> {
+    let command = &server.command;
+    let explicit = command.contains('/') || command.contains('\\');
+    if explicit {
+        let path = match &server.cwd {
+            Some(cwd) if Path::new(command).is_relative() => {
+                PathBuf::from(paths::expand_tilde(cwd)).join(command)
+            }
+            _ => PathBuf::from(command),
+        };
+        return resolve_command(&path.to_string_lossy());
+    }
+
+    let mut dirs = server.path_prepend_dirs();
+    if let Some(path) = server.env.get("PATH") {
+        dirs.extend(std::env::split_paths(path));
+    }
+    resolve_command_in(command, &dirs)
+}
+
+/// Resolve a command, looking in `preferred` directories before anything else
+pub fn resolve_command_in(command: &str, preferred: &[PathBuf]) -> SynapticResult<PathBuf> {
+    let explicit = command.contains('/') || command.contains('\\');
+    if !explicit {
+        if let Some(path) = find_in(command, preferred) {
+            return Ok(path);
+        }
+    }
+    resolve_command(command)
+}
+
+/// Look a command up in the given directories only
+pub fn find_in(command: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    let paths = std::env::join_paths(dirs.iter().filter(|d| d.is_dir())).ok()?;
+    which::which_in_global(command, Some(paths))
+        .ok()
+        .and_then(|mut found| found.next())
+}
+
+/// `PATH` for the child with the executable's own directory in front
+///
+/// Launcher scripts like `npx` use `#!/usr/bin/env node`, which fails when
+/// node was only found through a fallback location.
+pub fn path_with_dir(executable: &Path, current: Option<OsString>) -> Option<OsString> {
+    let dir = executable.parent()?.to_path_buf();
+    let mut dirs: Vec<PathBuf> = current
+        .as_ref()
+        .map(|p| std::env::split_paths(p).collect())
+        .unwrap_or_default();
+
+    if dirs.contains(&dir) {
+        return None;
+    }
+    dirs.insert(0, dir);
+    std::env::join_paths(dirs).ok()
+}
+
+/// `PATH` with `dirs` in front, in order (moved there if already present)
+pub fn path_with_dirs(dirs: &[PathBuf], current: Option<OsString>) -> Option<OsString> {
+    if dirs.is_empty() {
+        return None;
+    }
+    let rest = current
+        .as_ref()
+        .map(|p| std::env::split_paths(p).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|dir| !dirs.contains(dir));
+    std::env::join_paths(dirs.iter().cloned().chain(rest)).ok()
+}
+
+/// Well-known per-user runtime install locations, most specific first
+pub fn fallback_dirs() -> Vec<PathBuf> {
+    // Runtimes Synaptic installed itself
+    let mut dirs = crate::runtimes::bin_dirs();
+    let home = dirs::home_dir();
+
+    if let Some(home) = &home {
+        // nvm: newest installed node version
+        if let Ok(entries) = std::fs::read_dir(home.join(".nvm/versions/node")) {
+            let mut versions: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+            versions.sort_by_key(|p| version_key(p));
+            if let Some(latest) = versions.pop() {
+                dirs.push(latest.join("bin"));
+            }
+        }
+
+        dirs.push(home.join(".volta/bin"));
+        dirs.push(home.join(".asdf/shims"));
+        dirs.push(home.join(".local/share/mise/shims"));
+        dirs.push(home.join(".bun/bin"));
+        dirs.push(home.join(".deno/bin"));
+        dirs.push(home.join(".cargo/bin"));
+        dirs.push(home.join(".local/bin"));
+    }
+
+    #[cfg(windows)]
+    {
+        if let Some(app_data) = std::env::var_os("APPDATA") {
+            dirs.push(PathBuf::from(app_data).join("npm"));
+        }
+        if let Some(program_files) = std::env::var_os("ProgramFiles") {
+            dirs.push(PathBuf::from(program_files).join("nodejs"));
+            dirs.push(PathBuf::from(program_files).join("Docker/Docker/resources/bin"));
+        }
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            dirs.push(PathBuf::from(local).join("Programs/Python/Launcher"));
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        dirs.push(PathBuf::from("/opt/homebrew/bin"));
+        dirs.push(PathBuf::from("/usr/local/bin"));
+    }
+
+    dirs
+}
+
+/// Sort key for nvm version directories like `v20.11.1`
+fn version_key(path: &Path) -> Vec<u64> {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_key_orders_numerically() {
+        let mut versions = [
+            PathBuf::from("v9.11.2"),
+            PathBuf::from("v20.11.1"),
+            PathBuf::from("v18.19.0"),
+        ];
+        versions.sort_by_key(|p| version_key(p));
+        assert_eq!(versions.last().unwrap(), &PathBuf::from("v20.11.1"));
+    }
+
+    #[test]
+    fn test_missing_command_lists_searched_locations() {
+        let err = resolve_command("definitely-not-a-real-runtime-xyz").unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("not found, searched: PATH"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_server_commands_use_their_cwd_and_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("synaptic-resolve-{}", uuid::Uuid::new_v4()));
+        let tools = dir.join("tools");
+        std::fs::create_dir_all(&tools).unwrap();
+        let script = tools.join("synaptic-test-server");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let relative = McpServer {
+            command: "./tools/synaptic-test-server".to_string(),
+            cwd: Some(dir.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_server_command(&relative).unwrap(),
+            dir.join("./tools/synaptic-test-server")
+        );
+
+        let on_path = McpServer {
+            command: "synaptic-test-server".to_string(),
+            env: [("PATH".to_string(), tools.to_string_lossy().into_owned())].into(),
+            ..Default::default()
+        };
+        assert_eq!(resolve_server_command(&on_path).unwrap(), script);
+        assert!(resolve_command("synaptic-test-server").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_path_with_dir_prepends_once() {
+        let exe = PathBuf::from("/opt/node/bin/node");
+        let current = std::env::join_paths(["/usr/bin"]).ok();
+
+        let path = path_with_dir(&exe, current).unwrap();
+        let dirs: Vec<PathBuf> = std::env::split_paths(&path).collect();
+        assert_eq!(dirs[0], PathBuf::from("/opt/node/bin"));
+
+        assert!(path_with_dir(&exe, Some(path)).is_none());
+    }
+
+    #[test]
+    fn test_path_with_dirs_moves_dirs_to_front() {
+        let venv = PathBuf::from("/srv/app/.venv/bin");
+        let current = std::env::join_paths(["/usr/bin", "/srv/app/.venv/bin"]).ok();
+
+        let path = path_with_dirs(std::slice::from_ref(&venv), current).unwrap();
+        let dirs: Vec<PathBuf> = std::env::split_paths(&path).collect();
+        assert_eq!(dirs, vec![venv, PathBuf::from("/usr/bin")]);
+    }
+}
@@ -0,0 +1,152 @@
+//! Resource browser
+//!
+//! Reads a running server's resources with `resources/read` and subscribes
+//! to them with `resources/subscribe`. Both go through the correlated-send
+//! path, so they show up in the inspector like a client's requests. When a
+//! server sends `notifications/resources/updated`, a `resource-updated`
+//! event names the changed resource. Subscriptions end when the server
+//! exits.
+
+use crate::error::SynapticResult;
+use crate::probe::into_result;
+use crate::process_manager::ProcessManager;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Contents returned by `resources/read`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceContents {
+    pub server_name: String,
+    pub uri: String,
+    /// Text or base64 `blob` items, each with its own `uri` and `mimeType`
+    pub contents: Vec<Value>,
+}
+
+/// Payload of the `resource-updated` event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceUpdate {
+    pub server_name: String,
+    pub uri: String,
+    /// Whether Synaptic subscribed to the resource (a client may have instead)
+    pub subscribed: bool,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// Resources Synaptic subscribed to, per server
+#[derive(Default)]
+pub struct ResourceSubscriptions {
+    subscribed: Mutex<BTreeSet<(String, String)>>,
+}
+
+impl ResourceSubscriptions {
+    /// Subscribed URIs of a server, sorted
+    pub fn list(&self, server_name: &str) -> Vec<String> {
+        self.subscribed
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(server, _)| server == server_name)
+            .map(|(_, uri)| uri.clone())
+            .collect()
+    }
+
+    fn contains(&self, server_name: &str, uri: &str) -> bool {
+        self.subscribed
+            .lock()
+            .unwrap()
+            .contains(&(server_name.to_string(), uri.to_string()))
+    }
+
+    /// Look at an incoming message; returns the update it announces, if any
+    pub fn observe(&self, server_name: &str, message: &Value) -> Option<ResourceUpdate> {
+        if message.get("method").and_then(|m| m.as_str()) != Some("notifications/resources/updated")
+        {
+            return None;
+        }
+        let uri = message.get("params")?.get("uri")?.as_str()?;
+        Some(ResourceUpdate {
+            server_name: server_name.to_string(),
+            uri: uri.to_string(),
+            subscribed: self.contains(server_name, uri),
+            timestamp: chrono::Utc::now(),
+        })
+    }
+
+    /// Drop the subscriptions of a server that exited
+    pub fn forget_server(&self, server_name: &str) {
+        self.subscribed
+            .lock()
+            .unwrap()
+            .retain(|(server, _)| server != server_name);
+    }
+}
+
+async fn send(
+    pm: &ProcessManager,
+    server_name: &str,
+    method: &str,
+    uri: &str,
+    timeout: Duration,
+) -> SynapticResult<Value> {
+    let request = json!({ "jsonrpc": "2.0", "method": method, "params": { "uri": uri } });
+    let response = pm.send_request(server_name, request, timeout).await?;
+    into_result(method, response)
+}
+
+/// Read a resource of a running server
+pub async fn read_resource(
+    pm: &ProcessManager,
+    server_name: &str,
+    uri: &str,
+    timeout: Duration,
+) -> SynapticResult<ResourceContents> {
+    let result = send(pm, server_name, "resources/read", uri, timeout).await?;
+    Ok(ResourceContents {
+        server_name: server_name.to_string(),
+        uri: uri.to_string(),
+        contents: result
+            .get("contents")
+            .and_then(|c| c.as_array())
+            .cloned()
+            .unwrap_or_default(),
+    })
+}
+
+/// Subscribe to updates of a running server's resource
+pub async fn subscribe_resource(
+    pm: &ProcessManager,
+    server_name: &str,
+    uri: &str,
+    timeout: Duration,
+) -> SynapticResult<()> {
+    // Recorded first: an update may follow the response immediately
+    let key = (server_name.to_string(), uri.to_string());
+    let subscriptions = &pm.resource_subscriptions.subscribed;
+    let added = subscriptions.lock().unwrap().insert(key.clone());
+    let result = send(pm, server_name, "resources/subscribe", uri, timeout).await;
+    if result.is_err() && added {
+        subscriptions.lock().unwrap().remove(&key);
+    }
+    result.map(|_| ())
+}
+
+/// Stop receiving updates of a resource
+pub async fn unsubscribe_resource(
+    pm: &ProcessManager,
+    server_name: &str,
+    uri: &str,
+    timeout: Duration,
+) -> SynapticResult<()> {
+    send(pm, server_name, "resources/unsubscribe", uri, timeout).await?;
+    pm.resource_subscriptions
+        .subscribed
+        .lock()
+        .unwrap()
+        .remove(&(server_name.to_string(), uri.to_string()));
+    Ok(())
+}
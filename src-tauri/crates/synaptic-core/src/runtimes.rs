@@ -0,0 +1,366 @@
+//! Portable runtimes for servers whose runtime isn't installed
+//!
+//! `install_runtime` downloads the official portable build of Node.js (the
+//! latest LTS) or uv for this platform into `runtimes/<runtime>` under the
+//! Synaptic data directory, after checking it against the SHA-256 its project
+//! publishes next to it. Python is installed by uv (downloaded first when
+//! there's none) into `runtimes/python`. Nothing is installed system-wide and
+//! no admin rights are needed.
+//!
+//! The runtimes' bin directories are searched when commands are resolved, so
+//! `npx`, `uvx` and `python` servers find them, and get the runtime's
+//! directory put in front of their `PATH` when spawned. Installs run as jobs
+//! like registry installs: they report `install-progress` events under the
+//! runtime's name and `cancel_install` stops them.
+
+use crate::config;
+use crate::error::{SynapticError, SynapticResult};
+use crate::host::EventEmitter;
+use crate::installer::{self, InstallStage, Reporter};
+use crate::packages;
+use crate::registry::{self, RuntimeStatus};
+use crate::resolve;
+use crate::state::AppState;
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Where Node.js releases and their index are published
+pub const NODE_DIST_URL: &str = "https://nodejs.org/dist";
+
+/// Where the latest uv release's builds are published
+pub const UV_RELEASES_URL: &str = "https://github.com/astral-sh/uv/releases/latest/download";
+
+const CHECKSUM_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// A runtime Synaptic can install
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Runtime {
+    Node,
+    Uv,
+    Python,
+}
+
+impl Runtime {
+    pub const ALL: [Runtime; 3] = [Runtime::Node, Runtime::Uv, Runtime::Python];
+
+    /// Runtime providing a command, as named in a server config or `check_runtime`
+    pub fn parse(runtime: &str) -> SynapticResult<Self> {
+        match runtime {
+            "node" | "npx" => Ok(Runtime::Node),
+            "uv" | "uvx" => Ok(Runtime::Uv),
+            "python" | "python3" => Ok(Runtime::Python),
+            _ => Err(SynapticError::RuntimeNotFound(format!(
+                "Unknown runtime: {}",
+                runtime
+            ))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Runtime::Node => "node",
+            Runtime::Uv => "uv",
+            Runtime::Python => "python",
+        }
+    }
+
+    /// Directory of the runtime's executables, given where it's installed
+    fn bin_dir(self, root: &Path) -> PathBuf {
+        match self {
+            Runtime::Node if cfg!(windows) => root.to_path_buf(),
+            Runtime::Uv => root.to_path_buf(),
+            Runtime::Node | Runtime::Python => root.join("bin"),
+        }
+    }
+}
+
+/// Directory runtimes are installed into
+pub fn get_runtimes_dir() -> SynapticResult<PathBuf> {
+    Ok(config::get_synaptic_data_dir()?.join("runtimes"))
+}
+
+/// Bin directories of the runtimes Synaptic can install, whether or not they are
+pub fn bin_dirs() -> Vec<PathBuf> {
+    let Ok(runtimes) = get_runtimes_dir() else {
+        return Vec::new();
+    };
+    Runtime::ALL
+        .iter()
+        .map(|runtime| runtime.bin_dir(&runtimes.join(runtime.name())))
+        .collect()
+}
+
+/// Install a runtime into the data directory and report its status afterwards
+///
+/// Tracked as an install job under `job_id` when the caller gives one.
+pub async fn install_runtime<E: EventEmitter>(
+    emitter: &E,
+    state: &AppState,
+    runtime: &str,
+    job_id: Option<String>,
+) -> SynapticResult<RuntimeStatus> {
+    let runtime = Runtime::parse(runtime)?;
+    let job = state.install_jobs.start(job_id)?;
+    let reporter = Reporter::new(emitter, &job.id, runtime.name());
+    let installed = tokio::select! {
+        installed = fetch_runtime(&reporter, runtime) => installed,
+        cancelled = job.cancelled() => Err(cancelled),
+    };
+    state.install_jobs.finish(&job.id);
+    reporter.finish(&job, &installed);
+    installed?;
+    registry::check_runtime_availability(runtime.name()).await
+}
+
+async fn fetch_runtime<E: EventEmitter>(
+    reporter: &Reporter<'_, E>,
+    runtime: Runtime,
+) -> SynapticResult<()> {
+    let runtimes = get_runtimes_dir()?;
+    let (url, sha256) = match runtime {
+        Runtime::Node => node_download().await?,
+        Runtime::Uv => uv_download().await?,
+        Runtime::Python => return install_python(reporter, &runtimes).await,
+    };
+    fetch_archive(reporter, &url, &sha256, &runtimes.join(runtime.name())).await
+}
+
+/// Download an archive, verify it and extract it to `dest`, replacing what was there
+async fn fetch_archive<E: EventEmitter>(
+    reporter: &Reporter<'_, E>,
+    url: &Url,
+    sha256: &str,
+    dest: &Path,
+) -> SynapticResult<()> {
+    let bytes = installer::download(reporter, url.clone()).await?;
+    reporter.stage(InstallStage::Verifying, None);
+    installer::verify_binary(&bytes, sha256, None, None, &[])?;
+
+    reporter.stage(InstallStage::Extracting, None);
+    // Left behind by a cancelled install, if there is one
+    let staging = dest.with_extension("extracting");
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    let extracted = extract(&bytes, url, &staging, dest).await;
+    if let Err(e) = fs::remove_dir_all(&staging) {
+        eprintln!("Failed to remove {}: {}", staging.display(), e);
+    }
+    extracted
+}
+
+async fn extract(bytes: &[u8], url: &Url, staging: &Path, dest: &Path) -> SynapticResult<()> {
+    let out = staging.join("out");
+    fs::create_dir_all(&out)?;
+    let archive_name = if url.path().ends_with(".zip") {
+        "archive.zip"
+    } else {
+        "archive.tar.gz"
+    };
+    let archive = staging.join(archive_name);
+    fs::write(&archive, bytes)?;
+
+    // bsdtar, shipped with Windows and macOS, reads zips as well
+    let output = Command::new("tar")
+        .arg("-xf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(&out)
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(SynapticError::ProcessError(format!(
+            "Failed to extract {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    if dest.exists() {
+        fs::remove_dir_all(dest)?;
+    }
+    fs::rename(archive_root(&out)?, dest)?;
+    Ok(())
+}
+
+/// Directory an archive's files are in: its only top-level directory, if it has one
+fn archive_root(dir: &Path) -> SynapticResult<PathBuf> {
+    let entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    match entries.as_slice() {
+        [only] if only.is_dir() => Ok(only.clone()),
+        _ => Ok(dir.to_path_buf()),
+    }
+}
+
+/// Build of the latest Node.js LTS for this platform, with its SHA-256
+async fn node_download() -> SynapticResult<(Url, String)> {
+    let index =
+        packages::fetch_document(&format!("{}/index.json", NODE_DIST_URL), "application/json")
+            .await?;
+    let version = index
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|release| release["lts"].is_string())
+        .and_then(|release| release["version"].as_str())
+        .ok_or_else(|| {
+            SynapticError::RegistryError(format!("No LTS release listed at {}", NODE_DIST_URL))
+        })?;
+
+    let (platform, extension) = match std::env::consts::OS {
+        "linux" => ("linux", "tar.gz"),
+        "macos" => ("darwin", "tar.gz"),
+        "windows" => ("win", "zip"),
+        os => return Err(unsupported(os)),
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "x64",
+        "aarch64" => "arm64",
+        arch => return Err(unsupported(arch)),
+    };
+    let file = format!("node-{}-{}-{}.{}", version, platform, arch, extension);
+    let sums = fetch_text(&format!("{}/{}/SHASUMS256.txt", NODE_DIST_URL, version)).await?;
+    let sha256 = checksum_for(&sums, &file).ok_or_else(|| {
+        SynapticError::RegistryError(format!("No checksum published for {}", file))
+    })?;
+    Ok((
+        parse_url(&format!("{}/{}/{}", NODE_DIST_URL, version, file))?,
+        sha256,
+    ))
+}
+
+/// Build of the latest uv release for this platform, with its SHA-256
+async fn uv_download() -> SynapticResult<(Url, String)> {
+    let target = match (std::env::consts::ARCH, std::env::consts::OS) {
+        ("x86_64", "linux") => "x86_64-unknown-linux-gnu.tar.gz",
+        ("aarch64", "linux") => "aarch64-unknown-linux-gnu.tar.gz",
+        ("x86_64", "macos") => "x86_64-apple-darwin.tar.gz",
+        ("aarch64", "macos") => "aarch64-apple-darwin.tar.gz",
+        ("x86_64", "windows") => "x86_64-pc-windows-msvc.zip",
+        ("aarch64", "windows") => "aarch64-pc-windows-msvc.zip",
+        (arch, os) => return Err(unsupported(&format!("{}-{}", arch, os))),
+    };
+    let url = format!("{}/uv-{}", UV_RELEASES_URL, target);
+    // `<sha256>  <file>`, like the lines of a SHASUMS file
+    let sums = fetch_text(&format!("{}.sha256", url)).await?;
+    let sha256 = sums
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| SynapticError::RegistryError(format!("No checksum published for {}", url)))?
+        .to_string();
+    Ok((parse_url(&url)?, sha256))
+}
+
+/// Have uv install Python into `runtimes/python`, getting uv first when it's missing
+async fn install_python<E: EventEmitter>(
+    reporter: &Reporter<'_, E>,
+    runtimes: &Path,
+) -> SynapticResult<()> {
+    let uv = match resolve::resolve_command("uv") {
+        Ok(uv) => uv,
+        Err(_) => {
+            let (url, sha256) = uv_download().await?;
+            let root = runtimes.join(Runtime::Uv.name());
+            fetch_archive(reporter, &url, &sha256, &root).await?;
+            Runtime::Uv
+                .bin_dir(&root)
+                .join(format!("uv{}", std::env::consts::EXE_SUFFIX))
+        }
+    };
+
+    let root = runtimes.join(Runtime::Python.name());
+    reporter.stage(
+        InstallStage::Downloading,
+        Some("Installing Python with uv".to_string()),
+    );
+    // `--default` adds `python` and `python3` next to `python3.x`
+    let mut command = Command::new(uv);
+    command
+        .args(["python", "install", "--default"])
+        .env("UV_PYTHON_INSTALL_DIR", &root)
+        .env("UV_PYTHON_BIN_DIR", Runtime::Python.bin_dir(&root));
+    installer::run_streamed(reporter, InstallStage::Downloading, command).await
+}
+
+/// Checksum of `file` in a `SHASUMS256.txt` listing
+fn checksum_for(sums: &str, file: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let (sha256, name) = line.split_once(char::is_whitespace)?;
+        (name.trim().trim_start_matches('*') == file).then(|| sha256.to_string())
+    })
+}
+
+async fn fetch_text(url: &str) -> SynapticResult<String> {
+    let fetch_error = |e: reqwest::Error| SynapticError::RegistryError(format!("{}: {}", url, e));
+    Client::builder()
+        .timeout(CHECKSUM_TIMEOUT)
+        .build()
+        .map_err(fetch_error)?
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(fetch_error)?
+        .text()
+        .await
+        .map_err(fetch_error)
+}
+
+fn parse_url(url: &str) -> SynapticResult<Url> {
+    Url::parse(url).map_err(|e| SynapticError::RegistryError(format!("Invalid URL {}: {}", url, e)))
+}
+
+fn unsupported(platform: &str) -> SynapticError {
+    SynapticError::RuntimeNotFound(format!("No portable runtime builds for {}", platform))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_names_cover_their_commands() {
+        assert_eq!(Runtime::parse("npx").unwrap(), Runtime::Node);
+        assert_eq!(Runtime::parse("uvx").unwrap(), Runtime::Uv);
+        assert_eq!(Runtime::parse("python3").unwrap(), Runtime::Python);
+        assert!(Runtime::parse("ruby").is_err());
+    }
+
+    #[test]
+    fn test_finds_the_checksum_of_a_file() {
+        let sums = "\
+1111  node-v22.11.0-darwin-arm64.tar.gz
+2222  node-v22.11.0-linux-x64.tar.gz
+3333 *node-v22.11.0-win-x64.zip
+";
+        assert_eq!(
+            checksum_for(sums, "node-v22.11.0-linux-x64.tar.gz").as_deref(),
+            Some("2222")
+        );
+        assert_eq!(
+            checksum_for(sums, "node-v22.11.0-win-x64.zip").as_deref(),
+            Some("3333")
+        );
+        assert_eq!(checksum_for(sums, "node-v22.11.0-linux-x64.tar"), None);
+    }
+
+    #[test]
+    fn test_archive_root_skips_a_single_top_level_dir() {
+        let dir = std::env::temp_dir().join(format!("synaptic-archive-{}", uuid::Uuid::new_v4()));
+        let nested = dir.join("node-v22.11.0-linux-x64");
+        fs::create_dir_all(nested.join("bin")).unwrap();
+        assert_eq!(archive_root(&dir).unwrap(), nested);
+
+        fs::write(dir.join("README.md"), "").unwrap();
+        assert_eq!(archive_root(&dir).unwrap(), dir);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+//! Sampling requests from servers
+//!
+//! A server asks its client for an LLM completion with
+//! `sampling/createMessage`. Synaptic is that client for the servers it
+//! runs, so instead of leaving such requests unanswered it keeps them as
+//! pending, emits a `sampling-request` event and lets the user answer with
+//! a result or a denial. With `samplingAutoDeny` set, every request is
+//! denied as soon as it arrives.
+
+use crate::error::{SynapticError, SynapticResult};
+use crate::process_manager::ProcessManager;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+
+/// JSON-RPC error code of a denied sampling request
+pub const SAMPLING_DENIED_CODE: i64 = -1;
+
+/// A `sampling/createMessage` request waiting for an answer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingRequest {
+    pub server_name: String,
+    pub request_id: Value,
+    /// `messages`, `modelPreferences`, `systemPrompt`, `maxTokens`, ...
+    pub params: Value,
+    pub received_at: chrono::DateTime<chrono::Utc>,
+    /// Denied right away by the `samplingAutoDeny` setting
+    #[serde(default)]
+    pub auto_denied: bool,
+}
+
+/// How the user answers a sampling request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SamplingReply {
+    /// A `CreateMessageResult` (`role`, `content`, `model`, `stopReason`)
+    Respond {
+        result: Value,
+    },
+    Deny {
+        reason: Option<String>,
+    },
+}
+
+impl SamplingReply {
+    /// JSON-RPC response answering the request `request_id`
+    pub fn to_response(&self, request_id: &Value) -> Value {
+        match self {
+            SamplingReply::Respond { result } => {
+                json!({ "jsonrpc": "2.0", "id": request_id, "result": result })
+            }
+            SamplingReply::Deny { reason } => json!({
+                "jsonrpc": "2.0",
+                "id": request_id,
+                "error": {
+                    "code": SAMPLING_DENIED_CODE,
+                    "message": reason.as_deref().unwrap_or("User rejected sampling request"),
+                },
+            }),
+        }
+    }
+}
+
+/// Sampling requests not answered yet, oldest first
+#[derive(Default)]
+pub struct SamplingInbox {
+    pending: Mutex<Vec<SamplingRequest>>,
+}
+
+impl SamplingInbox {
+    /// Look at an incoming message; keeps and returns a sampling request
+    pub fn observe(&self, server_name: &str, message: &Value) -> Option<SamplingRequest> {
+        if message.get("method").and_then(|m| m.as_str()) != Some("sampling/createMessage") {
+            return None;
+        }
+        let request = SamplingRequest {
+            server_name: server_name.to_string(),
+            request_id: message.get("id").filter(|id| !id.is_null())?.clone(),
+            params: message.get("params").cloned().unwrap_or_default(),
+            received_at: chrono::Utc::now(),
+            auto_denied: false,
+        };
+        self.pending.lock().unwrap().push(request.clone());
+        Some(request)
+    }
+
+    /// Pending requests, optionally of a single server
+    pub fn list(&self, server_name: Option<&str>) -> Vec<SamplingRequest> {
+        self.pending
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| server_name.is_none_or(|name| r.server_name == name))
+            .cloned()
+            .collect()
+    }
+
+    fn take(&self, server_name: &str, request_id: &Value) -> Option<SamplingRequest> {
+        let mut pending = self.pending.lock().unwrap();
+        let index = pending
+            .iter()
+            .position(|r| r.server_name == server_name && &r.request_id == request_id)?;
+        Some(pending.remove(index))
+    }
+
+    /// Drop the requests of a server that exited
+    pub fn forget_server(&self, server_name: &str) {
+        self.pending
+            .lock()
+            .unwrap()
+            .retain(|r| r.server_name != server_name);
+    }
+}
+
+/// Answer a pending sampling request
+pub async fn respond_to_sampling(
+    pm: &ProcessManager,
+    server_name: &str,
+    request_id: &Value,
+    reply: &SamplingReply,
+) -> SynapticResult<()> {
+    let request = pm.sampling.take(server_name, request_id).ok_or_else(|| {
+        SynapticError::ProcessError(format!(
+            "No sampling request {} from {} is waiting for an answer",
+            request_id, server_name
+        ))
+    })?;
+    let response = reply.to_response(&request.request_id);
+    if let Err(e) = pm.send_to_stdin(server_name, response.to_string()).await {
+        // Still unanswered
+        pm.sampling.pending.lock().unwrap().push(request);
+        return Err(e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampling_requests_wait_for_an_answer() {
+        let inbox = SamplingInbox::default();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "sampling/createMessage",
+            "params": { "messages": [], "maxTokens": 100 }
+        });
+        assert!(inbox
+            .observe("fs", &json!({"jsonrpc":"2.0","id":4,"method":"roots/list"}))
+            .is_none());
+        let seen = inbox.observe("fs", &request).unwrap();
+        assert_eq!(seen.params["maxTokens"], 100);
+        assert_eq!(inbox.list(Some("fs")).len(), 1);
+        assert!(inbox.list(Some("git")).is_empty());
+
+        let denied = SamplingReply::Deny { reason: None }.to_response(&seen.request_id);
+        assert_eq!(denied["id"], 3);
+        assert_eq!(denied["error"]["code"], SAMPLING_DENIED_CODE);
+
+        assert!(inbox.take("fs", &json!("3")).is_none());
+        assert!(inbox.take("fs", &json!(3)).is_some());
+        assert!(inbox.list(None).is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+//! Scheduled start/stop windows for servers
+//!
+//! Schedules live in the settings and say when a server should be running,
+//! e.g. weekdays 09:00–18:00 for a heavy indexer. A background task starts
+//! and stops servers as windows open and close, recording each action in the
+//! audit log. It only acts on window edges, so a server started or stopped by
+//! hand stays that way until the next edge.
+
+use crate::audit::{self, AuditAction};
+use crate::error::SynapticError;
+use crate::host::Host;
+use crate::process_manager::spawn_mcp_server;
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How often the scheduler looks at the clock
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Daily window during which a server should be running
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSchedule {
+    pub server_name: String,
+    /// Days the window opens on (`"Mon"`, `"Tue"`, ...); empty means every day
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// Local time the server is started (`"09:00"`)
+    pub start: NaiveTime,
+    /// Local time the server is stopped; at or before `start` the window runs past midnight
+    pub stop: NaiveTime,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ServerSchedule {
+    /// Whether the window is open at the given local time
+    pub fn is_active_at(&self, now: NaiveDateTime) -> bool {
+        let opens_on = |day: Weekday| self.days.is_empty() || self.days.contains(&day);
+        let time = now.time();
+        let today = now.weekday();
+
+        if self.start < self.stop {
+            opens_on(today) && time >= self.start && time < self.stop
+        } else {
+            // Overnight: opened today, or opened yesterday and not closed yet
+            (opens_on(today) && time >= self.start) || (opens_on(today.pred()) && time < self.stop)
+        }
+    }
+}
+
+/// Whether each scheduled server should be running at `now`
+///
+/// A server with several schedules runs while any of them is open.
+pub fn desired_states(schedules: &[ServerSchedule], now: NaiveDateTime) -> HashMap<String, bool> {
+    let mut desired: HashMap<String, bool> = HashMap::new();
+    for schedule in schedules.iter().filter(|s| s.enabled) {
+        *desired.entry(schedule.server_name.clone()).or_default() |= schedule.is_active_at(now);
+    }
+    desired
+}
+
+/// Payload of the `schedule-applied` event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleEvent {
+    pub server_name: String,
+    /// `scheduled_start` or `scheduled_stop`
+    pub action: AuditAction,
+    pub error: Option<String>,
+}
+
+/// Background loop that starts and stops servers according to their schedules
+pub async fn run_schedule_scheduler<H: Host>(host: H) {
+    let mut ticker = tokio::time::interval(SCHEDULE_CHECK_INTERVAL);
+    // Desired state seen on the previous tick, to detect window edges
+    let mut previous: HashMap<String, bool> = HashMap::new();
+
+    loop {
+        ticker.tick().await;
+
+        let settings = match host.app_state().map(|state| state.get_settings()) {
+            Some(Ok(settings)) => settings,
+            Some(Err(e)) => {
+                eprintln!("Failed to load settings for schedules: {}", e);
+                continue;
+            }
+            None => continue,
+        };
+
+        let desired = desired_states(&settings.schedules, Local::now().naive_local());
+        previous.retain(|name, _| desired.contains_key(name));
+
+        for (server_name, should_run) in desired {
+            if previous.insert(server_name.clone(), should_run) == Some(should_run) {
+                continue;
+            }
+            let stop_after = (settings.drain_timeout(), settings.shutdown_grace());
+            if let Some(event) = apply_schedule(&host, server_name, should_run, stop_after).await {
+                host.emit("schedule-applied", &event);
+            }
+        }
+    }
+}
+
+/// Start or stop a server to match its schedule; `None` when nothing changed
+async fn apply_schedule<H: Host>(
+    host: &H,
+    server_name: String,
+    should_run: bool,
+    (drain, grace): (Duration, Duration),
+) -> Option<ScheduleEvent> {
+    let state = host.app_state()?;
+    let process_manager = host.process_manager()?;
+    if process_manager.is_running(&server_name).await == should_run {
+        return None;
+    }
+
+    let (action, result) = if should_run {
+        // Disabled servers stay off even inside their window
+        let server = match state.get_config() {
+            Ok(config) => match config.mcp_servers.get(&server_name) {
+                Some(server) if !server.enabled => return None,
+                Some(server) => Ok(server.clone()),
+                None => Err(SynapticError::ServerNotFound(server_name.clone())),
+            },
+            Err(e) => Err(e),
+        };
+        let result = match server {
+            Ok(server) => {
+                spawn_mcp_server(host.clone(), process_manager, server_name.clone(), server)
+                    .await
+                    .map(|_| ())
+            }
+            Err(e) => Err(e),
+        };
+        (AuditAction::ScheduledStart, result)
+    } else {
+        let result = process_manager
+            .drain_and_kill(&server_name, drain, grace)
+            .await
+            .map(|_| ());
+        (AuditAction::ScheduledStop, result)
+    };
+
+    match &result {
+        Ok(()) => {
+            if let Ok(db) = state.database() {
+                let record = audit::record_change(
+                    db,
+                    action,
+                    Some(&server_name),
+                    Some(json!({ "running": !should_run })),
+                    Some(json!({ "running": should_run })),
+                );
+                if let Err(e) = record {
+                    eprintln!(
+                        "Failed to audit scheduled action for {}: {}",
+                        server_name, e
+                    );
+                }
+            }
+        }
+        Err(e) => eprintln!(
+            "Scheduled {} failed for {}: {}",
+            action.as_str(),
+            server_name,
+            e
+        ),
+    }
+
+    Some(ScheduleEvent {
+        server_name,
+        action,
+        error: result.err().map(|e| e.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, time: &str) -> NaiveDateTime {
+        // 2024-01-01 was a Monday
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_time(time.parse().unwrap())
+    }
+
+    fn schedule(days: Vec<Weekday>, start: &str, stop: &str) -> ServerSchedule {
+        ServerSchedule {
+            server_name: "indexer".to_string(),
+            days,
+            start: start.parse().unwrap(),
+            stop: stop.parse().unwrap(),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_schedule_windows() {
+        let work_hours = schedule(
+            vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            "09:00",
+            "18:00",
+        );
+        assert!(work_hours.is_active_at(at(1, "09:00")));
+        assert!(!work_hours.is_active_at(at(1, "18:00")));
+        assert!(!work_hours.is_active_at(at(6, "12:00")));
+
+        // Opens Friday night, still open early Saturday, not Sunday night
+        let nightly = schedule(vec![Weekday::Fri], "22:00", "06:00");
+        assert!(nightly.is_active_at(at(5, "23:00")));
+        assert!(nightly.is_active_at(at(6, "05:59")));
+        assert!(!nightly.is_active_at(at(7, "23:00")));
+
+        let disabled = ServerSchedule {
+            server_name: "other".to_string(),
+            enabled: false,
+            ..nightly.clone()
+        };
+        let desired = desired_states(&[work_hours, nightly, disabled], at(5, "23:00"));
+        assert_eq!(desired, HashMap::from([("indexer".to_string(), true)]));
+
+        let parsed: ServerSchedule = serde_json::from_str(
+            r#"{"serverName":"indexer","days":["mon","Tue"],"start":"09:00","stop":"17:30"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.days, vec![Weekday::Mon, Weekday::Tue]);
+        assert!(parsed.enabled);
+    }
+}
@@ -0,0 +1,584 @@
+//! Side-by-side comparison of two servers
+//!
+//! Each side is either a live server, probed like [`probe::probe_server`]
+//! (and started just for the comparison when it isn't running), or a logged
+//! session, whose catalog is rebuilt from the `initialize` and list
+//! responses it recorded. Typical use is an old and a new version of the
+//! same server.
+//!
+//! The comparison covers the handshake (protocol version, serverInfo,
+//! capabilities), the tool, resource, template and prompt catalogs, and the
+//! responses both sides give to the same requests. Requests are sent to live
+//! sides and looked up by method and params in sessions. When none are
+//! given, the read-only requests recorded by the session sides are used;
+//! `tools/call` is only ever sent when asked for explicitly.
+
+use crate::config::McpServer;
+use crate::database::Database;
+use crate::error::{SynapticError, SynapticResult};
+use crate::host::Host;
+use crate::message_diff::{diff_values, DiffEntry, DiffKind};
+use crate::probe::{self, ServerCatalog};
+use crate::process_manager::{spawn_mcp_server, ProcessManager};
+use crate::session_archive::{self, HarEntry};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+/// Time each compared request may take on a live server
+const COMPARE_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Methods whose recorded requests are replayed when none are given
+const READ_ONLY_METHODS: &[&str] = &[
+    "ping",
+    "tools/list",
+    "resources/list",
+    "resources/templates/list",
+    "resources/read",
+    "prompts/list",
+    "prompts/get",
+    "completion/complete",
+];
+
+/// Where one side of a comparison comes from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ComparisonSource {
+    /// A configured server, probed now
+    #[serde(rename_all = "camelCase")]
+    Server { server_name: String },
+    /// A logged session
+    #[serde(rename_all = "camelCase")]
+    Session { session_id: String },
+}
+
+/// One side's catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparedSide {
+    pub source: ComparisonSource,
+    pub catalog: ServerCatalog,
+}
+
+/// Kind of catalog item
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Tool,
+    Resource,
+    ResourceTemplate,
+    Prompt,
+}
+
+/// A catalog item only one side has, or that differs between them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogDifference {
+    pub item: ItemKind,
+    /// Name (or URI / URI template) of the item
+    pub key: String,
+    /// `added` when only in `b`, `removed` when only in `a`
+    pub kind: DiffKind,
+    /// Changed members, for items on both sides
+    pub changes: Vec<DiffEntry>,
+}
+
+/// How the two sides' responses to a request relate
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BehaviorOutcome {
+    Same,
+    Different,
+    /// Only `a` answered (or recorded) the request
+    OnlyA,
+    /// Only `b` answered (or recorded) the request
+    OnlyB,
+    /// Neither side has a response
+    Neither,
+}
+
+/// Both sides' responses to one request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BehaviorComparison {
+    pub method: String,
+    pub params: Option<Value>,
+    /// Responses without their JSON-RPC `id`
+    pub response_a: Option<Value>,
+    pub response_b: Option<Value>,
+    pub outcome: BehaviorOutcome,
+    pub changes: Vec<DiffEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerComparison {
+    pub a: ComparedSide,
+    pub b: ComparedSide,
+    /// Differences in `serverInfo`, e.g. its version
+    pub server_info: Vec<DiffEntry>,
+    pub capabilities: Vec<DiffEntry>,
+    pub catalog: Vec<CatalogDifference>,
+    pub behavior: Vec<BehaviorComparison>,
+    /// Nothing but `serverInfo` differs
+    pub identical: bool,
+}
+
+/// One side's catalog plus its answers to the compared requests
+struct Snapshot {
+    catalog: ServerCatalog,
+    /// Logged exchanges (sessions only)
+    exchanges: Vec<HarEntry>,
+}
+
+/// Compare two servers or sessions; `requests` are JSON-RPC requests to compare responses for
+pub async fn compare_servers<H: Host>(
+    host: H,
+    a: ComparisonSource,
+    b: ComparisonSource,
+    requests: Vec<Value>,
+) -> SynapticResult<ServerComparison> {
+    let snapshot_a = snapshot(&host, &a).await?;
+    let snapshot_b = match snapshot(&host, &b).await {
+        Ok(snapshot_b) => snapshot_b,
+        Err(e) => {
+            // Don't leave `a` running when it was only started for this
+            if let (ComparisonSource::Server { server_name }, Ok(pm)) = (&a, process_manager(&host))
+            {
+                if snapshot_a.catalog.spawned {
+                    probe::stop_temporary(&host, pm, server_name).await;
+                }
+            }
+            return Err(e);
+        }
+    };
+
+    let requests = if requests.is_empty() {
+        recorded_requests(&[&snapshot_a, &snapshot_b])
+    } else {
+        requests
+    };
+    let responses_a = responses(&host, &a, &snapshot_a, &requests).await?;
+    let responses_b = responses(&host, &b, &snapshot_b, &requests).await?;
+
+    let behavior: Vec<BehaviorComparison> = requests
+        .iter()
+        .zip(responses_a.into_iter().zip(responses_b))
+        .map(|(request, (response_a, response_b))| {
+            compare_responses(request, response_a, response_b)
+        })
+        .collect();
+
+    let (catalog_a, catalog_b) = (snapshot_a.catalog, snapshot_b.catalog);
+    let server_info = diff_values(&catalog_a.server_info, &catalog_b.server_info);
+    let mut capabilities = diff_values(&catalog_a.capabilities, &catalog_b.capabilities);
+    if catalog_a.protocol_version != catalog_b.protocol_version {
+        capabilities.insert(
+            0,
+            DiffEntry {
+                path: "/protocolVersion".to_string(),
+                kind: DiffKind::Changed,
+                before: catalog_a.protocol_version.clone().map(Value::String),
+                after: catalog_b.protocol_version.clone().map(Value::String),
+            },
+        );
+    }
+    let catalog = diff_catalogs(&catalog_a, &catalog_b);
+    let identical = capabilities.is_empty()
+        && catalog.is_empty()
+        && behavior.iter().all(|b| b.outcome == BehaviorOutcome::Same);
+
+    Ok(ServerComparison {
+        a: ComparedSide {
+            source: a,
+            catalog: catalog_a,
+        },
+        b: ComparedSide {
+            source: b,
+            catalog: catalog_b,
+        },
+        server_info,
+        capabilities,
+        catalog,
+        behavior,
+        identical,
+    })
+}
+
+async fn snapshot<H: Host>(host: &H, source: &ComparisonSource) -> SynapticResult<Snapshot> {
+    match source {
+        ComparisonSource::Server { server_name } => {
+            let pm = process_manager(host)?;
+            let spawned = !pm.is_running(server_name).await;
+            let catalog = if spawned {
+                let server = configured(host, server_name)?;
+                spawn_mcp_server(host.clone(), pm, server_name.clone(), server).await?;
+                let catalog = probe::initialize_and_list(pm, server_name).await;
+                // Kept running for the compared requests; see `responses`
+                if catalog.is_err() {
+                    probe::stop_temporary(host, pm, server_name).await;
+                }
+                catalog?
+            } else {
+                probe::catalog_of_running(pm, server_name).await
+            };
+            Ok(Snapshot {
+                catalog,
+                exchanges: Vec::new(),
+            })
+        }
+        ComparisonSource::Session { session_id } => {
+            let redactor = process_manager(host)?.redactor();
+            let (session, entries) =
+                session_archive::load_session(database(host)?, session_id, &redactor)?;
+            let server_name = session.server_name.clone().unwrap_or_default();
+            let exchanges = session_archive::to_har(session, entries).log.entries;
+            Ok(Snapshot {
+                catalog: catalog_from_exchanges(&server_name, &exchanges),
+                exchanges,
+            })
+        }
+    }
+}
+
+fn process_manager<H: Host>(host: &H) -> SynapticResult<&ProcessManager> {
+    host.process_manager()
+        .ok_or_else(|| SynapticError::ProcessError("Process manager unavailable".to_string()))
+}
+
+fn database<H: Host>(host: &H) -> SynapticResult<&Database> {
+    host.app_state()
+        .ok_or_else(|| SynapticError::DatabaseError("Database not initialized".to_string()))?
+        .database()
+}
+
+fn configured<H: Host>(host: &H, server_name: &str) -> SynapticResult<McpServer> {
+    let state = host
+        .app_state()
+        .ok_or_else(|| SynapticError::ServerNotFound(server_name.to_string()))?;
+    state
+        .get_config()?
+        .mcp_servers
+        .get(server_name)
+        .cloned()
+        .ok_or_else(|| SynapticError::ServerNotFound(server_name.to_string()))
+}
+
+/// Catalog rebuilt from a session's `initialize` and list responses (the last full listing wins)
+fn catalog_from_exchanges(server_name: &str, exchanges: &[HarEntry]) -> ServerCatalog {
+    let mut catalog = ServerCatalog {
+        server_name: server_name.to_string(),
+        capabilities: Value::Null,
+        ..Default::default()
+    };
+    let mut lists: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    for exchange in exchanges {
+        let (Some(method), Some(result)) = (
+            exchange.method.as_deref(),
+            exchange.response.as_ref().and_then(|r| r.get("result")),
+        ) else {
+            continue;
+        };
+        if method == "initialize" {
+            catalog.protocol_version = result
+                .get("protocolVersion")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            catalog.server_info = result.get("serverInfo").cloned().unwrap_or(Value::Null);
+            catalog.capabilities = result.get("capabilities").cloned().unwrap_or(Value::Null);
+            catalog.instructions = result
+                .get("instructions")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            continue;
+        }
+        let Some(key) = list_key(method) else {
+            continue;
+        };
+        let first_page = exchange
+            .request
+            .as_ref()
+            .and_then(|r| r.pointer("/params/cursor"))
+            .is_none();
+        let items = lists.entry(key).or_default();
+        if first_page {
+            items.clear();
+        }
+        if let Some(Value::Array(page)) = result.get(key) {
+            items.extend(page.iter().cloned());
+        }
+    }
+    for (key, items) in lists {
+        match key {
+            "tools" => catalog.tools = items,
+            "resources" => catalog.resources = items,
+            "resourceTemplates" => catalog.resource_templates = items,
+            _ => catalog.prompts = items,
+        }
+    }
+    catalog
+}
+
+/// Result member holding the items of a list method
+fn list_key(method: &str) -> Option<&'static str> {
+    match method {
+        "tools/list" => Some("tools"),
+        "resources/list" => Some("resources"),
+        "resources/templates/list" => Some("resourceTemplates"),
+        "prompts/list" => Some("prompts"),
+        _ => None,
+    }
+}
+
+/// Read-only requests recorded by session sides, once each, in the order first seen
+fn recorded_requests(snapshots: &[&Snapshot]) -> Vec<Value> {
+    let mut seen = BTreeSet::new();
+    let mut requests = Vec::new();
+    for exchange in snapshots.iter().flat_map(|s| &s.exchanges) {
+        let Some(request) = &exchange.request else {
+            continue;
+        };
+        let method = request.get("method").and_then(|m| m.as_str());
+        if exchange.direction.as_deref() != Some("OUTGOING")
+            || !method.is_some_and(|m| READ_ONLY_METHODS.contains(&m))
+        {
+            continue;
+        }
+        let request = stripped(request);
+        if seen.insert(request.to_string()) {
+            requests.push(request);
+        }
+    }
+    requests
+}
+
+/// Each request's response on one side (`None` when unanswered or not recorded)
+async fn responses<H: Host>(
+    host: &H,
+    source: &ComparisonSource,
+    snapshot: &Snapshot,
+    requests: &[Value],
+) -> SynapticResult<Vec<Option<Value>>> {
+    let ComparisonSource::Server { server_name } = source else {
+        return Ok(requests
+            .iter()
+            .map(|request| {
+                snapshot
+                    .exchanges
+                    .iter()
+                    .rev()
+                    .filter(|exchange| exchange.direction.as_deref() == Some("OUTGOING"))
+                    .find(|exchange| {
+                        exchange.request.as_ref().is_some_and(|logged| {
+                            logged.get("method") == request.get("method")
+                                && logged.get("params") == request.get("params")
+                        })
+                    })
+                    .and_then(|exchange| exchange.response.as_ref())
+                    .map(stripped)
+            })
+            .collect());
+    };
+
+    let pm = process_manager(host)?;
+    let mut responses = Vec::new();
+    for request in requests {
+        let response = pm
+            .send_request(server_name, stripped(request), COMPARE_REQUEST_TIMEOUT)
+            .await
+            .ok();
+        responses.push(response.as_ref().map(stripped));
+    }
+    if snapshot.catalog.spawned {
+        probe::stop_temporary(host, pm, server_name).await;
+    }
+    Ok(responses)
+}
+
+/// A message without its `id` and `jsonrpc` members, which never carry behavior
+fn stripped(message: &Value) -> Value {
+    let mut message = message.clone();
+    if let Some(members) = message.as_object_mut() {
+        members.remove("id");
+        members.remove("jsonrpc");
+    }
+    message
+}
+
+fn compare_responses(
+    request: &Value,
+    response_a: Option<Value>,
+    response_b: Option<Value>,
+) -> BehaviorComparison {
+    let (outcome, changes) = match (&response_a, &response_b) {
+        (Some(a), Some(b)) => {
+            let changes = diff_values(a, b);
+            let outcome = if changes.is_empty() {
+                BehaviorOutcome::Same
+            } else {
+                BehaviorOutcome::Different
+            };
+            (outcome, changes)
+        }
+        (Some(_), None) => (BehaviorOutcome::OnlyA, Vec::new()),
+        (None, Some(_)) => (BehaviorOutcome::OnlyB, Vec::new()),
+        (None, None) => (BehaviorOutcome::Neither, Vec::new()),
+    };
+    BehaviorComparison {
+        method: request
+            .get("method")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        params: request.get("params").cloned(),
+        response_a,
+        response_b,
+        outcome,
+        changes,
+    }
+}
+
+/// Items added, removed or changed from `a` to `b`
+fn diff_catalogs(a: &ServerCatalog, b: &ServerCatalog) -> Vec<CatalogDifference> {
+    let lists = [
+        (ItemKind::Tool, "name", &a.tools, &b.tools),
+        (ItemKind::Resource, "uri", &a.resources, &b.resources),
+        (
+            ItemKind::ResourceTemplate,
+            "uriTemplate",
+            &a.resource_templates,
+            &b.resource_templates,
+        ),
+        (ItemKind::Prompt, "name", &a.prompts, &b.prompts),
+    ];
+    let mut differences = Vec::new();
+    for (item, key, items_a, items_b) in lists {
+        let by_key = |items: &[Value]| -> BTreeMap<String, Value> {
+            items
+                .iter()
+                .filter_map(|i| Some((i.get(key)?.as_str()?.to_string(), i.clone())))
+                .collect()
+        };
+        let (items_a, items_b) = (by_key(items_a), by_key(items_b));
+        let keys: BTreeSet<&String> = items_a.keys().chain(items_b.keys()).collect();
+        for name in keys {
+            let (kind, changes) = match (items_a.get(name), items_b.get(name)) {
+                (Some(x), Some(y)) => {
+                    let changes = diff_values(x, y);
+                    if changes.is_empty() {
+                        continue;
+                    }
+                    (DiffKind::Changed, changes)
+                }
+                (Some(_), None) => (DiffKind::Removed, Vec::new()),
+                (None, _) => (DiffKind::Added, Vec::new()),
+            };
+            differences.push(CatalogDifference {
+                item,
+                key: name.clone(),
+                kind,
+                changes,
+            });
+        }
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn exchange(request: Value, response: Value) -> HarEntry {
+        HarEntry {
+            started_date_time: chrono::Utc::now(),
+            time: Some(1),
+            server_name: Some("fs".to_string()),
+            direction: Some("OUTGOING".to_string()),
+            method: request["method"].as_str().map(String::from),
+            request_id: Some("1".to_string()),
+            request: Some(request),
+            response: Some(response),
+            cancelled: false,
+        }
+    }
+
+    #[test]
+    fn test_session_catalog_keeps_the_last_full_listing() {
+        let exchanges = vec![
+            exchange(
+                json!({"id": 1, "method": "initialize"}),
+                json!({"id": 1, "result": {"protocolVersion": "2025-06-18", "capabilities": {"tools": {}}}}),
+            ),
+            exchange(
+                json!({"id": 2, "method": "tools/list"}),
+                json!({"id": 2, "result": {"tools": [{"name": "old"}]}}),
+            ),
+            exchange(
+                json!({"id": 3, "method": "tools/list"}),
+                json!({"id": 3, "result": {"tools": [{"name": "a"}], "nextCursor": "p2"}}),
+            ),
+            exchange(
+                json!({"id": 4, "method": "tools/list", "params": {"cursor": "p2"}}),
+                json!({"id": 4, "result": {"tools": [{"name": "b"}]}}),
+            ),
+        ];
+        let catalog = catalog_from_exchanges("fs", &exchanges);
+        assert_eq!(catalog.protocol_version.as_deref(), Some("2025-06-18"));
+        assert_eq!(
+            catalog.tools,
+            vec![json!({"name": "a"}), json!({"name": "b"})]
+        );
+    }
+
+    #[test]
+    fn test_catalog_and_behavior_differences() {
+        let a = ServerCatalog {
+            tools: vec![
+                json!({"name": "read", "description": "Read a file"}),
+                json!({"name": "gone"}),
+            ],
+            ..Default::default()
+        };
+        let b = ServerCatalog {
+            tools: vec![
+                json!({"name": "read", "description": "Read files"}),
+                json!({"name": "new"}),
+            ],
+            ..Default::default()
+        };
+        let differences = diff_catalogs(&a, &b);
+        let summary: Vec<(&str, DiffKind)> = differences
+            .iter()
+            .map(|d| (d.key.as_str(), d.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("gone", DiffKind::Removed),
+                ("new", DiffKind::Added),
+                ("read", DiffKind::Changed)
+            ]
+        );
+        assert_eq!(differences[2].changes[0].path, "/description");
+
+        let request = json!({"method": "ping"});
+        let same = compare_responses(
+            &request,
+            Some(json!({"result": {}})),
+            Some(json!({"result": {}})),
+        );
+        assert_eq!(same.outcome, BehaviorOutcome::Same);
+        let different = compare_responses(
+            &request,
+            Some(json!({"result": {}})),
+            Some(json!({"error": {"code": -32601}})),
+        );
+        assert_eq!(different.outcome, BehaviorOutcome::Different);
+        assert_eq!(
+            compare_responses(&request, None, Some(json!({"result": {}}))).outcome,
+            BehaviorOutcome::OnlyB
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! Per-server log files on disk
+//!
+//! Every line a server writes to stdout or stderr is appended to
+//! `logs/<server>/<date>.log` under the Synaptic data dir, so output survives
+//! an app restart. Files rotate by size to `<date>.1.log`, `<date>.2.log`, ...
+
+use crate::config;
+use crate::error::{SynapticError, SynapticResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Size at which the current log file is rotated
+pub const MAX_LOG_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Rotated files kept per day (older ones are deleted)
+pub const ROTATED_FILES_KEPT: usize = 5;
+
+/// Get the directory holding one subdirectory of logs per server
+pub fn get_server_logs_dir() -> SynapticResult<PathBuf> {
+    Ok(config::get_synaptic_data_dir()?.join("logs"))
+}
+
+/// Directory name for a server, safe to use as a single path component
+pub fn log_dir_name(server_name: &str) -> String {
+    let name: String = server_name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if name.is_empty() || name.chars().all(|c| c == '.') {
+        name.replace('.', "_") + "_"
+    } else {
+        name
+    }
+}
+
+/// Which output stream a line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl LogStream {
+    fn as_str(self) -> &'static str {
+        match self {
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+        }
+    }
+}
+
+/// Appends one server's output to its rotating log files
+pub struct ServerLog {
+    dir: PathBuf,
+    max_bytes: u64,
+    date: String,
+    file: Option<File>,
+    size: u64,
+}
+
+impl ServerLog {
+    /// Log for `server_name` under `root` (one subdirectory per server)
+    pub fn new(root: &Path, server_name: &str, max_bytes: u64) -> Self {
+        Self {
+            dir: root.join(log_dir_name(server_name)),
+            max_bytes,
+            date: String::new(),
+            file: None,
+            size: 0,
+        }
+    }
+
+    /// Append a line, rotating first if the day changed or the file is full
+    pub fn write_line(&mut self, stream: LogStream, line: &str) -> io::Result<()> {
+        let now = Utc::now();
+        let entry = format!("{} [{}] {}\n", now.to_rfc3339(), stream.as_str(), line);
+
+        let date = now.format("%Y-%m-%d").to_string();
+        if self.file.is_none() || date != self.date {
+            self.open(date)?;
+        } else if self.size > 0 && self.size + entry.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let file = self.file.as_mut().expect("log file opened above");
+        file.write_all(entry.as_bytes())?;
+        self.size += entry.len() as u64;
+        Ok(())
+    }
+
+    fn current_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.log", self.date))
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        self.dir.join(format!("{}.{}.log", self.date, index))
+    }
+
+    fn open(&mut self, date: String) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        self.date = date;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.current_path())?;
+        self.size = file.metadata()?.len();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Shift `<date>.log` -> `<date>.1.log` -> ... dropping the oldest
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+
+        let oldest = self.rotated_path(ROTATED_FILES_KEPT);
+        if oldest.exists() {
+            fs::remove_file(oldest)?;
+        }
+        for index in (1..ROTATED_FILES_KEPT).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(from, self.rotated_path(index + 1))?;
+            }
+        }
+        fs::rename(self.current_path(), self.rotated_path(1))?;
+
+        let date = self.date.clone();
+        self.open(date)
+    }
+}
+
+/// A log file on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFileInfo {
+    /// Log directory name (the server name with unsafe characters replaced)
+    pub server_name: String,
+    pub file_name: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub modified: DateTime<Utc>,
+}
+
+/// List log files of one server (or all servers), newest first
+pub fn list_server_logs(server_name: Option<&str>) -> SynapticResult<Vec<LogFileInfo>> {
+    list_server_logs_in(&get_server_logs_dir()?, server_name)
+}
+
+/// Path-parameterized version of [`list_server_logs`]
+pub fn list_server_logs_in(
+    root: &Path,
+    server_name: Option<&str>,
+) -> SynapticResult<Vec<LogFileInfo>> {
+    let dirs: Vec<PathBuf> = match server_name {
+        Some(name) => vec![root.join(log_dir_name(name))],
+        None => match fs::read_dir(root) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect(),
+            Err(_) => Vec::new(),
+        },
+    };
+
+    let mut files = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let server = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("log") {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+
+            files.push(LogFileInfo {
+                server_name: server.clone(),
+                file_name: entry.file_name().to_string_lossy().to_string(),
+                path: path.to_string_lossy().to_string(),
+                size_bytes: metadata.len(),
+                modified: metadata
+                    .modified()
+                    .map(DateTime::<Utc>::from)
+                    .unwrap_or_else(|_| Utc::now()),
+            });
+        }
+    }
+
+    files.sort_by(|a, b| {
+        b.modified
+            .cmp(&a.modified)
+            .then_with(|| b.file_name.cmp(&a.file_name))
+    });
+    Ok(files)
+}
+
+/// Last `lines` lines of a server's log (the newest file unless one is named)
+pub fn tail_server_log(
+    server_name: &str,
+    file_name: Option<&str>,
+    lines: usize,
+) -> SynapticResult<Vec<String>> {
+    tail_server_log_in(&get_server_logs_dir()?, server_name, file_name, lines)
+}
+
+/// Path-parameterized version of [`tail_server_log`]
+pub fn tail_server_log_in(
+    root: &Path,
+    server_name: &str,
+    file_name: Option<&str>,
+    lines: usize,
+) -> SynapticResult<Vec<String>> {
+    let path = match file_name {
+        Some(file_name) => {
+            if file_name.contains(['/', '\\']) || file_name.starts_with('.') {
+                return Err(SynapticError::ConfigReadError(format!(
+                    "Invalid log file name: {}",
+                    file_name
+                )));
+            }
+            root.join(log_dir_name(server_name)).join(file_name)
+        }
+        None => match list_server_logs_in(root, Some(server_name))?
+            .into_iter()
+            .next()
+        {
+            Some(newest) => PathBuf::from(newest.path),
+            None => return Ok(Vec::new()),
+        },
+    };
+
+    let bytes = fs::read(&path).map_err(|e| {
+        SynapticError::ConfigReadError(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    let content = String::from_utf8_lossy(&bytes);
+    let all: Vec<&str> = content.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    Ok(all[start..].iter().map(|l| l.to_string()).collect())
+}
+
+/// Open a server's log directory in the system file manager
+pub fn open_server_log_dir(server_name: &str) -> SynapticResult<PathBuf> {
+    let dir = get_server_logs_dir()?.join(log_dir_name(server_name));
+    fs::create_dir_all(&dir).map_err(|e| {
+        SynapticError::ConfigWriteError(format!("Failed to create {}: {}", dir.display(), e))
+    })?;
+
+    #[cfg(target_os = "macos")]
+    let opener = "open";
+    #[cfg(target_os = "windows")]
+    let opener = "explorer";
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let opener = "xdg-open";
+
+    std::process::Command::new(opener)
+        .arg(&dir)
+        .spawn()
+        .map_err(|e| {
+            SynapticError::ProcessError(format!("Failed to open {}: {}", dir.display(), e))
+        })?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root() -> PathBuf {
+        std::env::temp_dir().join(format!("synaptic-logs-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_log_dir_name_is_a_single_component() {
+        assert_eq!(log_dir_name("github"), "github");
+        assert_eq!(log_dir_name("../etc/passwd"), ".._etc_passwd");
+        assert_eq!(log_dir_name(".."), "___");
+        assert_eq!(log_dir_name(""), "_");
+    }
+
+    #[test]
+    fn test_rotates_by_size_and_tails() {
+        let root = temp_root();
+        let mut log = ServerLog::new(&root, "echo", 200);
+
+        for i in 0..20 {
+            log.write_line(LogStream::Stdout, &format!("line {}", i))
+                .unwrap();
+        }
+        log.write_line(LogStream::Stderr, "boom").unwrap();
+
+        let files = list_server_logs_in(&root, Some("echo")).unwrap();
+        assert!(files.len() > 1);
+        assert!(files.len() <= ROTATED_FILES_KEPT + 1);
+        assert!(files.iter().all(|f| f.size_bytes <= 200));
+
+        let tail = tail_server_log_in(&root, "echo", None, 2).unwrap();
+        assert_eq!(tail.len(), 2);
+        assert!(tail[0].ends_with("[stdout] line 19"));
+        assert!(tail[1].ends_with("[stderr] boom"));
+
+        assert!(tail_server_log_in(&root, "echo", Some("../x.log"), 1).is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,490 @@
+//! Export and import of logged sessions
+//!
+//! Exports feed external tools and bug reports; importing one (captured on
+//! another machine, say) stores it as a new read-only session.
+//!
+//! Two formats are supported:
+//! - `jsonl`: one JSON object per line, the session first and then every
+//!   logged message in order, each tagged with a `type`
+//! - `har`: a HAR-inspired document pairing each JSON-RPC request with its
+//!   response and timing, with notifications and stderr kept alongside
+
+use crate::database::Database;
+use crate::error::{SynapticError, SynapticResult};
+use crate::redaction::Redactor;
+use crate::system_logs::{self, LogEntry, SessionInfo};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// File format of an exported session
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionFormat {
+    Jsonl,
+    Har,
+}
+
+/// One line of a JSONL export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonlLine {
+    Session(SessionInfo),
+    Message(LogEntry),
+}
+
+/// Root of a HAR-like export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarDocument {
+    pub log: HarLog,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarLog {
+    pub version: String,
+    pub creator: HarCreator,
+    pub session: SessionInfo,
+    /// Requests paired with their responses, in request order
+    pub entries: Vec<HarEntry>,
+    /// Messages that aren't part of a request/response pair
+    pub messages: Vec<LogEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarCreator {
+    pub name: String,
+    pub version: String,
+}
+
+/// A JSON-RPC request and, if one arrived, its response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    pub started_date_time: DateTime<Utc>,
+    /// Milliseconds until the response (none when unanswered)
+    pub time: Option<i64>,
+    pub server_name: Option<String>,
+    /// Direction the request travelled (`OUTGOING` for client requests)
+    pub direction: Option<String>,
+    pub method: Option<String>,
+    pub request_id: Option<String>,
+    pub request: Option<Value>,
+    pub response: Option<Value>,
+    /// A `notifications/cancelled` for the request followed it
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// Summary returned after a successful export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionExport {
+    pub path: String,
+    pub format: SessionFormat,
+    pub message_count: usize,
+    pub size_bytes: u64,
+}
+
+/// Write a logged session to `dest` in the given format, redacted by `redactor`
+pub fn export_session(
+    db: &Database,
+    session_id: &str,
+    format: SessionFormat,
+    dest: &Path,
+    redactor: &Redactor,
+) -> SynapticResult<SessionExport> {
+    let (session, entries) = load_session(db, session_id, redactor)?;
+
+    let message_count = entries.len();
+    let content = match format {
+        SessionFormat::Jsonl => to_jsonl(session, entries)?,
+        SessionFormat::Har => serde_json::to_string_pretty(&to_har(session, entries))?,
+    };
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(dest, &content)?;
+
+    Ok(SessionExport {
+        path: dest.to_string_lossy().to_string(),
+        format,
+        message_count,
+        size_bytes: content.len() as u64,
+    })
+}
+
+/// A session and its messages, with payloads passed through `redactor` once more
+///
+/// Rows logged before a rule was added thus don't leave the database unredacted.
+pub(crate) fn load_session(
+    db: &Database,
+    session_id: &str,
+    redactor: &Redactor,
+) -> SynapticResult<(SessionInfo, Vec<LogEntry>)> {
+    let mut entries = system_logs::session_entries(db, session_id)?;
+    for entry in &mut entries {
+        if let Some(payload) = &entry.payload {
+            entry.payload = Some(redactor.redact_value(payload));
+        }
+    }
+    let session = match system_logs::get_session(db, session_id)? {
+        Some(session) => session,
+        // Sessions recorded without metadata still export from their rows
+        None if !entries.is_empty() => SessionInfo {
+            id: session_id.to_string(),
+            started_at: entries[0].timestamp,
+            ended_at: entries.last().map(|entry| entry.timestamp),
+            server_name: entries[0].server_name.clone(),
+            log_count: entries.len() as i64,
+            description: None,
+            imported_from: None,
+        },
+        None => {
+            return Err(SynapticError::InspectorError(format!(
+                "Session not found: {}",
+                session_id
+            )))
+        }
+    };
+    Ok((session, entries))
+}
+
+fn to_jsonl(session: SessionInfo, entries: Vec<LogEntry>) -> SynapticResult<String> {
+    let mut out = String::new();
+    for line in std::iter::once(JsonlLine::Session(session))
+        .chain(entries.into_iter().map(JsonlLine::Message))
+    {
+        out.push_str(&serde_json::to_string(&line)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Pair requests with the responses travelling the other way
+pub fn to_har(session: SessionInfo, entries: Vec<LogEntry>) -> HarDocument {
+    let mut paired: Vec<HarEntry> = Vec::new();
+    let mut messages = Vec::new();
+    // Open requests by (direction, JSON-RPC id) -> index in `paired`
+    let mut open: HashMap<(Option<String>, String), usize> = HashMap::new();
+
+    for entry in entries {
+        let is_request = entry.message.is_some();
+        match (&entry.trace_id, is_request) {
+            (Some(id), true) => {
+                open.insert((entry.direction.clone(), id.clone()), paired.len());
+                paired.push(HarEntry {
+                    started_date_time: entry.timestamp,
+                    time: None,
+                    server_name: entry.server_name,
+                    direction: entry.direction,
+                    method: entry.message,
+                    request_id: Some(id.clone()),
+                    request: entry.payload,
+                    response: None,
+                    cancelled: false,
+                });
+            }
+            (Some(id), false) => {
+                let key = (opposite(entry.direction.as_deref()), id.clone());
+                match open.remove(&key) {
+                    Some(index) => {
+                        let har = &mut paired[index];
+                        har.time =
+                            Some((entry.timestamp - har.started_date_time).num_milliseconds());
+                        har.response = entry.payload;
+                    }
+                    None => messages.push(entry),
+                }
+            }
+            _ => {
+                // A late response may still pair with a cancelled request
+                if let Some(id) = cancelled_id(&entry) {
+                    if let Some(&index) = open.get(&(entry.direction.clone(), id)) {
+                        paired[index].cancelled = true;
+                    }
+                }
+                messages.push(entry);
+            }
+        }
+    }
+
+    HarDocument {
+        log: HarLog {
+            version: "1.2".to_string(),
+            creator: HarCreator {
+                name: "WeaR Synaptic".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            session,
+            entries: paired,
+            messages,
+        },
+    }
+}
+
+/// JSON-RPC id of the request a logged `notifications/cancelled` cancels
+fn cancelled_id(entry: &LogEntry) -> Option<String> {
+    if entry.message.as_deref() != Some("notifications/cancelled") {
+        return None;
+    }
+    let id = entry.payload.as_ref()?.pointer("/params/requestId")?;
+    Some(id.to_string())
+}
+
+/// Load an exported session (JSONL or HAR-like) from `path` as a new read-only session
+pub fn import_session(db: &Database, path: &Path) -> SynapticResult<SessionInfo> {
+    let content = fs::read_to_string(path)?;
+    let not_an_export = |e: serde_json::Error| {
+        SynapticError::InspectorError(format!("{} is not a session export: {}", path.display(), e))
+    };
+
+    let (session, mut entries) = match serde_json::from_str::<HarDocument>(&content) {
+        Ok(doc) => from_har(doc),
+        Err(har_error) if content.trim_start().starts_with("{\"log\"") => {
+            return Err(not_an_export(har_error))
+        }
+        Err(_) => from_jsonl(&content).map_err(not_an_export)?,
+    };
+    let session = session.ok_or_else(|| {
+        SynapticError::InspectorError(format!("{} has no session header", path.display()))
+    })?;
+    entries.sort_by_key(|entry| entry.timestamp);
+
+    let imported = SessionInfo {
+        id: uuid::Uuid::new_v4().to_string(),
+        log_count: entries.len() as i64,
+        imported_from: Some(path.to_string_lossy().to_string()),
+        ..session
+    };
+    system_logs::insert_session(db, &imported, &entries)?;
+    Ok(imported)
+}
+
+fn from_jsonl(content: &str) -> Result<(Option<SessionInfo>, Vec<LogEntry>), serde_json::Error> {
+    let mut session = None;
+    let mut entries = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line)? {
+            JsonlLine::Session(info) => session = Some(info),
+            JsonlLine::Message(entry) => entries.push(entry),
+        }
+    }
+    Ok((session, entries))
+}
+
+/// Split paired entries back into request and response rows
+fn from_har(doc: HarDocument) -> (Option<SessionInfo>, Vec<LogEntry>) {
+    let HarLog {
+        session,
+        entries,
+        mut messages,
+        ..
+    } = doc.log;
+    let row = |timestamp, message, payload, trace_id, server_name, direction| LogEntry {
+        id: 0,
+        session_id: session.id.clone(),
+        timestamp,
+        level: "INFO".to_string(),
+        category: "TRAFFIC".to_string(),
+        message,
+        payload,
+        trace_id,
+        server_name,
+        direction,
+    };
+
+    for entry in entries {
+        if entry.response.is_some() {
+            let answered_at =
+                entry.started_date_time + chrono::Duration::milliseconds(entry.time.unwrap_or(0));
+            messages.push(row(
+                answered_at,
+                None,
+                entry.response,
+                entry.request_id.clone(),
+                entry.server_name.clone(),
+                opposite(entry.direction.as_deref()),
+            ));
+        }
+        messages.push(row(
+            entry.started_date_time,
+            entry.method,
+            entry.request,
+            entry.request_id,
+            entry.server_name,
+            entry.direction,
+        ));
+    }
+    (Some(session), messages)
+}
+
+fn opposite(direction: Option<&str>) -> Option<String> {
+    match direction {
+        Some("INCOMING") => Some("OUTGOING".to_string()),
+        Some("OUTGOING") => Some("INCOMING".to_string()),
+        other => other.map(String::from),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_manager::McpTrafficEvent;
+
+    fn event(direction: &str, timestamp: &str, content: &str) -> McpTrafficEvent {
+        McpTrafficEvent {
+            server_id: "echo".to_string(),
+            timestamp: timestamp.to_string(),
+            direction: direction.to_string(),
+            content: content.to_string(),
+            message_id: uuid::Uuid::new_v4().to_string(),
+            level: None,
+        }
+    }
+
+    #[test]
+    fn test_export_and_import_roundtrip() {
+        let db = Database::open_in_memory().unwrap();
+        system_logs::insert_traffic(
+            &db,
+            "s1",
+            &[
+                event(
+                    "OUTGOING",
+                    "2025-01-01T00:00:00Z",
+                    r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+                ),
+                event(
+                    "OUTGOING",
+                    "2025-01-01T00:00:00.100Z",
+                    r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+                ),
+                event(
+                    "INCOMING",
+                    "2025-01-01T00:00:00.250Z",
+                    r#"{"jsonrpc":"2.0","id":1,"result":{"tools":[]}}"#,
+                ),
+            ],
+        )
+        .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("synaptic-export-{}", uuid::Uuid::new_v4()));
+        let redactor = Redactor::default();
+        let har = export_session(
+            &db,
+            "s1",
+            SessionFormat::Har,
+            &dir.join("s1.har"),
+            &redactor,
+        )
+        .unwrap();
+        assert_eq!(har.message_count, 3);
+
+        let doc: HarDocument =
+            serde_json::from_str(&fs::read_to_string(dir.join("s1.har")).unwrap()).unwrap();
+        assert_eq!(doc.log.entries.len(), 1);
+        assert_eq!(doc.log.entries[0].time, Some(250));
+        assert_eq!(
+            doc.log.entries[0].response.as_ref().unwrap()["result"]["tools"],
+            serde_json::json!([])
+        );
+        assert_eq!(doc.log.messages.len(), 1);
+
+        export_session(
+            &db,
+            "s1",
+            SessionFormat::Jsonl,
+            &dir.join("s1.jsonl"),
+            &redactor,
+        )
+        .unwrap();
+        let lines: Vec<JsonlLine> = fs::read_to_string(dir.join("s1.jsonl"))
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert!(matches!(&lines[0], JsonlLine::Session(session) if session.id == "s1"));
+
+        assert!(export_session(
+            &db,
+            "missing",
+            SessionFormat::Jsonl,
+            &dir.join("x"),
+            &redactor
+        )
+        .is_err());
+
+        // Both formats import back as read-only copies with the same traffic
+        for file in ["s1.har", "s1.jsonl"] {
+            let imported = import_session(&db, &dir.join(file)).unwrap();
+            assert_ne!(imported.id, "s1");
+            assert!(imported.imported_from.is_some());
+            let rows = system_logs::session_entries(&db, &imported.id).unwrap();
+            assert_eq!(rows.len(), 3);
+            assert_eq!(rows[0].message.as_deref(), Some("tools/list"));
+            assert_eq!(rows[2].trace_id.as_deref(), Some("1"));
+
+            // Kept out of the live views and closed to writes
+            let late = event("STDERR", "2025-01-01T00:00:01Z", "late");
+            assert!(system_logs::insert_traffic(&db, &imported.id, &[late]).is_err());
+        }
+        assert_eq!(
+            system_logs::query(&db, &Default::default()).unwrap().len(),
+            3
+        );
+        assert_eq!(
+            system_logs::search(&db, "tools", &Default::default())
+                .unwrap()
+                .len(),
+            2
+        );
+        let timeline =
+            system_logs::timeline(&db, None, 60, &crate::tool_stats::TimeRange::default()).unwrap();
+        assert_eq!(timeline.iter().map(|b| b.outgoing).sum::<u64>(), 2);
+        fs::write(dir.join("junk.txt"), "not json").unwrap();
+        assert!(import_session(&db, &dir.join("junk.txt")).is_err());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_har_marks_cancelled_requests() {
+        let db = Database::open_in_memory().unwrap();
+        system_logs::insert_traffic(
+            &db,
+            "s1",
+            &[
+                event(
+                    "OUTGOING",
+                    "2025-01-01T00:00:00Z",
+                    r#"{"jsonrpc":"2.0","id":"a","method":"tools/call"}"#,
+                ),
+                event(
+                    "OUTGOING",
+                    "2025-01-01T00:00:01Z",
+                    r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":"a"}}"#,
+                ),
+            ],
+        )
+        .unwrap();
+        let session = SessionInfo {
+            id: "s1".to_string(),
+            started_at: Utc::now(),
+            ended_at: None,
+            server_name: None,
+            log_count: 2,
+            description: None,
+            imported_from: None,
+        };
+
+        let doc = to_har(session, system_logs::session_entries(&db, "s1").unwrap());
+        assert!(doc.log.entries[0].cancelled);
+        assert!(doc.log.entries[0].response.is_none());
+        assert_eq!(doc.log.messages.len(), 1);
+    }
+}
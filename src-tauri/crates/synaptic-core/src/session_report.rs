@@ -0,0 +1,535 @@
+//! Markdown reports of logged sessions
+//!
+//! A report condenses a session into something that can be pasted straight
+//! into an issue against a misbehaving server: a traffic summary, the
+//! server's capabilities, per-tool call statistics, the errors seen, and the
+//! exchanges that were bookmarked with an annotation during the session.
+//! Payloads go through the redactor like any export, and long ones are
+//! truncated so the report stays readable.
+
+use crate::bookmarks;
+use crate::capabilities;
+use crate::database::Database;
+use crate::error::SynapticResult;
+use crate::framing::truncate;
+use crate::redaction::Redactor;
+use crate::session_archive::{self, HarEntry};
+use crate::system_logs::{LogEntry, SessionInfo};
+use crate::tool_stats::percentile;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Most errors listed; the rest are only counted
+const MAX_ERRORS: usize = 50;
+
+/// Most recent stderr lines quoted
+const MAX_STDERR_LINES: usize = 20;
+
+/// Longest payload quoted in an exchange, in bytes
+const MAX_PAYLOAD_BYTES: usize = 2_000;
+
+/// Longest table cell, in characters
+const MAX_CELL_CHARS: usize = 120;
+
+/// Calls, errors and latencies of one tool
+#[derive(Default)]
+struct ToolTally {
+    calls: u64,
+    errors: u64,
+    latencies_ms: Vec<u64>,
+}
+
+/// Render a logged session as a Markdown report, redacted by `redactor`
+pub fn export_session_report(
+    db: &Database,
+    session_id: &str,
+    redactor: &Redactor,
+) -> SynapticResult<String> {
+    let (session, entries) = session_archive::load_session(db, session_id, redactor)?;
+    let servers: BTreeSet<String> = session
+        .server_name
+        .iter()
+        .chain(
+            entries
+                .iter()
+                .filter_map(|entry| entry.server_name.as_ref()),
+        )
+        .cloned()
+        .collect();
+    let har = session_archive::to_har(session, entries).log;
+    let session = har.session;
+
+    let mut out = Vec::new();
+    header(&mut out, &session, &servers);
+    summary(&mut out, &har.entries, &har.messages);
+    for server in &servers {
+        if let Some(capabilities) = capabilities::load(db, server)? {
+            capability_table(&mut out, server, &capabilities);
+        }
+    }
+    tool_statistics(&mut out, &har.entries);
+    errors(&mut out, &har.entries, &har.messages);
+
+    let until = session.ended_at.unwrap_or_else(Utc::now);
+    let mut annotated = Vec::new();
+    for server in &servers {
+        annotated.extend(
+            bookmarks::list(db, Some(server))?
+                .into_iter()
+                .filter(|bookmark| bookmark.annotation.is_some())
+                .filter(|bookmark| {
+                    (session.started_at..=until).contains(&bookmark.message.timestamp)
+                }),
+        );
+    }
+    annotated.sort_by_key(|bookmark| bookmark.message.timestamp);
+    if !annotated.is_empty() {
+        out.push("## Annotated exchanges".to_string());
+        out.push(String::new());
+        for bookmark in annotated {
+            let message = &bookmark.message;
+            let exchange = har.entries.iter().find(|entry| {
+                entry.server_name.as_deref() == Some(message.server_name.as_str())
+                    && entry.request_id.is_some()
+                    && entry.request_id == message.payload.get("id").map(|id| id.to_string())
+            });
+            out.push(format!(
+                "### `{}` at {}",
+                message.method.as_deref().unwrap_or("response"),
+                timestamp(message.timestamp)
+            ));
+            out.push(String::new());
+            for line in bookmark.annotation.as_deref().unwrap_or_default().lines() {
+                out.push(format!("> {}", line));
+            }
+            out.push(String::new());
+            match exchange {
+                Some(entry) => {
+                    if let Some(request) = &entry.request {
+                        out.push("Request:".to_string());
+                        out.push(String::new());
+                        code_block(&mut out, request);
+                    }
+                    match &entry.response {
+                        Some(response) => {
+                            out.push(format!("Response ({}):", duration(entry.time)));
+                            out.push(String::new());
+                            code_block(&mut out, response);
+                        }
+                        None => {
+                            out.push("No response.".to_string());
+                            out.push(String::new());
+                        }
+                    }
+                }
+                None => code_block(&mut out, &redactor.redact_value(&message.payload)),
+            }
+        }
+    }
+
+    Ok(out.join("\n"))
+}
+
+fn header(out: &mut Vec<String>, session: &SessionInfo, servers: &BTreeSet<String>) {
+    let title = match servers.len() {
+        0 => "Session report".to_string(),
+        _ => format!(
+            "Session report: {}",
+            servers.iter().cloned().collect::<Vec<_>>().join(", ")
+        ),
+    };
+    out.push(format!("# {}", title));
+    out.push(String::new());
+    out.push(format!("- **Session:** `{}`", session.id));
+    out.push(format!("- **Started:** {}", timestamp(session.started_at)));
+    if let Some(ended_at) = session.ended_at {
+        let seconds = (ended_at - session.started_at).num_milliseconds() as f64 / 1000.0;
+        out.push(format!(
+            "- **Ended:** {} ({:.1} s)",
+            timestamp(ended_at),
+            seconds
+        ));
+    }
+    out.push(format!("- **Messages:** {}", session.log_count));
+    if let Some(description) = &session.description {
+        out.push(format!("- **Description:** {}", description));
+    }
+    if let Some(file) = &session.imported_from {
+        out.push(format!("- **Imported from:** `{}`", file));
+    }
+    out.push(String::new());
+}
+
+fn summary(out: &mut Vec<String>, entries: &[HarEntry], messages: &[LogEntry]) {
+    let answered = entries.iter().filter(|e| e.response.is_some()).count();
+    let cancelled = entries.iter().filter(|e| e.cancelled).count();
+    let failed = entries
+        .iter()
+        .filter(|e| e.response.as_ref().is_some_and(is_error))
+        .count();
+    let stderr = messages.iter().filter(|m| is_stderr(m)).count();
+    let notifications = messages
+        .iter()
+        .filter(|m| !is_stderr(m) && m.message.is_some())
+        .count();
+
+    out.push("## Summary".to_string());
+    out.push(String::new());
+    out.push(
+        "| Requests | Answered | Errors | Unanswered | Cancelled | Notifications | Stderr lines |"
+            .to_string(),
+    );
+    out.push("|---:|---:|---:|---:|---:|---:|---:|".to_string());
+    out.push(format!(
+        "| {} | {} | {} | {} | {} | {} | {} |",
+        entries.len(),
+        answered,
+        failed,
+        entries.len() - answered,
+        cancelled,
+        notifications,
+        stderr
+    ));
+    out.push(String::new());
+
+    let mut methods: BTreeMap<&str, (u64, u64)> = BTreeMap::new();
+    for entry in entries {
+        let counts = methods
+            .entry(entry.method.as_deref().unwrap_or("(unknown)"))
+            .or_default();
+        counts.0 += 1;
+        if entry.response.as_ref().is_some_and(is_error) {
+            counts.1 += 1;
+        }
+    }
+    if !methods.is_empty() {
+        out.push("| Method | Requests | Errors |".to_string());
+        out.push("|---|---:|---:|".to_string());
+        for (method, (requests, errors)) in methods {
+            out.push(format!(
+                "| `{}` | {} | {} |",
+                cell(method),
+                requests,
+                errors
+            ));
+        }
+        out.push(String::new());
+    }
+}
+
+fn capability_table(
+    out: &mut Vec<String>,
+    server: &str,
+    capabilities: &capabilities::ServerCapabilities,
+) {
+    out.push(format!("## Capabilities of `{}`", server));
+    out.push(String::new());
+    let name = capabilities
+        .server_info
+        .get("name")
+        .and_then(|v| v.as_str());
+    let version = capabilities
+        .server_info
+        .get("version")
+        .and_then(|v| v.as_str());
+    if let Some(name) = name {
+        out.push(format!(
+            "- **Server:** {} {}",
+            name,
+            version.unwrap_or_default()
+        ));
+    }
+    out.push(format!(
+        "- **Protocol version:** {}",
+        capabilities
+            .protocol_version
+            .as_deref()
+            .unwrap_or("unknown")
+    ));
+    out.push(format!(
+        "- **Negotiated:** {}",
+        timestamp(capabilities.negotiated_at)
+    ));
+    out.push(String::new());
+
+    match capabilities.capabilities.as_object() {
+        Some(declared) if !declared.is_empty() => {
+            out.push("| Capability | Options |".to_string());
+            out.push("|---|---|".to_string());
+            for (capability, options) in declared {
+                out.push(format!(
+                    "| `{}` | {} |",
+                    cell(capability),
+                    cell(&options.to_string())
+                ));
+            }
+        }
+        _ => out.push("No capabilities declared.".to_string()),
+    }
+    out.push(String::new());
+}
+
+fn tool_statistics(out: &mut Vec<String>, entries: &[HarEntry]) {
+    let mut tools: BTreeMap<String, ToolTally> = BTreeMap::new();
+    for entry in entries {
+        if entry.method.as_deref() != Some("tools/call") {
+            continue;
+        }
+        let tool = entry
+            .request
+            .as_ref()
+            .and_then(|r| r.pointer("/params/name"))
+            .and_then(|n| n.as_str())
+            .unwrap_or("(unnamed)");
+        let tally = tools.entry(tool.to_string()).or_default();
+        tally.calls += 1;
+        if entry.response.as_ref().is_some_and(is_error) {
+            tally.errors += 1;
+        }
+        if let Some(time) = entry.time {
+            tally.latencies_ms.push(time.max(0) as u64);
+        }
+    }
+    if tools.is_empty() {
+        return;
+    }
+
+    out.push("## Tool calls".to_string());
+    out.push(String::new());
+    out.push("| Tool | Calls | Errors | p50 | p95 | Max |".to_string());
+    out.push("|---|---:|---:|---:|---:|---:|".to_string());
+    for (tool, mut tally) in tools {
+        tally.latencies_ms.sort_unstable();
+        let latency = |p: usize| {
+            if tally.latencies_ms.is_empty() {
+                "-".to_string()
+            } else {
+                format!("{} ms", percentile(&tally.latencies_ms, p))
+            }
+        };
+        out.push(format!(
+            "| `{}` | {} | {} | {} | {} | {} |",
+            cell(&tool),
+            tally.calls,
+            tally.errors,
+            latency(50),
+            latency(95),
+            latency(100)
+        ));
+    }
+    out.push(String::new());
+}
+
+fn errors(out: &mut Vec<String>, entries: &[HarEntry], messages: &[LogEntry]) {
+    let mut listed = Vec::new();
+    for entry in entries {
+        let Some(response) = entry.response.as_ref().filter(|r| is_error(r)) else {
+            continue;
+        };
+        let mut what = format!("`{}`", entry.method.as_deref().unwrap_or("(unknown)"));
+        if let Some(tool) = entry
+            .request
+            .as_ref()
+            .and_then(|r| r.pointer("/params/name"))
+            .and_then(|n| n.as_str())
+        {
+            what.push_str(&format!(" (`{}`)", tool));
+        }
+        listed.push(format!(
+            "- {} {}: {}",
+            timestamp(entry.started_date_time),
+            what,
+            error_text(response)
+        ));
+    }
+    // Error responses that couldn't be paired with a request
+    for message in messages {
+        if let Some(payload) = message
+            .payload
+            .as_ref()
+            .filter(|p| !is_stderr(message) && p.get("error").is_some())
+        {
+            listed.push(format!(
+                "- {} unmatched response: {}",
+                timestamp(message.timestamp),
+                error_text(payload)
+            ));
+        }
+    }
+    let stderr: Vec<&LogEntry> = messages.iter().filter(|m| is_stderr(m)).collect();
+    if listed.is_empty() && stderr.is_empty() {
+        return;
+    }
+
+    out.push("## Errors".to_string());
+    out.push(String::new());
+    if !listed.is_empty() {
+        let total = listed.len();
+        out.extend(listed.into_iter().take(MAX_ERRORS));
+        if total > MAX_ERRORS {
+            out.push(format!("- ... and {} more", total - MAX_ERRORS));
+        }
+        out.push(String::new());
+    }
+    if !stderr.is_empty() {
+        let skipped = stderr.len().saturating_sub(MAX_STDERR_LINES);
+        out.push(match skipped {
+            0 => "Stderr:".to_string(),
+            _ => format!("Last {} stderr lines:", MAX_STDERR_LINES),
+        });
+        out.push(String::new());
+        out.push("```text".to_string());
+        for message in &stderr[skipped..] {
+            let line = match &message.payload {
+                Some(Value::String(line)) => line.trim_end().to_string(),
+                Some(other) => other.to_string(),
+                None => String::new(),
+            };
+            out.push(truncate(&line, MAX_PAYLOAD_BYTES).into_owned());
+        }
+        out.push("```".to_string());
+        out.push(String::new());
+    }
+}
+
+/// JSON-RPC error responses and tool results flagged `isError`
+fn is_error(response: &Value) -> bool {
+    response.get("error").is_some()
+        || response
+            .pointer("/result/isError")
+            .and_then(|e| e.as_bool())
+            == Some(true)
+}
+
+fn is_stderr(entry: &LogEntry) -> bool {
+    entry.direction.as_deref() == Some("STDERR")
+}
+
+/// Code and message of a JSON-RPC error, or the text of a failed tool result
+fn error_text(response: &Value) -> String {
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or_default();
+        return match error.get("code").and_then(|c| c.as_i64()) {
+            Some(code) => format!("{} {}", code, message),
+            None => message.to_string(),
+        };
+    }
+    response
+        .pointer("/result/content")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+        .next()
+        .map(|text| format!("tool error: {}", cell(text)))
+        .unwrap_or_else(|| "tool error".to_string())
+}
+
+fn code_block(out: &mut Vec<String>, payload: &Value) {
+    let text = serde_json::to_string_pretty(payload).unwrap_or_else(|_| payload.to_string());
+    out.push("```json".to_string());
+    out.push(truncate(&text, MAX_PAYLOAD_BYTES).into_owned());
+    out.push("```".to_string());
+    out.push(String::new());
+}
+
+/// Text made safe for a single table cell
+fn cell(text: &str) -> String {
+    let flat = text.replace(['\r', '\n'], " ").replace('|', "\\|");
+    if flat.chars().count() > MAX_CELL_CHARS {
+        format!("{}…", flat.chars().take(MAX_CELL_CHARS).collect::<String>())
+    } else {
+        flat
+    }
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S%.3f UTC").to_string()
+}
+
+fn duration(time: Option<i64>) -> String {
+    match time {
+        Some(ms) => format!("{} ms", ms),
+        None => "no timing".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_manager::McpTrafficEvent;
+    use crate::system_logs;
+
+    fn event(direction: &str, timestamp: &str, content: &str) -> McpTrafficEvent {
+        McpTrafficEvent {
+            server_id: "echo".to_string(),
+            timestamp: timestamp.to_string(),
+            direction: direction.to_string(),
+            content: content.to_string(),
+            message_id: uuid::Uuid::new_v4().to_string(),
+            level: None,
+        }
+    }
+
+    #[test]
+    fn test_report_summarizes_tools_and_errors() {
+        let db = Database::open_in_memory().unwrap();
+        system_logs::insert_traffic(
+            &db,
+            "s1",
+            &[
+                event(
+                    "OUTGOING",
+                    "2025-01-01T00:00:00Z",
+                    r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"search"}}"#,
+                ),
+                event(
+                    "INCOMING",
+                    "2025-01-01T00:00:00.040Z",
+                    r#"{"jsonrpc":"2.0","id":1,"result":{"content":[]}}"#,
+                ),
+                event(
+                    "OUTGOING",
+                    "2025-01-01T00:00:01Z",
+                    r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"search"}}"#,
+                ),
+                event(
+                    "INCOMING",
+                    "2025-01-01T00:00:01.100Z",
+                    r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32602,"message":"Bad | query"}}"#,
+                ),
+                event(
+                    "STDERR",
+                    "2025-01-01T00:00:01.200Z",
+                    "panic: index out of range",
+                ),
+                event(
+                    "OUTGOING",
+                    "2025-01-01T00:00:02Z",
+                    r#"{"jsonrpc":"2.0","id":3,"method":"ping"}"#,
+                ),
+            ],
+        )
+        .unwrap();
+
+        let report = export_session_report(&db, "s1", &Redactor::default()).unwrap();
+
+        assert!(report.starts_with("# Session report: echo"));
+        assert!(report.contains("| 3 | 2 | 1 | 1 | 0 | 0 | 1 |"));
+        assert!(report.contains("| `tools/call` | 2 | 1 |"));
+        assert!(report.contains("| `search` | 2 | 1 | 40 ms | 100 ms | 100 ms |"));
+        assert!(report.contains("`tools/call` (`search`): -32602 Bad | query"));
+        assert!(report.contains("panic: index out of range"));
+        assert!(!report.contains("## Annotated exchanges"));
+    }
+
+    #[test]
+    fn test_missing_session_is_an_error() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(export_session_report(&db, "nope", &Redactor::default()).is_err());
+    }
+}
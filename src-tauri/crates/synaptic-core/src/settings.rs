@@ -0,0 +1,407 @@
+//! Synaptic application settings
+//!
+//! Backend behaviour knobs persisted as `settings.json` in the Synaptic data
+//! directory. Unknown or missing fields fall back to defaults so older files
+//! keep loading as new settings are added.
+
+use crate::config::get_synaptic_data_dir;
+use crate::error::{SynapticError, SynapticResult};
+use crate::history_retention::HistoryRetention;
+use crate::installer::ManagedExecutable;
+use crate::oversized::OversizedPolicy;
+use crate::process_manager::{executable_name, DEFAULT_ALLOWED_EXECUTABLES};
+use crate::policy::ToolPolicy;
+use crate::rate_limit::RateLimit;
+use crate::redaction::{RedactionMode, RedactionRule};
+use crate::schedules::ServerSchedule;
+use crate::stdin_queue::StdinOverflow;
+use crate::traffic_mirror::TrafficMirror;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Persisted backend settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SynapticSettings {
+    /// Hours between scheduled config snapshots (0 disables them)
+    pub snapshot_interval_hours: u64,
+
+    /// Reject every config mutation (safe mode for demos and production machines)
+    pub read_only: bool,
+
+    /// Milliseconds each shutdown stage (close stdin, SIGTERM) gets before escalating
+    pub shutdown_grace_ms: u64,
+
+    /// Milliseconds a stop waits for in-flight requests to be answered (0 stops right away)
+    pub drain_timeout_ms: u64,
+
+    /// Milliseconds a spawned server has to produce its first output (0 disables the wait)
+    pub startup_timeout_ms: u64,
+
+    /// Largest message handled whole (0 means unlimited)
+    pub max_message_bytes: usize,
+
+    /// Whether larger messages are truncated, rejected with an error or passed on whole
+    pub oversized_policy: OversizedPolicy,
+
+    /// Servers allowed to start at the same time; the rest queue (0 means unlimited)
+    pub max_concurrent_starts: usize,
+
+    /// Restart a running server when an edit changes how it is launched
+    pub restart_on_config_change: bool,
+
+    /// Messages that may wait for a server's stdin
+    pub stdin_queue_capacity: usize,
+
+    /// What a send does when the stdin queue is full
+    pub stdin_overflow: StdinOverflow,
+
+    /// Daily windows during which servers are started and outside of which they are stopped
+    pub schedules: Vec<ServerSchedule>,
+
+    /// Minutes without JSON-RPC traffic after which a server is stopped (0 disables)
+    pub idle_timeout_mins: u64,
+
+    /// Seconds a request may go unanswered before it is flagged (0 disables)
+    pub request_timeout_secs: u64,
+
+    /// Captured messages kept in memory per server; older ones are read from the database
+    pub inspector_history_cap: usize,
+
+    /// Per-server overrides of how captured traffic is kept in memory and mirrored to the database
+    pub history_retention: Vec<HistoryRetention>,
+
+    /// NDJSON files servers' traffic is appended to as it happens
+    pub traffic_mirrors: Vec<TrafficMirror>,
+
+    /// Deny every `sampling/createMessage` request from a server instead of asking
+    pub sampling_auto_deny: bool,
+
+    /// Patterns redacted from captured traffic, on top of servers' env values
+    pub redaction_rules: Vec<RedactionRule>,
+
+    /// Whether redacted values are masked or stored as truncated hashes
+    pub redaction_mode: RedactionMode,
+
+    /// Allow, deny or ask about `tools/call` requests; the first matching policy decides
+    pub tool_policies: Vec<ToolPolicy>,
+
+    /// Caps on tool calls per minute, per server or per tool
+    pub rate_limits: Vec<RateLimit>,
+
+    /// Serve every enabled server behind one MCP endpoint (takes effect on restart)
+    pub gateway_enabled: bool,
+
+    /// Loopback port of the gateway endpoint
+    pub gateway_port: u16,
+
+    /// Base URL of the MCP registry servers are listed from (empty for the builtin list only)
+    pub registry_url: String,
+
+    /// Minutes a synced registry listing is used before it is synced again
+    pub registry_ttl_mins: u64,
+
+    /// Never contact the registry; browse the cached listing (or the builtin one)
+    pub registry_offline: bool,
+
+    /// Executables servers may be spawned with
+    pub allowed_executables: Vec<String>,
+
+    /// Executables the user refused to approve (never prompted again)
+    pub denied_executables: Vec<String>,
+
+    /// Binaries Synaptic installed, each allowed only for the server it was installed as
+    pub managed_executables: Vec<ManagedExecutable>,
+
+    /// Base64 Ed25519 public keys binary downloads may be signed with, on top of the pinned ones
+    pub trusted_signing_keys: Vec<String>,
+}
+
+impl Default for SynapticSettings {
+    fn default() -> Self {
+        Self {
+            snapshot_interval_hours: 24,
+            read_only: false,
+            shutdown_grace_ms: 3_000,
+            drain_timeout_ms: 5_000,
+            startup_timeout_ms: 30_000,
+            max_message_bytes: 8 * 1024 * 1024,
+            oversized_policy: OversizedPolicy::Truncate,
+            max_concurrent_starts: 4,
+            restart_on_config_change: false,
+            stdin_queue_capacity: 100,
+            stdin_overflow: StdinOverflow::Block,
+            schedules: Vec::new(),
+            idle_timeout_mins: 0,
+            request_timeout_secs: 60,
+            inspector_history_cap: 1_000,
+            history_retention: Vec::new(),
+            traffic_mirrors: Vec::new(),
+            sampling_auto_deny: false,
+            redaction_rules: Vec::new(),
+            redaction_mode: RedactionMode::Mask,
+            tool_policies: Vec::new(),
+            rate_limits: Vec::new(),
+            gateway_enabled: false,
+            gateway_port: crate::gateway::DEFAULT_GATEWAY_PORT,
+            registry_url: crate::registry::OFFICIAL_REGISTRY_URL.to_string(),
+            registry_ttl_mins: 60,
+            registry_offline: false,
+            allowed_executables: DEFAULT_ALLOWED_EXECUTABLES
+                .iter()
+                .map(|e| e.to_string())
+                .collect(),
+            denied_executables: Vec::new(),
+            managed_executables: Vec::new(),
+            trusted_signing_keys: Vec::new(),
+        }
+    }
+}
+
+/// Get the settings file path
+pub fn get_settings_path() -> SynapticResult<PathBuf> {
+    Ok(get_synaptic_data_dir()?.join("settings.json"))
+}
+
+impl SynapticSettings {
+    /// Load settings from a file, returning defaults if it doesn't exist
+    pub fn load_from(path: &Path) -> SynapticResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| {
+            SynapticError::ConfigParseError(format!("Failed to parse {}: {}", path.display(), e))
+        })
+    }
+
+    /// Write settings to a file
+    pub fn save_to(&self, path: &Path) -> SynapticResult<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).map_err(|e| {
+            SynapticError::ConfigWriteError(format!("Failed to write {}: {}", path.display(), e))
+        })
+    }
+
+    /// Load settings from the default location
+    pub fn load() -> SynapticResult<Self> {
+        Self::load_from(&get_settings_path()?)
+    }
+
+    /// Save settings to the default location
+    pub fn save(&self) -> SynapticResult<()> {
+        self.save_to(&get_settings_path()?)
+    }
+
+    /// Record the user's decision about an executable
+    ///
+    /// Approval moves it to the whitelist; denial remembers it so the user
+    /// is not asked again.
+    pub fn set_executable_approval(&mut self, executable: &str, approved: bool) {
+        let name = executable_name(executable);
+        self.allowed_executables
+            .retain(|e| executable_name(e) != name);
+        self.denied_executables
+            .retain(|e| executable_name(e) != name);
+
+        if approved {
+            self.allowed_executables.push(name);
+        } else {
+            self.denied_executables.push(name);
+        }
+    }
+
+    /// Remove an executable from the whitelist
+    pub fn remove_allowed_executable(&mut self, executable: &str) -> bool {
+        let name = executable_name(executable);
+        let before = self.allowed_executables.len();
+        self.allowed_executables
+            .retain(|e| executable_name(e) != name);
+        self.allowed_executables.len() != before
+    }
+
+    /// Allow the binary installed for a server at `path`, or drop it with `None`
+    ///
+    /// Only that file is allowed, and only for that server: the whitelist and
+    /// the user's denials are left as they are.
+    pub fn set_managed_executable(&mut self, server_name: &str, path: Option<PathBuf>) {
+        self.managed_executables
+            .retain(|managed| managed.server_name != server_name);
+        if let Some(path) = path {
+            self.managed_executables.push(ManagedExecutable {
+                server_name: server_name.to_string(),
+                path,
+            });
+        }
+    }
+
+    /// Paths of the binaries installed for a server
+    pub fn managed_executables_of(&self, server_name: &str) -> Vec<String> {
+        self.managed_executables
+            .iter()
+            .filter(|managed| managed.server_name == server_name)
+            .map(|managed| managed.path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// Replace a server's history retention, or go back to the default with `None`
+    pub fn set_history_retention(
+        &mut self,
+        server_name: &str,
+        retention: Option<HistoryRetention>,
+    ) {
+        self.history_retention
+            .retain(|entry| entry.server_name != server_name);
+        if let Some(retention) = retention {
+            self.history_retention.push(HistoryRetention {
+                server_name: server_name.to_string(),
+                ..retention
+            });
+        }
+    }
+
+    /// Mirror a server's traffic to `path`, or stop mirroring it with `None`
+    pub fn set_traffic_mirror(&mut self, server_name: &str, path: Option<PathBuf>) {
+        self.traffic_mirrors
+            .retain(|mirror| mirror.server_name != server_name);
+        if let Some(path) = path {
+            self.traffic_mirrors.push(TrafficMirror {
+                server_name: server_name.to_string(),
+                path,
+                enabled: true,
+            });
+        }
+    }
+
+    /// Grace period per process shutdown stage
+    pub fn shutdown_grace(&self) -> Duration {
+        Duration::from_millis(self.shutdown_grace_ms)
+    }
+
+    /// How long a stop waits for in-flight requests to be answered
+    pub fn drain_timeout(&self) -> Duration {
+        Duration::from_millis(self.drain_timeout_ms)
+    }
+
+    /// How long a server may go without traffic before it is stopped, if at all
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.idle_timeout_mins > 0).then(|| Duration::from_secs(self.idle_timeout_mins * 60))
+    }
+
+    /// How long a request may wait for its response before it is flagged, if at all
+    pub fn request_timeout(&self) -> Option<Duration> {
+        (self.request_timeout_secs > 0).then(|| Duration::from_secs(self.request_timeout_secs))
+    }
+
+    /// How long to wait for a spawned server to become ready, if at all
+    pub fn startup_timeout(&self) -> Option<Duration> {
+        (self.startup_timeout_ms > 0).then(|| Duration::from_millis(self.startup_timeout_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let settings: SynapticSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, SynapticSettings::default());
+    }
+
+    #[test]
+    fn test_executable_approval_decisions() {
+        let mut settings = SynapticSettings::default();
+
+        settings.set_executable_approval("/usr/bin/Bash", false);
+        assert_eq!(settings.denied_executables, vec!["bash"]);
+
+        // Changing your mind moves the entry across
+        settings.set_executable_approval("bash", true);
+        assert!(settings.denied_executables.is_empty());
+        assert!(settings.allowed_executables.contains(&"bash".to_string()));
+
+        assert!(settings.remove_allowed_executable("BASH.exe"));
+        assert!(!settings.remove_allowed_executable("bash"));
+    }
+
+    #[test]
+    fn test_roundtrip_file() {
+        let path = std::env::temp_dir()
+            .join(format!("synaptic-settings-{}", uuid::Uuid::new_v4()))
+            .join("settings.json");
+
+        let settings = SynapticSettings {
+            snapshot_interval_hours: 6,
+            read_only: true,
+            shutdown_grace_ms: 500,
+            drain_timeout_ms: 1_000,
+            startup_timeout_ms: 10_000,
+            max_message_bytes: 1024,
+            oversized_policy: OversizedPolicy::Reject,
+            max_concurrent_starts: 2,
+            restart_on_config_change: true,
+            stdin_queue_capacity: 10,
+            stdin_overflow: StdinOverflow::DropOldest,
+            schedules: vec![ServerSchedule {
+                server_name: "indexer".to_string(),
+                days: vec![chrono::Weekday::Mon],
+                start: "09:00".parse().unwrap(),
+                stop: "18:00".parse().unwrap(),
+                enabled: true,
+            }],
+            idle_timeout_mins: 15,
+            request_timeout_secs: 30,
+            inspector_history_cap: 200,
+            history_retention: vec![HistoryRetention {
+                clear_on_disconnect: true,
+                history_cap: Some(50),
+                mirror: crate::history_retention::HistoryMirror::Errors,
+                ..HistoryRetention::new("scratch")
+            }],
+            traffic_mirrors: vec![TrafficMirror {
+                server_name: "fs".to_string(),
+                path: PathBuf::from("/tmp/fs.ndjson"),
+                enabled: true,
+            }],
+            sampling_auto_deny: true,
+            redaction_rules: crate::redaction::presets(),
+            redaction_mode: RedactionMode::Hash,
+            tool_policies: vec![ToolPolicy {
+                server_name: Some("fs".to_string()),
+                tool: "delete_*".to_string(),
+                action: crate::policy::PolicyAction::Deny,
+                enabled: true,
+            }],
+            rate_limits: vec![RateLimit {
+                server_name: None,
+                tool: Some("search_*".to_string()),
+                calls_per_minute: 30,
+                enabled: true,
+            }],
+            gateway_enabled: true,
+            gateway_port: 40000,
+            registry_url: "https://registry.example.com".to_string(),
+            registry_ttl_mins: 5,
+            registry_offline: true,
+            allowed_executables: vec!["node".to_string()],
+            denied_executables: vec!["bash".to_string()],
+            managed_executables: vec![ManagedExecutable {
+                server_name: "tool".to_string(),
+                path: PathBuf::from("/data/bin/tool/tool"),
+            }],
+            trusted_signing_keys: vec!["MCowBQYDK2VwAyEA".to_string()],
+        };
+        settings.save_to(&path).unwrap();
+        assert_eq!(SynapticSettings::load_from(&path).unwrap(), settings);
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,583 @@
+//! Client-side capture through a stdio shim
+//!
+//! Synaptic only sees the traffic of servers it spawns itself. With client
+//! capture enabled, a server's entry in the Claude config is rewritten to
+//! launch `synaptic-shim`, which starts the real command and relays stdio
+//! byte for byte while copying every message (redacted) into `system_logs`
+//! under a session of its own. The original command is kept after `--`,
+//! so disabling capture restores the entry exactly.
+//!
+//! The desktop app follows those sessions with [`run_capture_follower`] and
+//! emits their rows as regular `mcp-traffic-batch` events.
+
+use crate::config::{self, McpServer};
+use crate::database::Database;
+use crate::error::{SynapticError, SynapticResult};
+use crate::framing::{Framing, MessageDecoder, SharedFraming};
+use crate::host::Host;
+use crate::process_manager::{executable_name, McpTrafficEvent};
+use crate::redaction::Redactor;
+use crate::resolve;
+use crate::secrets;
+use crate::settings::SynapticSettings;
+use crate::system_logs::{self, LogEntry, LogRecord, SessionInfo, ENTRY_COLUMNS};
+use bytes::BytesMut;
+use chrono::Utc;
+use rusqlite::params;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tokio_util::codec::Decoder;
+
+/// File name of the shim binary (without the platform's executable suffix)
+pub const SHIM_BINARY: &str = "synaptic-shim";
+
+/// `description` of the sessions a shim records
+pub const CLIENT_CAPTURE_DESCRIPTION: &str = "Captured from client";
+
+/// How often the app looks for rows written by shims
+pub const CAPTURE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Rows emitted per poll at most
+const CAPTURE_POLL_LIMIT: usize = 500;
+
+/// Longest a shim's pipes get to drain after the server exited
+const SHIM_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Bytes read from a pipe at once
+const RELAY_CHUNK_BYTES: usize = 64 * 1024;
+
+// ============================================
+// SHIM COMMAND LINE
+// ============================================
+
+/// Command line of `synaptic-shim`: `--server <name> [--db <path>] -- <command> [args...]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShimArgs {
+    pub server_name: String,
+    /// Database the traffic is recorded in; without one the shim only relays
+    pub db_path: Option<PathBuf>,
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl ShimArgs {
+    /// Parse the arguments after the program name
+    pub fn parse(args: impl IntoIterator<Item = String>) -> SynapticResult<Self> {
+        let mut args = args.into_iter();
+        let mut server_name = None;
+        let mut db_path = None;
+
+        loop {
+            match args.next().as_deref() {
+                Some("--server") => server_name = args.next(),
+                Some("--db") => db_path = args.next().map(PathBuf::from),
+                Some("--") => break,
+                Some(other) => {
+                    return Err(SynapticError::ProcessError(format!(
+                        "Unexpected shim argument: {}",
+                        other
+                    )))
+                }
+                None => {
+                    return Err(SynapticError::ProcessError(
+                        "Shim arguments end before `--` and the server command".to_string(),
+                    ))
+                }
+            }
+        }
+
+        let server_name = server_name
+            .ok_or_else(|| SynapticError::ProcessError("Shim needs --server <name>".to_string()))?;
+        let command = args.next().ok_or_else(|| {
+            SynapticError::ProcessError("Shim needs the server command after `--`".to_string())
+        })?;
+        Ok(Self {
+            server_name,
+            db_path,
+            command,
+            args: args.collect(),
+        })
+    }
+
+    /// The arguments `parse` reads back
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec!["--server".to_string(), self.server_name.clone()];
+        if let Some(db_path) = &self.db_path {
+            args.push("--db".to_string());
+            args.push(db_path.to_string_lossy().to_string());
+        }
+        args.push("--".to_string());
+        args.push(self.command.clone());
+        args.extend(self.args.iter().cloned());
+        args
+    }
+}
+
+/// Where the shim binary is installed: next to the running executable
+pub fn shim_path() -> SynapticResult<PathBuf> {
+    resolve::bundled_binary(SHIM_BINARY)
+}
+
+// ============================================
+// CONFIG REWRITING
+// ============================================
+
+/// Whether a config entry launches the shim
+pub fn is_capturing(server: &McpServer) -> bool {
+    executable_name(&server.command) == SHIM_BINARY
+}
+
+/// The entry with its command routed through the shim at `shim`
+pub fn enable_capture(
+    server_name: &str,
+    server: &McpServer,
+    shim: &Path,
+    db_path: &Path,
+) -> SynapticResult<McpServer> {
+    if is_capturing(server) {
+        return Err(SynapticError::InspectorError(format!(
+            "Client capture is already enabled for {}",
+            server_name
+        )));
+    }
+    if server.is_remote() {
+        return Err(SynapticError::InspectorError(format!(
+            "{} is reached over HTTP; client capture needs a stdio server",
+            server_name
+        )));
+    }
+    let args = ShimArgs {
+        server_name: server_name.to_string(),
+        db_path: Some(db_path.to_path_buf()),
+        command: server.command.clone(),
+        args: server.args.clone(),
+    };
+    Ok(McpServer {
+        command: shim.to_string_lossy().to_string(),
+        args: args.to_args(),
+        ..server.clone()
+    })
+}
+
+/// The entry with the shim taken out again
+pub fn disable_capture(server_name: &str, server: &McpServer) -> SynapticResult<McpServer> {
+    if !is_capturing(server) {
+        return Err(SynapticError::InspectorError(format!(
+            "Client capture is not enabled for {}",
+            server_name
+        )));
+    }
+    Ok(without_shim(server))
+}
+
+/// The entry the shim runs; entries without the shim are returned as they are
+pub fn without_shim(server: &McpServer) -> McpServer {
+    if !is_capturing(server) {
+        return server.clone();
+    }
+    match ShimArgs::parse(server.args.iter().cloned()) {
+        Ok(args) => McpServer {
+            command: args.command,
+            args: args.args,
+            ..server.clone()
+        },
+        Err(_) => server.clone(),
+    }
+}
+
+// ============================================
+// SHIM RUNTIME
+// ============================================
+
+/// Run the real server between the client's stdio and its own, returning its exit code
+pub async fn run(args: ShimArgs) -> SynapticResult<i32> {
+    let db = args
+        .db_path
+        .as_deref()
+        .and_then(|path| match Database::open(path) {
+            Ok(db) => Some(Arc::new(db)),
+            Err(e) => {
+                eprintln!("{}: not recording traffic: {}", SHIM_BINARY, e);
+                None
+            }
+        });
+    let settings = SynapticSettings::load().unwrap_or_default();
+    // The client passes the entry's env on to us; its values are secrets, and
+    // the keychain references among them are resolved for the server
+    let mut keychain_env = Vec::new();
+    for (key, value) in std::env::vars() {
+        if let Some(secret) = secrets::resolve_reference(&value)? {
+            keychain_env.push((key, secret));
+        }
+    }
+    let secrets: Vec<String> = config::read_config_file()
+        .ok()
+        .and_then(|config| config.mcp_servers.get(&args.server_name).cloned())
+        .map(|server| server.env.into_values().collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .chain(keychain_env.iter().map(|(_, secret)| secret.clone()))
+        .collect();
+    let redactor = Arc::new(Redactor::new(
+        secrets,
+        settings.redaction_rules.clone(),
+        settings.redaction_mode,
+    ));
+
+    let program = resolve::resolve_command(&args.command)?;
+    let mut child = Command::new(program)
+        .args(&args.args)
+        .envs(keychain_env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| SynapticError::ProcessError(format!("Failed to spawn: {}", e)))?;
+    let child_stdin = child.stdin.take().expect("Failed to capture stdin");
+    let child_stdout = child.stdout.take().expect("Failed to capture stdout");
+    let child_stderr = child.stderr.take().expect("Failed to capture stderr");
+
+    let (events, recorder) = match &db {
+        Some(db) => {
+            let (events, recorder) = spawn_recorder(db.clone(), &args.server_name)?;
+            (Some(events), Some(recorder))
+        }
+        None => (None, None),
+    };
+    let relay = |direction: &'static str, framing: Framing| Relay {
+        server_name: args.server_name.clone(),
+        direction,
+        framing,
+        max_message_bytes: settings.max_message_bytes,
+        redactor: redactor.clone(),
+        events: events.clone(),
+    };
+
+    // Client -> server; the server sees EOF once the client closes our stdin
+    let outgoing = relay("OUTGOING", Framing::Auto);
+    tokio::spawn(async move {
+        let _ = outgoing.run(tokio::io::stdin(), child_stdin).await;
+    });
+    let incoming = relay("INCOMING", Framing::Auto);
+    let stdout_task = tokio::spawn(async move {
+        let _ = incoming.run(child_stdout, tokio::io::stdout()).await;
+    });
+    let stderr = relay("STDERR", Framing::Newline);
+    let stderr_task = tokio::spawn(async move {
+        let _ = stderr.run(child_stderr, tokio::io::stderr()).await;
+    });
+
+    let status = child.wait().await?;
+    let pipes = futures::future::join(stdout_task, stderr_task);
+    let _ = tokio::time::timeout(SHIM_DRAIN_TIMEOUT, pipes).await;
+
+    drop(events);
+    if let Some(recorder) = recorder {
+        let _ = recorder.await;
+    }
+    Ok(status.code().unwrap_or(1))
+}
+
+/// One direction of the bridge
+struct Relay {
+    server_name: String,
+    direction: &'static str,
+    framing: Framing,
+    max_message_bytes: usize,
+    redactor: Arc<Redactor>,
+    events: Option<mpsc::UnboundedSender<McpTrafficEvent>>,
+}
+
+impl Relay {
+    /// Copy `from` to `to` unchanged, recording each message that passes
+    async fn run<R, W>(&self, mut from: R, mut to: W) -> std::io::Result<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut decoder =
+            MessageDecoder::new(SharedFraming::new(self.framing), self.max_message_bytes);
+        let mut pending = BytesMut::new();
+        let mut chunk = vec![0u8; RELAY_CHUNK_BYTES];
+
+        loop {
+            let read = from.read(&mut chunk).await?;
+            if read == 0 {
+                break;
+            }
+            to.write_all(&chunk[..read]).await?;
+            to.flush().await?;
+
+            if self.events.is_some() {
+                pending.extend_from_slice(&chunk[..read]);
+                while let Some(message) = decoder.decode(&mut pending)? {
+                    self.record(&message);
+                }
+            }
+        }
+        while let Some(message) = decoder.decode_eof(&mut pending)? {
+            self.record(&message);
+        }
+        to.shutdown().await
+    }
+
+    fn record(&self, message: &str) {
+        if message.trim().is_empty() {
+            return;
+        }
+        if let Some(events) = &self.events {
+            let _ = events.send(McpTrafficEvent {
+                server_id: self.server_name.clone(),
+                timestamp: Utc::now().to_rfc3339(),
+                direction: self.direction.to_string(),
+                content: self.redactor.redact(message).into_owned(),
+                message_id: uuid::Uuid::new_v4().to_string(),
+                level: None,
+            });
+        }
+    }
+}
+
+/// Open a capture session and a task writing its traffic until every sender is gone
+fn spawn_recorder(
+    db: Arc<Database>,
+    server_name: &str,
+) -> SynapticResult<(
+    mpsc::UnboundedSender<McpTrafficEvent>,
+    tokio::task::JoinHandle<()>,
+)> {
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let session = SessionInfo {
+        id: session_id.clone(),
+        started_at: Utc::now(),
+        ended_at: None,
+        server_name: Some(server_name.to_string()),
+        log_count: 0,
+        description: Some(CLIENT_CAPTURE_DESCRIPTION.to_string()),
+        imported_from: None,
+    };
+    system_logs::insert_session(&db, &session, &[])?;
+    db.conn().execute(
+        "INSERT INTO capture_sessions (session_id) VALUES (?1)",
+        params![session_id],
+    )?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<McpTrafficEvent>();
+    let task = tokio::spawn(async move {
+        while let Some(first) = rx.recv().await {
+            let mut batch = vec![first];
+            while let Ok(event) = rx.try_recv() {
+                batch.push(event);
+            }
+            let record = LogRecord::Traffic {
+                session_id: session_id.clone(),
+                events: batch,
+            };
+            if let Err(e) = system_logs::write(&db, &record) {
+                eprintln!("{}: failed to record traffic: {}", SHIM_BINARY, e);
+            }
+        }
+        let _ = system_logs::write(
+            &db,
+            &LogRecord::SessionEnded {
+                session_id,
+                ended_at: Utc::now(),
+            },
+        );
+    });
+    Ok((tx, task))
+}
+
+// ============================================
+// FOLLOWING CAPTURED SESSIONS
+// ============================================
+
+/// Rows of capture sessions logged after row `after_id`, oldest first
+///
+/// Shims list their sessions in `capture_sessions`; a session's description
+/// is free text and says nothing about where it came from.
+pub fn captured_since(db: &Database, after_id: i64, limit: usize) -> SynapticResult<Vec<LogEntry>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ENTRY_COLUMNS} FROM system_logs l
+         JOIN capture_sessions c ON c.session_id = l.session_id
+         WHERE l.id > ?1
+         ORDER BY l.id
+         LIMIT ?2"
+    ))?;
+    let rows = stmt.query_map(params![after_id, limit as i64], system_logs::entry_from_row)?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// A captured row as the traffic event the inspector listens for
+fn traffic_event(entry: &LogEntry) -> McpTrafficEvent {
+    let content = match &entry.payload {
+        Some(Value::String(text)) => text.clone(),
+        Some(payload) => payload.to_string(),
+        None => String::new(),
+    };
+    McpTrafficEvent {
+        server_id: entry.server_name.clone().unwrap_or_default(),
+        timestamp: entry.timestamp.to_rfc3339(),
+        direction: entry.direction.clone().unwrap_or_default(),
+        content,
+        message_id: uuid::Uuid::new_v4().to_string(),
+        level: None,
+    }
+}
+
+/// Background loop emitting what shims record as `mcp-traffic-batch` events
+///
+/// Only rows written after the loop started are emitted; older ones are in
+/// the history view.
+pub async fn run_capture_follower<H: Host>(host: H) {
+    let mut ticker = tokio::time::interval(CAPTURE_POLL_INTERVAL);
+    let mut last_id: Option<i64> = None;
+
+    loop {
+        ticker.tick().await;
+
+        let Some(db) = host.app_state().and_then(|state| state.database.as_ref()) else {
+            continue;
+        };
+        let Some(after) = last_id else {
+            let start =
+                db.conn()
+                    .query_row("SELECT COALESCE(MAX(id), 0) FROM system_logs", [], |row| {
+                        row.get(0)
+                    });
+            last_id = start.ok();
+            continue;
+        };
+
+        match captured_since(db, after, CAPTURE_POLL_LIMIT) {
+            Ok(rows) if !rows.is_empty() => {
+                last_id = rows.last().map(|row| row.id);
+                let batch: Vec<McpTrafficEvent> = rows.iter().map(traffic_event).collect();
+                host.emit("mcp-traffic-batch", &batch);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to read captured client traffic: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn server() -> McpServer {
+        McpServer {
+            command: "npx".to_string(),
+            args: vec![
+                "-y".to_string(),
+                "@modelcontextprotocol/server-memory".to_string(),
+            ],
+            env: [("TOKEN".to_string(), "s3cret".to_string())].into(),
+            ..McpServer::default()
+        }
+    }
+
+    #[test]
+    fn test_capture_round_trip() {
+        let shim = Path::new("/opt/synaptic/synaptic-shim");
+        let db = Path::new("/tmp/wear-synaptic.db");
+        let captured = enable_capture("memory", &server(), shim, db).unwrap();
+
+        assert!(is_capturing(&captured));
+        assert_eq!(captured.command, "/opt/synaptic/synaptic-shim");
+        assert_eq!(
+            captured.args,
+            [
+                "--server",
+                "memory",
+                "--db",
+                "/tmp/wear-synaptic.db",
+                "--",
+                "npx",
+                "-y",
+                "@modelcontextprotocol/server-memory"
+            ]
+        );
+        assert_eq!(captured.env, server().env);
+        assert!(enable_capture("memory", &captured, shim, db).is_err());
+
+        let restored = disable_capture("memory", &captured).unwrap();
+        assert_eq!(
+            (restored.command, restored.args),
+            (server().command, server().args)
+        );
+        assert!(disable_capture("memory", &server()).is_err());
+
+        assert!(ShimArgs::parse(["--server".to_string(), "memory".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_relay_copies_bytes_and_records_messages() {
+        let db = Arc::new(Database::open_in_memory().unwrap());
+        let (events, recorder) = spawn_recorder(db.clone(), "memory").unwrap();
+        let relay = Relay {
+            server_name: "memory".to_string(),
+            direction: "OUTGOING",
+            framing: Framing::Auto,
+            max_message_bytes: 0,
+            redactor: Arc::new(Redactor::new(
+                vec!["s3cret".to_string()],
+                Vec::new(),
+                Default::default(),
+            )),
+            events: Some(events),
+        };
+
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"token":"s3cret"}}"#,
+        );
+        let mut output = Vec::new();
+        relay.run(input.as_bytes(), &mut output).await.unwrap();
+        assert_eq!(output, input.as_bytes());
+
+        // Another session with the same description isn't a capture
+        db.conn()
+            .execute(
+                "INSERT INTO sessions (id, started_at, description) VALUES ('other', 0, ?1)",
+                params![CLIENT_CAPTURE_DESCRIPTION],
+            )
+            .unwrap();
+        let other = McpTrafficEvent {
+            server_id: "memory".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            direction: "INCOMING".to_string(),
+            content: r#"{"jsonrpc":"2.0","id":1,"result":{}}"#.to_string(),
+            message_id: uuid::Uuid::new_v4().to_string(),
+            level: None,
+        };
+        system_logs::insert_traffic(&db, "other", &[other]).unwrap();
+
+        drop(relay);
+        recorder.await.unwrap();
+        let rows = captured_since(&db, 0, 10).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].message.as_deref(), Some("tools/list"));
+        assert_eq!(rows[1].direction.as_deref(), Some("OUTGOING"));
+        assert!(!rows[1]
+            .payload
+            .as_ref()
+            .unwrap()
+            .to_string()
+            .contains("s3cret"));
+        assert_eq!(
+            traffic_event(&rows[0]).content,
+            json!({"jsonrpc":"2.0","id":1,"method":"tools/list"}).to_string()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Scheduled automatic config snapshots
+//!
+//! Write-triggered backups only protect changes Synaptic makes itself. This
+//! background task also snapshots the config on a fixed interval so edits made
+//! by hand or by other tools end up in the backup history too.
+
+use crate::config::{self, BackupInfo};
+use crate::error::SynapticResult;
+use crate::host::Host;
+use chrono::{DateTime, Duration, Utc};
+use std::fs;
+use std::path::Path;
+
+/// How often the scheduler wakes up to check whether a snapshot is due
+pub const SNAPSHOT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+/// Snapshot the live config if the newest backup is older than the interval
+pub fn snapshot_if_due(
+    interval_hours: u64,
+    now: DateTime<Utc>,
+) -> SynapticResult<Option<BackupInfo>> {
+    snapshot_if_due_in(
+        &config::get_claude_config_path()?,
+        &config::get_backups_dir()?,
+        interval_hours,
+        now,
+    )
+}
+
+/// Path-parameterized version of [`snapshot_if_due`]
+pub fn snapshot_if_due_in(
+    config_path: &Path,
+    backups_dir: &Path,
+    interval_hours: u64,
+    now: DateTime<Utc>,
+) -> SynapticResult<Option<BackupInfo>> {
+    if interval_hours == 0 || !config_path.exists() {
+        return Ok(None);
+    }
+
+    let backups = config::list_backups_in(backups_dir)?;
+
+    if let Some(latest) = backups.first() {
+        if now - latest.created_at < Duration::hours(interval_hours as i64) {
+            return Ok(None);
+        }
+
+        // Nothing new to protect if the config still matches the newest backup
+        let current = fs::read(config_path)?;
+        if fs::read(backups_dir.join(&latest.filename)).ok().as_ref() == Some(&current) {
+            return Ok(None);
+        }
+    }
+
+    let path = config::create_backup_in(config_path, backups_dir)?;
+    let id = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    Ok(config::list_backups_in(backups_dir)?
+        .into_iter()
+        .find(|b| b.id == id))
+}
+
+/// Background loop that takes snapshots according to the current settings
+pub async fn run_snapshot_scheduler<H: Host>(host: H) {
+    let mut ticker = tokio::time::interval(SNAPSHOT_CHECK_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        let interval_hours = match host.app_state().map(|state| state.get_settings()) {
+            Some(Ok(settings)) => settings.snapshot_interval_hours,
+            Some(Err(e)) => {
+                eprintln!("Failed to load settings for snapshots: {}", e);
+                continue;
+            }
+            None => continue,
+        };
+
+        match snapshot_if_due(interval_hours, Utc::now()) {
+            Ok(Some(backup)) => host.emit("config-snapshot-created", &backup),
+            Ok(None) => {}
+            Err(e) => eprintln!("Scheduled config snapshot failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_only_when_due_and_changed() {
+        let root = std::env::temp_dir().join(format!("synaptic-snap-{}", uuid::Uuid::new_v4()));
+        let config_path = root.join("claude_desktop_config.json");
+        let backups_dir = root.join("backups");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(&config_path, r#"{"mcpServers":{}}"#).unwrap();
+
+        let now = Utc::now();
+
+        // First run always snapshots
+        let first = snapshot_if_due_in(&config_path, &backups_dir, 24, now).unwrap();
+        assert!(first.is_some());
+
+        // Not due yet
+        assert!(snapshot_if_due_in(&config_path, &backups_dir, 24, now)
+            .unwrap()
+            .is_none());
+
+        // Due but unchanged
+        let later = now + Duration::hours(25);
+        assert!(snapshot_if_due_in(&config_path, &backups_dir, 24, later)
+            .unwrap()
+            .is_none());
+
+        // Disabled
+        assert!(snapshot_if_due_in(&config_path, &backups_dir, 0, later)
+            .unwrap()
+            .is_none());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,324 @@
+//! Parsing of pasted server definitions
+//!
+//! READMEs publish servers in several shapes: a full `{"mcpServers": {...}}`
+//! block, a `"name": {...}` entry, a bare server object, or a
+//! `claude mcp add` command line. All of them are turned into an `McpServer`
+//! ready for `add_server`.
+
+use crate::config::McpServer;
+use crate::error::{SynapticError, SynapticResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Server parsed from a snippet, with its name when the snippet carried one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSnippet {
+    pub name: Option<String>,
+    pub server: McpServer,
+}
+
+/// Parse a pasted JSON fragment or `claude mcp add` command line
+pub fn parse_server_snippet(text: &str) -> SynapticResult<ServerSnippet> {
+    let text = text.trim().trim_end_matches(',').trim();
+
+    if text.is_empty() {
+        return Err(parse_error("Snippet is empty"));
+    }
+
+    if text.starts_with("claude ") {
+        return parse_claude_command(text);
+    }
+
+    let value = parse_json_fragment(text)?;
+    parse_json_value(value)
+}
+
+// ============================================
+// JSON SNIPPETS
+// ============================================
+
+/// Parse JSON, also accepting a `"name": {...}` entry without outer braces
+fn parse_json_fragment(text: &str) -> SynapticResult<Value> {
+    match serde_json::from_str(text) {
+        Ok(value) => Ok(value),
+        Err(e) if !text.starts_with('{') => {
+            serde_json::from_str(&format!("{{{}}}", text)).map_err(|_| parse_error(e))
+        }
+        Err(e) => Err(parse_error(e)),
+    }
+}
+
+fn parse_json_value(value: Value) -> SynapticResult<ServerSnippet> {
+    let Value::Object(mut object) = value else {
+        return Err(parse_error("Expected a JSON object"));
+    };
+
+    if let Some(servers) = object.remove("mcpServers") {
+        let Value::Object(servers) = servers else {
+            return Err(parse_error("`mcpServers` must be an object"));
+        };
+        return single_named_server(servers);
+    }
+
+    if object.contains_key("command") {
+        return Ok(ServerSnippet {
+            name: None,
+            server: server_from_value(Value::Object(object))?,
+        });
+    }
+
+    single_named_server(object)
+}
+
+fn single_named_server(servers: Map<String, Value>) -> SynapticResult<ServerSnippet> {
+    let mut entries = servers.into_iter();
+
+    match (entries.next(), entries.next()) {
+        (Some((name, value)), None) => Ok(ServerSnippet {
+            name: Some(name),
+            server: server_from_value(value)?,
+        }),
+        (None, _) => Err(parse_error("Snippet contains no server")),
+        (Some(_), Some(_)) => Err(parse_error(
+            "Snippet contains more than one server; paste them one at a time",
+        )),
+    }
+}
+
+fn server_from_value(value: Value) -> SynapticResult<McpServer> {
+    let server: McpServer = serde_json::from_value(value)
+        .map_err(|e| parse_error(format!("Invalid server definition: {}", e)))?;
+
+    if server.command.trim().is_empty() {
+        return Err(parse_error("Server `command` is empty"));
+    }
+    Ok(server)
+}
+
+// ============================================
+// COMMAND LINE SNIPPETS
+// ============================================
+
+/// Parse `claude mcp add [options] <name> [--] <command> [args...]`
+/// or `claude mcp add-json <name> '<json>'`
+fn parse_claude_command(text: &str) -> SynapticResult<ServerSnippet> {
+    let words = split_command_line(text)?;
+    let mut words = words.into_iter();
+
+    match (
+        words.next().as_deref(),
+        words.next().as_deref(),
+        words.next().as_deref(),
+    ) {
+        (Some("claude"), Some("mcp"), Some("add")) => parse_mcp_add(words.collect()),
+        (Some("claude"), Some("mcp"), Some("add-json")) => {
+            let rest: Vec<String> = words.filter(|w| !w.starts_with('-')).collect();
+            let [name, json] = rest.as_slice() else {
+                return Err(parse_error("Expected `claude mcp add-json <name> <json>`"));
+            };
+            Ok(ServerSnippet {
+                name: Some(name.clone()),
+                server: server_from_value(parse_json_fragment(json)?)?,
+            })
+        }
+        _ => Err(parse_error(
+            "Only `claude mcp add` command lines are supported",
+        )),
+    }
+}
+
+fn parse_mcp_add(words: Vec<String>) -> SynapticResult<ServerSnippet> {
+    let mut env = HashMap::new();
+    let mut positional = Vec::new();
+    let mut words = words.into_iter();
+
+    while let Some(word) = words.next() {
+        // Everything after `--` or the command word belongs to the server command
+        if word == "--" {
+            positional.extend(words.by_ref());
+            break;
+        }
+
+        if positional.len() >= 2 {
+            positional.push(word);
+            continue;
+        }
+
+        match word.as_str() {
+            "-e" | "--env" => {
+                let pair = words
+                    .next()
+                    .ok_or_else(|| parse_error("`--env` needs a KEY=VALUE argument"))?;
+                let (key, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| parse_error(format!("Invalid env assignment: {}", pair)))?;
+                env.insert(key.to_string(), value.to_string());
+            }
+            "-s" | "--scope" | "-t" | "--transport" | "-H" | "--header" => {
+                words.next();
+            }
+            flag if flag.starts_with("--env=") => {
+                let pair = &flag["--env=".len()..];
+                let (key, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| parse_error(format!("Invalid env assignment: {}", pair)))?;
+                env.insert(key.to_string(), value.to_string());
+            }
+            flag if flag.starts_with('-') => {}
+            _ => positional.push(word),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let name = positional
+        .next()
+        .ok_or_else(|| parse_error("Missing server name"))?;
+    let command = positional
+        .next()
+        .ok_or_else(|| parse_error("Missing server command"))?;
+
+    if command.contains("://") {
+        return Err(parse_error(
+            "Remote (HTTP/SSE) servers are not supported; only stdio commands can be imported",
+        ));
+    }
+
+    Ok(ServerSnippet {
+        name: Some(name),
+        server: McpServer {
+            command,
+            args: positional.collect(),
+            env,
+            cwd: None,
+            enabled: true,
+            ..Default::default()
+        },
+    })
+}
+
+/// Split a shell command line into words, honouring quotes and backslashes
+fn split_command_line(text: &str) -> SynapticResult<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(parse_error("Unterminated single quote")),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err(parse_error("Unterminated double quote")),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(parse_error("Unterminated double quote")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                // Line continuation
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(c) => {
+                    in_word = true;
+                    current.push(c);
+                }
+                None => {}
+            },
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+fn parse_error(message: impl std::fmt::Display) -> SynapticError {
+    SynapticError::ConfigParseError(format!("Unrecognized server snippet: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mcp_servers_block() {
+        let snippet = parse_server_snippet(
+            r#"{"mcpServers": {"github": {"command": "npx", "args": ["-y", "@modelcontextprotocol/server-github"]}}}"#,
+        )
+        .unwrap();
+        assert_eq!(snippet.name.as_deref(), Some("github"));
+        assert_eq!(snippet.server.command, "npx");
+        assert!(snippet.server.enabled);
+    }
+
+    #[test]
+    fn test_parse_named_entry_and_bare_object() {
+        let named = parse_server_snippet(r#""memory": {"command": "npx", "args": []},"#).unwrap();
+        assert_eq!(named.name.as_deref(), Some("memory"));
+
+        let bare =
+            parse_server_snippet(r#"{"command": "uvx", "args": ["mcp-server-git"]}"#).unwrap();
+        assert!(bare.name.is_none());
+        assert_eq!(bare.server.args, vec!["mcp-server-git"]);
+    }
+
+    #[test]
+    fn test_parse_claude_mcp_add() {
+        let snippet = parse_server_snippet(
+            "claude mcp add -s user github -e GITHUB_TOKEN='abc 123' -- npx -y @modelcontextprotocol/server-github",
+        )
+        .unwrap();
+        assert_eq!(snippet.name.as_deref(), Some("github"));
+        assert_eq!(snippet.server.command, "npx");
+        assert_eq!(
+            snippet.server.args,
+            vec!["-y", "@modelcontextprotocol/server-github"]
+        );
+        assert_eq!(snippet.server.env.get("GITHUB_TOKEN").unwrap(), "abc 123");
+    }
+
+    #[test]
+    fn test_rejects_ambiguous_or_invalid() {
+        assert!(parse_server_snippet("").is_err());
+        assert!(parse_server_snippet("not json").is_err());
+        assert!(parse_server_snippet(
+            r#"{"mcpServers": {"a": {"command": "x"}, "b": {"command": "y"}}}"#
+        )
+        .is_err());
+        assert!(parse_server_snippet("claude mcp add remote https://example.com/mcp").is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Remote servers over SSH
+//!
+//! A server with an `ssh` target runs its command on another machine:
+//! Synaptic spawns `ssh -T <host> -- '<command line>'` locally and the MCP
+//! stdio flows through the connection, so interception, logging and events
+//! work exactly as for local servers. The remote side needs a POSIX shell;
+//! `cwd`, `env` and `pathPrepend` are applied there, not locally.
+
+use crate::config::McpServer;
+use crate::error::{SynapticError, SynapticResult};
+use serde::{Deserialize, Serialize};
+
+/// Where and as whom a remote server runs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SshTarget {
+    /// Host name or an alias from `~/.ssh/config`
+    pub host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity_file: Option<String>,
+    /// Extra `ssh` arguments, e.g. `["-o", "ProxyJump=bastion"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+}
+
+impl SshTarget {
+    /// Arguments for the local `ssh` process that runs `server` remotely
+    ///
+    /// Env values end up on the remote command line, where other users of
+    /// either machine can see them in the process list.
+    pub fn ssh_args(&self, server: &McpServer) -> SynapticResult<Vec<String>> {
+        if self.host.is_empty() || self.host.starts_with('-') {
+            return Err(SynapticError::ProcessError(format!(
+                "Invalid SSH host: {:?}",
+                self.host
+            )));
+        }
+
+        // No tty (it would mangle stdio) and no password prompts nobody can answer
+        let mut args = vec![
+            "-T".to_string(),
+            "-o".to_string(),
+            "BatchMode=yes".to_string(),
+        ];
+        if let Some(port) = self.port {
+            args.extend(["-p".to_string(), port.to_string()]);
+        }
+        if let Some(identity) = &self.identity_file {
+            args.extend(["-i".to_string(), crate::paths::expand_tilde(identity)]);
+        }
+        if let Some(user) = &self.user {
+            args.extend(["-l".to_string(), user.clone()]);
+        }
+        args.extend(self.options.iter().cloned());
+
+        args.push(self.host.clone());
+        args.push("--".to_string());
+        args.push(remote_command_line(server)?);
+        Ok(args)
+    }
+}
+
+/// Shell command line that starts `server` on the remote host
+pub fn remote_command_line(server: &McpServer) -> SynapticResult<String> {
+    let mut line = String::new();
+
+    if let Some(cwd) = &server.cwd {
+        line.push_str(&format!("cd {} && ", remote_path(cwd)));
+    }
+
+    let mut assignments = Vec::new();
+    if !server.path_prepend.is_empty() {
+        let dirs: Vec<String> = server.path_prepend.iter().map(|d| remote_path(d)).collect();
+        assignments.push(format!("PATH={}:\"$PATH\"", dirs.join(":")));
+    }
+    let mut env: Vec<(&String, &String)> = server.env.iter().collect();
+    env.sort();
+    for (key, value) in env {
+        if !is_env_name(key) {
+            return Err(SynapticError::ProcessError(format!(
+                "Invalid environment variable name for a remote server: {:?}",
+                key
+            )));
+        }
+        assignments.push(format!("{}={}", key, shell_quote(value)));
+    }
+    if !assignments.is_empty() {
+        line.push_str(&format!("export {}; ", assignments.join(" ")));
+    }
+
+    line.push_str("exec ");
+    line.push_str(&shell_quote(&server.command));
+    for arg in &server.args {
+        line.push(' ');
+        line.push_str(&shell_quote(arg));
+    }
+    Ok(line)
+}
+
+/// Quote a value for a POSIX shell
+pub fn shell_quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c));
+    if safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// Quote a remote path, expanding a leading `~` to the remote `$HOME`
+fn remote_path(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("\"$HOME\"/{}", shell_quote(rest)),
+        None if path == "~" => "\"$HOME\"".to_string(),
+        None => shell_quote(path),
+    }
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("npx"), "npx");
+        assert_eq!(shell_quote("@scope/pkg"), "@scope/pkg");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("$(rm -rf /)"), "'$(rm -rf /)'");
+    }
+
+    #[test]
+    fn test_ssh_args_run_the_server_remotely() {
+        let server = McpServer {
+            command: "npx".to_string(),
+            args: vec!["-y".to_string(), "server with space".to_string()],
+            env: [("TOKEN".to_string(), "s3cr'et".to_string())].into(),
+            cwd: Some("~/work dir".to_string()),
+            path_prepend: vec!["~/.venv/bin".to_string()],
+            ..Default::default()
+        };
+        let target = SshTarget {
+            host: "workstation".to_string(),
+            user: Some("dev".to_string()),
+            port: Some(2222),
+            ..Default::default()
+        };
+
+        let args = target.ssh_args(&server).unwrap();
+        assert_eq!(
+            args[..9],
+            [
+                "-T",
+                "-o",
+                "BatchMode=yes",
+                "-p",
+                "2222",
+                "-l",
+                "dev",
+                "workstation",
+                "--"
+            ]
+        );
+        assert_eq!(
+            args[9],
+            r#"cd "$HOME"/'work dir' && export PATH="$HOME"/.venv/bin:"$PATH" TOKEN='s3cr'\''et'; exec npx -y 'server with space'"#
+        );
+
+        let bad_env = McpServer {
+            env: [("BAD NAME".to_string(), "x".to_string())].into(),
+            ..server
+        };
+        assert!(target.ssh_args(&bad_env).is_err());
+        assert!(SshTarget {
+            host: "-oProxyCommand=x".to_string(),
+            ..Default::default()
+        }
+        .ssh_args(&McpServer::default())
+        .is_err());
+    }
+}
@@ -0,0 +1,503 @@
+//! Application state management with thread-safe access
+
+use crate::audit::{self, AuditAction};
+use crate::config::{McpConfig, McpServer};
+use crate::database::Database;
+use crate::history_retention::{self, HistoryRetention};
+use crate::inspector::{InspectorHistory, InspectorMessage};
+use crate::installer::InstallJobs;
+use crate::live_stats::LiveStatsTracker;
+use crate::metadata::{SynapticMetadata, CLAUDE_DESKTOP_TARGET};
+use crate::registry::RegistrySnapshot;
+use crate::settings::SynapticSettings;
+use crate::traffic_mirror;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Main application state managed by Tauri
+pub struct AppState {
+    /// Cached MCP configuration (to avoid repeated file reads)
+    pub config_cache: Mutex<Option<McpConfig>>,
+
+    /// Active inspector sessions by server name
+    pub inspector_sessions: Mutex<HashMap<String, InspectorSessionState>>,
+
+    /// Recent captured inspector messages by server name
+    pub inspector_messages: Mutex<HashMap<String, InspectorHistory>>,
+
+    /// Cached Synaptic settings
+    pub settings_cache: Mutex<Option<SynapticSettings>>,
+
+    /// Backend SQLite connection (audit log, persisted history)
+    pub database: Option<Database>,
+
+    /// Rolling per-method request counters fed by the traffic pipeline
+    pub live_stats: LiveStatsTracker,
+
+    /// Last listing synced from the MCP registry
+    pub registry_cache: Mutex<Option<RegistrySnapshot>>,
+
+    /// Registry installs in progress
+    pub install_jobs: InstallJobs,
+}
+
+impl AppState {
+    /// Create a new AppState instance
+    pub fn new() -> Self {
+        Self {
+            config_cache: Mutex::new(None),
+            inspector_sessions: Mutex::new(HashMap::new()),
+            inspector_messages: Mutex::new(HashMap::new()),
+            settings_cache: Mutex::new(None),
+            database: None,
+            live_stats: LiveStatsTracker::default(),
+            registry_cache: Mutex::new(None),
+            install_jobs: InstallJobs::default(),
+        }
+    }
+
+    /// Create an AppState backed by a database
+    pub fn with_database(database: Database) -> Self {
+        Self {
+            database: Some(database),
+            ..Self::new()
+        }
+    }
+
+    /// Get the database, failing if none is configured
+    pub fn database(&self) -> crate::error::SynapticResult<&Database> {
+        self.database.as_ref().ok_or_else(|| {
+            crate::error::SynapticError::DatabaseError("Database not initialized".to_string())
+        })
+    }
+
+    /// Get the cached config or read from file
+    pub fn get_config(&self) -> crate::error::SynapticResult<McpConfig> {
+        let mut cache = self.config_cache.lock().unwrap();
+
+        if let Some(ref config) = *cache {
+            return Ok(config.clone());
+        }
+
+        let config = crate::config::read_config_file()?;
+        *cache = Some(config.clone());
+        Ok(config)
+    }
+
+    /// Update the cached config and write to file
+    pub fn set_config(&self, config: McpConfig) -> crate::error::SynapticResult<()> {
+        crate::config::write_config_file(&config)?;
+        let mut cache = self.config_cache.lock().unwrap();
+        *cache = Some(config);
+        Ok(())
+    }
+
+    /// Invalidate the config cache (force re-read from disk)
+    pub fn invalidate_cache(&self) {
+        let mut cache = self.config_cache.lock().unwrap();
+        *cache = None;
+    }
+
+    /// Get the cached settings or read from file
+    pub fn get_settings(&self) -> crate::error::SynapticResult<SynapticSettings> {
+        let mut cache = self.settings_cache.lock().unwrap();
+
+        if let Some(ref settings) = *cache {
+            return Ok(settings.clone());
+        }
+
+        let settings = SynapticSettings::load()?;
+        *cache = Some(settings.clone());
+        Ok(settings)
+    }
+
+    /// Persist new settings and update the cache
+    pub fn update_settings(&self, settings: SynapticSettings) -> crate::error::SynapticResult<()> {
+        crate::redaction::validate(&settings.redaction_rules)?;
+        settings.save()?;
+        let mut cache = self.settings_cache.lock().unwrap();
+        *cache = Some(settings);
+        Ok(())
+    }
+
+    /// Approve or deny an executable for spawning servers
+    pub fn set_executable_approval(
+        &self,
+        executable: &str,
+        approved: bool,
+    ) -> crate::error::SynapticResult<SynapticSettings> {
+        let mut settings = self.get_settings()?;
+        settings.set_executable_approval(executable, approved);
+        self.update_settings(settings.clone())?;
+        Ok(settings)
+    }
+
+    /// Remove an executable from the spawn whitelist
+    pub fn remove_allowed_executable(
+        &self,
+        executable: &str,
+    ) -> crate::error::SynapticResult<SynapticSettings> {
+        let mut settings = self.get_settings()?;
+        settings.remove_allowed_executable(executable);
+        self.update_settings(settings.clone())?;
+        Ok(settings)
+    }
+
+    /// Allow the binary installed for a server, or drop it with `None`
+    pub fn set_managed_executable(
+        &self,
+        server_name: &str,
+        path: Option<PathBuf>,
+    ) -> crate::error::SynapticResult<SynapticSettings> {
+        let mut settings = self.get_settings()?;
+        settings.set_managed_executable(server_name, path);
+        self.update_settings(settings.clone())?;
+        Ok(settings)
+    }
+
+    /// Enable or disable read-only mode
+    pub fn set_read_only(&self, enabled: bool) -> crate::error::SynapticResult<()> {
+        let mut settings = self.get_settings()?;
+        settings.read_only = enabled;
+        self.update_settings(settings)
+    }
+
+    /// Fail with `ReadOnlyMode` if config mutations are currently disabled
+    pub fn ensure_writable(&self, operation: &str) -> crate::error::SynapticResult<()> {
+        if self.get_settings()?.read_only {
+            return Err(crate::error::SynapticError::ReadOnlyMode(format!(
+                "{} is not allowed",
+                operation
+            )));
+        }
+        Ok(())
+    }
+
+    /// Write the config and record the change in the audit log
+    fn commit_config(
+        &self,
+        action: AuditAction,
+        server_name: Option<&str>,
+        before: &McpConfig,
+        after: McpConfig,
+    ) -> crate::error::SynapticResult<()> {
+        self.set_config(after.clone())?;
+        self.record_audit(action, server_name, before, &after);
+        Ok(())
+    }
+
+    /// Record an audit entry; failures never block the config change itself
+    fn record_audit(
+        &self,
+        action: AuditAction,
+        server_name: Option<&str>,
+        before: &McpConfig,
+        after: &McpConfig,
+    ) {
+        if let Some(db) = &self.database {
+            if let Err(e) = audit::record(db, action, server_name, before, after) {
+                eprintln!("Failed to record audit entry: {}", e);
+            }
+        }
+    }
+
+    /// Replace the whole configuration (raw editor)
+    pub fn write_config(&self, config: McpConfig) -> crate::error::SynapticResult<()> {
+        self.ensure_writable("write_config")?;
+        let before = self.get_config()?;
+        self.commit_config(AuditAction::Write, None, &before, config)
+    }
+
+    /// Add a server to the configuration
+    pub fn add_server(&self, name: String, server: McpServer) -> crate::error::SynapticResult<()> {
+        self.ensure_writable("add_server")?;
+        let before = self.get_config()?;
+        let mut config = before.clone();
+
+        if config.mcp_servers.contains_key(&name) {
+            return Err(crate::error::SynapticError::ServerAlreadyExists(name));
+        }
+
+        config.mcp_servers.insert(name.clone(), server);
+        self.commit_config(AuditAction::Add, Some(&name), &before, config)
+    }
+
+    /// Remove a server from the configuration
+    pub fn remove_server(&self, name: &str) -> crate::error::SynapticResult<()> {
+        self.remove_server_as(name, AuditAction::Remove)
+    }
+
+    /// Remove a server, recording the removal in the audit log as `action`
+    pub fn remove_server_as(
+        &self,
+        name: &str,
+        action: AuditAction,
+    ) -> crate::error::SynapticResult<()> {
+        self.ensure_writable("remove_server")?;
+        let before = self.get_config()?;
+        let mut config = before.clone();
+
+        if config.mcp_servers.remove(name).is_none() {
+            return Err(crate::error::SynapticError::ServerNotFound(name.to_string()));
+        }
+
+        self.commit_config(action, Some(name), &before, config)?;
+
+        // Drop per-target state so a future server with the same name starts clean
+        if let Ok(mut metadata) = SynapticMetadata::load() {
+            if metadata.remove_server(name) {
+                let _ = metadata.save();
+            }
+        }
+        Ok(())
+    }
+
+    /// Update an existing server
+    pub fn update_server(&self, name: &str, server: McpServer) -> crate::error::SynapticResult<()> {
+        self.ensure_writable("update_server")?;
+        let before = self.get_config()?;
+        let mut config = before.clone();
+
+        if !config.mcp_servers.contains_key(name) {
+            return Err(crate::error::SynapticError::ServerNotFound(name.to_string()));
+        }
+
+        config.mcp_servers.insert(name.to_string(), server);
+        self.commit_config(AuditAction::Update, Some(name), &before, config)
+    }
+
+    /// Toggle server enabled state
+    pub fn toggle_server(&self, name: &str, enabled: bool) -> crate::error::SynapticResult<()> {
+        self.ensure_writable("toggle_server")?;
+        let before = self.get_config()?;
+        let mut config = before.clone();
+
+        let server = config
+            .mcp_servers
+            .get_mut(name)
+            .ok_or_else(|| crate::error::SynapticError::ServerNotFound(name.to_string()))?;
+
+        server.enabled = enabled;
+        self.commit_config(AuditAction::Toggle, Some(name), &before, config)
+    }
+
+    /// Enable or disable a server for a single target client
+    ///
+    /// The Claude Desktop target maps onto the config's own `enabled` flag;
+    /// other targets are tracked in Synaptic's metadata store.
+    pub fn toggle_server_for_target(
+        &self,
+        name: &str,
+        target: &str,
+        enabled: bool,
+    ) -> crate::error::SynapticResult<()> {
+        self.ensure_writable("toggle_server_for_target")?;
+
+        if target == CLAUDE_DESKTOP_TARGET {
+            return self.toggle_server(name, enabled);
+        }
+
+        let config = self.get_config()?;
+        if !config.mcp_servers.contains_key(name) {
+            return Err(crate::error::SynapticError::ServerNotFound(name.to_string()));
+        }
+
+        let mut metadata = SynapticMetadata::load()?;
+        let before = metadata.server(name);
+        metadata.set_target_enabled(name, target, enabled);
+        metadata.save()?;
+
+        if let Some(db) = &self.database {
+            let after = metadata.server(name);
+            if let Err(e) = audit::record_change(
+                db,
+                AuditAction::Toggle,
+                Some(name),
+                serde_json::to_value(before).ok(),
+                serde_json::to_value(after).ok(),
+            ) {
+                eprintln!("Failed to record audit entry: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the effective enable state of a server for every known target
+    pub fn get_server_targets(
+        &self,
+        name: &str,
+    ) -> crate::error::SynapticResult<BTreeMap<String, bool>> {
+        let config = self.get_config()?;
+        let server = config
+            .mcp_servers
+            .get(name)
+            .ok_or_else(|| crate::error::SynapticError::ServerNotFound(name.to_string()))?;
+
+        let mut targets = SynapticMetadata::load()?.server(name).targets;
+        targets.insert(CLAUDE_DESKTOP_TARGET.to_string(), server.enabled);
+        Ok(targets)
+    }
+
+    /// Restore the configuration from a backup
+    pub fn restore_backup(&self, backup_id: &str) -> crate::error::SynapticResult<()> {
+        self.ensure_writable("restore_backup")?;
+        let before = self.get_config()?;
+        crate::config::restore_from_backup(backup_id)?;
+
+        // Invalidate cache to force re-read
+        self.invalidate_cache();
+        let after = self.get_config()?;
+        self.record_audit(AuditAction::Restore, None, &before, &after);
+        Ok(())
+    }
+
+    /// Add an inspector message, evicting the oldest beyond the server's history cap
+    pub fn add_inspector_message(&self, server_name: &str, message: InspectorMessage) {
+        let cap = self
+            .settings_cache
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(SynapticSettings::default().inspector_history_cap, |s| {
+                s.inspector_history_cap
+            });
+        let retention = self.history_retention(server_name);
+        let cap = retention.history_cap.unwrap_or(cap);
+        let persisting =
+            self.database.is_some() && retention.mirror.keeps(false, Some(&message.payload));
+        let mut messages = self.inspector_messages.lock().unwrap();
+        messages
+            .entry(server_name.to_string())
+            .or_default()
+            .push(message, cap, persisting);
+    }
+
+    /// History retention of a server (the default when the settings have none)
+    pub fn history_retention(&self, server_name: &str) -> HistoryRetention {
+        match self.settings_cache.lock().unwrap().as_ref() {
+            Some(settings) => {
+                history_retention::for_server(&settings.history_retention, server_name)
+            }
+            None => HistoryRetention::new(server_name),
+        }
+    }
+
+    /// File a server's traffic is mirrored to, if any
+    pub fn traffic_mirror_path(&self, server_name: &str) -> Option<PathBuf> {
+        let settings = self.settings_cache.lock().unwrap();
+        let mirrors = &settings.as_ref()?.traffic_mirrors;
+        traffic_mirror::path_for(mirrors, server_name).map(Path::to_path_buf)
+    }
+
+    /// Start mirroring a server's traffic to `path`, or stop with `None`
+    pub fn set_traffic_mirror(
+        &self,
+        server_name: &str,
+        path: Option<PathBuf>,
+    ) -> crate::error::SynapticResult<SynapticSettings> {
+        if let Some(path) = &path {
+            traffic_mirror::validate_path(path)?;
+        }
+        let mut settings = self.get_settings()?;
+        settings.set_traffic_mirror(server_name, path);
+        self.update_settings(settings.clone())?;
+        Ok(settings)
+    }
+
+    /// Set or reset (with `None`) a server's history retention
+    pub fn set_history_retention(
+        &self,
+        server_name: &str,
+        retention: Option<HistoryRetention>,
+    ) -> crate::error::SynapticResult<SynapticSettings> {
+        let mut settings = self.get_settings()?;
+        settings.set_history_retention(server_name, retention);
+        self.update_settings(settings.clone())?;
+        Ok(settings)
+    }
+
+    /// Note inspector messages written to `system_logs`, so they may be evicted
+    pub fn mark_inspector_persisted<'a>(
+        &self,
+        server_name: &str,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) {
+        let mut messages = self.inspector_messages.lock().unwrap();
+        if let Some(history) = messages.get_mut(server_name) {
+            history.mark_persisted(ids);
+        }
+    }
+
+    /// Flag a server's inspector messages of a cancelled request
+    pub fn mark_inspector_cancelled(&self, server_name: &str, request_id: &serde_json::Value) {
+        let mut messages = self.inspector_messages.lock().unwrap();
+        if let Some(history) = messages.get_mut(server_name) {
+            history.mark_cancelled(request_id);
+        }
+    }
+
+    /// Get inspector messages for a server
+    pub fn get_inspector_messages(&self, server_name: &str) -> Vec<InspectorMessage> {
+        let messages = self.inspector_messages.lock().unwrap();
+        messages
+            .get(server_name)
+            .map(|history| history.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Find one inspector message of a server by id
+    pub fn find_inspector_message(&self, server_name: &str, id: &str) -> Option<InspectorMessage> {
+        let messages = self.inspector_messages.lock().unwrap();
+        messages
+            .get(server_name)
+            .and_then(|history| history.iter().find(|message| message.id == id).cloned())
+    }
+
+    /// Clear inspector messages for a server
+    pub fn clear_inspector_messages(&self, server_name: &str) {
+        let mut messages = self.inspector_messages.lock().unwrap();
+        messages.remove(server_name);
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// State for an active inspector session
+#[derive(Debug, Clone)]
+pub struct InspectorSessionState {
+    pub server_name: String,
+    pub is_active: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SynapticError;
+
+    #[test]
+    fn test_read_only_rejects_mutations() {
+        let state = AppState::new();
+        *state.settings_cache.lock().unwrap() = Some(SynapticSettings {
+            read_only: true,
+            ..SynapticSettings::default()
+        });
+
+        assert!(matches!(
+            state.write_config(McpConfig::default()),
+            Err(SynapticError::ReadOnlyMode(_))
+        ));
+        assert!(matches!(
+            state.remove_server("github"),
+            Err(SynapticError::ReadOnlyMode(_))
+        ));
+        assert!(matches!(
+            state.restore_backup("2025-12-19T08-30-00"),
+            Err(SynapticError::ReadOnlyMode(_))
+        ));
+    }
+}
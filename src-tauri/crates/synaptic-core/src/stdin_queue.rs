@@ -0,0 +1,195 @@
+//! Bounded queue of messages waiting for a server's stdin
+//!
+//! Senders hand messages to the queue and a writer task feeds them to the
+//! process. When the server reads slower than messages arrive (replays, load
+//! tests) the queue fills up and the configured overflow policy decides
+//! whether senders wait, the oldest message is dropped or the send fails.
+
+use crate::error::{SynapticError, SynapticResult};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+/// What a send does when the stdin queue is full
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum StdinOverflow {
+    /// Wait for the server to catch up
+    #[default]
+    Block,
+    /// Drop the oldest queued message to make room
+    DropOldest,
+    /// Fail the send
+    Error,
+}
+
+#[derive(Default)]
+struct QueueState {
+    messages: VecDeque<String>,
+    closed: bool,
+    dropped: u64,
+}
+
+/// Messages on their way to one process's stdin
+pub struct StdinQueue {
+    state: Mutex<QueueState>,
+    /// Wakes the writer when a message arrives
+    readable: Notify,
+    /// Wakes blocked senders when room frees up (or the queue closes)
+    writable: Notify,
+    capacity: usize,
+    overflow: StdinOverflow,
+}
+
+impl StdinQueue {
+    pub fn new(capacity: usize, overflow: StdinOverflow) -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            readable: Notify::new(),
+            writable: Notify::new(),
+            capacity: capacity.max(1),
+            overflow,
+        }
+    }
+
+    /// Queue a message, applying the overflow policy when full
+    pub async fn push(&self, message: String) -> SynapticResult<()> {
+        loop {
+            // Registered before checking, so a pop in between isn't missed
+            let writable = self.writable.notified();
+            if self.try_push(&message)? {
+                self.readable.notify_one();
+                return Ok(());
+            }
+            writable.await;
+        }
+    }
+
+    /// Queue a message unless it has to wait for room
+    fn try_push(&self, message: &str) -> SynapticResult<bool> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return Err(SynapticError::ProcessError(
+                "Failed to send: stdin is closed".to_string(),
+            ));
+        }
+
+        if state.messages.len() >= self.capacity {
+            match self.overflow {
+                StdinOverflow::Block => return Ok(false),
+                StdinOverflow::DropOldest => {
+                    state.messages.pop_front();
+                    state.dropped += 1;
+                }
+                StdinOverflow::Error => {
+                    return Err(SynapticError::ProcessError(format!(
+                        "Failed to send: stdin queue is full ({} messages)",
+                        self.capacity
+                    )));
+                }
+            }
+        }
+        state.messages.push_back(message.to_string());
+        Ok(true)
+    }
+
+    /// Next message for the writer; none once closed and drained
+    pub async fn pop(&self) -> Option<String> {
+        self.pop_batch(1)
+            .await
+            .and_then(|batch| batch.into_iter().next())
+    }
+
+    /// Up to `max` queued messages, waiting for at least one; none once closed and drained
+    pub async fn pop_batch(&self, max: usize) -> Option<Vec<String>> {
+        loop {
+            let readable = self.readable.notified();
+            {
+                let mut state = self.state.lock().unwrap();
+                if !state.messages.is_empty() {
+                    let count = max.max(1).min(state.messages.len());
+                    let batch: Vec<String> = state.messages.drain(..count).collect();
+                    drop(state);
+                    // Several blocked senders may fit now
+                    self.writable.notify_waiters();
+                    return Some(batch);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            readable.await;
+        }
+    }
+
+    /// Reject further sends and wake everyone waiting
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.readable.notify_waiters();
+        self.writable.notify_waiters();
+    }
+
+    /// Messages waiting to be written
+    pub fn depth(&self) -> usize {
+        self.state.lock().unwrap().messages.len()
+    }
+
+    /// Messages discarded by the drop-oldest policy
+    pub fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_overflow_policies() {
+        let queue = StdinQueue::new(2, StdinOverflow::DropOldest);
+        for message in ["a", "b", "c"] {
+            queue.push(message.to_string()).await.unwrap();
+        }
+        assert_eq!(queue.depth(), 2);
+        assert_eq!(queue.dropped(), 1);
+        assert_eq!(queue.pop().await.as_deref(), Some("b"));
+        queue.push("d".to_string()).await.unwrap();
+        assert_eq!(queue.pop_batch(10).await.unwrap(), ["c", "d"]);
+
+        let queue = StdinQueue::new(1, StdinOverflow::Error);
+        queue.push("a".to_string()).await.unwrap();
+        assert!(queue.push("b".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_blocked_send_waits_for_room_or_close() {
+        let queue = Arc::new(StdinQueue::new(1, StdinOverflow::Block));
+        queue.push("a".to_string()).await.unwrap();
+
+        let sender = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.push("b".to_string()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!sender.is_finished());
+
+        assert_eq!(queue.pop().await.as_deref(), Some("a"));
+        sender.await.unwrap().unwrap();
+        assert_eq!(queue.depth(), 1);
+
+        let blocked = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.push("c".to_string()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        queue.close();
+        assert!(blocked.await.unwrap().is_err());
+
+        // Whatever was queued before closing is still written
+        assert_eq!(queue.pop().await.as_deref(), Some("b"));
+        assert_eq!(queue.pop().await, None);
+    }
+}
@@ -0,0 +1,855 @@
+//! Persistent traffic log in the `system_logs` table
+//!
+//! Every run of a server process is a session. Its traffic batcher hands
+//! each batch to a logging service over a channel, and the service writes
+//! the messages to `system_logs` (and the run to `sessions`) independently
+//! of whether a webview is listening. [`query`] reads it back for the
+//! inspector's history view.
+
+use crate::database::Database;
+use crate::error::SynapticResult;
+use crate::host::Host;
+use crate::log_level::LogLevel;
+use crate::process_manager::McpTrafficEvent;
+use crate::timeouts::RequestTimeout;
+use crate::tool_stats::{self, TimeRange, ToolCallRecord};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tokio::sync::mpsc;
+
+/// What the logging service is asked to persist
+#[derive(Debug, Clone)]
+pub enum LogRecord {
+    SessionStarted {
+        session_id: String,
+        server_name: String,
+        started_at: DateTime<Utc>,
+    },
+    Traffic {
+        session_id: String,
+        events: Vec<McpTrafficEvent>,
+    },
+    SessionEnded {
+        session_id: String,
+        ended_at: DateTime<Utc>,
+    },
+    /// A `tools/call` that got its response
+    ToolCall(ToolCallRecord),
+    /// A request still unanswered after `requestTimeoutSecs`
+    RequestTimeout(RequestTimeout),
+}
+
+/// Sending half held by the process manager
+pub type LogSink = mpsc::UnboundedSender<LogRecord>;
+
+/// Channel between the traffic batchers and [`run_log_writer`]
+pub fn channel() -> (LogSink, mpsc::UnboundedReceiver<LogRecord>) {
+    mpsc::unbounded_channel()
+}
+
+/// Logging service: persists records until every sink is gone
+pub async fn run_log_writer<H: Host>(host: H, mut records: mpsc::UnboundedReceiver<LogRecord>) {
+    while let Some(record) = records.recv().await {
+        let Some(db) = host.app_state().and_then(|state| state.database.as_ref()) else {
+            continue;
+        };
+        if let Err(e) = write(db, &record) {
+            eprintln!("Failed to persist traffic log: {}", e);
+            continue;
+        }
+        // Persisted messages may now leave the in-memory inspector history
+        if let (LogRecord::Traffic { events, .. }, Some(state)) = (&record, host.app_state()) {
+            for event in events {
+                state.mark_inspector_persisted(&event.server_id, [event.message_id.as_str()]);
+            }
+        }
+    }
+}
+
+/// Persist one record
+pub fn write(db: &Database, record: &LogRecord) -> SynapticResult<()> {
+    match record {
+        LogRecord::SessionStarted {
+            session_id,
+            server_name,
+            started_at,
+        } => {
+            db.conn().execute(
+                "INSERT OR IGNORE INTO sessions (id, started_at, server_name, log_count)
+                 VALUES (?1, ?2, ?3, 0)",
+                params![session_id, started_at.timestamp_millis(), server_name],
+            )?;
+        }
+        LogRecord::Traffic { session_id, events } => insert_traffic(db, session_id, events)?,
+        LogRecord::SessionEnded {
+            session_id,
+            ended_at,
+        } => {
+            db.conn().execute(
+                "UPDATE sessions SET ended_at = ?2 WHERE id = ?1",
+                params![session_id, ended_at.timestamp_millis()],
+            )?;
+        }
+        LogRecord::ToolCall(call) => tool_stats::record(db, call)?,
+        LogRecord::RequestTimeout(timeout) => mark_timed_out(db, timeout)?,
+    }
+    Ok(())
+}
+
+/// Mark the most recent logged row of a timed-out request
+fn mark_timed_out(db: &Database, timeout: &RequestTimeout) -> SynapticResult<()> {
+    db.conn().execute(
+        &format!(
+            "UPDATE system_logs SET level = 'WARN', category = 'TIMEOUT'
+             WHERE id = (SELECT MAX(l.id) FROM system_logs l
+                         WHERE l.server_name = ?1 AND l.trace_id = ?2 AND l.direction = 'OUTGOING'
+                           AND {LIVE_SESSIONS})"
+        ),
+        params![timeout.server_name, timeout.request_id.to_string()],
+    )?;
+    Ok(())
+}
+
+/// Insert a batch of traffic in one transaction
+pub fn insert_traffic(
+    db: &Database,
+    session_id: &str,
+    events: &[McpTrafficEvent],
+) -> SynapticResult<()> {
+    let mut conn = db.conn();
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO system_logs
+                (session_id, timestamp, level, category, message, payload, trace_id, server_name, direction)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        for event in events {
+            let timestamp = DateTime::parse_from_rfc3339(&event.timestamp)
+                .map(|t| t.timestamp_millis())
+                .unwrap_or_else(|_| Utc::now().timestamp_millis());
+            let parsed: Option<Value> = serde_json::from_str(&event.content).ok();
+            let stderr = event.direction == "STDERR";
+
+            stmt.execute(params![
+                session_id,
+                timestamp,
+                match event.level {
+                    Some(level) => level.as_str(),
+                    None if stderr => "WARN",
+                    None => "INFO",
+                },
+                if stderr { "STDERR" } else { "TRAFFIC" },
+                parsed
+                    .as_ref()
+                    .and_then(|p| p.get("method"))
+                    .and_then(|m| m.as_str()),
+                parsed
+                    .clone()
+                    .unwrap_or(Value::String(event.content.clone())),
+                parsed
+                    .as_ref()
+                    .and_then(|p| p.get("id"))
+                    .filter(|id| !id.is_null())
+                    .map(|id| id.to_string()),
+                event.server_id,
+                event.direction,
+            ])?;
+
+            // A cancellation marks the request it cancels
+            let cancels = parsed
+                .as_ref()
+                .filter(|p| {
+                    p.get("method").and_then(|m| m.as_str()) == Some("notifications/cancelled")
+                })
+                .and_then(|p| p.pointer("/params/requestId"));
+            if let (Some(request_id), "OUTGOING") = (cancels, event.direction.as_str()) {
+                tx.execute(
+                    "UPDATE system_logs SET category = 'CANCELLED'
+                     WHERE id = (SELECT MAX(id) FROM system_logs
+                                 WHERE session_id = ?1 AND trace_id = ?2 AND direction = 'OUTGOING')",
+                    params![session_id, request_id.to_string()],
+                )?;
+            }
+        }
+    }
+    tx.execute(
+        "UPDATE sessions SET log_count = log_count + ?2 WHERE id = ?1",
+        params![session_id, events.len() as i64],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+// ============================================
+// QUERIES
+// ============================================
+
+/// One persisted row of `system_logs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub id: i64,
+    pub session_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub category: String,
+    /// JSON-RPC method, when the message has one
+    pub message: Option<String>,
+    pub payload: Option<Value>,
+    /// JSON-RPC id, pairing requests with their responses
+    pub trace_id: Option<String>,
+    pub server_name: Option<String>,
+    /// `OUTGOING`, `INCOMING` or `STDERR`
+    pub direction: Option<String>,
+}
+
+/// Filters for [`query`]; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogQuery {
+    pub server: Option<String>,
+    pub direction: Option<String>,
+    pub level: Option<String>,
+    /// At least this severe (rows of unknown levels count as `INFO`)
+    pub min_level: Option<LogLevel>,
+    pub method: Option<String>,
+    /// Substring of the method or payload
+    pub text: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// Rows returned when a query sets no limit
+pub const DEFAULT_QUERY_LIMIT: usize = 500;
+
+/// Columns of a [`LogEntry`], from `system_logs` aliased as `l`
+pub(crate) const ENTRY_COLUMNS: &str =
+    "l.id, l.session_id, l.timestamp, l.level, l.category, l.message, \
+     l.payload, l.trace_id, l.server_name, l.direction";
+
+/// Excludes rows of imported sessions (from `system_logs` aliased as `l`)
+///
+/// Imported sessions are only shown on their own, never mixed into the views
+/// of live traffic.
+pub(crate) const LIVE_SESSIONS: &str =
+    "l.session_id NOT IN (SELECT id FROM sessions WHERE imported_from IS NOT NULL)";
+
+/// [`LogQuery`] filters as a WHERE clause over `?1`..`?7` and `?10`
+///
+/// The `CASE` ranks levels like [`LogLevel::rank`].
+const FILTER_CLAUSE: &str = "(?1 IS NULL OR l.server_name = ?1)
+       AND (?2 IS NULL OR l.direction = ?2)
+       AND (?3 IS NULL OR l.level = ?3)
+       AND (?4 IS NULL OR l.message = ?4)
+       AND (?5 IS NULL OR l.message LIKE '%' || ?5 || '%' OR l.payload LIKE '%' || ?5 || '%')
+       AND (?6 IS NULL OR l.timestamp >= ?6)
+       AND (?7 IS NULL OR l.timestamp <= ?7)
+       AND (?10 IS NULL OR CASE l.level
+                WHEN 'TRACE' THEN 0 WHEN 'DEBUG' THEN 1 WHEN 'WARN' THEN 3
+                WHEN 'ERROR' THEN 4 WHEN 'FATAL' THEN 5 ELSE 2 END >= ?10)";
+
+/// Logged traffic matching `filter`, newest first
+pub fn query(db: &Database, filter: &LogQuery) -> SynapticResult<Vec<LogEntry>> {
+    let sql = format!(
+        "SELECT {ENTRY_COLUMNS} FROM system_logs l
+         WHERE {FILTER_CLAUSE} AND {LIVE_SESSIONS}
+         ORDER BY l.timestamp DESC, l.id DESC
+         LIMIT ?8 OFFSET ?9"
+    );
+    select(db, &sql, filter, None)
+}
+
+/// One logged row by id
+pub fn get_entry(db: &Database, id: i64) -> SynapticResult<Option<LogEntry>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ENTRY_COLUMNS} FROM system_logs l WHERE l.id = ?1"
+    ))?;
+    let mut rows = stmt.query_map(params![id], entry_from_row)?;
+    Ok(rows.next().transpose()?)
+}
+
+/// Full-text search over logged methods and payloads, best matches first
+///
+/// Every word of `text` must appear; each is matched as a literal phrase, so
+/// `/etc/passwd` finds payloads mentioning that path.
+pub fn search(db: &Database, text: &str, filter: &LogQuery) -> SynapticResult<Vec<LogEntry>> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let sql = format!(
+        "SELECT {ENTRY_COLUMNS} FROM system_logs_fts f
+         JOIN system_logs l ON l.id = f.rowid
+         WHERE system_logs_fts MATCH ?11 AND {FILTER_CLAUSE} AND {LIVE_SESSIONS}
+         ORDER BY f.rank, l.id DESC
+         LIMIT ?8 OFFSET ?9"
+    );
+    select(db, &sql, filter, Some(terms.join(" ")))
+}
+
+fn select(
+    db: &Database,
+    sql: &str,
+    filter: &LogQuery,
+    fts_match: Option<String>,
+) -> SynapticResult<Vec<LogEntry>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(sql)?;
+    let text = filter.text.as_deref().filter(|text| !text.is_empty());
+    let from = filter.from.map(|t| t.timestamp_millis());
+    let to = filter.to.map(|t| t.timestamp_millis());
+    let limit = filter.limit.unwrap_or(DEFAULT_QUERY_LIMIT) as i64;
+    let offset = filter.offset.unwrap_or(0) as i64;
+    let min_level = filter.min_level.map(LogLevel::rank);
+
+    let mut values = params![
+        filter.server,
+        filter.direction,
+        filter.level,
+        filter.method,
+        text,
+        from,
+        to,
+        limit,
+        offset,
+        min_level,
+    ]
+    .to_vec();
+    if let Some(fts_match) = &fts_match {
+        values.push(fts_match);
+    }
+
+    let rows = stmt.query_map(values.as_slice(), entry_from_row)?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+pub(crate) fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<LogEntry> {
+    let millis: i64 = row.get(2)?;
+    Ok(LogEntry {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        timestamp: from_millis(millis),
+        level: row.get(3)?,
+        category: row.get(4)?,
+        message: row.get(5)?,
+        payload: row.get(6)?,
+        trace_id: row.get(7)?,
+        server_name: row.get(8)?,
+        direction: row.get(9)?,
+    })
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .unwrap_or_default()
+}
+
+// ============================================
+// TIMELINE
+// ============================================
+
+/// Message counts of one time bucket
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineBucket {
+    pub start: DateTime<Utc>,
+    pub outgoing: u64,
+    pub incoming: u64,
+    pub stderr: u64,
+    /// JSON-RPC error responses
+    pub errors: u64,
+}
+
+/// Most buckets a timeline is padded to; longer ones only hold non-empty buckets
+pub const MAX_TIMELINE_BUCKETS: i64 = 10_000;
+
+/// Logged traffic counted per `bucket_seconds`, oldest first
+///
+/// Buckets without traffic between the first and last one (or the range
+/// bounds, when set) are included with zero counts.
+pub fn timeline(
+    db: &Database,
+    server_name: Option<&str>,
+    bucket_seconds: u64,
+    range: &TimeRange,
+) -> SynapticResult<Vec<TimelineBucket>> {
+    let bucket_ms = bucket_seconds.max(1) as i64 * 1000;
+    let from = range.from.map(|t| t.timestamp_millis());
+    let to = range.to.map(|t| t.timestamp_millis());
+
+    let conn = db.conn();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT (l.timestamp / ?2) * ?2 AS bucket,
+                SUM(l.direction = 'OUTGOING'),
+                SUM(l.direction = 'INCOMING'),
+                SUM(l.direction = 'STDERR'),
+                SUM(CASE WHEN json_valid(l.payload)
+                    THEN json_type(l.payload, '$.error') IS NOT NULL ELSE 0 END)
+         FROM system_logs l
+         WHERE (?1 IS NULL OR l.server_name = ?1)
+           AND (?3 IS NULL OR l.timestamp >= ?3)
+           AND (?4 IS NULL OR l.timestamp <= ?4)
+           AND {LIVE_SESSIONS}
+         GROUP BY bucket
+         ORDER BY bucket"
+    ))?;
+    let rows = stmt.query_map(params![server_name, bucket_ms, from, to], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            TimelineBucket {
+                start: from_millis(row.get(0)?),
+                outgoing: row.get::<_, i64>(1)? as u64,
+                incoming: row.get::<_, i64>(2)? as u64,
+                stderr: row.get::<_, i64>(3)? as u64,
+                errors: row.get::<_, i64>(4)? as u64,
+            },
+        ))
+    })?;
+    let mut buckets: BTreeMap<i64, TimelineBucket> = rows.collect::<Result<_, _>>()?;
+
+    let floor = |millis: i64| millis.div_euclid(bucket_ms) * bucket_ms;
+    let first = from.map(floor).or(buckets.keys().next().copied());
+    let last = to.map(floor).or(buckets.keys().next_back().copied());
+    if let (Some(first), Some(last)) = (first, last) {
+        if first <= last && (last - first) / bucket_ms < MAX_TIMELINE_BUCKETS {
+            for start in (first..=last).step_by(bucket_ms as usize) {
+                buckets.entry(start).or_insert_with(|| TimelineBucket {
+                    start: from_millis(start),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    Ok(buckets.into_values().collect())
+}
+
+// ============================================
+// SESSIONS
+// ============================================
+
+/// One row of `sessions`: a run of a server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub server_name: Option<String>,
+    pub log_count: i64,
+    pub description: Option<String>,
+    /// File the session was imported from; imported sessions are read-only
+    #[serde(default)]
+    pub imported_from: Option<String>,
+}
+
+/// A session's metadata, if it has a `sessions` row
+pub fn get_session(db: &Database, session_id: &str) -> SynapticResult<Option<SessionInfo>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT id, started_at, ended_at, server_name, log_count, description, imported_from
+         FROM sessions WHERE id = ?1",
+    )?;
+    let mut rows = stmt.query_map(params![session_id], |row| {
+        Ok(SessionInfo {
+            id: row.get(0)?,
+            started_at: from_millis(row.get(1)?),
+            ended_at: row.get::<_, Option<i64>>(2)?.map(from_millis),
+            server_name: row.get(3)?,
+            log_count: row.get::<_, Option<i64>>(4)?.unwrap_or_default(),
+            description: row.get(5)?,
+            imported_from: row.get(6)?,
+        })
+    })?;
+    Ok(rows.next().transpose()?)
+}
+
+/// Store a whole session and its messages in one transaction
+///
+/// Entry ids and session ids are replaced by the ones of the new rows.
+pub fn insert_session(
+    db: &Database,
+    session: &SessionInfo,
+    entries: &[LogEntry],
+) -> SynapticResult<()> {
+    let mut conn = db.conn();
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO system_logs
+                (session_id, timestamp, level, category, message, payload, trace_id, server_name, direction)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        for entry in entries {
+            stmt.execute(params![
+                session.id,
+                entry.timestamp.timestamp_millis(),
+                entry.level,
+                entry.category,
+                entry.message,
+                entry.payload,
+                entry.trace_id,
+                entry.server_name,
+                entry.direction,
+            ])?;
+        }
+    }
+    // Last: once the row marks the session imported, its logs can't be added to
+    tx.execute(
+        "INSERT INTO sessions
+            (id, started_at, ended_at, server_name, log_count, description, imported_from)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            session.id,
+            session.started_at.timestamp_millis(),
+            session.ended_at.map(|t| t.timestamp_millis()),
+            session.server_name,
+            entries.len() as i64,
+            session.description,
+            session.imported_from,
+        ],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Every logged message of a session, oldest first
+pub fn session_entries(db: &Database, session_id: &str) -> SynapticResult<Vec<LogEntry>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {ENTRY_COLUMNS} FROM system_logs l
+         WHERE l.session_id = ?1
+         ORDER BY l.timestamp, l.id"
+    ))?;
+    let rows = stmt.query_map(params![session_id], entry_from_row)?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(direction: &str, content: &str) -> McpTrafficEvent {
+        McpTrafficEvent {
+            server_id: "echo".to_string(),
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            direction: direction.to_string(),
+            content: content.to_string(),
+            message_id: uuid::Uuid::new_v4().to_string(),
+            level: None,
+        }
+    }
+
+    #[test]
+    fn test_traffic_is_persisted_per_session() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+
+        for record in [
+            LogRecord::SessionStarted {
+                session_id: "s1".to_string(),
+                server_name: "echo".to_string(),
+                started_at: now,
+            },
+            LogRecord::Traffic {
+                session_id: "s1".to_string(),
+                events: vec![
+                    event(
+                        "OUTGOING",
+                        r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+                    ),
+                    event("INCOMING", r#"{"jsonrpc":"2.0","id":1,"result":{}}"#),
+                    event("STDERR", "listening"),
+                ],
+            },
+            LogRecord::SessionEnded {
+                session_id: "s1".to_string(),
+                ended_at: now,
+            },
+        ] {
+            write(&db, &record).unwrap();
+        }
+
+        let conn = db.conn();
+        // (category, message, trace_id, payload)
+        type Row = (String, Option<String>, Option<String>, String);
+        let rows: Vec<Row> = conn
+            .prepare(
+                "SELECT category, message, trace_id, payload
+                 FROM system_logs WHERE session_id = 's1' ORDER BY id",
+            )
+            .unwrap()
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].1.as_deref(), Some("tools/list"));
+        assert_eq!(rows[1].2.as_deref(), Some("1"));
+        assert_eq!(rows[2].0, "STDERR");
+        assert_eq!(rows[2].3, r#""listening""#);
+
+        let (count, ended): (i64, Option<i64>) = conn
+            .query_row(
+                "SELECT log_count, ended_at FROM sessions WHERE id = 's1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(count, 3);
+        assert!(ended.is_some());
+    }
+
+    #[test]
+    fn test_cancellation_marks_the_request() {
+        let db = Database::open_in_memory().unwrap();
+        insert_traffic(
+            &db,
+            "s1",
+            &[event(
+                "OUTGOING",
+                r#"{"jsonrpc":"2.0","id":"a","method":"tools/call"}"#,
+            )],
+        )
+        .unwrap();
+        insert_traffic(
+            &db,
+            "s1",
+            &[event(
+                "OUTGOING",
+                r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":"a"}}"#,
+            )],
+        )
+        .unwrap();
+
+        let rows = query(&db, &LogQuery::default()).unwrap();
+        let request = rows.iter().find(|row| row.trace_id.is_some()).unwrap();
+        assert_eq!(request.category, "CANCELLED");
+        assert_eq!(
+            rows.iter()
+                .filter(|row| row.category == "CANCELLED")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_query_filters_and_pages() {
+        let db = Database::open_in_memory().unwrap();
+        let mut late = event(
+            "INCOMING",
+            r#"{"jsonrpc":"2.0","id":2,"result":{"tools":[]}}"#,
+        );
+        late.timestamp = "2025-01-02T00:00:00Z".to_string();
+        insert_traffic(
+            &db,
+            "s1",
+            &[
+                event(
+                    "OUTGOING",
+                    r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+                ),
+                event("STDERR", "warming cache"),
+                late,
+            ],
+        )
+        .unwrap();
+
+        let all = query(&db, &LogQuery::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].trace_id.as_deref(), Some("2"));
+
+        let by_method = LogQuery {
+            method: Some("tools/list".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            query(&db, &by_method).unwrap()[0].direction.as_deref(),
+            Some("OUTGOING")
+        );
+
+        let by_text = LogQuery {
+            text: Some("cache".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(query(&db, &by_text).unwrap()[0].category, "STDERR");
+
+        let since = LogQuery {
+            from: Some("2025-01-01T12:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(query(&db, &since).unwrap().len(), 1);
+
+        let paged = LogQuery {
+            limit: Some(2),
+            offset: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(query(&db, &paged).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_stderr_levels_are_stored_and_filtered() {
+        let db = Database::open_in_memory().unwrap();
+        let stderr = |line: &str| McpTrafficEvent {
+            level: crate::log_level::parse_stderr_level(line),
+            ..event("STDERR", line)
+        };
+        insert_traffic(
+            &db,
+            "s1",
+            &[
+                event(
+                    "OUTGOING",
+                    r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+                ),
+                stderr("[DEBUG] tick"),
+                stderr("listening on stdio"),
+                stderr(r#"{"level":50,"msg":"db down"}"#),
+            ],
+        )
+        .unwrap();
+
+        let levels = |min_level: Option<LogLevel>| -> Vec<String> {
+            let filter = LogQuery {
+                min_level,
+                ..Default::default()
+            };
+            let mut levels: Vec<_> = query(&db, &filter)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.level)
+                .collect();
+            levels.sort();
+            levels
+        };
+        assert_eq!(levels(None), ["DEBUG", "ERROR", "INFO", "WARN"]);
+        assert_eq!(levels(Some(LogLevel::Info)), ["ERROR", "INFO", "WARN"]);
+        assert_eq!(levels(Some(LogLevel::Error)), ["ERROR"]);
+
+        // Still combines with full-text search
+        let errors = LogQuery {
+            min_level: Some(LogLevel::Warn),
+            ..Default::default()
+        };
+        assert_eq!(search(&db, "db down", &errors).unwrap().len(), 1);
+        assert!(search(&db, "tick", &errors).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_full_text_search() {
+        let db = Database::open_in_memory().unwrap();
+        insert_traffic(
+            &db,
+            "s1",
+            &[
+                event(
+                    "OUTGOING",
+                    r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"read_file","arguments":{"path":"/etc/passwd"}}}"#,
+                ),
+                event(
+                    "OUTGOING",
+                    r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"read_file","arguments":{"path":"/etc/hosts"}}}"#,
+                ),
+                event("STDERR", "reading /etc/passwd"),
+            ],
+        )
+        .unwrap();
+
+        let all = LogQuery::default();
+        assert_eq!(search(&db, "/etc/passwd", &all).unwrap().len(), 2);
+        assert_eq!(
+            search(&db, "hosts read_file", &all).unwrap()[0]
+                .trace_id
+                .as_deref(),
+            Some("2")
+        );
+        assert!(search(&db, "\"unbalanced", &all).unwrap().is_empty());
+        assert!(search(&db, "   ", &all).unwrap().is_empty());
+
+        let outgoing = LogQuery {
+            direction: Some("OUTGOING".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(search(&db, "passwd", &outgoing).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_traffic_timeline_buckets() {
+        let db = Database::open_in_memory().unwrap();
+        let at = |timestamp: &str, direction: &str, content: &str| McpTrafficEvent {
+            timestamp: timestamp.to_string(),
+            ..event(direction, content)
+        };
+        insert_traffic(
+            &db,
+            "s1",
+            &[
+                at(
+                    "2025-01-01T00:00:05Z",
+                    "OUTGOING",
+                    r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+                ),
+                at(
+                    "2025-01-01T00:00:20Z",
+                    "INCOMING",
+                    r#"{"jsonrpc":"2.0","id":1,"error":{"code":-1,"message":"x"}}"#,
+                ),
+                at("2025-01-01T00:00:50Z", "STDERR", "warming up"),
+                at(
+                    "2025-01-01T00:02:10Z",
+                    "INCOMING",
+                    r#"{"jsonrpc":"2.0","id":2,"result":{}}"#,
+                ),
+            ],
+        )
+        .unwrap();
+
+        let buckets = timeline(&db, Some("echo"), 60, &TimeRange::default()).unwrap();
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(
+            (
+                buckets[0].outgoing,
+                buckets[0].incoming,
+                buckets[0].stderr,
+                buckets[0].errors
+            ),
+            (1, 1, 1, 1)
+        );
+        // The quiet minute in between is still there
+        assert_eq!(
+            buckets[1],
+            TimelineBucket {
+                start: buckets[1].start,
+                ..Default::default()
+            }
+        );
+        assert_eq!(buckets[2].start.to_rfc3339(), "2025-01-01T00:02:00+00:00");
+        assert_eq!(buckets[2].incoming, 1);
+
+        let range = TimeRange {
+            from: Some("2025-01-01T00:00:10Z".parse().unwrap()),
+            to: Some("2025-01-01T00:00:59Z".parse().unwrap()),
+        };
+        let buckets = timeline(&db, None, 30, &range).unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].incoming + buckets[1].stderr, 2);
+        assert!(timeline(&db, Some("other"), 60, &TimeRange::default())
+            .unwrap()
+            .is_empty());
+    }
+}
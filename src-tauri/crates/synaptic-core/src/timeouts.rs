@@ -0,0 +1,244 @@
+//! Detection of requests left unanswered
+//!
+//! Every request a client sends to a server is watched until its response
+//! (or a cancellation) comes by. A background check flags requests still
+//! waiting after `requestTimeoutSecs`: each is reported once with a
+//! `request-timeout` event and its logged row in `system_logs` is marked
+//! (level `WARN`, category `TIMEOUT`), so a hung tool call shows up instead
+//! of the client just looking stuck. Flagged requests aren't cancelled; a
+//! late response still goes through and ends the watch.
+
+use crate::host::Host;
+use crate::system_logs::LogRecord;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How often outstanding requests are checked
+pub const TIMEOUT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Requests watched at once, across servers
+const MAX_WATCHED_REQUESTS: usize = 10_000;
+
+/// Payload of the `request-timeout` event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestTimeout {
+    pub server_name: String,
+    pub request_id: Value,
+    pub method: String,
+    pub sent_at: DateTime<Utc>,
+    /// How long the request had been waiting when it was checked
+    pub waited_ms: u64,
+}
+
+struct Outstanding {
+    request_id: Value,
+    method: String,
+    sent: Instant,
+    sent_at: DateTime<Utc>,
+    /// Already reported as timed out
+    flagged: bool,
+}
+
+impl Outstanding {
+    fn report(&self, server_name: &str, now: Instant) -> RequestTimeout {
+        RequestTimeout {
+            server_name: server_name.to_string(),
+            request_id: self.request_id.clone(),
+            method: self.method.clone(),
+            sent_at: self.sent_at,
+            waited_ms: now.saturating_duration_since(self.sent).as_millis() as u64,
+        }
+    }
+}
+
+/// Requests waiting for a response, by (server, JSON text of the id)
+#[derive(Default)]
+pub struct RequestWatch {
+    outstanding: Mutex<HashMap<(String, String), Outstanding>>,
+}
+
+impl RequestWatch {
+    /// Look at an outgoing message; requests start a watch, cancellations end one
+    pub fn observe_request(&self, server_name: &str, message: &Value) {
+        self.observe_request_at(server_name, message, Instant::now());
+    }
+
+    fn observe_request_at(&self, server_name: &str, message: &Value, now: Instant) {
+        let Some(method) = message.get("method").and_then(|m| m.as_str()) else {
+            return;
+        };
+        let mut outstanding = self.outstanding.lock().unwrap();
+        if method == "notifications/cancelled" {
+            if let Some(id) = message.pointer("/params/requestId") {
+                outstanding.remove(&(server_name.to_string(), id.to_string()));
+            }
+            return;
+        }
+        let Some(id) = message.get("id").filter(|id| !id.is_null()) else {
+            return;
+        };
+        if outstanding.len() >= MAX_WATCHED_REQUESTS {
+            return;
+        }
+        outstanding.insert(
+            (server_name.to_string(), id.to_string()),
+            Outstanding {
+                request_id: id.clone(),
+                method: method.to_string(),
+                sent: now,
+                sent_at: Utc::now(),
+                flagged: false,
+            },
+        );
+    }
+
+    /// Look at an incoming message; a response ends the watch of its request
+    pub fn observe_response(&self, server_name: &str, message: &Value) {
+        if message.get("method").is_some() {
+            return;
+        }
+        if let Some(id) = message.get("id") {
+            self.outstanding
+                .lock()
+                .unwrap()
+                .remove(&(server_name.to_string(), id.to_string()));
+        }
+    }
+
+    /// Requests that have waited `timeout` since the last check, each reported once
+    pub fn overdue(&self, timeout: Duration) -> Vec<RequestTimeout> {
+        self.overdue_at(timeout, Instant::now())
+    }
+
+    fn overdue_at(&self, timeout: Duration, now: Instant) -> Vec<RequestTimeout> {
+        let mut outstanding = self.outstanding.lock().unwrap();
+        let mut overdue: Vec<RequestTimeout> = outstanding
+            .iter_mut()
+            .filter(|(_, request)| {
+                !request.flagged && now.saturating_duration_since(request.sent) >= timeout
+            })
+            .map(|((server_name, _), request)| {
+                request.flagged = true;
+                request.report(server_name, now)
+            })
+            .collect();
+        overdue.sort_by_key(|timeout| timeout.sent_at);
+        overdue
+    }
+
+    /// Timed-out requests still unanswered, optionally of a single server, oldest first
+    pub fn timed_out(&self, server_name: Option<&str>) -> Vec<RequestTimeout> {
+        let now = Instant::now();
+        let mut timed_out: Vec<RequestTimeout> = self
+            .outstanding
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((server, _), request)| {
+                request.flagged && server_name.is_none_or(|name| name == server)
+            })
+            .map(|((server, _), request)| request.report(server, now))
+            .collect();
+        timed_out.sort_by_key(|timeout| timeout.sent_at);
+        timed_out
+    }
+
+    /// Stop watching the requests of a server that exited
+    pub fn forget_server(&self, server_name: &str) {
+        self.outstanding
+            .lock()
+            .unwrap()
+            .retain(|(server, _), _| server != server_name);
+    }
+}
+
+/// Background loop flagging requests unanswered for longer than the configured timeout
+pub async fn run_timeout_monitor<H: Host>(host: H) {
+    let mut ticker = tokio::time::interval(TIMEOUT_CHECK_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        let Some(pm) = host.process_manager() else {
+            continue;
+        };
+        let Some(timeout) = host
+            .app_state()
+            .and_then(|state| state.get_settings().ok())
+            .and_then(|settings| settings.request_timeout())
+        else {
+            continue;
+        };
+
+        for overdue in pm.timeouts.overdue(timeout) {
+            host.emit("request-timeout", &overdue);
+            if let Some(sink) = pm.log_sink.get() {
+                let _ = sink.send(LogRecord::RequestTimeout(overdue));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use crate::process_manager::McpTrafficEvent;
+    use crate::system_logs::{self, LogQuery};
+    use serde_json::json;
+
+    #[test]
+    fn test_unanswered_requests_time_out_once() {
+        let watch = RequestWatch::default();
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let request = |id: u64| json!({"jsonrpc":"2.0","id":id,"method":"tools/call"});
+        let timeout = Duration::from_secs(30);
+
+        watch.observe_request_at("fs", &request(1), at(0));
+        watch.observe_request_at("fs", &request(2), at(0));
+        watch.observe_request_at("fs", &request(3), at(0));
+        watch.observe_response("fs", &json!({"jsonrpc":"2.0","id":1,"result":{}}));
+        watch.observe_request_at(
+            "fs",
+            &json!({"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":2}}),
+            at(1),
+        );
+
+        assert!(watch.overdue_at(timeout, at(29)).is_empty());
+        let overdue = watch.overdue_at(timeout, at(31));
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].request_id, json!(3));
+        assert_eq!(overdue[0].waited_ms, 31_000);
+        // Reported once, but listed until answered
+        assert!(watch.overdue_at(timeout, at(60)).is_empty());
+        assert_eq!(watch.timed_out(Some("fs")).len(), 1);
+        assert!(watch.timed_out(Some("git")).is_empty());
+
+        // The logged request is marked
+        let db = Database::open_in_memory().unwrap();
+        let event = McpTrafficEvent {
+            server_id: "fs".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            direction: "OUTGOING".to_string(),
+            content: request(3).to_string(),
+            message_id: uuid::Uuid::new_v4().to_string(),
+            level: None,
+        };
+        system_logs::insert_traffic(&db, "s1", &[event]).unwrap();
+        system_logs::write(&db, &LogRecord::RequestTimeout(overdue[0].clone())).unwrap();
+        let logged = system_logs::query(&db, &LogQuery::default()).unwrap();
+        assert_eq!(
+            (logged[0].level.as_str(), logged[0].category.as_str()),
+            ("WARN", "TIMEOUT")
+        );
+
+        watch.observe_response("fs", &json!({"jsonrpc":"2.0","id":3,"result":{}}));
+        assert!(watch.timed_out(None).is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+//! Tool-call analytics
+//!
+//! Each `tools/call` request is paired with its response as traffic flows
+//! through the stdin writer and stdout reader. Completed calls go to the
+//! logging service, which stores one row per call in `tool_stats`;
+//! [`query`] aggregates them into counts, latency percentiles and error
+//! rates per tool.
+
+use crate::database::Database;
+use crate::error::SynapticResult;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// One completed `tools/call`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCallRecord {
+    pub server_name: String,
+    pub tool_name: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// JSON-RPC error, or a result with `isError: true`
+    pub is_error: bool,
+}
+
+/// Optional bounds on when calls started
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimeRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Aggregated calls of one tool
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolStats {
+    pub server_name: String,
+    pub tool_name: String,
+    pub calls: u64,
+    pub errors: u64,
+    /// `errors / calls`, 0-1
+    pub error_rate: f64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+    pub last_called_at: DateTime<Utc>,
+}
+
+/// A call waiting for its response
+struct OpenCall {
+    tool_name: String,
+    started_at: DateTime<Utc>,
+    sent: Instant,
+}
+
+/// Pairs `tools/call` requests with their responses, per server
+#[derive(Default)]
+pub struct ToolCallTracker {
+    /// Open calls by (server, JSON-RPC id)
+    open: Mutex<HashMap<(String, String), OpenCall>>,
+}
+
+impl ToolCallTracker {
+    /// Look at an outgoing message; remembers `tools/call` requests
+    pub fn observe_request(&self, server_name: &str, message: &Value) {
+        if message.get("method").and_then(|m| m.as_str()) != Some("tools/call") {
+            return;
+        }
+        let (Some(id), Some(tool_name)) = (
+            message.get("id"),
+            message
+                .get("params")
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str()),
+        ) else {
+            return;
+        };
+        self.open.lock().unwrap().insert(
+            (server_name.to_string(), id.to_string()),
+            OpenCall {
+                tool_name: tool_name.to_string(),
+                started_at: Utc::now(),
+                sent: Instant::now(),
+            },
+        );
+    }
+
+    /// Look at an incoming message; returns the completed call when it answers one
+    pub fn observe_response(&self, server_name: &str, message: &Value) -> Option<ToolCallRecord> {
+        if message.get("method").is_some() {
+            return None;
+        }
+        let id = message.get("id")?.to_string();
+        let call = self
+            .open
+            .lock()
+            .unwrap()
+            .remove(&(server_name.to_string(), id))?;
+
+        let is_error = message.get("error").is_some()
+            || message
+                .get("result")
+                .and_then(|r| r.get("isError"))
+                .and_then(|e| e.as_bool())
+                .unwrap_or(false);
+        Some(ToolCallRecord {
+            server_name: server_name.to_string(),
+            tool_name: call.tool_name,
+            started_at: call.started_at,
+            duration_ms: call.sent.elapsed().as_millis() as u64,
+            is_error,
+        })
+    }
+
+    /// Forget unanswered calls of a server that exited
+    pub fn forget_server(&self, server_name: &str) {
+        self.open
+            .lock()
+            .unwrap()
+            .retain(|(server, _), _| server != server_name);
+    }
+}
+
+// ============================================
+// PERSISTENCE
+// ============================================
+
+pub fn record(db: &Database, call: &ToolCallRecord) -> SynapticResult<()> {
+    db.conn().execute(
+        "INSERT INTO tool_stats (server_name, tool_name, started_at, duration_ms, is_error)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            call.server_name,
+            call.tool_name,
+            call.started_at.timestamp_millis(),
+            call.duration_ms as i64,
+            call.is_error,
+        ],
+    )?;
+    Ok(())
+}
+
+/// `(started_at, duration_ms, is_error)` of a stored call
+type CallRow = (i64, i64, bool);
+
+/// Stats per tool, most called first, optionally for a single server
+pub fn query(
+    db: &Database,
+    server_name: Option<&str>,
+    range: &TimeRange,
+) -> SynapticResult<Vec<ToolStats>> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT server_name, tool_name, started_at, duration_ms, is_error
+         FROM tool_stats
+         WHERE (?1 IS NULL OR server_name = ?1)
+           AND (?2 IS NULL OR started_at >= ?2)
+           AND (?3 IS NULL OR started_at <= ?3)
+         ORDER BY duration_ms",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            server_name,
+            range.from.map(|t| t.timestamp_millis()),
+            range.to.map(|t| t.timestamp_millis()),
+        ],
+        |row| {
+            Ok((
+                (row.get::<_, String>(0)?, row.get::<_, String>(1)?),
+                (
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, bool>(4)?,
+                ),
+            ))
+        },
+    )?;
+
+    // Durations arrive sorted, so each group's are too
+    let mut groups: BTreeMap<(String, String), Vec<CallRow>> = BTreeMap::new();
+    for row in rows {
+        let (key, call) = row?;
+        groups.entry(key).or_default().push(call);
+    }
+
+    let mut stats: Vec<ToolStats> = groups
+        .into_iter()
+        .map(|((server_name, tool_name), calls)| {
+            let durations: Vec<u64> = calls.iter().map(|c| c.1.max(0) as u64).collect();
+            let errors = calls.iter().filter(|c| c.2).count() as u64;
+            let last = calls.iter().map(|c| c.0).max().unwrap_or_default();
+            ToolStats {
+                server_name,
+                tool_name,
+                calls: calls.len() as u64,
+                errors,
+                error_rate: errors as f64 / calls.len() as f64,
+                p50_ms: percentile(&durations, 50),
+                p95_ms: percentile(&durations, 95),
+                max_ms: durations.last().copied().unwrap_or(0),
+                last_called_at: DateTime::from_timestamp_millis(last).unwrap_or_default(),
+            }
+        })
+        .collect();
+    stats.sort_by_key(|s| std::cmp::Reverse(s.calls));
+    Ok(stats)
+}
+
+/// Nearest-rank percentile of sorted values
+pub(crate) fn percentile(sorted: &[u64], p: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call(tool_name: &str, duration_ms: u64, is_error: bool) -> ToolCallRecord {
+        ToolCallRecord {
+            server_name: "fs".to_string(),
+            tool_name: tool_name.to_string(),
+            started_at: Utc::now(),
+            duration_ms,
+            is_error,
+        }
+    }
+
+    #[test]
+    fn test_calls_are_paired_and_aggregated() {
+        let tracker = ToolCallTracker::default();
+        tracker.observe_request(
+            "fs",
+            &json!({"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"read_file"}}),
+        );
+        tracker.observe_request("fs", &json!({"jsonrpc":"2.0","id":4,"method":"tools/list"}));
+        assert!(tracker
+            .observe_response("fs", &json!({"jsonrpc":"2.0","id":4,"result":{}}))
+            .is_none());
+        let done = tracker
+            .observe_response(
+                "fs",
+                &json!({"jsonrpc":"2.0","id":3,"result":{"content":[],"isError":true}}),
+            )
+            .unwrap();
+        assert_eq!(done.tool_name, "read_file");
+        assert!(done.is_error);
+
+        let db = Database::open_in_memory().unwrap();
+        for ms in 1..=20 {
+            record(&db, &call("read_file", ms * 10, ms == 20)).unwrap();
+        }
+        record(&db, &call("write_file", 5, false)).unwrap();
+
+        let stats = query(&db, Some("fs"), &TimeRange::default()).unwrap();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].tool_name, "read_file");
+        assert_eq!(stats[0].calls, 20);
+        assert_eq!(stats[0].errors, 1);
+        assert_eq!(stats[0].error_rate, 0.05);
+        assert_eq!(stats[0].p50_ms, 100);
+        assert_eq!(stats[0].p95_ms, 190);
+        assert_eq!(stats[0].max_ms, 200);
+
+        let future = TimeRange {
+            from: Some(Utc::now() + chrono::Duration::hours(1)),
+            to: None,
+        };
+        assert!(query(&db, None, &future).unwrap().is_empty());
+        assert!(query(&db, Some("git"), &TimeRange::default())
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+//! Batched traffic events
+//!
+//! Emitting one event per stdio line floods the webview under replay or
+//! load-test traffic. A server's stdin writer and stdout/stderr readers hand
+//! their events to one batching task instead, which emits them as a single
+//! `mcp-traffic-batch` event every [`TRAFFIC_BATCH_INTERVAL`] or once
+//! [`TRAFFIC_BATCH_MAX`] have piled up. Events of a server are emitted in the
+//! order they were produced.
+//!
+//! Each batcher is one session of its server: when the traffic logging
+//! service runs, batches are also handed to it for `system_logs`, minus the
+//! events the server's history retention doesn't mirror. A server with a
+//! traffic mirror has each batch appended to its file as well.
+
+use crate::host::Host;
+use crate::process_manager::McpTrafficEvent;
+use crate::system_logs::LogRecord;
+use crate::traffic_mirror;
+use chrono::Utc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Longest an event waits before its batch is emitted
+pub const TRAFFIC_BATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Events emitted together at most
+pub const TRAFFIC_BATCH_MAX: usize = 200;
+
+/// Hands one server's traffic events to its batching task
+#[derive(Clone)]
+pub struct TrafficSender(mpsc::UnboundedSender<McpTrafficEvent>);
+
+impl TrafficSender {
+    pub fn send(&self, event: McpTrafficEvent) {
+        // The batcher only stops once every sender is gone
+        let _ = self.0.send(event);
+    }
+}
+
+/// Start a batching task; it flushes and exits when the last sender drops
+pub fn spawn_batcher<H: Host>(host: H, server_name: &str) -> (TrafficSender, JoinHandle<()>) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let sink = host
+        .process_manager()
+        .and_then(|pm| pm.log_sink.get().cloned());
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let server_name = server_name.to_string();
+    if let Some(sink) = &sink {
+        let _ = sink.send(LogRecord::SessionStarted {
+            session_id: session_id.clone(),
+            server_name: server_name.clone(),
+            started_at: Utc::now(),
+        });
+    }
+
+    let task = tokio::spawn(async move {
+        while let Some(first) = rx.recv().await {
+            let mut batch = vec![first];
+            let deadline = tokio::time::Instant::now() + TRAFFIC_BATCH_INTERVAL;
+
+            while batch.len() < TRAFFIC_BATCH_MAX {
+                match tokio::time::timeout_at(deadline, rx.recv()).await {
+                    Ok(Some(event)) => batch.push(event),
+                    // Closed or due: emit what we have
+                    Ok(None) | Err(_) => break,
+                }
+            }
+            if let Some(path) = host
+                .app_state()
+                .and_then(|state| state.traffic_mirror_path(&server_name))
+            {
+                if let Err(e) = traffic_mirror::append(&path, &batch) {
+                    eprintln!("Failed to mirror traffic of {}: {}", server_name, e);
+                }
+            }
+            host.emit("mcp-traffic-batch", &batch);
+            if let Some(sink) = &sink {
+                if let Some(state) = host.app_state() {
+                    let mirror = state.history_retention(&server_name).mirror;
+                    batch.retain(|event| mirror.keeps_event(event));
+                }
+                if !batch.is_empty() {
+                    let _ = sink.send(LogRecord::Traffic {
+                        session_id: session_id.clone(),
+                        events: batch,
+                    });
+                }
+            }
+        }
+
+        if let Some(sink) = &sink {
+            let _ = sink.send(LogRecord::SessionEnded {
+                session_id,
+                ended_at: Utc::now(),
+            });
+        }
+    });
+
+    (TrafficSender(tx), task)
+}
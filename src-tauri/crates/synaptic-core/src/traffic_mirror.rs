@@ -0,0 +1,152 @@
+//! Live NDJSON mirrors of a server's traffic
+//!
+//! A mirror appends every traffic event of one server to a file as it is
+//! batched, one JSON object per line, so tools like `jq` or `lnav` can follow
+//! it with `tail -f` while Synaptic runs. JSON-RPC messages are written as
+//! objects rather than escaped strings. The file is opened in append mode for
+//! every batch, so it may be truncated, moved or deleted at any time; it is
+//! never rotated or trimmed by Synaptic.
+
+use crate::error::{SynapticError, SynapticResult};
+use crate::log_level::LogLevel;
+use crate::process_manager::McpTrafficEvent;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Where a server's traffic is mirrored
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficMirror {
+    pub server_name: String,
+    /// Absolute path of the NDJSON file
+    pub path: PathBuf,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// One line of a mirror file
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MirrorLine<'a> {
+    timestamp: &'a str,
+    server: &'a str,
+    direction: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    level: Option<LogLevel>,
+    /// The JSON-RPC message, or the raw line when it isn't JSON
+    message: Value,
+}
+
+/// Check that `path` can hold a mirror: absolute, and not a directory
+pub fn validate_path(path: &Path) -> SynapticResult<()> {
+    if !path.is_absolute() {
+        return Err(SynapticError::IoError(format!(
+            "Traffic mirror path must be absolute: {}",
+            path.display()
+        )));
+    }
+    if path.is_dir() {
+        return Err(SynapticError::IoError(format!(
+            "Traffic mirror path is a directory: {}",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Enabled mirror file of `server_name` among `mirrors`
+pub fn path_for<'a>(mirrors: &'a [TrafficMirror], server_name: &str) -> Option<&'a Path> {
+    mirrors
+        .iter()
+        .find(|mirror| mirror.enabled && mirror.server_name == server_name)
+        .map(|mirror| mirror.path.as_path())
+}
+
+/// Append a batch of events to a mirror file, one line each, in a single write
+pub fn append(path: &Path, events: &[McpTrafficEvent]) -> io::Result<()> {
+    let mut lines = Vec::new();
+    for event in events {
+        let line = MirrorLine {
+            timestamp: &event.timestamp,
+            server: &event.server_id,
+            direction: &event.direction,
+            level: event.level,
+            message: serde_json::from_str(&event.content)
+                .unwrap_or_else(|_| Value::String(event.content.clone())),
+        };
+        serde_json::to_writer(&mut lines, &line)?;
+        lines.push(b'\n');
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(direction: &str, content: &str) -> McpTrafficEvent {
+        McpTrafficEvent {
+            server_id: "fs".to_string(),
+            timestamp: "2025-01-01T00:00:00Z".to_string(),
+            direction: direction.to_string(),
+            content: content.to_string(),
+            message_id: "m1".to_string(),
+            level: None,
+        }
+    }
+
+    #[test]
+    fn test_batches_append_one_json_line_per_event() {
+        let path = std::env::temp_dir()
+            .join(format!("synaptic-mirror-{}", uuid::Uuid::new_v4()))
+            .join("fs.ndjson");
+
+        append(
+            &path,
+            &[event(
+                "OUTGOING",
+                r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+            )],
+        )
+        .unwrap();
+        append(
+            &path,
+            &[McpTrafficEvent {
+                level: Some(LogLevel::Error),
+                ..event("STDERR", "[ERROR] disk full")
+            }],
+        )
+        .unwrap();
+
+        let lines: Vec<Value> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["message"]["method"], "tools/list");
+        assert!(lines[0].get("level").is_none());
+        assert_eq!(lines[1]["message"], "[ERROR] disk full");
+        assert_eq!(lines[1]["level"], "ERROR");
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_only_absolute_file_paths_are_accepted() {
+        assert!(validate_path(Path::new("traffic.ndjson")).is_err());
+        assert!(validate_path(&std::env::temp_dir()).is_err());
+        assert!(validate_path(&std::env::temp_dir().join("traffic.ndjson")).is_ok());
+    }
+}
@@ -0,0 +1,788 @@
+//! Remote MCP servers over Streamable HTTP and legacy SSE
+//!
+//! A server with a `url` is connected to instead of spawned. The connection
+//! is handed to the process manager as three in-memory pipes standing in for
+//! stdin, stdout and stderr, one JSON-RPC message per line, so remote traffic
+//! runs through the same inspector, interceptor, mocks and bookkeeping as
+//! traffic of local processes:
+//!
+//! - `http` (Streamable HTTP): every message is POSTed to the endpoint;
+//!   replies come back as a JSON body or an SSE stream. The `Mcp-Session-Id`
+//!   the server hands out is sent along from then on, and a GET stream picks
+//!   up messages the server sends on its own.
+//! - `sse` (the 2024-11-05 transport): a GET stream carries every server
+//!   message; its first `endpoint` event names the URL messages are POSTed to.
+//!
+//! Servers signed in to with [`crate::auth`] get their access token as a
+//! bearer `Authorization` header, renewed once when the server rejects it.
+//! Transport failures are written to the stderr pipe. A lost stream or an
+//! expired session ends the connection like an exiting process, so the
+//! restart policy applies.
+
+use crate::auth::TokenSource;
+use crate::config::McpServer;
+use crate::error::{SynapticError, SynapticResult};
+use futures::StreamExt;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use reqwest::{Client, RequestBuilder, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream};
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+
+/// Header carrying the Streamable HTTP session
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+/// JSON-RPC error code answering a request the remote server couldn't be reached for
+pub const TRANSPORT_ERROR_CODE: i64 = -32000;
+
+/// How long connecting to a remote server may take
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Buffer of each in-memory pipe
+const PIPE_BUFFER_BYTES: usize = 64 * 1024;
+
+/// How a server is reached
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transport {
+    /// Spawned process speaking over stdin/stdout
+    #[default]
+    Stdio,
+    /// Streamable HTTP at `url`
+    Http,
+    /// Legacy HTTP+SSE at `url`
+    Sse,
+}
+
+impl Transport {
+    pub fn is_stdio(&self) -> bool {
+        *self == Transport::Stdio
+    }
+}
+
+/// Ids of remote connections, counted down from `u32::MAX` so they never meet a real PID
+static NEXT_CONNECTION_ID: AtomicU32 = AtomicU32::new(u32::MAX);
+
+/// Identifier a remote connection is tracked under in place of a PID
+pub fn next_connection_id() -> u32 {
+    NEXT_CONNECTION_ID.fetch_sub(1, Ordering::SeqCst)
+}
+
+/// The process-side ends of a connection's pipes
+pub struct RemotePipes {
+    /// Messages to the server, one per line
+    pub stdin: DuplexStream,
+    /// Messages from the server, one per line
+    pub stdout: DuplexStream,
+    /// Transport errors
+    pub stderr: DuplexStream,
+}
+
+/// A live connection to a remote server
+pub struct RemoteConnection {
+    task: JoinHandle<Result<(), String>>,
+    close: Option<oneshot::Sender<()>>,
+    /// How it ended, once it did
+    outcome: Option<Result<(), String>>,
+}
+
+impl RemoteConnection {
+    /// Wait for the connection to end: `Ok` once closed, `Err` with the reason when lost
+    pub async fn wait(&mut self) -> Result<(), String> {
+        if let Some(outcome) = &self.outcome {
+            return outcome.clone();
+        }
+        let outcome = match (&mut self.task).await {
+            Ok(outcome) => outcome,
+            Err(e) => Err(e.to_string()),
+        };
+        self.outcome = Some(outcome.clone());
+        outcome
+    }
+
+    /// Whether the connection already ended
+    pub fn is_finished(&self) -> bool {
+        self.outcome.is_some() || self.task.is_finished()
+    }
+
+    /// Ask the connection to end its session and stop
+    pub fn close(&mut self) {
+        if let Some(close) = self.close.take() {
+            let _ = close.send(());
+        }
+    }
+
+    /// Stop right away, without ending the session
+    pub fn abort(&self) {
+        self.task.abort();
+    }
+}
+
+/// Connect to a server's `url` with its transport
+pub fn connect(
+    server_name: &str,
+    server: &McpServer,
+) -> SynapticResult<(RemoteConnection, RemotePipes)> {
+    let url = remote_url(server)?;
+    let client = Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .default_headers(header_map(&server.headers)?)
+        .build()
+        .map_err(|e| SynapticError::ProcessError(format!("HTTP client: {}", e)))?;
+
+    let (stdin, stdin_remote) = tokio::io::duplex(PIPE_BUFFER_BYTES);
+    let (stdout, stdout_remote) = tokio::io::duplex(PIPE_BUFFER_BYTES);
+    let (stderr, stderr_remote) = tokio::io::duplex(PIPE_BUFFER_BYTES);
+    let (close_tx, close_rx) = oneshot::channel();
+
+    let link = Link {
+        client,
+        server_name: server_name.to_string(),
+        incoming: spawn_line_writer(stdout_remote),
+        diagnostics: spawn_line_writer(stderr_remote),
+        auth: TokenSource::for_server(server),
+    };
+    let task = match server.transport {
+        Transport::Sse => tokio::spawn(run_sse(link, url, stdin_remote, close_rx)),
+        Transport::Http | Transport::Stdio => {
+            tokio::spawn(run_streamable_http(link, url, stdin_remote, close_rx))
+        }
+    };
+
+    Ok((
+        RemoteConnection {
+            task,
+            close: Some(close_tx),
+            outcome: None,
+        },
+        RemotePipes {
+            stdin,
+            stdout,
+            stderr,
+        },
+    ))
+}
+
+/// The server's `url`, checked to be http(s)
+pub fn remote_url(server: &McpServer) -> SynapticResult<Url> {
+    let Some(url) = &server.url else {
+        return Err(SynapticError::ConfigParseError(
+            "Remote server has no url".to_string(),
+        ));
+    };
+    let url = Url::parse(url)
+        .map_err(|e| SynapticError::ConfigParseError(format!("Invalid url {}: {}", url, e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(SynapticError::ConfigParseError(format!(
+            "Unsupported url scheme: {}",
+            url.scheme()
+        )));
+    }
+    Ok(url)
+}
+
+fn header_map(headers: &HashMap<String, String>) -> SynapticResult<HeaderMap> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let invalid =
+            |e: String| SynapticError::ConfigParseError(format!("Header {}: {}", name, e));
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid(e.to_string()))?;
+        let value = HeaderValue::from_str(value).map_err(|e| invalid(e.to_string()))?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+/// Task writing each line it is sent to a pipe
+fn spawn_line_writer(mut pipe: DuplexStream) -> mpsc::UnboundedSender<String> {
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    tokio::spawn(async move {
+        while let Some(line) = rx.recv().await {
+            let mut bytes = line.into_bytes();
+            bytes.push(b'\n');
+            if pipe.write_all(&bytes).await.is_err() {
+                break;
+            }
+        }
+    });
+    tx
+}
+
+/// What every transport task shares
+#[derive(Clone)]
+struct Link {
+    client: Client,
+    server_name: String,
+    /// Lines for the stdout pipe
+    incoming: mpsc::UnboundedSender<String>,
+    /// Lines for the stderr pipe
+    diagnostics: mpsc::UnboundedSender<String>,
+    /// Tokens of a server that was signed in to
+    auth: Option<Arc<TokenSource>>,
+}
+
+impl Link {
+    /// Add the access token, returning it so a rejection can be told apart from a renewal
+    async fn authorize(&self, request: RequestBuilder) -> (RequestBuilder, Option<String>) {
+        match &self.auth {
+            Some(auth) => {
+                let token = auth.access_token().await;
+                (request.bearer_auth(&token), Some(token))
+            }
+            None => (request, None),
+        }
+    }
+
+    /// Whether a request rejected with `token` is worth sending again
+    async fn renewed(&self, token: Option<String>) -> bool {
+        match (&self.auth, token) {
+            (Some(auth), Some(token)) => auth.renew(&token).await.is_some_and(|new| new != token),
+            _ => false,
+        }
+    }
+
+    fn report(&self, message: impl Into<String>) {
+        let _ = self.diagnostics.send(message.into());
+    }
+
+    /// Hand the messages of a JSON body (one message or a batch) to stdout
+    fn deliver_json(&self, body: &str) {
+        match serde_json::from_str::<Value>(body) {
+            Ok(Value::Array(messages)) => {
+                for message in messages {
+                    let _ = self.incoming.send(message.to_string());
+                }
+            }
+            Ok(message) => {
+                let _ = self.incoming.send(message.to_string());
+            }
+            Err(_) if body.trim().is_empty() => {}
+            Err(e) => self.report(format!("Unparseable response body: {}", e)),
+        }
+    }
+
+    /// Answer a request that couldn't be delivered, so nobody waits for it forever
+    fn fail_request(&self, message: &str, reason: &str) {
+        let Ok(message) = serde_json::from_str::<Value>(message) else {
+            return;
+        };
+        let (Some(_), Some(id)) = (message.get("method"), message.get("id")) else {
+            return;
+        };
+        let error = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": TRANSPORT_ERROR_CODE, "message": reason },
+        });
+        let _ = self.incoming.send(error.to_string());
+    }
+
+    /// Deliver the `message` events of an SSE response until it ends
+    async fn follow_events(
+        &self,
+        response: reqwest::Response,
+        mut on_event: impl FnMut(&SseEvent),
+    ) -> Result<(), String> {
+        let mut parser = SseParser::default();
+        let mut body = response.bytes_stream();
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(|e| e.to_string())?;
+            for event in parser.push(&chunk) {
+                on_event(&event);
+                if event.is_message() {
+                    self.deliver_json(&event.data);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_event_stream(response: &reqwest::Response) -> bool {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"))
+}
+
+// ============================================
+// STREAMABLE HTTP
+// ============================================
+
+async fn run_streamable_http(
+    link: Link,
+    url: Url,
+    stdin: DuplexStream,
+    mut close: oneshot::Receiver<()>,
+) -> Result<(), String> {
+    let session: Arc<Mutex<Option<String>>> = Arc::default();
+    // Set by a POST that finds its session gone
+    let (lost_tx, mut lost_rx) = watch::channel::<Option<String>>(None);
+    let (outgoing, outgoing_rx) = mpsc::unbounded_channel::<String>();
+    let poster = tokio::spawn(post_in_order(
+        link.clone(),
+        url.clone(),
+        session.clone(),
+        lost_tx,
+        outgoing_rx,
+    ));
+    let mut listener: Option<JoinHandle<()>> = None;
+    let mut lines = BufReader::new(stdin).lines();
+
+    let outcome = loop {
+        let line = tokio::select! {
+            _ = &mut close => break Ok(()),
+            _ = lost_rx.changed() => {
+                break Err(lost_rx.borrow().clone().unwrap_or_default());
+            }
+            line = lines.next_line() => line,
+        };
+        let message = match line {
+            Ok(Some(message)) if !message.trim().is_empty() => message,
+            Ok(Some(_)) => continue,
+            // Stdin closed: the process manager is done with us
+            Ok(None) | Err(_) => break Ok(()),
+        };
+
+        // Once there is a session, also listen for messages the server sends on its own
+        if listener.is_none() && session.lock().unwrap().is_some() {
+            listener = Some(tokio::spawn(listen(
+                link.clone(),
+                url.clone(),
+                session.clone(),
+            )));
+        }
+        let _ = outgoing.send(message);
+    };
+
+    poster.abort();
+    if let Some(listener) = listener {
+        listener.abort();
+    }
+
+    // Tell the server the session is over
+    let id = session.lock().unwrap().clone();
+    if let Some(id) = id {
+        let _ = link
+            .client
+            .delete(url)
+            .header(SESSION_HEADER, id)
+            .timeout(CONNECT_TIMEOUT)
+            .send()
+            .await;
+    }
+    outcome
+}
+
+const SESSION_EXPIRED: &str = "Session expired";
+const AUTHORIZATION_REQUIRED: &str = "Authorization required";
+
+/// POST messages one after the other, so the server sees them in the order they were written
+///
+/// Only sending waits its turn: a reply streamed back as SSE is delivered on its own task,
+/// except the `initialize` reply, which opens the session everything after it belongs to.
+async fn post_in_order(
+    link: Link,
+    url: Url,
+    session: Arc<Mutex<Option<String>>>,
+    lost: watch::Sender<Option<String>>,
+    mut outgoing: mpsc::UnboundedReceiver<String>,
+) {
+    while let Some(message) = outgoing.recv().await {
+        let response = match post_message(&link, &url, &session, &message).await {
+            Ok(Some(response)) => response,
+            Ok(None) => continue,
+            Err(reason) => {
+                give_up(&link, &lost, &message, reason);
+                continue;
+            }
+        };
+        let handshake = is_initialize(&message);
+        let link = link.clone();
+        let lost = lost.clone();
+        let delivery = tokio::spawn(async move {
+            if let Err(reason) = deliver_reply(&link, response).await {
+                give_up(&link, &lost, &message, reason);
+            }
+        });
+        if handshake {
+            let _ = delivery.await;
+        }
+    }
+}
+
+/// Report a message that didn't go through, failing it if it was a request
+fn give_up(link: &Link, lost: &watch::Sender<Option<String>>, message: &str, reason: String) {
+    link.report(format!("{}: {}", link.server_name, reason));
+    link.fail_request(message, &reason);
+    if reason.starts_with(SESSION_EXPIRED) {
+        let _ = lost.send(Some(reason));
+    }
+}
+
+/// Whether a message is the `initialize` request opening the session
+fn is_initialize(message: &str) -> bool {
+    serde_json::from_str::<Value>(message)
+        .is_ok_and(|message| message.get("method").and_then(Value::as_str) == Some("initialize"))
+}
+
+/// POST one message, returning the response when it carries a reply
+async fn post_message(
+    link: &Link,
+    url: &Url,
+    session: &Mutex<Option<String>>,
+    message: &str,
+) -> Result<Option<reqwest::Response>, String> {
+    let session_id = session.lock().unwrap().clone();
+    let mut retried = false;
+    let response = loop {
+        let mut request = link
+            .client
+            .post(url.clone())
+            .header(ACCEPT, "application/json, text/event-stream")
+            .header(CONTENT_TYPE, "application/json")
+            .body(message.to_string());
+        if let Some(id) = &session_id {
+            request = request.header(SESSION_HEADER, id);
+        }
+        let (request, token) = link.authorize(request).await;
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            break response;
+        }
+        if retried || !link.renewed(token).await {
+            return Err(format!(
+                "{} (HTTP 401); sign in again",
+                AUTHORIZATION_REQUIRED
+            ));
+        }
+        retried = true;
+    };
+
+    if let Some(id) = response
+        .headers()
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        *session.lock().unwrap() = Some(id.to_string());
+    }
+
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND && session_id.is_some() {
+        return Err(format!("{} (HTTP 404)", SESSION_EXPIRED));
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("HTTP {}: {}", status.as_u16(), body.trim()));
+    }
+    if status == StatusCode::ACCEPTED {
+        return Ok(None);
+    }
+    Ok(Some(response))
+}
+
+/// Deliver the reply a POST got back, as a JSON body or an SSE stream
+async fn deliver_reply(link: &Link, response: reqwest::Response) -> Result<(), String> {
+    if is_event_stream(&response) {
+        link.follow_events(response, |_| {}).await
+    } else {
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        link.deliver_json(&body);
+        Ok(())
+    }
+}
+
+/// GET stream of server-initiated messages; servers without one answer 405
+async fn listen(link: Link, url: Url, session: Arc<Mutex<Option<String>>>) {
+    let Some(id) = session.lock().unwrap().clone() else {
+        return;
+    };
+    let request = link
+        .client
+        .get(url)
+        .header(ACCEPT, "text/event-stream")
+        .header(SESSION_HEADER, id);
+    let response = link.authorize(request).await.0.send().await;
+    match response {
+        Ok(response) if response.status().is_success() && is_event_stream(&response) => {
+            if let Err(e) = link.follow_events(response, |_| {}).await {
+                link.report(format!("{}: event stream ended: {}", link.server_name, e));
+            }
+        }
+        Ok(response) if response.status() == StatusCode::METHOD_NOT_ALLOWED => {}
+        Ok(response) => link.report(format!(
+            "{}: no event stream (HTTP {})",
+            link.server_name,
+            response.status().as_u16()
+        )),
+        Err(e) => link.report(format!("{}: no event stream: {}", link.server_name, e)),
+    }
+}
+
+// ============================================
+// LEGACY HTTP+SSE
+// ============================================
+
+async fn run_sse(
+    link: Link,
+    url: Url,
+    stdin: DuplexStream,
+    mut close: oneshot::Receiver<()>,
+) -> Result<(), String> {
+    let request = link
+        .client
+        .get(url.clone())
+        .header(ACCEPT, "text/event-stream");
+    let response = link
+        .authorize(request)
+        .await
+        .0
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        let reason = format!(
+            "HTTP {} opening the event stream",
+            response.status().as_u16()
+        );
+        link.report(format!("{}: {}", link.server_name, reason));
+        return Err(reason);
+    }
+
+    // The stream's `endpoint` event says where messages go
+    let (endpoint_tx, mut endpoint_rx) = watch::channel::<Option<Url>>(None);
+    let stream_link = link.clone();
+    let base = url.clone();
+    let mut stream = tokio::spawn(async move {
+        let outcome = stream_link
+            .follow_events(response, |event| {
+                if event.event.as_deref() == Some("endpoint") {
+                    match base.join(event.data.trim()) {
+                        Ok(endpoint) => {
+                            let _ = endpoint_tx.send(Some(endpoint));
+                        }
+                        Err(e) => stream_link.report(format!("Invalid endpoint: {}", e)),
+                    }
+                }
+            })
+            .await;
+        outcome.and(Err::<(), _>(
+            "Event stream closed by the server".to_string(),
+        ))
+    });
+
+    // Messages are POSTed one after the other, once the endpoint is known
+    let (outgoing, mut outgoing_rx) = mpsc::unbounded_channel::<String>();
+    let poster_link = link.clone();
+    let poster = tokio::spawn(async move {
+        let link = poster_link;
+        while let Some(message) = outgoing_rx.recv().await {
+            let endpoint = match endpoint_rx.wait_for(Option::is_some).await {
+                Ok(endpoint) => endpoint.clone().unwrap_or_else(|| url.clone()),
+                Err(_) => url.clone(),
+            };
+            let request = link
+                .client
+                .post(endpoint)
+                .header(CONTENT_TYPE, "application/json")
+                .body(message.clone());
+            let sent = link.authorize(request).await.0.send().await;
+            let reason = match sent {
+                Ok(response) if response.status().is_success() => continue,
+                Ok(response) => format!("HTTP {}", response.status().as_u16()),
+                Err(e) => e.to_string(),
+            };
+            link.report(format!("{}: {}", link.server_name, reason));
+            link.fail_request(&message, &reason);
+        }
+    });
+
+    let mut lines = BufReader::new(stdin).lines();
+    let outcome = loop {
+        let line = tokio::select! {
+            _ = &mut close => break Ok(()),
+            ended = &mut stream => {
+                let reason = ended.unwrap_or_else(|e| Err(e.to_string())).err().unwrap_or_default();
+                link.report(format!("{}: {}", link.server_name, reason));
+                break Err(reason);
+            }
+            line = lines.next_line() => line,
+        };
+        let message = match line {
+            Ok(Some(message)) if !message.trim().is_empty() => message,
+            Ok(Some(_)) => continue,
+            Ok(None) | Err(_) => break Ok(()),
+        };
+
+        let _ = outgoing.send(message);
+    };
+
+    poster.abort();
+    stream.abort();
+    outcome
+}
+
+// ============================================
+// SERVER-SENT EVENTS
+// ============================================
+
+/// One dispatched server-sent event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// `event:` field; none means `message`
+    pub event: Option<String>,
+    pub data: String,
+}
+
+impl SseEvent {
+    fn is_message(&self) -> bool {
+        matches!(self.event.as_deref(), None | Some("message"))
+    }
+}
+
+/// Incremental `text/event-stream` parser
+#[derive(Debug, Default)]
+pub struct SseParser {
+    /// Bytes of a line not terminated yet
+    partial: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Feed a chunk, returning the events it completed
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.partial.extend_from_slice(chunk);
+        let mut events = Vec::new();
+
+        while let Some(end) = self.partial.iter().position(|&b| b == b'\n') {
+            let raw: Vec<u8> = self.partial.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&raw);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(SseEvent {
+                        event: self.event.take(),
+                        data: self.data.join("\n"),
+                    });
+                }
+                self.event = None;
+                self.data.clear();
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_parser_handles_split_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser
+            .push(b": keep-alive\n\nevent: endpoint\ndata: /mess")
+            .is_empty());
+
+        let events =
+            parser.push(b"ages?session=1\r\n\r\ndata: {\"id\":1,\ndata: \"result\":{}}\n\n");
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: Some("endpoint".to_string()),
+                    data: "/messages?session=1".to_string(),
+                },
+                SseEvent {
+                    event: None,
+                    data: "{\"id\":1,\n\"result\":{}}".to_string(),
+                },
+            ]
+        );
+        assert!(!events[0].is_message());
+        assert!(events[1].is_message());
+    }
+
+    #[test]
+    fn test_remote_url_requires_http() {
+        let server = |url: &str| McpServer {
+            url: Some(url.to_string()),
+            transport: Transport::Http,
+            ..McpServer::default()
+        };
+        assert!(remote_url(&server("https://mcp.example.com/mcp")).is_ok());
+        assert!(remote_url(&server("ftp://mcp.example.com")).is_err());
+        assert!(remote_url(&server("not a url")).is_err());
+        assert!(remote_url(&McpServer::default()).is_err());
+    }
+
+    #[test]
+    fn test_only_the_initialize_request_is_the_handshake() {
+        assert!(is_initialize(
+            r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#
+        ));
+        // Mentioning it elsewhere doesn't make a message the handshake
+        assert!(!is_initialize(
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"initialize"}}"#
+        ));
+        assert!(!is_initialize(
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#
+        ));
+        assert!(!is_initialize("not json \"initialize\""));
+    }
+
+    #[tokio::test]
+    async fn test_json_batches_and_failed_requests_reach_stdout() {
+        let (incoming, mut lines) = mpsc::unbounded_channel();
+        let (diagnostics, _) = mpsc::unbounded_channel();
+        let link = Link {
+            client: Client::new(),
+            server_name: "remote".to_string(),
+            incoming,
+            diagnostics,
+            auth: None,
+        };
+
+        link.deliver_json(r#"[{"jsonrpc":"2.0","id":1,"result":{}},{"jsonrpc":"2.0","method":"notifications/progress"}]"#);
+        assert_eq!(
+            lines.recv().await.unwrap(),
+            r#"{"id":1,"jsonrpc":"2.0","result":{}}"#
+        );
+        assert!(lines
+            .recv()
+            .await
+            .unwrap()
+            .contains("notifications/progress"));
+
+        // Notifications have nobody waiting; requests get an error response
+        link.fail_request(
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            "down",
+        );
+        link.fail_request(r#"{"jsonrpc":"2.0","id":7,"method":"tools/list"}"#, "down");
+        let error: Value = serde_json::from_str(&lines.recv().await.unwrap()).unwrap();
+        assert_eq!(error["id"], 7);
+        assert_eq!(error["error"]["code"], TRANSPORT_ERROR_CODE);
+    }
+}
@@ -0,0 +1,372 @@
+//! Update checks for servers pinned to a package version
+//!
+//! A server launched as `npx -y pkg@1.4.2` or `uvx pkg@0.6.1` (also
+//! `uvx --from pkg==0.6.1 cmd`) is pinned, and gets offered the latest
+//! release from npm or PyPI once one is newer. Unpinned servers already run
+//! the latest release whenever their runner refreshes its cache, so they
+//! aren't checked. Upgrading to a version the index has published rewrites
+//! it in the server's args, records it as the pinned version in Synaptic's
+//! metadata, and restarts the server if asked to (otherwise a running one is
+//! flagged stale). The upgrade is saved even if the restart fails; that comes
+//! back as a warning.
+
+use crate::config::McpServer;
+use crate::error::{SynapticError, SynapticResult};
+use crate::host::Host;
+use crate::metadata::SynapticMetadata;
+use crate::packages::{self, compare_versions, Ecosystem, PackageVersions};
+use crate::process_manager;
+use crate::state::AppState;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// `uvx` options whose value is the next arg
+const UVX_VALUE_OPTIONS: &[&str] = &[
+    "--with",
+    "--with-editable",
+    "--with-requirements",
+    "--python",
+    "-p",
+    "--index",
+    "--index-url",
+    "--extra-index-url",
+];
+
+/// The package a server runs, as written in its args
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageSpec {
+    pub ecosystem: Ecosystem,
+    pub package: String,
+    /// Pinned version; `None` when the spec runs the latest release
+    pub version: Option<String>,
+    /// Position of the spec in the server's args
+    arg: usize,
+    /// Whether the version is written `pkg==1.0` rather than `pkg@1.0`
+    equals: bool,
+}
+
+/// A newer release of a pinned server's package
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerUpdate {
+    pub server_name: String,
+    pub package: String,
+    pub ecosystem: Ecosystem,
+    /// Version pinned before; `None` when the server ran the latest release
+    pub current: Option<String>,
+    pub latest: String,
+    /// What went wrong applying an upgrade that was saved anyway
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// Package spec of a server launched through `npx` or `uvx`
+pub fn package_spec(server: &McpServer) -> Option<PackageSpec> {
+    let runner = Path::new(&server.command).file_stem()?.to_str()?;
+    let (ecosystem, arg) = match runner {
+        "npx" => (Ecosystem::Npm, npx_package_arg(&server.args)?),
+        "uvx" => (Ecosystem::Pypi, uvx_package_arg(&server.args)?),
+        _ => return None,
+    };
+    let spec = &server.args[arg];
+    let (package, version, equals) = match ecosystem {
+        // The scope's `@` isn't a version separator
+        Ecosystem::Npm => match spec.rfind('@').filter(|&at| at > 0) {
+            Some(at) => (&spec[..at], Some(&spec[at + 1..]), false),
+            None => (spec.as_str(), None, false),
+        },
+        Ecosystem::Pypi => match spec.split_once("==") {
+            Some((package, version)) => (package, Some(version), true),
+            None => match spec.split_once('@') {
+                Some((package, version)) => (package, Some(version), false),
+                None => (spec.as_str(), None, false),
+            },
+        },
+    };
+    Some(PackageSpec {
+        ecosystem,
+        package: package.to_string(),
+        version: version
+            .filter(|version| !version.is_empty() && *version != "latest")
+            .map(String::from),
+        arg,
+        equals,
+    })
+}
+
+/// Index of the package in `npx` args: `-p`/`--package`'s value, else the first non-option arg
+fn npx_package_arg(args: &[String]) -> Option<usize> {
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-p" | "--package" => return (i + 1 < args.len()).then_some(i + 1),
+            arg if arg.starts_with('-') => i += 1,
+            _ => return Some(i),
+        }
+    }
+    None
+}
+
+/// Index of the package in `uvx` args: `--from`'s value, else the first non-option arg
+fn uvx_package_arg(args: &[String]) -> Option<usize> {
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--from" => return (i + 1 < args.len()).then_some(i + 1),
+            arg if UVX_VALUE_OPTIONS.contains(&arg) => i += 2,
+            arg if arg.starts_with('-') => i += 1,
+            _ => return Some(i),
+        }
+    }
+    None
+}
+
+/// The server with its package pinned to `version`
+pub fn with_version(server: &McpServer, spec: &PackageSpec, version: &str) -> McpServer {
+    let mut server = server.clone();
+    let separator = if spec.equals { "==" } else { "@" };
+    server.args[spec.arg] = format!("{}{}{}", spec.package, separator, version);
+    server
+}
+
+/// Name a package is looked up by (PyPI extras like `pkg[cli]` dropped)
+fn lookup_name(spec: &PackageSpec) -> &str {
+    spec.package.split('[').next().unwrap_or(&spec.package)
+}
+
+/// Pinned servers whose package has a newer release, by server name
+pub async fn check_server_updates(state: &AppState) -> SynapticResult<Vec<ServerUpdate>> {
+    let pinned: Vec<(String, PackageSpec)> = state
+        .get_config()?
+        .mcp_servers
+        .iter()
+        .filter_map(|(name, server)| Some((name.clone(), package_spec(server)?)))
+        .filter(|(_, spec)| spec.version.is_some())
+        .collect();
+
+    let mut lookups: Vec<(Ecosystem, &str)> = pinned
+        .iter()
+        .map(|(_, spec)| (spec.ecosystem, lookup_name(spec)))
+        .collect();
+    lookups.sort_by_key(|&(ecosystem, package)| (ecosystem as u8, package));
+    lookups.dedup();
+
+    let fetched = join_all(
+        lookups
+            .iter()
+            .map(|&(ecosystem, package)| packages::fetch_package(ecosystem, package)),
+    )
+    .await;
+    let mut latest: HashMap<(Ecosystem, &str), String> = HashMap::new();
+    for (lookup, result) in lookups.iter().zip(fetched) {
+        match result {
+            Ok(versions) => {
+                if let Some(version) = versions.latest {
+                    latest.insert(*lookup, version);
+                }
+            }
+            Err(e) => eprintln!("Update check failed for {}: {}", lookup.1, e),
+        }
+    }
+
+    let mut updates: Vec<ServerUpdate> = pinned
+        .iter()
+        .filter_map(|(name, spec)| {
+            let newest = latest.get(&(spec.ecosystem, lookup_name(spec)))?;
+            let current = spec.version.as_deref()?;
+            (compare_versions(newest, current) == Ordering::Greater).then(|| ServerUpdate {
+                server_name: name.clone(),
+                package: spec.package.clone(),
+                ecosystem: spec.ecosystem,
+                current: Some(current.to_string()),
+                latest: newest.clone(),
+                warning: None,
+            })
+        })
+        .collect();
+    updates.sort_by(|a, b| a.server_name.cmp(&b.server_name));
+    Ok(updates)
+}
+
+/// Pin a server to `version` (by default the latest release) and restart it when `restart` is set
+pub async fn upgrade_server<H: Host>(
+    host: H,
+    name: &str,
+    version: Option<String>,
+    restart: bool,
+) -> SynapticResult<ServerUpdate> {
+    let state = host
+        .app_state()
+        .ok_or_else(|| SynapticError::ServerNotFound(name.to_string()))?;
+    let server = state
+        .get_config()?
+        .mcp_servers
+        .get(name)
+        .cloned()
+        .ok_or_else(|| SynapticError::ServerNotFound(name.to_string()))?;
+    let spec = package_spec(&server).ok_or_else(|| {
+        SynapticError::RegistryError(format!("{} doesn't run an npm or PyPI package", name))
+    })?;
+
+    let published = packages::fetch_package(spec.ecosystem, lookup_name(&spec)).await?;
+    let version = release_to_pin(&published, version)?;
+    let upgraded = with_version(&server, &spec, &version);
+    state.update_server(name, upgraded.clone())?;
+
+    // The pin is only worth keeping together with its record
+    let recorded = SynapticMetadata::load().and_then(|mut metadata| {
+        metadata.set_pinned_version(name, Some(&version));
+        metadata.save()
+    });
+    if let Err(e) = recorded {
+        if let Err(rollback) = state.update_server(name, server) {
+            eprintln!("Failed to roll back the upgrade of {}: {}", name, rollback);
+        }
+        return Err(e);
+    }
+
+    let mut warning = None;
+    if let Some(pm) = host.process_manager() {
+        let grace = state.get_settings()?.shutdown_grace();
+        if let Err(e) = process_manager::apply_config_change(
+            host.clone(),
+            pm,
+            name.to_string(),
+            upgraded,
+            restart,
+            grace,
+        )
+        .await
+        {
+            warning = Some(format!(
+                "Upgraded, but {} could not be restarted: {}",
+                name, e
+            ));
+        }
+    }
+
+    Ok(ServerUpdate {
+        server_name: name.to_string(),
+        package: spec.package,
+        ecosystem: spec.ecosystem,
+        current: spec.version,
+        latest: version,
+        warning,
+    })
+}
+
+/// The version to pin: `requested` if the index published it, by default the latest release
+fn release_to_pin(
+    published: &PackageVersions,
+    requested: Option<String>,
+) -> SynapticResult<String> {
+    let Some(requested) = requested else {
+        return published.latest.clone().ok_or_else(|| {
+            SynapticError::RegistryError(format!("{} has no latest release", published.package))
+        });
+    };
+    match published.versions.iter().find(|v| v.version == requested) {
+        Some(release) if release.withdrawn => Err(SynapticError::RegistryError(format!(
+            "{} {} was withdrawn",
+            published.package, requested
+        ))),
+        Some(_) => Ok(requested),
+        None => Err(SynapticError::RegistryError(format!(
+            "{} has no version {}",
+            published.package, requested
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(command: &str, args: &[&str]) -> McpServer {
+        McpServer {
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reads_package_specs_from_args() {
+        let scoped = package_spec(&server(
+            "npx",
+            &["-y", "@modelcontextprotocol/server-github@2025.4.8"],
+        ))
+        .unwrap();
+        assert_eq!(scoped.package, "@modelcontextprotocol/server-github");
+        assert_eq!(scoped.version.as_deref(), Some("2025.4.8"));
+
+        let unpinned =
+            package_spec(&server("/usr/local/bin/npx", &["-y", "@scope/pkg", "/tmp"])).unwrap();
+        assert_eq!(unpinned.package, "@scope/pkg");
+        assert_eq!(unpinned.version, None);
+        assert_eq!(
+            package_spec(&server("npx", &["pkg@latest"]))
+                .unwrap()
+                .version,
+            None
+        );
+
+        let from = package_spec(&server(
+            "uvx",
+            &[
+                "--python",
+                "3.12",
+                "--from",
+                "mcp-git==0.6.1",
+                "mcp-server-git",
+            ],
+        ))
+        .unwrap();
+        assert_eq!(from.ecosystem, Ecosystem::Pypi);
+        assert_eq!(from.package, "mcp-git");
+        assert_eq!(from.version.as_deref(), Some("0.6.1"));
+
+        assert!(package_spec(&server("node", &["index.js"])).is_none());
+    }
+
+    #[test]
+    fn test_only_published_versions_are_pinned() {
+        let release = |version: &str, withdrawn| packages::PackageVersion {
+            version: version.to_string(),
+            published_at: None,
+            withdrawn,
+            prerelease: false,
+        };
+        let published = PackageVersions {
+            package: "@scope/pkg".to_string(),
+            ecosystem: Ecosystem::Npm,
+            latest: Some("1.2.0".to_string()),
+            versions: vec![release("1.2.0", false), release("1.1.0", true)],
+        };
+
+        assert_eq!(release_to_pin(&published, None).unwrap(), "1.2.0");
+        assert_eq!(
+            release_to_pin(&published, Some("1.2.0".to_string())).unwrap(),
+            "1.2.0"
+        );
+        assert!(release_to_pin(&published, Some("1.1.0".to_string())).is_err());
+        assert!(release_to_pin(&published, Some("9.9.9; rm -rf /".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_rewrites_the_version_in_place() {
+        let npx = server("npx", &["-y", "@scope/pkg@1.0.0", "--port", "3000"]);
+        let spec = package_spec(&npx).unwrap();
+        assert_eq!(
+            with_version(&npx, &spec, "1.2.0").args,
+            vec!["-y", "@scope/pkg@1.2.0", "--port", "3000"]
+        );
+
+        let uvx = server("uvx", &["--from", "mcp-git==0.6.1", "mcp-server-git"]);
+        let spec = package_spec(&uvx).unwrap();
+        assert_eq!(with_version(&uvx, &spec, "0.7.0").args[1], "mcp-git==0.7.0");
+    }
+}
@@ -0,0 +1,533 @@
+//! JSON-RPC 2.0 and MCP schema validation of live traffic
+//!
+//! Every message to and from a server is checked for JSON-RPC structure and,
+//! for the core MCP methods (initialize, tools/*, resources/*, prompts/*),
+//! for the shape of its params or result. Responses are checked against the
+//! method of the request they answer. Problems are attached to the captured
+//! inspector message and kept per server for `get_protocol_violations`.
+
+use crate::intercept::TrafficDirection;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Violations kept per server, oldest dropped first
+pub const MAX_VIOLATIONS: usize = 500;
+
+/// A message that broke the protocol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolViolation {
+    pub server_name: String,
+    pub direction: TrafficDirection,
+    pub timestamp: DateTime<Utc>,
+    /// Method of the message, or of the request a response answers
+    pub method: Option<String>,
+    pub errors: Vec<String>,
+    /// The offending message (raw text when it isn't JSON)
+    pub payload: Value,
+}
+
+// ============================================
+// JSON-RPC STRUCTURE
+// ============================================
+
+/// Structural JSON-RPC 2.0 problems of a single message
+pub fn validate_jsonrpc(message: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    let Some(object) = message.as_object() else {
+        return vec!["message is not a JSON object".to_string()];
+    };
+
+    if object.get("jsonrpc").and_then(|v| v.as_str()) != Some("2.0") {
+        errors.push("\"jsonrpc\" must be \"2.0\"".to_string());
+    }
+
+    let id = object.get("id");
+    if let Some(id) = id {
+        if !(id.is_string() || id.is_i64() || id.is_u64() || id.is_null()) {
+            errors.push("\"id\" must be a string or integer".to_string());
+        }
+    }
+
+    match (
+        object.get("method"),
+        object.get("result"),
+        object.get("error"),
+    ) {
+        (Some(method), None, None) => {
+            if !method.is_string() {
+                errors.push("\"method\" must be a string".to_string());
+            }
+            if id.is_some_and(|id| id.is_null()) {
+                errors.push("request \"id\" must not be null".to_string());
+            }
+            if let Some(params) = object.get("params") {
+                if !(params.is_object() || params.is_array()) {
+                    errors.push("\"params\" must be an object or array".to_string());
+                }
+            }
+        }
+        (None, Some(_), None) => {
+            if id.is_none_or(|id| id.is_null()) {
+                errors.push("result response needs the request \"id\"".to_string());
+            }
+        }
+        (None, None, Some(error)) => {
+            if id.is_none() {
+                errors.push("error response needs an \"id\" (null if unknown)".to_string());
+            }
+            if !error.get("code").is_some_and(|c| c.is_i64()) {
+                errors.push("\"error.code\" must be an integer".to_string());
+            }
+            if !error.get("message").is_some_and(|m| m.is_string()) {
+                errors.push("\"error.message\" must be a string".to_string());
+            }
+        }
+        (None, Some(_), Some(_)) => {
+            errors.push("response has both \"result\" and \"error\"".to_string())
+        }
+        _ => errors.push("not a request, notification or response".to_string()),
+    }
+    errors
+}
+
+// ============================================
+// MCP SCHEMAS
+// ============================================
+
+fn require(
+    errors: &mut Vec<String>,
+    value: &Value,
+    path: &str,
+    check: fn(&Value) -> bool,
+    kind: &str,
+) {
+    let mut current = Some(value);
+    for key in path.split('.') {
+        current = current.and_then(|v| v.get(key));
+    }
+    match current {
+        Some(found) if check(found) => {}
+        Some(_) => errors.push(format!("\"{}\" must be {}", path, kind)),
+        None => errors.push(format!("\"{}\" is missing", path)),
+    }
+}
+
+fn optional(
+    errors: &mut Vec<String>,
+    value: &Value,
+    key: &str,
+    check: fn(&Value) -> bool,
+    kind: &str,
+) {
+    if let Some(found) = value.get(key) {
+        if !check(found) {
+            errors.push(format!("\"{}\" must be {}", key, kind));
+        }
+    }
+}
+
+/// Check every element of an array member with `item`
+fn each(
+    errors: &mut Vec<String>,
+    value: &Value,
+    key: &str,
+    item: impl Fn(&mut Vec<String>, &Value),
+) {
+    match value.get(key) {
+        Some(Value::Array(items)) => {
+            for (i, element) in items.iter().enumerate() {
+                let mut item_errors = Vec::new();
+                item(&mut item_errors, element);
+                errors.extend(
+                    item_errors
+                        .into_iter()
+                        .map(|e| format!("{}[{}]: {}", key, i, e)),
+                );
+            }
+        }
+        Some(_) => errors.push(format!("\"{}\" must be an array", key)),
+        None => errors.push(format!("\"{}\" is missing", key)),
+    }
+}
+
+/// MCP problems with the params of a request
+pub fn validate_params(method: &str, params: Option<&Value>) -> Vec<String> {
+    let mut errors = Vec::new();
+    let empty = Value::Object(Default::default());
+    let params = params.unwrap_or(&empty);
+
+    match method {
+        "initialize" => {
+            require(
+                &mut errors,
+                params,
+                "protocolVersion",
+                Value::is_string,
+                "a string",
+            );
+            require(
+                &mut errors,
+                params,
+                "capabilities",
+                Value::is_object,
+                "an object",
+            );
+            require(
+                &mut errors,
+                params,
+                "clientInfo.name",
+                Value::is_string,
+                "a string",
+            );
+            require(
+                &mut errors,
+                params,
+                "clientInfo.version",
+                Value::is_string,
+                "a string",
+            );
+        }
+        "tools/call" => {
+            require(&mut errors, params, "name", Value::is_string, "a string");
+            optional(
+                &mut errors,
+                params,
+                "arguments",
+                Value::is_object,
+                "an object",
+            );
+        }
+        "prompts/get" => {
+            require(&mut errors, params, "name", Value::is_string, "a string");
+            optional(
+                &mut errors,
+                params,
+                "arguments",
+                Value::is_object,
+                "an object",
+            );
+        }
+        "resources/read" | "resources/subscribe" | "resources/unsubscribe" => {
+            require(&mut errors, params, "uri", Value::is_string, "a string");
+        }
+        "tools/list" | "resources/list" | "resources/templates/list" | "prompts/list" => {
+            optional(&mut errors, params, "cursor", Value::is_string, "a string");
+        }
+        _ => {}
+    }
+    errors
+}
+
+/// MCP problems with the result answering `method`
+pub fn validate_result(method: &str, result: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    if !result.is_object() {
+        return vec!["\"result\" must be an object".to_string()];
+    }
+    if method.ends_with("/list") {
+        optional(
+            &mut errors,
+            result,
+            "nextCursor",
+            Value::is_string,
+            "a string",
+        );
+    }
+
+    match method {
+        "initialize" => {
+            require(
+                &mut errors,
+                result,
+                "protocolVersion",
+                Value::is_string,
+                "a string",
+            );
+            require(
+                &mut errors,
+                result,
+                "capabilities",
+                Value::is_object,
+                "an object",
+            );
+            require(
+                &mut errors,
+                result,
+                "serverInfo.name",
+                Value::is_string,
+                "a string",
+            );
+            require(
+                &mut errors,
+                result,
+                "serverInfo.version",
+                Value::is_string,
+                "a string",
+            );
+        }
+        "tools/list" => each(&mut errors, result, "tools", |errors, tool| {
+            require(errors, tool, "name", Value::is_string, "a string");
+            require(errors, tool, "inputSchema", Value::is_object, "an object");
+        }),
+        "tools/call" => {
+            each(&mut errors, result, "content", |errors, item| {
+                require(errors, item, "type", Value::is_string, "a string");
+            });
+            optional(
+                &mut errors,
+                result,
+                "isError",
+                Value::is_boolean,
+                "a boolean",
+            );
+        }
+        "resources/list" => each(&mut errors, result, "resources", |errors, resource| {
+            require(errors, resource, "uri", Value::is_string, "a string");
+            require(errors, resource, "name", Value::is_string, "a string");
+        }),
+        "resources/templates/list" => each(
+            &mut errors,
+            result,
+            "resourceTemplates",
+            |errors, template| {
+                require(
+                    errors,
+                    template,
+                    "uriTemplate",
+                    Value::is_string,
+                    "a string",
+                );
+                require(errors, template, "name", Value::is_string, "a string");
+            },
+        ),
+        "resources/read" => each(&mut errors, result, "contents", |errors, content| {
+            require(errors, content, "uri", Value::is_string, "a string");
+            if content.get("text").is_none() && content.get("blob").is_none() {
+                errors.push("needs \"text\" or \"blob\"".to_string());
+            }
+        }),
+        "prompts/list" => each(&mut errors, result, "prompts", |errors, prompt| {
+            require(errors, prompt, "name", Value::is_string, "a string");
+        }),
+        "prompts/get" => each(&mut errors, result, "messages", |errors, message| {
+            if !matches!(
+                message.get("role").and_then(|r| r.as_str()),
+                Some("user" | "assistant")
+            ) {
+                errors.push("\"role\" must be \"user\" or \"assistant\"".to_string());
+            }
+            require(errors, message, "content", Value::is_object, "an object");
+        }),
+        _ => {}
+    }
+    errors
+}
+
+// ============================================
+// LIVE VALIDATOR
+// ============================================
+
+/// Validates traffic as it flows and remembers the violations per server
+#[derive(Default)]
+pub struct ProtocolValidator {
+    /// Method of each unanswered request, by (server, direction it travelled, id)
+    methods: Mutex<HashMap<(String, TrafficDirection, String), String>>,
+    violations: Mutex<HashMap<String, VecDeque<ProtocolViolation>>>,
+}
+
+impl ProtocolValidator {
+    /// Validate one message, recording and returning its problems
+    pub fn check(&self, server_name: &str, direction: TrafficDirection, raw: &str) -> Vec<String> {
+        let Ok(message) = serde_json::from_str::<Value>(raw) else {
+            let errors = vec!["not valid JSON".to_string()];
+            self.record(
+                server_name,
+                direction,
+                None,
+                errors.clone(),
+                Value::String(raw.to_string()),
+            );
+            return errors;
+        };
+
+        let mut errors = Vec::new();
+        let mut method = None;
+        let messages = match &message {
+            Value::Array(batch) if !batch.is_empty() => batch.iter().collect(),
+            Value::Array(_) => {
+                errors.push("empty batch".to_string());
+                Vec::new()
+            }
+            single => vec![single],
+        };
+        for single in messages {
+            let (m, e) = self.check_single(server_name, direction, single);
+            method = method.or(m);
+            errors.extend(e);
+        }
+
+        if !errors.is_empty() {
+            self.record(server_name, direction, method, errors.clone(), message);
+        }
+        errors
+    }
+
+    fn check_single(
+        &self,
+        server_name: &str,
+        direction: TrafficDirection,
+        message: &Value,
+    ) -> (Option<String>, Vec<String>) {
+        let mut errors = validate_jsonrpc(message);
+        let id = message
+            .get("id")
+            .filter(|id| !id.is_null())
+            .map(|id| id.to_string());
+
+        if let Some(method) = message.get("method").and_then(|m| m.as_str()) {
+            errors.extend(validate_params(method, message.get("params")));
+            if let Some(id) = id {
+                self.methods
+                    .lock()
+                    .unwrap()
+                    .insert((server_name.to_string(), direction, id), method.to_string());
+            }
+            return (Some(method.to_string()), errors);
+        }
+
+        // A response answers a request that travelled the other way
+        let requested = match direction {
+            TrafficDirection::Incoming => TrafficDirection::Outgoing,
+            TrafficDirection::Outgoing => TrafficDirection::Incoming,
+        };
+        let method = id.and_then(|id| {
+            self.methods
+                .lock()
+                .unwrap()
+                .remove(&(server_name.to_string(), requested, id))
+        });
+        if let (Some(method), Some(result)) = (&method, message.get("result")) {
+            errors.extend(validate_result(method, result));
+        }
+        (method, errors)
+    }
+
+    fn record(
+        &self,
+        server_name: &str,
+        direction: TrafficDirection,
+        method: Option<String>,
+        errors: Vec<String>,
+        payload: Value,
+    ) {
+        let mut violations = self.violations.lock().unwrap();
+        let list = violations.entry(server_name.to_string()).or_default();
+        if list.len() >= MAX_VIOLATIONS {
+            list.pop_front();
+        }
+        list.push_back(ProtocolViolation {
+            server_name: server_name.to_string(),
+            direction,
+            timestamp: Utc::now(),
+            method,
+            errors,
+            payload,
+        });
+    }
+
+    /// Violations seen for a server, oldest first
+    pub fn violations(&self, server_name: &str) -> Vec<ProtocolViolation> {
+        self.violations
+            .lock()
+            .unwrap()
+            .get(server_name)
+            .map(|list| list.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&self, server_name: &str) {
+        self.violations.lock().unwrap().remove(server_name);
+    }
+
+    /// Forget unanswered requests of a server whose process exited
+    pub fn forget_requests(&self, server_name: &str) {
+        self.methods
+            .lock()
+            .unwrap()
+            .retain(|(server, _, _), _| server != server_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_jsonrpc_structure() {
+        assert!(validate_jsonrpc(&json!({"jsonrpc":"2.0","id":1,"method":"ping"})).is_empty());
+        assert!(
+            validate_jsonrpc(&json!({"jsonrpc":"2.0","method":"notifications/initialized"}))
+                .is_empty()
+        );
+        assert!(validate_jsonrpc(
+            &json!({"jsonrpc":"2.0","id":null,"error":{"code":-32700,"message":"Parse error"}})
+        )
+        .is_empty());
+
+        assert_eq!(validate_jsonrpc(&json!({"id":1,"method":"ping"})).len(), 1);
+        assert_eq!(
+            validate_jsonrpc(&json!({"jsonrpc":"2.0","id":1,"result":{},"error":{}})).len(),
+            1
+        );
+        assert_eq!(
+            validate_jsonrpc(&json!({"jsonrpc":"2.0","id":1,"error":{"code":"x"}})).len(),
+            2
+        );
+        assert_eq!(validate_jsonrpc(&json!({"jsonrpc":"2.0","id":1})).len(), 1);
+        assert_eq!(validate_jsonrpc(&json!([1])).len(), 1);
+    }
+
+    #[test]
+    fn test_responses_are_checked_against_their_request() {
+        let validator = ProtocolValidator::default();
+        let outgoing = TrafficDirection::Outgoing;
+        let incoming = TrafficDirection::Incoming;
+
+        assert!(validator
+            .check(
+                "fs",
+                outgoing,
+                r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#
+            )
+            .is_empty());
+        let errors = validator.check(
+            "fs",
+            incoming,
+            r#"{"jsonrpc":"2.0","id":1,"result":{"tools":[{"name":"read"}]}}"#,
+        );
+        assert_eq!(errors, vec!["tools[0]: \"inputSchema\" is missing"]);
+
+        let errors = validator.check(
+            "fs",
+            outgoing,
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"arguments":{}}}"#,
+        );
+        assert_eq!(errors, vec!["\"name\" is missing"]);
+
+        // Log lines on stdout are a classic server bug
+        assert_eq!(
+            validator.check("fs", incoming, "Server started"),
+            vec!["not valid JSON"]
+        );
+
+        let violations = validator.violations("fs");
+        assert_eq!(violations.len(), 3);
+        assert_eq!(violations[0].method.as_deref(), Some("tools/list"));
+        assert!(validator.violations("git").is_empty());
+    }
+}
@@ -0,0 +1,2391 @@
+//! End-to-end process lifecycle through a recording host (no Tauri runtime)
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use synaptic_core::audit::{self, AuditAction};
+use synaptic_core::chaos::ChaosConfig;
+use synaptic_core::config::{ProcessPriority, RestartMode, RestartPolicy};
+use synaptic_core::conformance::{self, CheckOutcome};
+use synaptic_core::database::Database;
+use synaptic_core::fuzz::{self, FindingKind, FuzzOptions};
+use synaptic_core::gateway;
+use synaptic_core::health;
+use synaptic_core::history_retention::{HistoryMirror, HistoryRetention};
+use synaptic_core::idle;
+use synaptic_core::installer;
+use synaptic_core::intercept::{Breakpoint, InterceptAction, TrafficDirection};
+use synaptic_core::load_test::{self, LoadTestPlan};
+use synaptic_core::message_diff::DiffKind;
+use synaptic_core::mock_server;
+use synaptic_core::mocks::MockRule;
+use synaptic_core::oversized::{self, OversizedPolicy};
+use synaptic_core::policy::{self, PolicyAction, ToolPolicy};
+use synaptic_core::probe;
+use synaptic_core::process_manager::{ProcessState, ShutdownStage};
+use synaptic_core::rate_limit::{self, RateLimit};
+use synaptic_core::redaction::RedactionRule;
+use synaptic_core::resources;
+use synaptic_core::sampling::{self, SamplingReply};
+use synaptic_core::server_compare::{self, BehaviorOutcome, ComparisonSource};
+use synaptic_core::server_logs;
+use synaptic_core::settings::SynapticSettings;
+use synaptic_core::system_logs;
+use synaptic_core::traffic_mirror::TrafficMirror;
+use synaptic_core::transport::Transport;
+use synaptic_core::{
+    AppState, EventEmitter, McpConfig, McpServer, ProcessManager, StateProvider, SynapticError,
+};
+
+/// Minimal host that records every emitted event
+#[derive(Clone, Default)]
+struct RecordingHost {
+    state: Arc<AppState>,
+    pm: Arc<ProcessManager>,
+    events: Arc<Mutex<Vec<(String, serde_json::Value)>>>,
+}
+
+impl EventEmitter for RecordingHost {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) {
+        self.events
+            .lock()
+            .unwrap()
+            .push((event.to_string(), payload));
+    }
+}
+
+impl StateProvider for RecordingHost {
+    fn app_state(&self) -> Option<&AppState> {
+        Some(&self.state)
+    }
+
+    fn process_manager(&self) -> Option<&ProcessManager> {
+        Some(&self.pm)
+    }
+}
+
+impl RecordingHost {
+    /// Host with default settings, no startup readiness wait and no log files
+    fn new() -> Self {
+        Self::with_log_dir(None)
+    }
+
+    fn with_log_dir(log_dir: Option<PathBuf>) -> Self {
+        let host = Self {
+            pm: Arc::new(ProcessManager::with_log_dir(log_dir)),
+            ..Self::default()
+        };
+        host.set_settings(SynapticSettings {
+            startup_timeout_ms: 0,
+            ..Default::default()
+        });
+        host
+    }
+
+    fn set_settings(&self, settings: SynapticSettings) {
+        *self.state.settings_cache.lock().unwrap() = Some(settings);
+    }
+
+    fn count(&self, event: &str) -> usize {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| name == event)
+            .count()
+    }
+
+    async fn wait_for(&self, event: &str, count: usize) -> bool {
+        for _ in 0..100 {
+            if self.count(event) >= count {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        false
+    }
+
+    /// Traffic events received so far, across all batches
+    fn traffic(&self) -> Vec<serde_json::Value> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| name == "mcp-traffic-batch")
+            .flat_map(|(_, batch)| batch.as_array().cloned().unwrap_or_default())
+            .collect()
+    }
+
+    async fn wait_for_traffic(&self, count: usize) -> bool {
+        for _ in 0..100 {
+            if self.traffic().len() >= count {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        false
+    }
+}
+
+fn node_available() -> bool {
+    std::process::Command::new("node")
+        .arg("--version")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+fn node_server(script: &str) -> McpServer {
+    McpServer {
+        command: "node".to_string(),
+        args: vec!["-e".to_string(), script.to_string()],
+        ..Default::default()
+    }
+}
+
+/// Node one-liner that echoes each stdin line back as a JSON-RPC result
+const ECHO_SERVER: &str = r#"require('readline').createInterface({input:process.stdin}).on('line',l=>{const m=JSON.parse(l);console.log(JSON.stringify({jsonrpc:'2.0',id:m.id,result:{echo:m.method}}))})"#;
+
+/// Node one-liner speaking enough MCP for probes and tool calls
+///
+/// Declares tools only, pages its tool list, echoes `tools/call` arguments
+/// back as text and reports the `fail` tool as erroring. Resources read as
+/// `hello` and announce an update right after being subscribed to; every
+/// prompt greets its `name` argument.
+const MCP_SERVER: &str = r#"require('readline').createInterface({input:process.stdin}).on('line',l=>{const m=JSON.parse(l);if(m.id===undefined)return;const p=m.params||{};const r={initialize:{protocolVersion:'2025-06-18',capabilities:{tools:{}},serverInfo:{name:'probe-me',version:'1.0'}},'tools/list':p.cursor?{tools:[{name:'b',inputSchema:{}}]}:{tools:[{name:'a',inputSchema:{}}],nextCursor:'p2'},'tools/call':p.name==='missing'?undefined:{content:[{type:'text',text:JSON.stringify(p.arguments)}],isError:p.name==='fail'},'resources/read':{contents:[{uri:p.uri,mimeType:'text/plain',text:'hello'}]},'resources/subscribe':{},'prompts/get':{description:'Greeting',messages:[{role:'user',content:{type:'text',text:'Hello '+(p.arguments||{}).name}}]},ping:{}}[m.method];console.log(JSON.stringify(r?{jsonrpc:'2.0',id:m.id,result:r}:{jsonrpc:'2.0',id:m.id,error:{code:-32601,message:'Method not found'}}));if(m.method==='resources/subscribe')console.log(JSON.stringify({jsonrpc:'2.0',method:'notifications/resources/updated',params:{uri:p.uri}}))})"#;
+
+#[tokio::test]
+async fn test_rejects_non_whitelisted_command() {
+    let host = RecordingHost::new();
+    let bash = McpServer {
+        command: "bash".to_string(),
+        ..Default::default()
+    };
+
+    let result = synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "evil".to_string(),
+        bash.clone(),
+    )
+    .await;
+
+    assert!(matches!(result, Err(SynapticError::ApprovalRequired(_))));
+    assert_eq!(host.count("executable-approval-requested"), 1);
+    assert!(!host.pm.is_running("evil").await);
+
+    // A remembered denial fails without asking again
+    let mut settings = SynapticSettings::default();
+    settings.set_executable_approval("bash", false);
+    host.set_settings(settings);
+
+    let result = synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "evil".to_string(),
+        bash,
+    )
+    .await;
+
+    assert!(matches!(result, Err(SynapticError::ProcessError(_))));
+    assert_eq!(host.count("executable-approval-requested"), 1);
+}
+
+#[tokio::test]
+async fn test_spawn_roundtrip_and_kill() {
+    if !node_available() {
+        return;
+    }
+
+    let log_dir = std::env::temp_dir().join(format!("synaptic-logs-{}", uuid::Uuid::new_v4()));
+    let host = RecordingHost::with_log_dir(Some(log_dir.clone()));
+    let pid = synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+    assert!(pid > 0);
+    assert!(host.pm.is_running("echo").await);
+
+    let status = host.pm.get_status("echo").await.unwrap();
+    assert_eq!(status.state, ProcessState::Running);
+    assert_eq!(status.pid, Some(pid));
+
+    host.pm
+        .send_to_stdin(
+            "echo",
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#.to_string(),
+        )
+        .await
+        .unwrap();
+
+    // One outgoing + one incoming traffic event, in that order
+    assert!(host.wait_for_traffic(2).await);
+    let directions: Vec<_> = host
+        .traffic()
+        .iter()
+        .map(|event| event["direction"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(directions, ["OUTGOING", "INCOMING"]);
+    let messages = host.state.get_inspector_messages("echo");
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].payload["result"]["echo"], "tools/list");
+
+    // The echo server exits as soon as its stdin is closed
+    let stage = host
+        .pm
+        .kill_process("echo", Duration::from_secs(2))
+        .await
+        .unwrap();
+    assert_eq!(stage, ShutdownStage::StdinClosed);
+    assert!(host.wait_for("process-stopped", 1).await);
+    assert!(!host.pm.is_running("echo").await);
+    assert!(host.pm.get_status("echo").await.is_none());
+
+    // Output also went to the server's log file
+    let tail = server_logs::tail_server_log_in(&log_dir, "echo", None, 10).unwrap();
+    assert_eq!(tail.len(), 1);
+    assert!(tail[0].contains("[stdout]") && tail[0].contains("tools/list"));
+    let _ = std::fs::remove_dir_all(&log_dir);
+}
+
+#[tokio::test]
+async fn test_send_request_awaits_correlated_response() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+    let timeout = Duration::from_secs(5);
+
+    // An id is injected when missing
+    let response = host
+        .pm
+        .send_request(
+            "echo",
+            serde_json::json!({ "method": "tools/list" }),
+            timeout,
+        )
+        .await
+        .unwrap();
+    assert!(response["id"].is_string());
+    assert_eq!(response["result"]["echo"], "tools/list");
+
+    let response = host
+        .pm
+        .send_request(
+            "echo",
+            serde_json::json!({ "jsonrpc": "2.0", "id": 7, "method": "ping" }),
+            timeout,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response["id"], 7);
+    assert!(host.pm.awaiting.lock().await.is_empty());
+
+    // Both exchanges are regular traffic
+    assert!(host.wait_for_traffic(4).await);
+
+    assert!(host
+        .pm
+        .send_request("echo", serde_json::json!({ "id": 1 }), timeout)
+        .await
+        .is_err());
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn test_probe_lists_catalog_of_stopped_server() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+
+    let catalog = probe::probe_server(host.clone(), "probed", node_server(MCP_SERVER))
+        .await
+        .unwrap();
+    assert!(catalog.spawned);
+    assert_eq!(catalog.protocol_version.as_deref(), Some("2025-06-18"));
+    assert_eq!(catalog.server_info["name"], "probe-me");
+    let tools: Vec<_> = catalog.tools.iter().map(|t| t["name"].clone()).collect();
+    assert_eq!(tools, vec!["a", "b"]);
+    // Undeclared lists aren't asked for
+    assert!(catalog.prompts.is_empty() && catalog.errors.is_empty());
+
+    // Stopped again afterwards
+    assert!(!host.pm.is_running("probed").await);
+}
+
+#[tokio::test]
+async fn test_compare_servers_diffs_catalogs_and_responses() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let newer = MCP_SERVER
+        .replace("version:'1.0'", "version:'2.0'")
+        .replace("{tools:[{name:'b'", "{tools:[{name:'c'");
+    let mut config = McpConfig::default();
+    config
+        .mcp_servers
+        .insert("old".to_string(), node_server(MCP_SERVER));
+    config
+        .mcp_servers
+        .insert("new".to_string(), node_server(&newer));
+    *host.state.config_cache.lock().unwrap() = Some(config);
+
+    let side = |name: &str| ComparisonSource::Server {
+        server_name: name.to_string(),
+    };
+    let comparison = server_compare::compare_servers(
+        host.clone(),
+        side("old"),
+        side("new"),
+        vec![
+            serde_json::json!({"method": "ping"}),
+            serde_json::json!({"method": "tools/list", "params": {"cursor": "p2"}}),
+        ],
+    )
+    .await
+    .unwrap();
+
+    assert!(!comparison.identical);
+    assert_eq!(comparison.server_info[0].path, "/version");
+    assert!(comparison.capabilities.is_empty());
+    let catalog: Vec<_> = comparison
+        .catalog
+        .iter()
+        .map(|d| (d.key.as_str(), d.kind))
+        .collect();
+    assert_eq!(
+        catalog,
+        vec![("b", DiffKind::Removed), ("c", DiffKind::Added)]
+    );
+    let outcomes: Vec<_> = comparison.behavior.iter().map(|b| b.outcome).collect();
+    assert_eq!(
+        outcomes,
+        vec![BehaviorOutcome::Same, BehaviorOutcome::Different]
+    );
+
+    // Both were only started for the comparison
+    assert!(comparison.a.catalog.spawned && comparison.b.catalog.spawned);
+    assert!(!host.pm.is_running("old").await);
+    assert!(!host.pm.is_running("new").await);
+}
+
+#[tokio::test]
+async fn test_conformance_suite_scores_a_server() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+
+    let report =
+        conformance::run_conformance_tests(host.clone(), "checked", node_server(MCP_SERVER))
+            .await
+            .unwrap();
+    assert!(report.spawned);
+    assert_eq!(report.protocol_version.as_deref(), Some("2025-06-18"));
+    let outcome = |id: &str| {
+        report
+            .checks
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.outcome)
+            .unwrap()
+    };
+    assert_eq!(outcome("initialize"), CheckOutcome::Pass);
+    assert_eq!(outcome("ping"), CheckOutcome::Pass);
+    assert_eq!(outcome("capabilities"), CheckOutcome::Pass);
+    assert_eq!(outcome("unknown-method"), CheckOutcome::Pass);
+    assert_eq!(outcome("cancellation"), CheckOutcome::Pass);
+    // Its tools have an empty inputSchema
+    assert_eq!(outcome("tools-schema"), CheckOutcome::Fail);
+    assert_eq!(report.score, 83);
+
+    // The suite's requests are ordinary traffic
+    assert!(host.traffic().iter().any(|event| event["content"]
+        .as_str()
+        .unwrap()
+        .contains("synaptic/conformance/no-such-method")));
+    assert!(!host.pm.is_running("checked").await);
+}
+
+#[tokio::test]
+async fn test_fuzz_run_finds_crashes_and_stores_the_report() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost {
+        state: Arc::new(AppState::with_database(Database::open_in_memory().unwrap())),
+        ..RecordingHost::new()
+    };
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 0,
+        ..Default::default()
+    });
+
+    let options = FuzzOptions {
+        tool: Some("a".to_string()),
+        timeout_ms: Some(2000),
+        ..Default::default()
+    };
+    let report = fuzz::run_fuzz(host.clone(), "fuzzed", node_server(MCP_SERVER), options)
+        .await
+        .unwrap();
+    assert_eq!(report.aborted, None);
+    assert_eq!(report.cases_run, report.cases_total);
+
+    // The server dies on lines that aren't JSON and is started again
+    let crashes: Vec<_> = report
+        .findings
+        .iter()
+        .filter(|f| f.kind == FindingKind::Crash)
+        .map(|f| f.description.as_str())
+        .collect();
+    assert_eq!(crashes, vec!["Not JSON", "Truncated object"]);
+    // and accepts tool arguments that aren't an object
+    assert!(report
+        .findings
+        .iter()
+        .any(|f| f.kind == FindingKind::ProtocolViolation
+            && f.tool.as_deref() == Some("a")
+            && f.details.contains("aren't an object")));
+    assert!(!report.findings.iter().any(|f| f.kind == FindingKind::Hang));
+    assert!(!host.pm.is_running("fuzzed").await);
+
+    let stored = fuzz::list_reports(host.state.database().unwrap(), Some("fuzzed")).unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].findings.len(), report.findings.len());
+}
+
+#[tokio::test]
+async fn test_load_test_measures_latency_and_errors() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "loaded".to_string(),
+        node_server(MCP_SERVER),
+    )
+    .await
+    .unwrap();
+    let plan = |method: &str| LoadTestPlan {
+        request: serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method }),
+        template: None,
+        concurrency: 4,
+        duration_ms: 300,
+        timeout_ms: 2000,
+    };
+
+    let result = load_test::run_load_test(&host.pm, "loaded", plan("ping"))
+        .await
+        .unwrap();
+    assert!(result.requests >= 4);
+    assert_eq!(result.successes, result.requests);
+    assert_eq!(
+        result.histogram.iter().map(|b| b.count).sum::<u64>(),
+        result.requests
+    );
+    assert!(result.latency.min_us <= result.latency.p50_us);
+    assert!(result.latency.p50_us <= result.latency.max_us);
+
+    // JSON-RPC errors are counted by code
+    let result = load_test::run_load_test(&host.pm, "loaded", plan("no/such/method"))
+        .await
+        .unwrap();
+    assert_eq!(result.errors, result.requests);
+    assert_eq!(result.error_codes[&-32601], result.requests);
+
+    assert!(!result.server_stopped);
+
+    // A server going away ends the run instead of being hammered with failed sends
+    let run = {
+        let pm = host.pm.clone();
+        let plan = LoadTestPlan {
+            duration_ms: 5000,
+            ..plan("ping")
+        };
+        tokio::spawn(async move { load_test::run_load_test(&pm, "loaded", plan).await })
+    };
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    host.pm
+        .kill_process("loaded", Duration::from_secs(1))
+        .await
+        .unwrap();
+    let result = run.await.unwrap().unwrap();
+    assert!(result.server_stopped);
+    assert!(result.elapsed_ms < 4000);
+    assert!(result.failures <= 8);
+
+    let zero = LoadTestPlan {
+        concurrency: 0,
+        ..plan("ping")
+    };
+    assert!(load_test::run_load_test(&host.pm, "loaded", zero)
+        .await
+        .is_err());
+    host.pm.kill_all(Duration::from_secs(1)).await;
+}
+
+#[tokio::test]
+async fn test_mock_server_answers_from_its_definition() {
+    let dir = std::env::temp_dir().join(format!("synaptic-mock-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let definition = dir.join("mock.json");
+    std::fs::write(
+        &definition,
+        serde_json::json!({
+            "name": "mock-weather",
+            "tools": [{
+                "name": "forecast",
+                "description": "Weather for a city",
+                "cases": [{ "arguments": { "city": "Oslo" }, "result": { "content": [{ "type": "text", "text": "Snow" }] } }],
+            }],
+            "resources": [{ "uri": "file:///readme", "name": "readme", "mimeType": "text/plain", "text": "hello" }],
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    // Laid out like the app bundle: the sidecar next to the running executable
+    let exe = std::env::current_exe().unwrap();
+    let bundled = exe.with_file_name(format!(
+        "{}{}",
+        mock_server::MOCK_SERVER_BINARY,
+        std::env::consts::EXE_SUFFIX
+    ));
+    let staged = bundled.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+    std::fs::copy(env!("CARGO_BIN_EXE_synaptic-mock-server"), &staged).unwrap();
+    std::fs::rename(&staged, &bundled).unwrap();
+
+    // The bundled mock server needs no whitelist entry
+    let host = RecordingHost::new();
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 0,
+        ..Default::default()
+    });
+    let server = mock_server::client_entry(&definition).unwrap();
+    assert!(mock_server::is_bundled(&server.command));
+
+    let catalog = probe::probe_server(host.clone(), "mock", server.clone())
+        .await
+        .unwrap();
+    assert_eq!(catalog.server_info["name"], "mock-weather");
+    assert_eq!(catalog.tools[0]["name"], "forecast");
+    assert_eq!(catalog.resources[0]["uri"], "file:///readme");
+
+    let snow = probe::call_tool(
+        host.clone(),
+        "mock",
+        server.clone(),
+        "forecast",
+        serde_json::json!({ "city": "Oslo" }),
+        Duration::from_secs(5),
+    )
+    .await
+    .unwrap();
+    assert_eq!(snow.content[0]["text"], "Snow");
+
+    // A deterministic server passes the whole conformance suite
+    let report = conformance::run_conformance_tests(host.clone(), "mock", server)
+        .await
+        .unwrap();
+    assert_eq!(report.score, 100, "{:?}", report.checks);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_call_tool_round_trip() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let call = |tool: &'static str| {
+        probe::call_tool(
+            host.clone(),
+            "tools",
+            node_server(MCP_SERVER),
+            tool,
+            serde_json::json!({ "path": "/tmp" }),
+            Duration::from_secs(5),
+        )
+    };
+
+    let result = call("a").await.unwrap();
+    assert!(result.spawned && !result.is_error);
+    assert_eq!(result.content[0]["text"], r#"{"path":"/tmp"}"#);
+    assert!(!host.pm.is_running("tools").await);
+
+    assert!(call("fail").await.unwrap().is_error);
+    let err = call("missing").await.unwrap_err();
+    assert!(err.to_string().contains("Method not found"));
+}
+
+#[tokio::test]
+async fn test_get_prompt_renders_messages() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let arguments = [("name".to_string(), "Ada".to_string())].into();
+    let prompt = probe::get_prompt(
+        host.clone(),
+        "prompts",
+        node_server(MCP_SERVER),
+        "greet",
+        arguments,
+        Duration::from_secs(5),
+    )
+    .await
+    .unwrap();
+    assert!(prompt.spawned);
+    assert_eq!(prompt.description.as_deref(), Some("Greeting"));
+    assert_eq!(prompt.messages[0]["content"]["text"], "Hello Ada");
+}
+
+#[tokio::test]
+async fn test_cancel_in_flight_request() {
+    if !node_available() {
+        return;
+    }
+
+    // Never answers `slow`
+    let script = r#"require('readline').createInterface({input:process.stdin}).on('line',l=>{const m=JSON.parse(l);if(m.method==='slow'||m.id===undefined)return;console.log(JSON.stringify({jsonrpc:'2.0',id:m.id,result:{}}))})"#;
+    let host = RecordingHost::new();
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "slow".to_string(),
+        node_server(script),
+    )
+    .await
+    .unwrap();
+
+    let pm = host.pm.clone();
+    let waiting = tokio::spawn(async move {
+        pm.send_request(
+            "slow",
+            serde_json::json!({ "jsonrpc": "2.0", "id": 9, "method": "slow" }),
+            Duration::from_secs(10),
+        )
+        .await
+    });
+    let id = serde_json::json!(9);
+    let mut cancelled = Err(SynapticError::ProcessError(String::new()));
+    for _ in 0..50 {
+        cancelled = host
+            .pm
+            .cancel_request("slow", &id, Some("user".to_string()))
+            .await;
+        if cancelled.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    cancelled.unwrap();
+
+    let response = waiting.await.unwrap().unwrap();
+    assert_eq!(
+        response["error"]["code"],
+        synaptic_core::process_manager::REQUEST_CANCELLED_CODE
+    );
+    assert!(host.wait_for("request-cancelled", 1).await);
+    // The inspector shows the cancellation
+    assert!(host
+        .state
+        .get_inspector_messages("slow")
+        .iter()
+        .any(|message| message.method.as_deref() == Some("notifications/cancelled")));
+    // No longer in flight
+    assert!(host.pm.cancel_request("slow", &id, None).await.is_err());
+
+    host.pm
+        .kill_process("slow", Duration::from_secs(1))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_sampling_request_answered_by_user_or_auto_denied() {
+    if !node_available() {
+        return;
+    }
+
+    // Answers `ask` with whatever its sampling request got back
+    let script = r#"let n=0;const asks={};require('readline').createInterface({input:process.stdin}).on('line',l=>{const m=JSON.parse(l);if(m.method==='ask'){const id='s'+(++n);asks[id]=m.id;console.log(JSON.stringify({jsonrpc:'2.0',id,method:'sampling/createMessage',params:{messages:[],maxTokens:10}}))}else if(asks[m.id]!==undefined){console.log(JSON.stringify({jsonrpc:'2.0',id:asks[m.id],result:m.result||{denied:m.error.code}}))}})"#;
+    let host = RecordingHost::new();
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "sampler".to_string(),
+        node_server(script),
+    )
+    .await
+    .unwrap();
+    let ask = || {
+        let pm = host.pm.clone();
+        tokio::spawn(async move {
+            pm.send_request(
+                "sampler",
+                serde_json::json!({ "jsonrpc": "2.0", "method": "ask" }),
+                Duration::from_secs(10),
+            )
+            .await
+        })
+    };
+
+    let answered = ask();
+    assert!(host.wait_for("sampling-request", 1).await);
+    let pending = host.pm.sampling.list(Some("sampler"));
+    assert_eq!(pending.len(), 1);
+    let reply = SamplingReply::Respond {
+        result: serde_json::json!({ "role": "assistant", "content": { "type": "text", "text": "hi" } }),
+    };
+    sampling::respond_to_sampling(&host.pm, "sampler", &pending[0].request_id, &reply)
+        .await
+        .unwrap();
+    let response = answered.await.unwrap().unwrap();
+    assert_eq!(response["result"]["content"]["text"], "hi");
+    assert!(host.pm.sampling.list(None).is_empty());
+
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 0,
+        sampling_auto_deny: true,
+        ..Default::default()
+    });
+    let response = ask().await.unwrap().unwrap();
+    assert_eq!(response["result"]["denied"], sampling::SAMPLING_DENIED_CODE);
+    assert!(host.wait_for("sampling-request", 2).await);
+
+    host.pm
+        .kill_process("sampler", Duration::from_secs(1))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_redaction_rules_apply_to_both_directions() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 0,
+        redaction_rules: vec![RedactionRule {
+            name: "tokens".to_string(),
+            pattern: "tok_[a-z]+".to_string(),
+            replacement: Some("tok_***".to_string()),
+            enabled: true,
+        }],
+        ..Default::default()
+    });
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "redact".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+
+    host.pm
+        .send_to_stdin(
+            "redact",
+            r#"{"jsonrpc":"2.0","id":1,"method":"tok_secret"}"#.to_string(),
+        )
+        .await
+        .unwrap();
+    assert!(host.wait_for_traffic(2).await);
+    for event in host.traffic() {
+        let content = event["content"].as_str().unwrap();
+        assert!(content.contains("tok_***") && !content.contains("tok_secret"));
+    }
+    // The stored message is the redacted one
+    let messages = host.state.get_inspector_messages("redact");
+    assert_eq!(messages[0].payload["result"]["echo"], "tok_***");
+
+    host.pm
+        .kill_process("redact", Duration::from_secs(1))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_resource_read_and_update_event() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "res".to_string(),
+        node_server(MCP_SERVER),
+    )
+    .await
+    .unwrap();
+    let timeout = Duration::from_secs(5);
+
+    let read = resources::read_resource(&host.pm, "res", "file:///a.txt", timeout)
+        .await
+        .unwrap();
+    assert_eq!(read.contents[0]["text"], "hello");
+
+    resources::subscribe_resource(&host.pm, "res", "file:///a.txt", timeout)
+        .await
+        .unwrap();
+    assert_eq!(
+        host.pm.resource_subscriptions.list("res"),
+        vec!["file:///a.txt"]
+    );
+    assert!(host.wait_for("resource-updated", 1).await);
+    let events = host.events.lock().unwrap().clone();
+    let (_, update) = events
+        .iter()
+        .find(|(name, _)| name == "resource-updated")
+        .unwrap();
+    assert_eq!(update["uri"], "file:///a.txt");
+    assert_eq!(update["subscribed"], true);
+
+    host.pm
+        .kill_process("res", Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert!(host.pm.resource_subscriptions.list("res").is_empty());
+}
+
+#[tokio::test]
+async fn test_breakpoints_hold_edit_and_drop_traffic() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+    let breakpoint = host.pm.interceptor.add_breakpoint(Breakpoint {
+        id: String::new(),
+        server_name: Some("echo".to_string()),
+        direction: Some(TrafficDirection::Outgoing),
+        method: Some("tools/*".to_string()),
+        enabled: true,
+    });
+
+    // Held until resolved, then the edited request goes out instead
+    let pm = host.pm.clone();
+    let request = tokio::spawn(async move {
+        pm.send_request(
+            "echo",
+            serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }),
+            Duration::from_secs(5),
+        )
+        .await
+    });
+    assert!(host.wait_for("traffic-intercepted", 1).await);
+    let held = host.pm.interceptor.list_held();
+    assert_eq!(held.len(), 1);
+    assert_eq!(held[0].method.as_deref(), Some("tools/list"));
+    host.pm
+        .interceptor
+        .resolve(
+            &held[0].id,
+            InterceptAction::Modify,
+            Some(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call" })),
+        )
+        .unwrap();
+    let response = request.await.unwrap().unwrap();
+    assert_eq!(response["result"]["echo"], "tools/call");
+
+    // Dropped requests never reach the server
+    let pm = host.pm.clone();
+    let request = tokio::spawn(async move {
+        pm.send_request(
+            "echo",
+            serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+            Duration::from_millis(500),
+        )
+        .await
+    });
+    assert!(host.wait_for("traffic-intercepted", 2).await);
+    let held = host.pm.interceptor.list_held();
+    host.pm
+        .interceptor
+        .resolve(&held[0].id, InterceptAction::Drop, None)
+        .unwrap();
+    assert!(request.await.unwrap().is_err());
+
+    // Other methods pass straight through
+    let response = host
+        .pm
+        .send_request(
+            "echo",
+            serde_json::json!({ "jsonrpc": "2.0", "id": 3, "method": "ping" }),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response["id"], 3);
+    assert!(host.pm.interceptor.remove_breakpoint(&breakpoint.id));
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn test_mock_rules_answer_instead_of_server() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+    host.pm.mocks.add_rule(MockRule {
+        id: String::new(),
+        server_name: None,
+        method: "tools/call".to_string(),
+        params: Some(serde_json::json!({ "name": "fetch_url" })),
+        result: Some(serde_json::json!({ "mocked": true })),
+        error: None,
+        delay_ms: 0,
+        enabled: true,
+    });
+    let timeout = Duration::from_secs(5);
+
+    let response = host
+        .pm
+        .send_request(
+            "echo",
+            serde_json::json!({ "id": 1, "method": "tools/call", "params": { "name": "fetch_url" } }),
+            timeout,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response["result"]["mocked"], true);
+
+    // Other tools still reach the server
+    let response = host
+        .pm
+        .send_request(
+            "echo",
+            serde_json::json!({ "id": 2, "method": "tools/call", "params": { "name": "search" } }),
+            timeout,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response["result"]["echo"], "tools/call");
+
+    // The mocked exchange is regular traffic too
+    assert!(host.wait_for_traffic(4).await);
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn test_tool_policies_deny_and_ask_for_approval() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost {
+        state: Arc::new(AppState::with_database(Database::open_in_memory().unwrap())),
+        ..RecordingHost::new()
+    };
+    let policy = |tool: &str, action| ToolPolicy {
+        server_name: Some("echo".to_string()),
+        tool: tool.to_string(),
+        action,
+        enabled: true,
+    };
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 0,
+        tool_policies: vec![
+            policy("delete_file", PolicyAction::Deny),
+            policy("*shell*", PolicyAction::RequireApproval),
+        ],
+        ..Default::default()
+    });
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+    let call = |id: u64, tool: &str| {
+        let pm = host.pm.clone();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": tool, "arguments": { "command": "ls" } },
+        });
+        tokio::spawn(async move {
+            pm.send_request("echo", request, Duration::from_secs(5))
+                .await
+        })
+    };
+
+    // Denied outright, without reaching the server
+    let response = call(1, "delete_file").await.unwrap().unwrap();
+    assert_eq!(response["error"]["code"], policy::POLICY_DENIED_CODE);
+
+    // Held until the user approves it
+    let approved = call(2, "run_shell");
+    assert!(host.wait_for("tool-approval-requested", 1).await);
+    let pending = host.pm.policies.list_pending();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].arguments["command"], "ls");
+    host.pm.policies.resolve(&pending[0].id, true).unwrap();
+    let response = approved.await.unwrap().unwrap();
+    assert_eq!(response["result"]["echo"], "tools/call");
+
+    // Or denies it; calls after a waiting one don't wait with it, and tools no
+    // policy matches pass without a decision
+    let refused = call(3, "run_shell");
+    assert!(host.wait_for("tool-approval-requested", 2).await);
+    let response = call(4, "search").await.unwrap().unwrap();
+    assert_eq!(response["result"]["echo"], "tools/call");
+    let pending = host.pm.policies.list_pending();
+    host.pm.policies.resolve(&pending[0].id, false).unwrap();
+    let response = refused.await.unwrap().unwrap();
+    assert_eq!(response["error"]["code"], policy::POLICY_DENIED_CODE);
+
+    let actions: Vec<AuditAction> =
+        audit::query(host.state.database().unwrap(), Some("echo"), 10, 0)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.action)
+            .collect();
+    assert_eq!(
+        actions,
+        vec![
+            AuditAction::ToolDenied,
+            AuditAction::ToolAllowed,
+            AuditAction::ToolDenied,
+        ]
+    );
+    assert_eq!(host.count("tool-policy-decision"), 3);
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn test_rate_limited_calls_get_an_error() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 0,
+        rate_limits: vec![RateLimit {
+            server_name: Some("echo".to_string()),
+            tool: Some("paid_*".to_string()),
+            calls_per_minute: 1,
+            enabled: true,
+        }],
+        ..Default::default()
+    });
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+    let call = |id: u64, tool: &str| {
+        host.pm.send_request(
+            "echo",
+            serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": "tools/call", "params": { "name": tool } }),
+            Duration::from_secs(5),
+        )
+    };
+
+    let response = call(1, "paid_search").await.unwrap();
+    assert_eq!(response["result"]["echo"], "tools/call");
+    let response = call(2, "paid_search").await.unwrap();
+    assert_eq!(response["error"]["code"], rate_limit::RATE_LIMITED_CODE);
+    assert!(response["error"]["data"]["retryAfterMs"].as_u64().unwrap() > 0);
+    assert_eq!(host.count("rate-limited"), 1);
+
+    // Unlimited tools still go through
+    let response = call(3, "free_search").await.unwrap();
+    assert_eq!(response["result"]["echo"], "tools/call");
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn test_chaos_delays_overlap_instead_of_queueing() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+    host.pm.chaos.set(
+        "echo",
+        Some(ChaosConfig {
+            delay_ms: 400,
+            ..Default::default()
+        }),
+    );
+    let request = |id: u64| {
+        host.pm.send_request(
+            "echo",
+            serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": "ping" }),
+            Duration::from_secs(5),
+        )
+    };
+
+    // Delayed one after another, three responses would take 1.2s
+    let started = std::time::Instant::now();
+    let (a, b, c) = tokio::join!(request(1), request(2), request(3));
+    assert!(a.is_ok() && b.is_ok() && c.is_ok());
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(400));
+    assert!(elapsed < Duration::from_millis(1000), "took {:?}", elapsed);
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn test_oversized_responses_follow_the_policy() {
+    if !node_available() {
+        return;
+    }
+
+    // Answers `big` with 5000 bytes of payload and anything else with a small result
+    let script = r#"require('readline').createInterface({input:process.stdin}).on('line',l=>{const m=JSON.parse(l);console.log(JSON.stringify({jsonrpc:'2.0',id:m.id,result:{blob:m.method==='big'?'A'.repeat(5000):'ok'}}))})"#;
+    let host = RecordingHost::new();
+    let request = |method: &str| {
+        host.pm.send_request(
+            "big",
+            serde_json::json!({ "jsonrpc": "2.0", "method": method }),
+            Duration::from_secs(5),
+        )
+    };
+
+    for policy in [OversizedPolicy::Reject, OversizedPolicy::PassThrough] {
+        host.set_settings(SynapticSettings {
+            startup_timeout_ms: 0,
+            max_message_bytes: 1000,
+            oversized_policy: policy,
+            ..Default::default()
+        });
+        synaptic_core::process_manager::spawn_mcp_server(
+            host.clone(),
+            &host.pm,
+            "big".to_string(),
+            node_server(script),
+        )
+        .await
+        .unwrap();
+
+        let response = request("big").await.unwrap();
+        match policy {
+            OversizedPolicy::Reject => {
+                assert_eq!(response["error"]["code"], oversized::MESSAGE_TOO_LARGE_CODE)
+            }
+            _ => assert_eq!(response["result"]["blob"].as_str().unwrap().len(), 5000),
+        }
+        let response = request("small").await.unwrap();
+        assert_eq!(response["result"]["blob"], "ok");
+
+        host.pm
+            .kill_process("big", Duration::from_secs(1))
+            .await
+            .unwrap();
+    }
+
+    // An oversized request of the server is answered with the error instead of hanging
+    let script = r#"let ask;require('readline').createInterface({input:process.stdin}).on('line',l=>{const m=JSON.parse(l);if(m.method==='ask'){ask=m.id;console.log(JSON.stringify({jsonrpc:'2.0',id:'s1',method:'sampling/createMessage',params:{blob:'A'.repeat(5000)}}))}else if(m.id==='s1'){console.log(JSON.stringify({jsonrpc:'2.0',id:ask,result:{code:m.error.code}}))}})"#;
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 0,
+        max_message_bytes: 1000,
+        oversized_policy: OversizedPolicy::Reject,
+        ..Default::default()
+    });
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "big".to_string(),
+        node_server(script),
+    )
+    .await
+    .unwrap();
+    let response = request("ask").await.unwrap();
+    assert_eq!(
+        response["result"]["code"],
+        oversized::MESSAGE_TOO_LARGE_CODE
+    );
+    host.pm
+        .kill_process("big", Duration::from_secs(1))
+        .await
+        .unwrap();
+
+    let stats = &host.pm.oversized.stats()["big"];
+    assert_eq!((stats.rejected, stats.passed_through), (2, 1));
+    assert!(stats.largest_bytes > 5000);
+    assert_eq!(host.count("message-oversized"), 3);
+    // The recorded copy is cut either way
+    assert!(host
+        .traffic()
+        .iter()
+        .filter_map(|event| event["content"].as_str())
+        .all(|content| content.len() < 1100));
+}
+
+#[tokio::test]
+async fn test_idle_servers_stop_and_wake_on_next_use() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+    let grace = Duration::from_secs(2);
+
+    // Synaptic's own pings are not traffic
+    let before = host.pm.get_status("echo").await.unwrap().last_traffic_at;
+    host.pm
+        .request("echo", "ping", None, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(
+        host.pm.get_status("echo").await.unwrap().last_traffic_at,
+        before
+    );
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(
+        idle::stop_idle_servers(&host, Duration::from_secs(60), grace)
+            .await
+            .is_empty()
+    );
+    let stopped = idle::stop_idle_servers(&host, Duration::from_millis(200), grace).await;
+    assert_eq!(stopped.len(), 1);
+    assert_eq!(host.count("server-idle-stopped"), 1);
+    assert!(!host.pm.is_running("echo").await);
+
+    // The next use starts it again from the same entry
+    assert!(idle::wake_server(&host, "echo").await.unwrap().is_some());
+    let response = host
+        .pm
+        .send_request(
+            "echo",
+            serde_json::json!({ "method": "tools/list" }),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response["result"]["echo"], "tools/list");
+    assert!(host.pm.get_status("echo").await.unwrap().last_traffic_at > before);
+    assert!(idle::wake_server(&host, "echo").await.unwrap().is_none());
+
+    host.pm.kill_all(grace).await;
+}
+
+#[tokio::test]
+async fn test_stop_waits_for_in_flight_requests() {
+    if !node_available() {
+        return;
+    }
+
+    // Answers every request after 500 ms
+    let slow_echo = r#"require('readline').createInterface({input:process.stdin}).on('line',l=>{const m=JSON.parse(l);setTimeout(()=>console.log(JSON.stringify({jsonrpc:'2.0',id:m.id,result:{}})),500)})"#;
+    let host = RecordingHost::new();
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "slow".to_string(),
+        node_server(slow_echo),
+    )
+    .await
+    .unwrap();
+
+    let request = tokio::spawn({
+        let host = host.clone();
+        async move {
+            host.pm
+                .send_request(
+                    "slow",
+                    serde_json::json!({ "id": 1, "method": "tools/call" }),
+                    Duration::from_secs(5),
+                )
+                .await
+        }
+    });
+    for _ in 0..100 {
+        if host.pm.in_flight_count("slow").await == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(host.pm.in_flight_count("slow").await, 1);
+
+    // The stop holds off until the response is in
+    host.pm
+        .drain_and_kill("slow", Duration::from_secs(5), Duration::from_secs(2))
+        .await
+        .unwrap();
+    assert!(request.await.unwrap().is_ok());
+    assert_eq!(host.pm.in_flight_count("slow").await, 0);
+
+    // Cancelled requests don't hold up a stop
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "slow".to_string(),
+        node_server(slow_echo),
+    )
+    .await
+    .unwrap();
+    for message in [
+        serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/call" }),
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/cancelled",
+            "params": { "requestId": 2 }
+        }),
+    ] {
+        host.pm
+            .send_to_stdin("slow", message.to_string())
+            .await
+            .unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(host.pm.in_flight_count("slow").await, 0);
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn test_path_prepend_goes_in_front_of_path() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let cwd = std::env::temp_dir().join(format!("synaptic-path-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(cwd.join("bin")).unwrap();
+    let server = McpServer {
+        cwd: Some(cwd.to_string_lossy().to_string()),
+        path_prepend: vec!["bin".to_string()],
+        ..node_server(
+            r#"require('readline').createInterface({input:process.stdin}).on('line',l=>{const m=JSON.parse(l);console.log(JSON.stringify({jsonrpc:'2.0',id:m.id,result:{path:process.env.PATH}}))})"#,
+        )
+    };
+
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "path".to_string(),
+        server,
+    )
+    .await
+    .unwrap();
+    let response = host
+        .pm
+        .send_request(
+            "path",
+            serde_json::json!({ "method": "env" }),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+
+    let path = response["result"]["path"].as_str().unwrap();
+    let first = std::env::split_paths(path).next().unwrap();
+    assert_eq!(first, cwd.join("bin"));
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+    let _ = std::fs::remove_dir_all(&cwd);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_low_priority_servers_run_niced() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let server = McpServer {
+        priority: ProcessPriority::Low,
+        ..node_server(
+            r#"require('readline').createInterface({input:process.stdin}).on('line',l=>{const m=JSON.parse(l);console.log(JSON.stringify({jsonrpc:'2.0',id:m.id,result:{nice:require('os').getPriority()}}))})"#,
+        )
+    };
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "niced".to_string(),
+        server,
+    )
+    .await
+    .unwrap();
+
+    let response = host
+        .pm
+        .send_request(
+            "niced",
+            serde_json::json!({ "method": "nice" }),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+    assert!(response["result"]["nice"].as_i64().unwrap() >= 10);
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn test_startup_waits_for_first_output() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 500,
+        ..Default::default()
+    });
+
+    // The echo server answers the readiness ping
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+    let status = host.pm.get_status("echo").await.unwrap();
+    assert_eq!(status.state, ProcessState::Running);
+    assert!(host.traffic().is_empty());
+
+    // A server that never writes to stdout is stopped again
+    let result = synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "silent".to_string(),
+        node_server("setInterval(() => {}, 1000)"),
+    )
+    .await;
+    assert!(matches!(result, Err(SynapticError::StartupTimeout(_))));
+    assert!(!host.pm.is_running("silent").await);
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn test_restart_respawns_from_config() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let grace = Duration::from_secs(2);
+    let first = synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+
+    let second = synaptic_core::process_manager::restart_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+        grace,
+    )
+    .await
+    .unwrap();
+    assert_ne!(first, second);
+    assert_eq!(host.pm.get_status("echo").await.unwrap().pid, Some(second));
+
+    // Running servers missing from the config are reported and left alone
+    let mut config = McpConfig::default();
+    config
+        .mcp_servers
+        .insert("echo".to_string(), node_server(ECHO_SERVER));
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "orphan".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+
+    let results =
+        synaptic_core::process_manager::restart_all_running(host.clone(), &host.pm, &config, grace)
+            .await;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].server_name, "echo");
+    assert!(results[0].pid.is_some_and(|pid| pid != second));
+    assert_eq!(results[1].server_name, "orphan");
+    assert!(results[1].error.is_some());
+    assert!(host.pm.is_running("orphan").await);
+
+    host.pm.kill_all(grace).await;
+}
+
+#[tokio::test]
+async fn test_config_change_restarts_or_flags_stale() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let grace = Duration::from_secs(2);
+    let first = synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+    let apply = |server: McpServer, auto_restart: bool| {
+        synaptic_core::process_manager::apply_config_change(
+            host.clone(),
+            &host.pm,
+            "echo".to_string(),
+            server,
+            auto_restart,
+            grace,
+        )
+    };
+
+    // Edits that don't touch the launch settings leave the process alone
+    let retry_only = McpServer {
+        restart: RestartPolicy {
+            max_retries: 9,
+            ..Default::default()
+        },
+        ..node_server(ECHO_SERVER)
+    };
+    assert_eq!(apply(retry_only, true).await.unwrap(), None);
+
+    let edited = McpServer {
+        env: [("DEBUG".to_string(), "1".to_string())].into(),
+        ..node_server(ECHO_SERVER)
+    };
+    assert_eq!(apply(edited.clone(), false).await.unwrap(), None);
+    assert_eq!(host.count("server-config-stale"), 1);
+    assert_eq!(host.pm.get_status("echo").await.unwrap().pid, Some(first));
+
+    let second = apply(edited.clone(), true).await.unwrap().unwrap();
+    assert_ne!(first, second);
+    assert_eq!(apply(edited, true).await.unwrap(), None);
+
+    host.pm.kill_all(grace).await;
+}
+
+#[tokio::test]
+async fn test_spawn_all_enabled_and_kill_all() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let mut config = McpConfig::default();
+    config
+        .mcp_servers
+        .insert("a".to_string(), node_server(ECHO_SERVER));
+    config
+        .mcp_servers
+        .insert("b".to_string(), node_server(ECHO_SERVER));
+    config.mcp_servers.insert(
+        "off".to_string(),
+        McpServer {
+            enabled: false,
+            ..node_server(ECHO_SERVER)
+        },
+    );
+    config.mcp_servers.insert(
+        "broken".to_string(),
+        McpServer {
+            command: "definitely-not-a-real-runtime-xyz".to_string(),
+            ..Default::default()
+        },
+    );
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 0,
+        allowed_executables: vec![
+            "node".to_string(),
+            "definitely-not-a-real-runtime-xyz".to_string(),
+        ],
+        ..Default::default()
+    });
+
+    let results =
+        synaptic_core::process_manager::spawn_all_enabled(host.clone(), &host.pm, &config).await;
+    assert_eq!(results.len(), 3);
+    assert_eq!(host.count("spawn-all-progress"), 3);
+    assert!(results.iter().all(|r| r.total == 3));
+    let broken = results.iter().find(|r| r.server_name == "broken").unwrap();
+    assert!(broken.pid.is_none() && broken.error.is_some());
+    assert!(host.pm.is_running("a").await && host.pm.is_running("b").await);
+    assert!(!host.pm.is_running("off").await);
+
+    // Already running servers are skipped
+    let again =
+        synaptic_core::process_manager::spawn_all_enabled(host.clone(), &host.pm, &config).await;
+    assert_eq!(again.len(), 1);
+
+    let stopped = host.pm.kill_all(Duration::from_secs(2)).await;
+    assert_eq!(stopped.len(), 2);
+    let mut completed: Vec<usize> = stopped.iter().map(|p| p.completed).collect();
+    completed.sort();
+    assert_eq!(completed, vec![1, 2]);
+    assert!(host.pm.list_running().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_concurrent_starts_are_queued() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let mut config = McpConfig::default();
+    for name in ["a", "b", "c"] {
+        config
+            .mcp_servers
+            .insert(name.to_string(), node_server(ECHO_SERVER));
+    }
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 5_000,
+        max_concurrent_starts: 1,
+        ..Default::default()
+    });
+
+    let results =
+        synaptic_core::process_manager::spawn_all_enabled(host.clone(), &host.pm, &config).await;
+    assert!(results.iter().all(|r| r.pid.is_some()));
+    assert_eq!(host.pm.start_queue.active(), 0);
+
+    let states: Vec<String> = host
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(name, _)| name == "process-state-changed")
+        .map(|(_, payload)| payload["state"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(states.iter().filter(|s| *s == "queued").count(), 2);
+    assert_eq!(states.iter().filter(|s| *s == "starting").count(), 3);
+    assert_eq!(states.iter().filter(|s| *s == "running").count(), 3);
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn test_abandoned_queued_start_clears_its_status() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    host.set_settings(SynapticSettings {
+        max_concurrent_starts: 1,
+        ..Default::default()
+    });
+    let slot = host.pm.start_queue.try_acquire(1).unwrap();
+
+    let start = synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    );
+    assert!(tokio::time::timeout(Duration::from_millis(200), start)
+        .await
+        .is_err());
+    assert!(host.pm.get_status("echo").await.is_none());
+
+    drop(slot);
+    assert_eq!(host.pm.start_queue.active(), 0);
+}
+
+#[tokio::test]
+async fn test_restart_on_failure_gives_up_after_max_retries() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let mut server = node_server("console.error('bye'); process.exit(3)");
+    server.restart = RestartPolicy {
+        mode: RestartMode::OnFailure,
+        max_retries: 2,
+        initial_backoff_ms: 10,
+        max_backoff_ms: 20,
+    };
+
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "flaky".to_string(),
+        server,
+    )
+    .await
+    .unwrap();
+
+    assert!(host.wait_for("process-stopped", 1).await);
+    assert_eq!(host.count("process-restarting"), 2);
+    assert!(!host.pm.is_running("flaky").await);
+
+    let status = host.pm.get_status("flaky").await.unwrap();
+    assert_eq!(status.state, ProcessState::Crashed);
+
+    // Stderr of every attempt stays available after the crash
+    let stderr = host.pm.get_recent_stderr("flaky", 2).await;
+    assert_eq!(stderr, vec!["bye", "bye"]);
+    assert_eq!(host.pm.get_recent_stderr("flaky", 10).await.len(), 3);
+    assert_eq!(status.restart_count, 2);
+    assert_eq!(status.last_exit_code, Some(3));
+
+    let events = host.events.lock().unwrap();
+    let (_, first) = events
+        .iter()
+        .find(|(name, _)| name == "process-restarting")
+        .unwrap();
+    assert_eq!(first["exitCode"], 3);
+    assert_eq!(first["attempt"], 1);
+
+    // Every failed exit is reported; only the last one isn't restarted
+    let crashes: Vec<&serde_json::Value> = events
+        .iter()
+        .filter(|(name, _)| name == "process-crashed")
+        .map(|(_, payload)| payload)
+        .collect();
+    assert_eq!(crashes.len(), 3);
+    assert_eq!(crashes[0]["exitCode"], 3);
+    assert_eq!(crashes[0]["stderrTail"], serde_json::json!(["bye"]));
+    assert_eq!(crashes[0]["willRestart"], true);
+    assert_eq!(crashes[2]["willRestart"], false);
+    // Each report only has the stderr of its own run
+    assert_eq!(crashes[2]["stderrTail"], serde_json::json!(["bye"]));
+}
+
+#[tokio::test]
+async fn test_shutdown_escalates_to_terminate_and_kill() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let grace = Duration::from_millis(300);
+
+    // Keeps running with stdin closed, but honours SIGTERM
+    let stubborn = "setInterval(()=>{},1000)";
+    // Ignores SIGTERM as well
+    let deaf =
+        "process.on('SIGTERM',()=>{});process.on('SIGBREAK',()=>{});setInterval(()=>{},1000)";
+
+    for (name, script) in [("stubborn", stubborn), ("deaf", deaf)] {
+        synaptic_core::process_manager::spawn_mcp_server(
+            host.clone(),
+            &host.pm,
+            name.to_string(),
+            node_server(script),
+        )
+        .await
+        .unwrap();
+    }
+
+    // Give node time to install its signal handlers
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    assert_eq!(
+        host.pm.kill_process("stubborn", grace).await.unwrap(),
+        ShutdownStage::Terminated
+    );
+    assert_eq!(
+        host.pm.kill_process("deaf", grace).await.unwrap(),
+        ShutdownStage::Killed
+    );
+    assert!(host.wait_for("process-stopped", 2).await);
+}
+
+/// Whether a pid belongs to a live (non-zombie) process
+#[cfg(target_os = "linux")]
+fn process_alive(pid: u64) -> bool {
+    std::fs::read_to_string(format!("/proc/{}/stat", pid))
+        .map(|stat| {
+            // State follows the parenthesised command name
+            let state = stat.rsplit(')').next().unwrap_or("").trim_start();
+            !state.starts_with('Z') && !state.starts_with('X')
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_kill_stops_grandchildren() {
+    if !node_available() {
+        return;
+    }
+
+    // Launcher that reports the pid of a long-lived grandchild, like npx does
+    let launcher = "const c=require('child_process').spawn(process.execPath,['-e','setInterval(()=>{},1000)'],{stdio:'ignore'});console.log(JSON.stringify({jsonrpc:'2.0',method:'pid',params:{pid:c.pid}}));setInterval(()=>{},1000)";
+
+    let host = RecordingHost::new();
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "tree".to_string(),
+        node_server(launcher),
+    )
+    .await
+    .unwrap();
+
+    assert!(host.wait_for_traffic(1).await);
+    let messages = host.state.get_inspector_messages("tree");
+    let grandchild = messages[0].payload["params"]["pid"].as_u64().unwrap();
+    assert!(process_alive(grandchild));
+
+    host.pm
+        .kill_process("tree", Duration::from_millis(300))
+        .await
+        .unwrap();
+
+    let mut alive = true;
+    for _ in 0..20 {
+        alive = process_alive(grandchild);
+        if !alive {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert!(!alive, "grandchild {} survived", grandchild);
+}
+
+#[tokio::test]
+async fn test_health_checks_ping_and_flag_unresponsive() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    for (name, script) in [
+        ("echo", ECHO_SERVER),
+        ("silent", "setInterval(()=>{},1000)"),
+    ] {
+        synaptic_core::process_manager::spawn_mcp_server(
+            host.clone(),
+            &host.pm,
+            name.to_string(),
+            node_server(script),
+        )
+        .await
+        .unwrap();
+    }
+
+    let timeout = Duration::from_millis(300);
+    let result = host
+        .pm
+        .request("echo", "ping", None, timeout)
+        .await
+        .unwrap();
+    assert_eq!(result["echo"], "ping");
+
+    let mut flipped = Vec::new();
+    for _ in 0..health::UNHEALTHY_AFTER_FAILURES {
+        flipped.extend(health::run_health_checks(&host.pm, timeout).await);
+    }
+
+    assert_eq!(flipped.len(), 1);
+    assert_eq!(flipped[0].server_name, "silent");
+    assert!(!flipped[0].healthy);
+
+    let echo = host.pm.get_health("echo").await.unwrap();
+    assert!(echo.healthy);
+    assert!(echo.latency_ms.is_some());
+
+    // Internal pings never reach the inspector
+    assert!(host.state.get_inspector_messages("echo").is_empty());
+
+    host.pm.kill_all(Duration::from_millis(300)).await;
+}
+
+/// Requests a fake Streamable HTTP server received: method and session header
+type HttpLog = Arc<Mutex<Vec<(String, Option<String>)>>>;
+
+/// Minimal Streamable HTTP endpoint echoing each request's method as its result
+async fn fake_http_server() -> (String, HttpLog) {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+    let log: HttpLog = Arc::default();
+    let requests = log.clone();
+
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            let requests = requests.clone();
+            tokio::spawn(async move {
+                let mut socket = BufReader::new(socket);
+                loop {
+                    let mut request_line = String::new();
+                    if socket.read_line(&mut request_line).await.unwrap_or(0) == 0 {
+                        return;
+                    }
+                    let method = request_line.split(' ').next().unwrap_or("").to_string();
+                    let (mut length, mut session) = (0, None);
+                    loop {
+                        let mut header = String::new();
+                        socket.read_line(&mut header).await.unwrap();
+                        let header = header.trim_end();
+                        if header.is_empty() {
+                            break;
+                        }
+                        let (name, value) = header.split_once(": ").unwrap();
+                        match name.to_ascii_lowercase().as_str() {
+                            "content-length" => length = value.parse().unwrap(),
+                            "mcp-session-id" => session = Some(value.to_string()),
+                            _ => {}
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    socket.read_exact(&mut body).await.unwrap();
+                    requests.lock().unwrap().push((method.clone(), session));
+
+                    let response = match method.as_str() {
+                        "POST" => {
+                            let message: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                            let reply = serde_json::json!({
+                                "jsonrpc": "2.0",
+                                "id": message["id"],
+                                "result": { "echo": message["method"] },
+                            })
+                            .to_string();
+                            format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nMcp-Session-Id: s-1\r\nContent-Length: {}\r\n\r\n{}", reply.len(), reply)
+                        }
+                        "GET" => "HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\n\r\n".to_string(),
+                        _ => "HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n".to_string(),
+                    };
+                    if socket.get_mut().write_all(response.as_bytes()).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    (url, log)
+}
+
+#[tokio::test]
+async fn test_remote_http_server_is_inspected_like_stdio() {
+    let (url, requests) = fake_http_server().await;
+    let host = RecordingHost::new();
+    let server = McpServer {
+        url: Some(url),
+        transport: Transport::Http,
+        ..Default::default()
+    };
+
+    let id = synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "remote".to_string(),
+        server,
+    )
+    .await
+    .unwrap();
+    assert!(host.pm.is_running("remote").await);
+    assert_eq!(host.pm.get_status("remote").await.unwrap().pid, Some(id));
+
+    for (id, method) in [(1, "initialize"), (2, "tools/list")] {
+        let message = serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method });
+        host.pm
+            .send_to_stdin("remote", message.to_string())
+            .await
+            .unwrap();
+        assert!(host.wait_for_traffic(id * 2).await);
+    }
+
+    // Responses are captured as incoming traffic, like a process's stdout
+    let messages = host.state.get_inspector_messages("remote");
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1].payload["result"]["echo"], "tools/list");
+
+    // Closing ends the session the server handed out
+    let stage = host
+        .pm
+        .kill_process("remote", Duration::from_secs(2))
+        .await
+        .unwrap();
+    assert_eq!(stage, ShutdownStage::StdinClosed);
+    assert!(host.wait_for("process-stopped", 1).await);
+
+    let requests = requests.lock().unwrap().clone();
+    let session = |method: &str| {
+        requests
+            .iter()
+            .filter(|(m, _)| m == method)
+            .map(|(_, session)| session.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(session("POST"), [None, Some("s-1".to_string())]);
+    assert_eq!(session("DELETE"), [Some("s-1".to_string())]);
+}
+
+#[tokio::test]
+async fn test_gateway_namespaces_and_routes_tools() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost::new();
+    let mut config = McpConfig::default();
+    config
+        .mcp_servers
+        .insert("probe".to_string(), node_server(MCP_SERVER));
+    *host.state.config_cache.lock().unwrap() = Some(config);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!(
+        "http://{}{}",
+        listener.local_addr().unwrap(),
+        gateway::GATEWAY_PATH
+    );
+    tokio::spawn(gateway::serve(
+        Arc::new(gateway::Gateway::new(host.clone(), "s3cret".to_string())),
+        listener,
+    ));
+
+    // Without the token, or from a web page, nothing gets through
+    let client = reqwest::Client::new();
+    let initialize = serde_json::json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize" });
+    let anonymous = client.post(&url).json(&initialize).send().await.unwrap();
+    assert_eq!(anonymous.status().as_u16(), 401);
+    let from_page = client
+        .post(&url)
+        .bearer_auth("s3cret")
+        .header("origin", "https://evil.example")
+        .json(&initialize)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(from_page.status().as_u16(), 403);
+
+    let post = |body: serde_json::Value| {
+        let url = url.clone();
+        async move {
+            let response = reqwest::Client::new()
+                .post(url)
+                .bearer_auth("s3cret")
+                .json(&body)
+                .send()
+                .await
+                .unwrap();
+            (response.status().as_u16(), response.text().await.unwrap())
+        }
+    };
+
+    // Listing starts the server; every page of its tools comes back namespaced
+    let (_, listed) = post(serde_json::json!([
+        { "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "protocolVersion": "2025-03-26" } },
+        { "jsonrpc": "2.0", "id": 2, "method": "tools/list" },
+    ]))
+    .await;
+    let listed: serde_json::Value = serde_json::from_str(&listed).unwrap();
+    assert_eq!(listed[0]["result"]["protocolVersion"], "2025-03-26");
+    let names: Vec<_> = listed[1]["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(names, ["probe.a", "probe.b"]);
+    assert!(host.pm.is_running("probe").await);
+
+    // Calls reach the server under its own tool name and answer under the client's id
+    let (_, called) = post(serde_json::json!({
+        "jsonrpc": "2.0", "id": "c1", "method": "tools/call",
+        "params": { "name": "probe.a", "arguments": { "x": 1 } },
+    }))
+    .await;
+    let called: serde_json::Value = serde_json::from_str(&called).unwrap();
+    assert_eq!(called["id"], "c1");
+    assert_eq!(called["result"]["content"][0]["text"], r#"{"x":1}"#);
+    assert!(host.wait_for_traffic(2).await);
+    assert!(host.traffic().iter().any(|event| event["direction"] == "OUTGOING"
+        && event["content"].as_str().unwrap().contains(r#""name":"a""#)));
+
+    let (_, unknown) = post(serde_json::json!({
+        "jsonrpc": "2.0", "id": 3, "method": "tools/call", "params": { "name": "other.a" },
+    }))
+    .await;
+    assert!(unknown.contains("Unknown name"));
+
+    // Notifications are accepted without a body
+    let (status, _) =
+        post(serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await;
+    assert_eq!(status, 202);
+
+    host.pm.kill_all(Duration::from_secs(2)).await;
+}
+
+#[tokio::test]
+async fn test_history_retention_clears_and_skips_mirroring() {
+    if !node_available() {
+        return;
+    }
+
+    let host = RecordingHost {
+        state: Arc::new(AppState::with_database(Database::open_in_memory().unwrap())),
+        ..RecordingHost::new()
+    };
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 0,
+        history_retention: vec![HistoryRetention {
+            clear_on_disconnect: true,
+            mirror: HistoryMirror::Off,
+            ..HistoryRetention::new("scratch")
+        }],
+        ..Default::default()
+    });
+    let (sink, records) = system_logs::channel();
+    host.pm.log_sink.set(sink).unwrap();
+    tokio::spawn(system_logs::run_log_writer(host.clone(), records));
+
+    for name in ["kept", "scratch"] {
+        synaptic_core::process_manager::spawn_mcp_server(
+            host.clone(),
+            &host.pm,
+            name.to_string(),
+            node_server(ECHO_SERVER),
+        )
+        .await
+        .unwrap();
+        host.pm
+            .send_to_stdin(
+                name,
+                r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#.to_string(),
+            )
+            .await
+            .unwrap();
+    }
+    assert!(host.wait_for_traffic(4).await);
+    host.pm.kill_all(Duration::from_secs(2)).await;
+    assert!(host.wait_for("process-stopped", 2).await);
+
+    // Only the default server keeps its history past the exit
+    assert_eq!(host.state.get_inspector_messages("kept").len(), 1);
+    assert!(host.state.get_inspector_messages("scratch").is_empty());
+
+    // and only its traffic reaches the database
+    let logged = |server: &str| -> i64 {
+        host.state
+            .database()
+            .unwrap()
+            .conn()
+            .query_row(
+                "SELECT COUNT(*) FROM system_logs WHERE server_name = ?1",
+                [server],
+                |row| row.get(0),
+            )
+            .unwrap()
+    };
+    for _ in 0..50 {
+        if logged("kept") == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(logged("kept"), 2);
+    assert_eq!(logged("scratch"), 0);
+}
+
+#[tokio::test]
+async fn test_traffic_is_mirrored_to_a_live_file() {
+    if !node_available() {
+        return;
+    }
+
+    let path = std::env::temp_dir()
+        .join(format!("synaptic-mirror-{}", uuid::Uuid::new_v4()))
+        .join("echo.ndjson");
+    let host = RecordingHost::new();
+    host.set_settings(SynapticSettings {
+        startup_timeout_ms: 0,
+        traffic_mirrors: vec![TrafficMirror {
+            server_name: "echo".to_string(),
+            path: path.clone(),
+            enabled: true,
+        }],
+        ..Default::default()
+    });
+
+    synaptic_core::process_manager::spawn_mcp_server(
+        host.clone(),
+        &host.pm,
+        "echo".to_string(),
+        node_server(ECHO_SERVER),
+    )
+    .await
+    .unwrap();
+    host.pm
+        .send_to_stdin(
+            "echo",
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#.to_string(),
+        )
+        .await
+        .unwrap();
+    assert!(host.wait_for_traffic(2).await);
+    host.pm.kill_all(Duration::from_secs(2)).await;
+
+    // Batches are written as they are emitted, one JSON object per event
+    let lines: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["direction"], "OUTGOING");
+    assert_eq!(lines[0]["message"]["method"], "tools/list");
+    assert_eq!(lines[1]["message"]["result"]["echo"], "tools/list");
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn test_git_installs_clone_and_build_with_streamed_progress() {
+    let root = std::env::temp_dir().join(format!("synaptic-git-{}", uuid::Uuid::new_v4()));
+    let source = root.join("source");
+    std::fs::create_dir_all(&source).unwrap();
+    std::fs::write(source.join("server.js"), "// server").unwrap();
+    for args in [
+        vec!["init", "-q"],
+        vec!["add", "."],
+        vec!["commit", "-qm", "init"],
+        vec!["tag", "v1"],
+    ] {
+        let status = std::process::Command::new("git")
+            .args(&args)
+            .current_dir(&source)
+            .env("GIT_AUTHOR_NAME", "synaptic")
+            .env("GIT_AUTHOR_EMAIL", "synaptic@localhost")
+            .env("GIT_COMMITTER_NAME", "synaptic")
+            .env("GIT_COMMITTER_EMAIL", "synaptic@localhost")
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+    let url = format!("file://{}", source.display());
+
+    let host = RecordingHost::new();
+    let checkout = root.join("servers").join("demo");
+    installer::fetch_checkout(
+        &installer::Reporter::new(&host, "job-1", "demo"),
+        &url,
+        Some("echo built > out.txt && echo build finished"),
+        Some("v1"),
+        &checkout,
+    )
+    .await
+    .unwrap();
+    assert!(checkout.join("server.js").exists());
+    assert!(checkout.join("out.txt").exists());
+    let progress: Vec<(String, String)> = host
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(name, _)| name == "install-progress")
+        .map(|(_, p)| {
+            (
+                p["stage"].as_str().unwrap().to_string(),
+                p["message"].as_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(progress[0].0, "cloning");
+    assert!(progress.contains(&("building".to_string(), "build finished".to_string())));
+    assert!(host
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|(_, p)| p["jobId"] == "job-1"));
+
+    // A failing build reports its last output line
+    let failed = installer::fetch_checkout(
+        &installer::Reporter::new(&host, "job-2", "broken"),
+        &url,
+        Some("echo missing toolchain >&2; exit 3"),
+        None,
+        &root.join("servers").join("broken"),
+    )
+    .await
+    .unwrap_err();
+    let SynapticError::ProcessError(message) = failed else {
+        panic!("expected a process error, got {:?}", failed);
+    };
+    assert!(message.contains("missing toolchain"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -1,11 +1,74 @@
 //! Tauri IPC command handlers
+//!
+//! Thin adapters over `synaptic_core`: extract managed state, delegate, return.
 
-use crate::config::{self, BackupInfo, McpConfig, McpServer};
-use crate::error::SynapticError;
-use crate::inspector::{InspectorMessage, InspectorSession};
-use crate::registry::{self, RegistryServer, RuntimeStatus};
-use crate::state::AppState;
-use tauri::State;
+use crate::host::TauriHost;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use synaptic_core::audit::{self, AuditEntry};
+use synaptic_core::auth::{self, AuthStatus};
+use synaptic_core::backup_archive::{self, BackupExport};
+use synaptic_core::bookmarks::{self, Bookmark};
+use synaptic_core::capabilities::{self, ServerCapabilities};
+use synaptic_core::chaos::ChaosConfig;
+use synaptic_core::config::{self, BackupInfo, McpConfig, McpServer};
+use synaptic_core::conformance::{self, ConformanceReport};
+use synaptic_core::crashes::{self, CrashReport};
+use synaptic_core::database::DATABASE_FILE;
+use synaptic_core::docker;
+use synaptic_core::error::SynapticError;
+use synaptic_core::error_summary::{self, ErrorGroup};
+use synaptic_core::fuzz::{self, FuzzOptions, FuzzReport};
+use synaptic_core::gateway;
+use synaptic_core::health::HealthStatus;
+use synaptic_core::history_retention::{self, HistoryRetention};
+use synaptic_core::host::EventEmitter;
+use synaptic_core::idle;
+use synaptic_core::inspector::{InspectorMessage, InspectorSession, MessageFilter};
+use synaptic_core::installer::{self, InstallOutcome, UninstallReport};
+use synaptic_core::intercept::{Breakpoint, InterceptAction, InterceptedMessage};
+use synaptic_core::live_stats::LiveStats;
+use synaptic_core::load_test::{self, LoadTestPlan, LoadTestResult};
+use synaptic_core::message_diff::{self, MessageDiff};
+use synaptic_core::metrics::ProcessMetrics;
+use synaptic_core::mock_server;
+use synaptic_core::mocks::MockRule;
+use synaptic_core::orphans::{self, OrphanAction, OrphanProcess};
+use synaptic_core::oversized::OversizedStats;
+use synaptic_core::packages::{self, PackageVersions};
+use synaptic_core::paths::{self, PathValidation};
+use synaptic_core::policy::ToolApproval;
+use synaptic_core::preflight::{self, PreflightReport};
+use synaptic_core::probe::{self, PromptResult, ServerCatalog, ToolCallResult};
+use synaptic_core::process_manager::{
+    self, ProcessManager, ProcessStatus, RestartResult, ShutdownStage, SpawnProgress, StopProgress,
+};
+use synaptic_core::progress::ProgressUpdate;
+use synaptic_core::redaction::{self, RedactionRule};
+use synaptic_core::registry::{
+    self, RegistryQuery, RegistrySearchResult, RegistryServer, RegistryServerDetails,
+    RegistrySnapshot, RuntimeStatus,
+};
+use synaptic_core::request_templates::{self, RequestTemplate};
+use synaptic_core::resend::{self, PatchOp, ResendResult};
+use synaptic_core::resources::{self, ResourceContents};
+use synaptic_core::runtimes;
+use synaptic_core::sampling::{self, SamplingReply, SamplingRequest};
+use synaptic_core::secrets::{self, SecretFinding};
+use synaptic_core::server_compare::{self, ComparisonSource, ServerComparison};
+use synaptic_core::server_logs::{self, LogFileInfo};
+use synaptic_core::session_archive::{self, SessionExport, SessionFormat};
+use synaptic_core::session_report;
+use synaptic_core::settings::SynapticSettings;
+use synaptic_core::shim;
+use synaptic_core::snippet::{self, ServerSnippet};
+use synaptic_core::state::{AppState, InspectorSessionState};
+use synaptic_core::system_logs::{self, LogEntry, LogQuery, SessionInfo, TimelineBucket};
+use synaptic_core::timeouts::RequestTimeout;
+use synaptic_core::tool_stats::{self, TimeRange, ToolStats};
+use synaptic_core::updates::{self, ServerUpdate};
+use synaptic_core::validation::ProtocolViolation;
+use tauri::{Manager, State};
 
 // ============================================
 // CONFIG MANAGER COMMANDS
@@ -30,17 +93,27 @@ pub async fn write_config(
     config: McpConfig,
     state: State<'_, AppState>,
 ) -> Result<(), SynapticError> {
-    state.set_config(config)
+    state.write_config(config)
 }
 
 /// Add a new MCP server to the configuration
+///
+/// Path-like args are normalized before saving; missing paths come back as warnings.
 #[tauri::command]
 pub async fn add_server(
     name: String,
     server: McpServer,
     state: State<'_, AppState>,
-) -> Result<(), SynapticError> {
-    state.add_server(name, server)
+) -> Result<PathValidation, SynapticError> {
+    let validation = paths::validate_server_paths(&server);
+    state.add_server(name, validation.server.clone())?;
+    Ok(validation)
+}
+
+/// Parse a pasted server definition (JSON fragment or `claude mcp add` line)
+#[tauri::command]
+pub async fn parse_server_snippet(text: String) -> Result<ServerSnippet, SynapticError> {
+    snippet::parse_server_snippet(&text)
 }
 
 /// Remove an MCP server from the configuration
@@ -50,13 +123,38 @@ pub async fn remove_server(name: String, state: State<'_, AppState>) -> Result<(
 }
 
 /// Update an existing MCP server configuration
+///
+/// Path-like args are normalized before saving; missing paths come back as warnings.
+/// A running server whose launch settings changed is restarted or flagged as
+/// stale, depending on `restartOnConfigChange`. The edit is saved either way, so
+/// a failed restart comes back as a warning too.
 #[tauri::command]
 pub async fn update_server(
     name: String,
     server: McpServer,
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), SynapticError> {
-    state.update_server(&name, server)
+    pm: State<'_, ProcessManager>,
+) -> Result<PathValidation, SynapticError> {
+    let mut validation = paths::validate_server_paths(&server);
+    state.update_server(&name, validation.server.clone())?;
+
+    let settings = state.get_settings()?;
+    if let Err(e) = process_manager::apply_config_change(
+        TauriHost(app),
+        pm.inner(),
+        name.clone(),
+        validation.server.clone(),
+        settings.restart_on_config_change,
+        settings.shutdown_grace(),
+    )
+    .await
+    {
+        validation
+            .warnings
+            .push(format!("Saved, but {} could not be restarted: {}", name, e));
+    }
+    Ok(validation)
 }
 
 /// Toggle server enabled/disabled state
@@ -69,6 +167,130 @@ pub async fn toggle_server(
     state.toggle_server(&name, enabled)
 }
 
+/// Enable or disable a server for one target client only
+#[tauri::command]
+pub async fn toggle_server_for_target(
+    name: String,
+    target: String,
+    enabled: bool,
+    state: State<'_, AppState>,
+) -> Result<(), SynapticError> {
+    state.toggle_server_for_target(&name, &target, enabled)
+}
+
+/// Get a server's enable state for each target client
+#[tauri::command]
+pub async fn get_server_targets(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<BTreeMap<String, bool>, SynapticError> {
+    state.get_server_targets(&name)
+}
+
+/// Route a server's config entry through `synaptic-shim`, capturing the client's own traffic
+///
+/// Takes effect the next time the client (re)starts the server.
+#[tauri::command]
+pub async fn enable_client_capture(
+    name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<McpServer, SynapticError> {
+    let config = state.get_config()?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .ok_or_else(|| SynapticError::ServerNotFound(name.clone()))?;
+    let db_path = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| SynapticError::IoError(e.to_string()))?
+        .join(DATABASE_FILE);
+
+    let captured = shim::enable_capture(&name, server, &shim::shim_path()?, &db_path)?;
+    state.update_server(&name, captured.clone())?;
+    Ok(captured)
+}
+
+/// Restore a server's original command after `enable_client_capture`
+#[tauri::command]
+pub async fn disable_client_capture(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<McpServer, SynapticError> {
+    let config = state.get_config()?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .ok_or_else(|| SynapticError::ServerNotFound(name.clone()))?;
+
+    let restored = shim::disable_capture(&name, server)?;
+    state.update_server(&name, restored.clone())?;
+    Ok(restored)
+}
+
+/// Look up a server's config entry
+fn configured_server(state: &AppState, name: &str) -> Result<McpServer, SynapticError> {
+    state
+        .get_config()?
+        .mcp_servers
+        .get(name)
+        .cloned()
+        .ok_or_else(|| SynapticError::ServerNotFound(name.to_string()))
+}
+
+/// Sign in to a remote server with OAuth in the system browser
+#[tauri::command]
+pub async fn authorize_server(
+    name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AuthStatus, SynapticError> {
+    use tauri_plugin_shell::ShellExt;
+
+    let server = configured_server(&state, &name)?;
+    #[allow(deprecated)]
+    let open_browser = |url: &str| {
+        app.shell()
+            .open(url, None)
+            .map_err(|e| SynapticError::AuthError(format!("Failed to open the browser: {}", e)))
+    };
+    auth::authorize(&name, &server, open_browser).await
+}
+
+/// Whether a remote server has been signed in to
+#[tauri::command]
+pub async fn get_auth_status(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<AuthStatus, SynapticError> {
+    let server = configured_server(&state, &name)?;
+    Ok(auth::status(&name, &server))
+}
+
+/// Forget a remote server's OAuth tokens
+#[tauri::command]
+pub async fn sign_out_server(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<AuthStatus, SynapticError> {
+    let server = configured_server(&state, &name)?;
+    auth::sign_out(&server)?;
+    Ok(auth::status(&name, &server))
+}
+
+/// Config entry that connects a client to the gateway instead of to each server
+#[tauri::command]
+pub async fn get_gateway_entry(state: State<'_, AppState>) -> Result<McpServer, SynapticError> {
+    gateway::client_entry(state.get_settings()?.gateway_port)
+}
+
+/// Config entry running the built-in mock server with a tool/resource definition file
+#[tauri::command]
+pub async fn get_mock_server_entry(definition_path: String) -> Result<McpServer, SynapticError> {
+    mock_server::client_entry(std::path::Path::new(&definition_path))
+}
+
 /// List all configuration backups
 #[tauri::command]
 pub async fn list_backups() -> Result<Vec<BackupInfo>, SynapticError> {
@@ -81,12 +303,205 @@ pub async fn restore_backup(
     backup_id: String,
     state: State<'_, AppState>,
 ) -> Result<(), SynapticError> {
-    config::restore_from_backup(&backup_id)?;
-    // Invalidate cache to force re-read
-    state.invalidate_cache();
+    state.restore_backup(&backup_id)
+}
+
+/// Zip all backups, the current config and a manifest into `dest_path`
+#[tauri::command]
+pub async fn export_backups(dest_path: String) -> Result<BackupExport, SynapticError> {
+    backup_archive::export_backups(std::path::Path::new(&dest_path))
+}
+
+/// Get config change history, newest first
+#[tauri::command]
+pub async fn get_audit_log(
+    server_name: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<AuditEntry>, SynapticError> {
+    audit::query(
+        state.database()?,
+        server_name.as_deref(),
+        limit.unwrap_or(100),
+        offset.unwrap_or(0),
+    )
+}
+
+/// Browse persisted traffic history, newest first
+#[tauri::command]
+pub async fn query_logs(
+    query: LogQuery,
+    state: State<'_, AppState>,
+) -> Result<Vec<LogEntry>, SynapticError> {
+    system_logs::query(state.database()?, &query)
+}
+
+/// Write a logged session to `path` as JSONL or a HAR-like document
+#[tauri::command]
+pub async fn export_session(
+    session_id: String,
+    format: SessionFormat,
+    path: String,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<SessionExport, SynapticError> {
+    session_archive::export_session(
+        state.database()?,
+        &session_id,
+        format,
+        std::path::Path::new(&path),
+        &pm.redactor(),
+    )
+}
+
+/// Render a logged session as a Markdown report for a bug report
+#[tauri::command]
+pub async fn export_session_report(
+    session_id: String,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<String, SynapticError> {
+    session_report::export_session_report(state.database()?, &session_id, &pm.redactor())
+}
+
+/// Load an exported session file as a new read-only session
+#[tauri::command]
+pub async fn import_session(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<SessionInfo, SynapticError> {
+    session_archive::import_session(state.database()?, std::path::Path::new(&path))
+}
+
+/// Full-text search over persisted traffic, best matches first
+#[tauri::command]
+pub async fn search_messages(
+    query: String,
+    filters: Option<LogQuery>,
+    state: State<'_, AppState>,
+) -> Result<Vec<LogEntry>, SynapticError> {
+    system_logs::search(state.database()?, &query, &filters.unwrap_or_default())
+}
+
+/// Scan env values and args for plaintext credentials
+#[tauri::command]
+pub async fn scan_config_for_secrets(
+    state: State<'_, AppState>,
+) -> Result<Vec<SecretFinding>, SynapticError> {
+    let config = state.get_config()?;
+    Ok(secrets::scan_config(&config))
+}
+
+/// Move a server's plaintext env value into the keychain, leaving a `keychain:` reference
+#[tauri::command]
+pub async fn migrate_secret(
+    server: String,
+    key: String,
+    state: State<'_, AppState>,
+) -> Result<String, SynapticError> {
+    secrets::migrate_secret(&state, &server, &key)
+}
+
+// ============================================
+// SETTINGS COMMANDS
+// ============================================
+
+/// Get Synaptic's backend settings
+#[tauri::command]
+pub async fn get_settings(state: State<'_, AppState>) -> Result<SynapticSettings, SynapticError> {
+    state.get_settings()
+}
+
+/// Persist Synaptic's backend settings
+#[tauri::command]
+pub async fn update_settings(
+    settings: SynapticSettings,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<(), SynapticError> {
+    let (rules, mode) = (settings.redaction_rules.clone(), settings.redaction_mode);
+    state.update_settings(settings)?;
+    // Running servers' traffic is redacted with the new rules right away
+    pm.set_redaction_rules(rules, mode);
     Ok(())
 }
 
+/// Ready-made redaction rules (bearer tokens, API keys, emails)
+#[tauri::command]
+pub async fn get_redaction_presets() -> Result<Vec<RedactionRule>, SynapticError> {
+    Ok(redaction::presets())
+}
+
+/// Approve or deny an executable after an `executable-approval-requested` event
+#[tauri::command]
+pub async fn resolve_executable_approval(
+    executable: String,
+    approved: bool,
+    state: State<'_, AppState>,
+) -> Result<SynapticSettings, SynapticError> {
+    state.set_executable_approval(&executable, approved)
+}
+
+/// Add an executable to the spawn whitelist
+#[tauri::command]
+pub async fn add_allowed_executable(
+    executable: String,
+    state: State<'_, AppState>,
+) -> Result<SynapticSettings, SynapticError> {
+    state.set_executable_approval(&executable, true)
+}
+
+/// Remove an executable from the spawn whitelist
+#[tauri::command]
+pub async fn remove_allowed_executable(
+    executable: String,
+    state: State<'_, AppState>,
+) -> Result<SynapticSettings, SynapticError> {
+    state.remove_allowed_executable(&executable)
+}
+
+/// History retention of a server (the default when none was set)
+#[tauri::command]
+pub async fn get_history_retention(
+    server_name: String,
+    state: State<'_, AppState>,
+) -> Result<HistoryRetention, SynapticError> {
+    let settings = state.get_settings()?;
+    Ok(history_retention::for_server(
+        &settings.history_retention,
+        &server_name,
+    ))
+}
+
+/// Set whether a server's inspector history survives restarts and how much is logged
+///
+/// `None` goes back to the default: kept in memory and mirrored in full.
+#[tauri::command]
+pub async fn set_history_retention(
+    server_name: String,
+    retention: Option<HistoryRetention>,
+    state: State<'_, AppState>,
+) -> Result<SynapticSettings, SynapticError> {
+    state.set_history_retention(&server_name, retention)
+}
+
+/// Mirror a server's traffic to an append-only NDJSON file, or stop with `None`
+#[tauri::command]
+pub async fn set_traffic_mirror(
+    server_name: String,
+    path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SynapticSettings, SynapticError> {
+    state.set_traffic_mirror(&server_name, path.map(std::path::PathBuf::from))
+}
+
+/// Turn read-only safe mode on or off
+#[tauri::command]
+pub async fn set_read_only(enabled: bool, state: State<'_, AppState>) -> Result<(), SynapticError> {
+    state.set_read_only(enabled)
+}
+
 // ============================================
 // INSPECTOR COMMANDS
 // ============================================
@@ -105,7 +520,7 @@ pub async fn start_inspector(
         let mut sessions = state.inspector_sessions.lock().unwrap();
         sessions.insert(
             server_name.clone(),
-            crate::state::InspectorSessionState {
+            InspectorSessionState {
                 server_name: server_name.clone(),
                 is_active: true,
             },
@@ -136,18 +551,34 @@ pub async fn get_inspector_messages(
     server_name: String,
     limit: Option<usize>,
     offset: Option<usize>,
+    filter: Option<MessageFilter>,
     state: State<'_, AppState>,
 ) -> Result<Vec<InspectorMessage>, SynapticError> {
     let messages = state.get_inspector_messages(&server_name);
+    let filter = filter.unwrap_or_default();
 
     let offset = offset.unwrap_or(0);
     let limit = limit.unwrap_or(100);
 
-    let paginated: Vec<_> = messages.into_iter().skip(offset).take(limit).collect();
+    let paginated: Vec<_> = messages
+        .into_iter()
+        .filter(|message| filter.matches(message))
+        .skip(offset)
+        .take(limit)
+        .collect();
 
     Ok(paginated)
 }
 
+/// Current request rates, latency and active requests of a server, per method
+#[tauri::command]
+pub async fn get_live_stats(
+    server_name: String,
+    state: State<'_, AppState>,
+) -> Result<LiveStats, SynapticError> {
+    Ok(state.live_stats.snapshot(&server_name))
+}
+
 /// Clear inspector message history
 #[tauri::command]
 pub async fn clear_inspector_messages(
@@ -158,93 +589,1021 @@ pub async fn clear_inspector_messages(
     Ok(())
 }
 
-// ============================================
-// REGISTRY COMMANDS
-// ============================================
+/// Pin a captured message (with an optional note) so it survives clearing the history
+#[tauri::command]
+pub async fn bookmark_message(
+    server_name: String,
+    message_id: String,
+    annotation: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Bookmark, SynapticError> {
+    let db = state.database()?;
+    match state.find_inspector_message(&server_name, &message_id) {
+        Some(message) => bookmarks::bookmark(db, &message, annotation),
+        // Gone from the history, but maybe pinned already
+        None => match (bookmarks::get(db, &message_id)?, annotation) {
+            (Some(_), Some(annotation)) => bookmarks::annotate(db, &message_id, Some(annotation)),
+            (Some(bookmark), None) => Ok(bookmark),
+            (None, _) => Err(SynapticError::InspectorError(format!(
+                "No captured message {} for {}",
+                message_id, server_name
+            ))),
+        },
+    }
+}
 
-/// Get list of available servers from registry
+/// Set or clear the note on a bookmarked message
 #[tauri::command]
-pub async fn get_registry_servers() -> Result<Vec<RegistryServer>, SynapticError> {
-    Ok(registry::get_builtin_registry())
+pub async fn annotate_message(
+    message_id: String,
+    annotation: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Bookmark, SynapticError> {
+    bookmarks::annotate(state.database()?, &message_id, annotation)
 }
 
-/// Install a server from the registry
 #[tauri::command]
-pub async fn install_registry_server(
-    server_id: String,
-    custom_name: Option<String>,
+pub async fn list_bookmarks(
+    server_name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Bookmark>, SynapticError> {
+    bookmarks::list(state.database()?, server_name.as_deref())
+}
+
+/// Unpin a message; returns whether it was bookmarked
+#[tauri::command]
+pub async fn remove_bookmark(
+    message_id: String,
     state: State<'_, AppState>,
+) -> Result<bool, SynapticError> {
+    bookmarks::remove(state.database()?, &message_id)
+}
+
+/// Hold matching traffic until it is resolved; returns the breakpoint with its id
+#[tauri::command]
+pub async fn add_breakpoint(
+    breakpoint: Breakpoint,
+    pm: State<'_, ProcessManager>,
+) -> Result<Breakpoint, SynapticError> {
+    Ok(pm.interceptor.add_breakpoint(breakpoint))
+}
+
+/// Remove a breakpoint; returns whether it existed
+#[tauri::command]
+pub async fn remove_breakpoint(
+    id: String,
+    pm: State<'_, ProcessManager>,
+) -> Result<bool, SynapticError> {
+    Ok(pm.interceptor.remove_breakpoint(&id))
+}
+
+#[tauri::command]
+pub async fn list_breakpoints(
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<Breakpoint>, SynapticError> {
+    Ok(pm.interceptor.list_breakpoints())
+}
+
+/// Messages currently held at breakpoints, oldest first
+#[tauri::command]
+pub async fn list_intercepted(
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<InterceptedMessage>, SynapticError> {
+    Ok(pm.interceptor.list_held())
+}
+
+/// Forward, edit or drop a held message
+#[tauri::command]
+pub async fn resolve_intercept(
+    id: String,
+    action: InterceptAction,
+    edited_payload: Option<serde_json::Value>,
+    pm: State<'_, ProcessManager>,
 ) -> Result<(), SynapticError> {
-    let registry_server = registry::get_registry_server(&server_id)
-        .ok_or_else(|| SynapticError::RegistryError(format!("Server not found: {}", server_id)))?;
+    pm.interceptor.resolve(&id, action, edited_payload)
+}
 
-    let name = custom_name.unwrap_or_else(|| registry_server.id.clone());
+/// Tool calls waiting for approval under a tool policy, oldest first
+#[tauri::command]
+pub async fn list_tool_approvals(
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<ToolApproval>, SynapticError> {
+    Ok(pm.policies.list_pending())
+}
 
-    // Add the server with default config
-    state.add_server(name, registry_server.default_config)
+/// Let a waiting tool call through, or answer it with an error
+#[tauri::command]
+pub async fn resolve_tool_approval(
+    id: String,
+    approved: bool,
+    pm: State<'_, ProcessManager>,
+) -> Result<(), SynapticError> {
+    pm.policies.resolve(&id, approved)
 }
 
-/// Check if required runtime is available (node, python, etc.)
+/// Answer matching requests with a canned response; returns the rule with its id
 #[tauri::command]
-pub async fn check_runtime(runtime: String) -> Result<RuntimeStatus, SynapticError> {
-    registry::check_runtime_availability(&runtime).await
+pub async fn add_mock_rule(
+    rule: MockRule,
+    pm: State<'_, ProcessManager>,
+) -> Result<MockRule, SynapticError> {
+    Ok(pm.mocks.add_rule(rule))
 }
 
-// ============================================
-// PROCESS MANAGER COMMANDS
-// ============================================
+#[tauri::command]
+pub async fn list_mock_rules(
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<MockRule>, SynapticError> {
+    Ok(pm.mocks.list_rules())
+}
 
-/// Spawn an MCP server process with MITM interception
+/// Remove a mock rule; returns whether it existed
 #[tauri::command]
-pub async fn spawn_server(
+pub async fn remove_mock_rule(
+    id: String,
+    pm: State<'_, ProcessManager>,
+) -> Result<bool, SynapticError> {
+    Ok(pm.mocks.remove_rule(&id))
+}
+
+/// Inject latency and faults into a server's traffic, or stop with `None`
+#[tauri::command]
+pub async fn set_chaos(
+    server_name: String,
+    config: Option<ChaosConfig>,
+    pm: State<'_, ProcessManager>,
+) -> Result<(), SynapticError> {
+    pm.chaos.set(&server_name, config);
+    Ok(())
+}
+
+/// Chaos settings by server name
+#[tauri::command]
+pub async fn get_chaos(
+    pm: State<'_, ProcessManager>,
+) -> Result<HashMap<String, ChaosConfig>, SynapticError> {
+    Ok(pm.chaos.all())
+}
+
+/// JSON-RPC / MCP schema violations seen in a server's traffic, oldest first
+#[tauri::command]
+pub async fn get_protocol_violations(
+    server_name: String,
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<ProtocolViolation>, SynapticError> {
+    Ok(pm.validator.violations(&server_name))
+}
+
+/// Messages over the size limit since Synaptic started, by server
+#[tauri::command]
+pub async fn get_oversized_stats(
+    pm: State<'_, ProcessManager>,
+) -> Result<HashMap<String, OversizedStats>, SynapticError> {
+    Ok(pm.oversized.stats())
+}
+
+/// Protocol version, capabilities and serverInfo a server negotiated
+///
+/// Falls back to the last stored handshake when the server hasn't been
+/// initialized since Synaptic started.
+#[tauri::command]
+pub async fn get_server_capabilities(
     name: String,
-    app: tauri::AppHandle,
     state: State<'_, AppState>,
-    pm: State<'_, crate::process_manager::ProcessManager>,
-) -> Result<u32, SynapticError> {
-    // Get server config
-    let config = state.get_config()?;
-    let server = config
-        .mcp_servers
-        .get(&name)
-        .ok_or_else(|| SynapticError::ServerNotFound(name.clone()))?;
+    pm: State<'_, ProcessManager>,
+) -> Result<Option<ServerCapabilities>, SynapticError> {
+    match pm.capabilities.get(&name) {
+        Some(negotiated) => Ok(Some(negotiated)),
+        None => capabilities::load(state.database()?, &name),
+    }
+}
 
-    // Spawn the process
-    crate::process_manager::spawn_mcp_server(
-        app,
-        pm,
-        name,
-        server.command.clone(),
-        server.args.clone(),
-        server.env.clone(),
-        server.cwd.clone(),
+/// Calls, latency percentiles and error rate per tool, most called first
+#[tauri::command]
+pub async fn get_tool_stats(
+    server: Option<String>,
+    time_range: Option<TimeRange>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ToolStats>, SynapticError> {
+    tool_stats::query(
+        state.database()?,
+        server.as_deref(),
+        &time_range.unwrap_or_default(),
     )
-    .await
 }
 
-/// Kill a running MCP server process
+/// Logged message counts per time bucket, for activity sparklines
 #[tauri::command]
-pub async fn kill_server(
-    name: String,
-    pm: State<'_, crate::process_manager::ProcessManager>,
-) -> Result<(), SynapticError> {
-    pm.kill_process(&name).await
+pub async fn get_traffic_timeline(
+    server: Option<String>,
+    bucket_seconds: u64,
+    range: Option<TimeRange>,
+    state: State<'_, AppState>,
+) -> Result<Vec<TimelineBucket>, SynapticError> {
+    system_logs::timeline(
+        state.database()?,
+        server.as_deref(),
+        bucket_seconds,
+        &range.unwrap_or_default(),
+    )
 }
 
-/// Send data to a running MCP server's stdin
+/// Logged error responses and stderr errors grouped by fingerprint, most frequent first
 #[tauri::command]
-pub async fn send_to_server(
-    name: String,
-    payload: String,
-    pm: State<'_, crate::process_manager::ProcessManager>,
-) -> Result<(), SynapticError> {
-    pm.send_to_stdin(&name, payload).await
+pub async fn get_error_summary(
+    server: Option<String>,
+    range: Option<TimeRange>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ErrorGroup>, SynapticError> {
+    error_summary::summarize(
+        state.database()?,
+        server.as_deref(),
+        &range.unwrap_or_default(),
+    )
 }
 
-/// Get list of currently running server processes
-#[tauri::command]
+// ============================================
+// REGISTRY COMMANDS
+// ============================================
+
+/// Get list of available servers from the registry, synced when the cached listing is past its TTL
+#[tauri::command]
+pub async fn get_registry_servers(
+    app: tauri::AppHandle,
+) -> Result<Vec<RegistryServer>, SynapticError> {
+    Ok(registry::refresh_and_enrich(&TauriHost(app), false)
+        .await
+        .servers)
+}
+
+/// Search the cached registry listing by words, tags and install method
+#[tauri::command]
+pub async fn search_registry(
+    query: RegistryQuery,
+    app: tauri::AppHandle,
+) -> Result<RegistrySearchResult, SynapticError> {
+    let snapshot = registry::refresh_and_enrich(&TauriHost(app), false).await;
+    Ok(registry::search(&snapshot.servers, &query))
+}
+
+/// Sync the registry listing now if its TTL has passed, or unconditionally when `force` is set
+///
+/// Download counts of a synced listing follow in the background; `registry-enriched`
+/// says when they are in.
+#[tauri::command]
+pub async fn refresh_registry(
+    force: bool,
+    app: tauri::AppHandle,
+) -> Result<RegistrySnapshot, SynapticError> {
+    Ok(registry::refresh_and_enrich(&TauriHost(app), force).await)
+}
+
+/// Install a server from the registry, pinned to `version` when given
+///
+/// Required env values missing from `env` (or not passing validation) are
+/// returned as needing configuration instead of installing with them empty.
+/// Git and binary entries run as a job that reports `install-progress` events
+/// carrying its job ID (`job_id` when given, so it can be cancelled right away)
+/// until it's done, has failed or is cancelled.
+#[tauri::command]
+pub async fn install_registry_server(
+    server_id: String,
+    custom_name: Option<String>,
+    version: Option<String>,
+    env: Option<HashMap<String, String>>,
+    job_id: Option<String>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<InstallOutcome, SynapticError> {
+    installer::install(
+        &TauriHost(app),
+        &state,
+        &server_id,
+        custom_name,
+        version.as_deref(),
+        &env.unwrap_or_default(),
+        job_id,
+    )
+    .await
+}
+
+/// Cancel a running install, killing git or the build; `false` when no such install is running
+#[tauri::command]
+pub async fn cancel_install(
+    job_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, SynapticError> {
+    Ok(state.install_jobs.cancel(&job_id))
+}
+
+/// Remove a server along with the repo or binary it installed
+///
+/// The running process is stopped first; `purge` also deletes its captured
+/// history and log files.
+#[tauri::command]
+pub async fn uninstall_server(
+    name: String,
+    purge: bool,
+    app: tauri::AppHandle,
+) -> Result<UninstallReport, SynapticError> {
+    installer::uninstall(TauriHost(app), &name, purge).await
+}
+
+/// A registry entry with its package's latest release, maintainers, license and weekly downloads
+#[tauri::command]
+pub async fn get_registry_server_details(
+    server_id: String,
+    state: State<'_, AppState>,
+) -> Result<RegistryServerDetails, SynapticError> {
+    registry::server_details(&state, &server_id).await
+}
+
+/// Published versions of a registry entry's npm or PyPI package, newest first
+#[tauri::command]
+pub async fn get_package_versions(
+    server_id: String,
+    state: State<'_, AppState>,
+) -> Result<PackageVersions, SynapticError> {
+    let entry = registry::find_server(&state, &server_id)
+        .ok_or_else(|| SynapticError::RegistryError(format!("Server not found: {}", server_id)))?;
+    packages::fetch_versions(&entry.install_method).await
+}
+
+/// Pinned servers whose npm or PyPI package has a newer release
+#[tauri::command]
+pub async fn check_server_updates(
+    state: State<'_, AppState>,
+) -> Result<Vec<ServerUpdate>, SynapticError> {
+    updates::check_server_updates(&state).await
+}
+
+/// Pin a server's package to `version` (the latest release when omitted)
+///
+/// The version must be one the package index published. A running server is
+/// restarted when `restart` is set and flagged as stale otherwise; a failed
+/// restart comes back as the result's warning.
+#[tauri::command]
+pub async fn upgrade_server(
+    name: String,
+    version: Option<String>,
+    restart: bool,
+    app: tauri::AppHandle,
+) -> Result<ServerUpdate, SynapticError> {
+    updates::upgrade_server(TauriHost(app), &name, version, restart).await
+}
+
+/// Check if required runtime is available (node, python, etc.)
+#[tauri::command]
+pub async fn check_runtime(runtime: String) -> Result<RuntimeStatus, SynapticError> {
+    registry::check_runtime_availability(&runtime).await
+}
+
+/// Download a portable node, uv or python into Synaptic's data directory
+///
+/// Reported as an install job through `install-progress` events, and
+/// cancellable with `cancel_install` (under `job_id` when given). Returns the
+/// runtime's status afterwards.
+#[tauri::command]
+pub async fn install_runtime(
+    runtime: String,
+    job_id: Option<String>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<RuntimeStatus, SynapticError> {
+    runtimes::install_runtime(&TauriHost(app), &state, &runtime, job_id).await
+}
+
+// ============================================
+// PROCESS MANAGER COMMANDS
+// ============================================
+
+/// Check a server's runtime, required env vars and paths without spawning it
+#[tauri::command]
+pub async fn preflight_server(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<PreflightReport, SynapticError> {
+    let config = state.get_config()?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .ok_or_else(|| SynapticError::ServerNotFound(name.clone()))?;
+    Ok(preflight::preflight_server(Some(&state), &name, server))
+}
+
+/// Spawn an MCP server process with MITM interception
+///
+/// Fails with `PREFLIGHT_FAILED` (report in `details`) when the server can't start.
+#[tauri::command]
+pub async fn spawn_server(
+    name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<u32, SynapticError> {
+    // Get server config
+    let config = state.get_config()?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| SynapticError::ServerNotFound(name.clone()))?;
+
+    // Spawn the process
+    process_manager::spawn_mcp_server(TauriHost(app), pm.inner(), name, server).await
+}
+
+/// List a server's tools, resources and prompts, starting it just for the probe if needed
+#[tauri::command]
+pub async fn probe_server(
+    name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ServerCatalog, SynapticError> {
+    let config = state.get_config()?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| SynapticError::ServerNotFound(name.clone()))?;
+
+    probe::probe_server(TauriHost(app), &name, server).await
+}
+
+/// Compare two servers or logged sessions: catalogs, capabilities and responses to the same requests
+#[tauri::command]
+pub async fn compare_servers(
+    a: ComparisonSource,
+    b: ComparisonSource,
+    requests: Option<Vec<serde_json::Value>>,
+    app: tauri::AppHandle,
+) -> Result<ServerComparison, SynapticError> {
+    server_compare::compare_servers(TauriHost(app), a, b, requests.unwrap_or_default()).await
+}
+
+/// Run the protocol conformance suite against a server, starting it just for the run if needed
+#[tauri::command]
+pub async fn run_conformance_tests(
+    name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ConformanceReport, SynapticError> {
+    let config = state.get_config()?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| SynapticError::ServerNotFound(name.clone()))?;
+
+    conformance::run_conformance_tests(TauriHost(app), &name, server).await
+}
+
+/// Fuzz a server with malformed and boundary input, starting it just for the run if needed
+#[tauri::command]
+pub async fn run_fuzz(
+    name: String,
+    options: Option<FuzzOptions>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<FuzzReport, SynapticError> {
+    let config = state.get_config()?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| SynapticError::ServerNotFound(name.clone()))?;
+
+    fuzz::run_fuzz(TauriHost(app), &name, server, options.unwrap_or_default()).await
+}
+
+/// Stored fuzz reports, newest first
+#[tauri::command]
+pub async fn list_fuzz_reports(
+    server_name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<FuzzReport>, SynapticError> {
+    fuzz::list_reports(state.database()?, server_name.as_deref())
+}
+
+/// Delete a stored fuzz report; returns whether it existed
+#[tauri::command]
+pub async fn delete_fuzz_report(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, SynapticError> {
+    fuzz::delete_report(state.database()?, &id)
+}
+
+/// Call one of a server's tools and return its result content
+#[tauri::command]
+pub async fn call_tool(
+    name: String,
+    tool_name: String,
+    arguments: Option<serde_json::Value>,
+    timeout_ms: Option<u64>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ToolCallResult, SynapticError> {
+    let config = state.get_config()?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| SynapticError::ServerNotFound(name.clone()))?;
+
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    let host = TauriHost(app);
+    idle::wake_server(&host, &name).await?;
+    let arguments = arguments.unwrap_or_else(|| serde_json::json!({}));
+    probe::call_tool(host, &name, server, &tool_name, arguments, timeout).await
+}
+
+/// Fetch one of a server's prompts rendered with the given arguments
+#[tauri::command]
+pub async fn get_prompt(
+    name: String,
+    prompt_name: String,
+    arguments: Option<HashMap<String, String>>,
+    timeout_ms: Option<u64>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<PromptResult, SynapticError> {
+    let config = state.get_config()?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| SynapticError::ServerNotFound(name.clone()))?;
+
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    let host = TauriHost(app);
+    idle::wake_server(&host, &name).await?;
+    let arguments = arguments.unwrap_or_default();
+    probe::get_prompt(host, &name, server, &prompt_name, arguments, timeout).await
+}
+
+/// Read a resource of a running server
+#[tauri::command]
+pub async fn read_resource(
+    name: String,
+    uri: String,
+    timeout_ms: Option<u64>,
+    app: tauri::AppHandle,
+    pm: State<'_, ProcessManager>,
+) -> Result<ResourceContents, SynapticError> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    idle::wake_server(&TauriHost(app), &name).await?;
+    resources::read_resource(pm.inner(), &name, &uri, timeout).await
+}
+
+/// Subscribe to a resource; updates arrive as `resource-updated` events
+#[tauri::command]
+pub async fn subscribe_resource(
+    name: String,
+    uri: String,
+    timeout_ms: Option<u64>,
+    app: tauri::AppHandle,
+    pm: State<'_, ProcessManager>,
+) -> Result<(), SynapticError> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    idle::wake_server(&TauriHost(app), &name).await?;
+    resources::subscribe_resource(pm.inner(), &name, &uri, timeout).await
+}
+
+/// Stop receiving updates of a resource
+#[tauri::command]
+pub async fn unsubscribe_resource(
+    name: String,
+    uri: String,
+    timeout_ms: Option<u64>,
+    pm: State<'_, ProcessManager>,
+) -> Result<(), SynapticError> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    resources::unsubscribe_resource(pm.inner(), &name, &uri, timeout).await
+}
+
+/// Resources of a server subscribed to from Synaptic
+#[tauri::command]
+pub async fn list_resource_subscriptions(
+    name: String,
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<String>, SynapticError> {
+    Ok(pm.resource_subscriptions.list(&name))
+}
+
+/// Progress notifications received for a request, oldest first
+#[tauri::command]
+pub async fn get_request_progress(
+    request_id: serde_json::Value,
+    server_name: Option<String>,
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<ProgressUpdate>, SynapticError> {
+    Ok(pm.progress.updates(server_name.as_deref(), &request_id))
+}
+
+/// Cancel a request still waiting for its response with `notifications/cancelled`
+#[tauri::command]
+pub async fn cancel_request(
+    name: String,
+    request_id: serde_json::Value,
+    reason: Option<String>,
+    pm: State<'_, ProcessManager>,
+) -> Result<(), SynapticError> {
+    pm.cancel_request(&name, &request_id, reason).await
+}
+
+/// Requests past `requestTimeoutSecs` that are still unanswered, oldest first
+#[tauri::command]
+pub async fn list_timed_out_requests(
+    name: Option<String>,
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<RequestTimeout>, SynapticError> {
+    Ok(pm.timeouts.timed_out(name.as_deref()))
+}
+
+/// Sampling requests from servers still waiting for an answer
+#[tauri::command]
+pub async fn list_sampling_requests(
+    name: Option<String>,
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<SamplingRequest>, SynapticError> {
+    Ok(pm.sampling.list(name.as_deref()))
+}
+
+/// Answer a server's `sampling/createMessage` request with a result or a denial
+#[tauri::command]
+pub async fn respond_to_sampling(
+    name: String,
+    request_id: serde_json::Value,
+    reply: SamplingReply,
+    pm: State<'_, ProcessManager>,
+) -> Result<(), SynapticError> {
+    sampling::respond_to_sampling(pm.inner(), &name, &request_id, &reply).await
+}
+
+/// Stop a running MCP server process, escalating from closing stdin to kill
+///
+/// In-flight requests get `drainTimeoutMs` to be answered first. Returns the shutdown stage that made the process exit.
+#[tauri::command]
+pub async fn kill_server(
+    name: String,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<ShutdownStage, SynapticError> {
+    let settings = state.get_settings()?;
+    pm.drain_and_kill(&name, settings.drain_timeout(), settings.shutdown_grace())
+        .await
+}
+
+/// Start every enabled server that isn't running, emitting `spawn-all-progress`
+#[tauri::command]
+pub async fn spawn_all_enabled(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<SpawnProgress>, SynapticError> {
+    let config = state.get_config()?;
+    Ok(process_manager::spawn_all_enabled(TauriHost(app), pm.inner(), &config).await)
+}
+
+/// Stop every running server, emitting `kill-all-progress`
+#[tauri::command]
+pub async fn kill_all_servers(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<StopProgress>, SynapticError> {
+    let settings = state.get_settings()?;
+    let host = TauriHost(app);
+
+    Ok(pm
+        .kill_all_with(
+            settings.drain_timeout(),
+            settings.shutdown_grace(),
+            |progress| host.emit("kill-all-progress", progress),
+        )
+        .await)
+}
+
+/// Gracefully stop a server (if running) and spawn it again from the current config
+#[tauri::command]
+pub async fn restart_server(
+    name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<u32, SynapticError> {
+    let server = state
+        .get_config()?
+        .mcp_servers
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| SynapticError::ServerNotFound(name.clone()))?;
+    let grace = state.get_settings()?.shutdown_grace();
+
+    process_manager::restart_mcp_server(TauriHost(app), pm.inner(), name, server, grace).await
+}
+
+/// Restart every running server one at a time to apply config changes
+#[tauri::command]
+pub async fn restart_all_running(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<RestartResult>, SynapticError> {
+    let config = state.get_config()?;
+    let grace = state.get_settings()?.shutdown_grace();
+
+    Ok(process_manager::restart_all_running(TauriHost(app), pm.inner(), &config, grace).await)
+}
+
+/// Send data to a running MCP server's stdin
+#[tauri::command]
+pub async fn send_to_server(
+    name: String,
+    payload: String,
+    app: tauri::AppHandle,
+    pm: State<'_, ProcessManager>,
+) -> Result<(), SynapticError> {
+    // Servers stopped for inactivity start again on their next message
+    idle::wake_server(&TauriHost(app), &name).await?;
+    pm.send_to_stdin(&name, payload).await
+}
+
+/// Send a JSON-RPC request to a running server and wait for its response
+#[tauri::command]
+pub async fn send_request(
+    name: String,
+    payload: serde_json::Value,
+    timeout_ms: Option<u64>,
+    app: tauri::AppHandle,
+    pm: State<'_, ProcessManager>,
+) -> Result<serde_json::Value, SynapticError> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    idle::wake_server(&TauriHost(app), &name).await?;
+    pm.send_request(&name, payload, timeout).await
+}
+
+/// Save a request to the library, replacing one with the same name
+#[tauri::command]
+pub async fn save_request_template(
+    template: RequestTemplate,
+    state: State<'_, AppState>,
+) -> Result<RequestTemplate, SynapticError> {
+    request_templates::save(state.database()?, &template)
+}
+
+#[tauri::command]
+pub async fn list_request_templates(
+    state: State<'_, AppState>,
+) -> Result<Vec<RequestTemplate>, SynapticError> {
+    request_templates::list(state.database()?)
+}
+
+/// Remove a saved request; returns whether it existed
+#[tauri::command]
+pub async fn delete_request_template(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<bool, SynapticError> {
+    request_templates::delete(state.database()?, &name)
+}
+
+/// Fill in a saved request's variables, send it and wait for the response
+///
+/// Goes to `server_name`, or the template's own server when unset.
+#[tauri::command]
+pub async fn send_request_template(
+    name: String,
+    vars: HashMap<String, serde_json::Value>,
+    server_name: Option<String>,
+    timeout_ms: Option<u64>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<serde_json::Value, SynapticError> {
+    let template = request_templates::get(state.database()?, &name)?.ok_or_else(|| {
+        SynapticError::InspectorError(format!("No request template named {}", name))
+    })?;
+    let server = server_name
+        .or_else(|| template.server_name.clone())
+        .ok_or_else(|| {
+            SynapticError::InspectorError(format!("Template {} has no server to send to", name))
+        })?;
+    let payload = request_templates::build_request(&template, &vars)?;
+
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    idle::wake_server(&TauriHost(app), &server).await?;
+    pm.send_request(&server, payload, timeout).await
+}
+
+/// Keep `concurrency` copies of a saved request in flight for `duration_ms` and store the result
+///
+/// Goes to `server_name`, or the template's own server when unset.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn run_load_test(
+    template: String,
+    vars: HashMap<String, serde_json::Value>,
+    server_name: Option<String>,
+    concurrency: usize,
+    duration_ms: u64,
+    timeout_ms: Option<u64>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<LoadTestResult, SynapticError> {
+    let saved = request_templates::get(state.database()?, &template)?.ok_or_else(|| {
+        SynapticError::InspectorError(format!("No request template named {}", template))
+    })?;
+    let server = server_name
+        .or_else(|| saved.server_name.clone())
+        .ok_or_else(|| {
+            SynapticError::InspectorError(format!("Template {} has no server to send to", template))
+        })?;
+    let plan = LoadTestPlan {
+        request: request_templates::build_request(&saved, &vars)?,
+        template: Some(template),
+        concurrency,
+        duration_ms,
+        timeout_ms: timeout_ms.unwrap_or(30_000),
+    };
+
+    idle::wake_server(&TauriHost(app), &server).await?;
+    let result = load_test::run_load_test(&pm, &server, plan).await?;
+    load_test::save_result(state.database()?, &result)?;
+    Ok(result)
+}
+
+/// Stored load test results, newest first
+#[tauri::command]
+pub async fn list_load_test_results(
+    server_name: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<LoadTestResult>, SynapticError> {
+    load_test::list_results(state.database()?, server_name.as_deref())
+}
+
+/// Delete a stored load test result; returns whether it existed
+#[tauri::command]
+pub async fn delete_load_test_result(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, SynapticError> {
+    load_test::delete_result(state.database()?, &id)
+}
+
+/// Send a logged request again, optionally edited, and return both exchanges
+#[tauri::command]
+pub async fn resend_message(
+    message_id: i64,
+    edits: Option<Vec<PatchOp>>,
+    timeout_ms: Option<u64>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    pm: State<'_, ProcessManager>,
+) -> Result<ResendResult, SynapticError> {
+    let original = resend::original_exchange(state.database()?, message_id)?;
+    if let Some(server) = &original.0.server_name {
+        idle::wake_server(&TauriHost(app), server).await?;
+    }
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    resend::resend(&pm, original, &edits.unwrap_or_default(), timeout).await
+}
+
+/// Added, removed and changed paths between two logged messages
+#[tauri::command]
+pub async fn diff_messages(
+    id_a: i64,
+    id_b: i64,
+    state: State<'_, AppState>,
+) -> Result<MessageDiff, SynapticError> {
+    message_diff::diff_messages(state.database()?, id_a, id_b)
+}
+
+/// Get list of currently running server processes
+#[tauri::command]
 pub async fn get_running_servers(
-    pm: State<'_, crate::process_manager::ProcessManager>,
+    pm: State<'_, ProcessManager>,
 ) -> Result<Vec<String>, SynapticError> {
     Ok(pm.list_running().await)
 }
+
+/// Get lifecycle status of one server (none if it was never started or stopped cleanly)
+#[tauri::command]
+pub async fn get_process_status(
+    name: String,
+    pm: State<'_, ProcessManager>,
+) -> Result<Option<ProcessStatus>, SynapticError> {
+    Ok(pm.get_status(&name).await)
+}
+
+/// Get the last `lines` stderr lines of a server (kept after it exits)
+#[tauri::command]
+pub async fn get_recent_stderr(
+    name: String,
+    lines: usize,
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<String>, SynapticError> {
+    Ok(pm.get_recent_stderr(&name, lines).await)
+}
+
+/// Get the last `lines` lines of a docker server's container logs
+#[tauri::command]
+pub async fn get_container_logs(
+    name: String,
+    lines: usize,
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<String>, SynapticError> {
+    let container_id = pm
+        .get_status(&name)
+        .await
+        .and_then(|status| status.container_id)
+        .ok_or_else(|| SynapticError::ProcessError(format!("{} has no running container", name)))?;
+    docker::logs(&container_id, lines).await
+}
+
+/// Get crash reports, newest first, optionally for a single server
+#[tauri::command]
+pub async fn get_crash_history(
+    server_name: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<CrashReport>, SynapticError> {
+    crashes::query(
+        state.database()?,
+        server_name.as_deref(),
+        limit.unwrap_or(100),
+        offset.unwrap_or(0),
+    )
+}
+
+/// List server processes left running by an earlier session that crashed
+#[tauri::command]
+pub async fn list_orphans(state: State<'_, AppState>) -> Result<Vec<OrphanProcess>, SynapticError> {
+    orphans::find_orphans(state.database()?)
+}
+
+/// Keep (adopt) or kill orphaned server processes, optionally only some servers'
+#[tauri::command]
+pub async fn adopt_or_kill_orphans(
+    action: OrphanAction,
+    server_names: Option<Vec<String>>,
+    state: State<'_, AppState>,
+) -> Result<Vec<OrphanProcess>, SynapticError> {
+    orphans::adopt_or_kill_orphans(state.database()?, action, server_names.as_deref())
+}
+
+/// Get the latest CPU/memory sample of a running server's process tree
+#[tauri::command]
+pub async fn get_process_metrics(
+    name: String,
+    pm: State<'_, ProcessManager>,
+) -> Result<Option<ProcessMetrics>, SynapticError> {
+    Ok(pm.get_metrics(&name).await)
+}
+
+/// Get ping health (latency, consecutive failures) of a running server
+#[tauri::command]
+pub async fn get_server_health(
+    name: String,
+    pm: State<'_, ProcessManager>,
+) -> Result<Option<HealthStatus>, SynapticError> {
+    Ok(pm.get_health(&name).await)
+}
+
+/// Get lifecycle status of every managed or crashed server
+#[tauri::command]
+pub async fn get_all_process_statuses(
+    pm: State<'_, ProcessManager>,
+) -> Result<Vec<ProcessStatus>, SynapticError> {
+    Ok(pm.all_statuses().await)
+}
+
+// ============================================
+// SERVER LOG COMMANDS
+// ============================================
+
+/// List on-disk log files of one server (or all servers), newest first
+#[tauri::command]
+pub async fn list_server_logs(name: Option<String>) -> Result<Vec<LogFileInfo>, SynapticError> {
+    server_logs::list_server_logs(name.as_deref())
+}
+
+/// Last `lines` lines of a server's newest log file (or of `file_name`)
+#[tauri::command]
+pub async fn tail_server_log(
+    name: String,
+    file_name: Option<String>,
+    lines: usize,
+) -> Result<Vec<String>, SynapticError> {
+    server_logs::tail_server_log(&name, file_name.as_deref(), lines)
+}
+
+/// Open a server's log directory in the system file manager
+#[tauri::command]
+pub async fn open_server_log_dir(name: String) -> Result<String, SynapticError> {
+    let dir = server_logs::open_server_log_dir(&name)?;
+    Ok(dir.to_string_lossy().to_string())
+}
@@ -0,0 +1,25 @@
+//! Tauri implementation of the core host traits
+
+use synaptic_core::{AppState, EventEmitter, ProcessManager, StateProvider};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Wraps the Tauri app handle so core background tasks can emit events
+/// to the webview and reach managed state
+#[derive(Clone)]
+pub struct TauriHost(pub AppHandle);
+
+impl EventEmitter for TauriHost {
+    fn emit_value(&self, event: &str, payload: serde_json::Value) {
+        let _ = self.0.emit(event, payload);
+    }
+}
+
+impl StateProvider for TauriHost {
+    fn app_state(&self) -> Option<&AppState> {
+        self.0.try_state::<AppState>().map(|state| state.inner())
+    }
+
+    fn process_manager(&self) -> Option<&ProcessManager> {
+        self.0.try_state::<ProcessManager>().map(|pm| pm.inner())
+    }
+}
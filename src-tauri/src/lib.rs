@@ -2,51 +2,106 @@
 //!
 //! Core library implementing the Tauri application with mobile-first pattern.
 //! This is the CORE module following Tauri v2 C1 constraint.
+//!
+//! Business logic lives in the Tauri-free `synaptic-core` crate; this crate
+//! only wires it into Tauri (IPC commands, managed state, event emission).
 
 // Module declarations
 mod commands;
-mod config;
-mod database;
-mod error;
-mod inspector;
-mod process_manager;
-mod registry;
-mod state;
+mod host;
 
 // Re-exports for external use
-pub use config::{McpConfig, McpServer};
-pub use error::{SynapticError, SynapticResult};
-pub use inspector::{InspectorMessage, InspectorSession, MessageDirection};
-pub use process_manager::ProcessManager;
-pub use registry::{InstallMethod, RegistryServer, RuntimeStatus};
-pub use state::AppState;
+pub use synaptic_core::config::{McpConfig, McpServer};
+pub use synaptic_core::error::{SynapticError, SynapticResult};
+pub use synaptic_core::inspector::{InspectorMessage, InspectorSession, MessageDirection};
+pub use synaptic_core::process_manager::ProcessManager;
+pub use synaptic_core::registry::{InstallMethod, RegistryServer, RuntimeStatus};
+pub use synaptic_core::state::AppState;
+
+use host::TauriHost;
+use synaptic_core::database::{Database, DATABASE_FILE};
+use synaptic_core::{
+    gateway, health, idle, metrics, orphans, schedules, shim, snapshots, system_logs, timeouts,
+};
 
-// Import Manager trait for app.manage() method
-use tauri::Manager;
+// Import Manager trait for app.manage() method (Emitter for app.emit())
+use tauri::{Emitter, Manager};
 
 /// Mobile entry point annotation for iOS/Android compatibility
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Get database migrations
-    let migrations = database::get_migrations();
-
     tauri::Builder::default()
         // Initialize plugins
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
-        // SQL plugin with migrations for persistent logging
-        .plugin(
-            tauri_plugin_sql::Builder::default()
-                .add_migrations("sqlite:wear-synaptic.db", migrations)
-                .build(),
-        )
+        // SQL plugin for the webview's reads; the schema is migrated by the
+        // backend's own connection in setup, before the webview loads
+        .plugin(tauri_plugin_sql::Builder::default().build())
         // Set up managed state
         .setup(|app| {
-            // Initialize application state
-            app.manage(AppState::new());
-            // Initialize process manager
-            app.manage(ProcessManager::new());
+            // Initialize application state with the backend database connection
+            // (same file the SQL plugin resolves under the app config dir)
+            let db_path = app.path().app_config_dir()?.join(DATABASE_FILE);
+            let state = match Database::open(&db_path) {
+                Ok(db) => AppState::with_database(db),
+                Err(e) => {
+                    eprintln!("Failed to open database {}: {}", db_path.display(), e);
+                    AppState::new()
+                }
+            };
+            // Servers that outlived a crashed session, announced as `orphans-found`
+            // (the UI offers to adopt or kill them; listOrphans returns the same list)
+            if let Some(db) = &state.database {
+                match orphans::find_orphans(db) {
+                    Ok(found) if !found.is_empty() => {
+                        eprintln!("Found {} orphaned server process(es)", found.len());
+                        let _ = app.emit("orphans-found", &found);
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Failed to scan for orphaned servers: {}", e),
+                }
+            }
+            app.manage(state);
+            // Initialize process manager, with its traffic persisted to system_logs
+            let process_manager = ProcessManager::new();
+            let (log_sink, log_records) = system_logs::channel();
+            let _ = process_manager.log_sink.set(log_sink);
+            app.manage(process_manager);
+            tauri::async_runtime::spawn(system_logs::run_log_writer(
+                TauriHost(app.handle().clone()),
+                log_records,
+            ));
+            // Periodic config snapshots (protects edits made outside Synaptic)
+            tauri::async_runtime::spawn(snapshots::run_snapshot_scheduler(TauriHost(
+                app.handle().clone(),
+            )));
+            // CPU/memory sampling of running servers
+            tauri::async_runtime::spawn(metrics::run_metrics_sampler(TauriHost(
+                app.handle().clone(),
+            )));
+            // MCP ping health checks of running servers
+            tauri::async_runtime::spawn(health::run_health_checker(TauriHost(
+                app.handle().clone(),
+            )));
+            // Stops servers without traffic for idleTimeoutMins
+            tauri::async_runtime::spawn(idle::run_idle_monitor(TauriHost(
+                app.handle().clone(),
+            )));
+            // Flags requests unanswered for requestTimeoutSecs
+            tauri::async_runtime::spawn(timeouts::run_timeout_monitor(TauriHost(
+                app.handle().clone(),
+            )));
+            // Live view of traffic recorded by client capture shims
+            tauri::async_runtime::spawn(shim::run_capture_follower(TauriHost(
+                app.handle().clone(),
+            )));
+            // Scheduled start/stop windows
+            tauri::async_runtime::spawn(schedules::run_schedule_scheduler(TauriHost(
+                app.handle().clone(),
+            )));
+            // Every enabled server behind one MCP endpoint, when turned on
+            tauri::async_runtime::spawn(gateway::run_gateway(TauriHost(app.handle().clone())));
             Ok(())
         })
         // Register IPC command handlers
@@ -56,25 +111,132 @@ pub fn run() {
             commands::read_config,
             commands::write_config,
             commands::add_server,
+            commands::parse_server_snippet,
             commands::remove_server,
             commands::update_server,
             commands::toggle_server,
+            commands::toggle_server_for_target,
+            commands::get_server_targets,
+            commands::enable_client_capture,
+            commands::disable_client_capture,
+            commands::authorize_server,
+            commands::get_auth_status,
+            commands::sign_out_server,
+            commands::get_gateway_entry,
+            commands::get_mock_server_entry,
             commands::list_backups,
             commands::restore_backup,
+            commands::export_backups,
+            commands::scan_config_for_secrets,
+            commands::migrate_secret,
+            commands::get_audit_log,
+            commands::query_logs,
+            commands::search_messages,
+            commands::export_session,
+            commands::export_session_report,
+            commands::import_session,
+            // Settings Commands
+            commands::get_settings,
+            commands::update_settings,
+            commands::get_redaction_presets,
+            commands::set_read_only,
+            commands::resolve_executable_approval,
+            commands::add_allowed_executable,
+            commands::remove_allowed_executable,
+            commands::get_history_retention,
+            commands::set_history_retention,
+            commands::set_traffic_mirror,
             // Inspector Commands
             commands::start_inspector,
             commands::stop_inspector,
             commands::get_inspector_messages,
+            commands::get_live_stats,
             commands::clear_inspector_messages,
+            commands::bookmark_message,
+            commands::annotate_message,
+            commands::list_bookmarks,
+            commands::remove_bookmark,
+            commands::add_breakpoint,
+            commands::remove_breakpoint,
+            commands::list_breakpoints,
+            commands::list_intercepted,
+            commands::resolve_intercept,
+            commands::list_tool_approvals,
+            commands::resolve_tool_approval,
+            commands::add_mock_rule,
+            commands::list_mock_rules,
+            commands::remove_mock_rule,
+            commands::set_chaos,
+            commands::get_chaos,
+            commands::get_protocol_violations,
+            commands::get_oversized_stats,
+            commands::get_server_capabilities,
+            commands::get_tool_stats,
+            commands::get_traffic_timeline,
+            commands::get_error_summary,
             // Process Manager Commands
+            commands::preflight_server,
             commands::spawn_server,
+            commands::probe_server,
+            commands::compare_servers,
+            commands::run_conformance_tests,
+            commands::run_fuzz,
+            commands::list_fuzz_reports,
+            commands::delete_fuzz_report,
+            commands::call_tool,
+            commands::get_prompt,
+            commands::read_resource,
+            commands::subscribe_resource,
+            commands::unsubscribe_resource,
+            commands::list_resource_subscriptions,
+            commands::get_request_progress,
+            commands::cancel_request,
+            commands::list_timed_out_requests,
+            commands::list_sampling_requests,
+            commands::respond_to_sampling,
             commands::kill_server,
+            commands::restart_server,
+            commands::restart_all_running,
+            commands::spawn_all_enabled,
+            commands::kill_all_servers,
             commands::send_to_server,
+            commands::send_request,
+            commands::save_request_template,
+            commands::list_request_templates,
+            commands::delete_request_template,
+            commands::send_request_template,
+            commands::run_load_test,
+            commands::list_load_test_results,
+            commands::delete_load_test_result,
+            commands::resend_message,
+            commands::diff_messages,
             commands::get_running_servers,
+            commands::get_process_status,
+            commands::get_all_process_statuses,
+            commands::get_recent_stderr,
+            commands::get_crash_history,
+            commands::get_container_logs,
+            commands::list_orphans,
+            commands::adopt_or_kill_orphans,
+            commands::get_process_metrics,
+            commands::get_server_health,
+            // Server Log Commands
+            commands::list_server_logs,
+            commands::tail_server_log,
+            commands::open_server_log_dir,
             // Registry Commands
             commands::get_registry_servers,
+            commands::refresh_registry,
+            commands::search_registry,
             commands::install_registry_server,
+            commands::cancel_install,
+            commands::uninstall_server,
+            commands::get_registry_server_details,
+            commands::get_package_versions,
+            commands::check_server_updates,
+            commands::upgrade_server,
             commands::check_runtime,
+            commands::install_runtime,
         ])
         .run(tauri::generate_context!())
         .expect("error while running Synaptic application");
@@ -39,6 +39,8 @@ interface McpTrafficEvent {
     direction: "INCOMING" | "OUTGOING" | "STDERR";
     content: string;
     messageId: string;
+    /** Level a stderr line states, when one was recognized */
+    level?: "TRACE" | "DEBUG" | "INFO" | "WARN" | "ERROR" | "FATAL";
 }
 
 // Internal message format
@@ -82,30 +84,32 @@ export function InspectorPanel() {
         let unlisten: UnlistenFn | null = null;
 
         async function setupListener() {
-            unlisten = await listen<McpTrafficEvent>("mcp-traffic", (event) => {
-                const traffic = event.payload;
-
-                // Only add messages for the selected server
-                if (traffic.serverId !== selectedServer) return;
-
-                // Parse method from content if it's a JSON-RPC request
-                let method: string | undefined;
-                try {
-                    const parsed = JSON.parse(traffic.content);
-                    method = parsed.method;
-                } catch {
-                    // Not JSON, that's fine
-                }
-
-                const msg: TrafficMessage = {
-                    id: traffic.messageId,
-                    timestamp: traffic.timestamp,
-                    direction: traffic.direction,
-                    content: traffic.content,
-                    method,
-                };
-
-                setMessages((prev) => [...prev.slice(-499), msg]); // Keep last 500
+            // Traffic arrives in ordered batches; apply each batch in one state update
+            unlisten = await listen<McpTrafficEvent[]>("mcp-traffic-batch", (event) => {
+                const batch: TrafficMessage[] = event.payload
+                    // Only add messages for the selected server
+                    .filter((traffic) => traffic.serverId === selectedServer)
+                    .map((traffic) => {
+                        // Parse method from content if it's a JSON-RPC request
+                        let method: string | undefined;
+                        try {
+                            const parsed = JSON.parse(traffic.content);
+                            method = parsed.method;
+                        } catch {
+                            // Not JSON, that's fine
+                        }
+
+                        return {
+                            id: traffic.messageId,
+                            timestamp: traffic.timestamp,
+                            direction: traffic.direction,
+                            content: traffic.content,
+                            method,
+                        };
+                    });
+                if (batch.length === 0) return;
+
+                setMessages((prev) => [...prev, ...batch].slice(-500)); // Keep last 500
             });
         }
 
@@ -3,8 +3,10 @@ import { Download, ExternalLink, Check } from "lucide-react";
 import { Card, CardContent, CardHeader, CardTitle, CardDescription } from "@/components/ui/card";
 import { Button } from "@/components/ui/button";
 import { Badge } from "@/components/ui/badge";
+import { Input } from "@/components/ui/input";
 import { getRegistryServers, installRegistryServer } from "@/lib/tauri";
-import type { RegistryServer } from "@/types";
+import { cn } from "@/lib/utils";
+import type { MissingEnv, RegistryServer } from "@/types";
 import { useEffect } from "react";
 
 interface RegistryBrowserProps {
@@ -16,6 +18,9 @@ export function RegistryBrowser({ onInstall }: RegistryBrowserProps) {
     const [loading, setLoading] = useState(true);
     const [installing, setInstalling] = useState<string | null>(null);
     const [installed, setInstalled] = useState<Set<string>>(new Set());
+    // Env values an install asked for, and what has been entered so far, by server ID
+    const [missing, setMissing] = useState<Record<string, MissingEnv[]>>({});
+    const [envValues, setEnvValues] = useState<Record<string, Record<string, string>>>({});
 
     useEffect(() => {
         loadRegistry();
@@ -36,7 +41,14 @@ export function RegistryBrowser({ onInstall }: RegistryBrowserProps) {
     async function handleInstall(server: RegistryServer) {
         try {
             setInstalling(server.id);
-            await installRegistryServer(server.id);
+            const env = envValues[server.id];
+            const outcome = await installRegistryServer(server.id, undefined, undefined, env);
+            if (outcome.status === "needs_configuration") {
+                setMissing((prev) => ({ ...prev, [server.id]: outcome.missing }));
+                return;
+            }
+            setMissing(({ [server.id]: _, ...rest }) => rest);
+            setEnvValues(({ [server.id]: _, ...rest }) => rest);
             setInstalled((prev) => new Set([...prev, server.id]));
             onInstall();
         } catch (err) {
@@ -46,6 +58,13 @@ export function RegistryBrowser({ onInstall }: RegistryBrowserProps) {
         }
     }
 
+    function setEnvValue(serverId: string, name: string, value: string) {
+        setEnvValues((prev) => ({
+            ...prev,
+            [serverId]: { ...prev[serverId], [name]: value },
+        }));
+    }
+
     if (loading) {
         return (
             <div className="flex items-center justify-center py-16">
@@ -99,6 +118,39 @@ export function RegistryBrowser({ onInstall }: RegistryBrowserProps) {
                                 </span>
                             </div>
 
+                            {/* Env values the install needs */}
+                            {missing[server.id] && (
+                                <div className="space-y-3">
+                                    {missing[server.id].map(({ spec, error }) => (
+                                        <div key={spec.name} className="space-y-1">
+                                            <label className="text-xs font-medium font-mono">
+                                                {spec.name}
+                                                {spec.required && (
+                                                    <span className="text-destructive"> *</span>
+                                                )}
+                                            </label>
+                                            <Input
+                                                type={spec.secret ? "password" : "text"}
+                                                autoComplete="off"
+                                                value={envValues[server.id]?.[spec.name] ?? ""}
+                                                onChange={(e) =>
+                                                    setEnvValue(server.id, spec.name, e.target.value)
+                                                }
+                                                className={cn(error && "border-destructive")}
+                                            />
+                                            {spec.description && (
+                                                <p className="text-xs text-muted-foreground">
+                                                    {spec.description}
+                                                </p>
+                                            )}
+                                            {error && (
+                                                <p className="text-xs text-destructive">{error}</p>
+                                            )}
+                                        </div>
+                                    ))}
+                                </div>
+                            )}
+
                             {/* Install Button */}
                             <Button
                                 className="w-full gap-2"
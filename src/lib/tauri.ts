@@ -5,12 +5,80 @@
 import { invoke } from "@tauri-apps/api/core";
 import type {
     McpConfig,
+    HistoryRetention,
     McpServer,
+    AuthStatus,
     BackupInfo,
+    BackupExport,
+    PathValidation,
+    PreflightReport,
+    ServerSnippet,
+    AuditEntry,
+    SecretFinding,
+    SynapticSettings,
     InspectorMessage,
+    Bookmark,
     InspectorSession,
+    Breakpoint,
+    InterceptedMessage,
+    InterceptAction,
+    ToolApproval,
+    OversizedStats,
+    MockRule,
+    ChaosConfig,
+    ProtocolViolation,
+    RequestTemplate,
+    LoadTestResult,
+    PatchOp,
+    ResendResult,
+    MessageDiff,
+    ErrorGroup,
+    ServerCapabilities,
+    ServerCatalog,
+    ComparisonSource,
+    ServerComparison,
+    ConformanceReport,
+    FuzzOptions,
+    FuzzReport,
+    ToolCallResult,
+    PromptResult,
+    ResourceContents,
+    ProgressUpdate,
+    SamplingRequest,
+    SamplingReply,
+    RedactionRule,
+    LiveStats,
+    RequestTimeout,
+    TimeRange,
+    TimelineBucket,
+    ToolStats,
+    LogEntry,
+    LogQuery,
+    MessageFilter,
+    SessionExport,
+    SessionFormat,
+    SessionInfo,
     RegistryServer,
+    RegistrySnapshot,
+    RegistryQuery,
+    RegistrySearchResult,
+    RegistryServerDetails,
+    InstallOutcome,
+    UninstallReport,
+    PackageVersions,
+    ServerUpdate,
     RuntimeStatus,
+    ShutdownStage,
+    ProcessStatus,
+    RestartResult,
+    SpawnProgress,
+    StopProgress,
+    LogFileInfo,
+    CrashReport,
+    OrphanAction,
+    OrphanProcess,
+    ProcessMetrics,
+    HealthStatus,
 } from "../types";
 
 // ============================================
@@ -29,22 +97,69 @@ export async function writeConfig(config: McpConfig): Promise<void> {
     return invoke<void>("write_config", { config });
 }
 
-export async function addServer(name: string, server: McpServer): Promise<void> {
-    return invoke<void>("add_server", { name, server });
+export async function addServer(name: string, server: McpServer): Promise<PathValidation> {
+    return invoke<PathValidation>("add_server", { name, server });
+}
+
+export async function parseServerSnippet(text: string): Promise<ServerSnippet> {
+    return invoke<ServerSnippet>("parse_server_snippet", { text });
 }
 
 export async function removeServer(name: string): Promise<void> {
     return invoke<void>("remove_server", { name });
 }
 
-export async function updateServer(name: string, server: McpServer): Promise<void> {
-    return invoke<void>("update_server", { name, server });
+export async function updateServer(name: string, server: McpServer): Promise<PathValidation> {
+    return invoke<PathValidation>("update_server", { name, server });
 }
 
 export async function toggleServer(name: string, enabled: boolean): Promise<void> {
     return invoke<void>("toggle_server", { name, enabled });
 }
 
+export async function toggleServerForTarget(
+    name: string,
+    target: string,
+    enabled: boolean
+): Promise<void> {
+    return invoke<void>("toggle_server_for_target", { name, target, enabled });
+}
+
+export async function getServerTargets(name: string): Promise<Record<string, boolean>> {
+    return invoke<Record<string, boolean>>("get_server_targets", { name });
+}
+
+/** Route a server through synaptic-shim so traffic from the Claude client itself is captured */
+export async function enableClientCapture(name: string): Promise<McpServer> {
+    return invoke<McpServer>("enable_client_capture", { name });
+}
+
+export async function disableClientCapture(name: string): Promise<McpServer> {
+    return invoke<McpServer>("disable_client_capture", { name });
+}
+
+/** Sign in to a remote server with OAuth; resolves once the browser flow completes */
+export async function authorizeServer(name: string): Promise<AuthStatus> {
+    return invoke<AuthStatus>("authorize_server", { name });
+}
+
+export async function getAuthStatus(name: string): Promise<AuthStatus> {
+    return invoke<AuthStatus>("get_auth_status", { name });
+}
+
+export async function signOutServer(name: string): Promise<AuthStatus> {
+    return invoke<AuthStatus>("sign_out_server", { name });
+}
+
+/** Config entry that connects a client to Synaptic's gateway instead of to each server; its env holds the gateway token */
+export async function getGatewayEntry(): Promise<McpServer> {
+    return invoke<McpServer>("get_gateway_entry");
+}
+
+export async function getMockServerEntry(definitionPath: string): Promise<McpServer> {
+    return invoke<McpServer>("get_mock_server_entry", { definitionPath });
+}
+
 export async function listBackups(): Promise<BackupInfo[]> {
     return invoke<BackupInfo[]>("list_backups");
 }
@@ -53,6 +168,80 @@ export async function restoreBackup(backupId: string): Promise<void> {
     return invoke<void>("restore_backup", { backupId });
 }
 
+export async function exportBackups(destPath: string): Promise<BackupExport> {
+    return invoke<BackupExport>("export_backups", { destPath });
+}
+
+export async function getAuditLog(
+    serverName?: string,
+    limit?: number,
+    offset?: number
+): Promise<AuditEntry[]> {
+    return invoke<AuditEntry[]>("get_audit_log", { serverName, limit, offset });
+}
+
+export async function scanConfigForSecrets(): Promise<SecretFinding[]> {
+    return invoke<SecretFinding[]>("scan_config_for_secrets");
+}
+
+/** Move an env value into the keychain; returns the `keychain:` reference left in the config */
+export async function migrateSecret(server: string, key: string): Promise<string> {
+    return invoke<string>("migrate_secret", { server, key });
+}
+
+// ============================================
+// SETTINGS COMMANDS
+// ============================================
+
+export async function getSettings(): Promise<SynapticSettings> {
+    return invoke<SynapticSettings>("get_settings");
+}
+
+export async function updateSettings(settings: SynapticSettings): Promise<void> {
+    return invoke<void>("update_settings", { settings });
+}
+
+export async function getRedactionPresets(): Promise<RedactionRule[]> {
+    return invoke<RedactionRule[]>("get_redaction_presets");
+}
+
+export async function resolveExecutableApproval(
+    executable: string,
+    approved: boolean
+): Promise<SynapticSettings> {
+    return invoke<SynapticSettings>("resolve_executable_approval", { executable, approved });
+}
+
+export async function addAllowedExecutable(executable: string): Promise<SynapticSettings> {
+    return invoke<SynapticSettings>("add_allowed_executable", { executable });
+}
+
+export async function removeAllowedExecutable(executable: string): Promise<SynapticSettings> {
+    return invoke<SynapticSettings>("remove_allowed_executable", { executable });
+}
+
+export async function getHistoryRetention(serverName: string): Promise<HistoryRetention> {
+    return invoke<HistoryRetention>("get_history_retention", { serverName });
+}
+
+export async function setHistoryRetention(
+    serverName: string,
+    retention: HistoryRetention | null
+): Promise<SynapticSettings> {
+    return invoke<SynapticSettings>("set_history_retention", { serverName, retention });
+}
+
+export async function setTrafficMirror(
+    serverName: string,
+    path: string | null
+): Promise<SynapticSettings> {
+    return invoke<SynapticSettings>("set_traffic_mirror", { serverName, path });
+}
+
+export async function setReadOnly(enabled: boolean): Promise<void> {
+    return invoke<void>("set_read_only", { enabled });
+}
+
 // ============================================
 // INSPECTOR COMMANDS
 // ============================================
@@ -68,19 +257,149 @@ export async function stopInspector(serverName: string): Promise<void> {
 export async function getInspectorMessages(
     serverName: string,
     limit?: number,
-    offset?: number
+    offset?: number,
+    filter?: MessageFilter
 ): Promise<InspectorMessage[]> {
     return invoke<InspectorMessage[]>("get_inspector_messages", {
         serverName,
         limit,
         offset,
+        filter,
     });
 }
 
+export async function getLiveStats(serverName: string): Promise<LiveStats> {
+    return invoke<LiveStats>("get_live_stats", { serverName });
+}
+
 export async function clearInspectorMessages(serverName: string): Promise<void> {
     return invoke<void>("clear_inspector_messages", { serverName });
 }
 
+export async function bookmarkMessage(
+    serverName: string,
+    messageId: string,
+    annotation?: string
+): Promise<Bookmark> {
+    return invoke<Bookmark>("bookmark_message", { serverName, messageId, annotation });
+}
+
+export async function annotateMessage(messageId: string, annotation: string | null): Promise<Bookmark> {
+    return invoke<Bookmark>("annotate_message", { messageId, annotation });
+}
+
+export async function listBookmarks(serverName?: string): Promise<Bookmark[]> {
+    return invoke<Bookmark[]>("list_bookmarks", { serverName });
+}
+
+export async function removeBookmark(messageId: string): Promise<boolean> {
+    return invoke<boolean>("remove_bookmark", { messageId });
+}
+
+export async function addBreakpoint(breakpoint: Omit<Breakpoint, "id">): Promise<Breakpoint> {
+    return invoke<Breakpoint>("add_breakpoint", { breakpoint });
+}
+
+export async function removeBreakpoint(id: string): Promise<boolean> {
+    return invoke<boolean>("remove_breakpoint", { id });
+}
+
+export async function listBreakpoints(): Promise<Breakpoint[]> {
+    return invoke<Breakpoint[]>("list_breakpoints");
+}
+
+export async function listIntercepted(): Promise<InterceptedMessage[]> {
+    return invoke<InterceptedMessage[]>("list_intercepted");
+}
+
+export async function resolveIntercept(
+    id: string,
+    action: InterceptAction,
+    editedPayload?: unknown
+): Promise<void> {
+    return invoke<void>("resolve_intercept", { id, action, editedPayload });
+}
+
+export async function listToolApprovals(): Promise<ToolApproval[]> {
+    return invoke<ToolApproval[]>("list_tool_approvals");
+}
+
+export async function resolveToolApproval(id: string, approved: boolean): Promise<void> {
+    return invoke<void>("resolve_tool_approval", { id, approved });
+}
+
+export async function addMockRule(rule: Omit<MockRule, "id">): Promise<MockRule> {
+    return invoke<MockRule>("add_mock_rule", { rule });
+}
+
+export async function listMockRules(): Promise<MockRule[]> {
+    return invoke<MockRule[]>("list_mock_rules");
+}
+
+export async function removeMockRule(id: string): Promise<boolean> {
+    return invoke<boolean>("remove_mock_rule", { id });
+}
+
+export async function setChaos(serverName: string, config: ChaosConfig | null): Promise<void> {
+    return invoke<void>("set_chaos", { serverName, config });
+}
+
+export async function getChaos(): Promise<Record<string, ChaosConfig>> {
+    return invoke<Record<string, ChaosConfig>>("get_chaos");
+}
+
+export async function getOversizedStats(): Promise<Record<string, OversizedStats>> {
+    return invoke<Record<string, OversizedStats>>("get_oversized_stats");
+}
+
+export async function getProtocolViolations(serverName: string): Promise<ProtocolViolation[]> {
+    return invoke<ProtocolViolation[]>("get_protocol_violations", { serverName });
+}
+
+export async function getServerCapabilities(name: string): Promise<ServerCapabilities | null> {
+    return invoke<ServerCapabilities | null>("get_server_capabilities", { name });
+}
+
+export async function getToolStats(server?: string, timeRange?: TimeRange): Promise<ToolStats[]> {
+    return invoke<ToolStats[]>("get_tool_stats", { server, timeRange });
+}
+
+export async function getTrafficTimeline(
+    server: string | undefined,
+    bucketSeconds: number,
+    range?: TimeRange
+): Promise<TimelineBucket[]> {
+    return invoke<TimelineBucket[]>("get_traffic_timeline", { server, bucketSeconds, range });
+}
+
+export async function getErrorSummary(server?: string, range?: TimeRange): Promise<ErrorGroup[]> {
+    return invoke<ErrorGroup[]>("get_error_summary", { server, range });
+}
+
+export async function queryLogs(query: LogQuery = {}): Promise<LogEntry[]> {
+    return invoke<LogEntry[]>("query_logs", { query });
+}
+
+export async function exportSession(
+    sessionId: string,
+    format: SessionFormat,
+    path: string
+): Promise<SessionExport> {
+    return invoke<SessionExport>("export_session", { sessionId, format, path });
+}
+
+export async function exportSessionReport(sessionId: string): Promise<string> {
+    return invoke<string>("export_session_report", { sessionId });
+}
+
+export async function importSession(path: string): Promise<SessionInfo> {
+    return invoke<SessionInfo>("import_session", { path });
+}
+
+export async function searchMessages(query: string, filters?: LogQuery): Promise<LogEntry[]> {
+    return invoke<LogEntry[]>("search_messages", { query, filters });
+}
+
 // ============================================
 // REGISTRY COMMANDS
 // ============================================
@@ -89,34 +408,334 @@ export async function getRegistryServers(): Promise<RegistryServer[]> {
     return invoke<RegistryServer[]>("get_registry_servers");
 }
 
+export async function refreshRegistry(force = false): Promise<RegistrySnapshot> {
+    return invoke<RegistrySnapshot>("refresh_registry", { force });
+}
+
+export async function searchRegistry(query: RegistryQuery = {}): Promise<RegistrySearchResult> {
+    return invoke<RegistrySearchResult>("search_registry", { query });
+}
+
 export async function installRegistryServer(
     serverId: string,
-    customName?: string
-): Promise<void> {
-    return invoke<void>("install_registry_server", { serverId, customName });
+    customName?: string,
+    version?: string,
+    env?: Record<string, string>,
+    jobId?: string
+): Promise<InstallOutcome> {
+    return invoke<InstallOutcome>("install_registry_server", {
+        serverId,
+        customName,
+        version,
+        env,
+        jobId,
+    });
+}
+
+export async function cancelInstall(jobId: string): Promise<boolean> {
+    return invoke<boolean>("cancel_install", { jobId });
+}
+
+export async function uninstallServer(name: string, purge = false): Promise<UninstallReport> {
+    return invoke<UninstallReport>("uninstall_server", { name, purge });
+}
+
+export async function getRegistryServerDetails(serverId: string): Promise<RegistryServerDetails> {
+    return invoke<RegistryServerDetails>("get_registry_server_details", { serverId });
+}
+
+export async function getPackageVersions(serverId: string): Promise<PackageVersions> {
+    return invoke<PackageVersions>("get_package_versions", { serverId });
+}
+
+export async function checkServerUpdates(): Promise<ServerUpdate[]> {
+    return invoke<ServerUpdate[]>("check_server_updates");
+}
+
+export async function upgradeServer(
+    name: string,
+    version?: string,
+    restart = false
+): Promise<ServerUpdate> {
+    return invoke<ServerUpdate>("upgrade_server", { name, version, restart });
 }
 
 export async function checkRuntime(runtime: string): Promise<RuntimeStatus> {
     return invoke<RuntimeStatus>("check_runtime", { runtime });
 }
 
+export async function installRuntime(runtime: string, jobId?: string): Promise<RuntimeStatus> {
+    return invoke<RuntimeStatus>("install_runtime", { runtime, jobId });
+}
+
 // ============================================
 // PROCESS MANAGER COMMANDS
 // ============================================
 
+export async function preflightServer(name: string): Promise<PreflightReport> {
+    return invoke<PreflightReport>("preflight_server", { name });
+}
+
 export async function spawnServer(name: string): Promise<number> {
     return invoke<number>("spawn_server", { name });
 }
 
-export async function killServer(name: string): Promise<void> {
-    return invoke<void>("kill_server", { name });
+export async function probeServer(name: string): Promise<ServerCatalog> {
+    return invoke<ServerCatalog>("probe_server", { name });
+}
+
+/** Compare two servers or sessions; without requests, the read-only ones the sessions logged are used */
+export async function compareServers(
+    a: ComparisonSource,
+    b: ComparisonSource,
+    requests?: Record<string, unknown>[]
+): Promise<ServerComparison> {
+    return invoke<ServerComparison>("compare_servers", { a, b, requests });
+}
+
+export async function runConformanceTests(name: string): Promise<ConformanceReport> {
+    return invoke<ConformanceReport>("run_conformance_tests", { name });
+}
+
+export async function runFuzz(name: string, options?: FuzzOptions): Promise<FuzzReport> {
+    return invoke<FuzzReport>("run_fuzz", { name, options });
+}
+
+export async function listFuzzReports(serverName?: string): Promise<FuzzReport[]> {
+    return invoke<FuzzReport[]>("list_fuzz_reports", { serverName });
+}
+
+export async function deleteFuzzReport(id: string): Promise<boolean> {
+    return invoke<boolean>("delete_fuzz_report", { id });
+}
+
+export async function callTool(
+    name: string,
+    toolName: string,
+    args?: Record<string, unknown>,
+    timeoutMs?: number
+): Promise<ToolCallResult> {
+    return invoke<ToolCallResult>("call_tool", { name, toolName, arguments: args, timeoutMs });
+}
+
+export async function getPrompt(
+    name: string,
+    promptName: string,
+    args?: Record<string, string>,
+    timeoutMs?: number
+): Promise<PromptResult> {
+    return invoke<PromptResult>("get_prompt", { name, promptName, arguments: args, timeoutMs });
+}
+
+export async function readResource(name: string, uri: string, timeoutMs?: number): Promise<ResourceContents> {
+    return invoke<ResourceContents>("read_resource", { name, uri, timeoutMs });
+}
+
+/** Updates arrive as `resource-updated` events carrying a ResourceUpdate */
+export async function subscribeResource(name: string, uri: string, timeoutMs?: number): Promise<void> {
+    return invoke<void>("subscribe_resource", { name, uri, timeoutMs });
+}
+
+export async function unsubscribeResource(name: string, uri: string, timeoutMs?: number): Promise<void> {
+    return invoke<void>("unsubscribe_resource", { name, uri, timeoutMs });
+}
+
+export async function listResourceSubscriptions(name: string): Promise<string[]> {
+    return invoke<string[]>("list_resource_subscriptions", { name });
+}
+
+/** Live updates also arrive as `request-progress` events */
+export async function getRequestProgress(
+    requestId: string | number,
+    serverName?: string
+): Promise<ProgressUpdate[]> {
+    return invoke<ProgressUpdate[]>("get_request_progress", { requestId, serverName });
+}
+
+/** Emits `request-cancelled`; a waiting sendRequest gets a -32800 error response */
+export async function cancelRequest(name: string, requestId: string | number, reason?: string): Promise<void> {
+    return invoke<void>("cancel_request", { name, requestId, reason });
+}
+
+/** Requests flagged by a `request-timeout` event that are still unanswered */
+export async function listTimedOutRequests(name?: string): Promise<RequestTimeout[]> {
+    return invoke<RequestTimeout[]>("list_timed_out_requests", { name });
+}
+
+export async function listSamplingRequests(name?: string): Promise<SamplingRequest[]> {
+    return invoke<SamplingRequest[]>("list_sampling_requests", { name });
+}
+
+export async function respondToSampling(
+    name: string,
+    requestId: string | number,
+    reply: SamplingReply
+): Promise<void> {
+    return invoke<void>("respond_to_sampling", { name, requestId, reply });
+}
+
+export async function killServer(name: string): Promise<ShutdownStage> {
+    return invoke<ShutdownStage>("kill_server", { name });
+}
+
+export async function spawnAllEnabled(): Promise<SpawnProgress[]> {
+    return invoke<SpawnProgress[]>("spawn_all_enabled");
+}
+
+export async function killAllServers(): Promise<StopProgress[]> {
+    return invoke<StopProgress[]>("kill_all_servers");
+}
+
+export async function restartServer(name: string): Promise<number> {
+    return invoke<number>("restart_server", { name });
+}
+
+export async function restartAllRunning(): Promise<RestartResult[]> {
+    return invoke<RestartResult[]>("restart_all_running");
 }
 
 export async function sendToServer(name: string, payload: string): Promise<void> {
     return invoke<void>("send_to_server", { name, payload });
 }
 
+/** Send a JSON-RPC request (an id is added if missing) and resolve with the response */
+export async function sendRequest(
+    name: string,
+    payload: Record<string, unknown>,
+    timeoutMs?: number
+): Promise<Record<string, unknown>> {
+    return invoke<Record<string, unknown>>("send_request", { name, payload, timeoutMs });
+}
+
+export async function saveRequestTemplate(
+    template: Omit<RequestTemplate, "variables" | "createdAt" | "updatedAt">
+): Promise<RequestTemplate> {
+    return invoke<RequestTemplate>("save_request_template", { template });
+}
+
+export async function listRequestTemplates(): Promise<RequestTemplate[]> {
+    return invoke<RequestTemplate[]>("list_request_templates");
+}
+
+export async function deleteRequestTemplate(name: string): Promise<boolean> {
+    return invoke<boolean>("delete_request_template", { name });
+}
+
+export async function sendRequestTemplate(
+    name: string,
+    vars: Record<string, unknown> = {},
+    serverName?: string,
+    timeoutMs?: number
+): Promise<Record<string, unknown>> {
+    return invoke<Record<string, unknown>>("send_request_template", {
+        name,
+        vars,
+        serverName,
+        timeoutMs,
+    });
+}
+
+export async function runLoadTest(
+    template: string,
+    concurrency: number,
+    durationMs: number,
+    vars: Record<string, unknown> = {},
+    serverName?: string,
+    timeoutMs?: number
+): Promise<LoadTestResult> {
+    return invoke<LoadTestResult>("run_load_test", {
+        template,
+        vars,
+        serverName,
+        concurrency,
+        durationMs,
+        timeoutMs,
+    });
+}
+
+export async function listLoadTestResults(serverName?: string): Promise<LoadTestResult[]> {
+    return invoke<LoadTestResult[]>("list_load_test_results", { serverName });
+}
+
+export async function deleteLoadTestResult(id: string): Promise<boolean> {
+    return invoke<boolean>("delete_load_test_result", { id });
+}
+
+export async function resendMessage(
+    messageId: number,
+    edits: PatchOp[] = [],
+    timeoutMs?: number
+): Promise<ResendResult> {
+    return invoke<ResendResult>("resend_message", { messageId, edits, timeoutMs });
+}
+
+export async function diffMessages(idA: number, idB: number): Promise<MessageDiff> {
+    return invoke<MessageDiff>("diff_messages", { idA, idB });
+}
+
 export async function getRunningServers(): Promise<string[]> {
     return invoke<string[]>("get_running_servers");
 }
 
+export async function getProcessStatus(name: string): Promise<ProcessStatus | null> {
+    return invoke<ProcessStatus | null>("get_process_status", { name });
+}
+
+export async function getRecentStderr(name: string, lines: number): Promise<string[]> {
+    return invoke<string[]>("get_recent_stderr", { name, lines });
+}
+
+export async function getContainerLogs(name: string, lines: number): Promise<string[]> {
+    return invoke<string[]>("get_container_logs", { name, lines });
+}
+
+export async function getCrashHistory(
+    serverName?: string,
+    limit?: number,
+    offset?: number
+): Promise<CrashReport[]> {
+    return invoke<CrashReport[]>("get_crash_history", { serverName, limit, offset });
+}
+
+export async function listOrphans(): Promise<OrphanProcess[]> {
+    return invoke<OrphanProcess[]>("list_orphans");
+}
+
+export async function adoptOrKillOrphans(
+    action: OrphanAction,
+    serverNames?: string[]
+): Promise<OrphanProcess[]> {
+    return invoke<OrphanProcess[]>("adopt_or_kill_orphans", { action, serverNames });
+}
+
+export async function getProcessMetrics(name: string): Promise<ProcessMetrics | null> {
+    return invoke<ProcessMetrics | null>("get_process_metrics", { name });
+}
+
+export async function getServerHealth(name: string): Promise<HealthStatus | null> {
+    return invoke<HealthStatus | null>("get_server_health", { name });
+}
+
+export async function getAllProcessStatuses(): Promise<ProcessStatus[]> {
+    return invoke<ProcessStatus[]>("get_all_process_statuses");
+}
+
+// ============================================
+// SERVER LOG COMMANDS
+// ============================================
+
+export async function listServerLogs(name?: string): Promise<LogFileInfo[]> {
+    return invoke<LogFileInfo[]>("list_server_logs", { name });
+}
+
+export async function tailServerLog(
+    name: string,
+    lines: number,
+    fileName?: string
+): Promise<string[]> {
+    return invoke<string[]>("tail_server_log", { name, fileName, lines });
+}
+
+export async function openServerLogDir(name: string): Promise<string> {
+    return invoke<string>("open_server_log_dir", { name });
+}
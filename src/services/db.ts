@@ -27,6 +27,8 @@ export interface Session {
     server_name: string | null;
     log_count: number;
     description: string | null;
+    /** Set for read-only sessions imported from an export */
+    imported_from: string | null;
 }
 
 // Singleton database instance
@@ -155,10 +157,12 @@ export async function deleteSession(sessionId: string): Promise<void> {
 }
 
 /**
- * Clear all logs (keep sessions)
+ * Clear all logs (keep sessions); imported sessions are read-only and keep theirs
  */
 export async function clearAllLogs(): Promise<void> {
     const db = await getDb();
-    await db.execute("DELETE FROM system_logs");
-    await db.execute("UPDATE sessions SET log_count = 0");
+    await db.execute(
+        "DELETE FROM system_logs WHERE session_id NOT IN (SELECT id FROM sessions WHERE imported_from IS NOT NULL)"
+    );
+    await db.execute("UPDATE sessions SET log_count = 0 WHERE imported_from IS NULL");
 }
@@ -16,7 +16,259 @@ export interface McpServer {
     args: string[];
     env: Record<string, string>;
     cwd?: string;
+    /** Directories put in front of PATH; relative ones resolve against cwd */
+    pathPrepend?: string[];
     enabled: boolean;
+    restart?: RestartPolicy;
+    /** Stdio message framing; detected from the server's output when omitted */
+    framing?: Framing;
+    /** Run the command on another machine over SSH */
+    ssh?: SshTarget;
+    /** CPU priority of the local process; normal when omitted */
+    priority?: ProcessPriority;
+    /** Memory and CPU caps of the local process tree */
+    limits?: ResourceLimits;
+    /** Command the launch is wrapped in, e.g. an inspector shim */
+    wrapper?: CommandWrapper;
+    /** Endpoint of a server reached over HTTP instead of spawned */
+    url?: string;
+    /** How url is spoken to; stdio when omitted */
+    transport?: Transport;
+    /** Extra HTTP headers sent to url, e.g. an API key */
+    headers?: Record<string, string>;
+}
+
+export type Transport = "stdio" | "http" | "sse";
+
+/** OAuth sign-in state of a remote server; tokens live in the OS keychain */
+export interface AuthStatus {
+    serverName: string;
+    authorized: boolean;
+    expiresAt?: string;
+    scope?: string;
+    /** Whether an expired access token can be renewed without signing in again */
+    refreshable: boolean;
+}
+
+/**
+ * "{command}" and "{args}" in args stand for the configured command and its
+ * arguments; with neither, both are appended. Leaving out "{command}" swaps the binary.
+ */
+export interface CommandWrapper {
+    command: string;
+    args: string[];
+}
+
+export interface ResourceLimits {
+    /** Memory for the whole tree, in MiB */
+    memoryMb?: number;
+    /** CPU time for the whole tree; 100 = one full core */
+    cpuPercent?: number;
+}
+
+/** Payload of the `resource-limit-violated` event */
+export interface LimitViolationEvent {
+    serverName: string;
+    kind: "memory" | "cpu";
+    /** Killed by the limit, not just slowed down */
+    killed: boolean;
+    message: string;
+}
+
+/** Payload of the `resource-limits-unavailable` event (the server runs uncapped) */
+export interface LimitsUnavailableEvent {
+    serverName: string;
+    error: string;
+}
+
+export type ProcessPriority = "low" | "normal" | "high";
+
+/** Remote host for a server; cwd, env and pathPrepend apply on that host */
+export interface SshTarget {
+    /** Host name or an alias from ~/.ssh/config */
+    host: string;
+    user?: string;
+    port?: number;
+    identityFile?: string;
+    /** Extra ssh arguments, e.g. ["-o", "ProxyJump=bastion"] */
+    options?: string[];
+}
+
+export type Framing = "auto" | "newline" | "content-length";
+
+export type StdinOverflow = "block" | "drop-oldest" | "error";
+
+export type RestartMode = "never" | "on-failure" | "always";
+
+export interface RestartPolicy {
+    mode: RestartMode;
+    maxRetries: number;
+    initialBackoffMs: number;
+    maxBackoffMs: number;
+}
+
+/** Stage of the stop sequence that made a server exit */
+export type ShutdownStage = "already_exited" | "stdin_closed" | "terminated" | "killed";
+
+export type ProcessState = "queued" | "starting" | "running" | "stopping" | "crashed";
+
+export interface ProcessStateEvent {
+    serverName: string;
+    state: ProcessState;
+}
+
+/** A running server no longer matches its edited config entry */
+export interface ServerConfigStaleEvent {
+    serverName: string;
+}
+
+/** A line of a docker server's container logs (`container-log` event) */
+export interface ContainerLogLine {
+    serverName: string;
+    containerId: string;
+    line: string;
+}
+
+export interface ProcessStatus {
+    serverName: string;
+    state: ProcessState;
+    pid: number | null;
+    startedAt: string | null;
+    uptimeSecs: number;
+    restartCount: number;
+    lastExitCode: number | null;
+    /** Container of a `docker run` server */
+    containerId: string | null;
+    /** Container state from the docker daemon ("running", "exited", ...) */
+    containerState: string | null;
+    /** Messages waiting to be written to stdin */
+    stdinQueueDepth: number;
+    /** Last JSON-RPC message to or from the server (Synaptic's own pings don't count) */
+    lastTrafficAt: string | null;
+}
+
+/** Payload of the `server-idle-stopped` event */
+export interface IdleStopEvent {
+    serverName: string;
+    idleSecs: number;
+}
+
+/** Per-server progress of `spawn_all_enabled` (`spawn-all-progress` event) */
+export interface SpawnProgress {
+    serverName: string;
+    completed: number;
+    total: number;
+    pid: number | null;
+    error: string | null;
+}
+
+/** Per-server progress of `kill_all_servers` (`kill-all-progress` event) */
+export interface StopProgress {
+    serverName: string;
+    completed: number;
+    total: number;
+    stage: ShutdownStage;
+}
+
+/** Outcome of one server in `restart_all_running` */
+export interface RestartResult {
+    serverName: string;
+    pid: number | null;
+    error: string | null;
+}
+
+/** Resource usage of a server's process tree (`process-metrics` event carries an array) */
+export interface ProcessMetrics {
+    serverName: string;
+    pid: number;
+    /** Summed over the tree; 100 = one full core */
+    cpuPercent: number;
+    memoryBytes: number;
+    threadCount: number | null;
+    processCount: number;
+    sampledAt: string;
+}
+
+/** Ping health of a running server (payload of `server-health-changed`) */
+export interface HealthStatus {
+    serverName: string;
+    healthy: boolean;
+    latencyMs: number | null;
+    consecutiveFailures: number;
+    lastChecked: string;
+    lastError: string | null;
+}
+
+/** Payload of the `process-restarting` event */
+export interface ProcessRestartingEvent {
+    serverName: string;
+    attempt: number;
+    maxRetries: number;
+    delayMs: number;
+    exitCode: number | null;
+}
+
+/** Unexpected server exit (`process-crashed` event, `get_crash_history`) */
+export interface CrashReport {
+    id: number | null;
+    serverName: string;
+    pid: number;
+    exitCode: number | null;
+    /** Terminating signal (Unix only) */
+    signal: number | null;
+    uptimeSecs: number;
+    stderrTail: string[];
+    willRestart: boolean;
+    occurredAt: string;
+}
+
+/** Server process left running by an earlier session that crashed (`orphans-found` event carries a list) */
+export interface OrphanProcess {
+    serverName: string;
+    pid: number;
+    command: string;
+    startedAt: string;
+}
+
+/** Adopt keeps the process running and tracks it again; kill stops it and its children */
+export type OrphanAction = "adopt" | "kill";
+
+export interface PathCheck {
+    location: string;
+    original: string;
+    normalized: string;
+    exists: boolean;
+}
+
+export interface PathValidation {
+    server: McpServer;
+    paths: PathCheck[];
+    warnings: string[];
+}
+
+export type PreflightCheckKind = "runtime" | "env" | "path" | "url";
+
+export type CheckStatus = "ok" | "warning" | "failed";
+
+export interface PreflightCheck {
+    kind: PreflightCheckKind;
+    /** Executable, env var name or path location ("cwd", "args[2]", ...) */
+    subject: string;
+    status: CheckStatus;
+    message: string;
+}
+
+/** `preflight_server` result; also the `details` of a PREFLIGHT_FAILED spawn error */
+export interface PreflightReport {
+    serverName: string;
+    ok: boolean;
+    checks: PreflightCheck[];
+}
+
+/** Server parsed from pasted JSON or a `claude mcp add` line */
+export interface ServerSnippet {
+    name: string | null;
+    server: McpServer;
 }
 
 // Form-specific types
@@ -42,6 +294,186 @@ export interface BackupInfo {
     sizeBytes: number;
 }
 
+export interface BackupExport {
+    path: string;
+    backupCount: number;
+    sizeBytes: number;
+}
+
+// ============================================
+// SETTINGS TYPES
+// ============================================
+
+export interface SynapticSettings {
+    /** Hours between scheduled config snapshots (0 disables) */
+    snapshotIntervalHours: number;
+    /** Reject every config mutation with a READ_ONLY_MODE error */
+    readOnly: boolean;
+    /** Milliseconds each shutdown stage gets before escalating */
+    shutdownGraceMs: number;
+    /** Milliseconds a stop waits for in-flight requests to be answered (0 stops right away) */
+    drainTimeoutMs: number;
+    /** Milliseconds a spawned server has to produce output before STARTUP_TIMEOUT (0 disables) */
+    startupTimeoutMs: number;
+    /** Largest message handled whole (0 = unlimited) */
+    maxMessageBytes: number;
+    /** What larger messages become; the recorded copy ends in "…[truncated N bytes]" either way */
+    oversizedPolicy: OversizedPolicy;
+    /** Servers allowed to start at the same time; the rest queue (0 = unlimited) */
+    maxConcurrentStarts: number;
+    /** Restart a running server when an edit changes how it is launched */
+    restartOnConfigChange: boolean;
+    /** Messages that may wait for a server's stdin */
+    stdinQueueCapacity: number;
+    /** What a send does when the stdin queue is full */
+    stdinOverflow: StdinOverflow;
+    /** Daily windows during which servers are started and outside of which they are stopped */
+    schedules: ServerSchedule[];
+    /** Minutes without JSON-RPC traffic after which a server is stopped (0 disables) */
+    idleTimeoutMins: number;
+    /** Seconds a request may go unanswered before it is flagged (0 disables) */
+    requestTimeoutSecs: number;
+    /** Captured messages kept in memory per server; older ones are read from the database */
+    inspectorHistoryCap: number;
+    /** Per-server overrides of how captured traffic is kept in memory and mirrored to the database */
+    historyRetention: HistoryRetention[];
+    /** NDJSON files servers' traffic is appended to as it happens */
+    trafficMirrors: TrafficMirror[];
+    /** Deny every sampling/createMessage request from a server instead of asking */
+    samplingAutoDeny: boolean;
+    /** Patterns redacted from captured traffic, on top of servers' env values */
+    redactionRules: RedactionRule[];
+    /** "hash" stores a truncated SHA-256 of each redacted value instead of a mask */
+    redactionMode: RedactionMode;
+    /** Allow, deny or ask about tool calls; the first matching policy decides */
+    toolPolicies: ToolPolicy[];
+    /** Caps on tool calls per minute, per server or per tool */
+    rateLimits: RateLimit[];
+    /** Serve every enabled server behind one MCP endpoint (takes effect on restart) */
+    gatewayEnabled: boolean;
+    /** Loopback port of the gateway endpoint */
+    gatewayPort: number;
+    /** Base URL of the MCP registry servers are listed from (empty for the builtin list only) */
+    registryUrl: string;
+    /** Minutes a synced registry listing is used before it is synced again */
+    registryTtlMins: number;
+    /** Never contact the registry; browse the cached listing (or the builtin one) */
+    registryOffline: boolean;
+    /** Executables servers may be spawned with */
+    allowedExecutables: string[];
+    /** Executables the user refused (never prompted again) */
+    deniedExecutables: string[];
+    /** Binaries Synaptic installed, each allowed only for the server it was installed as */
+    managedExecutables: ManagedExecutable[];
+    /** Base64 Ed25519 public keys binary downloads may be signed with, on top of the pinned ones */
+    trustedSigningKeys: string[];
+}
+
+/** A binary installed for a server, allowed for that server alone */
+export interface ManagedExecutable {
+    serverName: string;
+    /** Canonical path of the binary */
+    path: string;
+}
+
+/** A regex redacted from captured traffic */
+export interface RedactionRule {
+    name: string;
+    /** Rust regex syntax */
+    pattern: string;
+    /** May refer to groups as $1 (defaults to "[REDACTED]") */
+    replacement?: string;
+    enabled: boolean;
+}
+
+export type RedactionMode = "mask" | "hash";
+
+export type Weekday = "Mon" | "Tue" | "Wed" | "Thu" | "Fri" | "Sat" | "Sun";
+
+export interface ServerSchedule {
+    serverName: string;
+    /** Days the window opens on; empty means every day */
+    days: Weekday[];
+    /** Local start time ("09:00") */
+    start: string;
+    /** Local stop time; at or before start the window runs past midnight */
+    stop: string;
+    enabled: boolean;
+}
+
+/** Payload of the `schedule-applied` event */
+export interface ScheduleEvent {
+    serverName: string;
+    action: "scheduled_start" | "scheduled_stop";
+    error: string | null;
+}
+
+/** Payload of the `executable-approval-requested` event */
+export interface ExecutableApprovalRequest {
+    serverName: string;
+    command: string;
+    executable: string;
+}
+
+// ============================================
+// AUDIT LOG TYPES
+// ============================================
+
+export type AuditAction =
+    | "add"
+    | "remove"
+    | "update"
+    | "toggle"
+    | "restore"
+    | "write"
+    | "scheduled_start"
+    | "scheduled_stop"
+    | "tool_allowed"
+    | "tool_denied"
+    | "uninstall";
+
+export interface FieldChange {
+    path: string;
+    before?: unknown;
+    after?: unknown;
+}
+
+export interface AuditEntry {
+    id: number;
+    timestamp: string;
+    actor: string;
+    action: AuditAction;
+    serverName?: string;
+    before?: unknown;
+    after?: unknown;
+    diff: FieldChange[];
+}
+
+// ============================================
+// SECRET SCANNER TYPES
+// ============================================
+
+export type SecretKind =
+    | "github_token"
+    | "aws_access_key"
+    | "slack_token"
+    | "anthropic_key"
+    | "open_ai_key"
+    | "named_secret"
+    | "high_entropy";
+
+export type SecretLocation =
+    | { type: "env"; key: string }
+    | { type: "arg"; index: number };
+
+export interface SecretFinding {
+    serverName: string;
+    location: SecretLocation;
+    kind: SecretKind;
+    maskedValue: string;
+    suggestedSecretName: string;
+}
+
 // ============================================
 // INSPECTOR TYPES
 // ============================================
@@ -56,6 +488,59 @@ export interface InspectorMessage {
     payload: unknown;
     method?: string;
     durationMs?: number;
+    /** JSON-RPC / MCP schema problems found in the payload */
+    validationErrors?: string[];
+    /** Pretty-printed payload with long strings shortened, for list rows */
+    preview?: PayloadPreview;
+    /** The request this message belongs to was cancelled */
+    cancelled?: boolean;
+}
+
+/** Request statistics of one method over the last minute */
+export interface MethodStats {
+    method: string;
+    requestsPerMinute: number;
+    requestsPerSecond: number;
+    avgLatencyMs?: number;
+    /** Requests still waiting for a response */
+    active: number;
+}
+
+/** Live request statistics of a server, overall and per method */
+export interface LiveStats {
+    serverName: string;
+    requestsPerMinute: number;
+    requestsPerSecond: number;
+    avgLatencyMs?: number;
+    active: number;
+    methods: MethodStats[];
+}
+
+/** Normalized, size-annotated rendering of a payload */
+export interface PayloadPreview {
+    pretty: string;
+    /** Size of the payload as compact JSON */
+    sizeBytes: number;
+    /** JSON pointers of the strings that were shortened */
+    truncated: string[];
+}
+
+/** A pinned copy of a captured message */
+export interface Bookmark {
+    message: InspectorMessage;
+    annotation?: string;
+    createdAt: string;
+    updatedAt: string;
+}
+
+export interface MessageFilter {
+    direction?: MessageDirection;
+    /** Start of the method name, e.g. "tools/" */
+    methodPrefix?: string;
+    from?: string;
+    to?: string;
+    /** Only JSON-RPC error responses */
+    errorsOnly?: boolean;
 }
 
 export interface InspectorSession {
@@ -65,6 +550,597 @@ export interface InspectorSession {
     messageCount: number;
 }
 
+export type TrafficDirection = "OUTGOING" | "INCOMING" | "STDERR";
+
+/** Holds matching traffic until it is resolved */
+export interface Breakpoint {
+    id: string;
+    /** All servers when unset */
+    serverName?: string;
+    /** Both directions when unset */
+    direction?: "OUTGOING" | "INCOMING";
+    /** Method name, or its start when it ends with "*" ("tools/*") */
+    method?: string;
+    enabled: boolean;
+}
+
+/** Payload of the traffic-intercepted event */
+export interface InterceptedMessage {
+    id: string;
+    breakpointId: string;
+    serverName: string;
+    direction: "OUTGOING" | "INCOMING";
+    method?: string;
+    payload: unknown;
+    heldAt: string;
+}
+
+export type InterceptAction = "forward" | "modify" | "drop";
+
+export type PolicyAction = "allow" | "deny" | "require_approval";
+
+/** Decides what happens to `tools/call` requests for matching tools */
+export interface ToolPolicy {
+    /** Only this server (all servers when unset) */
+    serverName?: string;
+    /** Tool name; `*` matches any run of characters */
+    tool: string;
+    action: PolicyAction;
+    enabled: boolean;
+}
+
+/** Payload of the `tool-approval-requested` event */
+export interface ToolApproval {
+    id: string;
+    serverName: string;
+    tool: string;
+    /** Call arguments, with secrets redacted */
+    arguments: unknown;
+    requestedAt: string;
+}
+
+export type OversizedPolicy = "truncate" | "reject" | "pass-through";
+
+/** Payload of the `message-oversized` event */
+export interface OversizedMessage {
+    serverName: string;
+    direction: "OUTGOING" | "INCOMING";
+    sizeBytes: number;
+    limitBytes: number;
+    policy: OversizedPolicy;
+    method?: string;
+    at: string;
+}
+
+/** Messages over the size limit of one server since Synaptic started */
+export interface OversizedStats {
+    truncated: number;
+    rejected: number;
+    passedThrough: number;
+    largestBytes: number;
+    totalBytes: number;
+    lastAt?: string;
+}
+
+/** Which captured messages of a server are written to the database */
+export type HistoryMirror = "all" | "errors" | "off";
+
+/** How long a server's captured traffic is kept, and where */
+export interface HistoryRetention {
+    serverName: string;
+    /** Clear the in-memory history whenever the server's process exits */
+    clearOnDisconnect: boolean;
+    /** Messages kept in memory, instead of `inspectorHistoryCap` */
+    historyCap?: number;
+    mirror: HistoryMirror;
+}
+
+/** Where a server's traffic is mirrored, one JSON object per line */
+export interface TrafficMirror {
+    serverName: string;
+    /** Absolute path of the NDJSON file */
+    path: string;
+    enabled: boolean;
+}
+
+/** At most `callsPerMinute` tool calls to matching servers and tools */
+export interface RateLimit {
+    /** Only this server (each server counted on its own when unset) */
+    serverName?: string;
+    /** Count each matching tool on its own; unset counts all of a server's calls together */
+    tool?: string;
+    callsPerMinute: number;
+    enabled: boolean;
+}
+
+/** Payload of the `rate-limited` event */
+export interface RateLimited {
+    serverName: string;
+    tool: string;
+    limit: RateLimit;
+    retryAfterMs: number;
+}
+
+/** Payload of the `tool-policy-decision` event */
+export interface ToolDecision {
+    serverName: string;
+    tool: string;
+    allowed: boolean;
+    decidedBy: "policy" | "user" | "timeout";
+    policy: ToolPolicy;
+}
+
+/** Latency and faults injected into a server's traffic */
+export interface ChaosConfig {
+    /** Milliseconds added before each server message is delivered */
+    delayMs: number;
+    /** Up to this many milliseconds more, at random */
+    jitterMs: number;
+    /** Chance (0-1) a server message is lost */
+    dropProbability: number;
+    /** Chance (0-1) a request is answered with an error instead */
+    errorProbability: number;
+    errorCode: number;
+    errorMessage: string;
+}
+
+/** A message that broke JSON-RPC 2.0 or an MCP method schema */
+export interface ProtocolViolation {
+    serverName: string;
+    direction: TrafficDirection;
+    timestamp: string;
+    /** Method of the message, or of the request a response answers */
+    method?: string;
+    errors: string[];
+    /** The offending message (raw text when it isn't JSON) */
+    payload: unknown;
+}
+
+/** Result of a server's initialize handshake */
+export interface ServerCapabilities {
+    serverName: string;
+    /** Version the server agreed to */
+    protocolVersion?: string;
+    /** Version the client asked for */
+    requestedVersion?: string;
+    capabilities: Record<string, unknown> | null;
+    serverInfo: { name?: string; version?: string } | null;
+    clientInfo: { name?: string; version?: string } | null;
+    instructions?: string;
+    negotiatedAt: string;
+}
+
+/** Tools, resources and prompts a server reported to a probe */
+export interface ServerCatalog {
+    serverName: string;
+    /** Whether the server was started for the probe (and stopped after it) */
+    spawned: boolean;
+    protocolVersion?: string;
+    serverInfo: { name?: string; version?: string } | null;
+    capabilities: Record<string, unknown> | null;
+    instructions?: string;
+    tools: Record<string, unknown>[];
+    resources: Record<string, unknown>[];
+    resourceTemplates: Record<string, unknown>[];
+    prompts: Record<string, unknown>[];
+    /** Lists that failed, e.g. "prompts/list: Method not found" */
+    errors: string[];
+}
+
+/** One side of a server comparison: a server probed now, or a logged session */
+export type ComparisonSource =
+    | { kind: "server"; serverName: string }
+    | { kind: "session"; sessionId: string };
+
+export interface ComparedSide {
+    source: ComparisonSource;
+    catalog: ServerCatalog;
+}
+
+/** A catalog item only one side has ("added" when only in b), or that differs */
+export interface CatalogDifference {
+    item: "tool" | "resource" | "resource_template" | "prompt";
+    /** Name, URI or URI template of the item */
+    key: string;
+    kind: "added" | "removed" | "changed";
+    changes: DiffEntry[];
+}
+
+export type BehaviorOutcome = "same" | "different" | "only_a" | "only_b" | "neither";
+
+/** Both sides' responses (without their id) to one request */
+export interface BehaviorComparison {
+    method: string;
+    params?: unknown;
+    responseA?: Record<string, unknown>;
+    responseB?: Record<string, unknown>;
+    outcome: BehaviorOutcome;
+    changes: DiffEntry[];
+}
+
+export interface ServerComparison {
+    a: ComparedSide;
+    b: ComparedSide;
+    serverInfo: DiffEntry[];
+    capabilities: DiffEntry[];
+    catalog: CatalogDifference[];
+    behavior: BehaviorComparison[];
+    /** Nothing but serverInfo differs */
+    identical: boolean;
+}
+
+export type CheckOutcome = "pass" | "warn" | "fail" | "skip";
+
+/** One check of the conformance suite */
+export interface ConformanceCheck {
+    /** initialize, ping, capabilities, tools-schema, unknown-method or cancellation */
+    id: string;
+    name: string;
+    outcome: CheckOutcome;
+    /** What went wrong (or why the check was skipped) */
+    details: string[];
+    durationMs: number;
+}
+
+/** Result of running the conformance suite against a server */
+export interface ConformanceReport {
+    serverName: string;
+    /** Whether the server was started for the run (and stopped after it) */
+    spawned: boolean;
+    protocolVersion?: string;
+    capabilities: Record<string, unknown> | null;
+    /** 0-100: passed checks count fully, warnings half, skipped ones not at all */
+    score: number;
+    checks: ConformanceCheck[];
+    startedAt: string;
+    durationMs: number;
+}
+
+export type FuzzCategory = "malformed-json" | "wrong-type" | "boundary" | "oversized";
+
+export type FindingKind = "crash" | "hang" | "protocol-violation";
+
+/** A fuzz case the server didn't handle well */
+export interface FuzzFinding {
+    kind: FindingKind;
+    category: FuzzCategory;
+    description: string;
+    tool?: string;
+    /** The payload of the case, cut to a kilobyte */
+    payload: string;
+    details: string;
+}
+
+/** What to fuzz */
+export interface FuzzOptions {
+    /** Only tools matching this name, `*` matching any run of characters */
+    tool?: string;
+    maxCases?: number;
+    /** Time a case may take before the server counts as hung */
+    timeoutMs?: number;
+}
+
+/** Result of a fuzz run */
+export interface FuzzReport {
+    id: string;
+    serverName: string;
+    /** Whether the server was started for the run (and stopped after it) */
+    spawned: boolean;
+    startedAt: string;
+    durationMs: number;
+    casesRun: number;
+    casesTotal: number;
+    findings: FuzzFinding[];
+    /** Why the run stopped before its last case */
+    aborted?: string;
+}
+
+/** Outcome of calling a tool from Synaptic */
+export interface ToolCallResult {
+    serverName: string;
+    toolName: string;
+    /** Content blocks (text, image, resource, ...) */
+    content: Record<string, unknown>[];
+    structuredContent?: unknown;
+    /** The tool reported a failure */
+    isError: boolean;
+    /** Whether the server was started for the call (and stopped after it) */
+    spawned: boolean;
+    durationMs: number;
+}
+
+/** A prompt rendered by its server */
+export interface PromptResult {
+    serverName: string;
+    promptName: string;
+    description?: string;
+    /** `{ role, content }` messages */
+    messages: { role: string; content: Record<string, unknown> }[];
+    /** Whether the server was started for the request (and stopped after it) */
+    spawned: boolean;
+}
+
+/** Result of reading a resource */
+export interface ResourceContents {
+    serverName: string;
+    uri: string;
+    /** Items with `uri`, `mimeType` and either `text` or base64 `blob` */
+    contents: Record<string, unknown>[];
+}
+
+/** Payload of the `resource-updated` event */
+export interface ResourceUpdate {
+    serverName: string;
+    uri: string;
+    /** Whether Synaptic subscribed to it (a client may have instead) */
+    subscribed: boolean;
+    timestamp: string;
+}
+
+/** A server's sampling/createMessage request (also the `sampling-request` event payload) */
+export interface SamplingRequest {
+    serverName: string;
+    requestId: string | number;
+    /** messages, modelPreferences, systemPrompt, maxTokens, ... */
+    params: Record<string, unknown>;
+    receivedAt: string;
+    /** Denied right away by the samplingAutoDeny setting */
+    autoDenied: boolean;
+}
+
+/** How to answer a sampling request */
+export type SamplingReply =
+    | { kind: "respond"; result: Record<string, unknown> }
+    | { kind: "deny"; reason?: string };
+
+/** Payload of the `request-cancelled` event */
+export interface RequestCancelled {
+    serverName: string;
+    requestId: string | number;
+    reason?: string;
+    timestamp: string;
+}
+
+/** A progress notification tied to the request it reports on */
+export interface ProgressUpdate {
+    serverName: string;
+    requestId: string | number;
+    method: string;
+    progressToken: string | number;
+    progress: number;
+    total?: number;
+    message?: string;
+    timestamp: string;
+}
+
+/** A request still unanswered after requestTimeoutSecs (`request-timeout` event) */
+export interface RequestTimeout {
+    serverName: string;
+    requestId: string | number;
+    method: string;
+    sentAt: string;
+    waitedMs: number;
+}
+
+/** Optional bounds (ISO timestamps) on when something happened */
+export interface TimeRange {
+    from?: string;
+    to?: string;
+}
+
+/** Aggregated tools/call traffic of one tool */
+export interface ToolStats {
+    serverName: string;
+    toolName: string;
+    calls: number;
+    errors: number;
+    /** errors / calls, 0-1 */
+    errorRate: number;
+    p50Ms: number;
+    p95Ms: number;
+    maxMs: number;
+    lastCalledAt: string;
+}
+
+/** Logged message counts of one time bucket */
+export interface TimelineBucket {
+    start: string;
+    outgoing: number;
+    incoming: number;
+    stderr: number;
+    /** JSON-RPC error responses */
+    errors: number;
+}
+
+/** Logged errors sharing a code/message fingerprint */
+export interface ErrorGroup {
+    fingerprint: string;
+    source: "response" | "stderr";
+    serverName?: string;
+    /** JSON-RPC error code (responses only) */
+    code?: number;
+    /** Message of the most recent occurrence */
+    message: string;
+    count: number;
+    firstSeen: string;
+    lastSeen: string;
+    /** Most recent logged payload */
+    sample?: unknown;
+}
+
+/** A saved request with {{variable}} placeholders */
+export interface RequestTemplate {
+    name: string;
+    /** Server the template is sent to unless another one is given */
+    serverName?: string;
+    description?: string;
+    /** JSON-RPC request; an id is assigned on every send */
+    payload: Record<string, unknown>;
+    /** Placeholders used in the payload */
+    variables: string[];
+    createdAt: string;
+    updatedAt: string;
+}
+
+/** What a load test sends and for how long */
+export interface LoadTestPlan {
+    /** JSON-RPC request; every send gets a fresh id */
+    request: Record<string, unknown>;
+    /** Saved request the request was built from */
+    template?: string;
+    concurrency: number;
+    durationMs: number;
+    timeoutMs: number;
+}
+
+/** Responses that took at most leUs microseconds (any time, when unset) */
+export interface LatencyBucket {
+    leUs?: number;
+    count: number;
+}
+
+/** Latency of the answered requests of a load test, in microseconds */
+export interface LatencySummary {
+    minUs: number;
+    meanUs: number;
+    p50Us: number;
+    p90Us: number;
+    p99Us: number;
+    maxUs: number;
+}
+
+export interface LoadTestResult {
+    id: string;
+    serverName: string;
+    method: string;
+    plan: LoadTestPlan;
+    startedAt: string;
+    elapsedMs: number;
+    requests: number;
+    /** Requests answered with a result */
+    successes: number;
+    /** Requests answered with a JSON-RPC error */
+    errors: number;
+    /** Requests not answered in time */
+    timeouts: number;
+    /** Requests that couldn't be sent, or whose server went away */
+    failures: number;
+    /** The server stopped before the run's duration was up */
+    serverStopped: boolean;
+    /** JSON-RPC errors by code */
+    errorCodes: Record<string, number>;
+    /** Answered requests per second */
+    throughput: number;
+    latency: LatencySummary;
+    histogram: LatencyBucket[];
+}
+
+/** JSON-patch style edit of a resent payload */
+export interface PatchOp {
+    op: "add" | "replace" | "remove";
+    /** JSON pointer, e.g. "/params/arguments/path" */
+    path: string;
+    value?: unknown;
+}
+
+/** A resent request next to the logged one it was copied from */
+export interface ResendResult {
+    /** Row id of the original request in system_logs */
+    originalId: number;
+    serverName: string;
+    originalRequest?: unknown;
+    originalResponse?: unknown;
+    request: Record<string, unknown>;
+    response: Record<string, unknown>;
+}
+
+/** One differing value between two messages */
+export interface DiffEntry {
+    /** JSON pointer, e.g. "/result/tools/2/description" */
+    path: string;
+    kind: "added" | "removed" | "changed";
+    before?: unknown;
+    after?: unknown;
+}
+
+/** Differences between two logged messages (the JSON-RPC id is ignored) */
+export interface MessageDiff {
+    idA: number;
+    idB: number;
+    identical: boolean;
+    changes: DiffEntry[];
+}
+
+/** Answers matching requests instead of the server */
+export interface MockRule {
+    id: string;
+    /** All servers when unset */
+    serverName?: string;
+    /** Method name, or its start when it ends with "*" */
+    method: string;
+    /** Fields the request params must contain, e.g. { name: "fetch_url" } */
+    params?: unknown;
+    result?: unknown;
+    /** Takes precedence over result */
+    error?: unknown;
+    delayMs: number;
+    enabled: boolean;
+}
+
+/** Persisted traffic row from system_logs */
+export interface LogEntry {
+    id: number;
+    sessionId: string;
+    timestamp: string;
+    level: string;
+    category: string;
+    /** JSON-RPC method */
+    message?: string;
+    payload?: unknown;
+    /** JSON-RPC id */
+    traceId?: string;
+    serverName?: string;
+    direction?: TrafficDirection;
+}
+
+/** Row of the sessions table: one run of a server */
+export interface SessionInfo {
+    id: string;
+    startedAt: string;
+    endedAt?: string;
+    serverName?: string;
+    logCount: number;
+    description?: string;
+    /** File the session was imported from; imported sessions are read-only */
+    importedFrom?: string;
+}
+
+export type SessionFormat = "jsonl" | "har";
+
+export interface SessionExport {
+    path: string;
+    format: SessionFormat;
+    messageCount: number;
+    sizeBytes: number;
+}
+
+/** Severity of a logged line, least severe first */
+export type LogLevel = "TRACE" | "DEBUG" | "INFO" | "WARN" | "ERROR" | "FATAL";
+
+export interface LogQuery {
+    server?: string;
+    direction?: TrafficDirection;
+    level?: string;
+    /** At least this severe (rows of unknown levels count as INFO) */
+    minLevel?: LogLevel;
+    method?: string;
+    text?: string;
+    from?: string;
+    to?: string;
+    limit?: number;
+    offset?: number;
+}
+
 // ============================================
 // REGISTRY TYPES
 // ============================================
@@ -78,13 +1154,177 @@ export interface RegistryServer {
     defaultConfig: McpServer;
     repoUrl?: string;
     tags: string[];
+    /** Env vars the server takes, described for guided setup */
+    envSchema?: EnvVarSpec[];
+    /** Latest published version, for entries from a remote registry */
+    version?: string;
+    /** When the entry was last updated in the registry */
+    updatedAt?: string;
+    /** Package downloads over the last week, when known; filled in after a sync, announced by `registry-enriched` */
+    downloads?: number;
+}
+
+/** An env var a registry entry takes */
+export interface EnvVarSpec {
+    name: string;
+    description?: string;
+    required: boolean;
+    /** Whether the value is a credential, to be masked while entered */
+    secret: boolean;
+    /** Regex the whole value has to match */
+    validation?: string;
+}
+
+/** An env value an install can't go ahead without */
+export interface MissingEnv {
+    spec: EnvVarSpec;
+    /** Why the value given was rejected; absent when none was given */
+    error?: string;
 }
 
+/** Result of `install_registry_server` */
+export type InstallOutcome =
+    | { status: "installed"; name: string }
+    | { status: "needs_configuration"; missing: MissingEnv[] };
+
 export type InstallMethod =
     | { type: "npx"; package: string }
     | { type: "uvx"; package: string }
     | { type: "git_clone"; url: string; buildCommand?: string }
-    | { type: "binary"; url: string };
+    | {
+          type: "binary";
+          url: string;
+          /** Hex SHA-256 of the file */
+          sha256: string;
+          /** Base64 Ed25519 signature of the file */
+          signature?: string;
+          /** Base64 Ed25519 public key the signature was made with; it must be a trusted key */
+          publicKey?: string;
+      }
+    | { type: "remote"; url: string };
+
+/** One published version of an npm or PyPI package */
+export interface PackageVersion {
+    version: string;
+    /** Upload time (PyPI only) */
+    publishedAt?: string;
+    /** Deprecated on npm or yanked on PyPI */
+    withdrawn: boolean;
+    prerelease: boolean;
+}
+
+/** Versions of a registry entry's package, newest first */
+export interface PackageVersions {
+    package: string;
+    ecosystem: "npm" | "pypi";
+    /** What an unpinned install runs */
+    latest?: string;
+    versions: PackageVersion[];
+}
+
+export type InstallStage =
+    | "cloning"
+    | "building"
+    | "downloading"
+    | "verifying"
+    | "extracting"
+    | "done"
+    | "failed"
+    | "cancelled";
+
+/** Payload of the `install-progress` event */
+export interface InstallProgress {
+    /** Install job, for `cancel_install` */
+    jobId: string;
+    serverName: string;
+    stage: InstallStage;
+    /** Output line, command being run, or the error of a failed install */
+    message?: string;
+    /** How far the stage is, when that's known */
+    percent?: number;
+    /** Bytes downloaded so far, while downloading */
+    downloaded?: number;
+    /** Size of the download, when the server sent one */
+    total?: number;
+}
+
+/** What `uninstall_server` removed */
+export interface UninstallReport {
+    serverName: string;
+    /** Whether the server was running and got stopped */
+    stopped: boolean;
+    /** Cloned repos and downloaded binaries that were deleted */
+    removedPaths: string[];
+    /** Rows of captured history deleted by a purge */
+    purgedEntries: number;
+}
+
+/** What a package's index says about it */
+export interface PackageDetails {
+    package: string;
+    ecosystem: "npm" | "pypi";
+    latest?: string;
+    /** When the latest version was published */
+    publishedAt?: string;
+    /** Downloads over the last week, when the counts could be fetched */
+    weeklyDownloads?: number;
+    maintainers: string[];
+    license?: string;
+}
+
+/** A registry entry with its package's details */
+export interface RegistryServerDetails {
+    server: RegistryServer;
+    /** Absent for entries that don't run an npm or PyPI package */
+    package?: PackageDetails;
+}
+
+/** A newer release of a pinned server's package */
+export interface ServerUpdate {
+    serverName: string;
+    package: string;
+    ecosystem: "npm" | "pypi";
+    /** Version pinned before; absent when the server ran the latest release */
+    current?: string;
+    latest: string;
+    /** What went wrong applying an upgrade that was saved anyway */
+    warning?: string;
+}
+
+export type RegistrySort = "relevance" | "popularity" | "recency" | "name";
+
+/** Filters and order of a registry search */
+export interface RegistryQuery {
+    /** Words that must all appear in the name, ID, description or tags */
+    query?: string;
+    /** Tags an entry must all have */
+    tags?: string[];
+    /** Install method type, e.g. "npx" or "remote" */
+    installMethod?: InstallMethod["type"];
+    sort?: RegistrySort;
+    offset?: number;
+    limit?: number;
+}
+
+export interface RegistrySearchResult {
+    /** Matching entries before offset and limit are applied */
+    total: number;
+    servers: RegistryServer[];
+    /** Tags of the matching entries, most common first */
+    tags: { tag: string; count: number }[];
+}
+
+/** Servers listed by a registry as of its last sync */
+export interface RegistrySnapshot {
+    source: "remote" | "builtin";
+    /** Base URL of the registry (empty for the builtin list) */
+    registryUrl: string;
+    syncedAt?: string;
+    etag?: string;
+    /** Served from the cache past its TTL (registry unreachable or offline mode) */
+    stale: boolean;
+    servers: RegistryServer[];
+}
 
 export interface RuntimeStatus {
     runtime: string;
@@ -93,6 +1333,19 @@ export interface RuntimeStatus {
     path?: string;
 }
 
+// ============================================
+// SERVER LOG TYPES
+// ============================================
+
+/** A rotating server log file (`logs/<server>/<date>.log`) */
+export interface LogFileInfo {
+    serverName: string;
+    fileName: string;
+    path: string;
+    sizeBytes: number;
+    modified: string;
+}
+
 // ============================================
 // UI STATE TYPES
 // ============================================